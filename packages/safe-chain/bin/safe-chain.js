@@ -23,6 +23,10 @@ import { fileURLToPath } from "url";
 import fs from "fs";
 import { knownAikidoTools, getPackageManagerList } from "../src/shell-integration/helpers.js";
 import { getInstalledSafeChainDir } from "../src/installLocation.js";
+import { importDenylist } from "../src/scanning/localDenylist.js";
+import { replay } from "../src/registryProxy/replay.js";
+import { dryRunDiff } from "../src/registryProxy/dryRunDiff.js";
+import { dumpFeed } from "../src/scanning/dumpFeed.js";
 
 /** @type {string} */
 // This checks the current file's dirname in a way that's compatible with:
@@ -85,6 +89,20 @@ if (tool) {
 
   ui.writeInformation(installDir);
   process.exit(0);
+} else if (command === "import-denylist") {
+  process.exit(importDenylist(process.argv[3]));
+} else if (command === "replay") {
+  (async () => {
+    process.exit(await replay(process.argv.slice(3)));
+  })();
+} else if (command === "dry-run-diff") {
+  (async () => {
+    process.exit(await dryRunDiff(process.argv.slice(3)));
+  })();
+} else if (command === "dump-feed") {
+  (async () => {
+    process.exit(await dumpFeed(process.argv.slice(3)));
+  })();
 } else if (command === "--version" || command === "-v" || command === "-v") {
   (async () => {
     ui.writeInformation(`Current safe-chain version: ${await getVersion()}`);
@@ -106,7 +124,7 @@ function writeHelp() {
   ui.writeInformation(
     `Available commands: ${chalk.cyan("setup")}, ${chalk.cyan(
       "teardown",
-    )}, ${chalk.cyan("setup-ci")}, ${chalk.cyan("get-install-dir")}, ${chalk.cyan("help")}, ${chalk.cyan(
+    )}, ${chalk.cyan("setup-ci")}, ${chalk.cyan("get-install-dir")}, ${chalk.cyan("import-denylist")}, ${chalk.cyan("replay")}, ${chalk.cyan("dry-run-diff")}, ${chalk.cyan("dump-feed")}, ${chalk.cyan("help")}, ${chalk.cyan(
       "--version",
     )}`,
   );
@@ -131,6 +149,25 @@ function writeHelp() {
       "safe-chain get-install-dir",
     )}: Print the install directory for packaged safe-chain binaries.`,
   );
+  ui.writeInformation(
+    `- ${chalk.cyan(
+      "safe-chain import-denylist <FILE>",
+    )}: Add a newline-delimited list of ecosystem:name entries (npm:<name>, pypi:<name>) to the local package denylist.`,
+  );
+  ui.writeInformation(
+    `- ${chalk.cyan(
+      "safe-chain replay <URL>",
+    )} (or ${chalk.cyan(
+      "--audit-line=<LINE>",
+    )}): Evaluate a request against the current malware database and configuration without sending it.`,
+  );
+  ui.writeInformation(
+    `- ${chalk.cyan(
+      "safe-chain dump-feed",
+    )} [${chalk.cyan("--ecosystem=<npm|pypi>")}] [${chalk.cyan(
+      "--grep=<TEXT>",
+    )}]: Print the malware list entries as JSON, with counts per ecosystem.`,
+  );
   ui.writeInformation(
     `- ${chalk.cyan("safe-chain --version")} (or ${chalk.cyan(
       "-v",
@@ -0,0 +1,54 @@
+import { describe, it, before, after } from "node:test";
+import assert from "node:assert";
+import { initializeCliArguments } from "../config/cliArguments.js";
+import { createMockRegistry } from "../tests/support/mockRegistry.js";
+import { fetchDeniedHosts, fetchMalwareDatabase } from "./aikido.js";
+
+describe("aikido API downloads", () => {
+  const malwareData = [
+    { package_name: "malicious-pkg", version: "1.0.0", reason: "MALWARE" },
+  ];
+  let registry;
+
+  before(async () => {
+    registry = await createMockRegistry()
+      .withRedirect("/malware_predictions.json", "/mirror/malware.json")
+      .withResponse("/mirror/malware.json", {
+        headers: { "Content-Type": "application/json", ETag: '"mirror-1"' },
+        body: malwareData,
+      })
+      .withFailures("/mirror/malware.json", 1)
+      .withMalformedBody("/malicious_hosts.json")
+      .start();
+    initializeCliArguments([
+      `--safe-chain-malware-list-base-url=${registry.url}`,
+    ]);
+  });
+
+  after(async () => {
+    initializeCliArguments([]);
+    await registry.stop();
+  });
+
+  it("follows a redirect and retries a failed download of the malware list", async () => {
+    const result = await fetchMalwareDatabase();
+
+    assert.deepStrictEqual(result.malwareDatabase, malwareData);
+    assert.strictEqual(result.version, '"mirror-1"');
+    assert.deepStrictEqual(
+      registry.requests.map((request) => request.url),
+      [
+        "/malware_predictions.json",
+        "/mirror/malware.json",
+        "/malware_predictions.json",
+        "/mirror/malware.json",
+      ]
+    );
+  });
+
+  it("rejects a malformed denied hosts list", async () => {
+    await assert.rejects(() => fetchDeniedHosts(), {
+      message: /^Error parsing denied hosts list: /,
+    });
+  });
+});
@@ -4,8 +4,11 @@ import {
   ECOSYSTEM_JS,
   ECOSYSTEM_PY,
   getMalwareListBaseUrl,
+  getMalwareListToken,
+  getMaxRetryAfterSecs,
 } from "../config/settings.js";
 import { ui } from "../environment/userInteraction.js";
+import { recordServerDate } from "../environment/clockSkew.js";
 
 const malwareDatabasePaths = {
   [ECOSYSTEM_JS]: "malware_predictions.json",
@@ -17,13 +20,27 @@ const newPackagesListPaths = {
   [ECOSYSTEM_PY]: "releases/pypi.json",
 };
 
+const deniedHostsPath = "malicious_hosts.json";
+
+const flaggedMaintainersPaths = {
+  [ECOSYSTEM_JS]: "flagged_maintainers_npm.json",
+};
+
 const DEFAULT_FETCH_RETRY_ATTEMPTS = 4;
+// The lists that come with the malware list are small. The proxy starts
+// without waiting for them, so a slow server must not keep downloading them
+// for long.
+const FEED_LIST_TIMEOUT_MS = 10_000;
 
 /**
  * @typedef {Object} MalwarePackage
  * @property {string} package_name
- * @property {string} version
+ * @property {string} version - An exact version, "*" or a SemVer range
  * @property {string} reason
+ * @property {string[]} [advisories] - OSV/GHSA advisory IDs for the blocked version
+ * @property {string} [source] - The feed the entry came from, set when feeds are merged
+ * @property {number} [added_on] - Unix timestamp (seconds) the entry was added to the feed
+ * @property {number | string} [published_after] - Only versions published after this Unix timestamp (seconds) or date are covered
  */
 
 /**
@@ -35,6 +52,12 @@ const DEFAULT_FETCH_RETRY_ATTEMPTS = 4;
  * @property {number} scraped_on   - Unix timestamp (seconds)
  */
 
+/**
+ * @typedef {Object} FlaggedMaintainerEntry
+ * @property {string} maintainer
+ * @property {number} flagged_on  - Unix timestamp (seconds)
+ */
+
 /**
  * @returns {Promise<{malwareDatabase: MalwarePackage[], version: string | undefined}>}
  */
@@ -46,10 +69,12 @@ export async function fetchMalwareDatabase() {
       /** @type {keyof typeof malwareDatabasePaths} */ (ecosystem)
     ];
     const malwareDatabaseUrl = `${baseUrl}/${path}`;
-    const response = await fetch(malwareDatabaseUrl);
+    const response = await fetch(malwareDatabaseUrl, withMalwareListAuth());
+    recordFeedServerDate(response);
     if (!response.ok) {
-      throw new Error(
-        `Error fetching ${ecosystem} malware database: ${response.statusText}`
+      throw createResponseError(
+        `Error fetching ${ecosystem} malware database: ${response.statusText}`,
+        response
       );
     }
 
@@ -65,6 +90,48 @@ export async function fetchMalwareDatabase() {
   }, DEFAULT_FETCH_RETRY_ATTEMPTS);
 }
 
+/**
+ * Fetches the malware database of the current ecosystem from an additional
+ * feed. Feeds use the layout of the malware list base URL; a feed that does
+ * not cover the ecosystem responds with 404, which yields an empty list.
+ *
+ * @param {string} baseUrl
+ * @returns {Promise<MalwarePackage[]>}
+ */
+export async function fetchMalwareFeed(baseUrl) {
+  return retry(async () => {
+    const ecosystem = getEcoSystem();
+    const path = malwareDatabasePaths[
+      /** @type {keyof typeof malwareDatabasePaths} */ (ecosystem)
+    ];
+    const response = await fetch(`${baseUrl}/${path}`);
+    if (response.status === 404) {
+      return [];
+    }
+    if (!response.ok) {
+      throw createResponseError(
+        `Error fetching ${ecosystem} malware feed ${baseUrl}: ${response.statusText}`,
+        response
+      );
+    }
+
+    try {
+      const entries = await response.json();
+      if (!Array.isArray(entries)) {
+        return [];
+      }
+      return entries.filter(
+        (entry) =>
+          typeof entry?.package_name === "string" &&
+          typeof entry?.version === "string" &&
+          typeof entry?.reason === "string"
+      );
+    } catch (/** @type {any} */ error) {
+      throw new Error(`Error parsing malware feed ${baseUrl}: ${error.message}`);
+    }
+  }, DEFAULT_FETCH_RETRY_ATTEMPTS);
+}
+
 /**
  * @returns {Promise<string | undefined>}
  */
@@ -76,13 +143,16 @@ export async function fetchMalwareDatabaseVersion() {
       /** @type {keyof typeof malwareDatabasePaths} */ (ecosystem)
     ];
     const malwareDatabaseUrl = `${baseUrl}/${path}`;
-    const response = await fetch(malwareDatabaseUrl, {
-      method: "HEAD",
-    });
+    const response = await fetch(
+      malwareDatabaseUrl,
+      withMalwareListAuth({ method: "HEAD" })
+    );
+    recordFeedServerDate(response);
 
     if (!response.ok) {
-      throw new Error(
-        `Error fetching ${ecosystem} malware database version: ${response.statusText}`
+      throw createResponseError(
+        `Error fetching ${ecosystem} malware database version: ${response.statusText}`,
+        response
       );
     }
     return response.headers.get("etag") || undefined;
@@ -104,10 +174,12 @@ export async function fetchNewPackagesList() {
 
     const url = `${baseUrl}/${path}`;
 
-    const response = await fetch(url);
+    const response = await fetch(url, withMalwareListAuth());
+    recordFeedServerDate(response);
     if (!response.ok) {
-      throw new Error(
-        `Error fetching ${ecosystem} new packages list: ${response.statusText}`
+      throw createResponseError(
+        `Error fetching ${ecosystem} new packages list: ${response.statusText}`,
+        response
       );
     }
 
@@ -138,10 +210,12 @@ export async function fetchNewPackagesListVersion() {
 
     const url = `${baseUrl}/${path}`;
 
-    const response = await fetch(url, { method: "HEAD" });
+    const response = await fetch(url, withMalwareListAuth({ method: "HEAD" }));
+    recordFeedServerDate(response);
     if (!response.ok) {
-      throw new Error(
-        `Error fetching ${ecosystem} new packages list version: ${response.statusText}`
+      throw createResponseError(
+        `Error fetching ${ecosystem} new packages list version: ${response.statusText}`,
+        response
       );
     }
 
@@ -149,6 +223,107 @@ export async function fetchNewPackagesListVersion() {
   }, DEFAULT_FETCH_RETRY_ATTEMPTS);
 }
 
+/**
+ * Fetches the list of malicious hosts (exfiltration and C2 destinations) that the
+ * proxy should refuse to connect to. The list is optional: mirrors that do not
+ * provide it respond with 404, which yields an empty list.
+ *
+ * @returns {Promise<string[]>}
+ */
+export async function fetchDeniedHosts() {
+  const baseUrl = getMalwareListBaseUrl();
+  const url = `${baseUrl}/${deniedHostsPath}`;
+
+  const response = await fetch(
+    url,
+    withMalwareListAuth({ signal: AbortSignal.timeout(FEED_LIST_TIMEOUT_MS) })
+  );
+  recordFeedServerDate(response);
+  if (response.status === 404) {
+    return [];
+  }
+  if (!response.ok) {
+    throw new Error(`Error fetching denied hosts list: ${response.statusText}`);
+  }
+
+  try {
+    const deniedHosts = await response.json();
+    if (!Array.isArray(deniedHosts)) {
+      return [];
+    }
+    return deniedHosts.filter((host) => typeof host === "string");
+  } catch (/** @type {any} */ error) {
+    throw new Error(`Error parsing denied hosts list: ${error.message}`);
+  }
+}
+
+/**
+ * Fetches the maintainers flagged as suspicious (for example accounts that were
+ * compromised) for the current ecosystem. The list is optional: ecosystems and
+ * mirrors that do not provide it yield an empty list.
+ *
+ * @returns {Promise<FlaggedMaintainerEntry[]>}
+ */
+export async function fetchFlaggedMaintainers() {
+  const ecosystem = getEcoSystem();
+  const path =
+    flaggedMaintainersPaths[
+      /** @type {keyof typeof flaggedMaintainersPaths} */ (ecosystem)
+    ];
+  if (!path) {
+    return [];
+  }
+
+  const response = await fetch(
+    `${getMalwareListBaseUrl()}/${path}`,
+    withMalwareListAuth({ signal: AbortSignal.timeout(FEED_LIST_TIMEOUT_MS) })
+  );
+  recordFeedServerDate(response);
+  if (response.status === 404) {
+    return [];
+  }
+  if (!response.ok) {
+    throw new Error(
+      `Error fetching flagged maintainers list: ${response.statusText}`
+    );
+  }
+
+  try {
+    const entries = await response.json();
+    if (!Array.isArray(entries)) {
+      return [];
+    }
+    return entries.filter(
+      (entry) =>
+        typeof entry?.maintainer === "string" &&
+        typeof entry?.flagged_on === "number"
+    );
+  } catch (/** @type {any} */ error) {
+    throw new Error(
+      `Error parsing flagged maintainers list: ${error.message}`
+    );
+  }
+}
+
+/**
+ * Adds the configured bearer token to a request for the malware list base URL.
+ * Additional malware feeds are other servers and never receive it.
+ *
+ * @param {Record<string, any>} [options]
+ * @returns {Record<string, any>}
+ */
+function withMalwareListAuth(options = {}) {
+  const token = getMalwareListToken();
+  if (!token) {
+    return options;
+  }
+
+  return {
+    ...options,
+    headers: { ...options.headers, authorization: `Bearer ${token}` },
+  };
+}
+
 /**
  * Retries an asynchronous function multiple times until it succeeds or exhausts all attempts.
  *
@@ -173,15 +348,82 @@ async function retry(func, attempts) {
     }
 
     if (i < attempts - 1) {
-      // When this is not the last try, back-off exponentially:
+      // A rate-limited response says how long to wait
+      const retryAfterMs = /** @type {any} */ (lastError)?.retryAfterMs;
+      // A longer wait would stall the install: the download fails and the
+      // cached copy is used instead
+      if (retryAfterMs > getMaxRetryAfterSecs() * 1000) {
+        ui.writeVerbose(
+          `Safe-chain: download.rate-limited - the server asked to retry after ${Math.ceil(retryAfterMs / 1000)}s, giving up`
+        );
+        break;
+      }
+
+      // Otherwise, when this is not the last try, back-off exponentially:
       //  1st attempt - 500ms delay
       //  2nd attempt - 1000ms delay
       //  3rd attempt - 2000ms delay
       //  4th attempt - 4000ms delay
       //  ...
-      await new Promise((resolve) => setTimeout(resolve, Math.pow(2, i) * 500));
+      const delayMs = retryAfterMs ?? Math.pow(2, i) * 500;
+      await new Promise((resolve) => setTimeout(resolve, delayMs));
     }
   }
 
   throw lastError;
 }
+
+/**
+ * Creates the error for a failed response. When the server is rate limiting
+ * (429) or unavailable (503) and sends Retry-After, the error carries the delay
+ * it asked for, which the retry then waits for instead of backing off.
+ *
+ * @param {string} message
+ * @param {{status: number, headers?: {get: (name: string) => string | null}}} response
+ * @returns {Error & {retryAfterMs?: number}}
+ */
+function createResponseError(message, response) {
+  /** @type {Error & {retryAfterMs?: number}} */
+  const error = new Error(message);
+  if (response.status === 429 || response.status === 503) {
+    error.retryAfterMs = parseRetryAfter(response.headers?.get("retry-after"));
+  }
+  return error;
+}
+
+/**
+ * Parses a Retry-After header, which is either a number of seconds or an
+ * HTTP-date (RFC 9110, section 10.2.3).
+ *
+ * @param {string | null | undefined} value
+ * @param {number} [now] - Milliseconds since the epoch
+ * @returns {number | undefined} - The delay in milliseconds, undefined when the header is missing or invalid
+ */
+export function parseRetryAfter(value, now = Date.now()) {
+  const trimmed = value?.trim();
+  if (!trimmed) {
+    return undefined;
+  }
+
+  if (/^\d+$/.test(trimmed)) {
+    return Number(trimmed) * 1000;
+  }
+
+  // HTTP-dates start with the day name, anything else is invalid
+  const date = /^[a-z]/i.test(trimmed) ? Date.parse(trimmed) : NaN;
+  if (Number.isNaN(date)) {
+    return undefined;
+  }
+  // A date in the past means the server is ready again
+  return Math.max(0, date - now);
+}
+
+/**
+ * Feed responses carry a Date header, used to detect a skewed local clock.
+ *
+ * @param {{headers?: {get: (name: string) => string | null}}} response
+ * @returns {void}
+ */
+function recordFeedServerDate(response) {
+  recordServerDate(response.headers?.get("date"), "the malware database server");
+}
@@ -4,6 +4,8 @@ import assert from "node:assert";
 describe("aikido API", async () => {
   const mockFetch = mock.fn();
   let ecosystem = "js";
+  let malwareListToken = undefined;
+  let maxRetryAfterSecs = 30;
 
   mock.module("make-fetch-happen", {
     defaultExport: mockFetch,
@@ -23,6 +25,8 @@ describe("aikido API", async () => {
       ECOSYSTEM_JS: "js",
       ECOSYSTEM_PY: "py",
       getMalwareListBaseUrl: () => "https://malware-list.aikido.dev",
+      getMalwareListToken: () => malwareListToken,
+      getMaxRetryAfterSecs: () => maxRetryAfterSecs,
     },
   });
 
@@ -31,11 +35,14 @@ describe("aikido API", async () => {
     fetchMalwareDatabaseVersion,
     fetchNewPackagesList,
     fetchNewPackagesListVersion,
+    parseRetryAfter,
   } = await import("./aikido.js");
 
   beforeEach(() => {
     mockFetch.mock.resetCalls();
     ecosystem = "js";
+    malwareListToken = undefined;
+    maxRetryAfterSecs = 30;
   });
 
   describe("fetchMalwareDatabase", () => {
@@ -91,6 +98,100 @@ describe("aikido API", async () => {
       assert.deepStrictEqual(result.malwareDatabase, malwareData);
       assert.strictEqual(result.version, '"etag-456"');
     });
+
+    it("should send the malware list token as a bearer token", async () => {
+      malwareListToken = "secret-token";
+      mockFetch.mock.mockImplementationOnce(() => ({
+        ok: true,
+        json: async () => [],
+        headers: { get: () => '"etag-789"' },
+      }));
+
+      await fetchMalwareDatabase();
+
+      const [, options] = mockFetch.mock.calls[0].arguments;
+      assert.strictEqual(options.headers.authorization, "Bearer secret-token");
+    });
+
+    it("should wait for the Retry-After delay of a rate-limited response", async () => {
+      let callCount = 0;
+      mockFetch.mock.mockImplementation(() => {
+        callCount++;
+        if (callCount === 1) {
+          return {
+            ok: false,
+            status: 429,
+            statusText: "Too Many Requests",
+            headers: { get: (name) => (name === "retry-after" ? "1" : null) },
+          };
+        }
+        return { ok: true, json: async () => [], headers: { get: () => null } };
+      });
+
+      const startedAt = Date.now();
+      await fetchMalwareDatabase();
+
+      assert.strictEqual(mockFetch.mock.calls.length, 2);
+      assert.ok(Date.now() - startedAt >= 950);
+    });
+
+    it("should not retry when Retry-After asks for a longer wait than allowed", async () => {
+      mockFetch.mock.mockImplementation(() => ({
+        ok: false,
+        status: 429,
+        statusText: "Too Many Requests",
+        headers: { get: (name) => (name === "retry-after" ? "3600" : null) },
+      }));
+
+      await assert.rejects(() => fetchMalwareDatabase(), {
+        message: "Error fetching js malware database: Too Many Requests",
+      });
+
+      assert.strictEqual(mockFetch.mock.calls.length, 1);
+    });
+
+    it("should not retry when Retry-After exceeds the configured maximum", async () => {
+      maxRetryAfterSecs = 0.5;
+      mockFetch.mock.mockImplementation(() => ({
+        ok: false,
+        status: 503,
+        statusText: "Service Unavailable",
+        headers: { get: (name) => (name === "retry-after" ? "1" : null) },
+      }));
+
+      await assert.rejects(() => fetchMalwareDatabase(), {
+        message: "Error fetching js malware database: Service Unavailable",
+      });
+
+      assert.strictEqual(mockFetch.mock.calls.length, 1);
+    });
+  });
+
+  describe("parseRetryAfter", () => {
+    const now = Date.parse("Wed, 14 Oct 2026 12:00:00 GMT");
+
+    it("should parse a delay in seconds", () => {
+      assert.strictEqual(parseRetryAfter("120", now), 120_000);
+      assert.strictEqual(parseRetryAfter(" 0 ", now), 0);
+    });
+
+    it("should parse an HTTP-date", () => {
+      assert.strictEqual(
+        parseRetryAfter("Wed, 14 Oct 2026 12:00:30 GMT", now),
+        30_000
+      );
+      assert.strictEqual(
+        parseRetryAfter("Wed, 14 Oct 2026 11:59:00 GMT", now),
+        0
+      );
+    });
+
+    it("should ignore missing and invalid values", () => {
+      assert.strictEqual(parseRetryAfter(null, now), undefined);
+      assert.strictEqual(parseRetryAfter("", now), undefined);
+      assert.strictEqual(parseRetryAfter("soon", now), undefined);
+      assert.strictEqual(parseRetryAfter("-5", now), undefined);
+    });
   });
 
   describe("fetchMalwareDatabaseVersion", () => {
@@ -0,0 +1,31 @@
+import fetch from "make-fetch-happen";
+
+const LATEST_RELEASE_URL =
+  "https://api.github.com/repos/AikidoSec/safe-chain/releases/latest";
+const RELEASE_CHECK_TIMEOUT_MS = 3000;
+
+/**
+ * Fetches the version of the latest published safe-chain release.
+ *
+ * @returns {Promise<string | undefined>}
+ */
+export async function fetchLatestSafeChainVersion() {
+  const response = await fetch(LATEST_RELEASE_URL, {
+    headers: { accept: "application/vnd.github+json" },
+    timeout: RELEASE_CHECK_TIMEOUT_MS,
+    retry: { retries: 0 },
+  });
+
+  if (!response.ok) {
+    throw new Error(
+      `Error fetching the latest safe-chain release: ${response.statusText}`
+    );
+  }
+
+  const release = await response.json();
+  if (release && typeof release.tag_name === "string") {
+    return release.tag_name;
+  }
+
+  return undefined;
+}
@@ -1,7 +1,7 @@
 import { ui } from "../environment/userInteraction.js";
 
 /**
- * @type {{loggingLevel: string | undefined, skipMinimumPackageAge: boolean | undefined, minimumPackageAgeHours: string | undefined, malwareListBaseUrl: string | undefined, logFile: string | undefined, logFileFormat: string | undefined, logFileVerbosity: string | undefined}}
+ * @type {{loggingLevel: string | undefined, skipMinimumPackageAge: boolean | undefined, minimumPackageAgeHours: string | undefined, malwareListBaseUrl: string | undefined, logFile: string | undefined, logFileFormat: string | undefined, logFileVerbosity: string | undefined, dohResolver: string | undefined, denyHosts: string[], cacheDir: string | undefined, cacheMaxSizeGb: string | undefined, checkUpdates: boolean | undefined, blockPrereleases: boolean | undefined, prereleaseAllowlist: string[], proxyPort: string | undefined, portRetry: boolean | undefined, tcpBacklog: string | undefined, tcpNoDelay: string | undefined, strictSni: string | undefined, stripRequestHeaders: string[], stripResponseHeaders: string[], addVia: boolean | undefined, maxDecompressedMb: string | undefined, traceBody: boolean | undefined, failOnClockSkew: boolean | undefined, npmrc: string | undefined, circuitBreakerThreshold: string | undefined, circuitBreakerCooldownSeconds: string | undefined, maxHeaderBytes: string | undefined, noBlock: boolean | undefined, portFile: string | undefined, minTlsVersion: string | undefined, malwareFeedUrls: string[], uaEcosystemHints: boolean | undefined, noInterceptHosts: string[], strictInterception: boolean | undefined, metrics: boolean | undefined, blockStatus: string | undefined, inspectOnly: boolean | undefined, warnInstallScripts: boolean | undefined, blockInstallScripts: boolean | undefined, installScriptAllowlist: string[], readyBanner: boolean | undefined, excludePaths: string[], feedEntryMinAgeMins: string | undefined, flagSizeAnomaly: boolean | undefined, blockSizeAnomaly: boolean | undefined, sizeAnomalyFactor: string | undefined, strictUnknownHosts: boolean | undefined, allowedUnknownHosts: string[], feedMaxStalenessMins: string | undefined, failOnStaleFeed: boolean | undefined, selfTest: boolean | undefined, eventSocket: string | undefined, tunnelConnectTimeoutSecs: string | undefined, maxLifetimeSecs: string | undefined, vcsPolicy: string | undefined, reusePort: boolean | undefined, offline: boolean | undefined, noTelemetry: boolean | undefined, checkDependencyClosure: boolean | undefined, listenOnlyWhenWrapped: boolean | undefined, idleStartupTimeoutSecs: string | undefined, upstreamProxyUser: string | undefined, upstreamProxyPass: string | undefined, useTestFixture: boolean | undefined, redirectMap: string[], malwareCheckEcosystems: string | undefined, minimumPackageAgeEcosystems: string | undefined, maxTrackedPackages: string | undefined, compressResponses: boolean | undefined, allowMethods: string | undefined, denyMethods: string | undefined, policySource: string | undefined, dnsCacheTtlSecs: string | undefined, dnsCacheMinTtlSecs: string | undefined, dnsCacheMaxTtlSecs: string | undefined, dnsNegativeTtlSecs: string | undefined, minPackageFirstPublishAgeHours: string | undefined, upstreamBind: string | undefined, maxRetryAfterSecs: string | undefined}}
  */
 const state = {
   loggingLevel: undefined,
@@ -11,6 +11,80 @@ const state = {
   logFile: undefined,
   logFileFormat: undefined,
   logFileVerbosity: undefined,
+  dohResolver: undefined,
+  denyHosts: [],
+  cacheDir: undefined,
+  cacheMaxSizeGb: undefined,
+  checkUpdates: undefined,
+  blockPrereleases: undefined,
+  prereleaseAllowlist: [],
+  proxyPort: undefined,
+  portRetry: undefined,
+  tcpBacklog: undefined,
+  tcpNoDelay: undefined,
+  strictSni: undefined,
+  stripRequestHeaders: [],
+  stripResponseHeaders: [],
+  addVia: undefined,
+  maxDecompressedMb: undefined,
+  traceBody: undefined,
+  failOnClockSkew: undefined,
+  npmrc: undefined,
+  circuitBreakerThreshold: undefined,
+  circuitBreakerCooldownSeconds: undefined,
+  maxHeaderBytes: undefined,
+  noBlock: undefined,
+  portFile: undefined,
+  minTlsVersion: undefined,
+  malwareFeedUrls: [],
+  uaEcosystemHints: undefined,
+  noInterceptHosts: [],
+  strictInterception: undefined,
+  metrics: undefined,
+  blockStatus: undefined,
+  inspectOnly: undefined,
+  warnInstallScripts: undefined,
+  blockInstallScripts: undefined,
+  installScriptAllowlist: [],
+  readyBanner: undefined,
+  excludePaths: [],
+  feedEntryMinAgeMins: undefined,
+  flagSizeAnomaly: undefined,
+  blockSizeAnomaly: undefined,
+  sizeAnomalyFactor: undefined,
+  strictUnknownHosts: undefined,
+  allowedUnknownHosts: [],
+  feedMaxStalenessMins: undefined,
+  failOnStaleFeed: undefined,
+  selfTest: undefined,
+  eventSocket: undefined,
+  tunnelConnectTimeoutSecs: undefined,
+  maxLifetimeSecs: undefined,
+  vcsPolicy: undefined,
+  reusePort: undefined,
+  offline: undefined,
+  noTelemetry: undefined,
+  checkDependencyClosure: undefined,
+  listenOnlyWhenWrapped: undefined,
+  idleStartupTimeoutSecs: undefined,
+  upstreamProxyUser: undefined,
+  upstreamProxyPass: undefined,
+  useTestFixture: undefined,
+  redirectMap: [],
+  malwareCheckEcosystems: undefined,
+  minimumPackageAgeEcosystems: undefined,
+  maxTrackedPackages: undefined,
+  compressResponses: undefined,
+  allowMethods: undefined,
+  denyMethods: undefined,
+  policySource: undefined,
+  dnsCacheTtlSecs: undefined,
+  dnsCacheMinTtlSecs: undefined,
+  dnsCacheMaxTtlSecs: undefined,
+  dnsNegativeTtlSecs: undefined,
+  minPackageFirstPublishAgeHours: undefined,
+  upstreamBind: undefined,
+  maxRetryAfterSecs: undefined,
 };
 
 const SAFE_CHAIN_ARG_PREFIX = "--safe-chain-";
@@ -28,6 +102,80 @@ export function initializeCliArguments(args) {
   state.logFile = undefined;
   state.logFileFormat = undefined;
   state.logFileVerbosity = undefined;
+  state.dohResolver = undefined;
+  state.denyHosts = [];
+  state.cacheDir = undefined;
+  state.cacheMaxSizeGb = undefined;
+  state.checkUpdates = undefined;
+  state.blockPrereleases = undefined;
+  state.prereleaseAllowlist = [];
+  state.proxyPort = undefined;
+  state.portRetry = undefined;
+  state.tcpBacklog = undefined;
+  state.tcpNoDelay = undefined;
+  state.strictSni = undefined;
+  state.stripRequestHeaders = [];
+  state.stripResponseHeaders = [];
+  state.addVia = undefined;
+  state.maxDecompressedMb = undefined;
+  state.traceBody = undefined;
+  state.failOnClockSkew = undefined;
+  state.npmrc = undefined;
+  state.circuitBreakerThreshold = undefined;
+  state.circuitBreakerCooldownSeconds = undefined;
+  state.maxHeaderBytes = undefined;
+  state.noBlock = undefined;
+  state.portFile = undefined;
+  state.minTlsVersion = undefined;
+  state.malwareFeedUrls = [];
+  state.uaEcosystemHints = undefined;
+  state.noInterceptHosts = [];
+  state.strictInterception = undefined;
+  state.metrics = undefined;
+  state.blockStatus = undefined;
+  state.inspectOnly = undefined;
+  state.warnInstallScripts = undefined;
+  state.blockInstallScripts = undefined;
+  state.installScriptAllowlist = [];
+  state.readyBanner = undefined;
+  state.excludePaths = [];
+  state.feedEntryMinAgeMins = undefined;
+  state.flagSizeAnomaly = undefined;
+  state.blockSizeAnomaly = undefined;
+  state.sizeAnomalyFactor = undefined;
+  state.strictUnknownHosts = undefined;
+  state.allowedUnknownHosts = [];
+  state.feedMaxStalenessMins = undefined;
+  state.failOnStaleFeed = undefined;
+  state.selfTest = undefined;
+  state.eventSocket = undefined;
+  state.tunnelConnectTimeoutSecs = undefined;
+  state.maxLifetimeSecs = undefined;
+  state.vcsPolicy = undefined;
+  state.reusePort = undefined;
+  state.offline = undefined;
+  state.noTelemetry = undefined;
+  state.checkDependencyClosure = undefined;
+  state.listenOnlyWhenWrapped = undefined;
+  state.idleStartupTimeoutSecs = undefined;
+  state.upstreamProxyUser = undefined;
+  state.upstreamProxyPass = undefined;
+  state.useTestFixture = undefined;
+  state.redirectMap = [];
+  state.malwareCheckEcosystems = undefined;
+  state.minimumPackageAgeEcosystems = undefined;
+  state.maxTrackedPackages = undefined;
+  state.compressResponses = undefined;
+  state.allowMethods = undefined;
+  state.denyMethods = undefined;
+  state.policySource = undefined;
+  state.dnsCacheTtlSecs = undefined;
+  state.dnsCacheMinTtlSecs = undefined;
+  state.dnsCacheMaxTtlSecs = undefined;
+  state.dnsNegativeTtlSecs = undefined;
+  state.minPackageFirstPublishAgeHours = undefined;
+  state.upstreamBind = undefined;
+  state.maxRetryAfterSecs = undefined;
 
   const safeChainArgs = [];
   const remainingArgs = [];
@@ -47,6 +195,80 @@ export function initializeCliArguments(args) {
   setLogFile(safeChainArgs);
   setLogFileFormat(safeChainArgs);
   setLogFileVerbosity(safeChainArgs);
+  setDohResolver(safeChainArgs);
+  setDenyHosts(safeChainArgs);
+  setCacheDir(safeChainArgs);
+  setCacheMaxSizeGb(safeChainArgs);
+  setCheckUpdates(safeChainArgs);
+  setBlockPrereleases(safeChainArgs);
+  setPrereleaseAllowlist(safeChainArgs);
+  setProxyPort(safeChainArgs);
+  setPortRetry(safeChainArgs);
+  setTcpBacklog(safeChainArgs);
+  setTcpNoDelay(safeChainArgs);
+  setStrictSni(safeChainArgs);
+  setStripRequestHeaders(safeChainArgs);
+  setStripResponseHeaders(safeChainArgs);
+  setAddVia(safeChainArgs);
+  setMaxDecompressedMb(safeChainArgs);
+  setTraceBody(safeChainArgs);
+  setFailOnClockSkew(safeChainArgs);
+  setNpmrc(safeChainArgs);
+  setCircuitBreakerThreshold(safeChainArgs);
+  setCircuitBreakerCooldownSeconds(safeChainArgs);
+  setMaxHeaderBytes(safeChainArgs);
+  setNoBlock(safeChainArgs);
+  setPortFile(safeChainArgs);
+  setMinTlsVersion(safeChainArgs);
+  setMalwareFeedUrls(safeChainArgs);
+  setUaEcosystemHints(safeChainArgs);
+  setNoInterceptHosts(safeChainArgs);
+  setStrictInterception(safeChainArgs);
+  setMetrics(safeChainArgs);
+  setBlockStatus(safeChainArgs);
+  setInspectOnly(safeChainArgs);
+  setWarnInstallScripts(safeChainArgs);
+  setBlockInstallScripts(safeChainArgs);
+  setInstallScriptAllowlist(safeChainArgs);
+  setReadyBanner(safeChainArgs);
+  setExcludePaths(safeChainArgs);
+  setFeedEntryMinAgeMins(safeChainArgs);
+  setFlagSizeAnomaly(safeChainArgs);
+  setBlockSizeAnomaly(safeChainArgs);
+  setSizeAnomalyFactor(safeChainArgs);
+  setStrictUnknownHosts(safeChainArgs);
+  setAllowedUnknownHosts(safeChainArgs);
+  setFeedMaxStalenessMins(safeChainArgs);
+  setFailOnStaleFeed(safeChainArgs);
+  setSelfTest(safeChainArgs);
+  setEventSocket(safeChainArgs);
+  setTunnelConnectTimeoutSecs(safeChainArgs);
+  setMaxLifetimeSecs(safeChainArgs);
+  setVcsPolicy(safeChainArgs);
+  setReusePort(safeChainArgs);
+  setOffline(safeChainArgs);
+  setNoTelemetry(safeChainArgs);
+  setCheckDependencyClosure(safeChainArgs);
+  setListenOnlyWhenWrapped(safeChainArgs);
+  setIdleStartupTimeoutSecs(safeChainArgs);
+  setUpstreamProxyUser(safeChainArgs);
+  setUpstreamProxyPass(safeChainArgs);
+  setUseTestFixture(safeChainArgs);
+  setRedirectMap(safeChainArgs);
+  setMalwareCheckEcosystems(safeChainArgs);
+  setMinimumPackageAgeEcosystems(safeChainArgs);
+  setMaxTrackedPackages(safeChainArgs);
+  setCompressResponses(safeChainArgs);
+  setAllowMethods(safeChainArgs);
+  setDenyMethods(safeChainArgs);
+  setPolicySource(safeChainArgs);
+  setDnsCacheTtlSecs(safeChainArgs);
+  setDnsCacheMinTtlSecs(safeChainArgs);
+  setDnsCacheMaxTtlSecs(safeChainArgs);
+  setDnsNegativeTtlSecs(safeChainArgs);
+  setMinPackageFirstPublishAgeHours(safeChainArgs);
+  setUpstreamBind(safeChainArgs);
+  setMaxRetryAfterSecs(safeChainArgs);
   checkDeprecatedPythonFlag(args);
   return remainingArgs;
 }
@@ -67,6 +289,22 @@ function getLastArgEqualsValue(args, prefix) {
   return undefined;
 }
 
+/**
+ * @param {string[]} args
+ * @param {string} prefix
+ * @returns {string[]}
+ */
+function getAllArgEqualsValues(args, prefix) {
+  const values = [];
+  for (const arg of args) {
+    if (arg.toLowerCase().startsWith(prefix)) {
+      values.push(arg.substring(prefix.length));
+    }
+  }
+
+  return values;
+}
+
 /**
  * @param {string[]} args
  * @returns {void}
@@ -201,6 +439,1451 @@ export function getLogFileVerbosity() {
   return state.logFileVerbosity;
 }
 
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setDohResolver(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "doh-resolver=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.dohResolver = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getDohResolver() {
+  return state.dohResolver;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setDenyHosts(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "deny-host=";
+
+  state.denyHosts = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getDenyHosts() {
+  return state.denyHosts;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setCacheDir(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "cache-dir=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.cacheDir = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getCacheDir() {
+  return state.cacheDir;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setCacheMaxSizeGb(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "cache-max-size-gb=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.cacheMaxSizeGb = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getCacheMaxSizeGb() {
+  return state.cacheMaxSizeGb;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setCheckUpdates(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "check-updates";
+
+  if (hasFlagArg(args, flagName)) {
+    state.checkUpdates = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getCheckUpdates() {
+  return state.checkUpdates;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setBlockPrereleases(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "block-prereleases";
+
+  if (hasFlagArg(args, flagName)) {
+    state.blockPrereleases = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getBlockPrereleases() {
+  return state.blockPrereleases;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setPrereleaseAllowlist(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "allow-prerelease=";
+
+  state.prereleaseAllowlist = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getPrereleaseAllowlist() {
+  return state.prereleaseAllowlist;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setProxyPort(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "proxy-port=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.proxyPort = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getProxyPort() {
+  return state.proxyPort;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setPortRetry(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "port-retry";
+
+  if (hasFlagArg(args, flagName)) {
+    state.portRetry = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getPortRetry() {
+  return state.portRetry;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setTcpBacklog(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "tcp-backlog=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.tcpBacklog = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getTcpBacklog() {
+  return state.tcpBacklog;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setTcpNoDelay(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "tcp-nodelay=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.tcpNoDelay = value.toLowerCase();
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getTcpNoDelay() {
+  return state.tcpNoDelay;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setStrictSni(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "strict-sni=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.strictSni = value.toLowerCase();
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getStrictSni() {
+  return state.strictSni;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setStripRequestHeaders(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "strip-request-header=";
+
+  state.stripRequestHeaders = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getStripRequestHeaders() {
+  return state.stripRequestHeaders;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setStripResponseHeaders(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "strip-response-header=";
+
+  state.stripResponseHeaders = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getStripResponseHeaders() {
+  return state.stripResponseHeaders;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setAddVia(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "add-via";
+
+  if (hasFlagArg(args, flagName)) {
+    state.addVia = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getAddVia() {
+  return state.addVia;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMaxDecompressedMb(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "max-decompressed-mb=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.maxDecompressedMb = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMaxDecompressedMb() {
+  return state.maxDecompressedMb;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setTraceBody(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "trace-body";
+
+  if (hasFlagArg(args, flagName)) {
+    state.traceBody = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getTraceBody() {
+  return state.traceBody;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setFailOnClockSkew(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "fail-on-clock-skew";
+
+  if (hasFlagArg(args, flagName)) {
+    state.failOnClockSkew = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getFailOnClockSkew() {
+  return state.failOnClockSkew;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setNpmrc(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "npmrc=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.npmrc = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getNpmrc() {
+  return state.npmrc;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setCircuitBreakerThreshold(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "circuit-breaker-threshold=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.circuitBreakerThreshold = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getCircuitBreakerThreshold() {
+  return state.circuitBreakerThreshold;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setCircuitBreakerCooldownSeconds(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "circuit-breaker-cooldown-seconds=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.circuitBreakerCooldownSeconds = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getCircuitBreakerCooldownSeconds() {
+  return state.circuitBreakerCooldownSeconds;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMaxHeaderBytes(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "max-header-bytes=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.maxHeaderBytes = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMaxHeaderBytes() {
+  return state.maxHeaderBytes;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setNoBlock(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "no-block";
+
+  if (hasFlagArg(args, flagName)) {
+    state.noBlock = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getNoBlock() {
+  return state.noBlock;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setPortFile(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "port-file=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.portFile = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getPortFile() {
+  return state.portFile;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMinTlsVersion(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "min-tls-version=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.minTlsVersion = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMinTlsVersion() {
+  return state.minTlsVersion;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMalwareFeedUrls(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "malware-feed-url=";
+
+  state.malwareFeedUrls = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getMalwareFeedUrls() {
+  return state.malwareFeedUrls;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setUaEcosystemHints(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "ua-ecosystem-hints";
+
+  if (hasFlagArg(args, flagName)) {
+    state.uaEcosystemHints = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getUaEcosystemHints() {
+  return state.uaEcosystemHints;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setNoInterceptHosts(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "no-intercept-host=";
+
+  state.noInterceptHosts = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getNoInterceptHosts() {
+  return state.noInterceptHosts;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setStrictInterception(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "strict-interception";
+
+  if (hasFlagArg(args, flagName)) {
+    state.strictInterception = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getStrictInterception() {
+  return state.strictInterception;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMetrics(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "metrics";
+
+  if (hasFlagArg(args, flagName)) {
+    state.metrics = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getMetrics() {
+  return state.metrics;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setBlockStatus(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "block-status=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.blockStatus = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getBlockStatus() {
+  return state.blockStatus;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setInspectOnly(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "inspect-only";
+
+  if (hasFlagArg(args, flagName)) {
+    state.inspectOnly = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getInspectOnly() {
+  return state.inspectOnly;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setWarnInstallScripts(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "warn-install-scripts";
+
+  if (hasFlagArg(args, flagName)) {
+    state.warnInstallScripts = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getWarnInstallScripts() {
+  return state.warnInstallScripts;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setBlockInstallScripts(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "block-install-scripts";
+
+  if (hasFlagArg(args, flagName)) {
+    state.blockInstallScripts = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getBlockInstallScripts() {
+  return state.blockInstallScripts;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setInstallScriptAllowlist(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "allow-install-scripts=";
+
+  state.installScriptAllowlist = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getInstallScriptAllowlist() {
+  return state.installScriptAllowlist;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setReadyBanner(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "ready-banner";
+
+  if (hasFlagArg(args, flagName)) {
+    state.readyBanner = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getReadyBanner() {
+  return state.readyBanner;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setExcludePaths(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "exclude-path=";
+
+  state.excludePaths = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getExcludePaths() {
+  return state.excludePaths;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setFeedEntryMinAgeMins(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "feed-entry-min-age-mins=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.feedEntryMinAgeMins = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getFeedEntryMinAgeMins() {
+  return state.feedEntryMinAgeMins;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setFlagSizeAnomaly(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "flag-size-anomaly";
+
+  if (hasFlagArg(args, flagName)) {
+    state.flagSizeAnomaly = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getFlagSizeAnomaly() {
+  return state.flagSizeAnomaly;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setBlockSizeAnomaly(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "block-size-anomaly";
+
+  if (hasFlagArg(args, flagName)) {
+    state.blockSizeAnomaly = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getBlockSizeAnomaly() {
+  return state.blockSizeAnomaly;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setSizeAnomalyFactor(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "size-anomaly-factor=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.sizeAnomalyFactor = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getSizeAnomalyFactor() {
+  return state.sizeAnomalyFactor;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setStrictUnknownHosts(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "strict-unknown-hosts";
+
+  if (hasFlagArg(args, flagName)) {
+    state.strictUnknownHosts = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getStrictUnknownHosts() {
+  return state.strictUnknownHosts;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setAllowedUnknownHosts(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "allow-unknown-host=";
+
+  state.allowedUnknownHosts = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getAllowedUnknownHosts() {
+  return state.allowedUnknownHosts;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setFeedMaxStalenessMins(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "feed-max-staleness-mins=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.feedMaxStalenessMins = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getFeedMaxStalenessMins() {
+  return state.feedMaxStalenessMins;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setFailOnStaleFeed(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "fail-on-stale-feed";
+
+  if (hasFlagArg(args, flagName)) {
+    state.failOnStaleFeed = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getFailOnStaleFeed() {
+  return state.failOnStaleFeed;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setSelfTest(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "self-test";
+
+  if (hasFlagArg(args, flagName)) {
+    state.selfTest = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getSelfTest() {
+  return state.selfTest;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setEventSocket(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "event-socket=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.eventSocket = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getEventSocket() {
+  return state.eventSocket;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setTunnelConnectTimeoutSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "tunnel-connect-timeout-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.tunnelConnectTimeoutSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getTunnelConnectTimeoutSecs() {
+  return state.tunnelConnectTimeoutSecs;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMaxLifetimeSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "max-lifetime-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.maxLifetimeSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMaxLifetimeSecs() {
+  return state.maxLifetimeSecs;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setVcsPolicy(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "vcs-policy=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.vcsPolicy = value.toLowerCase();
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getVcsPolicy() {
+  return state.vcsPolicy;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setReusePort(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "reuse-port";
+
+  if (hasFlagArg(args, flagName)) {
+    state.reusePort = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getReusePort() {
+  return state.reusePort;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setOffline(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "offline";
+
+  if (hasFlagArg(args, flagName)) {
+    state.offline = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getOffline() {
+  return state.offline;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setNoTelemetry(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "no-telemetry";
+
+  if (hasFlagArg(args, flagName)) {
+    state.noTelemetry = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getNoTelemetry() {
+  return state.noTelemetry;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setCheckDependencyClosure(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "check-dependency-closure";
+
+  if (hasFlagArg(args, flagName)) {
+    state.checkDependencyClosure = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getCheckDependencyClosure() {
+  return state.checkDependencyClosure;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setListenOnlyWhenWrapped(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "listen-only-when-wrapped";
+
+  if (hasFlagArg(args, flagName)) {
+    state.listenOnlyWhenWrapped = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getListenOnlyWhenWrapped() {
+  return state.listenOnlyWhenWrapped;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setIdleStartupTimeoutSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "idle-startup-timeout-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.idleStartupTimeoutSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getIdleStartupTimeoutSecs() {
+  return state.idleStartupTimeoutSecs;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setUpstreamProxyUser(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "upstream-proxy-user=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.upstreamProxyUser = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyUser() {
+  return state.upstreamProxyUser;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setUpstreamProxyPass(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "upstream-proxy-pass=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.upstreamProxyPass = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyPass() {
+  return state.upstreamProxyPass;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setUseTestFixture(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "use-test-fixture";
+
+  if (hasFlagArg(args, flagName)) {
+    state.useTestFixture = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getUseTestFixture() {
+  return state.useTestFixture;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setRedirectMap(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "redirect-map=";
+
+  state.redirectMap = getAllArgEqualsValues(args, argName).filter(
+    (value) => value.length > 0
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+export function getRedirectMap() {
+  return state.redirectMap;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMalwareCheckEcosystems(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "malware-check-ecosystems=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.malwareCheckEcosystems = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMalwareCheckEcosystems() {
+  return state.malwareCheckEcosystems;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMinimumPackageAgeEcosystems(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "minimum-package-age-ecosystems=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.minimumPackageAgeEcosystems = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMinimumPackageAgeEcosystems() {
+  return state.minimumPackageAgeEcosystems;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMaxTrackedPackages(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "max-tracked-packages=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.maxTrackedPackages = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMaxTrackedPackages() {
+  return state.maxTrackedPackages;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setCompressResponses(args) {
+  const flagName = SAFE_CHAIN_ARG_PREFIX + "compress-responses";
+
+  if (hasFlagArg(args, flagName)) {
+    state.compressResponses = true;
+  }
+}
+
+/**
+ * @returns {boolean | undefined}
+ */
+export function getCompressResponses() {
+  return state.compressResponses;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setAllowMethods(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "allow-methods=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.allowMethods = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getAllowMethods() {
+  return state.allowMethods;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setDenyMethods(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "deny-methods=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.denyMethods = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getDenyMethods() {
+  return state.denyMethods;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setPolicySource(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "policy-source=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.policySource = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getPolicySource() {
+  return state.policySource;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setDnsCacheTtlSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "dns-cache-ttl-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.dnsCacheTtlSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getDnsCacheTtlSecs() {
+  return state.dnsCacheTtlSecs;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setDnsCacheMinTtlSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "dns-cache-min-ttl-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.dnsCacheMinTtlSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getDnsCacheMinTtlSecs() {
+  return state.dnsCacheMinTtlSecs;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setDnsCacheMaxTtlSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "dns-cache-max-ttl-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.dnsCacheMaxTtlSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getDnsCacheMaxTtlSecs() {
+  return state.dnsCacheMaxTtlSecs;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setDnsNegativeTtlSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "dns-negative-ttl-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.dnsNegativeTtlSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getDnsNegativeTtlSecs() {
+  return state.dnsNegativeTtlSecs;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMinPackageFirstPublishAgeHours(args) {
+  const argName =
+    SAFE_CHAIN_ARG_PREFIX + "min-package-first-publish-age-hours=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.minPackageFirstPublishAgeHours = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMinPackageFirstPublishAgeHours() {
+  return state.minPackageFirstPublishAgeHours;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setUpstreamBind(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "upstream-bind=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.upstreamBind = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getUpstreamBind() {
+  return state.upstreamBind;
+}
+
+/**
+ * @param {string[]} args
+ * @returns {void}
+ */
+function setMaxRetryAfterSecs(args) {
+  const argName = SAFE_CHAIN_ARG_PREFIX + "max-retry-after-secs=";
+
+  const value = getLastArgEqualsValue(args, argName);
+  if (value) {
+    state.maxRetryAfterSecs = value;
+  }
+}
+
+/**
+ * @returns {string | undefined}
+ */
+export function getMaxRetryAfterSecs() {
+  return state.maxRetryAfterSecs;
+}
+
 /**
  * @param {string[]} args
  * @param {string} flagName
@@ -8,6 +8,7 @@ import {
   getLogFile,
   getLogFileFormat,
   getLogFileVerbosity,
+  getDenyHosts,
 } from "./cliArguments.js";
 import { ui } from "../environment/userInteraction.js";
 
@@ -447,4 +448,21 @@ describe("initializeCliArguments", () => {
 
     assert.strictEqual(getLogFileVerbosity(), "silent");
   });
+
+  it("should collect every deny-host argument", () => {
+    initializeCliArguments([
+      "--safe-chain-deny-host=evil.example",
+      "--safe-chain-deny-host=*.c2.example",
+      "install",
+    ]);
+
+    assert.deepStrictEqual(getDenyHosts(), ["evil.example", "*.c2.example"]);
+  });
+
+  it("should reset denyHosts between calls", () => {
+    initializeCliArguments(["--safe-chain-deny-host=evil.example"]);
+    initializeCliArguments(["install"]);
+
+    assert.deepStrictEqual(getDenyHosts(), []);
+  });
 });
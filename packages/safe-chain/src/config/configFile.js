@@ -4,17 +4,95 @@ import os from "os";
 import { ui } from "../environment/userInteraction.js";
 import { getEcoSystem } from "./settings.js";
 import { getSafeChainBaseDir } from "./safeChainDir.js";
+import {
+  EXIT_CODE_CONFIG_ERROR,
+  SafeChainStartupError,
+} from "../utils/exitCodes.js";
 
 /**
  * @typedef {Object} SafeChainConfig
  *
  * We cannot trust the input and should add the necessary validations
  * @property {unknown | Number} scanTimeout
- * @property {unknown | Number} minimumPackageAgeHours
+ * @property {unknown | Number | string} minimumPackageAgeHours
  * @property {unknown | string} malwareListBaseUrl
  * @property {unknown | string} logFile
  * @property {unknown | string} logFileFormat
  * @property {unknown | string} logFileVerbosity
+ * @property {unknown | string} dohResolver
+ * @property {unknown | string[]} denyHosts
+ * @property {unknown | string} cacheDir
+ * @property {unknown | Number} cacheMaxSizeGb
+ * @property {unknown | boolean} checkUpdates
+ * @property {unknown | boolean} blockPrereleases
+ * @property {unknown | string[]} prereleaseAllowlist
+ * @property {unknown | Number} proxyPort
+ * @property {unknown | boolean} portRetry
+ * @property {unknown | Number} tcpBacklog
+ * @property {unknown | boolean} tcpNoDelay
+ * @property {unknown | boolean} strictSni
+ * @property {unknown | string[]} stripRequestHeaders
+ * @property {unknown | string[]} stripResponseHeaders
+ * @property {unknown | boolean} addVia
+ * @property {unknown | Number} maxDecompressedMb
+ * @property {unknown | boolean} failOnClockSkew
+ * @property {unknown | string} npmrc
+ * @property {unknown | Number} circuitBreakerThreshold
+ * @property {unknown | Number} circuitBreakerCooldownSeconds
+ * @property {unknown | Number} maxHeaderBytes
+ * @property {unknown | string} portFile
+ * @property {unknown | string} minTlsVersion
+ * @property {unknown | string[]} malwareFeedUrls
+ * @property {unknown | boolean} uaEcosystemHints
+ * @property {unknown | string[]} noInterceptHosts
+ * @property {unknown | boolean} strictInterception
+ * @property {unknown | boolean} metrics
+ * @property {unknown | string | number} blockStatus
+ * @property {unknown | boolean} inspectOnly
+ * @property {unknown | boolean} warnInstallScripts
+ * @property {unknown | boolean} blockInstallScripts
+ * @property {unknown | string[]} installScriptAllowlist
+ * @property {unknown | boolean} readyBanner
+ * @property {unknown | string[]} excludePaths
+ * @property {unknown | string} malwareListToken
+ * @property {unknown | Number} feedEntryMinAgeMins
+ * @property {unknown | boolean} flagSizeAnomaly
+ * @property {unknown | boolean} blockSizeAnomaly
+ * @property {unknown | Number} sizeAnomalyFactor
+ * @property {unknown | boolean} strictUnknownHosts
+ * @property {unknown | string[]} allowedUnknownHosts
+ * @property {unknown | Number} feedMaxStalenessMins
+ * @property {unknown | boolean} failOnStaleFeed
+ * @property {unknown | boolean} selfTest
+ * @property {unknown | string} eventSocket
+ * @property {unknown | Number} tunnelConnectTimeoutSecs
+ * @property {unknown | Number} maxLifetimeSecs
+ * @property {unknown | Record<string, unknown>} blockMessages
+ * @property {unknown | string} vcsPolicy
+ * @property {unknown | boolean} reusePort
+ * @property {unknown | boolean} offline
+ * @property {unknown | boolean} noTelemetry
+ * @property {unknown | boolean} checkDependencyClosure
+ * @property {unknown | boolean} listenOnlyWhenWrapped
+ * @property {unknown | Number} idleStartupTimeoutSecs
+ * @property {unknown | string} upstreamProxyUser
+ * @property {unknown | string} upstreamProxyPass
+ * @property {unknown | Record<string, unknown>} redirectMap
+ * @property {unknown | string[]} malwareCheckEcosystems
+ * @property {unknown | string[]} minimumPackageAgeEcosystems
+ * @property {unknown | string} adminToken
+ * @property {unknown | Number} maxTrackedPackages
+ * @property {unknown | boolean} compressResponses
+ * @property {unknown | string[]} allowMethods
+ * @property {unknown | string[]} denyMethods
+ * @property {unknown | string} policySource
+ * @property {unknown | Number} dnsCacheTtlSecs
+ * @property {unknown | Number} dnsCacheMinTtlSecs
+ * @property {unknown | Number} dnsCacheMaxTtlSecs
+ * @property {unknown | Number} dnsNegativeTtlSecs
+ * @property {unknown | Number} minPackageFirstPublishAgeHours
+ * @property {unknown | string} upstreamBind
+ * @property {unknown | Number} maxRetryAfterSecs
  * @property {unknown | SafeChainRegistryConfiguration} npm
  * @property {unknown | SafeChainRegistryConfiguration} pip
  *
@@ -73,70 +151,1109 @@ function validateMinimumPackageAgeHours(value) {
 }
 
 /**
- * Gets the minimum package age in hours from config file only
+ * Gets the minimum package age in hours from config file only.
+ * Per-ecosystem values such as "npm=72,pypi=500" are returned as-is.
+ * @returns {number | string | undefined}
+ */
+export function getMinimumPackageAgeHours() {
+  const config = readConfigFile();
+  if (
+    typeof config.minimumPackageAgeHours === "string" &&
+    config.minimumPackageAgeHours.includes("=")
+  ) {
+    return config.minimumPackageAgeHours;
+  }
+  if (config.minimumPackageAgeHours !== undefined) {
+    const validated = validateMinimumPackageAgeHours(
+      config.minimumPackageAgeHours
+    );
+    if (validated !== undefined) {
+      return validated;
+    }
+  }
+  return undefined;
+}
+
+/**
+ * Gets the malware list base URL from config file only
+ * @returns {string | undefined}
+ */
+export function getMalwareListBaseUrl() {
+  const config = readConfigFile();
+  if (config.malwareListBaseUrl && typeof config.malwareListBaseUrl === "string") {
+    return config.malwareListBaseUrl;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the log file path from the config file
+ * @returns {string | undefined}
+ */
+export function getLogFile() {
+  const config = readConfigFile();
+  if (config.logFile && typeof config.logFile === "string") {
+    return config.logFile;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the log file format from the config file
+ * @returns {string | undefined}
+ */
+export function getLogFileFormat() {
+  const config = readConfigFile();
+  if (config.logFileFormat && typeof config.logFileFormat === "string") {
+    return config.logFileFormat;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the log file verbosity from the config file
+ * @returns {string | undefined}
+ */
+export function getLogFileVerbosity() {
+  const config = readConfigFile();
+  if (config.logFileVerbosity && typeof config.logFileVerbosity === "string") {
+    return config.logFileVerbosity;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the DNS-over-HTTPS resolver URL from the config file
+ * @returns {string | undefined}
+ */
+export function getDohResolver() {
+  const config = readConfigFile();
+  if (config.dohResolver && typeof config.dohResolver === "string") {
+    return config.dohResolver;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the hosts the proxy refuses to connect to from the config file (format parsing only, no validation)
+ * @returns {string[]}
+ */
+export function getDenyHosts() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.denyHosts)) {
+    return [];
+  }
+
+  return config.denyHosts.filter((item) => typeof item === "string");
+}
+
+/**
+ * Gets the response cache directory from the config file
+ * @returns {string | undefined}
+ */
+export function getCacheDir() {
+  const config = readConfigFile();
+  if (config.cacheDir && typeof config.cacheDir === "string") {
+    return config.cacheDir;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the maximum response cache size in gigabytes from the config file
+ * @returns {number | undefined}
+ */
+export function getCacheMaxSizeGb() {
+  const config = readConfigFile();
+  if (config.cacheMaxSizeGb === undefined || config.cacheMaxSizeGb === null) {
+    return undefined;
+  }
+
+  const value = Number(config.cacheMaxSizeGb);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Gets whether to check for newer safe-chain versions from the config file
+ * @returns {boolean | undefined}
+ */
+export function getCheckUpdates() {
+  const config = readConfigFile();
+  if (typeof config.checkUpdates === "boolean") {
+    return config.checkUpdates;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether to block pre-release package versions from the config file
+ * @returns {boolean | undefined}
+ */
+export function getBlockPrereleases() {
+  const config = readConfigFile();
+  if (typeof config.blockPrereleases === "boolean") {
+    return config.blockPrereleases;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the pre-releases that may still be installed from the config file (format parsing only, no validation)
+ * @returns {string[]}
+ */
+export function getPrereleaseAllowlist() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.prereleaseAllowlist)) {
+    return [];
+  }
+
+  return config.prereleaseAllowlist.filter((item) => typeof item === "string");
+}
+
+/**
+ * Gets the port the proxy listens on from the config file
+ * @returns {number | undefined}
+ */
+export function getProxyPort() {
+  const config = readConfigFile();
+  if (config.proxyPort === undefined || config.proxyPort === null) {
+    return undefined;
+  }
+
+  const value = Number(config.proxyPort);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Gets whether to retry or fall back to another port when the proxy port is in use from the config file
+ * @returns {boolean | undefined}
+ */
+export function getPortRetry() {
+  const config = readConfigFile();
+  if (typeof config.portRetry === "boolean") {
+    return config.portRetry;
+  }
+  return undefined;
+}
+
+/**
+ * Maximum length of the queue of pending connections on the proxy listener
+ * @returns {number | undefined}
+ */
+export function getTcpBacklog() {
+  const config = readConfigFile();
+  if (config.tcpBacklog === undefined || config.tcpBacklog === null) {
+    return undefined;
+  }
+
+  const value = Number(config.tcpBacklog);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Whether Nagle's algorithm is disabled on accepted proxy connections
+ * @returns {boolean | undefined}
+ */
+export function getTcpNoDelay() {
+  const config = readConfigFile();
+  if (typeof config.tcpNoDelay === "boolean") {
+    return config.tcpNoDelay;
+  }
+  return undefined;
+}
+
+/**
+ * Whether intercepted TLS connections must send an SNI matching the CONNECT host
+ * @returns {boolean | undefined}
+ */
+export function getStrictSni() {
+  const config = readConfigFile();
+  if (typeof config.strictSni === "boolean") {
+    return config.strictSni;
+  }
+  return undefined;
+}
+
+/**
+ * Request header names removed before forwarding to the registry
+ * @returns {string[]}
+ */
+export function getStripRequestHeaders() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.stripRequestHeaders)) {
+    return [];
+  }
+
+  return config.stripRequestHeaders.filter((item) => typeof item === "string");
+}
+
+/**
+ * Response header names removed before returning to the client
+ * @returns {string[]}
+ */
+export function getStripResponseHeaders() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.stripResponseHeaders)) {
+    return [];
+  }
+
+  return config.stripResponseHeaders.filter((item) => typeof item === "string");
+}
+
+/**
+ * Whether a Via header is added to proxied requests and responses
+ * @returns {boolean | undefined}
+ */
+export function getAddVia() {
+  const config = readConfigFile();
+  if (typeof config.addVia === "boolean") {
+    return config.addVia;
+  }
+  return undefined;
+}
+
+/**
+ * Maximum size in megabytes a compressed registry response may decompress to before safe-chain rejects it
+ * @returns {number | undefined}
+ */
+export function getMaxDecompressedMb() {
+  const config = readConfigFile();
+  if (config.maxDecompressedMb === undefined || config.maxDecompressedMb === null) {
+    return undefined;
+  }
+
+  const value = Number(config.maxDecompressedMb);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Fails when the local clock differs from the server time by more than an hour
+ * @returns {boolean | undefined}
+ */
+export function getFailOnClockSkew() {
+  const config = readConfigFile();
+  if (typeof config.failOnClockSkew === "boolean") {
+    return config.failOnClockSkew;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the path of an .npmrc file with registry and scope mappings from the config file
+ * @returns {string | undefined}
+ */
+export function getNpmrc() {
+  const config = readConfigFile();
+  if (config.npmrc && typeof config.npmrc === "string") {
+    return config.npmrc;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the number of consecutive upstream failures after which a host is short-circuited from the config file
+ * @returns {number | undefined}
+ */
+export function getCircuitBreakerThreshold() {
+  const config = readConfigFile();
+  if (config.circuitBreakerThreshold === undefined || config.circuitBreakerThreshold === null) {
+    return undefined;
+  }
+
+  const value = Number(config.circuitBreakerThreshold);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Gets how long a short-circuited host is skipped, in seconds, from the config file
+ * @returns {number | undefined}
+ */
+export function getCircuitBreakerCooldownSeconds() {
+  const config = readConfigFile();
+  if (config.circuitBreakerCooldownSeconds === undefined || config.circuitBreakerCooldownSeconds === null) {
+    return undefined;
+  }
+
+  const value = Number(config.circuitBreakerCooldownSeconds);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Gets the maximum size of the request line and headers the proxy accepts from the config file
+ * @returns {number | undefined}
+ */
+export function getMaxHeaderBytes() {
+  const config = readConfigFile();
+  if (config.maxHeaderBytes === undefined || config.maxHeaderBytes === null) {
+    return undefined;
+  }
+
+  const value = Number(config.maxHeaderBytes);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Gets the path of the file the bound proxy port is written to from the config file
+ * @returns {string | undefined}
+ */
+export function getPortFile() {
+  const config = readConfigFile();
+  if (config.portFile && typeof config.portFile === "string") {
+    return config.portFile;
+  }
+  return undefined;
+}
+
+/**
+ * Minimum TLS version for connections to upstream registries
+ * @returns {string | undefined}
+ */
+export function getMinTlsVersion() {
+  const config = readConfigFile();
+  if (config.minTlsVersion && typeof config.minTlsVersion === "string") {
+    return config.minTlsVersion;
+  }
+  return undefined;
+}
+
+/**
+ * Base URLs of additional malware feeds
+ * @returns {string[]}
+ */
+export function getMalwareFeedUrls() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.malwareFeedUrls)) {
+    return [];
+  }
+
+  return config.malwareFeedUrls.filter((item) => typeof item === "string");
+}
+
+/**
+ * Gets whether the client's User-Agent is used as an ecosystem hint for unrecognized hosts
+ * @returns {boolean | undefined}
+ */
+export function getUaEcosystemHints() {
+  const config = readConfigFile();
+  if (typeof config.uaEcosystemHints === "boolean") {
+    return config.uaEcosystemHints;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the registry hosts that are tunneled without interception
+ * @returns {string[]}
+ */
+export function getNoInterceptHosts() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.noInterceptHosts)) {
+    return [];
+  }
+
+  return config.noInterceptHosts.filter((item) => typeof item === "string");
+}
+
+/**
+ * Gets whether failed interception handshakes block instead of falling back to tunneling
+ * @returns {boolean | undefined}
+ */
+export function getStrictInterception() {
+  const config = readConfigFile();
+  if (typeof config.strictInterception === "boolean") {
+    return config.strictInterception;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether the proxy serves Prometheus metrics on /metrics
+ * @returns {boolean | undefined}
+ */
+export function getMetrics() {
+  const config = readConfigFile();
+  if (typeof config.metrics === "boolean") {
+    return config.metrics;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the HTTP status code for blocked requests, optionally per ecosystem
+ * such as "403,npm=451" (format parsing only, no validation)
+ * @returns {string | number | undefined}
+ */
+export function getBlockStatus() {
+  const config = readConfigFile();
+  if (
+    (typeof config.blockStatus === "string" && config.blockStatus) ||
+    typeof config.blockStatus === "number"
+  ) {
+    return config.blockStatus;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the block message templates by block reason (format parsing only, the
+ * templates themselves are validated by the caller)
+ * @returns {Record<string, unknown> | undefined}
+ */
+export function getBlockMessages() {
+  const config = readConfigFile();
+  if (
+    config.blockMessages &&
+    typeof config.blockMessages === "object" &&
+    !Array.isArray(config.blockMessages)
+  ) {
+    return /** @type {Record<string, unknown>} */ (config.blockMessages);
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether the proxy records decisions without enforcing them
+ * @returns {boolean | undefined}
+ */
+export function getInspectOnly() {
+  const config = readConfigFile();
+  if (typeof config.inspectOnly === "boolean") {
+    return config.inspectOnly;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether to warn about npm packages with install scripts from the config file
+ * @returns {boolean | undefined}
+ */
+export function getWarnInstallScripts() {
+  const config = readConfigFile();
+  if (typeof config.warnInstallScripts === "boolean") {
+    return config.warnInstallScripts;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether to block npm packages with install scripts from the config file
+ * @returns {boolean | undefined}
+ */
+export function getBlockInstallScripts() {
+  const config = readConfigFile();
+  if (typeof config.blockInstallScripts === "boolean") {
+    return config.blockInstallScripts;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the npm packages whose install scripts are allowed from the config file
+ * @returns {string[]}
+ */
+export function getInstallScriptAllowlist() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.installScriptAllowlist)) {
+    return [];
+  }
+
+  return config.installScriptAllowlist.filter((item) => typeof item === "string");
+}
+
+/**
+ * Gets whether to write a ready line once the proxy accepts connections from the config file
+ * @returns {boolean | undefined}
+ */
+export function getReadyBanner() {
+  const config = readConfigFile();
+  if (typeof config.readyBanner === "boolean") {
+    return config.readyBanner;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the regular expressions for registry paths that are forwarded without inspection from the config file
+ * @returns {string[]}
+ */
+export function getExcludePaths() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.excludePaths)) {
+    return [];
+  }
+
+  return config.excludePaths.filter((item) => typeof item === "string");
+}
+
+/**
+ * Gets the bearer token sent to the malware list base URL from the config file
+ * @returns {string | undefined}
+ */
+export function getMalwareListToken() {
+  const config = readConfigFile();
+  if (config.malwareListToken && typeof config.malwareListToken === "string") {
+    return config.malwareListToken;
+  }
+  return undefined;
+}
+
+/**
+ * Minutes a malware feed entry is only reported before it is enforced
+ * @returns {number | undefined}
+ */
+export function getFeedEntryMinAgeMins() {
+  const config = readConfigFile();
+  if (config.feedEntryMinAgeMins === undefined || config.feedEntryMinAgeMins === null) {
+    return undefined;
+  }
+
+  const value = Number(config.feedEntryMinAgeMins);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Warn about npm package versions much larger than the other versions of the package
+ * @returns {boolean | undefined}
+ */
+export function getFlagSizeAnomaly() {
+  const config = readConfigFile();
+  if (typeof config.flagSizeAnomaly === "boolean") {
+    return config.flagSizeAnomaly;
+  }
+  return undefined;
+}
+
+/**
+ * Block npm package versions much larger than the other versions of the package
+ * @returns {boolean | undefined}
+ */
+export function getBlockSizeAnomaly() {
+  const config = readConfigFile();
+  if (typeof config.blockSizeAnomaly === "boolean") {
+    return config.blockSizeAnomaly;
+  }
+  return undefined;
+}
+
+/**
+ * How many times larger than usual a version must be to count as a size anomaly
+ * @returns {number | undefined}
+ */
+export function getSizeAnomalyFactor() {
+  const config = readConfigFile();
+  if (config.sizeAnomalyFactor === undefined || config.sizeAnomalyFactor === null) {
+    return undefined;
+  }
+
+  const value = Number(config.sizeAnomalyFactor);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Block requests to hosts that are not a known or configured registry
+ * @returns {boolean | undefined}
+ */
+export function getStrictUnknownHosts() {
+  const config = readConfigFile();
+  if (typeof config.strictUnknownHosts === "boolean") {
+    return config.strictUnknownHosts;
+  }
+  return undefined;
+}
+
+/**
+ * Hosts that are not registries but may be reached in strict unknown hosts mode
+ * @returns {string[]}
+ */
+export function getAllowedUnknownHosts() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.allowedUnknownHosts)) {
+    return [];
+  }
+
+  return config.allowedUnknownHosts.filter((item) => typeof item === "string");
+}
+
+/**
+ * Minutes since the last successful malware list refresh after which the list is reported as stale
+ * @returns {number | undefined}
+ */
+export function getFeedMaxStalenessMins() {
+  const config = readConfigFile();
+  if (config.feedMaxStalenessMins === undefined || config.feedMaxStalenessMins === null) {
+    return undefined;
+  }
+
+  const value = Number(config.feedMaxStalenessMins);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Whether safe-chain fails when the malware list is stale
+ * @returns {boolean | undefined}
+ */
+export function getFailOnStaleFeed() {
+  const config = readConfigFile();
+  if (typeof config.failOnStaleFeed === "boolean") {
+    return config.failOnStaleFeed;
+  }
+  return undefined;
+}
+
+/**
+ * Whether the policy is checked against known fixtures when the proxy starts
+ * @returns {boolean | undefined}
+ */
+export function getSelfTest() {
+  const config = readConfigFile();
+  if (typeof config.selfTest === "boolean") {
+    return config.selfTest;
+  }
+  return undefined;
+}
+
+/**
+ * Path of a Unix socket, named pipe or FIFO the proxy streams decision events to
+ * @returns {string | undefined}
+ */
+export function getEventSocket() {
+  const config = readConfigFile();
+  if (config.eventSocket && typeof config.eventSocket === "string") {
+    return config.eventSocket;
+  }
+  return undefined;
+}
+
+/**
+ * Seconds to wait for the connection to the upstream of a CONNECT tunnel
+ * @returns {number | undefined}
+ */
+export function getTunnelConnectTimeoutSecs() {
+  const config = readConfigFile();
+  if (config.tunnelConnectTimeoutSecs === undefined || config.tunnelConnectTimeoutSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.tunnelConnectTimeoutSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Seconds after which the proxy shuts down, unset to disable
+ * @returns {number | undefined}
+ */
+export function getMaxLifetimeSecs() {
+  const config = readConfigFile();
+  if (config.maxLifetimeSecs === undefined || config.maxLifetimeSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.maxLifetimeSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Gets how git dependency fetches from VCS hosts are handled from the config file
+ * @returns {string | undefined}
+ */
+export function getVcsPolicy() {
+  const config = readConfigFile();
+  if (config.vcsPolicy && typeof config.vcsPolicy === "string") {
+    return config.vcsPolicy;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether the proxy binds its port with SO_REUSEPORT from the config file
+ * @returns {boolean | undefined}
+ */
+export function getReusePort() {
+  const config = readConfigFile();
+  if (typeof config.reusePort === "boolean") {
+    return config.reusePort;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether safe-chain runs without downloading the malware list and other feeds from the config file
+ * @returns {boolean | undefined}
+ */
+export function getOffline() {
+  const config = readConfigFile();
+  if (typeof config.offline === "boolean") {
+    return config.offline;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether safe-chain skips requests that are not needed for protection from the config file
+ * @returns {boolean | undefined}
+ */
+export function getNoTelemetry() {
+  const config = readConfigFile();
+  if (typeof config.noTelemetry === "boolean") {
+    return config.noTelemetry;
+  }
+  return undefined;
+}
+
+/**
+ * Enables the check of the declared dependencies of npm packages against the malware list
+ * @returns {boolean | undefined}
+ */
+export function getCheckDependencyClosure() {
+  const config = readConfigFile();
+  if (typeof config.checkDependencyClosure === "boolean") {
+    return config.checkDependencyClosure;
+  }
+  return undefined;
+}
+
+/**
+ * Shuts the proxy down when the parent process exits or no client connects
+ * @returns {boolean | undefined}
+ */
+export function getListenOnlyWhenWrapped() {
+  const config = readConfigFile();
+  if (typeof config.listenOnlyWhenWrapped === "boolean") {
+    return config.listenOnlyWhenWrapped;
+  }
+  return undefined;
+}
+
+/**
+ * Seconds the proxy waits for the first client when it only listens when wrapped
  * @returns {number | undefined}
  */
-export function getMinimumPackageAgeHours() {
+export function getIdleStartupTimeoutSecs() {
   const config = readConfigFile();
-  if (config.minimumPackageAgeHours !== undefined) {
-    const validated = validateMinimumPackageAgeHours(
-      config.minimumPackageAgeHours
-    );
-    if (validated !== undefined) {
-      return validated;
-    }
+  if (config.idleStartupTimeoutSecs === undefined || config.idleStartupTimeoutSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.idleStartupTimeoutSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * User name for the upstream proxy from HTTPS_PROXY
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyUser() {
+  const config = readConfigFile();
+  if (config.upstreamProxyUser && typeof config.upstreamProxyUser === "string") {
+    return config.upstreamProxyUser;
   }
   return undefined;
 }
 
 /**
- * Gets the malware list base URL from config file only
+ * Password for the upstream proxy from HTTPS_PROXY
  * @returns {string | undefined}
  */
-export function getMalwareListBaseUrl() {
+export function getUpstreamProxyPass() {
   const config = readConfigFile();
-  if (config.malwareListBaseUrl && typeof config.malwareListBaseUrl === "string") {
-    return config.malwareListBaseUrl;
+  if (config.upstreamProxyPass && typeof config.upstreamProxyPass === "string") {
+    return config.upstreamProxyPass;
   }
   return undefined;
 }
 
 /**
- * Gets the log file path from the config file
+ * Gets the replacement base URLs for blocked packages, by "type:name" (format
+ * parsing only, the URLs are validated by the caller)
+ * @returns {Record<string, unknown> | undefined}
+ */
+export function getRedirectMap() {
+  const config = readConfigFile();
+  if (
+    config.redirectMap &&
+    typeof config.redirectMap === "object" &&
+    !Array.isArray(config.redirectMap)
+  ) {
+    return /** @type {Record<string, unknown>} */ (config.redirectMap);
+  }
+  return undefined;
+}
+
+/**
+ * Ecosystems the malware check applies to, for example ["npm", "pypi"]
+ * @returns {string[] | undefined}
+ */
+export function getMalwareCheckEcosystems() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.malwareCheckEcosystems)) {
+    return undefined;
+  }
+
+  return config.malwareCheckEcosystems.filter(
+    (item) => typeof item === "string"
+  );
+}
+
+/**
+ * Ecosystems the minimum package age check applies to, for example ["pypi"]
+ * @returns {string[] | undefined}
+ */
+export function getMinimumPackageAgeEcosystems() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.minimumPackageAgeEcosystems)) {
+    return undefined;
+  }
+
+  return config.minimumPackageAgeEcosystems.filter(
+    (item) => typeof item === "string"
+  );
+}
+
+/**
+ * Bearer token for the admin endpoints of the proxy
  * @returns {string | undefined}
  */
-export function getLogFile() {
+export function getAdminToken() {
   const config = readConfigFile();
-  if (config.logFile && typeof config.logFile === "string") {
-    return config.logFile;
+  if (config.adminToken && typeof config.adminToken === "string") {
+    return config.adminToken;
   }
   return undefined;
 }
 
 /**
- * Gets the log file format from the config file
+ * Number of packages whose metadata details are kept in memory
+ * @returns {number | undefined}
+ */
+export function getMaxTrackedPackages() {
+  const config = readConfigFile();
+  if (config.maxTrackedPackages === undefined || config.maxTrackedPackages === null) {
+    return undefined;
+  }
+
+  const value = Number(config.maxTrackedPackages);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Whether the proxy compresses the responses it rewrites or serves from its cache, for clients that accept it
+ * @returns {boolean | undefined}
+ */
+export function getCompressResponses() {
+  const config = readConfigFile();
+  if (typeof config.compressResponses === "boolean") {
+    return config.compressResponses;
+  }
+  return undefined;
+}
+
+/**
+ * The HTTP methods the proxy forwards, undefined when not set
+ * @returns {string[] | undefined}
+ */
+export function getAllowMethods() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.allowMethods)) {
+    return undefined;
+  }
+
+  return config.allowMethods.filter((item) => typeof item === "string");
+}
+
+/**
+ * The HTTP methods the proxy refuses to forward, undefined when not set
+ * @returns {string[] | undefined}
+ */
+export function getDenyMethods() {
+  const config = readConfigFile();
+
+  if (!Array.isArray(config.denyMethods)) {
+    return undefined;
+  }
+
+  return config.denyMethods.filter((item) => typeof item === "string");
+}
+
+/**
+ * Local Aikido agent endpoint to load the policy from
  * @returns {string | undefined}
  */
-export function getLogFileFormat() {
+export function getPolicySource() {
   const config = readConfigFile();
-  if (config.logFileFormat && typeof config.logFileFormat === "string") {
-    return config.logFileFormat;
+  if (config.policySource && typeof config.policySource === "string") {
+    return config.policySource;
   }
   return undefined;
 }
 
 /**
- * Gets the log file verbosity from the config file
+ * Seconds to cache hostnames resolved by the system resolver
+ * @returns {number | undefined}
+ */
+export function getDnsCacheTtlSecs() {
+  const config = readConfigFile();
+  if (config.dnsCacheTtlSecs === undefined || config.dnsCacheTtlSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.dnsCacheTtlSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Minimum seconds to cache a resolved hostname
+ * @returns {number | undefined}
+ */
+export function getDnsCacheMinTtlSecs() {
+  const config = readConfigFile();
+  if (config.dnsCacheMinTtlSecs === undefined || config.dnsCacheMinTtlSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.dnsCacheMinTtlSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Maximum seconds to cache a resolved hostname
+ * @returns {number | undefined}
+ */
+export function getDnsCacheMaxTtlSecs() {
+  const config = readConfigFile();
+  if (config.dnsCacheMaxTtlSecs === undefined || config.dnsCacheMaxTtlSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.dnsCacheMaxTtlSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Seconds to cache that a hostname does not exist
+ * @returns {number | undefined}
+ */
+export function getDnsNegativeTtlSecs() {
+  const config = readConfigFile();
+  if (config.dnsNegativeTtlSecs === undefined || config.dnsNegativeTtlSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.dnsNegativeTtlSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Minimum hours since the first version of a package was published
+ * @returns {number | undefined}
+ */
+export function getMinPackageFirstPublishAgeHours() {
+  const config = readConfigFile();
+  if (config.minPackageFirstPublishAgeHours === undefined || config.minPackageFirstPublishAgeHours === null) {
+    return undefined;
+  }
+
+  const value = Number(config.minPackageFirstPublishAgeHours);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
+/**
+ * Source IP address of upstream connections
  * @returns {string | undefined}
  */
-export function getLogFileVerbosity() {
+export function getUpstreamBind() {
   const config = readConfigFile();
-  if (config.logFileVerbosity && typeof config.logFileVerbosity === "string") {
-    return config.logFileVerbosity;
+  if (config.upstreamBind && typeof config.upstreamBind === "string") {
+    return config.upstreamBind;
   }
   return undefined;
 }
 
+/**
+ * Longest Retry-After delay in seconds that feed downloads wait for
+ * @returns {number | undefined}
+ */
+export function getMaxRetryAfterSecs() {
+  const config = readConfigFile();
+  if (config.maxRetryAfterSecs === undefined || config.maxRetryAfterSecs === null) {
+    return undefined;
+  }
+
+  const value = Number(config.maxRetryAfterSecs);
+  if (Number.isNaN(value)) {
+    return undefined;
+  }
+  return value;
+}
+
 /**
  * Gets the custom npm registries from the config file (format parsing only, no validation)
  * @returns {string[]}
@@ -226,7 +1343,22 @@ export function writeDatabaseToLocalCache(data, version) {
 }
 
 /**
- * @returns {{malwareDatabase: import("../api/aikido.js").MalwarePackage[] | null, version: string | null}}
+ * Marks the cached malware database as refreshed, after the server confirmed
+ * that it is still the latest version.
+ *
+ * @returns {void}
+ */
+export function markDatabaseCacheRefreshed() {
+  try {
+    const now = new Date();
+    fs.utimesSync(getDatabasePath(), now, now);
+  } catch {
+    // best-effort: the cache then only looks older than it is
+  }
+}
+
+/**
+ * @returns {{malwareDatabase: import("../api/aikido.js").MalwarePackage[] | null, version: string | null, refreshedAt: number | null}}
  */
 export function readDatabaseFromLocalCache() {
   try {
@@ -235,6 +1367,7 @@ export function readDatabaseFromLocalCache() {
       return {
         malwareDatabase: null,
         version: null,
+        refreshedAt: null,
       };
     }
     const data = fs.readFileSync(databasePath, "utf8");
@@ -247,6 +1380,8 @@ export function readDatabaseFromLocalCache() {
     return {
       malwareDatabase: malwareDatabase,
       version: version,
+      // The cache is written or touched on every successful refresh
+      refreshedAt: fs.statSync(databasePath).mtimeMs,
     };
   } catch {
     ui.writeWarning(
@@ -255,10 +1390,121 @@ export function readDatabaseFromLocalCache() {
     return {
       malwareDatabase: null,
       version: null,
+      refreshedAt: null,
     };
   }
 }
 
+/**
+ * Writes a list shipped with the malware database, such as the denied hosts,
+ * so the next run can use it before the download finished or when it fails.
+ *
+ * @param {string} name - Identifies the list, e.g. "deniedHosts"
+ * @param {unknown[]} list
+ * @returns {void}
+ */
+export function writeFeedListToLocalCache(name, list) {
+  try {
+    fs.writeFileSync(getFeedListPath(name), JSON.stringify(list));
+  } catch {
+    // best-effort: the next run then starts without a cached copy
+  }
+}
+
+/**
+ * @param {string} name
+ * @returns {{list: unknown[], refreshedAt: number} | null}
+ */
+export function readFeedListFromLocalCache(name) {
+  try {
+    const listPath = getFeedListPath(name);
+    if (!fs.existsSync(listPath)) {
+      return null;
+    }
+    const list = JSON.parse(fs.readFileSync(listPath, "utf8"));
+    if (!Array.isArray(list)) {
+      return null;
+    }
+    return { list, refreshedAt: fs.statSync(listPath).mtimeMs };
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * @returns {string}
+ */
+export function getLocalDenylistPath() {
+  return path.join(getSafeChainDirectory(), "denylist.json");
+}
+
+/**
+ * Reads the package denylist imported with `safe-chain import-denylist`.
+ * @returns {import("../scanning/localDenylist.js").DenylistEntry[]}
+ */
+export function readLocalDenylist() {
+  try {
+    const denylistPath = getLocalDenylistPath();
+    if (!fs.existsSync(denylistPath)) {
+      return [];
+    }
+
+    const entries = JSON.parse(fs.readFileSync(denylistPath, "utf8"));
+    if (!Array.isArray(entries)) {
+      return [];
+    }
+    return entries.filter(
+      (entry) =>
+        typeof entry?.ecosystem === "string" &&
+        typeof entry?.package_name === "string"
+    );
+  } catch {
+    ui.writeWarning(
+      "Failed to read the local package denylist. Continuing without it."
+    );
+    return [];
+  }
+}
+
+/**
+ * @param {import("../scanning/localDenylist.js").DenylistEntry[]} entries
+ * @returns {void}
+ */
+export function writeLocalDenylist(entries) {
+  fs.writeFileSync(getLocalDenylistPath(), JSON.stringify(entries, null, 2));
+}
+
+/**
+ * Throws when the config file exists but cannot be used. Reading settings never
+ * fails on a broken config file, so this check runs once at startup instead.
+ *
+ * @returns {void}
+ */
+export function validateConfigFile() {
+  const configFilePath = getConfigFilePath();
+  if (!fs.existsSync(configFilePath)) {
+    return;
+  }
+
+  let config;
+  try {
+    config = JSON.parse(fs.readFileSync(configFilePath, "utf8"));
+  } catch (/** @type {any} */ error) {
+    throw new SafeChainStartupError(
+      EXIT_CODE_CONFIG_ERROR,
+      `The config file ${configFilePath} is not valid JSON (${error.message}). Fix or remove the file.`,
+      { cause: error }
+    );
+  }
+
+  if (!config || typeof config !== "object" || Array.isArray(config)) {
+    throw new SafeChainStartupError(
+      EXIT_CODE_CONFIG_ERROR,
+      `The config file ${configFilePath} must contain a JSON object. Fix or remove the file.`
+    );
+  }
+}
+
 /**
  * @returns {SafeChainConfig}
  */
@@ -271,6 +1517,80 @@ function readConfigFile() {
     logFile: undefined,
     logFileFormat: undefined,
     logFileVerbosity: undefined,
+    dohResolver: undefined,
+    denyHosts: undefined,
+    cacheDir: undefined,
+    cacheMaxSizeGb: undefined,
+    checkUpdates: undefined,
+    blockPrereleases: undefined,
+    prereleaseAllowlist: undefined,
+    proxyPort: undefined,
+    portRetry: undefined,
+    tcpBacklog: undefined,
+    tcpNoDelay: undefined,
+    strictSni: undefined,
+    stripRequestHeaders: undefined,
+    stripResponseHeaders: undefined,
+    addVia: undefined,
+    maxDecompressedMb: undefined,
+    failOnClockSkew: undefined,
+    npmrc: undefined,
+    circuitBreakerThreshold: undefined,
+    circuitBreakerCooldownSeconds: undefined,
+    maxHeaderBytes: undefined,
+    portFile: undefined,
+    minTlsVersion: undefined,
+    malwareFeedUrls: undefined,
+    uaEcosystemHints: undefined,
+    noInterceptHosts: undefined,
+    strictInterception: undefined,
+    metrics: undefined,
+    blockStatus: undefined,
+    inspectOnly: undefined,
+    warnInstallScripts: undefined,
+    blockInstallScripts: undefined,
+    installScriptAllowlist: undefined,
+    readyBanner: undefined,
+    excludePaths: undefined,
+    malwareListToken: undefined,
+    feedEntryMinAgeMins: undefined,
+    flagSizeAnomaly: undefined,
+    blockSizeAnomaly: undefined,
+    sizeAnomalyFactor: undefined,
+    strictUnknownHosts: undefined,
+    allowedUnknownHosts: undefined,
+    feedMaxStalenessMins: undefined,
+    failOnStaleFeed: undefined,
+    selfTest: undefined,
+    eventSocket: undefined,
+    tunnelConnectTimeoutSecs: undefined,
+    maxLifetimeSecs: undefined,
+    blockMessages: undefined,
+    vcsPolicy: undefined,
+    reusePort: undefined,
+    offline: undefined,
+    noTelemetry: undefined,
+    checkDependencyClosure: undefined,
+    listenOnlyWhenWrapped: undefined,
+    idleStartupTimeoutSecs: undefined,
+    upstreamProxyUser: undefined,
+    upstreamProxyPass: undefined,
+    redirectMap: undefined,
+    malwareCheckEcosystems: undefined,
+    minimumPackageAgeEcosystems: undefined,
+    adminToken: undefined,
+    maxTrackedPackages: undefined,
+    compressResponses: undefined,
+    allowMethods: undefined,
+    denyMethods: undefined,
+    policySource: undefined,
+    dnsCacheTtlSecs: undefined,
+    dnsCacheMinTtlSecs: undefined,
+    dnsCacheMaxTtlSecs: undefined,
+    dnsNegativeTtlSecs: undefined,
+    minPackageFirstPublishAgeHours: undefined,
+    upstreamBind: undefined,
+    maxRetryAfterSecs: undefined,
     npm: {
       customRegistries: undefined,
     },
@@ -302,6 +1622,14 @@ function getDatabasePath() {
   return path.join(aikidoDir, `malwareDatabase_${ecosystem}.json`);
 }
 
+/**
+ * @param {string} name
+ * @returns {string}
+ */
+function getFeedListPath(name) {
+  return path.join(getAikidoDirectory(), `${name}.json`);
+}
+
 function getDatabaseVersionPath() {
   const aikidoDir = getAikidoDirectory();
   const ecosystem = getEcoSystem();
@@ -326,10 +1654,29 @@ export function getNewPackagesListVersionPath() {
   return path.join(safeChainDir, `newPackagesList_version_${ecosystem}.txt`);
 }
 
+/** @type {string | undefined} */
+let configFilePathOverride = undefined;
+
+/**
+ * Reads settings from another config file instead of the one in the safe-chain
+ * directory, for example to evaluate a candidate policy. Pass undefined to go
+ * back to the regular config file.
+ *
+ * @param {string | undefined} filePath
+ * @returns {void}
+ */
+export function setConfigFilePathOverride(filePath) {
+  configFilePathOverride = filePath;
+}
+
 /**
  * @returns {string}
  */
 function getConfigFilePath() {
+  if (configFilePathOverride) {
+    return configFilePathOverride;
+  }
+
   const primaryPath = path.join(getSafeChainDirectory(), "config.json");
   if (fs.existsSync(primaryPath)) {
     return primaryPath;
@@ -504,3 +504,39 @@ describe("config file location fallback", async () => {
     assert.strictEqual(getScanTimeout(), 10000);
   });
 });
+
+describe("validateConfigFile", async () => {
+  const { validateConfigFile } = await import("./configFile.js");
+  const { EXIT_CODE_CONFIG_ERROR } = await import("../utils/exitCodes.js");
+
+  afterEach(() => {
+    mockFiles.clear();
+  });
+
+  it("should accept a missing config file", () => {
+    assert.doesNotThrow(() => validateConfigFile());
+  });
+
+  it("should accept a config file with a JSON object", () => {
+    setConfigContent(JSON.stringify({ scanTimeout: 3000 }));
+
+    assert.doesNotThrow(() => validateConfigFile());
+  });
+
+  it("should throw a config error for invalid JSON", () => {
+    setConfigContent("{ invalid json");
+
+    assert.throws(() => validateConfigFile(), {
+      name: "SafeChainStartupError",
+      exitCode: EXIT_CODE_CONFIG_ERROR,
+    });
+  });
+
+  it("should throw a config error when the config is not an object", () => {
+    setConfigContent(JSON.stringify(["scanTimeout"]));
+
+    assert.throws(() => validateConfigFile(), {
+      exitCode: EXIT_CODE_CONFIG_ERROR,
+    });
+  });
+});
@@ -82,3 +82,676 @@ export function getLogFileFormat() {
 export function getLogFileVerbosity() {
   return process.env.SAFE_CHAIN_LOG_FILE_VERBOSITY;
 }
+
+/**
+ * Gets the DNS-over-HTTPS resolver URL from environment variable
+ * Example: "https://cloudflare-dns.com/dns-query"
+ * @returns {string | undefined}
+ */
+export function getDohResolver() {
+  return process.env.SAFE_CHAIN_DOH_RESOLVER;
+}
+
+/**
+ * Gets the hosts the proxy refuses to connect to from environment variable
+ * Expected format: comma-separated list of hostnames or wildcard patterns
+ * Example: "evil.example,*.c2.example"
+ * @returns {string | undefined}
+ */
+export function getDenyHosts() {
+  return process.env.SAFE_CHAIN_DENY_HOSTS;
+}
+
+/**
+ * Gets the response cache directory from environment variable
+ * @returns {string | undefined}
+ */
+export function getCacheDir() {
+  return process.env.SAFE_CHAIN_CACHE_DIR;
+}
+
+/**
+ * Gets the maximum response cache size in gigabytes from environment variable
+ * @returns {string | undefined}
+ */
+export function getCacheMaxSizeGb() {
+  return process.env.SAFE_CHAIN_CACHE_MAX_SIZE_GB;
+}
+
+/**
+ * Gets whether to check for newer safe-chain versions from environment variable
+ * Example: "true" or "1"
+ * @returns {string | undefined}
+ */
+export function getCheckUpdates() {
+  return process.env.SAFE_CHAIN_CHECK_UPDATES;
+}
+
+/**
+ * Gets whether to block pre-release package versions from environment variable
+ * Example: "true" or "1"
+ * @returns {string | undefined}
+ */
+export function getBlockPrereleases() {
+  return process.env.SAFE_CHAIN_BLOCK_PRERELEASES;
+}
+
+/**
+ * Gets the pre-releases that may still be installed when pre-releases are blocked from environment variable
+ * Expected format: comma-separated list of package names or name@version entries
+ * Example: "my-package,other-package@2.0.0-rc.1"
+ * @returns {string | undefined}
+ */
+export function getPrereleaseAllowlist() {
+  return process.env.SAFE_CHAIN_PRERELEASE_ALLOWLIST;
+}
+
+/**
+ * Gets the port the proxy listens on from environment variable
+ * Example: "8585"
+ * @returns {string | undefined}
+ */
+export function getProxyPort() {
+  return process.env.SAFE_CHAIN_PROXY_PORT;
+}
+
+/**
+ * Gets whether to retry or fall back to another port when the proxy port is in use from environment variable
+ * Example: "true" or "1"
+ * @returns {string | undefined}
+ */
+export function getPortRetry() {
+  return process.env.SAFE_CHAIN_PORT_RETRY;
+}
+
+/**
+ * Maximum length of the queue of pending connections on the proxy listener
+ * Example: 1024
+ * @returns {string | undefined}
+ */
+export function getTcpBacklog() {
+  return process.env.SAFE_CHAIN_TCP_BACKLOG;
+}
+
+/**
+ * Whether Nagle's algorithm is disabled on accepted proxy connections (true/false)
+ * Example: false
+ * @returns {string | undefined}
+ */
+export function getTcpNoDelay() {
+  return process.env.SAFE_CHAIN_TCP_NODELAY;
+}
+
+/**
+ * Whether intercepted TLS connections must send an SNI matching the CONNECT host (true/false)
+ * Example: false
+ * @returns {string | undefined}
+ */
+export function getStrictSni() {
+  return process.env.SAFE_CHAIN_STRICT_SNI;
+}
+
+/**
+ * Comma-separated request header names removed before forwarding to the registry
+ * Example: cookie,x-tracking-id
+ * @returns {string | undefined}
+ */
+export function getStripRequestHeaders() {
+  return process.env.SAFE_CHAIN_STRIP_REQUEST_HEADERS;
+}
+
+/**
+ * Comma-separated response header names removed before returning to the client
+ * Example: set-cookie
+ * @returns {string | undefined}
+ */
+export function getStripResponseHeaders() {
+  return process.env.SAFE_CHAIN_STRIP_RESPONSE_HEADERS;
+}
+
+/**
+ * Whether a Via header is added to proxied requests and responses (true/false)
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getAddVia() {
+  return process.env.SAFE_CHAIN_ADD_VIA;
+}
+
+/**
+ * Maximum size in megabytes a compressed registry response may decompress to
+ * Example: 512
+ * @returns {string | undefined}
+ */
+export function getMaxDecompressedMb() {
+  return process.env.SAFE_CHAIN_MAX_DECOMPRESSED_MB;
+}
+
+/**
+ * Logs small request and response bodies to the verbose log (debugging only)
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getTraceBody() {
+  return process.env.SAFE_CHAIN_TRACE_BODY;
+}
+
+/**
+ * Skips the minimum package age check
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getSkipMinimumPackageAge() {
+  return process.env.SAFE_CHAIN_SKIP_MINIMUM_PACKAGE_AGE;
+}
+
+/**
+ * Fails when the local clock differs from the server time by more than an hour
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getFailOnClockSkew() {
+  return process.env.SAFE_CHAIN_FAIL_ON_CLOCK_SKEW;
+}
+
+/**
+ * Gets the path of an .npmrc file with registry and scope mappings from environment variable
+ * Example: "~/work/acme/.npmrc"
+ * @returns {string | undefined}
+ */
+export function getNpmrc() {
+  return process.env.SAFE_CHAIN_NPMRC;
+}
+
+/**
+ * Gets the number of consecutive upstream failures after which a host is short-circuited from environment variable
+ * @returns {string | undefined}
+ */
+export function getCircuitBreakerThreshold() {
+  return process.env.SAFE_CHAIN_CIRCUIT_BREAKER_THRESHOLD;
+}
+
+/**
+ * Gets how long a short-circuited host is skipped, in seconds, from environment variable
+ * @returns {string | undefined}
+ */
+export function getCircuitBreakerCooldownSeconds() {
+  return process.env.SAFE_CHAIN_CIRCUIT_BREAKER_COOLDOWN_SECONDS;
+}
+
+/**
+ * Gets the maximum size of the request line and headers the proxy accepts from environment variable
+ * @returns {string | undefined}
+ */
+export function getMaxHeaderBytes() {
+  return process.env.SAFE_CHAIN_MAX_HEADER_BYTES;
+}
+
+/**
+ * Gets whether blocking is disabled (break-glass switch) from environment variable
+ * Example: "1"
+ * @returns {string | undefined}
+ */
+export function getDisableBlocking() {
+  return process.env.SAFE_CHAIN_DISABLE_BLOCKING;
+}
+
+/**
+ * Gets the path of the file the bound proxy port is written to from environment variable
+ * @returns {string | undefined}
+ */
+export function getPortFile() {
+  return process.env.SAFE_CHAIN_PORT_FILE;
+}
+
+/**
+ * Minimum TLS version for connections to upstream registries
+ * Example: TLSv1.3
+ * @returns {string | undefined}
+ */
+export function getMinTlsVersion() {
+  return process.env.SAFE_CHAIN_MIN_TLS_VERSION;
+}
+
+/**
+ * Comma-separated base URLs of additional malware feeds
+ * Example: https://intel.acme.com/feeds,https://feeds.example.com
+ * @returns {string | undefined}
+ */
+export function getMalwareFeedUrls() {
+  return process.env.SAFE_CHAIN_MALWARE_FEED_URLS;
+}
+
+/**
+ * Gets whether the client's User-Agent is used as an ecosystem hint for unrecognized hosts
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getUaEcosystemHints() {
+  return process.env.SAFE_CHAIN_UA_ECOSYSTEM_HINTS;
+}
+
+/**
+ * Gets the registry hosts that are tunneled without interception from environment variable
+ * Example: registry.acme.com,*.pinned.example
+ * @returns {string | undefined}
+ */
+export function getNoInterceptHosts() {
+  return process.env.SAFE_CHAIN_NO_INTERCEPT_HOSTS;
+}
+
+/**
+ * Gets whether failed interception handshakes block instead of falling back to tunneling
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getStrictInterception() {
+  return process.env.SAFE_CHAIN_STRICT_INTERCEPTION;
+}
+
+/**
+ * Gets whether the proxy serves Prometheus metrics on /metrics from environment variable
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getMetrics() {
+  return process.env.SAFE_CHAIN_METRICS;
+}
+
+/**
+ * Gets the HTTP status code for blocked requests from environment variable
+ * Example: 451,npm=403
+ * @returns {string | undefined}
+ */
+export function getBlockStatus() {
+  return process.env.SAFE_CHAIN_BLOCK_STATUS;
+}
+
+/**
+ * Gets whether the proxy records decisions without enforcing them from environment variable
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getInspectOnly() {
+  return process.env.SAFE_CHAIN_INSPECT_ONLY;
+}
+
+/**
+ * Gets whether to warn about npm packages with install scripts from environment variable
+ * Example: "true"
+ * @returns {string | undefined}
+ */
+export function getWarnInstallScripts() {
+  return process.env.SAFE_CHAIN_WARN_INSTALL_SCRIPTS;
+}
+
+/**
+ * Gets whether to block npm packages with install scripts from environment variable
+ * Example: "true"
+ * @returns {string | undefined}
+ */
+export function getBlockInstallScripts() {
+  return process.env.SAFE_CHAIN_BLOCK_INSTALL_SCRIPTS;
+}
+
+/**
+ * Gets the npm packages whose install scripts are allowed from environment variable
+ * Example: "esbuild,@swc/*"
+ * @returns {string | undefined}
+ */
+export function getInstallScriptAllowlist() {
+  return process.env.SAFE_CHAIN_INSTALL_SCRIPT_ALLOWLIST;
+}
+
+/**
+ * Gets whether to write a ready line once the proxy accepts connections from environment variable
+ * Example: "true"
+ * @returns {string | undefined}
+ */
+export function getReadyBanner() {
+  return process.env.SAFE_CHAIN_READY_BANNER;
+}
+
+/**
+ * Gets the regular expressions for registry paths that are forwarded without inspection from environment variable
+ * Example: "^/-/v1/search,^/-/npm/v1/security/"
+ * @returns {string | undefined}
+ */
+export function getExcludePaths() {
+  return process.env.SAFE_CHAIN_EXCLUDE_PATHS;
+}
+
+/**
+ * Gets the bearer token sent to the malware list base URL from environment variable
+ * @returns {string | undefined}
+ */
+export function getMalwareListToken() {
+  return process.env.SAFE_CHAIN_MALWARE_LIST_TOKEN;
+}
+
+/**
+ * Minutes a malware feed entry is only reported before it is enforced
+ * Example: 60
+ * @returns {string | undefined}
+ */
+export function getFeedEntryMinAgeMins() {
+  return process.env.SAFE_CHAIN_FEED_ENTRY_MIN_AGE_MINS;
+}
+
+/**
+ * Warn about npm package versions much larger than the other versions of the package
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getFlagSizeAnomaly() {
+  return process.env.SAFE_CHAIN_FLAG_SIZE_ANOMALY;
+}
+
+/**
+ * Block npm package versions much larger than the other versions of the package
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getBlockSizeAnomaly() {
+  return process.env.SAFE_CHAIN_BLOCK_SIZE_ANOMALY;
+}
+
+/**
+ * How many times larger than usual a version must be to count as a size anomaly
+ * Example: 10
+ * @returns {string | undefined}
+ */
+export function getSizeAnomalyFactor() {
+  return process.env.SAFE_CHAIN_SIZE_ANOMALY_FACTOR;
+}
+
+/**
+ * Block requests to hosts that are not a known or configured registry
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getStrictUnknownHosts() {
+  return process.env.SAFE_CHAIN_STRICT_UNKNOWN_HOSTS;
+}
+
+/**
+ * Hosts that are not registries but may be reached in strict unknown hosts mode
+ * Example: github.com,*.githubusercontent.com
+ * @returns {string | undefined}
+ */
+export function getAllowedUnknownHosts() {
+  return process.env.SAFE_CHAIN_ALLOWED_UNKNOWN_HOSTS;
+}
+
+/**
+ * Minutes since the last successful malware list refresh after which the list is reported as stale
+ * Example: 1440
+ * @returns {string | undefined}
+ */
+export function getFeedMaxStalenessMins() {
+  return process.env.SAFE_CHAIN_FEED_MAX_STALENESS_MINS;
+}
+
+/**
+ * Whether safe-chain fails when the malware list is stale
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getFailOnStaleFeed() {
+  return process.env.SAFE_CHAIN_FAIL_ON_STALE_FEED;
+}
+
+/**
+ * Whether the policy is checked against known fixtures when the proxy starts
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getSelfTest() {
+  return process.env.SAFE_CHAIN_SELF_TEST;
+}
+
+/**
+ * Path of a Unix socket, named pipe or FIFO the proxy streams decision events to
+ * Example: /tmp/safe-chain-events.sock
+ * @returns {string | undefined}
+ */
+export function getEventSocket() {
+  return process.env.SAFE_CHAIN_EVENT_SOCKET;
+}
+
+/**
+ * Seconds to wait for the connection to the upstream of a CONNECT tunnel
+ * Example: 10
+ * @returns {string | undefined}
+ */
+export function getTunnelConnectTimeoutSecs() {
+  return process.env.SAFE_CHAIN_TUNNEL_CONNECT_TIMEOUT_SECS;
+}
+
+/**
+ * Seconds after which the proxy shuts down
+ * Example: 3600
+ * @returns {string | undefined}
+ */
+export function getMaxLifetimeSecs() {
+  return process.env.SAFE_CHAIN_MAX_LIFETIME_SECS;
+}
+
+/**
+ * Gets how git dependency fetches from VCS hosts are handled
+ * Example: log
+ * @returns {string | undefined}
+ */
+export function getVcsPolicy() {
+  return process.env.SAFE_CHAIN_VCS_POLICY;
+}
+
+/**
+ * Gets whether the proxy binds its port with SO_REUSEPORT
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getReusePort() {
+  return process.env.SAFE_CHAIN_REUSE_PORT;
+}
+
+/**
+ * Gets whether safe-chain runs without downloading the malware list and other feeds
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getOffline() {
+  return process.env.SAFE_CHAIN_OFFLINE;
+}
+
+/**
+ * Gets whether safe-chain skips requests that are not needed for protection, such as the update check
+ * Example: true
+ * @returns {string | undefined}
+ */
+export function getNoTelemetry() {
+  return process.env.SAFE_CHAIN_NO_TELEMETRY;
+}
+
+/**
+ * Enables the check of the declared dependencies of npm packages against the malware list
+ * @returns {string | undefined}
+ */
+export function getCheckDependencyClosure() {
+  return process.env.SAFE_CHAIN_CHECK_DEPENDENCY_CLOSURE;
+}
+
+/**
+ * Shuts the proxy down when the parent process exits or no client connects
+ * @returns {string | undefined}
+ */
+export function getListenOnlyWhenWrapped() {
+  return process.env.SAFE_CHAIN_LISTEN_ONLY_WHEN_WRAPPED;
+}
+
+/**
+ * Seconds the proxy waits for the first client when it only listens when wrapped
+ * @returns {string | undefined}
+ */
+export function getIdleStartupTimeoutSecs() {
+  return process.env.SAFE_CHAIN_IDLE_STARTUP_TIMEOUT_SECS;
+}
+
+/**
+ * User name for the upstream proxy from HTTPS_PROXY
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyUser() {
+  return process.env.SAFE_CHAIN_UPSTREAM_PROXY_USER;
+}
+
+/**
+ * Password for the upstream proxy from HTTPS_PROXY
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyPass() {
+  return process.env.SAFE_CHAIN_UPSTREAM_PROXY_PASS;
+}
+
+/**
+ * Replaces the malware list with the embedded test fixture
+ * @returns {string | undefined}
+ */
+export function getUseTestFixture() {
+  return process.env.SAFE_CHAIN_USE_TEST_FIXTURE;
+}
+
+/**
+ * Replacement base URLs for blocked packages (comma-separated type:name=URL entries)
+ * @returns {string | undefined}
+ */
+export function getRedirectMap() {
+  return process.env.SAFE_CHAIN_REDIRECT_MAP;
+}
+
+/**
+ * Comma-separated ecosystems the malware check applies to, for example "npm,pypi"
+ * @returns {string | undefined}
+ */
+export function getMalwareCheckEcosystems() {
+  return process.env.SAFE_CHAIN_MALWARE_CHECK_ECOSYSTEMS;
+}
+
+/**
+ * Comma-separated ecosystems the minimum package age check applies to, for example "pypi"
+ * @returns {string | undefined}
+ */
+export function getMinimumPackageAgeEcosystems() {
+  return process.env.SAFE_CHAIN_MINIMUM_PACKAGE_AGE_ECOSYSTEMS;
+}
+
+/**
+ * Bearer token for the admin endpoints of the proxy
+ * @returns {string | undefined}
+ */
+export function getAdminToken() {
+  return process.env.SAFE_CHAIN_ADMIN_TOKEN;
+}
+
+/**
+ * Number of packages whose metadata details are kept in memory
+ * @returns {string | undefined}
+ */
+export function getMaxTrackedPackages() {
+  return process.env.SAFE_CHAIN_MAX_TRACKED_PACKAGES;
+}
+
+/**
+ * Whether the proxy compresses the responses it rewrites or serves from its cache
+ * @returns {string | undefined}
+ */
+export function getCompressResponses() {
+  return process.env.SAFE_CHAIN_COMPRESS_RESPONSES;
+}
+
+/**
+ * The HTTP methods the proxy forwards, comma separated
+ * @returns {string | undefined}
+ */
+export function getAllowMethods() {
+  return process.env.SAFE_CHAIN_ALLOW_METHODS;
+}
+
+/**
+ * The HTTP methods the proxy refuses to forward, comma separated
+ * @returns {string | undefined}
+ */
+export function getDenyMethods() {
+  return process.env.SAFE_CHAIN_DENY_METHODS;
+}
+
+/**
+ * Local Aikido agent endpoint to load the policy from
+ * Example: agent://127.0.0.1:7981
+ * @returns {string | undefined}
+ */
+export function getPolicySource() {
+  return process.env.SAFE_CHAIN_POLICY_SOURCE;
+}
+
+/**
+ * Seconds to cache hostnames resolved by the system resolver
+ * Example: 30
+ * @returns {string | undefined}
+ */
+export function getDnsCacheTtlSecs() {
+  return process.env.SAFE_CHAIN_DNS_CACHE_TTL_SECS;
+}
+
+/**
+ * Minimum seconds to cache a resolved hostname
+ * Example: 5
+ * @returns {string | undefined}
+ */
+export function getDnsCacheMinTtlSecs() {
+  return process.env.SAFE_CHAIN_DNS_CACHE_MIN_TTL_SECS;
+}
+
+/**
+ * Maximum seconds to cache a resolved hostname
+ * Example: 600
+ * @returns {string | undefined}
+ */
+export function getDnsCacheMaxTtlSecs() {
+  return process.env.SAFE_CHAIN_DNS_CACHE_MAX_TTL_SECS;
+}
+
+/**
+ * Seconds to cache that a hostname does not exist
+ * Example: 10
+ * @returns {string | undefined}
+ */
+export function getDnsNegativeTtlSecs() {
+  return process.env.SAFE_CHAIN_DNS_NEGATIVE_TTL_SECS;
+}
+
+/**
+ * Minimum hours since the first version of a package was published
+ * Example: 168
+ * @returns {string | undefined}
+ */
+export function getMinPackageFirstPublishAgeHours() {
+  return process.env.SAFE_CHAIN_MIN_PACKAGE_FIRST_PUBLISH_AGE_HOURS;
+}
+
+/**
+ * Source IP address of upstream connections
+ * Example: 10.0.0.5
+ * @returns {string | undefined}
+ */
+export function getUpstreamBind() {
+  return process.env.SAFE_CHAIN_UPSTREAM_BIND;
+}
+
+/**
+ * Longest Retry-After delay in seconds that feed downloads wait for
+ * @returns {string | undefined}
+ */
+export function getMaxRetryAfterSecs() {
+  return process.env.SAFE_CHAIN_MAX_RETRY_AFTER_SECS;
+}
@@ -0,0 +1,132 @@
+import fs from "fs";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * @typedef {Object} NpmrcRegistries
+ * @property {string | undefined} defaultRegistry - The `registry=` entry
+ * @property {Record<string, string>} scopes - Scope (`@acme`) to registry
+ * @property {string[]} registries - Every registry the file refers to,
+ *   including registries that only have auth settings
+ */
+
+/** @type {{filePath: string | undefined, registries: NpmrcRegistries | undefined}} */
+const state = {
+  filePath: undefined,
+  registries: undefined,
+};
+
+/**
+ * Parses the registry related keys of an `.npmrc` file:
+ *  - `registry=https://registry.acme.com/`
+ *  - `@acme:registry=https://npm.acme.com/`
+ *  - `//npm.acme.com/:_authToken=${NPM_TOKEN}`
+ *
+ * Registries are returned without protocol and trailing slash, which is the
+ * format used for custom registries. Other keys are ignored.
+ *
+ * @param {string} content
+ * @returns {NpmrcRegistries}
+ */
+export function parseNpmrc(content) {
+  /** @type {NpmrcRegistries} */
+  const result = { defaultRegistry: undefined, scopes: {}, registries: [] };
+
+  for (const rawLine of content.split(/\r?\n/)) {
+    const line = rawLine.trim();
+    if (line.length === 0 || line.startsWith("#") || line.startsWith(";")) {
+      continue;
+    }
+
+    const separatorIndex = line.indexOf("=");
+    if (separatorIndex <= 0) {
+      continue;
+    }
+
+    const key = line.slice(0, separatorIndex).trim();
+    const value = expandEnvironmentVariables(
+      line.slice(separatorIndex + 1).trim()
+    );
+
+    if (key === "registry") {
+      result.defaultRegistry = normalizeNpmrcRegistry(value);
+      addRegistry(result, result.defaultRegistry);
+      continue;
+    }
+
+    const scopeMatch = key.match(/^(@[^:\s]+):registry$/);
+    if (scopeMatch) {
+      const registry = normalizeNpmrcRegistry(value);
+      result.scopes[scopeMatch[1].toLowerCase()] = registry;
+      addRegistry(result, registry);
+      continue;
+    }
+
+    // Per-registry settings such as `//npm.acme.com/:_authToken`
+    if (key.startsWith("//")) {
+      const settingIndex = key.lastIndexOf(":");
+      if (settingIndex > 2) {
+        addRegistry(result, normalizeNpmrcRegistry(key.slice(0, settingIndex)));
+      }
+    }
+  }
+
+  return result;
+}
+
+/**
+ * Reads and parses an `.npmrc` file. The result is cached per path because
+ * registries are looked up for every proxied request. An unreadable file is
+ * reported once and treated as empty.
+ *
+ * @param {string} filePath
+ * @returns {NpmrcRegistries}
+ */
+export function readNpmrc(filePath) {
+  if (state.filePath === filePath && state.registries) {
+    return state.registries;
+  }
+
+  let content = "";
+  try {
+    content = fs.readFileSync(filePath, "utf8");
+  } catch (/** @type {any} */ error) {
+    ui.writeWarning(
+      `Safe-chain: Could not read npmrc file ${filePath}: ${error.message}`
+    );
+  }
+
+  state.filePath = filePath;
+  state.registries = parseNpmrc(content);
+  return state.registries;
+}
+
+/**
+ * @param {NpmrcRegistries} result
+ * @param {string} registry
+ */
+function addRegistry(result, registry) {
+  if (registry.length > 0 && !result.registries.includes(registry)) {
+    result.registries.push(registry);
+  }
+}
+
+/**
+ * @param {string} value
+ * @returns {string}
+ */
+function normalizeNpmrcRegistry(value) {
+  return value
+    .replace(/^["']|["']$/g, "")
+    .replace(/^(https?:)?\/\//, "")
+    .replace(/\/+$/, "");
+}
+
+/**
+ * npm expands `${VAR}` in .npmrc values; tokens are usually configured that way.
+ *
+ * @param {string} value
+ * @returns {string}
+ */
+function expandEnvironmentVariables(value) {
+  return value.replace(/\$\{([^}]+)\}/g, (_, name) => process.env[name] ?? "");
+}
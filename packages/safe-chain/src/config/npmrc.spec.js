@@ -0,0 +1,116 @@
+import { describe, it, afterEach, mock } from "node:test";
+import assert from "node:assert";
+
+/** @type {Record<string, string>} */
+let files = {};
+mock.module("fs", {
+  namedExports: {
+    existsSync: (filePath) => filePath in files,
+    readFileSync: (filePath) => {
+      if (!(filePath in files)) {
+        throw new Error(`ENOENT: no such file or directory, open '${filePath}'`);
+      }
+      return files[filePath];
+    },
+    writeFileSync: () => {},
+    mkdirSync: () => {},
+  },
+});
+
+let warnings = [];
+mock.module("../environment/userInteraction.js", {
+  namedExports: {
+    ui: {
+      writeWarning: (message) => warnings.push(message),
+      writeVerbose: () => {},
+      writeError: () => {},
+    },
+  },
+});
+
+const { parseNpmrc } = await import("./npmrc.js");
+const { getNpmCustomRegistries } = await import("./settings.js");
+
+describe("parseNpmrc", () => {
+  it("maps scopes to their registries", () => {
+    const npmrc = parseNpmrc(
+      [
+        "registry=https://registry.npmjs.org/",
+        "@acme:registry=https://npm.acme.com/",
+        "@Tools:registry=https://acme.jfrog.io/artifactory/api/npm/npm-local/",
+      ].join("\n")
+    );
+
+    assert.strictEqual(npmrc.defaultRegistry, "registry.npmjs.org");
+    assert.deepStrictEqual(npmrc.scopes, {
+      "@acme": "npm.acme.com",
+      "@tools": "acme.jfrog.io/artifactory/api/npm/npm-local",
+    });
+    assert.deepStrictEqual(npmrc.registries, [
+      "registry.npmjs.org",
+      "npm.acme.com",
+      "acme.jfrog.io/artifactory/api/npm/npm-local",
+    ]);
+  });
+
+  it("includes registries that only have auth settings", () => {
+    const npmrc = parseNpmrc(
+      [
+        "//npm.acme.com/:_authToken=${NPM_TOKEN}",
+        "//npm.internal.net/repo/:always-auth=true",
+        "@acme:registry=https://npm.acme.com/",
+      ].join("\n")
+    );
+
+    assert.deepStrictEqual(npmrc.registries, [
+      "npm.acme.com",
+      "npm.internal.net/repo",
+    ]);
+  });
+
+  it("ignores comments, unrelated keys and malformed lines", () => {
+    const npmrc = parseNpmrc(
+      [
+        "# registry=https://commented.example.com/",
+        "; @acme:registry=https://commented.example.com/",
+        "save-exact=true",
+        "not a setting",
+        "",
+      ].join("\r\n")
+    );
+
+    assert.deepStrictEqual(npmrc, {
+      defaultRegistry: undefined,
+      scopes: {},
+      registries: [],
+    });
+  });
+});
+
+describe("getNpmCustomRegistries with an npmrc file", () => {
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_NPMRC;
+    delete process.env.SAFE_CHAIN_NPM_CUSTOM_REGISTRIES;
+    files = {};
+    warnings = [];
+  });
+
+  it("merges the npmrc registries with the custom registries", () => {
+    files["/work/.npmrc"] = "@acme:registry=https://npm.acme.com/\n";
+    process.env.SAFE_CHAIN_NPMRC = "/work/.npmrc";
+    process.env.SAFE_CHAIN_NPM_CUSTOM_REGISTRIES = "npm.company.com,npm.acme.com";
+
+    assert.deepStrictEqual(getNpmCustomRegistries(), [
+      "npm.company.com",
+      "npm.acme.com",
+    ]);
+  });
+
+  it("warns and ignores an npmrc file that cannot be read", () => {
+    process.env.SAFE_CHAIN_NPMRC = "/missing/.npmrc";
+
+    assert.deepStrictEqual(getNpmCustomRegistries(), []);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(warnings[0], /Could not read npmrc file \/missing\/\.npmrc/);
+  });
+});
@@ -1,13 +1,17 @@
+import net from "net";
 import os from "os";
 import path from "path";
 import * as cliArguments from "./cliArguments.js";
 import * as configFile from "./configFile.js";
 import * as environmentVariables from "./environmentVariables.js";
+import { readNpmrc } from "./npmrc.js";
 import { ui } from "../environment/userInteraction.js";
 
 export const LOGGING_SILENT = "silent";
 export const LOGGING_NORMAL = "normal";
 export const LOGGING_VERBOSE = "verbose";
+// Only block decisions and errors; routine output is hidden.
+export const LOGGING_DECISIONS = "decisions";
 
 export function getLoggingLevel() {
   // Priority 1: CLI argument
@@ -139,13 +143,14 @@ export function getLogFileVerbosity() {
 
 /**
  * @param {string | undefined} value
- * @returns {value is "silent" | "normal" | "verbose"}
+ * @returns {value is "silent" | "normal" | "verbose" | "decisions"}
  */
 function isValidVerbosity(value) {
   return (
     value === LOGGING_SILENT ||
     value === LOGGING_NORMAL ||
-    value === LOGGING_VERBOSE
+    value === LOGGING_VERBOSE ||
+    value === LOGGING_DECISIONS
   );
 }
 
@@ -169,26 +174,69 @@ export function setEcoSystem(setting) {
 }
 
 const defaultMinimumPackageAge = 48;
-/** @returns {number} */
-export function getMinimumPackageAgeHours() {
+/**
+ * Resolves the minimum package age in hours. Every minimum age check (metadata
+ * suppression and direct download blocking, for all ecosystems) reads the value
+ * through this function.
+ *
+ * Precedence, the first valid value wins:
+ * 1. CLI argument (--safe-chain-minimum-package-age-hours), passed per invocation
+ * 2. Environment variable (SAFE_CHAIN_MINIMUM_PACKAGE_AGE_HOURS)
+ * 3. Config file (minimumPackageAgeHours)
+ * 4. Default (48 hours)
+ *
+ * Invalid and negative values are ignored, so the next source applies.
+ *
+ * A per-request override (X-Safe-Chain-Minimum-Package-Age-Hours header) only
+ * applies when it is higher than the configured value. Any client of the proxy
+ * can send the header, so it may make the policy stricter but never weaker.
+ *
+ * The CLI argument, environment variable and config file also accept per-ecosystem
+ * values such as "48,npm=72,pypi=500". The value for the current ecosystem wins over
+ * the global value of the same source.
+ *
+ * @param {import("../registryProxy/requestPolicyOverrides.js").RequestPolicyOverrides} [requestOverrides]
+ * @returns {number}
+ */
+export function getMinimumPackageAgeHours(requestOverrides) {
+  const configuredValue = getConfiguredMinimumPackageAgeHours();
+  const requestValue = validateMinimumPackageAgeHours(
+    requestOverrides?.minimumPackageAgeHours
+  );
+  if (requestValue !== undefined && requestValue > configuredValue) {
+    return requestValue;
+  }
+
+  return configuredValue;
+}
+
+/**
+ * @returns {number}
+ */
+function getConfiguredMinimumPackageAgeHours() {
   // Priority 1: CLI argument
-  const cliValue = validateMinimumPackageAgeHours(
-    cliArguments.getMinimumPackageAgeHours()
+  const cliValue = resolveEcosystemValue(
+    cliArguments.getMinimumPackageAgeHours(),
+    validateMinimumPackageAgeHours
   );
   if (cliValue !== undefined) {
     return cliValue;
   }
 
   // Priority 2: Environment variable
-  const envValue = validateMinimumPackageAgeHours(
-    environmentVariables.getMinimumPackageAgeHours()
+  const envValue = resolveEcosystemValue(
+    environmentVariables.getMinimumPackageAgeHours(),
+    validateMinimumPackageAgeHours
   );
   if (envValue !== undefined) {
     return envValue;
   }
 
   // Priority 3: Config file
-  const configValue = configFile.getMinimumPackageAgeHours();
+  const configValue = resolveEcosystemValue(
+    configFile.getMinimumPackageAgeHours(),
+    validateMinimumPackageAgeHours
+  );
   if (configValue !== undefined) {
     return configValue;
   }
@@ -196,12 +244,56 @@ export function getMinimumPackageAgeHours() {
   return defaultMinimumPackageAge;
 }
 
+/** @type {Record<string, string>} */
+const ecosystemNames = {
+  npm: ECOSYSTEM_JS,
+  js: ECOSYSTEM_JS,
+  pypi: ECOSYSTEM_PY,
+  pip: ECOSYSTEM_PY,
+  py: ECOSYSTEM_PY,
+};
+
+/**
+ * Resolves a setting for the current ecosystem from a value like "48",
+ * "npm=72,pypi=500" or "48,npm=72". Entries for other or unknown ecosystems
+ * are ignored, as are entries the validator rejects.
+ *
+ * @param {string | number | undefined} value
+ * @param {(value: string | number | undefined) => number | undefined} validate
+ * @returns {number | undefined}
+ */
+function resolveEcosystemValue(value, validate) {
+  if (typeof value !== "string" || !value.includes("=")) {
+    return validate(value);
+  }
+
+  /** @type {number | undefined} */
+  let globalValue;
+  /** @type {number | undefined} */
+  let ecosystemValue;
+  for (const entry of value.split(",")) {
+    const separatorIndex = entry.indexOf("=");
+    if (separatorIndex === -1) {
+      globalValue = validate(entry.trim()) ?? globalValue;
+      continue;
+    }
+
+    const ecosystemName = entry.slice(0, separatorIndex).trim().toLowerCase();
+    if (ecosystemNames[ecosystemName] === getEcoSystem()) {
+      ecosystemValue =
+        validate(entry.slice(separatorIndex + 1).trim()) ?? ecosystemValue;
+    }
+  }
+
+  return ecosystemValue ?? globalValue;
+}
+
 /**
- * @param {string | undefined} value
+ * @param {string | number | undefined} value
  * @returns {number | undefined}
  */
 function validateMinimumPackageAgeHours(value) {
-  if (!value) {
+  if (value === undefined || value === null || value === "") {
     return undefined;
   }
 
@@ -218,16 +310,128 @@ function validateMinimumPackageAgeHours(value) {
 }
 
 const defaultSkipMinimumPackageAge = false;
-export function skipMinimumPackageAge() {
+/**
+ * The minimum age check is also skipped for ecosystems it is not enabled for,
+ * see isMinimumPackageAgeCheckEnabled.
+ *
+ * A per-request override (X-Safe-Chain-Skip-Minimum-Package-Age header) can
+ * only keep the check: a request cannot skip a check that is configured.
+ *
+ * @param {import("../registryProxy/requestPolicyOverrides.js").RequestPolicyOverrides} [requestOverrides]
+ * @returns {boolean}
+ */
+export function skipMinimumPackageAge(requestOverrides) {
+  // Per-request override
+  if (requestOverrides?.skipMinimumPackageAge === false) {
+    return false;
+  }
+
+  if (!isMinimumPackageAgeCheckEnabled()) {
+    return true;
+  }
+
   const cliValue = cliArguments.getSkipMinimumPackageAge();
 
   if (cliValue === true) {
     return true;
   }
 
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getSkipMinimumPackageAge()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
   return defaultSkipMinimumPackageAge;
 }
 
+/**
+ * Gets the minimum hours since the first version of a package was published,
+ * with priority: CLI argument > environment variable > config file. Returns
+ * undefined when brand-new packages are not blocked.
+ * @returns {number | undefined}
+ */
+export function getMinPackageFirstPublishAgeHours() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(
+    cliArguments.getMinPackageFirstPublishAgeHours()
+  );
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getMinPackageFirstPublishAgeHours()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return validatePositiveNumber(
+    configFile.getMinPackageFirstPublishAgeHours()
+  );
+}
+
+/**
+ * Whether malware is blocked for the current ecosystem. The ecosystems come
+ * from CLI argument > environment variable > config file, as a list such as
+ * "npm,pypi", and default to all of them.
+ *
+ * @returns {boolean}
+ */
+export function isMalwareCheckEnabled() {
+  return isEnabledForEcosystem(
+    cliArguments.getMalwareCheckEcosystems(),
+    environmentVariables.getMalwareCheckEcosystems(),
+    configFile.getMalwareCheckEcosystems()
+  );
+}
+
+/**
+ * Whether the minimum package age applies to the current ecosystem. The
+ * ecosystems come from CLI argument > environment variable > config file, as a
+ * list such as "pypi", and default to all of them.
+ *
+ * @returns {boolean}
+ */
+export function isMinimumPackageAgeCheckEnabled() {
+  return isEnabledForEcosystem(
+    cliArguments.getMinimumPackageAgeEcosystems(),
+    environmentVariables.getMinimumPackageAgeEcosystems(),
+    configFile.getMinimumPackageAgeEcosystems()
+  );
+}
+
+/**
+ * The first source that is set wins, so a higher priority list replaces a
+ * lower one instead of being merged with it. Names of other or unknown
+ * ecosystems are ignored, so "none" turns the rule off everywhere.
+ *
+ * @param {string | undefined} cliValue
+ * @param {string | undefined} envValue
+ * @param {string[] | undefined} configValue
+ * @returns {boolean}
+ */
+function isEnabledForEcosystem(cliValue, envValue, configValue) {
+  let ecosystems = configValue;
+  if (cliValue) {
+    ecosystems = parseExclusionsFromEnv(cliValue);
+  } else if (envValue) {
+    ecosystems = parseExclusionsFromEnv(envValue);
+  }
+
+  if (ecosystems === undefined) {
+    return true;
+  }
+
+  return ecosystems.some(
+    (name) => ecosystemNames[name.trim().toLowerCase()] === getEcoSystem()
+  );
+}
+
 /**
  * Normalizes a registry URL by removing protocol if present
  * @param {string} registry
@@ -256,7 +460,35 @@ function parseRegistriesFromEnv(envValue) {
 }
 
 /**
- * Gets the custom npm registries from both environment variable and config file (merged)
+ * Gets the path of an .npmrc file whose registries (default, scoped and
+ * registries with auth settings) are treated as npm registries.
+ * Priority: CLI argument > environment variable > config file > undefined
+ * @returns {string | undefined}
+ */
+export function getNpmrc() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getNpmrc();
+  if (cliValue) {
+    return expandTilde(cliValue);
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getNpmrc();
+  if (envValue) {
+    return expandTilde(envValue);
+  }
+
+  // Priority 3: Config file
+  const configValue = configFile.getNpmrc();
+  if (configValue) {
+    return expandTilde(configValue);
+  }
+
+  return undefined;
+}
+
+/**
+ * Gets the custom npm registries from the environment variable, config file and npmrc file (merged)
  * @returns {string[]}
  */
 export function getNpmCustomRegistries() {
@@ -264,9 +496,15 @@ export function getNpmCustomRegistries() {
     environmentVariables.getNpmCustomRegistries()
   );
   const configRegistries = configFile.getNpmCustomRegistries();
+  const npmrcPath = getNpmrc();
+  const npmrcRegistries = npmrcPath ? readNpmrc(npmrcPath).registries : [];
 
-  // Merge both sources and remove duplicates
-  const allRegistries = [...envRegistries, ...configRegistries];
+  // Merge all sources and remove duplicates
+  const allRegistries = [
+    ...envRegistries,
+    ...configRegistries,
+    ...npmrcRegistries,
+  ];
   const uniqueRegistries = [...new Set(allRegistries)];
 
   // Normalize each registry (remove protocol if any)
@@ -327,10 +565,17 @@ export function getMinimumPackageAgeExclusions() {
  * @returns {string}
  */
 export function getMalwareListBaseUrl() {
+  // A local agent replaces the malware list base URL
+  const agentUrl = getPolicySource();
+  if (agentUrl) {
+    ui.writeVerbose(`Fetching malware lists from the Aikido agent at ${agentUrl}`);
+    return agentUrl;
+  }
+
   // Priority 1: CLI argument
   const cliValue = cliArguments.getMalwareListBaseUrl();
   if (cliValue) {
-    const url = removeTrailingSlashes(cliValue);
+    const url = resolveObjectStorageUrl(removeTrailingSlashes(cliValue));
     ui.writeVerbose(`Fetching malware lists from ${url} as defined by CLI argument --safe-chain-malware-list-base-url`);
     return url;
   }
@@ -338,7 +583,7 @@ export function getMalwareListBaseUrl() {
   // Priority 2: Environment variable
   const envValue = environmentVariables.getMalwareListBaseUrl();
   if (envValue) {
-    const url = removeTrailingSlashes(envValue);
+    const url = resolveObjectStorageUrl(removeTrailingSlashes(envValue));
     ui.writeVerbose(`Fetching malware lists from ${url} as defined by environment variable SAFE_CHAIN_MALWARE_LIST_BASE_URL`);
     return url;
   }
@@ -346,7 +591,7 @@ export function getMalwareListBaseUrl() {
   // Priority 3: Config file
   const configValue = configFile.getMalwareListBaseUrl();
   if (configValue) {
-    const url = removeTrailingSlashes(configValue);
+    const url = resolveObjectStorageUrl(removeTrailingSlashes(configValue));
     ui.writeVerbose(`Fetching malware lists from ${url} as defined by config file (malwareListBaseUrl)`);
     return url;
   }
@@ -355,6 +600,90 @@ export function getMalwareListBaseUrl() {
   return removeTrailingSlashes("https://malware-list.aikido.dev");
 }
 
+/** @type {Set<string>} */
+const invalidPolicySourceWarnings = new Set();
+
+/**
+ * Gets the base URL of the local Aikido agent the malware lists are loaded
+ * from, with priority: CLI argument > environment variable > config file >
+ * none. Invalid values are ignored with a warning so the next source applies.
+ * @returns {string | undefined}
+ */
+export function getPolicySource() {
+  const values = [
+    cliArguments.getPolicySource(),
+    environmentVariables.getPolicySource(),
+    configFile.getPolicySource(),
+  ];
+
+  for (const value of values) {
+    if (!value) {
+      continue;
+    }
+    const url = resolveAgentUrl(value.trim());
+    if (url) {
+      return url;
+    }
+    if (!invalidPolicySourceWarnings.has(value)) {
+      invalidPolicySourceWarnings.add(value);
+      ui.writeWarning(
+        `Safe-chain: Ignoring invalid policy source "${value}", expected agent://<host>:<port>.`
+      );
+    }
+  }
+  return undefined;
+}
+
+/**
+ * Rewrites an agent://<host>:<port> policy source to the HTTP endpoint of the
+ * agent, which serves the malware lists in the layout of the malware list base
+ * URL. Returns undefined for other values.
+ * @param {string} value
+ * @returns {string | undefined}
+ */
+export function resolveAgentUrl(value) {
+  const match = /^agent:\/\/([^/?#]+)(\/[^?#]*)?$/i.exec(value);
+  if (!match) {
+    return undefined;
+  }
+  return removeTrailingSlashes(`http://${match[1]}${match[2] ?? ""}`);
+}
+
+/**
+ * Rewrites a gs:// or s3:// bucket URL to the HTTPS endpoint of the bucket, for
+ * malware lists mirrored into object storage. Other URLs are returned as is.
+ * @param {string} value
+ * @returns {string}
+ */
+export function resolveObjectStorageUrl(value) {
+  const match = /^(gs|s3):\/\/([^/]+)(\/.*)?$/i.exec(value);
+  if (!match) {
+    return value;
+  }
+
+  const [, scheme, bucket, path = ""] = match;
+  return scheme.toLowerCase() === "gs"
+    ? `https://storage.googleapis.com/${bucket}${path}`
+    : `https://${bucket}.s3.amazonaws.com${path}`;
+}
+
+/**
+ * Gets the bearer token sent with requests to the malware list base URL, with
+ * priority: environment variable > config file. It is not accepted as a CLI
+ * argument, so it does not show up in process listings.
+ * @returns {string | undefined}
+ */
+export function getMalwareListToken() {
+  // Priority 1: Environment variable
+  const envValue = environmentVariables.getMalwareListToken()?.trim();
+  if (envValue) {
+    return envValue;
+  }
+
+  // Priority 2: Config file
+  return configFile.getMalwareListToken()?.trim() || undefined;
+}
+
 /**
  * Removes trailing slashes from a URL-like string.
  * @param {string} value
@@ -367,3 +696,1873 @@ function removeTrailingSlashes(value) {
 
   return value.replace(/\/+$/, "");
 }
+
+/**
+ * Gets the DNS-over-HTTPS resolver URL with priority: CLI argument > environment variable > config file > undefined.
+ * When undefined, upstream hostnames are resolved with the system resolver.
+ * @returns {string | undefined}
+ */
+export function getDohResolver() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getDohResolver();
+  if (cliValue) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getDohResolver();
+  if (envValue) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getDohResolver();
+}
+
+/**
+ * Parses comma-separated hosts from environment variable
+ * @param {string | undefined} envValue
+ * @returns {string[]}
+ */
+function parseHostsFromEnv(envValue) {
+  if (!envValue || typeof envValue !== "string") {
+    return [];
+  }
+
+  return envValue
+    .split(",")
+    .map((host) => host.trim())
+    .filter((host) => host.length > 0);
+}
+
+/**
+ * Gets the hosts the proxy refuses to connect to, merged from CLI arguments, environment variable and config file.
+ * Entries are either exact hostnames or wildcard patterns such as "*.evil.example".
+ * @returns {string[]}
+ */
+export function getDenyHosts() {
+  const cliHosts = cliArguments.getDenyHosts();
+  const envHosts = parseHostsFromEnv(environmentVariables.getDenyHosts());
+  const configHosts = configFile.getDenyHosts();
+
+  // Merge all sources and remove duplicates
+  const allHosts = [...cliHosts, ...envHosts, ...configHosts]
+    .map((host) => host.trim().toLowerCase())
+    .filter((host) => host.length > 0);
+  return [...new Set(allHosts)];
+}
+
+/**
+ * Gets the response cache directory with priority: CLI argument > environment variable > config file > undefined.
+ * The response cache is disabled when no directory is configured.
+ * @returns {string | undefined}
+ */
+export function getCacheDir() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getCacheDir();
+  if (cliValue) {
+    return expandTilde(cliValue);
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getCacheDir();
+  if (envValue) {
+    return expandTilde(envValue);
+  }
+
+  // Priority 3: Config file
+  const configValue = configFile.getCacheDir();
+  if (configValue) {
+    return expandTilde(configValue);
+  }
+
+  return undefined;
+}
+
+const defaultCacheMaxSizeGb = 1;
+/**
+ * Gets the maximum response cache size in gigabytes with priority: CLI argument > environment variable > config file > 1
+ * @returns {number}
+ */
+export function getCacheMaxSizeGb() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(cliArguments.getCacheMaxSizeGb());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getCacheMaxSizeGb()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(configFile.getCacheMaxSizeGb());
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultCacheMaxSizeGb;
+}
+
+/**
+ * @param {string | number | undefined} value
+ * @returns {number | undefined}
+ */
+function validatePositiveNumber(value) {
+  if (value === undefined || value === "") {
+    return undefined;
+  }
+
+  const numericValue = Number(value);
+  if (Number.isNaN(numericValue) || numericValue <= 0) {
+    return undefined;
+  }
+
+  return numericValue;
+}
+
+/**
+ * Parses a boolean environment variable value ("true"/"1" or "false"/"0")
+ * @param {string | undefined} value
+ * @returns {boolean | undefined}
+ */
+function parseBooleanFromEnv(value) {
+  if (!value) {
+    return undefined;
+  }
+
+  const normalizedValue = value.trim().toLowerCase();
+  if (normalizedValue === "true" || normalizedValue === "1") {
+    return true;
+  }
+  if (normalizedValue === "false" || normalizedValue === "0") {
+    return false;
+  }
+
+  return undefined;
+}
+
+/**
+ * Gets whether safe-chain should check for newer versions at startup with priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getCheckUpdates() {
+  // Priority 1: CLI argument
+  if (cliArguments.getCheckUpdates() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getCheckUpdates());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getCheckUpdates() ?? false;
+}
+
+/**
+ * Gets whether pre-release package versions are blocked with priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getBlockPrereleases() {
+  // Priority 1: CLI argument
+  if (cliArguments.getBlockPrereleases() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getBlockPrereleases()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getBlockPrereleases() ?? false;
+}
+
+/**
+ * Gets the pre-releases that are allowed while pre-releases are blocked, from CLI arguments, environment variable and config file (merged)
+ * @returns {string[]}
+ */
+export function getPrereleaseAllowlist() {
+  const cliEntries = cliArguments.getPrereleaseAllowlist();
+  const envEntries = parseExclusionsFromEnv(
+    environmentVariables.getPrereleaseAllowlist()
+  );
+  const configEntries = configFile.getPrereleaseAllowlist();
+
+  // Merge all sources and remove duplicates
+  return [...new Set([...cliEntries, ...envEntries, ...configEntries])];
+}
+
+/**
+ * Gets the port the proxy listens on with priority: CLI argument > environment variable > config file.
+ * Returns undefined when no valid port is configured, the OS then assigns a free port.
+ * @returns {number | undefined}
+ */
+export function getProxyPort() {
+  // Priority 1: CLI argument
+  const cliValue = validatePort(cliArguments.getProxyPort());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePort(environmentVariables.getProxyPort());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return validatePort(configFile.getProxyPort());
+}
+
+/**
+ * @param {string | number | undefined} value
+ * @returns {number | undefined}
+ */
+function validatePort(value) {
+  if (value === undefined || value === "") {
+    return undefined;
+  }
+
+  const port = Number(value);
+  if (!Number.isInteger(port) || port < 1 || port > 65535) {
+    return undefined;
+  }
+
+  return port;
+}
+
+/**
+ * Gets whether the proxy retries and then falls back to an OS-assigned port when the configured port is in use,
+ * with priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getPortRetry() {
+  // Priority 1: CLI argument
+  if (cliArguments.getPortRetry() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getPortRetry());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getPortRetry() ?? false;
+}
+
+/**
+ * Gets the accept backlog of the proxy listener with priority: CLI argument > environment variable > config file.
+ * Returns undefined to keep Node's default backlog (511).
+ * @returns {number | undefined}
+ */
+export function getTcpBacklog() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveInteger(cliArguments.getTcpBacklog());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveInteger(environmentVariables.getTcpBacklog());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return validatePositiveInteger(configFile.getTcpBacklog());
+}
+
+/**
+ * @param {string | number | undefined} value
+ * @returns {number | undefined}
+ */
+function validatePositiveInteger(value) {
+  const number = validatePositiveNumber(value);
+  if (number === undefined || !Number.isInteger(number)) {
+    return undefined;
+  }
+
+  return number;
+}
+
+/**
+ * Gets whether Nagle's algorithm is disabled on accepted proxy connections with priority:
+ * CLI argument > environment variable > config file > true (Node's default)
+ * @returns {boolean}
+ */
+export function getTcpNoDelay() {
+  // Priority 1: CLI argument
+  const cliValue = parseBooleanFromEnv(cliArguments.getTcpNoDelay());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getTcpNoDelay());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getTcpNoDelay() ?? true;
+}
+
+/**
+ * Gets whether intercepted TLS connections must send an SNI that matches the CONNECT host,
+ * with priority: CLI argument > environment variable > config file > true
+ * @returns {boolean}
+ */
+export function getStrictSni() {
+  // Priority 1: CLI argument
+  const cliValue = parseBooleanFromEnv(cliArguments.getStrictSni());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getStrictSni());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getStrictSni() ?? true;
+}
+
+/**
+ * Gets the request header names removed before forwarding to the registry,
+ * merged from CLI arguments, environment variable and config file.
+ * @returns {string[]}
+ */
+export function getStripRequestHeaders() {
+  return mergeHeaderNames(
+    cliArguments.getStripRequestHeaders(),
+    parseExclusionsFromEnv(environmentVariables.getStripRequestHeaders()),
+    configFile.getStripRequestHeaders()
+  );
+}
+
+/**
+ * Gets the response header names removed before returning to the client,
+ * merged from CLI arguments, environment variable and config file.
+ * @returns {string[]}
+ */
+export function getStripResponseHeaders() {
+  return mergeHeaderNames(
+    cliArguments.getStripResponseHeaders(),
+    parseExclusionsFromEnv(environmentVariables.getStripResponseHeaders()),
+    configFile.getStripResponseHeaders()
+  );
+}
+
+/**
+ * @param {...string[]} sources
+ * @returns {string[]}
+ */
+function mergeHeaderNames(...sources) {
+  const headerNames = sources
+    .flat()
+    .map((name) => name.trim().toLowerCase())
+    .filter((name) => name.length > 0);
+  return [...new Set(headerNames)];
+}
+
+/**
+ * Gets whether a Via header is added to proxied requests and responses with priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getAddVia() {
+  // Priority 1: CLI argument
+  if (cliArguments.getAddVia() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getAddVia());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getAddVia() ?? false;
+}
+
+const defaultMaxDecompressedMb = 512;
+/**
+ * Gets the maximum size in megabytes a compressed registry response may decompress to while
+ * safe-chain inspects it, with priority: CLI argument > environment variable > config file > 512
+ * @returns {number}
+ */
+export function getMaxDecompressedMb() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(cliArguments.getMaxDecompressedMb());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getMaxDecompressedMb()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(
+    configFile.getMaxDecompressedMb()
+  );
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultMaxDecompressedMb;
+}
+
+/**
+ * Gets whether small request and response bodies are written to the verbose log,
+ * with priority: CLI argument > environment variable > false.
+ * This is a debugging aid, so it deliberately cannot be enabled from the config file.
+ * @returns {boolean}
+ */
+export function getTraceBody() {
+  // Priority 1: CLI argument
+  if (cliArguments.getTraceBody() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  return parseBooleanFromEnv(environmentVariables.getTraceBody()) ?? false;
+}
+
+/**
+ * Gets whether safe-chain fails when the local clock is skewed, with priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getFailOnClockSkew() {
+  // Priority 1: CLI argument
+  if (cliArguments.getFailOnClockSkew() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getFailOnClockSkew()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getFailOnClockSkew() ?? false;
+}
+
+const defaultCircuitBreakerThreshold = 5;
+/**
+ * Gets the number of consecutive upstream failures after which requests to a host
+ * are short-circuited, with priority: CLI argument > environment variable > config file > 5
+ * @returns {number}
+ */
+export function getCircuitBreakerThreshold() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveInteger(cliArguments.getCircuitBreakerThreshold());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveInteger(
+    environmentVariables.getCircuitBreakerThreshold()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveInteger(configFile.getCircuitBreakerThreshold());
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultCircuitBreakerThreshold;
+}
+
+const defaultCircuitBreakerCooldownSeconds = 30;
+/**
+ * Gets how long requests to a failing host are short-circuited before it is probed again,
+ * with priority: CLI argument > environment variable > config file > 30 seconds
+ * @returns {number}
+ */
+export function getCircuitBreakerCooldownSeconds() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(
+    cliArguments.getCircuitBreakerCooldownSeconds()
+  );
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getCircuitBreakerCooldownSeconds()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(
+    configFile.getCircuitBreakerCooldownSeconds()
+  );
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultCircuitBreakerCooldownSeconds;
+}
+
+/**
+ * Gets the maximum size in bytes of the request line and headers the proxy accepts,
+ * with priority: CLI argument > environment variable > config file.
+ * Returns undefined to keep Node's default (16 KB).
+ * @returns {number | undefined}
+ */
+export function getMaxHeaderBytes() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveInteger(cliArguments.getMaxHeaderBytes());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveInteger(
+    environmentVariables.getMaxHeaderBytes()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return validatePositiveInteger(configFile.getMaxHeaderBytes());
+}
+
+/**
+ * Gets whether the break-glass switch that disables all blocking is on, with priority:
+ * CLI argument > environment variable > false.
+ * It is meant for emergencies only, so it deliberately cannot be enabled from the config file.
+ * @returns {boolean}
+ */
+export function getBlockingDisabled() {
+  // Priority 1: CLI argument
+  if (cliArguments.getNoBlock() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  return parseBooleanFromEnv(environmentVariables.getDisableBlocking()) ?? false;
+}
+
+/**
+ * Gets the path of the file the bound proxy port is written to, with priority:
+ * CLI argument > environment variable > config file > undefined
+ * @returns {string | undefined}
+ */
+export function getPortFile() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getPortFile();
+  if (cliValue) {
+    return expandTilde(cliValue);
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getPortFile();
+  if (envValue) {
+    return expandTilde(envValue);
+  }
+
+  // Priority 3: Config file
+  const configValue = configFile.getPortFile();
+  if (configValue) {
+    return expandTilde(configValue);
+  }
+
+  return undefined;
+}
+
+export const TLS_VERSION_1_2 = "TLSv1.2";
+export const TLS_VERSION_1_3 = "TLSv1.3";
+
+/**
+ * Gets the minimum TLS version for connections to upstream registries, with priority:
+ * CLI argument > environment variable > config file > TLSv1.2.
+ * Accepts `1.2`, `1.3`, `TLSv1.2` and `TLSv1.3`; older versions cannot be configured.
+ * @returns {import("tls").SecureVersion}
+ */
+export function getMinTlsVersion() {
+  // Priority 1: CLI argument
+  const cliValue = validateTlsVersion(cliArguments.getMinTlsVersion());
+  if (cliValue) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validateTlsVersion(environmentVariables.getMinTlsVersion());
+  if (envValue) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return validateTlsVersion(configFile.getMinTlsVersion()) ?? TLS_VERSION_1_2;
+}
+
+/**
+ * @param {string | undefined} value
+ * @returns {import("tls").SecureVersion | undefined}
+ */
+function validateTlsVersion(value) {
+  const version = value?.trim().replace(/^tlsv?/i, "");
+  if (version === "1.2") {
+    return TLS_VERSION_1_2;
+  }
+  if (version === "1.3") {
+    return TLS_VERSION_1_3;
+  }
+  return undefined;
+}
+
+/**
+ * Gets the base URLs of additional malware feeds, merged from config file,
+ * environment variable and CLI arguments. The list is ordered by ascending
+ * precedence: on conflicting entries, feeds later in the list win, so CLI
+ * arguments override the environment variable, which overrides the config file.
+ * @returns {string[]}
+ */
+export function getMalwareFeedUrls() {
+  const urls = [
+    ...configFile.getMalwareFeedUrls(),
+    ...parseExclusionsFromEnv(environmentVariables.getMalwareFeedUrls()),
+    ...cliArguments.getMalwareFeedUrls(),
+  ]
+    .map((url) => resolveObjectStorageUrl(removeTrailingSlashes(url.trim())))
+    .filter((url) => url.length > 0);
+
+  // A feed that is listed twice keeps its highest precedence
+  return urls.filter((url, index) => urls.lastIndexOf(url) === index);
+}
+
+/**
+ * Gets whether the User-Agent of a CONNECT request is used as a hint for the
+ * ecosystem of a host that is not a known or configured registry, with priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getUaEcosystemHints() {
+  // Priority 1: CLI argument
+  if (cliArguments.getUaEcosystemHints() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getUaEcosystemHints()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getUaEcosystemHints() ?? false;
+}
+
+/**
+ * Gets the registry hosts that are tunneled without TLS interception, merged from
+ * CLI arguments, environment variable and config file. Entries are either exact
+ * hostnames or wildcard patterns such as "*.pinned.example".
+ * @returns {string[]}
+ */
+export function getNoInterceptHosts() {
+  const cliHosts = cliArguments.getNoInterceptHosts();
+  const envHosts = parseHostsFromEnv(environmentVariables.getNoInterceptHosts());
+  const configHosts = configFile.getNoInterceptHosts();
+
+  // Merge all sources and remove duplicates
+  const allHosts = [...cliHosts, ...envHosts, ...configHosts]
+    .map((host) => host.trim().toLowerCase())
+    .filter((host) => host.length > 0);
+  return [...new Set(allHosts)];
+}
+
+/**
+ * Gets whether a host whose interception handshake failed stays intercepted, so its
+ * connections keep failing, instead of falling back to tunneling, with priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getStrictInterception() {
+  // Priority 1: CLI argument
+  if (cliArguments.getStrictInterception() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getStrictInterception()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getStrictInterception() ?? false;
+}
+
+/**
+ * Gets whether the proxy records metrics and serves them on /metrics, with priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getMetricsEnabled() {
+  // Priority 1: CLI argument
+  if (cliArguments.getMetrics() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getMetrics());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getMetrics() ?? false;
+}
+
+const defaultBlockStatus = 403;
+/**
+ * Gets the HTTP status code of responses to blocked package requests, with priority:
+ * CLI argument > environment variable > config file > 403.
+ * Each source accepts per-ecosystem values such as "403,npm=451"; the value for the
+ * current ecosystem wins over the global value of the same source. Only 4xx and 5xx
+ * codes are valid, invalid values are ignored so the next source applies.
+ * @returns {number}
+ */
+export function getBlockStatus() {
+  // Priority 1: CLI argument
+  const cliValue = resolveEcosystemValue(
+    cliArguments.getBlockStatus(),
+    validateBlockStatus
+  );
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = resolveEcosystemValue(
+    environmentVariables.getBlockStatus(),
+    validateBlockStatus
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return (
+    resolveEcosystemValue(configFile.getBlockStatus(), validateBlockStatus) ??
+    defaultBlockStatus
+  );
+}
+
+/**
+ * @param {string | number | undefined} value
+ * @returns {number | undefined}
+ */
+function validateBlockStatus(value) {
+  if (value === undefined || value === "") {
+    return undefined;
+  }
+
+  const statusCode = Number(value);
+  if (Number.isInteger(statusCode) && statusCode >= 400 && statusCode <= 599) {
+    return statusCode;
+  }
+
+  return undefined;
+}
+
+/**
+ * Gets whether safe-chain runs in inspect-only mode: every request is still
+ * inspected and every decision recorded, but nothing is blocked. Priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getInspectOnly() {
+  // Priority 1: CLI argument
+  if (cliArguments.getInspectOnly() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getInspectOnly());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getInspectOnly() ?? false;
+}
+
+/**
+ * Gets the HTTP methods the proxy forwards, from the first source that sets
+ * them: CLI argument > environment variable > config file. Undefined when none
+ * does, in which case every method that is not denied is forwarded.
+ * @returns {string[] | undefined}
+ */
+export function getAllowMethods() {
+  return getMethodList(
+    cliArguments.getAllowMethods(),
+    environmentVariables.getAllowMethods(),
+    configFile.getAllowMethods()
+  );
+}
+
+/**
+ * Gets the HTTP methods the proxy refuses to forward, from the first source
+ * that sets them: CLI argument > environment variable > config file > none
+ * @returns {string[]}
+ */
+export function getDenyMethods() {
+  return (
+    getMethodList(
+      cliArguments.getDenyMethods(),
+      environmentVariables.getDenyMethods(),
+      configFile.getDenyMethods()
+    ) ?? []
+  );
+}
+
+/**
+ * @param {string | undefined} cliValue - Comma separated
+ * @param {string | undefined} envValue - Comma separated
+ * @param {string[] | undefined} configValue
+ * @returns {string[] | undefined}
+ */
+function getMethodList(cliValue, envValue, configValue) {
+  // Priority 1: CLI argument, priority 2: environment variable
+  const commaSeparated = cliValue ?? envValue;
+  // Priority 3: Config file
+  const methods =
+    commaSeparated !== undefined
+      ? parseExclusionsFromEnv(commaSeparated)
+      : configValue;
+
+  return methods
+    ?.map((method) => method.trim().toUpperCase())
+    .filter((method) => method.length > 0);
+}
+
+/**
+ * Gets whether the proxy compresses the responses it rewrites or serves from
+ * its cache for clients that accept it. Priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getCompressResponses() {
+  // Priority 1: CLI argument
+  if (cliArguments.getCompressResponses() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getCompressResponses()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getCompressResponses() ?? false;
+}
+
+/**
+ * Gets whether safe-chain warns about npm packages with preinstall, install or
+ * postinstall scripts. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getWarnInstallScripts() {
+  // Priority 1: CLI argument
+  if (cliArguments.getWarnInstallScripts() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getWarnInstallScripts()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getWarnInstallScripts() ?? false;
+}
+
+/**
+ * Gets whether safe-chain blocks downloads of npm packages with preinstall,
+ * install or postinstall scripts. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getBlockInstallScripts() {
+  // Priority 1: CLI argument
+  if (cliArguments.getBlockInstallScripts() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getBlockInstallScripts()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getBlockInstallScripts() ?? false;
+}
+
+/**
+ * Gets the npm packages whose install scripts are allowed, from CLI arguments, environment variable and config file (merged)
+ * @returns {string[]}
+ */
+export function getInstallScriptAllowlist() {
+  const cliEntries = cliArguments.getInstallScriptAllowlist();
+  const envEntries = parseExclusionsFromEnv(
+    environmentVariables.getInstallScriptAllowlist()
+  );
+  const configEntries = configFile.getInstallScriptAllowlist();
+
+  // Merge all sources and remove duplicates
+  return [...new Set([...cliEntries, ...envEntries, ...configEntries])];
+}
+
+/**
+ * Gets whether the proxy writes a JSON ready line to stderr once it accepts
+ * connections. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getReadyBanner() {
+  // Priority 1: CLI argument
+  if (cliArguments.getReadyBanner() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getReadyBanner());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getReadyBanner() ?? false;
+}
+
+/**
+ * Gets the regular expressions for registry request paths that are forwarded
+ * without policy evaluation, from CLI arguments, environment variable and config file (merged)
+ * @returns {string[]}
+ */
+export function getExcludePaths() {
+  const cliEntries = cliArguments.getExcludePaths();
+  const envEntries = parseExclusionsFromEnv(
+    environmentVariables.getExcludePaths()
+  );
+  const configEntries = configFile.getExcludePaths();
+
+  // Merge all sources and remove duplicates
+  return [...new Set([...cliEntries, ...envEntries, ...configEntries])];
+}
+
+const defaultFeedEntryMinAgeMins = 0;
+/**
+ * Gets how old a malware feed entry must be before it is enforced; younger
+ * entries are only reported. With priority: CLI argument > environment
+ * variable > config file > 0 (every entry is enforced right away)
+ * @returns {number}
+ */
+export function getFeedEntryMinAgeMins() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(
+    cliArguments.getFeedEntryMinAgeMins()
+  );
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getFeedEntryMinAgeMins()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(
+    configFile.getFeedEntryMinAgeMins()
+  );
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultFeedEntryMinAgeMins;
+}
+
+/**
+ * Gets whether safe-chain warns about npm package versions that are much
+ * larger than the other versions of the package. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getFlagSizeAnomaly() {
+  // Priority 1: CLI argument
+  if (cliArguments.getFlagSizeAnomaly() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getFlagSizeAnomaly()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getFlagSizeAnomaly() ?? false;
+}
+
+/**
+ * Gets whether safe-chain blocks downloads of npm package versions that are
+ * much larger than the other versions of the package. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getBlockSizeAnomaly() {
+  // Priority 1: CLI argument
+  if (cliArguments.getBlockSizeAnomaly() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getBlockSizeAnomaly()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getBlockSizeAnomaly() ?? false;
+}
+
+const defaultSizeAnomalyFactor = 10;
+/**
+ * Gets how many times larger than the usual size of a package a version must
+ * be to count as a size anomaly, with priority: CLI argument > environment variable > config file > 10
+ * @returns {number}
+ */
+export function getSizeAnomalyFactor() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(cliArguments.getSizeAnomalyFactor());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getSizeAnomalyFactor()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(configFile.getSizeAnomalyFactor());
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultSizeAnomalyFactor;
+}
+
+/**
+ * Gets whether requests to hosts that are not a known or configured registry
+ * are blocked instead of forwarded without inspection. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getStrictUnknownHosts() {
+  // Priority 1: CLI argument
+  if (cliArguments.getStrictUnknownHosts() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getStrictUnknownHosts()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getStrictUnknownHosts() ?? false;
+}
+
+/**
+ * Gets the hosts that may be reached in strict unknown hosts mode although they
+ * are not registries, merged from CLI arguments, environment variable and config
+ * file. Entries are either exact hostnames or wildcard patterns such as "*.example.com".
+ * @returns {string[]}
+ */
+export function getAllowedUnknownHosts() {
+  const cliHosts = cliArguments.getAllowedUnknownHosts();
+  const envHosts = parseHostsFromEnv(
+    environmentVariables.getAllowedUnknownHosts()
+  );
+  const configHosts = configFile.getAllowedUnknownHosts();
+
+  // Merge all sources and remove duplicates
+  const allHosts = [...cliHosts, ...envHosts, ...configHosts]
+    .map((host) => host.trim().toLowerCase())
+    .filter((host) => host.length > 0);
+  return [...new Set(allHosts)];
+}
+
+const defaultFeedMaxStalenessMins = 7 * 24 * 60;
+/**
+ * Gets the minutes since the last successful malware list refresh after which
+ * the list is reported as stale. Priority: CLI argument > environment variable > config file > 10080 (7 days)
+ * @returns {number}
+ */
+export function getFeedMaxStalenessMins() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(
+    cliArguments.getFeedMaxStalenessMins()
+  );
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getFeedMaxStalenessMins()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(
+    configFile.getFeedMaxStalenessMins()
+  );
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultFeedMaxStalenessMins;
+}
+
+/**
+ * Gets whether safe-chain fails when the malware list is stale, like it does
+ * when no malware list is available. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getFailOnStaleFeed() {
+  // Priority 1: CLI argument
+  if (cliArguments.getFailOnStaleFeed() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getFailOnStaleFeed()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getFailOnStaleFeed() ?? false;
+}
+
+/**
+ * Gets whether the proxy checks its policy against a known malicious and a
+ * known good request when it starts. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getSelfTest() {
+  // Priority 1: CLI argument
+  if (cliArguments.getSelfTest() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getSelfTest());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getSelfTest() ?? false;
+}
+
+/**
+ * Gets the path of the Unix socket, named pipe or FIFO the proxy streams
+ * decision events to, with priority: CLI argument > environment variable > config file > undefined
+ * @returns {string | undefined}
+ */
+export function getEventSocket() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getEventSocket();
+  if (cliValue) {
+    return expandTilde(cliValue);
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getEventSocket();
+  if (envValue) {
+    return expandTilde(envValue);
+  }
+
+  // Priority 3: Config file
+  const configValue = configFile.getEventSocket();
+  if (configValue) {
+    return expandTilde(configValue);
+  }
+
+  return undefined;
+}
+
+const defaultTunnelConnectTimeoutSecs = 30;
+/**
+ * Gets the seconds to wait for the connection to the upstream of a CONNECT
+ * tunnel, with priority: CLI argument > environment variable > config file > 30
+ * @returns {number}
+ */
+export function getTunnelConnectTimeoutSecs() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(
+    cliArguments.getTunnelConnectTimeoutSecs()
+  );
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getTunnelConnectTimeoutSecs()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(
+    configFile.getTunnelConnectTimeoutSecs()
+  );
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultTunnelConnectTimeoutSecs;
+}
+
+const defaultMaxRetryAfterSecs = 30;
+/**
+ * Gets the longest Retry-After delay that a rate-limited feed download waits
+ * for before it is retried, with priority: CLI argument > environment variable
+ * > config file > 30
+ * @returns {number}
+ */
+export function getMaxRetryAfterSecs() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(cliArguments.getMaxRetryAfterSecs());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getMaxRetryAfterSecs()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(configFile.getMaxRetryAfterSecs());
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultMaxRetryAfterSecs;
+}
+
+const defaultDnsCacheTtlSecs = 30;
+/**
+ * Gets the seconds to cache a hostname resolved by the system resolver, which
+ * does not report TTLs, with priority: CLI argument > environment variable >
+ * config file > 30. 0 turns the cache off for the system resolver.
+ * @returns {number}
+ */
+export function getDnsCacheTtlSecs() {
+  return getNonNegativeSeconds(
+    cliArguments.getDnsCacheTtlSecs(),
+    environmentVariables.getDnsCacheTtlSecs(),
+    configFile.getDnsCacheTtlSecs(),
+    defaultDnsCacheTtlSecs
+  );
+}
+
+/**
+ * Gets the minimum seconds a resolved hostname is cached, even when its records
+ * have a shorter TTL, with priority: CLI argument > environment variable >
+ * config file > 0
+ * @returns {number}
+ */
+export function getDnsCacheMinTtlSecs() {
+  return getNonNegativeSeconds(
+    cliArguments.getDnsCacheMinTtlSecs(),
+    environmentVariables.getDnsCacheMinTtlSecs(),
+    configFile.getDnsCacheMinTtlSecs(),
+    0
+  );
+}
+
+const defaultDnsCacheMaxTtlSecs = 300;
+/**
+ * Gets the maximum seconds a resolved hostname is cached, even when its records
+ * have a longer TTL, with priority: CLI argument > environment variable >
+ * config file > 300
+ * @returns {number}
+ */
+export function getDnsCacheMaxTtlSecs() {
+  return getNonNegativeSeconds(
+    cliArguments.getDnsCacheMaxTtlSecs(),
+    environmentVariables.getDnsCacheMaxTtlSecs(),
+    configFile.getDnsCacheMaxTtlSecs(),
+    defaultDnsCacheMaxTtlSecs
+  );
+}
+
+const defaultDnsNegativeTtlSecs = 5;
+/**
+ * Gets the seconds to cache that a hostname does not exist, with priority: CLI
+ * argument > environment variable > config file > 5. 0 turns negative caching
+ * off.
+ * @returns {number}
+ */
+export function getDnsNegativeTtlSecs() {
+  return getNonNegativeSeconds(
+    cliArguments.getDnsNegativeTtlSecs(),
+    environmentVariables.getDnsNegativeTtlSecs(),
+    configFile.getDnsNegativeTtlSecs(),
+    defaultDnsNegativeTtlSecs
+  );
+}
+
+/**
+ * @param {string | undefined} cliValue
+ * @param {string | undefined} envValue
+ * @param {number | undefined} configValue
+ * @param {number} defaultValue
+ * @returns {number}
+ */
+function getNonNegativeSeconds(cliValue, envValue, configValue, defaultValue) {
+  // Priority 1: CLI argument, priority 2: environment variable,
+  // priority 3: config file. Invalid values are ignored.
+  for (const value of [cliValue, envValue, configValue]) {
+    if (value === undefined || value === "") {
+      continue;
+    }
+    const seconds = Number(value);
+    if (Number.isFinite(seconds) && seconds >= 0) {
+      return seconds;
+    }
+  }
+
+  return defaultValue;
+}
+
+/**
+ * Gets the seconds after which the proxy shuts down, with priority: CLI
+ * argument > environment variable > config file. Returns undefined when no
+ * maximum lifetime is configured.
+ * @returns {number | undefined}
+ */
+export function getMaxLifetimeSecs() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(cliArguments.getMaxLifetimeSecs());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getMaxLifetimeSecs()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return validatePositiveNumber(configFile.getMaxLifetimeSecs());
+}
+
+/**
+ * @typedef {Object} BlockMessageTemplate
+ * @property {string} template - May contain {package}, {version}, {reason} and {message}
+ * @property {"text" | "html"} format
+ */
+
+/**
+ * Gets the templates for the body of block responses by block reason, from
+ * the config file only. A template is either a string for a plain text body
+ * or an object with an "html" or "text" property; the "default" key applies
+ * to reasons without a template of their own. Invalid entries are ignored.
+ * @returns {Record<string, BlockMessageTemplate>}
+ */
+export function getBlockMessageTemplates() {
+  /** @type {Record<string, BlockMessageTemplate>} */
+  const templates = {};
+  for (const [reason, value] of Object.entries(
+    configFile.getBlockMessages() ?? {}
+  )) {
+    const template = parseBlockMessageTemplate(value);
+    if (template) {
+      templates[reason] = template;
+    }
+  }
+  return templates;
+}
+
+/**
+ * @param {unknown} value
+ * @returns {BlockMessageTemplate | undefined}
+ */
+function parseBlockMessageTemplate(value) {
+  if (typeof value === "string") {
+    return value ? { template: value, format: "text" } : undefined;
+  }
+  if (!value || typeof value !== "object") {
+    return undefined;
+  }
+
+  const { html, text } = /** @type {{html?: unknown, text?: unknown}} */ (
+    value
+  );
+  if (typeof html === "string" && html) {
+    return { template: html, format: "html" };
+  }
+  if (typeof text === "string" && text) {
+    return { template: text, format: "text" };
+  }
+  return undefined;
+}
+
+export const VCS_POLICY_ALLOW = "allow";
+export const VCS_POLICY_LOG = "log";
+export const VCS_POLICY_DENY = "deny";
+
+/**
+ * Gets how dependencies fetched from VCS hosts such as github.com are handled:
+ * "allow" tunnels them without inspection, "log" inspects and records them,
+ * "deny" blocks them. Priority: CLI argument > environment variable > config
+ * file > "allow". Invalid values are ignored so the next source applies.
+ * @returns {string}
+ */
+export function getVcsPolicy() {
+  // Priority 1: CLI argument
+  const cliValue = validateVcsPolicy(cliArguments.getVcsPolicy());
+  if (cliValue) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validateVcsPolicy(environmentVariables.getVcsPolicy());
+  if (envValue) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return validateVcsPolicy(configFile.getVcsPolicy()) ?? VCS_POLICY_ALLOW;
+}
+
+/**
+ * @param {string | undefined} value
+ * @returns {string | undefined}
+ */
+function validateVcsPolicy(value) {
+  const policy = value?.trim().toLowerCase();
+  if (
+    policy === VCS_POLICY_ALLOW ||
+    policy === VCS_POLICY_LOG ||
+    policy === VCS_POLICY_DENY
+  ) {
+    return policy;
+  }
+  return undefined;
+}
+
+/**
+ * Gets whether the proxy binds its port with SO_REUSEPORT, so a new instance
+ * can bind the same port while the old one drains, with priority: CLI
+ * argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getReusePort() {
+  // Priority 1: CLI argument
+  if (cliArguments.getReusePort() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getReusePort());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getReusePort() ?? false;
+}
+
+/**
+ * Gets whether safe-chain runs offline: the malware list and the new packages
+ * list are read from the local cache only, and no other feed is downloaded.
+ * Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getOffline() {
+  // The test fixture replaces the feeds, so nothing is downloaded
+  if (getUseTestFixture()) {
+    return true;
+  }
+
+  // Priority 1: CLI argument
+  if (cliArguments.getOffline() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getOffline());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getOffline() ?? false;
+}
+
+/**
+ * Gets whether safe-chain skips every request that is not needed for
+ * protection, such as the update check, even when it is enabled. Priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getNoTelemetry() {
+  // Priority 1: CLI argument
+  if (cliArguments.getNoTelemetry() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(environmentVariables.getNoTelemetry());
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getNoTelemetry() ?? false;
+}
+
+/**
+ * Gets whether safe-chain resolves the declared dependencies of a downloaded
+ * npm package and blocks it when one of them resolves to malware. Priority:
+ * CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getCheckDependencyClosure() {
+  // Priority 1: CLI argument
+  if (cliArguments.getCheckDependencyClosure() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getCheckDependencyClosure()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getCheckDependencyClosure() ?? false;
+}
+
+/**
+ * Gets whether the proxy shuts itself down when the process that launched
+ * safe-chain exits, or when no client connects within the idle startup
+ * timeout. Priority: CLI argument > environment variable > config file > false
+ * @returns {boolean}
+ */
+export function getListenOnlyWhenWrapped() {
+  // Priority 1: CLI argument
+  if (cliArguments.getListenOnlyWhenWrapped() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = parseBooleanFromEnv(
+    environmentVariables.getListenOnlyWhenWrapped()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getListenOnlyWhenWrapped() ?? false;
+}
+
+const defaultIdleStartupTimeoutSecs = 60;
+/**
+ * Gets the seconds a proxy that only listens when wrapped waits for its first
+ * client with priority: CLI argument > environment variable > config file > 60
+ * @returns {number}
+ */
+export function getIdleStartupTimeoutSecs() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveNumber(
+    cliArguments.getIdleStartupTimeoutSecs()
+  );
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveNumber(
+    environmentVariables.getIdleStartupTimeoutSecs()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveNumber(
+    configFile.getIdleStartupTimeoutSecs()
+  );
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultIdleStartupTimeoutSecs;
+}
+
+/**
+ * Gets the user name safe-chain presents to the upstream proxy from
+ * HTTPS_PROXY. Priority: CLI argument > environment variable > config file >
+ * the user name in the proxy URL
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyUser() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getUpstreamProxyUser();
+  if (cliValue) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getUpstreamProxyUser();
+  if (envValue) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getUpstreamProxyUser();
+}
+
+/**
+ * Gets the password safe-chain presents to the upstream proxy from
+ * HTTPS_PROXY. Priority: CLI argument > environment variable > config file >
+ * the password in the proxy URL
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyPass() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getUpstreamProxyPass();
+  if (cliValue) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getUpstreamProxyPass();
+  if (envValue) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  return configFile.getUpstreamProxyPass();
+}
+
+/**
+ * Gets the local IP address upstream connections originate from, with
+ * priority: CLI argument > environment variable > config file. Values that
+ * are not an IP address are ignored. Returns undefined when the operating
+ * system picks the source address.
+ * @returns {string | undefined}
+ */
+export function getUpstreamBind() {
+  // Priority 1: CLI argument
+  const cliValue = cliArguments.getUpstreamBind();
+  if (cliValue && net.isIP(cliValue)) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = environmentVariables.getUpstreamBind();
+  if (envValue && net.isIP(envValue)) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = configFile.getUpstreamBind();
+  return configValue && net.isIP(configValue) ? configValue : undefined;
+}
+
+/**
+ * Gets whether the malware list is replaced by the embedded test fixture, for
+ * reproducible tests and demos. It is deliberately not read from the config
+ * file, so it is only on when requested for a run. Priority: CLI argument >
+ * environment variable > false
+ * @returns {boolean}
+ */
+export function getUseTestFixture() {
+  // Priority 1: CLI argument
+  if (cliArguments.getUseTestFixture() === true) {
+    return true;
+  }
+
+  // Priority 2: Environment variable
+  return (
+    parseBooleanFromEnv(environmentVariables.getUseTestFixture()) ?? false
+  );
+}
+
+/**
+ * Gets the replacement base URLs for blocked packages, by "type:name" as in
+ * the X-Safe-Chain-Package header, for example "npm:lodash" or
+ * "pypi:requests". Entries are merged from config file, environment variable
+ * and CLI arguments; on conflicting entries, CLI arguments override the
+ * environment variable, which overrides the config file. Entries without a
+ * valid http(s) URL are ignored.
+ * @returns {Record<string, string>}
+ */
+export function getRedirectMap() {
+  /** @type {Array<[string, unknown]>} */
+  const entries = [
+    ...Object.entries(configFile.getRedirectMap() ?? {}),
+    ...[
+      ...parseExclusionsFromEnv(environmentVariables.getRedirectMap()),
+      ...cliArguments.getRedirectMap(),
+    ].map(parseRedirectMapEntry),
+  ];
+
+  /** @type {Record<string, string>} */
+  const redirectMap = {};
+  for (const [key, value] of entries) {
+    const packageKey = key.trim();
+    if (!packageKey.includes(":") || typeof value !== "string") {
+      continue;
+    }
+    const baseUrl = removeTrailingSlashes(value.trim());
+    if (/^https?:\/\/[^/]/i.test(baseUrl)) {
+      redirectMap[packageKey] = baseUrl;
+    }
+  }
+  return redirectMap;
+}
+
+/**
+ * @param {string} entry - For example "npm:lodash=https://mirror.internal/npm"
+ * @returns {[string, string]}
+ */
+function parseRedirectMapEntry(entry) {
+  const separatorIndex = entry.indexOf("=");
+  if (separatorIndex === -1) {
+    return [entry, ""];
+  }
+  return [entry.slice(0, separatorIndex), entry.slice(separatorIndex + 1)];
+}
+
+/**
+ * Gets the bearer token that protects the admin endpoints of the proxy. The
+ * endpoints are disabled without it. There is no CLI argument, so the token
+ * does not show up in process listings. Priority: environment variable >
+ * config file
+ * @returns {string | undefined}
+ */
+export function getAdminToken() {
+  // Priority 1: Environment variable
+  const envValue = environmentVariables.getAdminToken();
+  if (envValue) {
+    return envValue;
+  }
+
+  // Priority 2: Config file
+  return configFile.getAdminToken();
+}
+
+const defaultMaxTrackedPackages = 10000;
+/**
+ * Gets how many packages the details remembered from their metadata (publish
+ * times, dependencies, install scripts and sizes) are kept in memory for, with
+ * priority: CLI argument > environment variable > config file > 10000
+ * @returns {number}
+ */
+export function getMaxTrackedPackages() {
+  // Priority 1: CLI argument
+  const cliValue = validatePositiveInteger(cliArguments.getMaxTrackedPackages());
+  if (cliValue !== undefined) {
+    return cliValue;
+  }
+
+  // Priority 2: Environment variable
+  const envValue = validatePositiveInteger(
+    environmentVariables.getMaxTrackedPackages()
+  );
+  if (envValue !== undefined) {
+    return envValue;
+  }
+
+  // Priority 3: Config file
+  const configValue = validatePositiveInteger(
+    configFile.getMaxTrackedPackages()
+  );
+  if (configValue !== undefined) {
+    return configValue;
+  }
+
+  return defaultMaxTrackedPackages;
+}
@@ -0,0 +1,227 @@
+import { describe, it, beforeEach, afterEach, mock } from "node:test";
+import assert from "node:assert";
+
+let configFileContent = undefined;
+mock.module("fs", {
+  namedExports: {
+    existsSync: () => configFileContent !== undefined,
+    readFileSync: () => configFileContent,
+    writeFileSync: (content) => (configFileContent = content),
+    mkdirSync: () => {},
+  },
+});
+
+const {
+  getMinimumPackageAgeHours,
+  getMinPackageFirstPublishAgeHours,
+  setEcoSystem,
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+} = await import("./settings.js");
+const { initializeCliArguments } = await import("./cliArguments.js");
+const { buildNewPackagesDatabase } = await import(
+  "../scanning/newPackagesDatabaseBuilder.js"
+);
+
+const envVarName = "SAFE_CHAIN_MINIMUM_PACKAGE_AGE_HOURS";
+
+function hoursAgo(hours) {
+  return Math.floor((Date.now() - hours * 3600 * 1000) / 1000);
+}
+
+describe("getMinimumPackageAgeHours precedence", () => {
+  let originalEnv;
+
+  beforeEach(() => {
+    originalEnv = process.env[envVarName];
+    delete process.env[envVarName];
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    if (originalEnv !== undefined) {
+      process.env[envVarName] = originalEnv;
+    } else {
+      delete process.env[envVarName];
+    }
+    configFileContent = undefined;
+    initializeCliArguments([]);
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  it("defaults to 48 hours", () => {
+    assert.strictEqual(getMinimumPackageAgeHours(), 48);
+  });
+
+  it("uses the config file over the default", () => {
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: 72 });
+
+    assert.strictEqual(getMinimumPackageAgeHours(), 72);
+  });
+
+  it("uses the environment variable over the config file", () => {
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: 72 });
+    process.env[envVarName] = "96";
+
+    assert.strictEqual(getMinimumPackageAgeHours(), 96);
+  });
+
+  it("uses the CLI argument over the environment variable and config file", () => {
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: 72 });
+    process.env[envVarName] = "96";
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=500"]);
+
+    assert.strictEqual(getMinimumPackageAgeHours(), 500);
+  });
+
+  it("accepts 0 from every source", () => {
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: 0 });
+    assert.strictEqual(getMinimumPackageAgeHours(), 0);
+
+    process.env[envVarName] = "0";
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: 72 });
+    assert.strictEqual(getMinimumPackageAgeHours(), 0);
+
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=0"]);
+    process.env[envVarName] = "96";
+    assert.strictEqual(getMinimumPackageAgeHours(), 0);
+  });
+
+  it("falls through to the next source for invalid or negative values", () => {
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: -24 });
+    assert.strictEqual(getMinimumPackageAgeHours(), 48);
+
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: 72 });
+    process.env[envVarName] = "-1";
+    assert.strictEqual(getMinimumPackageAgeHours(), 72);
+
+    process.env[envVarName] = "96";
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=abc"]);
+    assert.strictEqual(getMinimumPackageAgeHours(), 96);
+  });
+
+  for (const ecosystem of [ECOSYSTEM_JS, ECOSYSTEM_PY]) {
+    it(`applies a per-invocation CLI value of 500 hours to the direct download decision (${ecosystem})`, () => {
+      setEcoSystem(ecosystem);
+      const db = buildNewPackagesDatabase([
+        { package_name: "foo", version: "1.0.0", released_on: hoursAgo(100) },
+      ]);
+
+      // With the default of 48 hours a release from 100 hours ago is allowed
+      assert.strictEqual(db.isNewlyReleasedPackage("foo", "1.0.0"), false);
+
+      initializeCliArguments([
+        "install",
+        "foo==1.0.0",
+        "--safe-chain-minimum-package-age-hours=500",
+      ]);
+
+      assert.strictEqual(db.isNewlyReleasedPackage("foo", "1.0.0"), true);
+    });
+  }
+});
+
+describe("getMinimumPackageAgeHours per ecosystem", () => {
+  let originalEnv;
+
+  beforeEach(() => {
+    originalEnv = process.env[envVarName];
+    delete process.env[envVarName];
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    if (originalEnv !== undefined) {
+      process.env[envVarName] = originalEnv;
+    } else {
+      delete process.env[envVarName];
+    }
+    configFileContent = undefined;
+    initializeCliArguments([]);
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  it("uses the PyPI threshold for Python even when the global value differs", () => {
+    initializeCliArguments([
+      "--safe-chain-minimum-package-age-hours=24,npm=72,pypi=500",
+    ]);
+
+    setEcoSystem(ECOSYSTEM_PY);
+    assert.strictEqual(getMinimumPackageAgeHours(), 500);
+
+    setEcoSystem(ECOSYSTEM_JS);
+    assert.strictEqual(getMinimumPackageAgeHours(), 72);
+  });
+
+  it("uses the global value of the same source when the ecosystem has no entry", () => {
+    process.env[envVarName] = "96, pypi=500, cargo=24";
+
+    assert.strictEqual(getMinimumPackageAgeHours(), 96);
+  });
+
+  it("falls through to the next source when neither the ecosystem nor a global value is set", () => {
+    configFileContent = JSON.stringify({ minimumPackageAgeHours: "npm=12" });
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=pypi=500"]);
+
+    assert.strictEqual(getMinimumPackageAgeHours(), 12);
+  });
+
+  it("only applies a per-request override above the per-ecosystem value", () => {
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=npm=72"]);
+
+    assert.strictEqual(
+      getMinimumPackageAgeHours({ minimumPackageAgeHours: "6" }),
+      72
+    );
+    assert.strictEqual(
+      getMinimumPackageAgeHours({ minimumPackageAgeHours: "96" }),
+      96
+    );
+  });
+});
+
+describe("getMinPackageFirstPublishAgeHours", () => {
+  const firstPublishEnvVarName =
+    "SAFE_CHAIN_MIN_PACKAGE_FIRST_PUBLISH_AGE_HOURS";
+
+  beforeEach(() => {
+    delete process.env[firstPublishEnvVarName];
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    delete process.env[firstPublishEnvVarName];
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  it("is not set by default", () => {
+    assert.strictEqual(getMinPackageFirstPublishAgeHours(), undefined);
+  });
+
+  it("uses the CLI argument over the environment variable and config file", () => {
+    configFileContent = JSON.stringify({ minPackageFirstPublishAgeHours: 24 });
+    assert.strictEqual(getMinPackageFirstPublishAgeHours(), 24);
+
+    process.env[firstPublishEnvVarName] = "72";
+    assert.strictEqual(getMinPackageFirstPublishAgeHours(), 72);
+
+    initializeCliArguments([
+      "--safe-chain-min-package-first-publish-age-hours=168",
+    ]);
+    assert.strictEqual(getMinPackageFirstPublishAgeHours(), 168);
+  });
+
+  it("falls through to the next source for invalid, zero or negative values", () => {
+    configFileContent = JSON.stringify({ minPackageFirstPublishAgeHours: 24 });
+    process.env[firstPublishEnvVarName] = "0";
+    initializeCliArguments([
+      "--safe-chain-min-package-first-publish-age-hours=abc",
+    ]);
+
+    assert.strictEqual(getMinPackageFirstPublishAgeHours(), 24);
+  });
+});
@@ -28,8 +28,28 @@ const {
   getLogFileVerbosity,
   LOG_FILE_FORMAT_PLAIN,
   LOG_FILE_FORMAT_JSON,
+  getTcpBacklog,
+  getTcpNoDelay,
+  getStripRequestHeaders,
+  getAddVia,
+  getMaxDecompressedMb,
+  getCircuitBreakerThreshold,
+  getCircuitBreakerCooldownSeconds,
+  getBlockingDisabled,
+  getMinTlsVersion,
+  getMalwareFeedUrls,
+  getRedirectMap,
+  getNoInterceptHosts,
+  getBlockMessageTemplates,
+  getVcsPolicy,
+  getUpstreamBind,
+  getMaxRetryAfterSecs,
+  getBlockStatus,
+  getProxyPort,
+  skipMinimumPackageAge,
 } = await import("./settings.js");
 const { initializeCliArguments } = await import("./cliArguments.js");
+const { ui } = await import("../environment/userInteraction.js");
 
 for (const { packageManager, getCustomRegistries, envVarName } of [
   {
@@ -649,6 +669,47 @@ describe("getMalwareListBaseUrl", () => {
 
     assert.strictEqual(url, "https://cli-mirror.com");
   });
+
+  it("should translate gs:// and s3:// buckets to their HTTPS endpoints", () => {
+    process.env[envVarName] = "gs://acme-mirror/safe-chain/";
+    assert.strictEqual(
+      getMalwareListBaseUrl(),
+      "https://storage.googleapis.com/acme-mirror/safe-chain"
+    );
+
+    process.env[envVarName] = "s3://acme-mirror/safe-chain";
+    assert.strictEqual(
+      getMalwareListBaseUrl(),
+      "https://acme-mirror.s3.amazonaws.com/safe-chain"
+    );
+  });
+
+  it("should load the malware lists from an agent policy source", () => {
+    process.env[envVarName] = "https://env-mirror.com";
+    initializeCliArguments(["--safe-chain-policy-source=agent://127.0.0.1:7981/"]);
+
+    assert.strictEqual(getMalwareListBaseUrl(), "http://127.0.0.1:7981");
+  });
+
+  it("should ignore a policy source that is not an agent endpoint", () => {
+    initializeCliArguments(["--safe-chain-policy-source=https://127.0.0.1:7981"]);
+
+    assert.strictEqual(getMalwareListBaseUrl(), "https://malware-list.aikido.dev");
+  });
+
+  it("should warn once about an ignored policy source", (t) => {
+    const writeWarning = t.mock.method(ui, "writeWarning", () => {});
+    initializeCliArguments(["--safe-chain-policy-source=agent:127.0.0.1:7981"]);
+
+    getMalwareListBaseUrl();
+    getMalwareListBaseUrl();
+
+    assert.strictEqual(writeWarning.mock.callCount(), 1);
+    assert.match(
+      writeWarning.mock.calls[0].arguments[0],
+      /Ignoring invalid policy source "agent:127\.0\.0\.1:7981"/
+    );
+  });
 });
 
 describe("getLogFile", () => {
@@ -917,3 +978,588 @@ describe("getLogFileVerbosity", () => {
     assert.strictEqual(getLogFileVerbosity(), LOGGING_VERBOSE);
   });
 });
+
+describe("getTcpBacklog", () => {
+  let originalEnv;
+  const envVarName = "SAFE_CHAIN_TCP_BACKLOG";
+
+  beforeEach(() => {
+    originalEnv = process.env[envVarName];
+    delete process.env[envVarName];
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    if (originalEnv !== undefined) {
+      process.env[envVarName] = originalEnv;
+    } else {
+      delete process.env[envVarName];
+    }
+    configFileContent = undefined;
+  });
+
+  it("should keep Node's default when nothing is configured", () => {
+    assert.strictEqual(getTcpBacklog(), undefined);
+  });
+
+  it("should prioritize CLI over environment variable and config file", () => {
+    process.env[envVarName] = "2048";
+    configFileContent = JSON.stringify({ tcpBacklog: 4096 });
+    initializeCliArguments(["--safe-chain-tcp-backlog=1024"]);
+
+    assert.strictEqual(getTcpBacklog(), 1024);
+  });
+
+  it("should read from config file", () => {
+    configFileContent = JSON.stringify({ tcpBacklog: 4096 });
+
+    assert.strictEqual(getTcpBacklog(), 4096);
+  });
+
+  it("should ignore values that are not positive integers", () => {
+    process.env[envVarName] = "-5";
+    configFileContent = JSON.stringify({ tcpBacklog: 12.5 });
+
+    assert.strictEqual(getTcpBacklog(), undefined);
+  });
+});
+
+describe("getTcpNoDelay", () => {
+  let originalEnv;
+  const envVarName = "SAFE_CHAIN_TCP_NODELAY";
+
+  beforeEach(() => {
+    originalEnv = process.env[envVarName];
+    delete process.env[envVarName];
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    if (originalEnv !== undefined) {
+      process.env[envVarName] = originalEnv;
+    } else {
+      delete process.env[envVarName];
+    }
+    configFileContent = undefined;
+  });
+
+  it("should default to true, matching Node's http server", () => {
+    assert.strictEqual(getTcpNoDelay(), true);
+  });
+
+  it("should be disabled from the CLI", () => {
+    process.env[envVarName] = "true";
+    initializeCliArguments(["--safe-chain-tcp-nodelay=false"]);
+
+    assert.strictEqual(getTcpNoDelay(), false);
+  });
+
+  it("should read from environment variable and config file", () => {
+    process.env[envVarName] = "0";
+    assert.strictEqual(getTcpNoDelay(), false);
+
+    delete process.env[envVarName];
+    configFileContent = JSON.stringify({ tcpNoDelay: false });
+    assert.strictEqual(getTcpNoDelay(), false);
+  });
+});
+
+describe("getStripRequestHeaders", () => {
+  let originalEnv;
+  const envVarName = "SAFE_CHAIN_STRIP_REQUEST_HEADERS";
+
+  beforeEach(() => {
+    originalEnv = process.env[envVarName];
+    delete process.env[envVarName];
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    if (originalEnv !== undefined) {
+      process.env[envVarName] = originalEnv;
+    } else {
+      delete process.env[envVarName];
+    }
+    configFileContent = undefined;
+  });
+
+  it("should default to an empty list", () => {
+    assert.deepStrictEqual(getStripRequestHeaders(), []);
+  });
+
+  it("should merge CLI, environment variable and config file, lowercased and deduplicated", () => {
+    initializeCliArguments([
+      "--safe-chain-strip-request-header=X-Internal-Token",
+      "--safe-chain-strip-request-header=x-debug",
+    ]);
+    process.env[envVarName] = "x-debug, X-Forwarded-For";
+    configFileContent = JSON.stringify({ stripRequestHeaders: ["via"] });
+
+    assert.deepStrictEqual(getStripRequestHeaders(), [
+      "x-internal-token",
+      "x-debug",
+      "x-forwarded-for",
+      "via",
+    ]);
+  });
+});
+
+describe("getAddVia", () => {
+  let originalEnv;
+  const envVarName = "SAFE_CHAIN_ADD_VIA";
+
+  beforeEach(() => {
+    originalEnv = process.env[envVarName];
+    delete process.env[envVarName];
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    if (originalEnv !== undefined) {
+      process.env[envVarName] = originalEnv;
+    } else {
+      delete process.env[envVarName];
+    }
+    configFileContent = undefined;
+  });
+
+  it("should default to false", () => {
+    assert.strictEqual(getAddVia(), false);
+  });
+
+  it("should be enabled from the CLI, environment variable or config file", () => {
+    initializeCliArguments(["--safe-chain-add-via"]);
+    assert.strictEqual(getAddVia(), true);
+
+    initializeCliArguments([]);
+    process.env[envVarName] = "true";
+    assert.strictEqual(getAddVia(), true);
+
+    delete process.env[envVarName];
+    configFileContent = JSON.stringify({ addVia: true });
+    assert.strictEqual(getAddVia(), true);
+  });
+});
+
+describe("getMaxDecompressedMb", () => {
+  let originalEnv;
+  const envVarName = "SAFE_CHAIN_MAX_DECOMPRESSED_MB";
+
+  beforeEach(() => {
+    originalEnv = process.env[envVarName];
+    delete process.env[envVarName];
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    if (originalEnv !== undefined) {
+      process.env[envVarName] = originalEnv;
+    } else {
+      delete process.env[envVarName];
+    }
+    configFileContent = undefined;
+  });
+
+  it("should default to 512", () => {
+    assert.strictEqual(getMaxDecompressedMb(), 512);
+  });
+
+  it("should prefer the CLI over the environment variable and config file", () => {
+    configFileContent = JSON.stringify({ maxDecompressedMb: 64 });
+    process.env[envVarName] = "128";
+    initializeCliArguments(["--safe-chain-max-decompressed-mb=256"]);
+
+    assert.strictEqual(getMaxDecompressedMb(), 256);
+  });
+
+  it("should ignore values that are not positive numbers", () => {
+    process.env[envVarName] = "0";
+    configFileContent = JSON.stringify({ maxDecompressedMb: "lots" });
+
+    assert.strictEqual(getMaxDecompressedMb(), 512);
+  });
+});
+
+describe("circuit breaker settings", () => {
+  beforeEach(() => {
+    delete process.env.SAFE_CHAIN_CIRCUIT_BREAKER_THRESHOLD;
+    delete process.env.SAFE_CHAIN_CIRCUIT_BREAKER_COOLDOWN_SECONDS;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_CIRCUIT_BREAKER_THRESHOLD;
+    delete process.env.SAFE_CHAIN_CIRCUIT_BREAKER_COOLDOWN_SECONDS;
+    configFileContent = undefined;
+  });
+
+  it("should default to 5 failures and a 30 second cooldown", () => {
+    assert.strictEqual(getCircuitBreakerThreshold(), 5);
+    assert.strictEqual(getCircuitBreakerCooldownSeconds(), 30);
+  });
+
+  it("should prioritize CLI over environment variable and config file", () => {
+    process.env.SAFE_CHAIN_CIRCUIT_BREAKER_THRESHOLD = "8";
+    configFileContent = JSON.stringify({
+      circuitBreakerThreshold: 10,
+      circuitBreakerCooldownSeconds: 60,
+    });
+    initializeCliArguments(["--safe-chain-circuit-breaker-threshold=2"]);
+
+    assert.strictEqual(getCircuitBreakerThreshold(), 2);
+    assert.strictEqual(getCircuitBreakerCooldownSeconds(), 60);
+  });
+
+  it("should ignore a threshold that is not a positive integer", () => {
+    process.env.SAFE_CHAIN_CIRCUIT_BREAKER_THRESHOLD = "2.5";
+
+    assert.strictEqual(getCircuitBreakerThreshold(), 5);
+  });
+});
+
+describe("getMinTlsVersion", () => {
+  beforeEach(() => {
+    delete process.env.SAFE_CHAIN_MIN_TLS_VERSION;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_MIN_TLS_VERSION;
+    configFileContent = undefined;
+  });
+
+  it("should default to TLS 1.2", () => {
+    assert.strictEqual(getMinTlsVersion(), "TLSv1.2");
+  });
+
+  it("should prioritize CLI over environment variable and config file", () => {
+    configFileContent = JSON.stringify({ minTlsVersion: "TLSv1.2" });
+    process.env.SAFE_CHAIN_MIN_TLS_VERSION = "1.2";
+    initializeCliArguments(["--safe-chain-min-tls-version=1.3"]);
+
+    assert.strictEqual(getMinTlsVersion(), "TLSv1.3");
+  });
+
+  it("should ignore versions below TLS 1.2", () => {
+    configFileContent = JSON.stringify({ minTlsVersion: "tlsv1.3" });
+    process.env.SAFE_CHAIN_MIN_TLS_VERSION = "TLSv1.1";
+
+    assert.strictEqual(getMinTlsVersion(), "TLSv1.3");
+  });
+});
+
+describe("getMalwareFeedUrls", () => {
+  beforeEach(() => {
+    delete process.env.SAFE_CHAIN_MALWARE_FEED_URLS;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_MALWARE_FEED_URLS;
+    configFileContent = undefined;
+  });
+
+  it("should order feeds from config file to CLI by ascending precedence", () => {
+    configFileContent = JSON.stringify({
+      malwareFeedUrls: ["https://config.example.com", "https://shared.example.com/"],
+    });
+    process.env.SAFE_CHAIN_MALWARE_FEED_URLS = "https://env.example.com";
+    initializeCliArguments([
+      "--safe-chain-malware-feed-url=https://shared.example.com",
+      "--safe-chain-malware-feed-url=https://cli.example.com",
+    ]);
+
+    assert.deepStrictEqual(getMalwareFeedUrls(), [
+      "https://config.example.com",
+      "https://env.example.com",
+      "https://shared.example.com",
+      "https://cli.example.com",
+    ]);
+  });
+});
+
+describe("getRedirectMap", () => {
+  beforeEach(() => {
+    delete process.env.SAFE_CHAIN_REDIRECT_MAP;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_REDIRECT_MAP;
+    configFileContent = undefined;
+  });
+
+  it("should merge entries with CLI over environment over config file", () => {
+    configFileContent = JSON.stringify({
+      redirectMap: {
+        "npm:lodash": "https://config.example.com/npm",
+        "pypi:requests": "https://config.example.com/pypi/",
+      },
+    });
+    process.env.SAFE_CHAIN_REDIRECT_MAP =
+      "npm:lodash=https://env.example.com/npm, npm:@acme/ui=https://env.example.com/npm";
+    initializeCliArguments([
+      "--safe-chain-redirect-map=npm:@acme/ui=https://cli.example.com/npm",
+    ]);
+
+    assert.deepStrictEqual(getRedirectMap(), {
+      "npm:lodash": "https://env.example.com/npm",
+      "pypi:requests": "https://config.example.com/pypi",
+      "npm:@acme/ui": "https://cli.example.com/npm",
+    });
+  });
+
+  it("should ignore entries without a package type or http(s) URL", () => {
+    configFileContent = JSON.stringify({
+      redirectMap: { lodash: "https://example.com", "npm:left-pad": 42 },
+    });
+    initializeCliArguments([
+      "--safe-chain-redirect-map=npm:express=ftp://example.com",
+      "--safe-chain-redirect-map=npm:react",
+    ]);
+
+    assert.deepStrictEqual(getRedirectMap(), {});
+  });
+});
+
+describe("getNoInterceptHosts", () => {
+  beforeEach(() => {
+    delete process.env.SAFE_CHAIN_NO_INTERCEPT_HOSTS;
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_NO_INTERCEPT_HOSTS;
+    configFileContent = undefined;
+  });
+
+  it("should merge hosts from all sources", () => {
+    configFileContent = JSON.stringify({
+      noInterceptHosts: ["Pinned.Example.com", "*.acme.com"],
+    });
+    process.env.SAFE_CHAIN_NO_INTERCEPT_HOSTS = "pinned.example.com, pypi.acme.com";
+    initializeCliArguments(["--safe-chain-no-intercept-host=registry.corp"]);
+
+    assert.deepStrictEqual(getNoInterceptHosts(), [
+      "registry.corp",
+      "pinned.example.com",
+      "pypi.acme.com",
+      "*.acme.com",
+    ]);
+  });
+});
+
+describe("getBlockStatus", () => {
+  beforeEach(() => {
+    delete process.env.SAFE_CHAIN_BLOCK_STATUS;
+    initializeCliArguments([]);
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_BLOCK_STATUS;
+    configFileContent = undefined;
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  it("should default to 403", () => {
+    assert.strictEqual(getBlockStatus(), 403);
+  });
+
+  it("should prefer the value for the current ecosystem", () => {
+    process.env.SAFE_CHAIN_BLOCK_STATUS = "451,pypi=410";
+
+    assert.strictEqual(getBlockStatus(), 451);
+    setEcoSystem(ECOSYSTEM_PY);
+    assert.strictEqual(getBlockStatus(), 410);
+  });
+
+  it("should ignore codes that are not client or server errors", () => {
+    configFileContent = JSON.stringify({ blockStatus: 418 });
+    initializeCliArguments(["--safe-chain-block-status=200"]);
+    process.env.SAFE_CHAIN_BLOCK_STATUS = "npm=forbidden";
+
+    assert.strictEqual(getBlockStatus(), 418);
+  });
+});
+
+describe("getBlockMessageTemplates", () => {
+  afterEach(() => {
+    configFileContent = undefined;
+  });
+
+  it("should read text and HTML templates and ignore invalid ones", () => {
+    configFileContent = JSON.stringify({
+      blockMessages: {
+        malware: "{package}@{version} is malware",
+        "minimum-age": { html: "<p>{package} is too new</p>" },
+        prerelease: { text: "no pre-releases" },
+        default: 42,
+      },
+    });
+
+    assert.deepStrictEqual(getBlockMessageTemplates(), {
+      malware: { template: "{package}@{version} is malware", format: "text" },
+      "minimum-age": { template: "<p>{package} is too new</p>", format: "html" },
+      prerelease: { template: "no pre-releases", format: "text" },
+    });
+  });
+
+  it("should be empty without templates", () => {
+    assert.deepStrictEqual(getBlockMessageTemplates(), {});
+  });
+});
+
+describe("getVcsPolicy", () => {
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_VCS_POLICY;
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  it("should default to allow", () => {
+    assert.strictEqual(getVcsPolicy(), "allow");
+  });
+
+  it("should ignore invalid policies so the next source applies", () => {
+    configFileContent = JSON.stringify({ vcsPolicy: "log" });
+    process.env.SAFE_CHAIN_VCS_POLICY = "block";
+    assert.strictEqual(getVcsPolicy(), "log");
+
+    initializeCliArguments(["--safe-chain-vcs-policy=DENY"]);
+    assert.strictEqual(getVcsPolicy(), "deny");
+  });
+});
+
+describe("getUpstreamBind", () => {
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_UPSTREAM_BIND;
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  it("should let the operating system pick the source address by default", () => {
+    assert.strictEqual(getUpstreamBind(), undefined);
+  });
+
+  it("should prioritize CLI over environment variable and config file", () => {
+    configFileContent = JSON.stringify({ upstreamBind: "10.0.0.5" });
+    assert.strictEqual(getUpstreamBind(), "10.0.0.5");
+
+    process.env.SAFE_CHAIN_UPSTREAM_BIND = "2001:db8::5";
+    assert.strictEqual(getUpstreamBind(), "2001:db8::5");
+
+    initializeCliArguments(["--safe-chain-upstream-bind=192.168.1.20"]);
+    assert.strictEqual(getUpstreamBind(), "192.168.1.20");
+  });
+
+  it("should ignore values that are not an IP address so the next source applies", () => {
+    configFileContent = JSON.stringify({ upstreamBind: "10.0.0.5" });
+    process.env.SAFE_CHAIN_UPSTREAM_BIND = "eth0";
+    initializeCliArguments(["--safe-chain-upstream-bind=10.0.0.300"]);
+
+    assert.strictEqual(getUpstreamBind(), "10.0.0.5");
+  });
+});
+
+describe("getMaxRetryAfterSecs", () => {
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_MAX_RETRY_AFTER_SECS;
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  it("should wait for at most 30 seconds by default", () => {
+    assert.strictEqual(getMaxRetryAfterSecs(), 30);
+  });
+
+  it("should prioritize CLI over environment variable and config file", () => {
+    configFileContent = JSON.stringify({ maxRetryAfterSecs: 60 });
+    assert.strictEqual(getMaxRetryAfterSecs(), 60);
+
+    process.env.SAFE_CHAIN_MAX_RETRY_AFTER_SECS = "120";
+    assert.strictEqual(getMaxRetryAfterSecs(), 120);
+
+    initializeCliArguments(["--safe-chain-max-retry-after-secs=5"]);
+    assert.strictEqual(getMaxRetryAfterSecs(), 5);
+  });
+
+  it("should ignore values that are not a positive number so the next source applies", () => {
+    configFileContent = JSON.stringify({ maxRetryAfterSecs: 60 });
+    process.env.SAFE_CHAIN_MAX_RETRY_AFTER_SECS = "soon";
+    initializeCliArguments(["--safe-chain-max-retry-after-secs=-1"]);
+
+    assert.strictEqual(getMaxRetryAfterSecs(), 60);
+  });
+});
+
+describe("configuration through environment variables only", () => {
+  const envVarNames = [
+    "SAFE_CHAIN_PROXY_PORT",
+    "SAFE_CHAIN_SKIP_MINIMUM_PACKAGE_AGE",
+    "SAFE_CHAIN_DISABLE_BLOCKING",
+  ];
+  let originalEnv;
+
+  beforeEach(() => {
+    originalEnv = Object.fromEntries(
+      envVarNames.map((name) => [name, process.env[name]])
+    );
+    for (const name of envVarNames) {
+      delete process.env[name];
+    }
+    initializeCliArguments([]);
+  });
+
+  afterEach(() => {
+    for (const name of envVarNames) {
+      if (originalEnv[name] !== undefined) {
+        process.env[name] = originalEnv[name];
+      } else {
+        delete process.env[name];
+      }
+    }
+    configFileContent = undefined;
+    initializeCliArguments([]);
+  });
+
+  it("should set the proxy port from the environment when no flag is given", () => {
+    process.env.SAFE_CHAIN_PROXY_PORT = "8585";
+
+    assert.strictEqual(getProxyPort(), 8585);
+  });
+
+  it("should prefer the CLI flag over the environment variable for the proxy port", () => {
+    process.env.SAFE_CHAIN_PROXY_PORT = "8585";
+    initializeCliArguments(["--safe-chain-proxy-port=9090"]);
+
+    assert.strictEqual(getProxyPort(), 9090);
+  });
+
+  it("should skip the minimum package age from the environment", () => {
+    assert.strictEqual(skipMinimumPackageAge(), false);
+
+    process.env.SAFE_CHAIN_SKIP_MINIMUM_PACKAGE_AGE = "true";
+    assert.strictEqual(skipMinimumPackageAge(), true);
+
+    process.env.SAFE_CHAIN_SKIP_MINIMUM_PACKAGE_AGE = "0";
+    assert.strictEqual(skipMinimumPackageAge(), false);
+  });
+
+  it("should disable blocking with the flag or the environment variable", () => {
+    assert.strictEqual(getBlockingDisabled(), false);
+
+    process.env.SAFE_CHAIN_DISABLE_BLOCKING = "1";
+    assert.strictEqual(getBlockingDisabled(), true);
+
+    delete process.env.SAFE_CHAIN_DISABLE_BLOCKING;
+    initializeCliArguments(["--safe-chain-no-block"]);
+    assert.strictEqual(getBlockingDisabled(), true);
+  });
+
+  it("should not disable blocking from the config file", () => {
+    configFileContent = JSON.stringify({ noBlock: true, disableBlocking: true });
+
+    assert.strictEqual(getBlockingDisabled(), false);
+  });
+});
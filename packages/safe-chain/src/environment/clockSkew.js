@@ -0,0 +1,86 @@
+import { ui } from "./userInteraction.js";
+
+// Date headers have a resolution of one second and responses can be cached
+// for a while, so only a skew that would noticeably shift minimum package age
+// decisions is reported.
+export const CLOCK_SKEW_THRESHOLD_MS = 60 * 60 * 1000;
+
+/**
+ * @type {{skewMs: number | undefined, source: string | undefined, warned: boolean}}
+ */
+const state = {
+  skewMs: undefined,
+  source: undefined,
+  warned: false,
+};
+
+/**
+ * Compares the local clock with the Date header of a server response. The
+ * minimum package age checks depend on the local clock, so a badly skewed
+ * clock makes every age decision wrong.
+ *
+ * @param {string | null | undefined} dateHeader
+ * @param {string} source - Who sent the response, e.g. "registry.npmjs.org"
+ * @param {number} [now]
+ * @returns {void}
+ */
+export function recordServerDate(dateHeader, source, now = Date.now()) {
+  // HTTP dates are always in GMT. Checking for it keeps Date.parse from
+  // accepting arbitrary strings through its lenient legacy parser.
+  if (!dateHeader || !dateHeader.endsWith(" GMT")) {
+    return;
+  }
+
+  const serverTime = Date.parse(dateHeader);
+  if (Number.isNaN(serverTime)) {
+    return;
+  }
+
+  const isFirstMeasurement = state.skewMs === undefined;
+  state.skewMs = now - serverTime;
+  state.source = source;
+
+  if (isFirstMeasurement) {
+    ui.writeVerbose(
+      `Safe-chain: clock.skew - the local clock differs from ${source} by ${Math.round(
+        state.skewMs / 1000
+      )}s`
+    );
+  }
+
+  if (isClockSkewed() && !state.warned) {
+    state.warned = true;
+    ui.writeWarning(
+      `Safe-chain: clock.skew - ${describeClockSkew()}. Minimum package age checks depend on the local clock and may be wrong.`
+    );
+  }
+}
+
+/**
+ * @returns {boolean}
+ */
+export function isClockSkewed() {
+  return (
+    state.skewMs !== undefined &&
+    Math.abs(state.skewMs) > CLOCK_SKEW_THRESHOLD_MS
+  );
+}
+
+/**
+ * @returns {string}
+ */
+export function describeClockSkew() {
+  const skewMs = state.skewMs ?? 0;
+  const hours = (Math.abs(skewMs) / (60 * 60 * 1000)).toFixed(1);
+  const direction = skewMs > 0 ? "ahead of" : "behind";
+  return `The local clock is ${hours} hours ${direction} ${state.source}`;
+}
+
+/**
+ * @returns {void}
+ */
+export function resetClockSkew() {
+  state.skewMs = undefined;
+  state.source = undefined;
+  state.warned = false;
+}
@@ -0,0 +1,75 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("clockSkew", async () => {
+  const warnings = [];
+  const verboseMessages = [];
+
+  mock.module("./userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeWarning: (message) => warnings.push(message),
+        writeVerbose: (message) => verboseMessages.push(message),
+      },
+    },
+  });
+
+  const {
+    recordServerDate,
+    isClockSkewed,
+    describeClockSkew,
+    resetClockSkew,
+    CLOCK_SKEW_THRESHOLD_MS,
+  } = await import("./clockSkew.js");
+
+  const serverTime = Date.parse("2026-10-14T12:00:00Z");
+  const serverDate = new Date(serverTime).toUTCString();
+
+  beforeEach(() => {
+    resetClockSkew();
+    warnings.length = 0;
+    verboseMessages.length = 0;
+  });
+
+  it("should not report a clock within the threshold", () => {
+    recordServerDate(serverDate, "registry.npmjs.org", serverTime + 30_000);
+
+    assert.strictEqual(isClockSkewed(), false);
+    assert.deepStrictEqual(warnings, []);
+    assert.match(verboseMessages[0], /differs from registry\.npmjs\.org by 30s/);
+  });
+
+  it("should warn once when the local clock is far ahead of the server", () => {
+    const now = serverTime + 3 * 60 * 60 * 1000;
+
+    recordServerDate(serverDate, "registry.npmjs.org", now);
+    recordServerDate(serverDate, "registry.npmjs.org", now);
+
+    assert.strictEqual(isClockSkewed(), true);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(
+      warnings[0],
+      /clock\.skew - The local clock is 3\.0 hours ahead of registry\.npmjs\.org/
+    );
+  });
+
+  it("should detect a clock that is behind the server", () => {
+    recordServerDate(
+      serverDate,
+      "pypi.org",
+      serverTime - CLOCK_SKEW_THRESHOLD_MS - 1000
+    );
+
+    assert.strictEqual(isClockSkewed(), true);
+    assert.match(describeClockSkew(), /behind pypi\.org/);
+  });
+
+  it("should ignore missing and invalid Date headers", () => {
+    recordServerDate(undefined, "registry.npmjs.org");
+    recordServerDate("not a date", "registry.npmjs.org");
+    recordServerDate('"etag-123"', "registry.npmjs.org");
+
+    assert.strictEqual(isClockSkewed(), false);
+    assert.deepStrictEqual(verboseMessages, []);
+  });
+});
@@ -4,9 +4,11 @@ import {
   getLogFileFormat,
   getLogFileVerbosity,
   LOG_FILE_FORMAT_JSON,
+  LOGGING_DECISIONS,
   LOGGING_SILENT,
   LOGGING_VERBOSE,
 } from "../config/settings.js";
+import { getFeedVersion } from "../scanning/feedVersion.js";
 
 /**
  * @type {{
@@ -84,7 +86,7 @@ export function isFileLoggingActive() {
  * Writes a log entry to the file. No-op when not active. Accepts the same
  * trailing-params shape as console.log.
  *
- * @param {"info" | "warning" | "error" | "verbose"} level
+ * @param {"info" | "decision" | "warning" | "error" | "verbose"} level
  * @param {string} message
  * @param {...any} params
  */
@@ -105,6 +107,9 @@ export function writeToLogFile(level, message, ...params) {
 function shouldWriteLevel(level) {
   if (state.verbosity === LOGGING_VERBOSE) return true;
   if (state.verbosity === LOGGING_SILENT) return level === "error";
+  if (state.verbosity === LOGGING_DECISIONS) {
+    return level === "decision" || level === "error";
+  }
   // LOGGING_NORMAL: drop verbose, keep info/warning/error.
   return level !== "verbose";
 }
@@ -188,6 +193,8 @@ export function closeFileLoggerSync() {
 /**
  * Formats a log entry as a single newline-terminated line, either as NDJSON
  * or as bracketed plain text: `[timestamp] [level] message param1 param2 ...`
+ * Once the malware list is loaded, its version is part of every entry, so
+ * decisions can be traced back to it.
  *
  * @param {string} level
  * @param {string} message
@@ -202,13 +209,23 @@ function formatLine(level, message, params) {
       ? message
       : `${message} ${params.map(formatParam).join(" ")}`;
 
+  const feedVersion = getFeedVersion();
+
   if (state.format === LOG_FILE_FORMAT_JSON) {
-    return JSON.stringify({ timestamp, level, message: fullMessage }) + "\n";
+    return (
+      JSON.stringify({
+        timestamp,
+        level,
+        feed_version: feedVersion ?? null,
+        message: fullMessage,
+      }) + "\n"
+    );
   }
 
   // Escape CR/LF in plain mode so a message containing a newline can't forge
   // a separate log entry. JSON mode is already safe via JSON.stringify.
-  return `[${timestamp}] [${level}] ${escapeNewlines(fullMessage)}\n`;
+  const feed = feedVersion ? ` [feed ${escapeNewlines(feedVersion)}]` : "";
+  return `[${timestamp}] [${level}]${feed} ${escapeNewlines(fullMessage)}\n`;
 }
 
 /**
@@ -46,6 +46,7 @@ mock.module("../config/settings.js", {
         LOGGING_SILENT: "silent",
         LOGGING_NORMAL: "normal",
         LOGGING_VERBOSE: "verbose",
+        LOGGING_DECISIONS: "decisions",
         getLogFileVerbosity: () => currentVerbosity,
     },
 });
@@ -57,6 +58,9 @@ const {
     closeFileLogger,
     closeFileLoggerSync,
 } = await import("./fileLogger.js");
+const { recordFeedVersion, resetFeedVersion } = await import(
+    "../scanning/feedVersion.js"
+);
 
 function initAndReset() {
     initializeFileLogger("/tmp/test.log", () => { });
@@ -103,6 +107,22 @@ describe("fileLogger - plain format", () => {
         assert.ok(writtenData[0].endsWith("\n"));
     });
 
+    it("should stamp plain-text log entries with the malware list version", () => {
+        initAndReset();
+        recordFeedVersion("W/\"feed-42\"");
+
+        try {
+            writeToLogFile("warning", "something happened");
+        } finally {
+            resetFeedVersion();
+        }
+
+        assert.match(
+            writtenData[0],
+            /^\[.+\] \[warning\] \[feed W\/"feed-42"\] something happened\n$/
+        );
+    });
+
     it("should write plain-text log entries for all levels", () => {
         initAndReset();
 
@@ -271,6 +291,21 @@ describe("fileLogger - json format", () => {
         assert.ok(!("params" in entry));
     });
 
+    it("should include the malware list version in JSON log entries", () => {
+        initAndReset();
+
+        writeToLogFile("info", "before the list is loaded");
+        recordFeedVersion("feed-42");
+        try {
+            writeToLogFile("info", "after the list is loaded");
+        } finally {
+            resetFeedVersion();
+        }
+
+        assert.strictEqual(JSON.parse(writtenData[0]).feed_version, null);
+        assert.strictEqual(JSON.parse(writtenData[1]).feed_version, "feed-42");
+    });
+
     it("should preserve ANSI codes in JSON message field", () => {
         initAndReset();
 
@@ -424,6 +459,20 @@ describe("fileLogger - verbosity filter", () => {
         assert.deepEqual(levels, ["info", "warning", "error"]);
     });
 
+    it("decisions verbosity records only decision and error entries", () => {
+        currentVerbosity = "decisions";
+        initAndReset();
+
+        writeToLogFile("info", "i");
+        writeToLogFile("decision", "blocked");
+        writeToLogFile("warning", "w");
+        writeToLogFile("verbose", "v");
+        writeToLogFile("error", "e");
+
+        const levels = writtenData.map((d) => JSON.parse(d).level);
+        assert.deepEqual(levels, ["decision", "error"]);
+    });
+
     it("silent verbosity records only error entries", async () => {
         // The file is still created so errors have somewhere to land — silent
         // mirrors console silent semantics (errors always surface) rather than
@@ -0,0 +1,55 @@
+import fs from "fs";
+import path from "path";
+import { fileURLToPath } from "url";
+
+/** @type {string | undefined} */
+let cachedVersion;
+
+/**
+ * Reads the safe-chain version from its package.json.
+ *
+ * In the npm package this module lives in src/environment, two levels below
+ * package.json. In the packaged binary all modules are bundled into
+ * bin/safe-chain.cjs, one level below package.json.
+ *
+ * @returns {Promise<string>}
+ */
+export async function getSafeChainVersion() {
+  if (cachedVersion) {
+    return cachedVersion;
+  }
+
+  const moduleDir = resolveModuleDir(import.meta.url);
+  const candidates = [
+    path.join(moduleDir, "..", "..", "package.json"),
+    path.join(moduleDir, "..", "package.json"),
+  ];
+
+  for (const candidate of candidates) {
+    try {
+      const data = await fs.promises.readFile(candidate);
+      const json = JSON.parse(data.toString("utf8"));
+      if (json && json.name === "@aikidosec/safe-chain" && json.version) {
+        cachedVersion = /** @type {string} */ (json.version);
+        return cachedVersion;
+      }
+    } catch {
+      // Try the next location
+    }
+  }
+
+  return "0.0.0";
+}
+
+/**
+ * Falls back to __dirname when import.meta.url is unavailable (pkg CJS binary).
+ * @param {string | undefined} moduleUrl
+ * @returns {string}
+ */
+function resolveModuleDir(moduleUrl) {
+  if (moduleUrl) {
+    return path.dirname(fileURLToPath(moduleUrl));
+  }
+  // eslint-disable-next-line no-undef
+  return __dirname;
+}
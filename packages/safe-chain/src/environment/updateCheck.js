@@ -0,0 +1,115 @@
+import fs from "fs";
+import path from "path";
+import * as semver from "semver";
+import { fetchLatestSafeChainVersion } from "../api/safeChainReleases.js";
+import { getSafeChainDirectory } from "../config/configFile.js";
+import {
+  getCheckUpdates,
+  getNoTelemetry,
+  getOffline,
+} from "../config/settings.js";
+import { getSafeChainVersion } from "./safeChainVersion.js";
+import { ui } from "./userInteraction.js";
+
+const UPDATE_CHECK_INTERVAL_MS = 24 * 60 * 60 * 1000;
+
+/**
+ * Warns when a newer safe-chain release is available. Only runs when enabled with
+ * --safe-chain-check-updates. The latest version is cached for a day so short-lived
+ * invocations don't query the releases endpoint every time. Never updates safe-chain
+ * itself and stays silent when the check fails (e.g. offline). Never runs with
+ * --safe-chain-no-telemetry or --safe-chain-offline.
+ *
+ * @returns {Promise<void>}
+ */
+export async function checkForUpdates() {
+  if (!getCheckUpdates() || getNoTelemetry() || getOffline()) {
+    return;
+  }
+
+  try {
+    const latestVersion = await getLatestVersion();
+    const currentVersion = await getSafeChainVersion();
+
+    if (isNewerVersion(latestVersion, currentVersion)) {
+      ui.writeWarning(
+        `Safe-chain: A newer version of safe-chain is available (current: ${currentVersion}, latest: ${semver.clean(
+          /** @type {string} */ (latestVersion)
+        )}). See https://github.com/AikidoSec/safe-chain/releases`
+      );
+    }
+  } catch (/** @type {any} */ error) {
+    ui.writeVerbose(`Safe-chain: Could not check for updates: ${error.message}`);
+  }
+}
+
+/**
+ * @param {string | undefined} latestVersion
+ * @param {string} currentVersion
+ * @returns {boolean}
+ */
+export function isNewerVersion(latestVersion, currentVersion) {
+  const latest = latestVersion ? semver.clean(latestVersion) : null;
+  const current = semver.clean(currentVersion);
+  if (!latest || !current) {
+    return false;
+  }
+
+  return semver.gt(latest, current);
+}
+
+/**
+ * @returns {Promise<string | undefined>}
+ */
+async function getLatestVersion() {
+  const cached = readCachedLatestVersion();
+  if (cached && Date.now() - cached.checkedAt < UPDATE_CHECK_INTERVAL_MS) {
+    return cached.latestVersion;
+  }
+
+  const latestVersion = await fetchLatestSafeChainVersion();
+  writeCachedLatestVersion(latestVersion);
+  return latestVersion;
+}
+
+/**
+ * @returns {{latestVersion: string | undefined, checkedAt: number} | undefined}
+ */
+function readCachedLatestVersion() {
+  try {
+    const cachePath = getUpdateCheckCachePath();
+    if (!fs.existsSync(cachePath)) {
+      return undefined;
+    }
+
+    const cached = JSON.parse(fs.readFileSync(cachePath, "utf8"));
+    if (typeof cached.checkedAt !== "number") {
+      return undefined;
+    }
+    return cached;
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * @param {string | undefined} latestVersion
+ * @returns {void}
+ */
+function writeCachedLatestVersion(latestVersion) {
+  try {
+    fs.writeFileSync(
+      getUpdateCheckCachePath(),
+      JSON.stringify({ latestVersion, checkedAt: Date.now() })
+    );
+  } catch {
+    // The check will simply run again on the next invocation
+  }
+}
+
+/**
+ * @returns {string}
+ */
+function getUpdateCheckCachePath() {
+  return path.join(getSafeChainDirectory(), "updateCheck.json");
+}
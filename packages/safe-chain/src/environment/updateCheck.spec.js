@@ -0,0 +1,109 @@
+import { describe, it, mock, beforeEach, afterEach } from "node:test";
+import assert from "node:assert";
+import fs from "node:fs";
+import os from "node:os";
+import path from "node:path";
+
+describe("updateCheck", async () => {
+  let safeChainDir;
+  let checkUpdates = true;
+  let noTelemetry = false;
+  let currentVersion = "1.4.7";
+  const fetchLatestSafeChainVersion = mock.fn(async () => "1.5.13");
+  const warnings = [];
+
+  mock.module("../api/safeChainReleases.js", {
+    namedExports: { fetchLatestSafeChainVersion },
+  });
+
+  mock.module("../config/configFile.js", {
+    namedExports: { getSafeChainDirectory: () => safeChainDir },
+  });
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getCheckUpdates: () => checkUpdates,
+      getNoTelemetry: () => noTelemetry,
+      getOffline: () => false,
+    },
+  });
+
+  mock.module("./safeChainVersion.js", {
+    namedExports: { getSafeChainVersion: async () => currentVersion },
+  });
+
+  mock.module("./userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeWarning: (message) => warnings.push(message),
+        writeVerbose: () => {},
+      },
+    },
+  });
+
+  const { checkForUpdates, isNewerVersion } = await import("./updateCheck.js");
+
+  beforeEach(() => {
+    safeChainDir = fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-update-"));
+    checkUpdates = true;
+    noTelemetry = false;
+    currentVersion = "1.4.7";
+    warnings.length = 0;
+    fetchLatestSafeChainVersion.mock.resetCalls();
+    fetchLatestSafeChainVersion.mock.mockImplementation(async () => "1.5.13");
+  });
+
+  afterEach(() => {
+    fs.rmSync(safeChainDir, { recursive: true, force: true });
+  });
+
+  it("warns with the current and latest version when outdated", async () => {
+    await checkForUpdates();
+
+    assert.strictEqual(warnings.length, 1);
+    assert.ok(warnings[0].includes("current: 1.4.7"));
+    assert.ok(warnings[0].includes("latest: 1.5.13"));
+  });
+
+  it("does nothing unless enabled", async () => {
+    checkUpdates = false;
+
+    await checkForUpdates();
+
+    assert.strictEqual(fetchLatestSafeChainVersion.mock.calls.length, 0);
+    assert.strictEqual(warnings.length, 0);
+  });
+
+  it("does nothing with telemetry turned off, even when enabled", async () => {
+    noTelemetry = true;
+
+    await checkForUpdates();
+
+    assert.strictEqual(fetchLatestSafeChainVersion.mock.calls.length, 0);
+  });
+
+  it("stays silent when the check fails", async () => {
+    fetchLatestSafeChainVersion.mock.mockImplementation(async () => {
+      throw new Error("getaddrinfo ENOTFOUND api.github.com");
+    });
+
+    await checkForUpdates();
+
+    assert.strictEqual(warnings.length, 0);
+  });
+
+  it("caches the latest version between invocations", async () => {
+    await checkForUpdates();
+    await checkForUpdates();
+
+    assert.strictEqual(fetchLatestSafeChainVersion.mock.calls.length, 1);
+    assert.strictEqual(warnings.length, 2);
+  });
+
+  it("compares versions with an optional v prefix", () => {
+    assert.strictEqual(isNewerVersion("v1.5.0", "1.4.7"), true);
+    assert.strictEqual(isNewerVersion("1.4.7", "1.4.7"), false);
+    assert.strictEqual(isNewerVersion("1.3.2", "1.4.7"), false);
+    assert.strictEqual(isNewerVersion(undefined, "1.4.7"), false);
+  });
+});
@@ -3,17 +3,24 @@ import chalk from "chalk";
 import { isCi } from "./environment.js";
 import {
   getLoggingLevel,
+  LOGGING_DECISIONS,
   LOGGING_SILENT,
   LOGGING_VERBOSE,
 } from "../config/settings.js";
 import { writeToLogFile } from "./fileLogger.js";
 
+// Verbose lines beyond this are not kept while the package manager runs, so a
+// very large install does not hold every line in memory
+const MAX_BUFFERED_VERBOSE_MESSAGES = 10000;
+
 /**
- * @type {{ bufferOutput: boolean, bufferedMessages:(() => void)[]}}
+ * @type {{ bufferOutput: boolean, bufferedMessages:(() => void)[], bufferedVerboseCount: number, droppedVerboseCount: number}}
  */
 const state = {
   bufferOutput: false,
   bufferedMessages: [],
+  bufferedVerboseCount: 0,
+  droppedVerboseCount: 0,
 };
 
 function isSilentMode() {
@@ -24,8 +31,12 @@ function isVerboseMode() {
   return getLoggingLevel() === LOGGING_VERBOSE;
 }
 
+function isDecisionsMode() {
+  return getLoggingLevel() === LOGGING_DECISIONS;
+}
+
 function emptyLine() {
-  if (isSilentMode()) return;
+  if (isSilentMode() || isDecisionsMode()) return;
 
   writeOrBuffer(() => console.log(""));
 }
@@ -38,6 +49,23 @@ function emptyLine() {
 function writeInformation(message, ...optionalParams) {
   writeToLogFile("info", message, ...optionalParams);
 
+  if (isSilentMode() || isDecisionsMode()) return;
+
+  writeOrBuffer(() => console.log(message, ...optionalParams));
+}
+
+/**
+ * Writes a line that is part of a block decision: the packages safe-chain
+ * blocked and how to resolve it. Unlike writeInformation, these lines are
+ * kept with `--safe-chain-logging=decisions`.
+ *
+ * @param {string} message
+ * @param {...any} optionalParams
+ * @returns {void}
+ */
+function writeDecision(message, ...optionalParams) {
+  writeToLogFile("decision", message, ...optionalParams);
+
   if (isSilentMode()) return;
 
   writeOrBuffer(() => console.log(message, ...optionalParams));
@@ -62,7 +90,7 @@ function writeWarning(message, ...optionalParams) {
  * @returns {void}
  */
 function writeWarningToConsole(message, ...optionalParams) {
-  if (isSilentMode()) return;
+  if (isSilentMode() || isDecisionsMode()) return;
 
   if (!isCi()) {
     message = chalk.yellow(message);
@@ -104,6 +132,14 @@ function writeVerbose(message, ...optionalParams) {
 
   if (!isVerboseMode()) return;
 
+  if (state.bufferOutput) {
+    // Still written to the log file above
+    if (state.bufferedVerboseCount >= MAX_BUFFERED_VERBOSE_MESSAGES) {
+      state.droppedVerboseCount++;
+      return;
+    }
+    state.bufferedVerboseCount++;
+  }
   writeOrBuffer(() => console.log(message, ...optionalParams));
 }
 
@@ -122,6 +158,8 @@ function writeOrBuffer(messageFunction) {
 function startBufferingLogs() {
   state.bufferOutput = true;
   state.bufferedMessages = [];
+  state.bufferedVerboseCount = 0;
+  state.droppedVerboseCount = 0;
 }
 
 function writeBufferedLogsAndStopBuffering() {
@@ -130,11 +168,19 @@ function writeBufferedLogsAndStopBuffering() {
     log();
   }
   state.bufferedMessages = [];
+  if (state.droppedVerboseCount > 0) {
+    console.log(
+      `Safe-chain: ${state.droppedVerboseCount} more verbose lines were not shown. Use --safe-chain-log-file to keep all of them.`
+    );
+  }
+  state.bufferedVerboseCount = 0;
+  state.droppedVerboseCount = 0;
 }
 
 export const ui = {
   writeVerbose,
   writeInformation,
+  writeDecision,
   writeWarning,
   writeWarningToConsole,
   writeError,
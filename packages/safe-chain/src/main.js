@@ -4,7 +4,15 @@ import { scanCommand, shouldScanCommand } from "./scanning/index.js";
 import { ui } from "./environment/userInteraction.js";
 import { getPackageManager } from "./packagemanager/currentPackageManager.js";
 import { initializeCliArguments } from "./config/cliArguments.js";
-import { getLogFile } from "./config/settings.js";
+import {
+  getBlockingDisabled,
+  getFailOnClockSkew,
+  getInspectOnly,
+  getLogFile,
+  getMaxLifetimeSecs,
+  getTraceBody,
+  getUseTestFixture,
+} from "./config/settings.js";
 import { createSafeChainProxy } from "./registryProxy/registryProxy.js";
 import chalk from "chalk";
 import { getAuditStats } from "./scanning/audit/index.js";
@@ -13,6 +21,16 @@ import {
   closeFileLogger,
   closeFileLoggerSync,
 } from "./environment/fileLogger.js";
+import { checkForUpdates } from "./environment/updateCheck.js";
+import { validateConfigFile } from "./config/configFile.js";
+import {
+  EXIT_CODE_CLOCK_SKEW,
+  EXIT_CODE_GENERAL_ERROR,
+  getExitCodeForError,
+  SafeChainStartupError,
+} from "./utils/exitCodes.js";
+import { describeClockSkew, isClockSkewed } from "./environment/clockSkew.js";
+import { reloadLocalDenylist } from "./scanning/localDenylist.js";
 
 /**
  * @param {string[]} args
@@ -27,6 +45,7 @@ export async function main(args) {
   process.on("SIGTERM", handleProcessTermination);
 
   const proxy = createSafeChainProxy();
+  proxy.onLifetimeExpired(handleLifetimeExpired);
 
   // Global error handlers to log unhandled errors
   process.on("uncaughtException", (error) => {
@@ -50,6 +69,31 @@ export async function main(args) {
   try {
     // This parses all the --safe-chain arguments and removes them from the args array
     args = initializeCliArguments(args);
+    registerHangupHandler();
+
+    validateConfigFile();
+
+    if (getTraceBody()) {
+      ui.writeWarning(
+        "Safe-chain: Body tracing is enabled. Small registry request and response bodies are written to the verbose log. Use this for debugging only."
+      );
+    }
+
+    if (getBlockingDisabled()) {
+      writeProtectionOffWarning();
+    } else if (getInspectOnly()) {
+      ui.writeWarning(
+        "Safe-chain: Inspect-only mode is on. Every decision is recorded, but nothing is blocked."
+      );
+    }
+
+    if (getUseTestFixture()) {
+      ui.writeWarning(
+        "Safe-chain: test-fixture - the malware list is replaced by the test fixture. Only the safe-chain test packages are blocked."
+      );
+    }
+
+    await checkForUpdates();
 
     // Only start the proxy for commands that actually download packages.
     // Lifecycle-script commands (run, test, start, etc.) don't download packages
@@ -68,7 +112,7 @@ export async function main(args) {
       initializeFileLogger(logFile, ui.writeWarningToConsole);
     }
 
-    if (shouldScanCommand(args)) {
+    if (!getBlockingDisabled() && shouldScanCommand(args)) {
       const commandScanResult = await scanCommand(args);
 
       // Returning the exit code back to the caller allows the promise
@@ -78,6 +122,14 @@ export async function main(args) {
       }
     }
 
+    // The feed responses fetched so far carry the server time
+    if (getFailOnClockSkew() && isClockSkewed()) {
+      throw new SafeChainStartupError(
+        EXIT_CODE_CLOCK_SKEW,
+        `${describeClockSkew()}. Fix the system clock or remove --safe-chain-fail-on-clock-skew.`
+      );
+    }
+
     // Buffer logs during package manager execution, this avoids interleaving
     //  of logs from the package manager and safe-chain
     // Not doing this could cause bugs to disappear when cursor movement codes
@@ -88,11 +140,59 @@ export async function main(args) {
     // Write all buffered logs
     ui.writeBufferedLogsAndStopBuffering();
 
-    if (proxy.hasBlockedMaliciousPackages()) {
-      return 1;
+    if (getBlockingDisabled()) {
+      writeProtectionOffWarning();
     }
 
-    if (proxy.hasBlockedMinimumAgeRequests()) {
+    if (getInspectOnly()) {
+      // Report everything that would have been blocked, without failing the run
+      proxy.hasBlockedMaliciousPackages();
+      proxy.hasBlockedMinimumAgeRequests();
+      proxy.hasBlockedHostRequests();
+      proxy.hasBlockedUnknownHostRequests();
+      proxy.hasBlockedPrereleaseRequests();
+      proxy.hasBlockedInstallScriptRequests();
+      proxy.hasBlockedSizeAnomalyRequests();
+      proxy.hasBlockedVcsDependencyRequests();
+    } else {
+      if (proxy.hasBlockedMaliciousPackages()) {
+        return 1;
+      }
+
+      if (proxy.hasBlockedMinimumAgeRequests()) {
+        return 1;
+      }
+
+      if (proxy.hasBlockedHostRequests()) {
+        return 1;
+      }
+
+      if (proxy.hasBlockedUnknownHostRequests()) {
+        return 1;
+      }
+
+      if (proxy.hasBlockedPrereleaseRequests()) {
+        return 1;
+      }
+
+      if (proxy.hasBlockedInstallScriptRequests()) {
+        return 1;
+      }
+
+      if (proxy.hasBlockedSizeAnomalyRequests()) {
+        return 1;
+      }
+
+      if (proxy.hasBlockedVcsDependencyRequests()) {
+        return 1;
+      }
+    }
+
+    if (getFailOnClockSkew() && isClockSkewed()) {
+      // Registry responses received during the install showed a skewed clock
+      ui.writeError(
+        `Safe-chain: ${describeClockSkew()}, so minimum package age checks during this install may be wrong.`
+      );
       return 1;
     }
 
@@ -105,11 +205,27 @@ export async function main(args) {
       );
     }
 
+    const cacheStats = proxy.getResponseCacheStats();
+    if (cacheStats.hits + cacheStats.misses > 0) {
+      ui.writeVerbose(
+        `Safe-chain: Response cache: ${cacheStats.hits} hit(s), ${cacheStats.misses} miss(es).`,
+      );
+    }
+
+    const disconnectStats = proxy.getClientDisconnectStats();
+    if (disconnectStats.abortedTransfers > 0) {
+      ui.writeVerbose(
+        `Safe-chain: ${disconnectStats.abortedTransfers} transfer(s) aborted by the client.`,
+      );
+    }
+
     if (proxy.hasSuppressedVersions()) {
       ui.writeInformation(
         `${chalk.yellow(
           "ℹ",
-        )} Safe-chain: Some package versions were suppressed during package metadata resolution due to minimum package age.`,
+        )} Safe-chain: Some package versions ${
+          getInspectOnly() ? "would have been" : "were"
+        } suppressed during package metadata resolution due to minimum package age.`,
       );
       ui.writeInformation(
         `  To disable this check, use: ${chalk.cyan(
@@ -122,12 +238,16 @@ export async function main(args) {
     //  to be awaited in the bin files and return the correct exit code
     return packageManagerResult.status;
   } catch (/** @type any */ error) {
-    ui.writeError("Failed to check for malicious packages:", error.message);
+    if (error instanceof SafeChainStartupError) {
+      ui.writeError(`Safe-chain: ${error.message}`);
+    } else {
+      ui.writeError("Failed to check for malicious packages:", error.message);
+    }
     ui.writeBufferedLogsAndStopBuffering();
 
     // Returning the exit code back to the caller allows the promise
     //  to be awaited in the bin files and return the correct exit code
-    return 1;
+    return getExitCodeForError(error);
   } finally {
     // Both must run even if one throws. Losing the session-end entry
     // because stopServer() rejected (or vice versa) defeats the point of
@@ -136,11 +256,56 @@ export async function main(args) {
   }
 }
 
+/**
+ * Written as an error so it shows up with every logging level, including
+ * silent: nobody should be able to miss that a run was unprotected.
+ */
+function writeProtectionOffWarning() {
+  ui.writeError(
+    chalk.bold(
+      "Safe-chain: PROTECTION IS OFF. Blocking is disabled with --safe-chain-no-block or SAFE_CHAIN_DISABLE_BLOCKING: packages are installed without any malware, minimum age or deny list checks.",
+    ),
+  );
+}
+
 function handleProcessTermination() {
   ui.writeBufferedLogsAndStopBuffering();
   closeFileLoggerSync();
 }
 
+function handleLifetimeExpired() {
+  ui.writeBufferedLogsAndStopBuffering();
+  closeFileLoggerSync();
+  process.exit(EXIT_CODE_GENERAL_ERROR);
+}
+
+/**
+ * A hangup ends safe-chain like any other command, unless the proxy runs with
+ * a maximum lifetime: such a long-lived proxy reloads the local denylist
+ * instead, so imported entries apply without a restart.
+ */
+function registerHangupHandler() {
+  if (getMaxLifetimeSecs() !== undefined) {
+    process.on("SIGHUP", handleReloadSignal);
+  } else {
+    process.once("SIGHUP", handleHangup);
+  }
+}
+
+function handleHangup() {
+  handleProcessTermination();
+  // The listener is removed, so the re-raised signal terminates the process
+  // as the default action would
+  process.kill(process.pid, "SIGHUP");
+}
+
+function handleReloadSignal() {
+  const entryCount = reloadLocalDenylist();
+  ui.writeInformation(
+    `Safe-chain: Reloaded the local package denylist (${entryCount} entries).`,
+  );
+}
+
 /** @param {string[]} args  */
 function isSafeChainVerify(args) {
   const safeChainCheckCommand = "safe-chain-verify";
@@ -0,0 +1,57 @@
+import { after, before, describe, it } from "node:test";
+import assert from "node:assert";
+import { spawn } from "child_process";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import { fileURLToPath } from "url";
+
+const aikidoNpm = fileURLToPath(
+  new URL("../bin/aikido-npm.js", import.meta.url)
+);
+
+describe("main", { skip: process.platform === "win32" }, () => {
+  let projectDir = "";
+
+  before(() => {
+    projectDir = fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-main-"));
+    fs.writeFileSync(
+      path.join(projectDir, "package.json"),
+      JSON.stringify({
+        scripts: { wait: "echo started && sleep 30" },
+      })
+    );
+  });
+
+  after(() => {
+    fs.rmSync(projectDir, { recursive: true, force: true });
+  });
+
+  it("ends on a hangup like the command it wraps", async () => {
+    // In a process group of its own, so npm and the script can be cleaned up
+    const safeChain = spawn(process.execPath, [aikidoNpm, "run", "wait"], {
+      cwd: projectDir,
+      detached: true,
+      stdio: ["ignore", "pipe", "ignore"],
+    });
+    const exited = new Promise((resolve) =>
+      safeChain.on("exit", (code, signal) => resolve({ code, signal }))
+    );
+
+    // npm prints the output of the script once safe-chain is running it
+    await new Promise((resolve) =>
+      safeChain.stdout.on("data", (data) => {
+        if (String(data).includes("started")) {
+          resolve(undefined);
+        }
+      })
+    );
+    safeChain.kill("SIGHUP");
+
+    try {
+      assert.deepStrictEqual(await exited, { code: null, signal: "SIGHUP" });
+    } finally {
+      process.kill(-safeChain.pid, "SIGKILL");
+    }
+  });
+});
@@ -0,0 +1,211 @@
+import * as crypto from "crypto";
+import { getAdminToken } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import { getFeedVersion } from "../scanning/feedVersion.js";
+import { refreshMalwareDatabase } from "../scanning/malwareDatabase.js";
+import { evaluateRequest } from "./replay.js";
+
+export const ADMIN_PATH_PREFIX = "/admin/";
+export const RELOAD_FEED_PATH = "/admin/reload-feed";
+export const EXPLAIN_PATH = "/admin/explain";
+
+/**
+ * @type {Map<string, {method: string, handle: (url: URL, res: import("http").ServerResponse) => Promise<void>}>}
+ */
+const ADMIN_ROUTES = new Map([
+  [
+    RELOAD_FEED_PATH,
+    { method: "POST", handle: (_url, res) => reloadFeed(res) },
+  ],
+  [EXPLAIN_PATH, { method: "GET", handle: explain }],
+]);
+
+/**
+ * Serves the admin endpoints on the proxy port, for operations such as pushing
+ * an urgent malware list update into a running proxy or explaining one of its
+ * decisions. They work on every platform, unlike signals, and are only served
+ * when an admin token is set.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("http").ServerResponse} res
+ * @returns {boolean} - Whether the request was for an admin endpoint
+ */
+export function handleAdminRequest(req, res) {
+  const token = getAdminToken();
+  if (!token || !req.url?.startsWith(ADMIN_PATH_PREFIX)) {
+    return false;
+  }
+
+  // The endpoints do not read a body
+  req.resume();
+
+  if (!isAuthorized(req.headers.authorization, token)) {
+    ui.writeWarning(
+      `Safe-chain: admin.unauthorized - rejected ${req.method} ${req.url} without a valid admin token`
+    );
+    writeJson(
+      res,
+      401,
+      { status: "unauthorized" },
+      { "www-authenticate": 'Bearer realm="safe-chain"' }
+    );
+    return true;
+  }
+
+  const url = new URL(req.url, "http://localhost");
+  const route = ADMIN_ROUTES.get(url.pathname);
+  if (!route) {
+    writeJson(res, 404, { status: "not_found" });
+    return true;
+  }
+  if (req.method !== route.method) {
+    writeJson(
+      res,
+      405,
+      { status: "method_not_allowed" },
+      { allow: route.method }
+    );
+    return true;
+  }
+
+  route.handle(url, res).catch((/** @type {any} */ error) => {
+    ui.writeError(
+      `Safe-chain: admin.error - ${req.method} ${url.pathname} failed: ${error.message}`
+    );
+    if (!res.headersSent) {
+      writeJson(res, 500, { status: "error", error: error.message });
+    } else {
+      res.destroy();
+    }
+  });
+  return true;
+}
+
+/**
+ * @param {import("http").ServerResponse} res
+ * @returns {Promise<void>}
+ */
+async function reloadFeed(res) {
+  const previousVersion = getFeedVersion() ?? null;
+  try {
+    const database = await refreshMalwareDatabase();
+    ui.writeInformation(
+      `Safe-chain: admin.reload-feed - reloaded the malware list, version ${database.version ?? "unknown"} with ${database.entryCount} entries (was ${previousVersion ?? "unknown"})`
+    );
+    writeJson(res, 200, {
+      status: "ok",
+      previous_version: previousVersion,
+      version: database.version ?? null,
+      entry_count: database.entryCount,
+    });
+  } catch (/** @type {any} */ error) {
+    ui.writeError(
+      `Safe-chain: admin.reload-feed - could not reload the malware list, keeping version ${previousVersion ?? "unknown"}: ${error.message}`
+    );
+    writeJson(res, 502, {
+      status: "error",
+      error: error.message,
+      previous_version: previousVersion,
+      version: previousVersion,
+    });
+  }
+}
+
+/**
+ * Evaluates the URL in the url query parameter against the policy the proxy
+ * runs with, without sending the request: the live counterpart of
+ * `safe-chain replay`.
+ *
+ * @param {URL} url
+ * @param {import("http").ServerResponse} res
+ * @returns {Promise<void>}
+ */
+async function explain(url, res) {
+  const targetUrl = url.searchParams.get("url");
+  if (!targetUrl || !isHttpUrl(targetUrl)) {
+    writeJson(res, 400, {
+      status: "bad_request",
+      error:
+        "Pass the request to explain as an absolute http or https URL in the url query parameter",
+    });
+    return;
+  }
+
+  try {
+    const result = await evaluateRequest(targetUrl, {
+      detectEcosystem: false,
+    });
+    writeJson(res, 200, {
+      status: "ok",
+      url: result.url,
+      feed_version: getFeedVersion() ?? null,
+      ecosystem: result.ecosystem ?? null,
+      package: result.packageName
+        ? { name: result.packageName, version: result.version ?? null }
+        : null,
+      rules: result.rules.map((rule) => ({
+        name: rule.name,
+        outcome: rule.outcome,
+        input: rule.input ?? {},
+      })),
+      decision: result.blockResponse ? "blocked" : "allowed",
+      block_response: result.blockResponse ?? null,
+    });
+  } catch (/** @type {any} */ error) {
+    writeJson(res, 500, { status: "error", error: error.message });
+  }
+}
+
+/**
+ * @param {string} value
+ * @returns {boolean}
+ */
+function isHttpUrl(value) {
+  try {
+    const { protocol } = new URL(value);
+    return protocol === "https:" || protocol === "http:";
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Compares digests, so neither the content nor the length of the token can be
+ * learned from response times.
+ *
+ * @param {string | undefined} authorization
+ * @param {string} token
+ * @returns {boolean}
+ */
+function isAuthorized(authorization, token) {
+  const match = /^Bearer\s+(.+)$/i.exec(authorization ?? "");
+  if (!match) {
+    return false;
+  }
+
+  return crypto.timingSafeEqual(hash(match[1].trim()), hash(token));
+}
+
+/**
+ * @param {string} value
+ * @returns {Buffer}
+ */
+function hash(value) {
+  return crypto.createHash("sha256").update(value).digest();
+}
+
+/**
+ * @param {import("http").ServerResponse} res
+ * @param {number} statusCode
+ * @param {object} body
+ * @param {Record<string, string>} [headers]
+ * @returns {void}
+ */
+function writeJson(res, statusCode, body, headers = {}) {
+  res.writeHead(statusCode, {
+    "content-type": "application/json",
+    "cache-control": "no-store",
+    ...headers,
+  });
+  res.end(JSON.stringify(body));
+}
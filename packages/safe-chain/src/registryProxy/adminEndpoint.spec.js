@@ -0,0 +1,234 @@
+import { after, afterEach, before, describe, it, mock } from "node:test";
+import assert from "node:assert";
+import http from "http";
+
+describe("adminEndpoint", async () => {
+  /** @type {Error | undefined} */
+  let refreshError;
+  let refreshCount = 0;
+  /** @type {Error | undefined} */
+  let writeErrorFailure;
+  /** @type {string[]} */
+  const evaluatedUrls = [];
+
+  mock.module("../scanning/malwareDatabase.js", {
+    namedExports: {
+      refreshMalwareDatabase: async () => {
+        refreshCount++;
+        if (refreshError) {
+          throw refreshError;
+        }
+        recordFeedVersion("v2");
+        return { version: "v2", entryCount: 42 };
+      },
+    },
+  });
+  mock.module("./replay.js", {
+    namedExports: {
+      evaluateRequest: async (url, options) => {
+        evaluatedUrls.push(url);
+        assert.deepStrictEqual(options, { detectEcosystem: false });
+        return {
+          url,
+          ecosystem: "js",
+          packageName: "safe-chain-test",
+          version: "0.0.1-security",
+          blockResponse: "403 Forbidden - blocked by safe-chain",
+          rules: [
+            {
+              name: "host deny list",
+              outcome: "passed",
+              input: { host: "registry.npmjs.org" },
+            },
+            { name: "interception", outcome: "inspected" },
+            {
+              name: "minimum package age",
+              outcome: "passed or not evaluated",
+              input: { enabled: true, minimum_age_hours: 24, age_hours: 30 },
+            },
+          ],
+        };
+      },
+    },
+  });
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeError: () => {
+          const error = writeErrorFailure;
+          writeErrorFailure = undefined;
+          if (error) {
+            throw error;
+          }
+        },
+        writeInformation: () => {},
+        writeWarning: () => {},
+      },
+    },
+  });
+
+  const { recordFeedVersion, resetFeedVersion } = await import(
+    "../scanning/feedVersion.js"
+  );
+  const { handleAdminRequest } = await import("./adminEndpoint.js");
+
+  /** @type {http.Server} */
+  let server;
+  let baseUrl = "";
+
+  before(async () => {
+    server = http.createServer((req, res) => {
+      if (!handleAdminRequest(req, res)) {
+        res.writeHead(400);
+        res.end("not an admin request");
+      }
+    });
+    await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+    baseUrl = `http://127.0.0.1:${server.address().port}`;
+  });
+
+  after(() => new Promise((resolve) => server.close(resolve)));
+
+  afterEach(() => {
+    delete process.env.SAFE_CHAIN_ADMIN_TOKEN;
+    resetFeedVersion();
+    refreshError = undefined;
+    writeErrorFailure = undefined;
+    refreshCount = 0;
+    evaluatedUrls.length = 0;
+  });
+
+  /**
+   * @param {string} [token]
+   * @param {string} [method]
+   */
+  function reloadFeed(token, method = "POST") {
+    return fetch(`${baseUrl}/admin/reload-feed`, {
+      method,
+      headers: token ? { Authorization: `Bearer ${token}` } : {},
+    });
+  }
+
+  it("is not served without an admin token", async () => {
+    const response = await reloadFeed("anything");
+
+    assert.strictEqual(response.status, 400);
+    assert.strictEqual(refreshCount, 0);
+  });
+
+  it("rejects requests without the admin token", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+
+    const missing = await reloadFeed();
+    const wrong = await reloadFeed("s3cret-but-longer");
+
+    assert.strictEqual(missing.status, 401);
+    assert.strictEqual(wrong.status, 401);
+    assert.match(missing.headers.get("www-authenticate") ?? "", /^Bearer/);
+    assert.strictEqual(refreshCount, 0);
+  });
+
+  it("reloads the feed and returns the versions before and after", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+    recordFeedVersion("v1");
+
+    const response = await reloadFeed("s3cret");
+
+    assert.strictEqual(response.status, 200);
+    assert.deepStrictEqual(await response.json(), {
+      status: "ok",
+      previous_version: "v1",
+      version: "v2",
+      entry_count: 42,
+    });
+  });
+
+  it("keeps the feed in use when it cannot be reloaded", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+    recordFeedVersion("v1");
+    refreshError = new Error("malware list unavailable");
+
+    const response = await reloadFeed("s3cret");
+
+    assert.strictEqual(response.status, 502);
+    assert.deepStrictEqual(await response.json(), {
+      status: "error",
+      error: "malware list unavailable",
+      previous_version: "v1",
+      version: "v1",
+    });
+  });
+
+  it("answers 500 when an endpoint fails unexpectedly", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+    refreshError = new Error("malware list unavailable");
+    writeErrorFailure = new Error("log file is not writable");
+
+    const response = await reloadFeed("s3cret");
+
+    assert.strictEqual(response.status, 500);
+    assert.deepStrictEqual(await response.json(), {
+      status: "error",
+      error: "log file is not writable",
+    });
+  });
+
+  it("only reloads on POST", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+
+    const response = await reloadFeed("s3cret", "GET");
+
+    assert.strictEqual(response.status, 405);
+    assert.strictEqual(response.headers.get("allow"), "POST");
+    assert.strictEqual(refreshCount, 0);
+  });
+
+  it("explains the decision for a URL with the rule inputs", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+    recordFeedVersion("v1");
+    const targetUrl =
+      "https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz";
+
+    const response = await fetch(
+      `${baseUrl}/admin/explain?url=${encodeURIComponent(targetUrl)}`,
+      { headers: { Authorization: "Bearer s3cret" } }
+    );
+
+    assert.strictEqual(response.status, 200);
+    assert.deepStrictEqual(evaluatedUrls, [targetUrl]);
+    assert.deepStrictEqual(await response.json(), {
+      status: "ok",
+      url: targetUrl,
+      feed_version: "v1",
+      ecosystem: "js",
+      package: { name: "safe-chain-test", version: "0.0.1-security" },
+      rules: [
+        {
+          name: "host deny list",
+          outcome: "passed",
+          input: { host: "registry.npmjs.org" },
+        },
+        { name: "interception", outcome: "inspected", input: {} },
+        {
+          name: "minimum package age",
+          outcome: "passed or not evaluated",
+          input: { enabled: true, minimum_age_hours: 24, age_hours: 30 },
+        },
+      ],
+      decision: "blocked",
+      block_response: "403 Forbidden - blocked by safe-chain",
+    });
+  });
+
+  it("requires an absolute URL to explain", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+
+    const response = await fetch(`${baseUrl}/admin/explain?url=lodash`, {
+      headers: { Authorization: "Bearer s3cret" },
+    });
+
+    assert.strictEqual(response.status, 400);
+    assert.strictEqual((await response.json()).status, "bad_request");
+    assert.deepStrictEqual(evaluatedUrls, []);
+  });
+});
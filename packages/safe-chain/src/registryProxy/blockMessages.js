@@ -0,0 +1,68 @@
+import { getBlockMessageTemplates } from "../config/settings.js";
+
+const DEFAULT_TEMPLATE_KEY = "default";
+
+/**
+ * @typedef {import("./interceptors/interceptorBuilder.js").BlockResponse} BlockResponse
+ *
+ * @typedef {Object} BlockMessageBody
+ * @property {string} body
+ * @property {string} contentType
+ */
+
+/**
+ * Renders the body of a block response from the template configured for its
+ * block reason, or the "default" template. The placeholders {package},
+ * {version}, {reason} and {message} are replaced, and HTML-escaped in HTML
+ * templates. Without a template the body is the block message itself.
+ *
+ * @param {BlockResponse} blockResponse
+ * @returns {BlockMessageBody}
+ */
+export function renderBlockMessage(blockResponse) {
+  const templates = getBlockMessageTemplates();
+  const template =
+    (blockResponse.reason ? templates[blockResponse.reason] : undefined) ??
+    templates[DEFAULT_TEMPLATE_KEY];
+
+  if (!template) {
+    return {
+      body: blockResponse.message,
+      contentType: "text/plain; charset=utf-8",
+    };
+  }
+
+  /** @type {Record<string, string>} */
+  const values = {
+    package: blockResponse.packageName ?? "",
+    version: blockResponse.version ?? "",
+    reason: blockResponse.reason ?? "",
+    message: blockResponse.message,
+  };
+  const escape = template.format === "html" ? escapeHtml : String;
+  const body = template.template.replace(
+    /\{(package|version|reason|message)\}/g,
+    (_, name) => escape(values[name])
+  );
+
+  return {
+    body,
+    contentType:
+      template.format === "html"
+        ? "text/html; charset=utf-8"
+        : "text/plain; charset=utf-8",
+  };
+}
+
+/**
+ * @param {string} value
+ * @returns {string}
+ */
+function escapeHtml(value) {
+  return value
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;")
+    .replace(/'/g, "&#39;");
+}
@@ -0,0 +1,60 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("blockMessages", async () => {
+  let templates = {};
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getBlockMessageTemplates: () => templates,
+    },
+  });
+
+  const { renderBlockMessage } = await import("./blockMessages.js");
+
+  const blockResponse = {
+    statusCode: 403,
+    message: "Forbidden - blocked by safe-chain",
+    headers: {},
+    reason: "malware",
+    packageName: "<evil>",
+    version: "1.0.0",
+  };
+
+  beforeEach(() => {
+    templates = {};
+  });
+
+  it("uses the block message without a template", () => {
+    assert.deepStrictEqual(renderBlockMessage(blockResponse), {
+      body: "Forbidden - blocked by safe-chain",
+      contentType: "text/plain; charset=utf-8",
+    });
+  });
+
+  it("fills in the placeholders of the template for the block reason", () => {
+    templates = {
+      malware: {
+        template: "{package}@{version} was blocked ({reason}): {message}",
+        format: "text",
+      },
+      default: { template: "blocked", format: "text" },
+    };
+
+    assert.deepStrictEqual(renderBlockMessage(blockResponse), {
+      body: "<evil>@1.0.0 was blocked (malware): Forbidden - blocked by safe-chain",
+      contentType: "text/plain; charset=utf-8",
+    });
+  });
+
+  it("escapes the values in HTML templates and falls back to the default", () => {
+    templates = {
+      default: { template: "<h1>{package} {unknown}</h1>", format: "html" },
+    };
+
+    assert.deepStrictEqual(renderBlockMessage(blockResponse), {
+      body: "<h1>&lt;evil&gt; {unknown}</h1>",
+      contentType: "text/html; charset=utf-8",
+    });
+  });
+});
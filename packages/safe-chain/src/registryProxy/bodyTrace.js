@@ -0,0 +1,116 @@
+import { getTraceBody } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+export const TRACE_BODY_MAX_BYTES = 8 * 1024;
+
+const TEXT_CONTENT_TYPE_PATTERN =
+  /json|xml|html|^text\/|x-www-form-urlencoded/i;
+
+const SECRET_PATTERNS = [
+  // JSON properties such as "_authToken", "password", "client_secret" or "_auth"
+  {
+    pattern:
+      /("(?:[^"]*(?:token|password|secret)[^"]*|_auth|authorization)"\s*:\s*)"(?:[^"\\]|\\.)*"/gi,
+    replacement: '$1"[REDACTED]"',
+  },
+  // Form fields with the same names
+  {
+    pattern:
+      /((?:^|&)(?:[^=&]*(?:token|password|secret)[^=&]*|_auth|authorization)=)[^&]*/gi,
+    replacement: "$1[REDACTED]",
+  },
+  { pattern: /(Bearer|Basic)\s+[A-Za-z0-9._~+/=-]+/g, replacement: "$1 [REDACTED]" },
+  { pattern: /npm_[A-Za-z0-9]{36}/g, replacement: "[REDACTED]" },
+  { pattern: /pypi-[A-Za-z0-9_-]{16,}/g, replacement: "[REDACTED]" },
+];
+
+/**
+ * Replaces credentials in a traced body with a placeholder.
+ *
+ * @param {string} text
+ * @returns {string}
+ */
+export function redactSecrets(text) {
+  return SECRET_PATTERNS.reduce(
+    (redacted, { pattern, replacement }) =>
+      redacted.replace(pattern, replacement),
+    text
+  );
+}
+
+/**
+ * Writes a body to the verbose log when body tracing is enabled. Bodies over
+ * TRACE_BODY_MAX_BYTES and non-text bodies (such as tarballs) are never logged.
+ *
+ * @param {"request" | "response"} direction
+ * @param {string} targetUrl
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @param {Buffer} body
+ * @param {number} [totalBytes] - The full body size, when only a prefix was collected
+ * @returns {void}
+ */
+export function traceBody(
+  direction,
+  targetUrl,
+  headers,
+  body,
+  totalBytes = body.byteLength
+) {
+  if (!getTraceBody() || totalBytes === 0) {
+    return;
+  }
+
+  const prefix = `Safe-chain: trace.body ${direction} ${targetUrl} (${totalBytes} bytes)`;
+  const contentType = String(headers["content-type"] ?? "");
+  if (!TEXT_CONTENT_TYPE_PATTERN.test(contentType)) {
+    ui.writeVerbose(
+      `${prefix} not logged: content type "${contentType}" is not text`
+    );
+    return;
+  }
+  if (totalBytes > TRACE_BODY_MAX_BYTES) {
+    ui.writeVerbose(
+      `${prefix} not logged: larger than ${TRACE_BODY_MAX_BYTES} bytes`
+    );
+    return;
+  }
+
+  ui.writeVerbose(`${prefix}\n${redactSecrets(body.toString("utf8"))}`);
+}
+
+/**
+ * Collects a streamed body for traceBody, keeping at most TRACE_BODY_MAX_BYTES in memory.
+ *
+ * @param {"request" | "response"} direction
+ * @param {string} targetUrl
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @returns {{add: (chunk: Buffer) => void, end: () => void}}
+ */
+export function createBodyTracer(direction, targetUrl, headers) {
+  if (!getTraceBody()) {
+    return { add: () => {}, end: () => {} };
+  }
+
+  /** @type {Buffer[]} */
+  const chunks = [];
+  let collectedBytes = 0;
+  let totalBytes = 0;
+
+  return {
+    add: (chunk) => {
+      totalBytes += chunk.byteLength;
+      if (collectedBytes <= TRACE_BODY_MAX_BYTES) {
+        chunks.push(chunk);
+        collectedBytes += chunk.byteLength;
+      }
+    },
+    end: () =>
+      traceBody(
+        direction,
+        targetUrl,
+        headers,
+        Buffer.concat(chunks),
+        totalBytes
+      ),
+  };
+}
@@ -0,0 +1,99 @@
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * A Map that keeps the most recently used entries only, so details remembered
+ * from package metadata cannot grow without bound during a very large install.
+ *
+ * @template V
+ * @typedef {Object} BoundedCache
+ * @property {(key: string) => V | undefined} get - Also marks the entry as recently used
+ * @property {(key: string, value: V) => void} set - Evicts the least recently used entry when full
+ * @property {(key: string) => boolean} has
+ * @property {() => void} clear
+ * @property {() => number} size
+ */
+
+/**
+ * @typedef {Object} BoundedCacheSize
+ * @property {string} name
+ * @property {number} size
+ * @property {number} evictions
+ */
+
+/**
+ * @type {{maxEntries: number, caches: Map<string, {size: () => number, evictions: () => number}>}}
+ */
+const state = {
+  maxEntries: Infinity,
+  caches: new Map(),
+};
+
+/**
+ * Sets the limit for all bounded caches. Called when the proxy starts, so
+ * requests do not read the setting. Until then the caches are not bounded.
+ *
+ * @param {number} maxEntries
+ * @returns {void}
+ */
+export function initializeBoundedCaches(maxEntries) {
+  state.maxEntries = maxEntries;
+}
+
+/**
+ * @template V
+ * @param {string} name - Reported in metrics and log lines, for example "publish_times"
+ * @returns {BoundedCache<V>}
+ */
+export function createBoundedCache(name) {
+  // A Map iterates in insertion order: the first key is the least recently used
+  /** @type {Map<string, V>} */
+  const entries = new Map();
+  let evictions = 0;
+
+  state.caches.set(name, {
+    size: () => entries.size,
+    evictions: () => evictions,
+  });
+
+  return {
+    get(key) {
+      const value = entries.get(key);
+      if (value !== undefined) {
+        entries.delete(key);
+        entries.set(key, value);
+      }
+      return value;
+    },
+    set(key, value) {
+      entries.delete(key);
+      entries.set(key, value);
+      while (entries.size > state.maxEntries) {
+        const oldestKey = /** @type {string} */ (entries.keys().next().value);
+        entries.delete(oldestKey);
+        if (evictions === 0) {
+          ui.writeVerbose(
+            `Safe-chain: cache.evicting - the ${name} cache holds ${state.maxEntries} entries, forgetting the least recently used ones. Raise --safe-chain-max-tracked-packages to keep more.`
+          );
+        }
+        evictions++;
+      }
+    },
+    has: (key) => entries.has(key),
+    clear() {
+      entries.clear();
+      evictions = 0;
+    },
+    size: () => entries.size,
+  };
+}
+
+/**
+ * @returns {BoundedCacheSize[]}
+ */
+export function getBoundedCacheSizes() {
+  return [...state.caches].map(([name, cache]) => ({
+    name,
+    size: cache.size(),
+    evictions: cache.evictions(),
+  }));
+}
@@ -0,0 +1,58 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import {
+  createBoundedCache,
+  getBoundedCacheSizes,
+  initializeBoundedCaches,
+} from "./boundedCache.js";
+
+describe("boundedCache", () => {
+  afterEach(() => {
+    initializeBoundedCaches(Infinity);
+  });
+
+  it("is not bounded until the proxy sets the limit", () => {
+    const cache = createBoundedCache("unbounded_test");
+    for (let i = 0; i < 100; i++) {
+      cache.set(`package-${i}`, i);
+    }
+
+    assert.strictEqual(cache.size(), 100);
+  });
+
+  it("evicts the least recently used entries once full", () => {
+    initializeBoundedCaches(2);
+    const cache = createBoundedCache("lru_test");
+
+    cache.set("lodash", 1);
+    cache.set("express", 2);
+    // Reading lodash makes express the least recently used entry
+    assert.strictEqual(cache.get("lodash"), 1);
+    cache.set("react", 3);
+
+    assert.strictEqual(cache.size(), 2);
+    assert.strictEqual(cache.has("express"), false);
+    assert.strictEqual(cache.get("lodash"), 1);
+    assert.strictEqual(cache.get("react"), 3);
+  });
+
+  it("reports sizes and evictions per cache", () => {
+    initializeBoundedCaches(1);
+    const cache = createBoundedCache("size_test");
+
+    cache.set("lodash", 1);
+    cache.set("lodash", 2);
+    cache.set("express", 3);
+
+    assert.deepStrictEqual(
+      getBoundedCacheSizes().find(({ name }) => name === "size_test"),
+      { name: "size_test", size: 1, evictions: 1 }
+    );
+
+    cache.clear();
+    assert.deepStrictEqual(
+      getBoundedCacheSizes().find(({ name }) => name === "size_test"),
+      { name: "size_test", size: 0, evictions: 0 }
+    );
+  });
+});
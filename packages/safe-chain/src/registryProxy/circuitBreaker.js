@@ -0,0 +1,146 @@
+import { ui } from "../environment/userInteraction.js";
+import {
+  getCircuitBreakerCooldownSeconds,
+  getCircuitBreakerThreshold,
+} from "../config/settings.js";
+
+/**
+ * @typedef {Object} HostCircuit
+ * @property {number} consecutiveFailures
+ * @property {number | undefined} openedAt - When the circuit opened, undefined while closed
+ */
+
+// Only gateway errors mean the host itself is unavailable. Other 5xx responses
+// are regular (if unhelpful) answers from a working registry.
+const FAILURE_STATUS_CODES = new Set([502, 503, 504]);
+
+/** @type {Map<string, HostCircuit>} */
+const circuits = new Map();
+
+/**
+ * Returns how many seconds requests to the host are still short-circuited, or
+ * undefined when the request may go upstream. Once the cooldown has passed,
+ * requests go through again as probes: a success closes the circuit and a
+ * failure opens it for another cooldown.
+ *
+ * @param {string} host
+ * @param {number} [now]
+ * @returns {number | undefined}
+ */
+export function getCircuitRetryAfterSeconds(host, now = Date.now()) {
+  const circuit = circuits.get(host);
+  if (!circuit || circuit.openedAt === undefined) {
+    return undefined;
+  }
+
+  const remainingMs =
+    circuit.openedAt + getCircuitBreakerCooldownSeconds() * 1000 - now;
+  if (remainingMs <= 0) {
+    return undefined;
+  }
+
+  return Math.ceil(remainingMs / 1000);
+}
+
+/**
+ * Short-circuits the request with a 503 when the circuit of the host is open.
+ *
+ * @param {string} host
+ * @param {import("http").ServerResponse} res
+ * @returns {boolean} - true when the response was written
+ */
+export function rejectIfCircuitOpen(host, res) {
+  const retryAfterSeconds = getCircuitRetryAfterSeconds(host);
+  if (retryAfterSeconds === undefined) {
+    return false;
+  }
+
+  ui.writeVerbose(
+    `Safe-chain: Short-circuiting request to ${host}, retry after ${retryAfterSeconds}s`
+  );
+  res.writeHead(503, "Service Unavailable", {
+    "Retry-After": String(retryAfterSeconds),
+  });
+  res.end(`Service Unavailable: ${host} is failing, retry after ${retryAfterSeconds}s`);
+  return true;
+}
+
+/**
+ * Short-circuits an upgrade request with a 503 on the raw client socket when
+ * the circuit of the host is open.
+ *
+ * @param {string} host
+ * @param {import("stream").Duplex} clientSocket
+ * @returns {boolean} - true when the response was written
+ */
+export function rejectUpgradeIfCircuitOpen(host, clientSocket) {
+  const retryAfterSeconds = getCircuitRetryAfterSeconds(host);
+  if (retryAfterSeconds === undefined) {
+    return false;
+  }
+
+  ui.writeVerbose(
+    `Safe-chain: Short-circuiting upgrade to ${host}, retry after ${retryAfterSeconds}s`
+  );
+  clientSocket.end(
+    `HTTP/1.1 503 Service Unavailable\r\nRetry-After: ${retryAfterSeconds}\r\nConnection: close\r\n\r\nService Unavailable: ${host} is failing, retry after ${retryAfterSeconds}s`
+  );
+  return true;
+}
+
+/**
+ * Records the outcome of an upstream request. Pass the response status code, or
+ * undefined when no response was received (connection error).
+ *
+ * @param {string} host
+ * @param {number | undefined} statusCode
+ * @param {number} [now]
+ * @returns {void}
+ */
+export function recordUpstreamResult(host, statusCode, now = Date.now()) {
+  if (statusCode !== undefined && !FAILURE_STATUS_CODES.has(statusCode)) {
+    recordUpstreamSuccess(host);
+    return;
+  }
+
+  const circuit = circuits.get(host) ?? {
+    consecutiveFailures: 0,
+    openedAt: undefined,
+  };
+  circuit.consecutiveFailures++;
+  circuits.set(host, circuit);
+
+  if (circuit.consecutiveFailures < getCircuitBreakerThreshold()) {
+    return;
+  }
+
+  const wasOpen = circuit.openedAt !== undefined;
+  circuit.openedAt = now;
+  if (!wasOpen) {
+    ui.writeWarning(
+      `Safe-chain: circuit.open - ${host} failed ${circuit.consecutiveFailures} times in a row, requests to it fail fast for ${getCircuitBreakerCooldownSeconds()}s.`
+    );
+  }
+}
+
+/**
+ * @param {string} host
+ * @returns {void}
+ */
+function recordUpstreamSuccess(host) {
+  const circuit = circuits.get(host);
+  if (!circuit) {
+    return;
+  }
+
+  circuits.delete(host);
+  if (circuit.openedAt !== undefined) {
+    ui.writeInformation(
+      `Safe-chain: circuit.close - ${host} is responding again.`
+    );
+  }
+}
+
+export function resetCircuitBreakers() {
+  circuits.clear();
+}
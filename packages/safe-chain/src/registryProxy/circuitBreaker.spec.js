@@ -0,0 +1,137 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("circuitBreaker", async () => {
+  const warnings = [];
+  const informationMessages = [];
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getCircuitBreakerThreshold: () => 3,
+      getCircuitBreakerCooldownSeconds: () => 30,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeWarning: (message) => warnings.push(message),
+        writeInformation: (message) => informationMessages.push(message),
+      },
+    },
+  });
+
+  const {
+    getCircuitRetryAfterSeconds,
+    recordUpstreamResult,
+    rejectIfCircuitOpen,
+    rejectUpgradeIfCircuitOpen,
+    resetCircuitBreakers,
+  } = await import("./circuitBreaker.js");
+
+  const host = "registry.npmjs.org";
+  const now = 1_000_000;
+
+  /**
+   * @param {number} count
+   * @param {number | undefined} statusCode
+   */
+  function fail(count, statusCode = undefined) {
+    for (let i = 0; i < count; i++) {
+      recordUpstreamResult(host, statusCode, now);
+    }
+  }
+
+  beforeEach(() => {
+    resetCircuitBreakers();
+    warnings.length = 0;
+    informationMessages.length = 0;
+  });
+
+  it("opens after the configured number of consecutive failures", () => {
+    fail(2);
+    assert.strictEqual(getCircuitRetryAfterSeconds(host, now), undefined);
+
+    fail(1, 503);
+    assert.strictEqual(getCircuitRetryAfterSeconds(host, now), 30);
+    assert.strictEqual(getCircuitRetryAfterSeconds(host, now + 20_500), 10);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(warnings[0], /circuit\.open - registry\.npmjs\.org failed 3 times/);
+  });
+
+  it("resets the failure count on a successful response", () => {
+    fail(2);
+    recordUpstreamResult(host, 404, now);
+    fail(2);
+
+    assert.strictEqual(getCircuitRetryAfterSeconds(host, now), undefined);
+    assert.strictEqual(warnings.length, 0);
+  });
+
+  it("lets probes through after the cooldown and closes on success", () => {
+    fail(3);
+
+    assert.strictEqual(getCircuitRetryAfterSeconds(host, now + 30_000), undefined);
+
+    recordUpstreamResult(host, 200, now + 30_000);
+    assert.strictEqual(getCircuitRetryAfterSeconds(host, now + 30_000), undefined);
+    assert.deepStrictEqual(informationMessages, [
+      "Safe-chain: circuit.close - registry.npmjs.org is responding again.",
+    ]);
+  });
+
+  it("reopens without a new warning when a probe fails", () => {
+    fail(3);
+    recordUpstreamResult(host, 502, now + 30_000);
+
+    assert.strictEqual(getCircuitRetryAfterSeconds(host, now + 30_000), 30);
+    assert.strictEqual(warnings.length, 1);
+  });
+
+  it("tracks hosts independently", () => {
+    fail(3);
+
+    assert.strictEqual(getCircuitRetryAfterSeconds("files.pythonhosted.org", now), undefined);
+  });
+
+  it("responds with 503 and Retry-After while open", () => {
+    recordUpstreamResult(host, undefined);
+    recordUpstreamResult(host, undefined);
+    recordUpstreamResult(host, undefined);
+
+    let written;
+    const res = {
+      writeHead: (statusCode, statusMessage, headers) => {
+        written = { statusCode, statusMessage, headers };
+      },
+      end: () => {},
+    };
+
+    assert.strictEqual(rejectIfCircuitOpen(host, res), true);
+    assert.strictEqual(written.statusCode, 503);
+    assert.strictEqual(written.headers["Retry-After"], "30");
+    assert.strictEqual(rejectIfCircuitOpen("pypi.org", res), false);
+  });
+
+  it("answers an upgrade with 503 and Retry-After on the socket while open", () => {
+    recordUpstreamResult(host, undefined);
+    recordUpstreamResult(host, undefined);
+    recordUpstreamResult(host, undefined);
+
+    let written;
+    const clientSocket = {
+      end: (data) => {
+        written = data;
+      },
+    };
+
+    assert.strictEqual(rejectUpgradeIfCircuitOpen(host, clientSocket), true);
+    assert.match(written, /^HTTP\/1\.1 503 Service Unavailable\r\n/);
+    assert.match(written, /\r\nRetry-After: 30\r\n/);
+    assert.strictEqual(
+      rejectUpgradeIfCircuitOpen("pypi.org", clientSocket),
+      false
+    );
+  });
+});
@@ -0,0 +1,49 @@
+import { ui } from "../environment/userInteraction.js";
+
+// Error codes Node reports when the peer goes away (for example when the user
+// presses Ctrl+C during an install). These are expected and not worth an error.
+const CLIENT_DISCONNECT_ERROR_CODES = [
+  "ECONNRESET",
+  "ECONNABORTED",
+  "EPIPE",
+  "ERR_STREAM_PREMATURE_CLOSE",
+];
+
+const state = {
+  abortedTransfers: 0,
+};
+
+/**
+ * @param {unknown} err
+ * @returns {boolean}
+ */
+export function isClientDisconnectError(err) {
+  if (!(err instanceof Error)) {
+    return false;
+  }
+  const code = /** @type {NodeJS.ErrnoException} */ (err).code;
+  return code !== undefined && CLIENT_DISCONNECT_ERROR_CODES.includes(code);
+}
+
+/**
+ * Records a transfer that was aborted because the client disconnected before it
+ * finished. Logged at verbose level with a `client.disconnect` event so it does
+ * not drown out real upstream errors.
+ *
+ * @param {string} target
+ * @param {string} [reason]
+ * @returns {void}
+ */
+export function recordClientDisconnect(target, reason) {
+  state.abortedTransfers++;
+  ui.writeVerbose(
+    `Safe-chain: client.disconnect ${target}${reason ? ` (${reason})` : ""}`
+  );
+}
+
+/**
+ * @returns {{abortedTransfers: number}}
+ */
+export function getClientDisconnectStats() {
+  return { abortedTransfers: state.abortedTransfers };
+}
@@ -0,0 +1,52 @@
+import { describe, it, mock } from "node:test";
+import assert from "node:assert";
+
+describe("clientDisconnects", async () => {
+  const verboseMessages = [];
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: (message) => verboseMessages.push(message),
+      },
+    },
+  });
+
+  const {
+    isClientDisconnectError,
+    recordClientDisconnect,
+    getClientDisconnectStats,
+  } = await import("./clientDisconnects.js");
+
+  it("recognizes errors caused by the client going away", () => {
+    const reset = Object.assign(new Error("read ECONNRESET"), {
+      code: "ECONNRESET",
+    });
+    const pipe = Object.assign(new Error("write EPIPE"), { code: "EPIPE" });
+
+    assert.strictEqual(isClientDisconnectError(reset), true);
+    assert.strictEqual(isClientDisconnectError(pipe), true);
+  });
+
+  it("does not treat other errors as client disconnects", () => {
+    const refused = Object.assign(new Error("connect ECONNREFUSED"), {
+      code: "ECONNREFUSED",
+    });
+
+    assert.strictEqual(isClientDisconnectError(refused), false);
+    assert.strictEqual(isClientDisconnectError(new Error("boom")), false);
+    assert.strictEqual(isClientDisconnectError(undefined), false);
+  });
+
+  it("counts aborted transfers and logs them at verbose level", () => {
+    const before = getClientDisconnectStats().abortedTransfers;
+
+    recordClientDisconnect("https://registry.npmjs.org/lodash", "aborted");
+
+    assert.strictEqual(getClientDisconnectStats().abortedTransfers, before + 1);
+    assert.strictEqual(
+      verboseMessages.at(-1),
+      "Safe-chain: client.disconnect https://registry.npmjs.org/lodash (aborted)"
+    );
+  });
+});
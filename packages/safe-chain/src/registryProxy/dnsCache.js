@@ -0,0 +1,226 @@
+import dns from "dns";
+import * as net from "net";
+import { createBoundedCache } from "./boundedCache.js";
+import {
+  getDnsCacheMaxTtlSecs,
+  getDnsCacheMinTtlSecs,
+  getDnsCacheTtlSecs,
+  getDnsNegativeTtlSecs,
+} from "../config/settings.js";
+
+const SYSTEM_RESOLVER = "system";
+
+// Answers that the hostname does not exist. Failures such as EAI_AGAIN are
+// temporary and are not cached.
+const NEGATIVE_ERROR_CODES = new Set(["ENOTFOUND", "ENODATA"]);
+
+/**
+ * @typedef {{address: string, family: number}} ResolvedAddress
+ * @typedef {{addresses: ResolvedAddress[], ttlSeconds: number}} Resolution
+ *
+ * @typedef {Object} CacheEntry
+ * @property {ResolvedAddress[]} [addresses]
+ * @property {NodeJS.ErrnoException} [error] - Set for a hostname that does not exist
+ * @property {number} expiresAt
+ */
+
+/**
+ * @type {{
+ *   entries: import("./boundedCache.js").BoundedCache<CacheEntry>,
+ *   pending: Map<string, Promise<ResolvedAddress[]>>,
+ *   hits: number,
+ *   negativeHits: number,
+ *   misses: number,
+ * }}
+ */
+const state = {
+  // Bounded like the metadata caches, so a build resolving many distinct
+  // hostnames does not keep every answer it has seen
+  entries: createBoundedCache("dns"),
+  pending: new Map(),
+  hits: 0,
+  negativeHits: 0,
+  misses: 0,
+};
+
+/**
+ * Resolves a hostname through the cache shared by all upstream connections.
+ * Answers are cached for their TTL, clamped to the configured minimum and
+ * maximum, and hostnames that do not exist for the negative TTL. Concurrent
+ * lookups of a hostname share a single query.
+ *
+ * @param {string} resolver - Identifies the resolver, so answers of different resolvers are never mixed
+ * @param {string} hostname
+ * @param {() => Promise<Resolution>} resolve
+ * @returns {Promise<ResolvedAddress[]>}
+ */
+export function resolveCached(resolver, hostname, resolve) {
+  const key = `${resolver}\n${hostname.toLowerCase()}`;
+  const cached = state.entries.get(key);
+  if (cached && cached.expiresAt > Date.now()) {
+    if (cached.error) {
+      state.negativeHits++;
+      return Promise.reject(cached.error);
+    }
+    state.hits++;
+    return Promise.resolve(/** @type {ResolvedAddress[]} */ (cached.addresses));
+  }
+
+  const pending = state.pending.get(key);
+  if (pending) {
+    state.hits++;
+    return pending;
+  }
+
+  state.misses++;
+  const lookup = resolve()
+    .then(({ addresses, ttlSeconds }) => {
+      const cacheSeconds = clampTtlSeconds(ttlSeconds);
+      if (cacheSeconds > 0) {
+        state.entries.set(key, {
+          addresses,
+          expiresAt: Date.now() + cacheSeconds * 1000,
+        });
+      }
+      return addresses;
+    })
+    .catch((/** @type {NodeJS.ErrnoException} */ error) => {
+      const negativeSeconds = getDnsNegativeTtlSecs();
+      if (NEGATIVE_ERROR_CODES.has(error.code ?? "") && negativeSeconds > 0) {
+        state.entries.set(key, {
+          error,
+          expiresAt: Date.now() + negativeSeconds * 1000,
+        });
+      }
+      throw error;
+    })
+    .finally(() => state.pending.delete(key));
+  state.pending.set(key, lookup);
+  return lookup;
+}
+
+/**
+ * @param {number} ttlSeconds
+ * @returns {number}
+ */
+function clampTtlSeconds(ttlSeconds) {
+  return Math.min(
+    Math.max(ttlSeconds, getDnsCacheMinTtlSecs()),
+    getDnsCacheMaxTtlSecs()
+  );
+}
+
+/**
+ * Returns a dns.lookup compatible function that caches the answers of the
+ * system resolver, or undefined when caching is turned off so Node looks every
+ * hostname up itself.
+ *
+ * @returns {import("net").LookupFunction | undefined}
+ */
+export function getSystemCachingLookup() {
+  if (getDnsCacheTtlSecs() === 0) {
+    return undefined;
+  }
+
+  return createLookup((hostname) =>
+    net.isIP(hostname)
+      ? Promise.resolve([{ address: hostname, family: net.isIP(hostname) }])
+      : resolveCached(SYSTEM_RESOLVER, hostname, () => lookupAll(hostname))
+  );
+}
+
+/**
+ * @param {string} hostname
+ * @returns {Promise<Resolution>}
+ */
+function lookupAll(hostname) {
+  return new Promise((resolve, reject) => {
+    dns.lookup(hostname, { all: true }, (error, addresses) => {
+      if (error) {
+        reject(error);
+        return;
+      }
+      resolve({ addresses, ttlSeconds: getDnsCacheTtlSecs() });
+    });
+  });
+}
+
+/**
+ * Adapts a function resolving all addresses of a hostname to the dns.lookup
+ * signature that sockets and agents call.
+ *
+ * @param {(hostname: string) => Promise<ResolvedAddress[]>} resolveAddresses
+ * @returns {import("net").LookupFunction}
+ */
+export function createLookup(resolveAddresses) {
+  // @ts-expect-error - the options argument is optional in dns.lookup, but not in the LookupFunction type
+  return (hostname, options, callback) => {
+    if (typeof options === "function") {
+      callback = options;
+      options = {};
+    }
+
+    resolveAddresses(hostname).then(
+      (resolved) => {
+        const family = normalizeFamily(options?.family);
+        const addresses = family
+          ? resolved.filter((entry) => entry.family === family)
+          : resolved;
+
+        if (addresses.length === 0) {
+          callback(createNotFoundError(hostname), "", 0);
+          return;
+        }
+
+        if (options?.all) {
+          // @ts-expect-error - with `all: true` dns.lookup passes the address list instead of a single address
+          callback(null, addresses);
+        } else {
+          callback(null, addresses[0].address, addresses[0].family);
+        }
+      },
+      (/** @type {NodeJS.ErrnoException} */ error) => callback(error, "", 0)
+    );
+  };
+}
+
+/**
+ * @param {string} hostname
+ * @returns {NodeJS.ErrnoException}
+ */
+export function createNotFoundError(hostname) {
+  /** @type {NodeJS.ErrnoException} */
+  const error = new Error(`getaddrinfo ENOTFOUND ${hostname}`);
+  error.code = "ENOTFOUND";
+  return error;
+}
+
+/**
+ * @param {number | string | undefined} family
+ * @returns {number}
+ */
+function normalizeFamily(family) {
+  if (family === "IPv4") return 4;
+  if (family === "IPv6") return 6;
+  return typeof family === "number" ? family : 0;
+}
+
+/**
+ * @returns {{hits: number, negativeHits: number, misses: number, size: number}}
+ */
+export function getDnsCacheStats() {
+  return {
+    hits: state.hits,
+    negativeHits: state.negativeHits,
+    misses: state.misses,
+    size: state.entries.size(),
+  };
+}
+
+export function clearDnsCache() {
+  state.entries.clear();
+  state.pending.clear();
+  state.hits = 0;
+  state.negativeHits = 0;
+  state.misses = 0;
+}
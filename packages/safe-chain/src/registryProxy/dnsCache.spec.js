@@ -0,0 +1,162 @@
+import { describe, it, mock, afterEach, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("dnsCache", async () => {
+  let minTtlSecs = 0;
+  let maxTtlSecs = 300;
+  let negativeTtlSecs = 5;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getDnsCacheTtlSecs: () => 30,
+      getDnsCacheMinTtlSecs: () => minTtlSecs,
+      getDnsCacheMaxTtlSecs: () => maxTtlSecs,
+      getDnsNegativeTtlSecs: () => negativeTtlSecs,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: { writeVerbose: () => {} },
+    },
+  });
+
+  const { clearDnsCache, getDnsCacheStats, resolveCached } = await import(
+    "./dnsCache.js"
+  );
+  const { initializeBoundedCaches } = await import("./boundedCache.js");
+
+  const addresses = [{ address: "203.0.113.10", family: 4 }];
+
+  beforeEach(() => {
+    minTtlSecs = 0;
+    maxTtlSecs = 300;
+    negativeTtlSecs = 5;
+    clearDnsCache();
+  });
+
+  afterEach(() => {
+    initializeBoundedCaches(Infinity);
+  });
+
+  /**
+   * @param {number} ttlSeconds
+   */
+  function countingResolver(ttlSeconds) {
+    const resolver = {
+      queries: 0,
+      resolve: async () => {
+        resolver.queries++;
+        return { addresses, ttlSeconds };
+      },
+    };
+    return resolver;
+  }
+
+  it("caches answers for their TTL and counts hits", async () => {
+    const resolver = countingResolver(60);
+
+    await resolveCached("system", "registry.example.com", resolver.resolve);
+    const cached = await resolveCached(
+      "system",
+      "Registry.Example.com",
+      resolver.resolve
+    );
+
+    assert.deepStrictEqual(cached, addresses);
+    assert.strictEqual(resolver.queries, 1);
+    assert.deepStrictEqual(getDnsCacheStats(), {
+      hits: 1,
+      negativeHits: 0,
+      misses: 1,
+      size: 1,
+    });
+  });
+
+  it("shares a single query between concurrent lookups", async () => {
+    const resolver = countingResolver(0);
+
+    await Promise.all([
+      resolveCached("system", "registry.example.com", resolver.resolve),
+      resolveCached("system", "registry.example.com", resolver.resolve),
+    ]);
+
+    assert.strictEqual(resolver.queries, 1);
+  });
+
+  it("clamps the TTL to the configured minimum and maximum", async () => {
+    minTtlSecs = 10;
+    const shortTtl = countingResolver(0);
+    await resolveCached("system", "short.example.com", shortTtl.resolve);
+    await resolveCached("system", "short.example.com", shortTtl.resolve);
+    assert.strictEqual(shortTtl.queries, 1);
+
+    minTtlSecs = 0;
+    maxTtlSecs = 0;
+    const longTtl = countingResolver(3600);
+    await resolveCached("system", "long.example.com", longTtl.resolve);
+    await resolveCached("system", "long.example.com", longTtl.resolve);
+    assert.strictEqual(longTtl.queries, 2);
+  });
+
+  it("caches hostnames that do not exist for the negative TTL", async () => {
+    let queries = 0;
+    const resolve = async () => {
+      queries++;
+      throw Object.assign(new Error("getaddrinfo ENOTFOUND missing"), {
+        code: "ENOTFOUND",
+      });
+    };
+
+    for (let attempt = 0; attempt < 2; attempt++) {
+      await assert.rejects(
+        () => resolveCached("system", "missing.example.com", resolve),
+        (err) => err.code === "ENOTFOUND"
+      );
+    }
+
+    assert.strictEqual(queries, 1);
+    assert.strictEqual(getDnsCacheStats().negativeHits, 1);
+  });
+
+  it("does not cache temporary resolver failures", async () => {
+    let queries = 0;
+    const resolve = async () => {
+      queries++;
+      throw Object.assign(new Error("getaddrinfo EAI_AGAIN registry"), {
+        code: "EAI_AGAIN",
+      });
+    };
+
+    for (let attempt = 0; attempt < 2; attempt++) {
+      await assert.rejects(() =>
+        resolveCached("system", "registry.example.com", resolve)
+      );
+    }
+
+    assert.strictEqual(queries, 2);
+  });
+
+  it("keeps the answers of different resolvers apart", async () => {
+    const system = countingResolver(60);
+    const doh = countingResolver(60);
+
+    await resolveCached("system", "registry.example.com", system.resolve);
+    await resolveCached("https://dns.example/dns-query", "registry.example.com", doh.resolve);
+
+    assert.strictEqual(doh.queries, 1);
+  });
+
+  it("forgets the least recently used hostnames once full", async () => {
+    initializeBoundedCaches(2);
+    const resolver = countingResolver(60);
+
+    await resolveCached("system", "a.example.com", resolver.resolve);
+    await resolveCached("system", "b.example.com", resolver.resolve);
+    await resolveCached("system", "c.example.com", resolver.resolve);
+    assert.strictEqual(getDnsCacheStats().size, 2);
+
+    await resolveCached("system", "a.example.com", resolver.resolve);
+    assert.strictEqual(resolver.queries, 4);
+  });
+});
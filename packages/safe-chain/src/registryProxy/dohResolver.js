@@ -0,0 +1,199 @@
+import * as http from "http";
+import * as https from "https";
+import * as net from "net";
+import { getDohResolver } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import {
+  clearDnsCache,
+  createLookup,
+  createNotFoundError,
+  getSystemCachingLookup,
+  resolveCached,
+} from "./dnsCache.js";
+import { getUpstreamBindOptions } from "./upstreamBind.js";
+
+const DNS_TYPE_A = 1;
+const DNS_TYPE_AAAA = 28;
+const DNS_STATUS_NOERROR = 0;
+const DOH_REQUEST_TIMEOUT_MS = 5000;
+
+/**
+ * @typedef {import("./dnsCache.js").ResolvedAddress} ResolvedAddress
+ */
+
+/** @type {Set<string>} */
+const invalidResolverWarnings = new Set();
+
+/**
+ * Returns a dns.lookup compatible function that resolves upstream hostnames
+ * through the configured DNS-over-HTTPS resolver. Without a resolver, hostnames
+ * are resolved by the system resolver through the DNS cache, or by Node itself
+ * when the cache is turned off.
+ *
+ * @returns {import("net").LookupFunction | undefined}
+ */
+export function getUpstreamLookup() {
+  const resolverUrl = getDohResolver();
+  if (!resolverUrl) {
+    return getSystemCachingLookup();
+  }
+
+  if (!isValidResolverUrl(resolverUrl)) {
+    if (!invalidResolverWarnings.has(resolverUrl)) {
+      invalidResolverWarnings.add(resolverUrl);
+      ui.writeWarning(
+        `Safe-chain: Ignoring invalid DNS-over-HTTPS resolver URL "${resolverUrl}", using the system resolver instead.`
+      );
+    }
+    return getSystemCachingLookup();
+  }
+
+  return createDohLookup(resolverUrl);
+}
+
+/**
+ * @param {string} resolverUrl
+ * @returns {import("net").LookupFunction}
+ */
+export function createDohLookup(resolverUrl) {
+  return createLookup((hostname) => resolveWithDoh(resolverUrl, hostname));
+}
+
+/**
+ * Resolves a hostname to its A and AAAA records using the DoH JSON API
+ * (application/dns-json). Results are cached for the shortest TTL of the
+ * returned records, within the limits of the DNS cache.
+ *
+ * @param {string} resolverUrl
+ * @param {string} hostname
+ * @returns {Promise<ResolvedAddress[]>}
+ */
+export async function resolveWithDoh(resolverUrl, hostname) {
+  const ipFamily = net.isIP(hostname);
+  if (ipFamily) {
+    return [{ address: hostname, family: ipFamily }];
+  }
+
+  return resolveCached(resolverUrl, hostname, () =>
+    queryAddresses(resolverUrl, hostname)
+  );
+}
+
+/**
+ * @param {string} resolverUrl
+ * @param {string} hostname
+ * @returns {Promise<import("./dnsCache.js").Resolution>}
+ */
+async function queryAddresses(resolverUrl, hostname) {
+  const [ipv4Answers, ipv6Answers] = await Promise.all([
+    queryDoh(resolverUrl, hostname, DNS_TYPE_A),
+    queryDoh(resolverUrl, hostname, DNS_TYPE_AAAA),
+  ]);
+  const answers = [...ipv4Answers, ...ipv6Answers];
+
+  if (answers.length === 0) {
+    throw createNotFoundError(hostname);
+  }
+
+  const addresses = answers.map((answer) => ({
+    address: answer.data,
+    family: answer.type === DNS_TYPE_A ? 4 : 6,
+  }));
+  const ttlSeconds = Math.min(...answers.map((answer) => answer.TTL));
+
+  ui.writeVerbose(
+    `Safe-chain: Resolved ${hostname} to ${addresses
+      .map((entry) => entry.address)
+      .join(", ")} via DNS-over-HTTPS (ttl ${ttlSeconds}s)`
+  );
+
+  return { addresses, ttlSeconds };
+}
+
+export function clearDohCache() {
+  clearDnsCache();
+}
+
+/**
+ * @param {string} resolverUrl
+ * @param {string} hostname
+ * @param {number} recordType
+ * @returns {Promise<{type: number, TTL: number, data: string}[]>}
+ */
+function queryDoh(resolverUrl, hostname, recordType) {
+  const url = new URL(resolverUrl);
+  url.searchParams.set("name", hostname);
+  url.searchParams.set("type", String(recordType));
+  const protocol = url.protocol === "http:" ? http : https;
+
+  return new Promise((resolve, reject) => {
+    const request = protocol.get(
+      url,
+      {
+        headers: { accept: "application/dns-json" },
+        ...getUpstreamBindOptions(),
+      },
+      (response) => {
+        /** @type {Buffer[]} */
+        const chunks = [];
+        response.on("data", (chunk) => chunks.push(chunk));
+        response.on("error", reject);
+        response.on("end", () => {
+          if (response.statusCode !== 200) {
+            reject(
+              new Error(
+                `DNS-over-HTTPS resolver returned status ${response.statusCode} for ${hostname}`
+              )
+            );
+            return;
+          }
+
+          try {
+            const body = JSON.parse(Buffer.concat(chunks).toString("utf8"));
+            if (body.Status !== DNS_STATUS_NOERROR || !Array.isArray(body.Answer)) {
+              resolve([]);
+              return;
+            }
+
+            resolve(
+              body.Answer.filter(
+                (/** @type {any} */ answer) =>
+                  answer.type === recordType && typeof answer.data === "string"
+              ).map((/** @type {any} */ answer) => ({
+                type: answer.type,
+                TTL: Number.isFinite(answer.TTL) ? answer.TTL : 0,
+                data: answer.data,
+              }))
+            );
+          } catch (/** @type {any} */ error) {
+            reject(
+              new Error(
+                `Invalid DNS-over-HTTPS response for ${hostname}: ${error.message}`
+              )
+            );
+          }
+        });
+      }
+    );
+
+    request.setTimeout(DOH_REQUEST_TIMEOUT_MS, () => {
+      request.destroy(
+        new Error(`DNS-over-HTTPS query for ${hostname} timed out`)
+      );
+    });
+    request.on("error", reject);
+  });
+}
+
+/**
+ * @param {string} value
+ * @returns {boolean}
+ */
+function isValidResolverUrl(value) {
+  try {
+    const url = new URL(value);
+    return url.protocol === "https:" || url.protocol === "http:";
+  } catch {
+    return false;
+  }
+}
@@ -0,0 +1,114 @@
+import { before, after, beforeEach, describe, it } from "node:test";
+import assert from "node:assert";
+import http from "node:http";
+import {
+  clearDohCache,
+  createDohLookup,
+  resolveWithDoh,
+} from "./dohResolver.js";
+
+describe("dohResolver", () => {
+  let server, resolverUrl;
+  /** @type {URL[]} */
+  let queries = [];
+
+  /** @type {Record<string, {Status: number, Answer?: any[]}>} */
+  const records = {
+    "1:registry.example.com": {
+      Status: 0,
+      Answer: [
+        { name: "registry.example.com", type: 5, TTL: 300, data: "cdn.example.com." },
+        { name: "cdn.example.com", type: 1, TTL: 60, data: "203.0.113.10" },
+      ],
+    },
+    "28:registry.example.com": {
+      Status: 0,
+      Answer: [
+        { name: "registry.example.com", type: 28, TTL: 120, data: "2001:db8::10" },
+      ],
+    },
+    "1:short-ttl.example.com": {
+      Status: 0,
+      Answer: [{ name: "short-ttl.example.com", type: 1, TTL: 0, data: "203.0.113.20" }],
+    },
+  };
+
+  before(async () => {
+    server = http.createServer((req, res) => {
+      const url = new URL(req.url, "http://localhost");
+      queries.push(url);
+      const key = `${url.searchParams.get("type")}:${url.searchParams.get("name")}`;
+      res.writeHead(200, { "content-type": "application/dns-json" });
+      res.end(JSON.stringify(records[key] ?? { Status: 3 }));
+    });
+    await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+    resolverUrl = `http://127.0.0.1:${server.address().port}/dns-query`;
+  });
+
+  after(async () => {
+    await new Promise((resolve) => server.close(resolve));
+  });
+
+  beforeEach(() => {
+    queries = [];
+    clearDohCache();
+  });
+
+  it("resolves A and AAAA records and ignores CNAME answers", async () => {
+    const addresses = await resolveWithDoh(resolverUrl, "registry.example.com");
+
+    assert.deepStrictEqual(addresses, [
+      { address: "203.0.113.10", family: 4 },
+      { address: "2001:db8::10", family: 6 },
+    ]);
+    assert.strictEqual(queries.length, 2);
+  });
+
+  it("caches answers for the shortest TTL", async () => {
+    await resolveWithDoh(resolverUrl, "registry.example.com");
+    await resolveWithDoh(resolverUrl, "registry.example.com");
+    assert.strictEqual(queries.length, 2);
+
+    await resolveWithDoh(resolverUrl, "short-ttl.example.com");
+    await resolveWithDoh(resolverUrl, "short-ttl.example.com");
+    assert.strictEqual(queries.length, 6);
+  });
+
+  it("does not query the resolver for IP literals", async () => {
+    const addresses = await resolveWithDoh(resolverUrl, "127.0.0.1");
+
+    assert.deepStrictEqual(addresses, [{ address: "127.0.0.1", family: 4 }]);
+    assert.strictEqual(queries.length, 0);
+  });
+
+  it("fails with ENOTFOUND when no records exist", async () => {
+    await assert.rejects(
+      () => resolveWithDoh(resolverUrl, "missing.example.com"),
+      (err) => err.code === "ENOTFOUND"
+    );
+  });
+
+  it("lookup honours the requested address family", async () => {
+    const lookup = createDohLookup(resolverUrl);
+
+    const result = await new Promise((resolve, reject) => {
+      lookup("registry.example.com", { family: 6 }, (err, address, family) =>
+        err ? reject(err) : resolve({ address, family })
+      );
+    });
+
+    assert.deepStrictEqual(result, { address: "2001:db8::10", family: 6 });
+  });
+
+  it("lookup returns all addresses when requested", async () => {
+    const lookup = createDohLookup(resolverUrl);
+
+    const result = await new Promise((resolve, reject) => {
+      lookup("registry.example.com", { all: true }, (err, addresses) =>
+        err ? reject(err) : resolve(addresses)
+      );
+    });
+
+    assert.strictEqual(result.length, 2);
+  });
+});
@@ -0,0 +1,128 @@
+import fs from "fs";
+import path from "path";
+import { ui } from "../environment/userInteraction.js";
+import {
+  setConfigFilePathOverride,
+  validateConfigFile,
+} from "../config/configFile.js";
+import { evaluateRequest, getUrlFromAuditLine } from "./replay.js";
+import { loadProxyPolicy } from "./registryProxy.js";
+import { getExitCodeForError } from "../utils/exitCodes.js";
+
+const CANDIDATE_CONFIG_ARG = "--candidate-config=";
+
+/**
+ * @typedef {Object} DecisionChange
+ * @property {string} url
+ * @property {string} decision - Status and message of the block
+ *
+ * @typedef {Object} DecisionDiff
+ * @property {number} evaluated - Distinct request URLs that were evaluated
+ * @property {DecisionChange[]} newlyBlocked - Blocked by the candidate policy only
+ * @property {DecisionChange[]} newlyAllowed - Blocked by the current policy only
+ */
+
+/**
+ * Evaluates request URLs under the current config file and under a candidate
+ * config file, and returns the requests whose decision differs.
+ *
+ * @param {string[]} urls
+ * @param {string} candidateConfigPath
+ * @returns {Promise<DecisionDiff>}
+ */
+export async function diffDecisions(urls, candidateConfigPath) {
+  const distinctUrls = [...new Set(urls)];
+
+  const current = await evaluateRequests(distinctUrls);
+  setConfigFilePathOverride(candidateConfigPath);
+  let candidate;
+  try {
+    validateConfigFile();
+    candidate = await evaluateRequests(distinctUrls);
+  } finally {
+    setConfigFilePathOverride(undefined);
+  }
+
+  /** @type {DecisionDiff} */
+  const diff = {
+    evaluated: distinctUrls.length,
+    newlyBlocked: [],
+    newlyAllowed: [],
+  };
+  for (const url of distinctUrls) {
+    const currentDecision = current.get(url);
+    const candidateDecision = candidate.get(url);
+    if (candidateDecision && !currentDecision) {
+      diff.newlyBlocked.push({ url, decision: candidateDecision });
+    } else if (currentDecision && !candidateDecision) {
+      diff.newlyAllowed.push({ url, decision: currentDecision });
+    }
+  }
+  return diff;
+}
+
+/**
+ * Handles `safe-chain dry-run-diff <FILE> --candidate-config=<CONFIG>`: reads
+ * request URLs from a file with one URL or log line per line, and prints the
+ * decisions that change under the candidate config as JSON.
+ *
+ * @param {string[]} args - The arguments after `dry-run-diff`
+ * @returns {Promise<number>}
+ */
+export async function dryRunDiff(args) {
+  const candidateConfig = args
+    .find((arg) => arg.startsWith(CANDIDATE_CONFIG_ARG))
+    ?.slice(CANDIDATE_CONFIG_ARG.length);
+  const requestsFile = args.find((arg) => !arg.startsWith("--"));
+
+  if (!candidateConfig || !requestsFile) {
+    ui.writeError(
+      "Usage: safe-chain dry-run-diff <FILE> --candidate-config=<CONFIG>"
+    );
+    return 1;
+  }
+
+  const candidateConfigPath = path.resolve(candidateConfig);
+  if (!fs.existsSync(candidateConfigPath)) {
+    ui.writeError(`The candidate config ${candidateConfig} does not exist.`);
+    return 1;
+  }
+
+  /** @type {string[]} */
+  let urls;
+  try {
+    urls = fs
+      .readFileSync(requestsFile, "utf8")
+      .split(/\r?\n/)
+      .map((line) => getUrlFromAuditLine(line.trim()))
+      .filter((url) => url !== undefined);
+  } catch (/** @type {any} */ error) {
+    ui.writeError(`Could not read ${requestsFile}: ${error.message}`);
+    return 1;
+  }
+
+  try {
+    await loadProxyPolicy();
+    const diff = await diffDecisions(urls, candidateConfigPath);
+    ui.writeInformation(JSON.stringify(diff, null, 2));
+  } catch (/** @type {any} */ error) {
+    ui.writeError(`Failed to compare the policies: ${error.message}`);
+    return getExitCodeForError(error);
+  }
+
+  return 0;
+}
+
+/**
+ * @param {string[]} urls
+ * @returns {Promise<Map<string, string | undefined>>} - The block decision per URL
+ */
+async function evaluateRequests(urls) {
+  /** @type {Map<string, string | undefined>} */
+  const decisions = new Map();
+  for (const url of urls) {
+    const result = await evaluateRequest(url);
+    decisions.set(url, result.blockResponse);
+  }
+  return decisions;
+}
@@ -0,0 +1,96 @@
+import { describe, it, mock, beforeEach, after } from "node:test";
+import assert from "node:assert";
+import fs from "fs";
+import os from "os";
+import path from "path";
+
+describe("dryRunDiff", async () => {
+  const output = [];
+  const tempDir = fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-diff-"));
+
+  mock.module("../api/aikido.js", {
+    namedExports: {
+      fetchMalwareDatabase: async () => ({
+        malwareDatabase: [
+          {
+            package_name: "safe-chain-test",
+            version: "0.0.1-security",
+            reason: "MALWARE",
+          },
+        ],
+        version: undefined,
+      }),
+      fetchMalwareDatabaseVersion: async () => undefined,
+      fetchMalwareFeed: async () => [],
+      fetchNewPackagesList: async () => ({
+        newPackagesList: [],
+        version: undefined,
+      }),
+      fetchNewPackagesListVersion: async () => undefined,
+      fetchDeniedHosts: async () => [],
+      fetchFlaggedMaintainers: async () => [],
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeInformation: (message) => output.push(message),
+        writeWarning: () => {},
+        writeError: (message) => output.push(message),
+        writeDecision: () => {},
+        emptyLine: () => {},
+      },
+    },
+  });
+
+  const { dryRunDiff } = await import("./dryRunDiff.js");
+
+  const candidateConfig = path.join(tempDir, "candidate.json");
+  fs.writeFileSync(candidateConfig, JSON.stringify({ blockPrereleases: true }));
+  const requestsFile = path.join(tempDir, "requests.log");
+  fs.writeFileSync(
+    requestsFile,
+    [
+      "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+      "[2026-10-14T09:12:44.120Z] [verbose] Safe-chain: Intercepting https://registry.npmjs.org/next/-/next-15.0.0-canary.1.tgz",
+      "https://registry.npmjs.org/next/-/next-15.0.0-canary.1.tgz",
+      "https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz",
+      "",
+    ].join("\n")
+  );
+
+  beforeEach(() => {
+    output.length = 0;
+  });
+
+  after(() => {
+    fs.rmSync(tempDir, { recursive: true, force: true });
+  });
+
+  it("lists the requests the candidate config blocks and the current one allows", async () => {
+    const exitCode = await dryRunDiff([
+      requestsFile,
+      `--candidate-config=${candidateConfig}`,
+    ]);
+
+    assert.strictEqual(exitCode, 0);
+    assert.deepStrictEqual(JSON.parse(output[0]), {
+      evaluated: 3,
+      newlyBlocked: [
+        {
+          url: "https://registry.npmjs.org/next/-/next-15.0.0-canary.1.tgz",
+          decision:
+            "403 Forbidden - blocked by safe-chain pre-release policy (next@15.0.0-canary.1)",
+        },
+      ],
+      newlyAllowed: [],
+    });
+  });
+
+  it("prints usage without a candidate config", async () => {
+    assert.strictEqual(await dryRunDiff([requestsFile]), 1);
+    assert.match(output[0], /Usage: safe-chain dry-run-diff <FILE>/);
+  });
+});
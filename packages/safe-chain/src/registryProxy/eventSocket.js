@@ -0,0 +1,123 @@
+import fs from "fs";
+import net from "net";
+import {
+  getEcoSystem,
+  getEventSocket,
+  getInspectOnly,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * @typedef {Object} DecisionEvent
+ * @property {"decision"} event
+ * @property {string} ecosystem - "js" or "py"
+ * @property {string} package
+ * @property {string} version
+ * @property {"blocked" | "would-block"} decision - "would-block" in inspect-only mode
+ * @property {string} reason - The block reason, as in the X-Safe-Chain-Reason header
+ * @property {string} url
+ * @property {string} timestamp
+ */
+
+/**
+ * @type {{stream: import("stream").Writable | undefined}}
+ */
+const state = {
+  stream: undefined,
+};
+
+/**
+ * Opens the socket the wrapper listens on, so decisions reach it as they are
+ * made instead of through the log. A FIFO is opened for writing; any other
+ * path is connected to as a Unix socket, or a named pipe on Windows.
+ *
+ * @returns {void}
+ */
+export function openEventSocket() {
+  const socketPath = getEventSocket();
+  if (!socketPath) {
+    return;
+  }
+
+  /** @type {import("stream").Writable} */
+  let stream;
+  try {
+    stream = isFifo(socketPath)
+      ? fs.createWriteStream(socketPath, { flags: "a" })
+      : net.createConnection(socketPath);
+  } catch (/** @type {any} */ err) {
+    warnWriteFailure(socketPath, err);
+    return;
+  }
+
+  // Best-effort: a wrapper that stops listening does not stop the install
+  stream.on("error", (err) => {
+    if (state.stream === stream) {
+      state.stream = undefined;
+      warnWriteFailure(socketPath, err);
+    }
+    stream.destroy();
+  });
+  state.stream = stream;
+}
+
+/**
+ * Writes a decision about a package to the event socket as a single JSON
+ * line. Does nothing without an event socket.
+ *
+ * @param {string} packageName
+ * @param {string} version
+ * @param {string} reason
+ * @param {string} url
+ * @returns {void}
+ */
+export function emitDecisionEvent(packageName, version, reason, url) {
+  if (!state.stream) {
+    return;
+  }
+
+  /** @type {DecisionEvent} */
+  const event = {
+    event: "decision",
+    ecosystem: getEcoSystem(),
+    package: packageName,
+    version,
+    decision: getInspectOnly() ? "would-block" : "blocked",
+    reason,
+    url,
+    timestamp: new Date().toISOString(),
+  };
+  state.stream.write(`${JSON.stringify(event)}\n`);
+}
+
+/**
+ * @returns {void}
+ */
+export function closeEventSocket() {
+  state.stream?.end();
+  state.stream = undefined;
+}
+
+/**
+ * @param {string} socketPath
+ * @returns {boolean}
+ */
+function isFifo(socketPath) {
+  try {
+    return fs.statSync(socketPath).isFIFO();
+  } catch {
+    // Named pipes on Windows cannot be stat'ed
+    return false;
+  }
+}
+
+/**
+ * @param {string} socketPath
+ * @param {Error} err
+ * @returns {void}
+ */
+function warnWriteFailure(socketPath, err) {
+  ui.writeWarning(
+    `Safe-chain: event-socket - could not write decision events to ${socketPath}: ${err.message}`
+  );
+}
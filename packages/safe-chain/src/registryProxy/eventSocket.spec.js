@@ -0,0 +1,118 @@
+import { describe, it, mock, beforeEach, afterEach } from "node:test";
+import assert from "node:assert";
+import fs from "fs";
+import net from "net";
+import os from "os";
+import path from "path";
+
+describe("eventSocket", async () => {
+  const warnings = [];
+  /** @type {string | undefined} */
+  let eventSocket;
+  let inspectOnly = false;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getEcoSystem: () => "js",
+      getEventSocket: () => eventSocket,
+      getInspectOnly: () => inspectOnly,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeWarning: (message) => warnings.push(message),
+      },
+    },
+  });
+
+  const { closeEventSocket, emitDecisionEvent, openEventSocket } =
+    await import("./eventSocket.js");
+
+  let tempDir;
+
+  beforeEach(() => {
+    tempDir = fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-events-"));
+    eventSocket = undefined;
+    inspectOnly = false;
+    warnings.length = 0;
+  });
+
+  afterEach(() => {
+    closeEventSocket();
+    fs.rmSync(tempDir, { recursive: true, force: true });
+  });
+
+  /**
+   * @param {string} socketPath
+   * @returns {Promise<{server: net.Server, received: Promise<string>}>}
+   */
+  async function listenOnSocket(socketPath) {
+    let resolveReceived;
+    const received = new Promise((resolve) => (resolveReceived = resolve));
+    const server = net.createServer((connection) => {
+      let data = "";
+      connection.setEncoding("utf8");
+      connection.on("data", (chunk) => (data += chunk));
+      connection.on("end", () => resolveReceived(data));
+    });
+    await new Promise((resolve) => server.listen(socketPath, resolve));
+    return { server, received };
+  }
+
+  it("streams decisions to the socket as JSON lines", async () => {
+    eventSocket = path.join(tempDir, "events.sock");
+    const { server, received } = await listenOnSocket(eventSocket);
+
+    openEventSocket();
+    emitDecisionEvent(
+      "safe-chain-test",
+      "0.0.1-security",
+      "malware",
+      "https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz"
+    );
+    inspectOnly = true;
+    emitDecisionEvent(
+      "next",
+      "15.0.0-canary.1",
+      "prerelease",
+      "https://registry.npmjs.org/next/-/next-15.0.0-canary.1.tgz"
+    );
+    closeEventSocket();
+
+    const events = (await received)
+      .trim()
+      .split("\n")
+      .map((line) => JSON.parse(line));
+    server.close();
+
+    assert.strictEqual(events.length, 2);
+    assert.deepStrictEqual(
+      { ...events[0], timestamp: undefined },
+      {
+        event: "decision",
+        ecosystem: "js",
+        package: "safe-chain-test",
+        version: "0.0.1-security",
+        decision: "blocked",
+        reason: "malware",
+        url: "https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz",
+        timestamp: undefined,
+      }
+    );
+    assert.strictEqual(events[1].decision, "would-block");
+    assert.deepStrictEqual(warnings, []);
+  });
+
+  it("warns once and keeps going when nobody listens", async () => {
+    eventSocket = path.join(tempDir, "missing.sock");
+
+    openEventSocket();
+    await new Promise((resolve) => setTimeout(resolve, 50));
+    emitDecisionEvent("lodash", "4.17.21", "malware", "https://example.com");
+
+    assert.strictEqual(warnings.length, 1);
+    assert.match(warnings[0], /event-socket - could not write decision events/);
+  });
+});
@@ -0,0 +1,60 @@
+import { getExcludePaths } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * @type {{patterns: string[], expressions: RegExp[]}}
+ */
+const compiled = {
+  patterns: [],
+  expressions: [],
+};
+
+/**
+ * Checks whether a registry request path is excluded from inspection, so it is
+ * forwarded without evaluating the policy.
+ *
+ * @param {string} pathAndQuery - For example "/-/v1/search?text=react"
+ * @returns {boolean}
+ */
+export function isExcludedPath(pathAndQuery) {
+  const expression = getExcludePathExpressions().find((expr) =>
+    expr.test(pathAndQuery)
+  );
+  if (!expression) {
+    return false;
+  }
+
+  ui.writeVerbose(
+    `Safe-chain: exclude-path - ${pathAndQuery} matches ${expression}, forwarding without inspection`
+  );
+  return true;
+}
+
+/**
+ * Compiles the configured patterns once, and again only when they change.
+ * Invalid patterns are reported and ignored.
+ *
+ * @returns {RegExp[]}
+ */
+function getExcludePathExpressions() {
+  const patterns = getExcludePaths();
+  if (
+    patterns.length === compiled.patterns.length &&
+    patterns.every((pattern, index) => pattern === compiled.patterns[index])
+  ) {
+    return compiled.expressions;
+  }
+
+  compiled.patterns = patterns;
+  compiled.expressions = [];
+  for (const pattern of patterns) {
+    try {
+      compiled.expressions.push(new RegExp(pattern));
+    } catch (/** @type {any} */ err) {
+      ui.writeWarning(
+        `Safe-chain: Ignoring invalid exclude path pattern ${pattern}: ${err.message}`
+      );
+    }
+  }
+  return compiled.expressions;
+}
@@ -0,0 +1,54 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("excludedPaths", async () => {
+  const warnings = [];
+  let excludePaths = [];
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getExcludePaths: () => excludePaths,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeWarning: (message) => warnings.push(message),
+      },
+    },
+  });
+
+  const { isExcludedPath } = await import("./excludedPaths.js");
+
+  beforeEach(() => {
+    excludePaths = [];
+    warnings.length = 0;
+  });
+
+  it("excludes nothing by default", () => {
+    assert.strictEqual(isExcludedPath("/-/v1/search?text=react"), false);
+  });
+
+  it("matches the path and query against every pattern", () => {
+    excludePaths = ["^/-/v1/search", "^/-/npm/v1/security/"];
+
+    assert.strictEqual(isExcludedPath("/-/v1/search?text=react"), true);
+    assert.strictEqual(
+      isExcludedPath("/-/npm/v1/security/advisories/bulk"),
+      true
+    );
+    assert.strictEqual(isExcludedPath("/react"), false);
+    assert.strictEqual(isExcludedPath("/react?q=/-/v1/search"), false);
+  });
+
+  it("ignores invalid patterns with a warning", () => {
+    excludePaths = ["(unclosed", "^/-/whoami$"];
+
+    assert.strictEqual(isExcludedPath("/-/whoami"), true);
+    assert.strictEqual(isExcludedPath("(unclosed"), false);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(warnings[0], /invalid exclude path pattern \(unclosed/);
+  });
+});
@@ -0,0 +1,55 @@
+import {
+  readFeedListFromLocalCache,
+  writeFeedListToLocalCache,
+} from "../config/configFile.js";
+import { getOffline } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * @template T
+ * @typedef {Object} FeedList
+ * @property {string} description - Names the list in warnings, e.g. "denied hosts"
+ * @property {string} cacheName - File name of the cached copy, without extension
+ * @property {() => Promise<T[]>} download
+ * @property {(list: T[]) => void} apply
+ */
+
+/**
+ * Loads a list shipped with the malware database. The copy cached by the
+ * previous run is applied right away, before the returned promise is awaited,
+ * so the proxy does not have to wait for the download to start listening. The
+ * downloaded list then replaces it. A download that fails is reported and the
+ * cached copy stays in use. Offline, only the cached copy is used.
+ *
+ * @template T
+ * @param {FeedList<T>} feedList
+ * @returns {Promise<void>} - Settles once the download finished or failed
+ */
+export async function loadFeedList({
+  description,
+  cacheName,
+  download,
+  apply,
+}) {
+  const cached = readFeedListFromLocalCache(cacheName);
+  if (cached) {
+    apply(/** @type {T[]} */ (cached.list));
+  }
+
+  if (getOffline()) {
+    return;
+  }
+
+  try {
+    const list = await download();
+    apply(list);
+    writeFeedListToLocalCache(cacheName, list);
+  } catch (/** @type {any} */ error) {
+    const fallback = cached
+      ? `using the copy cached on ${new Date(cached.refreshedAt).toISOString()}`
+      : "continuing without it";
+    ui.writeWarning(
+      `Safe-chain: feed-list.unavailable - could not load the ${description} list, ${fallback}: ${error.message}`
+    );
+  }
+}
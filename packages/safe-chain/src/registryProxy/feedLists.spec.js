@@ -0,0 +1,113 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("feedLists", async () => {
+  let cachedLists = new Map();
+  let offline = false;
+  const warnings = [];
+
+  mock.module("../config/configFile.js", {
+    namedExports: {
+      readFeedListFromLocalCache: (name) =>
+        cachedLists.has(name)
+          ? { list: cachedLists.get(name), refreshedAt: Date.UTC(2026, 9, 1) }
+          : null,
+      writeFeedListToLocalCache: (name, list) => cachedLists.set(name, list),
+    },
+  });
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getOffline: () => offline,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeWarning: (message) => warnings.push(message),
+      },
+    },
+  });
+
+  const { loadFeedList } = await import("./feedLists.js");
+
+  beforeEach(() => {
+    cachedLists = new Map();
+    offline = false;
+    warnings.length = 0;
+  });
+
+  it("applies the cached copy before the download finished and caches the new list", async () => {
+    cachedLists.set("hosts", ["cached.example"]);
+    let applied;
+    let finishDownload;
+
+    const loading = loadFeedList({
+      description: "denied hosts",
+      cacheName: "hosts",
+      download: () => new Promise((resolve) => (finishDownload = resolve)),
+      apply: (list) => (applied = list),
+    });
+
+    assert.deepStrictEqual(applied, ["cached.example"]);
+
+    finishDownload(["fresh.example"]);
+    await loading;
+
+    assert.deepStrictEqual(applied, ["fresh.example"]);
+    assert.deepStrictEqual(cachedLists.get("hosts"), ["fresh.example"]);
+    assert.deepStrictEqual(warnings, []);
+  });
+
+  it("keeps the cached copy and warns when the download fails", async () => {
+    cachedLists.set("hosts", ["cached.example"]);
+    let applied;
+
+    await loadFeedList({
+      description: "denied hosts",
+      cacheName: "hosts",
+      download: async () => {
+        throw new Error("The operation was aborted due to timeout");
+      },
+      apply: (list) => (applied = list),
+    });
+
+    assert.deepStrictEqual(applied, ["cached.example"]);
+    assert.strictEqual(
+      warnings[0],
+      "Safe-chain: feed-list.unavailable - could not load the denied hosts list, using the copy cached on 2026-10-01T00:00:00.000Z: The operation was aborted due to timeout"
+    );
+  });
+
+  it("warns that it continues without the list when nothing is cached", async () => {
+    await loadFeedList({
+      description: "flagged maintainers",
+      cacheName: "maintainers",
+      download: async () => {
+        throw new Error("Service Unavailable");
+      },
+      apply: () => assert.fail("nothing to apply"),
+    });
+
+    assert.match(
+      warnings[0],
+      /flagged maintainers list, continuing without it/
+    );
+  });
+
+  it("only uses the cached copy offline", async () => {
+    cachedLists.set("hosts", ["cached.example"]);
+    offline = true;
+    let applied;
+
+    await loadFeedList({
+      description: "denied hosts",
+      cacheName: "hosts",
+      download: async () => assert.fail("must not download offline"),
+      apply: (list) => (applied = list),
+    });
+
+    assert.deepStrictEqual(applied, ["cached.example"]);
+  });
+});
@@ -1,13 +1,16 @@
+import { getTunnelConnectTimeoutSecs } from "../config/settings.js";
 import { isImdsEndpoint } from "./isImdsEndpoint.js";
 
 /**
- * Returns appropriate connection timeout for a host.
+ * Returns appropriate connection timeout for a host, in milliseconds.
  * - IMDS endpoints: 3s (fail fast when outside cloud, reduce 5min delay to ~20s)
- * - Other endpoints: 30s (allow for slow networks while preventing indefinite hangs)
+ * - Other endpoints: the tunnel connect timeout, 30s by default (allow for slow
+ *   networks while preventing indefinite hangs)
  */
 export function getConnectTimeout(/** @type {string} */ host) {
+  const tunnelConnectTimeout = getTunnelConnectTimeoutSecs() * 1000;
   if (isImdsEndpoint(host)) {
-    return 3000;
+    return Math.min(3000, tunnelConnectTimeout);
   }
-  return 30000;
+  return tunnelConnectTimeout;
 }
@@ -0,0 +1,24 @@
+import { describe, it, afterEach } from "node:test";
+import assert from "node:assert";
+import { getConnectTimeout } from "./getConnectTimeout.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+
+describe("getConnectTimeout", () => {
+  afterEach(() => {
+    initializeCliArguments([]);
+  });
+
+  it("waits 30 seconds for registries and 3 seconds for IMDS endpoints by default", () => {
+    initializeCliArguments([]);
+
+    assert.strictEqual(getConnectTimeout("registry.npmjs.org"), 30000);
+    assert.strictEqual(getConnectTimeout("169.254.169.254"), 3000);
+  });
+
+  it("uses the configured tunnel connect timeout", () => {
+    initializeCliArguments(["--safe-chain-tunnel-connect-timeout-secs=2"]);
+
+    assert.strictEqual(getConnectTimeout("registry.npmjs.org"), 2000);
+    assert.strictEqual(getConnectTimeout("169.254.169.254"), 2000);
+  });
+});
@@ -0,0 +1,60 @@
+import {
+  getAddVia,
+  getStripRequestHeaders,
+  getStripResponseHeaders,
+} from "../config/settings.js";
+import { omitHeaders } from "./http-utils.js";
+
+const VIA_HEADER_VALUE = "1.1 safe-chain";
+
+// Removing these would break message framing or routing, so they are never stripped.
+const PROTECTED_HEADERS = ["host", "content-length", "transfer-encoding"];
+
+/**
+ * Applies the configured header hygiene to a request forwarded to the registry.
+ *
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @returns {NodeJS.Dict<string | string[]>}
+ */
+export function applyRequestHeaderPolicy(headers) {
+  return applyHeaderPolicy(headers, getStripRequestHeaders());
+}
+
+/**
+ * Applies the configured header hygiene to a response returned to the client.
+ *
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @returns {NodeJS.Dict<string | string[]>}
+ */
+export function applyResponseHeaderPolicy(headers) {
+  return applyHeaderPolicy(headers, getStripResponseHeaders());
+}
+
+/**
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @param {string[]} stripHeaderNames
+ * @returns {NodeJS.Dict<string | string[]>}
+ */
+function applyHeaderPolicy(headers, stripHeaderNames) {
+  const headerNames = stripHeaderNames.filter(
+    (name) => !PROTECTED_HEADERS.includes(name)
+  );
+  const filteredHeaders =
+    omitHeaders(headers, headerNames, { caseInsensitive: true }) ?? {};
+
+  if (getAddVia()) {
+    // Via is a list: append this proxy after any proxies already on the path
+    const existingVia = Object.keys(filteredHeaders).find(
+      (name) => name.toLowerCase() === "via"
+    );
+    const existingValue = existingVia ? filteredHeaders[existingVia] : undefined;
+    if (existingVia) {
+      delete filteredHeaders[existingVia];
+    }
+    filteredHeaders["via"] = existingValue
+      ? `${[existingValue].flat().join(", ")}, ${VIA_HEADER_VALUE}`
+      : VIA_HEADER_VALUE;
+  }
+
+  return filteredHeaders;
+}
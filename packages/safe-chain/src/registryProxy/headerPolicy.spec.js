@@ -0,0 +1,66 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("headerPolicy", async () => {
+  let stripRequestHeaders = [];
+  let stripResponseHeaders = [];
+  let addVia = false;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getStripRequestHeaders: () => stripRequestHeaders,
+      getStripResponseHeaders: () => stripResponseHeaders,
+      getAddVia: () => addVia,
+    },
+  });
+
+  const { applyRequestHeaderPolicy, applyResponseHeaderPolicy } = await import(
+    "./headerPolicy.js"
+  );
+
+  beforeEach(() => {
+    stripRequestHeaders = [];
+    stripResponseHeaders = [];
+    addVia = false;
+  });
+
+  it("should leave headers unchanged by default", () => {
+    const headers = { "user-agent": "npm/10", via: "1.1 corp-proxy" };
+
+    assert.deepStrictEqual(applyRequestHeaderPolicy(headers), headers);
+    assert.deepStrictEqual(applyResponseHeaderPolicy(headers), headers);
+  });
+
+  it("should strip configured headers case-insensitively", () => {
+    stripRequestHeaders = ["x-internal-token"];
+    stripResponseHeaders = ["server"];
+
+    assert.deepStrictEqual(
+      applyRequestHeaderPolicy({ "X-Internal-Token": "secret", accept: "*/*" }),
+      { accept: "*/*" }
+    );
+    assert.deepStrictEqual(
+      applyResponseHeaderPolicy({ Server: "nginx", etag: '"abc"' }),
+      { etag: '"abc"' }
+    );
+  });
+
+  it("should never strip headers needed for message framing", () => {
+    stripResponseHeaders = ["content-length", "transfer-encoding"];
+
+    assert.deepStrictEqual(
+      applyResponseHeaderPolicy({ "content-length": "12" }),
+      { "content-length": "12" }
+    );
+  });
+
+  it("should add or append a Via header when enabled", () => {
+    addVia = true;
+
+    assert.strictEqual(applyRequestHeaderPolicy({}).via, "1.1 safe-chain");
+    assert.deepStrictEqual(
+      applyResponseHeaderPolicy({ Via: ["1.0 fred", "1.1 corp-proxy"] }),
+      { via: "1.0 fred, 1.1 corp-proxy, 1.1 safe-chain" }
+    );
+  });
+});
@@ -0,0 +1,179 @@
+import dns from "dns";
+import {
+  getBlockingDisabled,
+  getDenyHosts,
+  getInspectOnly,
+} from "../config/settings.js";
+import { fetchDeniedHosts } from "../api/aikido.js";
+import { ui } from "../environment/userInteraction.js";
+import { loadFeedList } from "./feedLists.js";
+
+/**
+ * @type {{
+ *   feedHosts: string[],
+ *   blockedHostRequests: {host: string, url: string}[]
+ * }}
+ */
+const state = {
+  feedHosts: [],
+  blockedHostRequests: [],
+};
+
+export const DENIED_ADDRESS_ERROR_CODE = "ESAFECHAINDENIED";
+
+/**
+ * Loads the denied hosts shipped with the remote malware database. The copy
+ * cached by the previous run applies right away. Failures are not fatal: the
+ * locally configured deny list is still enforced.
+ *
+ * @returns {Promise<void>}
+ */
+export function loadDeniedHostsFromFeed() {
+  return loadFeedList({
+    description: "denied hosts",
+    cacheName: "deniedHosts",
+    download: fetchDeniedHosts,
+    apply: (hosts) => {
+      state.feedHosts = hosts
+        .map((host) => host.trim().toLowerCase())
+        .filter((host) => host.length > 0);
+    },
+  });
+}
+
+/**
+ * @param {string} hostname
+ * @returns {boolean}
+ */
+export function isDeniedHost(hostname) {
+  const normalizedHost = normalizeHost(hostname);
+  if (!normalizedHost || getBlockingDisabled()) {
+    return false;
+  }
+
+  return [...getDenyHosts(), ...state.feedHosts].some((pattern) =>
+    matchesHostPattern(normalizedHost, pattern)
+  );
+}
+
+/**
+ * Matches a hostname against an exact hostname or a wildcard pattern.
+ * "*.evil.example" matches every subdomain of evil.example, but not evil.example itself.
+ *
+ * @param {string} hostname
+ * @param {string} pattern
+ * @returns {boolean}
+ */
+export function matchesHostPattern(hostname, pattern) {
+  const normalizedPattern = normalizeHost(pattern);
+  if (!normalizedPattern) {
+    return false;
+  }
+
+  if (normalizedPattern.startsWith("*.")) {
+    return hostname.endsWith(normalizedPattern.substring(1));
+  }
+
+  return hostname === normalizedPattern;
+}
+
+/**
+ * Wraps a dns.lookup compatible function so connections are refused when the
+ * hostname resolves to a denied address. The deny check on the requested host
+ * only sees the hostname, this check runs on the addresses the socket actually
+ * connects to. When any resolved address is denied, the lookup fails with
+ * DENIED_ADDRESS_ERROR_CODE and no connection is made. In inspect-only mode the
+ * denied address is recorded and the connection is made anyway.
+ *
+ * @param {import("net").LookupFunction | undefined} lookup - Defaults to dns.lookup
+ * @returns {import("net").LookupFunction}
+ */
+export function createDenylistLookup(lookup) {
+  const baseLookup = lookup ?? dns.lookup;
+
+  /**
+   * @param {string} hostname
+   * @param {import("dns").LookupOptions} options
+   * @param {(err: NodeJS.ErrnoException | null, address: string | import("dns").LookupAddress[], family?: number) => void} callback
+   */
+  function denylistLookup(hostname, options, callback) {
+    baseLookup(hostname, { ...options, all: true }, (err, addresses) => {
+      if (err) {
+        callback(err, "", 0);
+        return;
+      }
+
+      const resolvedAddresses = /** @type {import("dns").LookupAddress[]} */ (
+        /** @type {unknown} */ (addresses)
+      );
+      const deniedAddress = resolvedAddresses.find(({ address }) =>
+        isDeniedHost(address)
+      );
+      if (deniedAddress && getInspectOnly()) {
+        recordBlockedHostRequest(
+          hostname,
+          `${hostname} resolves to denied address ${deniedAddress.address}`
+        );
+      } else if (deniedAddress) {
+        /** @type {NodeJS.ErrnoException} */
+        const error = new Error(
+          `${hostname} resolves to denied address ${deniedAddress.address}`
+        );
+        error.code = DENIED_ADDRESS_ERROR_CODE;
+        callback(error, "", 0);
+        return;
+      }
+
+      if (options.all) {
+        callback(null, resolvedAddresses);
+        return;
+      }
+
+      const [first] = resolvedAddresses;
+      callback(null, first.address, first.family);
+    });
+  }
+
+  return /** @type {import("net").LookupFunction} */ (
+    /** @type {unknown} */ (denylistLookup)
+  );
+}
+
+/**
+ * Records a blocked request for the end-of-run summary and the audit log.
+ * In inspect-only mode the request is recorded the same way, but goes through.
+ *
+ * @param {string} host
+ * @param {string} url
+ * @returns {boolean} - true when the request must be refused
+ */
+export function recordBlockedHostRequest(host, url) {
+  state.blockedHostRequests.push({ host, url });
+
+  if (getInspectOnly()) {
+    ui.writeWarning(
+      `Safe-chain: inspect.would-block - request to denied host ${host} (${url}) is let through in inspect-only mode`
+    );
+    return false;
+  }
+
+  ui.writeWarning(
+    `Safe-chain: Blocked request to denied host ${host} (${url})`
+  );
+  return true;
+}
+
+/**
+ * @returns {{host: string, url: string}[]}
+ */
+export function getBlockedHostRequests() {
+  return state.blockedHostRequests;
+}
+
+/**
+ * @param {string} host
+ * @returns {string}
+ */
+function normalizeHost(host) {
+  return host.trim().toLowerCase().replace(/\.$/, "").replace(/^\[|\]$/g, "");
+}
@@ -0,0 +1,184 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("hostDenylist", async () => {
+  let configuredHosts = [];
+  let feedHosts = [];
+  let blockingDisabled = false;
+  let inspectOnly = false;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getDenyHosts: () => configuredHosts,
+      getBlockingDisabled: () => blockingDisabled,
+      getInspectOnly: () => inspectOnly,
+      getOffline: () => false,
+    },
+  });
+
+  mock.module("../api/aikido.js", {
+    namedExports: {
+      fetchDeniedHosts: async () => feedHosts,
+    },
+  });
+
+  mock.module("../config/configFile.js", {
+    namedExports: {
+      readFeedListFromLocalCache: () => null,
+      writeFeedListToLocalCache: () => {},
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeWarning: () => {},
+      },
+    },
+  });
+
+  const {
+    isDeniedHost,
+    matchesHostPattern,
+    loadDeniedHostsFromFeed,
+    recordBlockedHostRequest,
+    getBlockedHostRequests,
+    createDenylistLookup,
+    DENIED_ADDRESS_ERROR_CODE,
+  } = await import("./hostDenylist.js");
+
+  beforeEach(async () => {
+    configuredHosts = [];
+    feedHosts = [];
+    blockingDisabled = false;
+    inspectOnly = false;
+    await loadDeniedHostsFromFeed();
+  });
+
+  describe("matchesHostPattern", () => {
+    it("matches exact hostnames case-insensitively", () => {
+      assert.strictEqual(matchesHostPattern("evil.example", "Evil.Example"), true);
+      assert.strictEqual(matchesHostPattern("sub.evil.example", "evil.example"), false);
+    });
+
+    it("matches subdomains for wildcard patterns but not the apex", () => {
+      assert.strictEqual(matchesHostPattern("c2.evil.example", "*.evil.example"), true);
+      assert.strictEqual(matchesHostPattern("a.b.evil.example", "*.evil.example"), true);
+      assert.strictEqual(matchesHostPattern("evil.example", "*.evil.example"), false);
+      assert.strictEqual(matchesHostPattern("notevil.example", "*.evil.example"), false);
+    });
+  });
+
+  describe("isDeniedHost", () => {
+    it("denies hosts from the configured list", () => {
+      configuredHosts = ["*.evil.example"];
+
+      assert.strictEqual(isDeniedHost("c2.evil.example"), true);
+      assert.strictEqual(isDeniedHost("c2.evil.example."), true);
+      assert.strictEqual(isDeniedHost("registry.npmjs.org"), false);
+    });
+
+    it("denies hosts shipped in the remote feed", async () => {
+      feedHosts = ["exfil.example"];
+      await loadDeniedHostsFromFeed();
+
+      assert.strictEqual(isDeniedHost("EXFIL.example"), true);
+    });
+
+    it("denies nothing while blocking is disabled", () => {
+      configuredHosts = ["*.evil.example"];
+      blockingDisabled = true;
+
+      assert.strictEqual(isDeniedHost("c2.evil.example"), false);
+    });
+  });
+
+  describe("createDenylistLookup", () => {
+    const fakeLookup = (_hostname, _options, callback) => {
+      callback(null, [
+        { address: "93.184.216.34", family: 4 },
+        { address: "10.0.0.5", family: 4 },
+      ]);
+    };
+
+    const lookupAsync = (lookup, hostname, options) =>
+      new Promise((resolve) => {
+        lookup(hostname, options, (err, address, family) =>
+          resolve({ err, address, family })
+        );
+      });
+
+    it("returns the first resolved address when nothing is denied", async () => {
+      const result = await lookupAsync(
+        createDenylistLookup(fakeLookup),
+        "registry.example",
+        {}
+      );
+
+      assert.strictEqual(result.err, null);
+      assert.strictEqual(result.address, "93.184.216.34");
+      assert.strictEqual(result.family, 4);
+    });
+
+    it("returns every address when the caller asks for all", async () => {
+      const result = await lookupAsync(
+        createDenylistLookup(fakeLookup),
+        "registry.example",
+        { all: true }
+      );
+
+      assert.strictEqual(result.address.length, 2);
+    });
+
+    it("fails when any resolved address is denied", async () => {
+      configuredHosts = ["10.0.0.5"];
+
+      const result = await lookupAsync(
+        createDenylistLookup(fakeLookup),
+        "registry.example",
+        {}
+      );
+
+      assert.strictEqual(result.err.code, DENIED_ADDRESS_ERROR_CODE);
+      assert.match(result.err.message, /10\.0\.0\.5/);
+    });
+
+    it("records denied addresses without failing in inspect-only mode", async () => {
+      configuredHosts = ["10.0.0.5"];
+      inspectOnly = true;
+
+      const result = await lookupAsync(
+        createDenylistLookup(fakeLookup),
+        "registry.example",
+        {}
+      );
+
+      assert.strictEqual(result.err, null);
+      assert.strictEqual(result.address, "93.184.216.34");
+      assert.match(getBlockedHostRequests().at(-1).url, /10\.0\.0\.5/);
+    });
+  });
+
+  it("records blocked requests for the summary", () => {
+    assert.strictEqual(
+      recordBlockedHostRequest("c2.evil.example", "c2.evil.example:443"),
+      true
+    );
+
+    assert.deepStrictEqual(getBlockedHostRequests().at(-1), {
+      host: "c2.evil.example",
+      url: "c2.evil.example:443",
+    });
+  });
+
+  it("lets requests through in inspect-only mode", () => {
+    inspectOnly = true;
+
+    assert.strictEqual(
+      recordBlockedHostRequest("c2.evil.example", "c2.evil.example:80"),
+      false
+    );
+    assert.strictEqual(getBlockedHostRequests().at(-1).url, "c2.evil.example:80");
+  });
+});
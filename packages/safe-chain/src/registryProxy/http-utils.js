@@ -78,3 +78,46 @@ export function clearCachingHeaders(headers) {
 
   Object.assign(headers, filteredHeaders);
 }
+
+// Headers that only apply to a single connection (RFC 9110, section 7.6.1).
+// Node frames each side of the proxy itself, for example without chunked
+// encoding and with connection close semantics for HTTP/1.0 clients.
+const HOP_BY_HOP_HEADERS = [
+  "connection",
+  "keep-alive",
+  "proxy-connection",
+  "proxy-authenticate",
+  "proxy-authorization",
+  "te",
+  "trailer",
+  "transfer-encoding",
+  "upgrade",
+];
+
+/**
+ * Returns a copy of headers without hop-by-hop headers, including the
+ * headers that the Connection header lists.
+ *
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {NodeJS.Dict<string | string[]> | undefined}
+ */
+export function omitHopByHopHeaders(headers) {
+  if (!headers) {
+    return headers;
+  }
+
+  const connectionHeader = Object.keys(headers).find(
+    (name) => name.toLowerCase() === "connection"
+  );
+  const connectionOptions = connectionHeader
+    ? [headers[connectionHeader] ?? []]
+        .flat()
+        .flatMap((value) => value.split(","))
+        .map((name) => name.trim())
+        .filter((name) => name.length > 0)
+    : [];
+
+  return omitHeaders(headers, [...HOP_BY_HOP_HEADERS, ...connectionOptions], {
+    caseInsensitive: true,
+  });
+}
@@ -0,0 +1,74 @@
+import { initializeCliArguments } from "../config/cliArguments.js";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  setEcoSystem,
+} from "../config/settings.js";
+import {
+  createSafeChainProxy,
+  mergeSafeChainProxyEnvironmentVariables,
+} from "./registryProxy.js";
+
+export { ECOSYSTEM_JS, ECOSYSTEM_PY };
+
+/**
+ * @typedef {Object} ProxyConfig
+ * @property {string} ecosystem - ECOSYSTEM_JS or ECOSYSTEM_PY, selects the interceptors and malware database
+ * @property {string[]} [args] - `--safe-chain-*` arguments, exactly as accepted on the command line
+ *
+ * @typedef {Object} ServerHandle
+ * @property {number} port - The port the proxy is bound to on 127.0.0.1
+ * @property {string} url - The proxy URL, for example http://127.0.0.1:8080
+ * @property {Record<string, string>} environmentVariables - Proxy and CA variables for the processes that should use the proxy
+ * @property {() => boolean} hasBlockedMaliciousPackages
+ * @property {() => boolean} hasBlockedMinimumAgeRequests
+ * @property {() => boolean} hasBlockedPrereleaseRequests
+ * @property {() => boolean} hasBlockedInstallScriptRequests
+ * @property {() => boolean} hasBlockedSizeAnomalyRequests
+ * @property {() => boolean} hasBlockedVcsDependencyRequests
+ * @property {() => boolean} hasBlockedHostRequests
+ * @property {() => boolean} hasBlockedUnknownHostRequests
+ * @property {() => boolean} isBlockingDisabled - true while the `--safe-chain-no-block` break-glass switch is on, so protection is off
+ * @property {() => Promise<void>} shutdown
+ */
+
+/**
+ * Starts the safe-chain proxy inside the current process, for tools that embed
+ * it instead of running the safe-chain binary.
+ *
+ * Settings are process-wide: the arguments replace any previously parsed
+ * `--safe-chain-*` arguments, and environment variables and the config file
+ * apply as they do for the binary. Run at most one proxy per process.
+ *
+ * @param {ProxyConfig} config
+ * @returns {Promise<ServerHandle>}
+ */
+export async function runServer(config) {
+  if (config.ecosystem !== ECOSYSTEM_JS && config.ecosystem !== ECOSYSTEM_PY) {
+    throw new Error(
+      `Unsupported ecosystem "${config.ecosystem}", expected "${ECOSYSTEM_JS}" or "${ECOSYSTEM_PY}"`
+    );
+  }
+
+  setEcoSystem(config.ecosystem);
+  initializeCliArguments(config.args ?? []);
+
+  const proxy = createSafeChainProxy();
+  const { port } = await proxy.startServer();
+
+  return {
+    port,
+    url: `http://127.0.0.1:${port}`,
+    environmentVariables: mergeSafeChainProxyEnvironmentVariables({}),
+    hasBlockedMaliciousPackages: proxy.hasBlockedMaliciousPackages,
+    hasBlockedMinimumAgeRequests: proxy.hasBlockedMinimumAgeRequests,
+    hasBlockedPrereleaseRequests: proxy.hasBlockedPrereleaseRequests,
+    hasBlockedInstallScriptRequests: proxy.hasBlockedInstallScriptRequests,
+    hasBlockedSizeAnomalyRequests: proxy.hasBlockedSizeAnomalyRequests,
+    hasBlockedVcsDependencyRequests: proxy.hasBlockedVcsDependencyRequests,
+    hasBlockedHostRequests: proxy.hasBlockedHostRequests,
+    hasBlockedUnknownHostRequests: proxy.hasBlockedUnknownHostRequests,
+    isBlockingDisabled: proxy.isBlockingDisabled,
+    shutdown: proxy.stopServer,
+  };
+}
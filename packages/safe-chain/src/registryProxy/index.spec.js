@@ -0,0 +1,56 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import * as http from "http";
+import { runServer, ECOSYSTEM_JS } from "./index.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+
+describe("embedded proxy", () => {
+  /** @type {import("./index.js").ServerHandle | undefined} */
+  let handle;
+
+  afterEach(async () => {
+    await handle?.shutdown();
+    handle = undefined;
+    initializeCliArguments([]);
+  });
+
+  it("starts the proxy and exposes the bound address", async () => {
+    handle = await runServer({ ecosystem: ECOSYSTEM_JS });
+
+    assert.ok(handle.port > 0);
+    assert.strictEqual(handle.url, `http://127.0.0.1:${handle.port}`);
+    assert.strictEqual(handle.environmentVariables.HTTPS_PROXY, handle.url);
+  });
+
+  it("applies the given safe-chain arguments", async () => {
+    handle = await runServer({
+      ecosystem: ECOSYSTEM_JS,
+      args: ["--safe-chain-deny-host=blocked.example"],
+    });
+
+    const statusCode = await new Promise((resolve, reject) => {
+      http
+        .get(
+          {
+            host: "127.0.0.1",
+            port: handle.port,
+            path: "http://blocked.example/",
+          },
+          (res) => {
+            res.resume();
+            resolve(res.statusCode);
+          }
+        )
+        .on("error", reject);
+    });
+
+    assert.strictEqual(statusCode, 403);
+    assert.strictEqual(handle.hasBlockedHostRequests(), true);
+  });
+
+  it("rejects unknown ecosystems", async () => {
+    await assert.rejects(runServer({ ecosystem: "cargo" }), {
+      message: /Unsupported ecosystem "cargo"/,
+    });
+  });
+});
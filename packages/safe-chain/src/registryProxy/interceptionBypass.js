@@ -0,0 +1,89 @@
+import {
+  getNoInterceptHosts,
+  getStrictInterception,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import { matchesHostPattern } from "./hostDenylist.js";
+
+/**
+ * Hosts whose clients rejected the TLS handshake with safe-chain's certificate,
+ * for example because they pin the registry certificate.
+ * @type {Set<string>}
+ */
+const failedHosts = new Set();
+
+/**
+ * Checks whether connections to a registry host are tunneled instead of
+ * intercepted: the host is on the no-intercept list, or an earlier interception
+ * handshake for it failed and strict interception is off.
+ *
+ * @param {string} hostname
+ * @returns {boolean}
+ */
+export function shouldBypassInterception(hostname) {
+  const normalizedHost = normalizeHost(hostname);
+
+  if (
+    getNoInterceptHosts().some((pattern) =>
+      matchesHostPattern(normalizedHost, pattern)
+    )
+  ) {
+    ui.writeVerbose(
+      `Safe-chain: intercept.bypass - ${hostname} is on the no-intercept list, tunneling without inspection`
+    );
+    return true;
+  }
+
+  if (failedHosts.has(normalizedHost) && !getStrictInterception()) {
+    ui.writeVerbose(
+      `Safe-chain: intercept.bypass - interception failed earlier for ${hostname}, tunneling without inspection`
+    );
+    return true;
+  }
+
+  return false;
+}
+
+/**
+ * Records a client that failed the TLS handshake with safe-chain's certificate.
+ * The connection itself is lost, but later connections to the host fall back to
+ * tunneling, unless strict interception is on. Only TLS errors count: a client
+ * that disconnects during the handshake says nothing about pinning.
+ *
+ * @param {string} hostname
+ * @param {Error & {code?: string}} error
+ * @returns {void}
+ */
+export function recordInterceptionFailure(hostname, error) {
+  const normalizedHost = normalizeHost(hostname);
+  if (!error.code?.startsWith("ERR_SSL_") || failedHosts.has(normalizedHost)) {
+    return;
+  }
+  failedHosts.add(normalizedHost);
+
+  if (getStrictInterception()) {
+    ui.writeWarning(
+      `Safe-chain: intercept.failed - the TLS handshake for ${hostname} failed (${error.message}). Strict interception is on, so connections to ${hostname} stay blocked.`
+    );
+    return;
+  }
+
+  ui.writeWarning(
+    `Safe-chain: intercept.bypass - the TLS handshake for ${hostname} failed (${error.message}). Later connections to ${hostname} are tunneled without inspection.`
+  );
+}
+
+/**
+ * @returns {void}
+ */
+export function resetInterceptionFailures() {
+  failedHosts.clear();
+}
+
+/**
+ * @param {string} host
+ * @returns {string}
+ */
+function normalizeHost(host) {
+  return host.trim().toLowerCase().replace(/\.$/, "");
+}
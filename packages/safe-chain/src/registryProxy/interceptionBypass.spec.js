@@ -0,0 +1,88 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("interceptionBypass", async () => {
+  const warnings = [];
+  let noInterceptHosts = [];
+  let strictInterception = false;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getNoInterceptHosts: () => noInterceptHosts,
+      getStrictInterception: () => strictInterception,
+    },
+  });
+
+  mock.module("./hostDenylist.js", {
+    namedExports: {
+      matchesHostPattern: (hostname, pattern) =>
+        pattern.startsWith("*.")
+          ? hostname.endsWith(pattern.substring(1))
+          : hostname === pattern,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeWarning: (message) => warnings.push(message),
+      },
+    },
+  });
+
+  const {
+    recordInterceptionFailure,
+    resetInterceptionFailures,
+    shouldBypassInterception,
+  } = await import("./interceptionBypass.js");
+
+  const unknownCa = Object.assign(new Error("tlsv1 alert unknown ca"), {
+    code: "ERR_SSL_TLSV1_ALERT_UNKNOWN_CA",
+  });
+
+  beforeEach(() => {
+    resetInterceptionFailures();
+    noInterceptHosts = [];
+    strictInterception = false;
+    warnings.length = 0;
+  });
+
+  it("bypasses hosts on the no-intercept list", () => {
+    noInterceptHosts = ["registry.acme.com", "*.pinned.example"];
+
+    assert.strictEqual(shouldBypassInterception("registry.acme.com"), true);
+    assert.strictEqual(shouldBypassInterception("Files.Pinned.Example."), true);
+    assert.strictEqual(shouldBypassInterception("registry.npmjs.org"), false);
+  });
+
+  it("falls back to tunneling after a failed handshake", () => {
+    recordInterceptionFailure("pypi.org", unknownCa);
+    recordInterceptionFailure("pypi.org", unknownCa);
+
+    assert.strictEqual(shouldBypassInterception("pypi.org"), true);
+    assert.strictEqual(shouldBypassInterception("files.pythonhosted.org"), false);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(warnings[0], /intercept\.bypass - the TLS handshake for pypi\.org failed/);
+  });
+
+  it("keeps intercepting in strict mode", () => {
+    strictInterception = true;
+
+    recordInterceptionFailure("pypi.org", unknownCa);
+
+    assert.strictEqual(shouldBypassInterception("pypi.org"), false);
+    assert.match(warnings[0], /intercept\.failed - .* stay blocked/);
+  });
+
+  it("ignores clients that disconnect without a TLS error", () => {
+    const reset = Object.assign(new Error("socket hang up"), {
+      code: "ECONNRESET",
+    });
+
+    recordInterceptionFailure("pypi.org", reset);
+
+    assert.strictEqual(shouldBypassInterception("pypi.org"), false);
+    assert.strictEqual(warnings.length, 0);
+  });
+});
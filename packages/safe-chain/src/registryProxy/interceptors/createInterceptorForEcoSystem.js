@@ -2,9 +2,20 @@ import {
   ECOSYSTEM_JS,
   ECOSYSTEM_PY,
   getEcoSystem,
+  getUaEcosystemHints,
 } from "../../config/settings.js";
-import { npmInterceptorForUrl } from "./npm/npmInterceptor.js";
-import { pipInterceptorForUrl } from "./pip/pipInterceptor.js";
+import { ui } from "../../environment/userInteraction.js";
+import {
+  npmInterceptorForRegistry,
+  npmInterceptorForUrl,
+} from "./npm/npmInterceptor.js";
+import { jsrInterceptorForUrl } from "./jsr/jsrInterceptor.js";
+import {
+  pipInterceptorForRegistry,
+  pipInterceptorForUrl,
+} from "./pip/pipInterceptor.js";
+import { getEcosystemFromUserAgent } from "./userAgentEcosystemHints.js";
+import { vcsInterceptorForUrl } from "./vcs/vcsInterceptor.js";
 
 /**
  * @param {string} url
@@ -14,12 +25,69 @@ export function createInterceptorForUrl(url) {
   const ecosystem = getEcoSystem();
 
   if (ecosystem === ECOSYSTEM_JS) {
-    return npmInterceptorForUrl(url);
+    return (
+      npmInterceptorForUrl(url) ??
+      jsrInterceptorForUrl(url) ??
+      vcsInterceptorForUrl(url)
+    );
   }
 
   if (ecosystem === ECOSYSTEM_PY) {
-    return pipInterceptorForUrl(url);
+    return pipInterceptorForUrl(url) ?? vcsInterceptorForUrl(url);
   }
 
   return undefined;
 }
+
+/**
+ * Falls back on the User-Agent of the CONNECT request for a host that is not a
+ * known or configured registry, such as a custom mirror. The host is only
+ * intercepted when the User-Agent names a package manager of the current
+ * ecosystem, because the malware database is loaded for that ecosystem only.
+ *
+ * @param {string} url - The CONNECT target (host:port)
+ * @param {string | undefined} userAgent
+ * @returns {import("./interceptorBuilder.js").Interceptor | undefined}
+ */
+export function createInterceptorForUserAgentHint(url, userAgent) {
+  if (!getUaEcosystemHints()) {
+    return undefined;
+  }
+
+  const hintedEcosystem = getEcosystemFromUserAgent(userAgent);
+  if (!hintedEcosystem) {
+    return undefined;
+  }
+
+  const ecosystem = getEcoSystem();
+  if (hintedEcosystem !== ecosystem) {
+    ui.writeVerbose(
+      `Safe-chain: User-Agent "${userAgent}" for ${url} points to the ${hintedEcosystem} ecosystem, not ${ecosystem}, not inspecting`
+    );
+    return undefined;
+  }
+
+  const hostname = getHostname(url);
+  if (!hostname) {
+    return undefined;
+  }
+
+  ui.writeVerbose(
+    `Safe-chain: Inspecting ${hostname} as a ${ecosystem} registry based on User-Agent "${userAgent}"`
+  );
+  return ecosystem === ECOSYSTEM_JS
+    ? npmInterceptorForRegistry(hostname)
+    : pipInterceptorForRegistry(hostname);
+}
+
+/**
+ * @param {string} url
+ * @returns {string | undefined}
+ */
+function getHostname(url) {
+  try {
+    return new URL(`http://${url}`).hostname || undefined;
+  } catch {
+    return undefined;
+  }
+}
@@ -0,0 +1,79 @@
+/**
+ * @type {{
+ *   flaggedMaintainers: Map<string, number>,
+ *   flaggedVersions: Map<string, string>,
+ * }}
+ */
+const state = {
+  // maintainer name -> time the maintainer was flagged (ms since epoch)
+  flaggedMaintainers: new Map(),
+  // "name@version" -> maintainer, for versions seen in package metadata
+  flaggedVersions: new Map(),
+};
+
+/**
+ * Replaces the flagged maintainers, as shipped with the remote malware database.
+ *
+ * @param {import("../../api/aikido.js").FlaggedMaintainerEntry[]} entries
+ * @returns {void}
+ */
+export function setFlaggedMaintainers(entries) {
+  state.flaggedMaintainers = new Map(
+    entries.map((entry) => [
+      entry.maintainer.trim().toLowerCase(),
+      entry.flagged_on * 1000,
+    ])
+  );
+}
+
+/**
+ * @returns {boolean}
+ */
+export function hasFlaggedMaintainers() {
+  return state.flaggedMaintainers.size > 0;
+}
+
+/**
+ * Returns the flagged maintainer that published a version after being flagged.
+ * Versions published before the flag timestamp are not affected, so releases
+ * from before an account compromise keep working.
+ *
+ * @param {string | undefined} publisher
+ * @param {string | undefined} publishedAt - ISO timestamp from the registry metadata
+ * @returns {string | undefined}
+ */
+export function getFlaggedPublisher(publisher, publishedAt) {
+  if (!publisher || !publishedAt) {
+    return undefined;
+  }
+
+  const flaggedOn = state.flaggedMaintainers.get(publisher.toLowerCase());
+  const publishTime = new Date(publishedAt).getTime();
+  if (flaggedOn === undefined || Number.isNaN(publishTime)) {
+    return undefined;
+  }
+
+  return publishTime > flaggedOn ? publisher : undefined;
+}
+
+/**
+ * Remembers a version published by a flagged maintainer, so a later tarball
+ * download of that version can be blocked without the registry metadata.
+ *
+ * @param {string} packageName
+ * @param {string} version
+ * @param {string} maintainer
+ * @returns {void}
+ */
+export function recordFlaggedVersion(packageName, version, maintainer) {
+  state.flaggedVersions.set(`${packageName}@${version}`, maintainer);
+}
+
+/**
+ * @param {string} packageName
+ * @param {string} version
+ * @returns {string | undefined} - The flagged maintainer that published the version
+ */
+export function getFlaggedVersionPublisher(packageName, version) {
+  return state.flaggedVersions.get(`${packageName}@${version}`);
+}
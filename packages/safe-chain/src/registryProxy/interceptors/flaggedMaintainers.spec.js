@@ -0,0 +1,72 @@
+import { describe, it, beforeEach } from "node:test";
+import assert from "node:assert";
+import {
+  getFlaggedPublisher,
+  getFlaggedVersionPublisher,
+  hasFlaggedMaintainers,
+  recordFlaggedVersion,
+  setFlaggedMaintainers,
+} from "./flaggedMaintainers.js";
+
+describe("flaggedMaintainers", () => {
+  const flaggedOn = Date.parse("2025-09-15T12:00:00Z") / 1000;
+
+  beforeEach(() => {
+    setFlaggedMaintainers([
+      { maintainer: "Compromised-User", flagged_on: flaggedOn },
+    ]);
+  });
+
+  it("reports whether any maintainers are flagged", () => {
+    assert.strictEqual(hasFlaggedMaintainers(), true);
+
+    setFlaggedMaintainers([]);
+    assert.strictEqual(hasFlaggedMaintainers(), false);
+  });
+
+  it("flags versions published after the flag timestamp", () => {
+    assert.strictEqual(
+      getFlaggedPublisher("compromised-user", "2025-09-15T12:00:01Z"),
+      "compromised-user"
+    );
+  });
+
+  it("does not flag versions published at or before the flag timestamp", () => {
+    assert.strictEqual(
+      getFlaggedPublisher("compromised-user", "2025-09-15T12:00:00Z"),
+      undefined
+    );
+    assert.strictEqual(
+      getFlaggedPublisher("compromised-user", "2024-01-01T00:00:00Z"),
+      undefined
+    );
+  });
+
+  it("does not flag other maintainers or versions without publish info", () => {
+    assert.strictEqual(
+      getFlaggedPublisher("someone-else", "2025-10-01T00:00:00Z"),
+      undefined
+    );
+    assert.strictEqual(
+      getFlaggedPublisher(undefined, "2025-10-01T00:00:00Z"),
+      undefined
+    );
+    assert.strictEqual(
+      getFlaggedPublisher("compromised-user", "not a date"),
+      undefined
+    );
+  });
+
+  it("remembers flagged versions for tarball downloads", () => {
+    recordFlaggedVersion("@scope/pkg", "2.0.1", "compromised-user");
+
+    assert.strictEqual(
+      getFlaggedVersionPublisher("@scope/pkg", "2.0.1"),
+      "compromised-user"
+    );
+    assert.strictEqual(
+      getFlaggedVersionPublisher("@scope/pkg", "2.0.0"),
+      undefined
+    );
+  });
+});
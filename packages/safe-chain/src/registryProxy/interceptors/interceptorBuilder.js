@@ -1,24 +1,47 @@
 import { EventEmitter } from "events";
+import { getBlockStatus } from "../../config/settings.js";
+import { getFeedVersion } from "../../scanning/feedVersion.js";
+import { getRedirectUrl } from "../redirectMap.js";
 
 /**
  * @typedef {Object} Interceptor
- * @property {(targetUrl: string) => Promise<RequestInterceptionHandler>} handleRequest
+ * @property {(targetUrl: string, policyOverrides?: RequestPolicyOverrides) => Promise<RequestInterceptionHandler>} handleRequest
  * @property {(event: string, listener: (...args: any[]) => void) => Interceptor} on
  * @property {(event: string, ...args: any[]) => boolean} emit
  *
  *
+ * @typedef {import("../requestPolicyOverrides.js").RequestPolicyOverrides} RequestPolicyOverrides
+ *
+ *
  * @typedef {Object} RequestInterceptionContext
  * @property {string} targetUrl
- * @property {(packageName: string | undefined, version: string | undefined) => void} blockMalware
+ * @property {RequestPolicyOverrides} policyOverrides
+ * @property {(packageName: string | undefined, version: string | undefined) => void} identifyPackage - Records the package parsed from the URL
+ * @property {(packageName: string | undefined, version: string | undefined, message?: string, advisories?: string[]) => void} blockMalware
  * @property {(packageName: string, version: string, message: string) => void} blockMinimumAgeRequest
+ * @property {(packageName: string, version: string) => void} blockPrerelease
+ * @property {(packageName: string, version: string, scripts: string) => void} blockInstallScript
+ * @property {(packageName: string, version: string, description: string) => void} blockSizeAnomaly
+ * @property {(repository: string, ref: string | undefined) => void} blockVcsDependency
  * @property {(modificationFunc: (headers: NodeJS.Dict<string | string[]>) => NodeJS.Dict<string | string[]>) => void} modifyRequestHeaders
  * @property {(modificationFunc: (body: Buffer, headers: NodeJS.Dict<string | string[]> | undefined) => Buffer) => void} modifyBody
  * @property {(response: {statusCode: number, headers: NodeJS.Dict<string>, body: Buffer}) => void} setSyntheticResponse
  * @property {() => RequestInterceptionHandler} build
  *
  *
+ * @typedef {Object} BlockResponse
+ * @property {number} statusCode
+ * @property {string} message
+ * @property {Record<string, string>} headers - X-Safe-Chain-* headers describing the block
+ * @property {string} reason - One of the BLOCK_REASON_* values
+ * @property {string | undefined} packageName
+ * @property {string | undefined} version
+ * @property {string | undefined} redirectUrl - The replacement the request is redirected to, if any
+ *
  * @typedef {Object} RequestInterceptionHandler
- * @property {{statusCode: number, message: string} | undefined} blockResponse
+ * @property {string | undefined} packageName - As parsed from the URL by the interceptor
+ * @property {string | undefined} version
+ * @property {BlockResponse | undefined} blockResponse
  * @property {{statusCode: number, headers: NodeJS.Dict<string>, body: Buffer} | undefined} syntheticResponse
  * @property {(headers: NodeJS.Dict<string | string[]> | undefined) => NodeJS.Dict<string | string[]> | undefined} modifyRequestHeaders
  * @property {() => boolean} modifiesResponse
@@ -28,6 +51,7 @@ import { EventEmitter } from "events";
  * @property {string} packageName
  * @property {string} version
  * @property {string} targetUrl
+ * @property {string[]} advisories - Advisory IDs the feed lists for the package
  * @property {number} timestamp
  *
  * @typedef {Object} MinimumAgeRequestBlockedEvent
@@ -35,26 +59,77 @@ import { EventEmitter } from "events";
  * @property {string} version
  * @property {string} targetUrl
  * @property {number} timestamp
+ *
+ * @typedef {Object} PrereleaseBlockedEvent
+ * @property {string} packageName
+ * @property {string} version
+ * @property {string} targetUrl
+ * @property {"prerelease"} reason
+ * @property {number} timestamp
+ *
+ * @typedef {Object} InstallScriptBlockedEvent
+ * @property {string} packageName
+ * @property {string} version
+ * @property {string} scripts - The install scripts, for example "postinstall"
+ * @property {string} targetUrl
+ * @property {number} timestamp
+ *
+ * @typedef {Object} SizeAnomalyBlockedEvent
+ * @property {string} packageName
+ * @property {string} version
+ * @property {string} description - The observed and expected sizes
+ * @property {string} targetUrl
+ * @property {number} timestamp
+ *
+ * @typedef {Object} PackageRedirectedEvent
+ * @property {string | undefined} packageName
+ * @property {string | undefined} version
+ * @property {string} reason - The block reason the redirect replaces
+ * @property {string} targetUrl
+ * @property {string} redirectUrl
+ * @property {number} timestamp
+ *
+ * @typedef {Object} VcsDependencyBlockedEvent
+ * @property {string} repository - Host and path of the repository, for example "github.com/org/repo"
+ * @property {string | undefined} ref - The git ref, undefined when not part of the URL
+ * @property {string} targetUrl
+ * @property {number} timestamp
  */
 
+/** Block reasons, as sent in the X-Safe-Chain-Reason header */
+export const BLOCK_REASON_MALWARE = "malware";
+export const BLOCK_REASON_MINIMUM_AGE = "minimum-age";
+export const BLOCK_REASON_PRERELEASE = "prerelease";
+export const BLOCK_REASON_INSTALL_SCRIPT = "install-script";
+export const BLOCK_REASON_SIZE_ANOMALY = "size-anomaly";
+export const BLOCK_REASON_VCS_DEPENDENCY = "vcs-dependency";
+export const BLOCK_REASON_UNKNOWN_REGISTRY = "unknown-registry";
+
 /**
  * @param {(requestHandlerBuilder: RequestInterceptionContext) => Promise<void>} requestInterceptionFunc
+ * @param {string} [packageType] - Prefix of the package in the X-Safe-Chain-Package header, such as "npm" or "pypi"
  * @returns {Interceptor}
  */
-export function interceptRequests(requestInterceptionFunc) {
-  return buildInterceptor([requestInterceptionFunc]);
+export function interceptRequests(requestInterceptionFunc, packageType) {
+  return buildInterceptor([requestInterceptionFunc], packageType);
 }
 
 /**
  * @param {Array<(requestHandlerBuilder: RequestInterceptionContext) => Promise<void>>} requestHandlers
+ * @param {string | undefined} packageType
  * @returns {Interceptor}
  */
-function buildInterceptor(requestHandlers) {
+function buildInterceptor(requestHandlers, packageType) {
   const eventEmitter = new EventEmitter();
 
   return {
-    async handleRequest(targetUrl) {
-      const requestContext = createRequestContext(targetUrl, eventEmitter);
+    async handleRequest(targetUrl, policyOverrides = {}) {
+      const requestContext = createRequestContext(
+        targetUrl,
+        policyOverrides,
+        eventEmitter,
+        packageType
+      );
 
       for (const handler of requestHandlers) {
         await handler(requestContext);
@@ -74,11 +149,18 @@ function buildInterceptor(requestHandlers) {
 
 /**
  * @param {string} targetUrl
+ * @param {RequestPolicyOverrides} policyOverrides
  * @param {import('events').EventEmitter} eventEmitter
+ * @param {string | undefined} packageType
  * @returns {RequestInterceptionContext}
  */
-function createRequestContext(targetUrl, eventEmitter) {
-  /** @type {{statusCode: number, message: string} | undefined}  */
+function createRequestContext(
+  targetUrl,
+  policyOverrides,
+  eventEmitter,
+  packageType
+) {
+  /** @type {BlockResponse | undefined}  */
   let blockResponse = undefined;
   /** @type {{statusCode: number, headers: NodeJS.Dict<string>, body: Buffer} | undefined} */
   let syntheticResponse = undefined;
@@ -86,19 +168,36 @@ function createRequestContext(targetUrl, eventEmitter) {
   let reqheaderModificationFuncs = [];
   /** @type {Array<(body: Buffer, headers: NodeJS.Dict<string | string[]> | undefined) => Buffer>} */
   let modifyBodyFuncs = [];
+  /** @type {{packageName: string | undefined, version: string | undefined}} */
+  let identifiedPackage = { packageName: undefined, version: undefined };
 
   /**
    * @param {string | undefined} packageName
    * @param {string | undefined} version
+   * @param {string} [message]
+   * @param {string[]} [advisories]
    */
-  function blockMalwareSetup(packageName, version) {
-    blockResponse = createBlockResponse("Forbidden - blocked by safe-chain");
+  function blockMalwareSetup(
+    packageName,
+    version,
+    message = "Forbidden - blocked by safe-chain",
+    advisories = []
+  ) {
+    blockResponse = createBlockResponse(
+      advisories.length > 0
+        ? `${message} (advisories: ${advisories.join(", ")})`
+        : message,
+      BLOCK_REASON_MALWARE,
+      packageName,
+      version
+    );
 
     // Emit the malwareBlocked event
-    eventEmitter.emit("malwareBlocked", {
+    emitBlockEvent("malwareBlocked", {
       packageName,
       version,
       targetUrl,
+      advisories,
       timestamp: Date.now(),
     });
   }
@@ -111,23 +210,169 @@ function createRequestContext(targetUrl, eventEmitter) {
     /** @type {string} */ version,
     /** @type {string} */ message
   ) {
-    blockResponse = createBlockResponse(message);
-    eventEmitter.emit("minimumAgeRequestBlocked", {
+    blockResponse = createBlockResponse(
+      message,
+      BLOCK_REASON_MINIMUM_AGE,
+      packageName,
+      version
+    );
+    emitBlockEvent("minimumAgeRequestBlocked", {
+      packageName,
+      version,
+      targetUrl,
+      timestamp: Date.now(),
+    });
+  }
+
+  /**
+   * @param {string} packageName
+   * @param {string} version
+   */
+  function blockPrereleaseSetup(packageName, version) {
+    blockResponse = createBlockResponse(
+      `Forbidden - blocked by safe-chain pre-release policy (${packageName}@${version})`,
+      BLOCK_REASON_PRERELEASE,
+      packageName,
+      version
+    );
+    emitBlockEvent("prereleaseBlocked", {
+      packageName,
+      version,
+      targetUrl,
+      reason: "prerelease",
+      timestamp: Date.now(),
+    });
+  }
+
+  /**
+   * @param {string} packageName
+   * @param {string} version
+   * @param {string} scripts
+   */
+  function blockInstallScriptSetup(packageName, version, scripts) {
+    blockResponse = createBlockResponse(
+      `Forbidden - blocked by safe-chain install script policy (${packageName}@${version} runs ${scripts})`,
+      BLOCK_REASON_INSTALL_SCRIPT,
+      packageName,
+      version
+    );
+    emitBlockEvent("installScriptBlocked", {
+      packageName,
+      version,
+      scripts,
+      targetUrl,
+      timestamp: Date.now(),
+    });
+  }
+
+  /**
+   * @param {string} packageName
+   * @param {string} version
+   * @param {string} description
+   */
+  function blockSizeAnomalySetup(packageName, version, description) {
+    blockResponse = createBlockResponse(
+      `Forbidden - blocked by safe-chain size anomaly check (${packageName}@${version} ${description})`,
+      BLOCK_REASON_SIZE_ANOMALY,
+      packageName,
+      version
+    );
+    emitBlockEvent("sizeAnomalyBlocked", {
       packageName,
       version,
+      description,
       targetUrl,
       timestamp: Date.now(),
     });
   }
 
+  /**
+   * @param {string} repository
+   * @param {string | undefined} ref
+   */
+  function blockVcsDependencySetup(repository, ref) {
+    blockResponse = createBlockResponse(
+      `Forbidden - blocked by safe-chain VCS dependency policy (${repository}${
+        ref ? `@${ref}` : ""
+      })`,
+      BLOCK_REASON_VCS_DEPENDENCY,
+      repository,
+      ref
+    );
+    emitBlockEvent("vcsDependencyBlocked", {
+      repository,
+      ref,
+      targetUrl,
+      timestamp: Date.now(),
+    });
+  }
+
+  /**
+   * Emits the event of a block, or a packageRedirected event instead when the
+   * blocked package is redirected to a replacement.
+   *
+   * @param {string} eventName
+   * @param {object} event
+   */
+  function emitBlockEvent(eventName, event) {
+    if (!blockResponse?.redirectUrl) {
+      eventEmitter.emit(eventName, event);
+      return;
+    }
+
+    /** @type {PackageRedirectedEvent} */
+    const redirectedEvent = {
+      packageName: blockResponse.packageName,
+      version: blockResponse.version,
+      reason: blockResponse.reason,
+      targetUrl,
+      redirectUrl: blockResponse.redirectUrl,
+      timestamp: Date.now(),
+    };
+    eventEmitter.emit("packageRedirected", redirectedEvent);
+  }
+
   /**
    * @param {string} message
-   * @returns {{statusCode: number, message: string}}
+   * @param {string} reason
+   * @param {string | undefined} packageName
+   * @param {string | undefined} version
+   * @returns {BlockResponse}
    */
-  function createBlockResponse(message) {
+  function createBlockResponse(message, reason, packageName, version) {
+    const redirectUrl = getRedirectUrl(packageType, packageName, targetUrl);
+
+    /** @type {Record<string, string>} */
+    const headers = redirectUrl
+      ? {
+          "X-Safe-Chain-Redirected": "true",
+          "X-Safe-Chain-Reason": reason,
+          Location: redirectUrl,
+        }
+      : {
+          "X-Safe-Chain-Blocked": "true",
+          "X-Safe-Chain-Reason": reason,
+        };
+
+    const packageSpec = formatPackageSpec(packageType, packageName, version);
+    if (packageSpec) {
+      headers["X-Safe-Chain-Package"] = packageSpec;
+    }
+    const feedVersion = getFeedVersion();
+    if (feedVersion) {
+      headers["X-Safe-Chain-Feed-Version"] = feedVersion;
+    }
+
     return {
-      statusCode: 403,
-      message,
+      statusCode: redirectUrl ? 302 : getBlockStatus(),
+      message: redirectUrl
+        ? `Found - blocked by safe-chain (${reason}), redirected to a replacement`
+        : message,
+      headers,
+      reason,
+      packageName,
+      version,
+      redirectUrl,
     };
   }
 
@@ -164,6 +409,8 @@ function createRequestContext(targetUrl, eventEmitter) {
 
     // These functions are invoked in the proxy, allowing to apply the configured modifications
     return {
+      packageName: identifiedPackage.packageName,
+      version: identifiedPackage.version,
       blockResponse,
       syntheticResponse,
       modifyRequestHeaders: modifyRequestHeaders,
@@ -175,11 +422,40 @@ function createRequestContext(targetUrl, eventEmitter) {
   // These functions are used to setup the modifications
   return {
     targetUrl,
+    policyOverrides,
+    identifyPackage: (packageName, version) => {
+      identifiedPackage = { packageName, version };
+    },
     blockMalware: blockMalwareSetup,
     blockMinimumAgeRequest: blockMinimumAgeRequestSetup,
+    blockPrerelease: blockPrereleaseSetup,
+    blockInstallScript: blockInstallScriptSetup,
+    blockSizeAnomaly: blockSizeAnomalySetup,
+    blockVcsDependency: blockVcsDependencySetup,
     modifyRequestHeaders: (func) => reqheaderModificationFuncs.push(func),
     modifyBody: (func) => modifyBodyFuncs.push(func),
     setSyntheticResponse: (response) => { syntheticResponse = response; },
     build,
   };
 }
+
+/**
+ * Formats a package like "npm:foo@1.2.3". Names and versions come from the
+ * request URL, so values that are not valid in a header are left out.
+ *
+ * @param {string | undefined} packageType
+ * @param {string | undefined} packageName
+ * @param {string | undefined} version
+ * @returns {string | undefined}
+ */
+function formatPackageSpec(packageType, packageName, version) {
+  if (!packageName) {
+    return undefined;
+  }
+
+  const packageSpec =
+    (packageType ? `${packageType}:` : "") +
+    packageName +
+    (version ? `@${version}` : "");
+  return /^[\x21-\x7e]+$/.test(packageSpec) ? packageSpec : undefined;
+}
@@ -0,0 +1,43 @@
+import { interceptRequests } from "../interceptorBuilder.js";
+import { isNpmPrereleaseVersion } from "../npm/parseNpmPackageUrl.js";
+import { isBlockedPrerelease } from "../prereleasePolicy.js";
+import {
+  DENO_LAND_HOST,
+  JSR_HOST,
+  parseJsrPackageUrl,
+} from "./parseJsrPackageUrl.js";
+
+const knownDenoRegistries = [JSR_HOST, DENO_LAND_HOST];
+
+/**
+ * @param {string} url
+ * @returns {import("../interceptorBuilder.js").Interceptor | undefined}
+ */
+export function jsrInterceptorForUrl(url) {
+  const registry = knownDenoRegistries.find((reg) => url.includes(reg));
+
+  if (registry) {
+    return buildJsrInterceptor();
+  }
+
+  return undefined;
+}
+
+/**
+ * @returns {import("../interceptorBuilder.js").Interceptor}
+ */
+function buildJsrInterceptor() {
+  return interceptRequests(async (reqContext) => {
+    const { packageName, version } = parseJsrPackageUrl(reqContext.targetUrl);
+
+    if (!packageName || !version) {
+      return;
+    }
+
+    // No malware feed covers JSR or deno.land/x modules, so only the
+    // pre-release policy applies. JSR and deno.land/x versions follow SemVer.
+    if (isBlockedPrerelease(packageName, version, isNpmPrereleaseVersion)) {
+      reqContext.blockPrerelease(packageName, version);
+    }
+  }, "jsr");
+}
@@ -0,0 +1,71 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+let malwarePackages = new Set();
+let blockPrereleasesSetting = false;
+
+mock.module("../../../scanning/audit/index.js", {
+  namedExports: {
+    isMalwarePackage: async (packageName, version) =>
+      malwarePackages.has(`${packageName}@${version}`),
+    getMalwareAdvisories: async () => [],
+  },
+});
+
+mock.module("../../../config/settings.js", {
+  namedExports: {
+    getBlockStatus: () => 403,
+    getRedirectMap: () => ({}),
+    ECOSYSTEM_JS: "js",
+    ECOSYSTEM_PY: "py",
+    getEcoSystem: () => "js",
+    getMinimumPackageAgeExclusions: () => [],
+    getBlockPrereleases: () => blockPrereleasesSetting,
+    getWarnInstallScripts: () => false,
+    getCheckDependencyClosure: () => false,
+    getBlockInstallScripts: () => false,
+    getInstallScriptAllowlist: () => [],
+    getFlagSizeAnomaly: () => false,
+    getBlockSizeAnomaly: () => false,
+    getSizeAnomalyFactor: () => 10,
+    getPrereleaseAllowlist: () => [],
+  },
+});
+
+describe("jsrInterceptor", async () => {
+  const { jsrInterceptorForUrl } = await import("./jsrInterceptor.js");
+
+  beforeEach(() => {
+    malwarePackages = new Set();
+    blockPrereleasesSetting = false;
+  });
+
+  it("creates an interceptor for jsr.io and deno.land", () => {
+    assert.ok(jsrInterceptorForUrl("jsr.io:443"));
+    assert.ok(jsrInterceptorForUrl("deno.land:443"));
+    assert.equal(jsrInterceptorForUrl("registry.npmjs.org:443"), undefined);
+  });
+
+  it("does not check modules against the malware list", async () => {
+    const url = "https://jsr.io/@evil/pkg/1.0.0/mod.ts";
+    malwarePackages = new Set(["jsr:@evil/pkg@1.0.0"]);
+
+    const interceptor = jsrInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.equal(result.blockResponse, undefined);
+  });
+
+  it("blocks JSR pre-releases when pre-releases are blocked", async () => {
+    const url = "https://jsr.io/@luca/flag/1.0.0-beta.1/main.ts";
+    blockPrereleasesSetting = true;
+
+    const interceptor = jsrInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.equal(
+      result.blockResponse.message,
+      "Forbidden - blocked by safe-chain pre-release policy (jsr:@luca/flag@1.0.0-beta.1)"
+    );
+  });
+});
@@ -0,0 +1,91 @@
+import { readPackageNameFromPathSegments } from "../npm/parseNpmPackageUrl.js";
+
+export const JSR_HOST = "jsr.io";
+export const DENO_LAND_HOST = "deno.land";
+
+/**
+ * Parses Deno module URLs into a package coordinate. The package name uses the
+ * specifier Deno itself uses for the package, so it cannot collide with npm names.
+ *
+ * @example
+ * parseJsrPackageUrl("https://jsr.io/@std/path/1.0.8/mod.ts")
+ * // => { packageName: "jsr:@std/path", version: "1.0.8" }
+ *
+ * parseJsrPackageUrl("https://jsr.io/@std/path/1.0.8_meta.json")
+ * // => { packageName: "jsr:@std/path", version: "1.0.8" }
+ *
+ * parseJsrPackageUrl("https://deno.land/x/oak@v12.6.1/mod.ts")
+ * // => { packageName: "deno.land/x/oak", version: "v12.6.1" }
+ *
+ * @param {string} url
+ * @returns {{packageName: string | undefined, version: string | undefined}}
+ */
+export function parseJsrPackageUrl(url) {
+  let parsedUrl;
+  try {
+    parsedUrl = new URL(url);
+  } catch {
+    return { packageName: undefined, version: undefined };
+  }
+
+  const segments = parsedUrl.pathname
+    .split("/")
+    .filter(Boolean)
+    .map((segment) => decodeURIComponent(segment));
+
+  if (parsedUrl.hostname === JSR_HOST) {
+    return parseJsrPath(segments);
+  }
+
+  if (parsedUrl.hostname === DENO_LAND_HOST) {
+    return parseDenoLandPath(segments);
+  }
+
+  return { packageName: undefined, version: undefined };
+}
+
+/**
+ * @param {string[]} segments
+ * @returns {{packageName: string | undefined, version: string | undefined}}
+ */
+function parseJsrPath(segments) {
+  const { packageName, remainingSegments } =
+    readPackageNameFromPathSegments(segments);
+
+  // JSR packages are always scoped
+  if (!packageName || !packageName.startsWith("@")) {
+    return { packageName: undefined, version: undefined };
+  }
+
+  const [versionSegment] = remainingSegments;
+  if (!versionSegment || versionSegment === "meta.json") {
+    return { packageName: `jsr:${packageName}`, version: undefined };
+  }
+
+  const version = versionSegment.replace(/_meta\.json$/, "");
+  return { packageName: `jsr:${packageName}`, version };
+}
+
+/**
+ * @param {string[]} segments
+ * @returns {{packageName: string | undefined, version: string | undefined}}
+ */
+function parseDenoLandPath(segments) {
+  const [prefix, moduleSegment] = segments;
+  if (prefix !== "x" || !moduleSegment) {
+    return { packageName: undefined, version: undefined };
+  }
+
+  const separatorIndex = moduleSegment.indexOf("@");
+  if (separatorIndex === -1) {
+    return { packageName: `deno.land/x/${moduleSegment}`, version: undefined };
+  }
+
+  const name = moduleSegment.substring(0, separatorIndex);
+  const version = moduleSegment.substring(separatorIndex + 1);
+  if (!name || !version) {
+    return { packageName: undefined, version: undefined };
+  }
+
+  return { packageName: `deno.land/x/${name}`, version };
+}
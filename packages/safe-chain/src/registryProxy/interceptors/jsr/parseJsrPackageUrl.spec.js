@@ -0,0 +1,56 @@
+import { describe, it } from "node:test";
+import assert from "node:assert";
+import { parseJsrPackageUrl } from "./parseJsrPackageUrl.js";
+
+describe("parseJsrPackageUrl", () => {
+  const cases = [
+    {
+      url: "https://jsr.io/@std/path/1.0.8/mod.ts",
+      expected: { packageName: "jsr:@std/path", version: "1.0.8" },
+    },
+    {
+      url: "https://jsr.io/@std/path/1.0.8/posix/join.ts",
+      expected: { packageName: "jsr:@std/path", version: "1.0.8" },
+    },
+    {
+      url: "https://jsr.io/@std/path/1.0.8_meta.json",
+      expected: { packageName: "jsr:@std/path", version: "1.0.8" },
+    },
+    {
+      url: "https://jsr.io/@luca/flag/1.0.0-beta.1/main.ts",
+      expected: { packageName: "jsr:@luca/flag", version: "1.0.0-beta.1" },
+    },
+    {
+      url: "https://jsr.io/@std/path/meta.json",
+      expected: { packageName: "jsr:@std/path", version: undefined },
+    },
+    {
+      url: "https://deno.land/x/oak@v12.6.1/mod.ts",
+      expected: { packageName: "deno.land/x/oak", version: "v12.6.1" },
+    },
+    {
+      url: "https://deno.land/x/oak/mod.ts",
+      expected: { packageName: "deno.land/x/oak", version: undefined },
+    },
+    // JSR only hosts scoped packages
+    {
+      url: "https://jsr.io/docs/introduction",
+      expected: { packageName: undefined, version: undefined },
+    },
+    // The standard library outside of /x/ is not a third-party module
+    {
+      url: "https://deno.land/std@0.200.0/path/mod.ts",
+      expected: { packageName: undefined, version: undefined },
+    },
+    {
+      url: "https://registry.npmjs.org/@std/path/-/path-1.0.8.tgz",
+      expected: { packageName: undefined, version: undefined },
+    },
+  ];
+
+  for (const { url, expected } of cases) {
+    it(`parses ${url}`, () => {
+      assert.deepEqual(parseJsrPackageUrl(url), expected);
+    });
+  }
+});
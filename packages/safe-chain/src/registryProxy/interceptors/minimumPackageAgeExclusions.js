@@ -1,18 +1,58 @@
-import { getMinimumPackageAgeExclusions, getEcoSystem } from "../../config/settings.js";
+import {
+  ECOSYSTEM_PY,
+  getMinimumPackageAgeExclusions,
+  getEcoSystem,
+} from "../../config/settings.js";
 import { getEquivalentPackageNames } from "../../scanning/packageNameVariants.js";
 
+const ECOSYSTEM_PREFIXES = ["npm:", "pypi:"];
+
 /**
- * Checks if a package name matches an exclusion pattern.
- * Supports trailing wildcard (*) for prefix matching.
+ * Checks if a package name matches an exclusion pattern. A "*" in the pattern
+ * matches any characters except "/", so "@acme/*" matches all packages of the
+ * scope and "acme-*" all packages with the prefix. A pattern prefixed with
+ * "npm:" or "pypi:" only applies to that ecosystem.
  * @param {string} packageName
  * @param {string} pattern
  * @returns {boolean}
  */
 export function matchesExclusionPattern(packageName, pattern) {
-  if (pattern.endsWith("/*")) {
-    return packageName.startsWith(pattern.slice(0, -1));
+  const { prefix, pattern: namePattern } = splitEcosystemPrefix(pattern);
+  if (prefix && (prefix === "pypi:") !== (getEcoSystem() === ECOSYSTEM_PY)) {
+    return false;
+  }
+
+  if (!namePattern.includes("*")) {
+    return packageName === namePattern;
+  }
+  return globToRegExp(namePattern).test(packageName);
+}
+
+/**
+ * Splits the ecosystem prefix ("npm:" or "pypi:") off a pattern.
+ * @param {string} pattern
+ * @returns {{prefix: string, pattern: string}} - prefix is "" without one
+ */
+export function splitEcosystemPrefix(pattern) {
+  const prefix = ECOSYSTEM_PREFIXES.find((candidate) =>
+    pattern.toLowerCase().startsWith(candidate)
+  );
+  if (!prefix) {
+    return { prefix: "", pattern };
   }
-  return packageName === pattern;
+  return { prefix, pattern: pattern.slice(prefix.length) };
+}
+
+/**
+ * @param {string} pattern
+ * @returns {RegExp}
+ */
+function globToRegExp(pattern) {
+  const source = pattern
+    .split("*")
+    .map((part) => part.replace(/[.*+?^${}()|[\]\\]/g, "\\$&"))
+    .join("[^/]*");
+  return new RegExp(`^${source}$`);
 }
 
 /**
@@ -0,0 +1,58 @@
+import { describe, it, afterEach } from "node:test";
+import assert from "node:assert";
+import { matchesExclusionPattern } from "./minimumPackageAgeExclusions.js";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  setEcoSystem,
+} from "../../config/settings.js";
+
+describe("matchesExclusionPattern", () => {
+  afterEach(() => {
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  it("matches exact package names", () => {
+    assert.equal(matchesExclusionPattern("lodash", "lodash"), true);
+    assert.equal(matchesExclusionPattern("lodash-es", "lodash"), false);
+  });
+
+  it("matches all packages of a scope with @scope/*", () => {
+    assert.equal(matchesExclusionPattern("@acme/utils", "@acme/*"), true);
+    assert.equal(matchesExclusionPattern("@acme/ui-kit", "npm:@acme/*"), true);
+  });
+
+  it("does not match unrelated scopes or names with a scope glob", () => {
+    assert.equal(matchesExclusionPattern("@acme-evil/utils", "@acme/*"), false);
+    assert.equal(matchesExclusionPattern("acme-utils", "@acme/*"), false);
+    assert.equal(matchesExclusionPattern("@other/acme", "@acme/*"), false);
+  });
+
+  it("matches packages with a prefix glob", () => {
+    setEcoSystem(ECOSYSTEM_PY);
+
+    assert.equal(matchesExclusionPattern("acme-utils", "pypi:acme-*"), true);
+    assert.equal(matchesExclusionPattern("acme-", "acme-*"), true);
+  });
+
+  it("does not match other names containing the prefix", () => {
+    setEcoSystem(ECOSYSTEM_PY);
+
+    assert.equal(matchesExclusionPattern("notacme-utils", "acme-*"), false);
+    assert.equal(matchesExclusionPattern("acme", "acme-*"), false);
+    assert.equal(matchesExclusionPattern("acmeutils", "acme-*"), false);
+  });
+
+  it("treats other characters in the pattern literally", () => {
+    assert.equal(matchesExclusionPattern("acmeXjs", "acme.js"), false);
+    assert.equal(matchesExclusionPattern("acmeXjs-cli", "acme.js-*"), false);
+    assert.equal(matchesExclusionPattern("acme.js-cli", "acme.js-*"), true);
+  });
+
+  it("applies ecosystem-prefixed patterns to their ecosystem only", () => {
+    assert.equal(matchesExclusionPattern("acme-utils", "pypi:acme-*"), false);
+
+    setEcoSystem(ECOSYSTEM_PY);
+    assert.equal(matchesExclusionPattern("acme-utils", "npm:acme-*"), false);
+  });
+});
@@ -0,0 +1,211 @@
+import * as semver from "semver";
+import { resolvePackageVersion } from "../../../api/npmApi.js";
+import { getCheckDependencyClosure } from "../../../config/settings.js";
+import { ui } from "../../../environment/userInteraction.js";
+import { isMalwarePackage } from "../../../scanning/audit/index.js";
+import { createBoundedCache } from "../../boundedCache.js";
+import { getHeaderValueAsString } from "../../http-utils.js";
+import { getPublishTime } from "./publishTimes.js";
+
+// The dependencies npm installs together with the package
+const DEPENDENCY_FIELDS = ["dependencies", "optionalDependencies"];
+
+// Specs like "latest" or "next", resolved through the dist-tags
+const DIST_TAG_PATTERN = /^[a-z][\w.-]*$/i;
+
+/**
+ * @typedef {Object} MaliciousDependency
+ * @property {string} name
+ * @property {string} range - The range the package declares
+ * @property {string} version - The version the range resolves to
+ */
+
+/**
+ * @type {{
+ *   dependencies: import("../../boundedCache.js").BoundedCache<Map<string, Record<string, string>>>,
+ *   resolvedVersions: import("../../boundedCache.js").BoundedCache<Promise<string | null>>,
+ * }}
+ */
+const state = {
+  // package name -> version -> dependency name -> declared range
+  dependencies: createBoundedCache("dependencies"),
+  // "name@range" -> the resolved version, so every range is only fetched once
+  resolvedVersions: createBoundedCache("resolved_ranges"),
+};
+
+/**
+ * @returns {boolean}
+ */
+export function isDependencyClosureCheckEnabled() {
+  return getCheckDependencyClosure();
+}
+
+/**
+ * Remembers the declared dependencies of all versions in npm package
+ * metadata, so the tarball download of a version can be checked against the
+ * malware list before its dependencies are fetched. Both the full and the
+ * abbreviated metadata list them.
+ *
+ * @param {Buffer} body
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {void}
+ */
+export function recordDependencies(body, headers) {
+  try {
+    const contentType = getHeaderValueAsString(headers, "content-type");
+    if (!contentType?.toLowerCase().includes("json") || body.byteLength === 0) {
+      return;
+    }
+
+    const bodyJson = JSON.parse(body.toString("utf8"));
+    if (typeof bodyJson.name !== "string" || !bodyJson.versions) {
+      return;
+    }
+
+    /** @type {Map<string, Record<string, string>>} */
+    const versionDependencies = new Map();
+    for (const [version, metadata] of Object.entries(bodyJson.versions)) {
+      const dependencies = getDeclaredDependencies(metadata);
+      if (Object.keys(dependencies).length > 0) {
+        versionDependencies.set(version, dependencies);
+      }
+    }
+    if (versionDependencies.size > 0) {
+      state.dependencies.set(bodyJson.name, versionDependencies);
+    }
+  } catch (/** @type {any} */ err) {
+    ui.writeVerbose(
+      `Safe-chain: Package metadata not in expected format - skipping dependency closure check. Error: ${err.message}`
+    );
+  }
+}
+
+/**
+ * @param {any} metadata - The metadata of a single version
+ * @returns {Record<string, string>}
+ */
+function getDeclaredDependencies(metadata) {
+  /** @type {Record<string, string>} */
+  const dependencies = {};
+  for (const field of DEPENDENCY_FIELDS) {
+    const declared = metadata?.[field];
+    if (!declared || typeof declared !== "object") {
+      continue;
+    }
+    for (const [name, range] of Object.entries(declared)) {
+      if (typeof range === "string") {
+        dependencies[name] = range;
+      }
+    }
+  }
+  return dependencies;
+}
+
+/**
+ * Resolves the declared dependencies of a version that is downloaded the way
+ * npm would without a lockfile, to the highest version that satisfies the
+ * range, and returns the first one that is malware. Only the direct
+ * dependencies are resolved; theirs are checked when they are downloaded.
+ *
+ * @param {string | undefined} packageName
+ * @param {string | undefined} version
+ * @returns {Promise<MaliciousDependency | undefined>}
+ */
+export async function findMaliciousDependency(packageName, version) {
+  if (!packageName || !version || !isDependencyClosureCheckEnabled()) {
+    return undefined;
+  }
+
+  const dependencies = state.dependencies.get(packageName)?.get(version);
+  if (!dependencies) {
+    return undefined;
+  }
+
+  const results = await Promise.all(
+    Object.entries(dependencies).map(([name, range]) =>
+      checkDependency(name, range)
+    )
+  );
+  return results.find((result) => result !== undefined);
+}
+
+/**
+ * @param {string} name
+ * @param {string} range
+ * @returns {Promise<MaliciousDependency | undefined>}
+ */
+async function checkDependency(name, range) {
+  const target = parseRegistrySpec(name, range);
+  if (!target) {
+    // Git, file and URL dependencies do not come from the registry
+    return undefined;
+  }
+
+  const resolvedVersion = await resolveVersion(target.name, target.range);
+  if (!resolvedVersion) {
+    return undefined;
+  }
+
+  const publishedAt = getPublishTime(target.name, resolvedVersion);
+  if (await isMalwarePackage(target.name, resolvedVersion, publishedAt)) {
+    return { name: target.name, range, version: resolvedVersion };
+  }
+  return undefined;
+}
+
+/**
+ * @param {string} name
+ * @param {string} spec - For example "^1.2.0", "latest" or "npm:other@^2.0.0"
+ * @returns {{name: string, range: string} | undefined}
+ */
+function parseRegistrySpec(name, spec) {
+  const trimmedSpec = spec.trim();
+
+  if (trimmedSpec.startsWith("npm:")) {
+    const alias = trimmedSpec.slice("npm:".length);
+    // The first character can be the @ of a scope
+    const separator = alias.indexOf("@", 1);
+    return separator === -1
+      ? { name: alias, range: "*" }
+      : parseRegistrySpec(
+          alias.slice(0, separator),
+          alias.slice(separator + 1)
+        );
+  }
+
+  if (semver.validRange(trimmedSpec) || DIST_TAG_PATTERN.test(trimmedSpec)) {
+    return { name, range: trimmedSpec || "*" };
+  }
+  return undefined;
+}
+
+/**
+ * @param {string} name
+ * @param {string} range
+ * @returns {Promise<string | null>}
+ */
+function resolveVersion(name, range) {
+  const key = `${name}@${range}`;
+  let resolved = state.resolvedVersions.get(key);
+  if (!resolved) {
+    resolved = resolvePackageVersion(name, range).catch(() => null);
+    state.resolvedVersions.set(key, resolved);
+  }
+  return resolved;
+}
+
+/**
+ * @param {MaliciousDependency} dependency
+ * @returns {string}
+ */
+export function describeMaliciousDependency(dependency) {
+  return `${dependency.name}@${dependency.range}, which resolves to malware (${dependency.name}@${dependency.version})`;
+}
+
+/**
+ * @returns {void}
+ */
+export function resetDependencyClosure() {
+  state.dependencies.clear();
+  state.resolvedVersions.clear();
+}
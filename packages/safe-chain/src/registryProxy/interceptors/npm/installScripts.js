@@ -0,0 +1,144 @@
+import {
+  getBlockInstallScripts,
+  getInstallScriptAllowlist,
+  getWarnInstallScripts,
+} from "../../../config/settings.js";
+import { ui } from "../../../environment/userInteraction.js";
+import { createBoundedCache } from "../../boundedCache.js";
+import { getHeaderValueAsString } from "../../http-utils.js";
+import { matchesExclusionPattern } from "../minimumPackageAgeExclusions.js";
+
+// The lifecycle scripts npm runs when it installs a package
+const INSTALL_SCRIPTS = ["preinstall", "install", "postinstall"];
+
+/**
+ * @type {{
+ *   installScriptVersions: import("../../boundedCache.js").BoundedCache<Map<string, string>>,
+ *   warnedVersions: Set<string>,
+ * }}
+ */
+const state = {
+  // package name -> version -> the install scripts, from package metadata
+  installScriptVersions: createBoundedCache("install_scripts"),
+  // "name@version" of the versions a warning was written for
+  warnedVersions: new Set(),
+};
+
+/**
+ * @returns {boolean}
+ */
+export function isInstallScriptCheckEnabled() {
+  return getWarnInstallScripts() || getBlockInstallScripts();
+}
+
+/**
+ * Remembers the versions with install scripts in npm package metadata, so the
+ * tarball download of such a version can be reported or blocked. The full
+ * metadata lists the scripts, the abbreviated format only has `hasInstallScript`.
+ *
+ * @param {Buffer} body
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {void}
+ */
+export function recordInstallScriptVersions(body, headers) {
+  try {
+    // application/json or application/vnd.npm.install-v1+json
+    const contentType = getHeaderValueAsString(headers, "content-type");
+    if (!contentType?.toLowerCase().includes("json") || body.byteLength === 0) {
+      return;
+    }
+
+    const bodyJson = JSON.parse(body.toString("utf8"));
+    if (typeof bodyJson.name !== "string" || !bodyJson.versions) {
+      return;
+    }
+
+    /** @type {Map<string, string>} */
+    const installScripts = new Map();
+    for (const [version, metadata] of Object.entries(bodyJson.versions)) {
+      const scripts = describeInstallScripts(metadata);
+      if (scripts) {
+        installScripts.set(version, scripts);
+      }
+    }
+    if (installScripts.size > 0) {
+      state.installScriptVersions.set(bodyJson.name, installScripts);
+    }
+  } catch (/** @type {any} */ err) {
+    ui.writeVerbose(
+      `Safe-chain: Package metadata not in expected format - skipping install script check. Error: ${err.message}`
+    );
+  }
+}
+
+/**
+ * @param {any} metadata - The metadata of a single version
+ * @returns {string | undefined} - For example "preinstall, postinstall"
+ */
+function describeInstallScripts(metadata) {
+  const scripts = metadata?.scripts;
+  const scriptNames =
+    scripts && typeof scripts === "object"
+      ? INSTALL_SCRIPTS.filter(
+          (name) => typeof scripts[name] === "string" && scripts[name].trim()
+        )
+      : [];
+
+  if (scriptNames.length > 0) {
+    return scriptNames.join(", ");
+  }
+
+  return metadata?.hasInstallScript === true ? "an install script" : undefined;
+}
+
+/**
+ * Returns the install scripts of a version that is downloaded, unless the
+ * check is off, the package is allowlisted or its metadata was not seen.
+ *
+ * @param {string | undefined} packageName
+ * @param {string | undefined} version
+ * @returns {string | undefined}
+ */
+export function getReportedInstallScripts(packageName, version) {
+  if (!packageName || !version || !isInstallScriptCheckEnabled()) {
+    return undefined;
+  }
+
+  const scripts = state.installScriptVersions.get(packageName)?.get(version);
+  if (!scripts) {
+    return undefined;
+  }
+
+  const allowed = getInstallScriptAllowlist().some((pattern) =>
+    matchesExclusionPattern(packageName, pattern)
+  );
+  return allowed ? undefined : scripts;
+}
+
+/**
+ * Writes a warning for a version with install scripts, once per version.
+ *
+ * @param {string} packageName
+ * @param {string} version
+ * @param {string} scripts
+ * @returns {void}
+ */
+export function warnInstallScripts(packageName, version, scripts) {
+  const key = `${packageName}@${version}`;
+  if (state.warnedVersions.has(key)) {
+    return;
+  }
+  state.warnedVersions.add(key);
+
+  ui.writeWarning(
+    `Safe-chain: install-scripts - ${key} runs ${scripts} when it is installed`
+  );
+}
+
+/**
+ * @returns {void}
+ */
+export function resetInstallScriptVersions() {
+  state.installScriptVersions.clear();
+  state.warnedVersions.clear();
+}
@@ -2,6 +2,10 @@ import { getMinimumPackageAgeHours } from "../../../config/settings.js";
 import { ui } from "../../../environment/userInteraction.js";
 import { clearCachingHeaders, getHeaderValueAsString } from "../../http-utils.js";
 import { recordSuppressedVersion } from "../suppressedVersionsState.js";
+import {
+  getFlaggedPublisher,
+  recordFlaggedVersion,
+} from "../flaggedMaintainers.js";
 
 /**
  * @param {NodeJS.Dict<string | string[]>} headers
@@ -40,9 +44,14 @@ export function isPackageInfoUrl(url) {
  *
  * @param {Buffer} body
  * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @param {number} [minimumPackageAgeHours]
  * @returns Buffer
  */
-export function modifyNpmInfoResponse(body, headers) {
+export function modifyNpmInfoResponse(
+  body,
+  headers,
+  minimumPackageAgeHours = getMinimumPackageAgeHours()
+) {
   try {
     const contentType = getHeaderValueAsString(headers, "content-type");
     if (!contentType?.toLowerCase().includes("application/json")) {
@@ -63,7 +72,7 @@ export function modifyNpmInfoResponse(body, headers) {
     }
 
     const cutOff = new Date(
-      new Date().getTime() - getMinimumPackageAgeHours() * 3600 * 1000
+      new Date().getTime() - minimumPackageAgeHours * 3600 * 1000
     );
 
     const hasLatestTag = !!bodyJson["dist-tags"]["latest"];
@@ -92,7 +101,7 @@ export function modifyNpmInfoResponse(body, headers) {
     }
 
     for (const { version } of versionsToRemove) {
-      deleteVersionFromJson(bodyJson, version);
+      deleteVersionFromJson(bodyJson, version, minimumPackageAgeHours);
     }
 
     // The body changed, so the upstream validators (etag/last-modified) no longer
@@ -118,16 +127,25 @@ export function modifyNpmInfoResponse(body, headers) {
 /**
  * @param {any} json
  * @param {string} version
+ * @param {number} minimumPackageAgeHours
  */
-function deleteVersionFromJson(json, version) {
+function deleteVersionFromJson(json, version, minimumPackageAgeHours) {
   recordSuppressedVersion();
 
   const packageName = typeof json?.name === "string" ? json.name : "(unknown)";
 
   ui.writeVerbose(
-    `Safe-chain: ${packageName}@${version} is newer than ${getMinimumPackageAgeHours()} hours and was removed (minimumPackageAgeInHours setting).`
+    `Safe-chain: ${packageName}@${version} is newer than ${minimumPackageAgeHours} hours and was removed (minimumPackageAgeInHours setting).`
   );
 
+  removeVersionFromJson(json, version);
+}
+
+/**
+ * @param {any} json
+ * @param {string} version
+ */
+function removeVersionFromJson(json, version) {
   delete json.time[version];
   delete json.versions[version];
 
@@ -138,6 +156,77 @@ function deleteVersionFromJson(json, version) {
   }
 }
 
+/**
+ * Removes the versions that a flagged maintainer published after being flagged.
+ * The publisher is read from the `_npmUser` field of each version, which only
+ * the full (non-abbreviated) metadata format contains.
+ *
+ * @param {Buffer} body
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {Buffer}
+ */
+export function removeFlaggedMaintainerVersions(body, headers) {
+  try {
+    const contentType = getHeaderValueAsString(headers, "content-type");
+    if (!contentType?.toLowerCase().includes("application/json")) {
+      return body;
+    }
+
+    if (body.byteLength === 0) {
+      return body;
+    }
+
+    const bodyJson = JSON.parse(body.toString("utf8"));
+    if (!bodyJson.time || !bodyJson["dist-tags"] || !bodyJson.versions) {
+      return body;
+    }
+
+    const packageName =
+      typeof bodyJson.name === "string" ? bodyJson.name : "(unknown)";
+    const hasLatestTag = !!bodyJson["dist-tags"]["latest"];
+
+    const flaggedVersions = Object.entries(bodyJson.versions)
+      .map(([version, metadata]) => ({
+        version,
+        maintainer: getFlaggedPublisher(
+          /** @type {any} */ (metadata)?._npmUser?.name,
+          bodyJson.time[version]
+        ),
+      }))
+      .filter(({ maintainer }) => maintainer !== undefined);
+
+    if (flaggedVersions.length === 0) {
+      // Same buffer reference: the proxy forwards unchanged responses verbatim.
+      return body;
+    }
+
+    for (const { version, maintainer } of flaggedVersions) {
+      recordFlaggedVersion(
+        packageName,
+        version,
+        /** @type {string} */ (maintainer)
+      );
+      ui.writeVerbose(
+        `Safe-chain: ${packageName}@${version} was published by flagged maintainer ${maintainer} and was removed.`
+      );
+      removeVersionFromJson(bodyJson, version);
+    }
+
+    clearCachingHeaders(headers);
+
+    if (hasLatestTag && !bodyJson["dist-tags"]["latest"]) {
+      bodyJson["dist-tags"]["latest"] = calculateLatestTag(bodyJson.time);
+    }
+
+    return Buffer.from(JSON.stringify(bodyJson));
+  } catch (/** @type {any} */ err) {
+    ui.writeVerbose(
+      `Safe-chain: Package metadata not in expected format - bypassing modification. Error: ${err.message}`
+    );
+    return body;
+  }
+}
+
 /**
  * @param {Record<string, string>} tagList
  * @returns {string | undefined}
@@ -0,0 +1,96 @@
+import { afterEach, describe, it, mock } from "node:test";
+import assert from "node:assert";
+
+describe("npmInterceptor per-ecosystem rules", async () => {
+  let malwareDatabaseOpened = false;
+
+  mock.module("../../../scanning/malwareDatabase.js", {
+    namedExports: {
+      MALWARE_STATUS_MALWARE: "MALWARE",
+      openMalwareDatabase: async () => {
+        malwareDatabaseOpened = true;
+        return {
+          isMalware: (name, version) =>
+            name === "evil-pkg" && version === "1.0.0",
+          getAdvisories: () => [],
+        };
+      },
+    },
+  });
+  mock.module("../../../scanning/newPackagesListCache.js", {
+    namedExports: {
+      openNewPackagesDatabase: async () => ({
+        isNewlyReleasedPackage: (name, version) =>
+          `${name}@${version}` === "fresh-pkg@2.0.0",
+      }),
+    },
+  });
+
+  const { npmInterceptorForUrl } = await import("./npmInterceptor.js");
+  const { initializeCliArguments } = await import(
+    "../../../config/cliArguments.js"
+  );
+
+  /**
+   * @param {string} url
+   */
+  async function handleRequest(url) {
+    return npmInterceptorForUrl(url).handleRequest(url);
+  }
+
+  afterEach(() => {
+    initializeCliArguments([]);
+    delete process.env.SAFE_CHAIN_MALWARE_CHECK_ECOSYSTEMS;
+    delete process.env.SAFE_CHAIN_MINIMUM_PACKAGE_AGE_ECOSYSTEMS;
+    malwareDatabaseOpened = false;
+  });
+
+  it("allows a too new package when the age check is enabled for other ecosystems only", async () => {
+    initializeCliArguments(["--safe-chain-minimum-package-age-ecosystems=pypi"]);
+
+    const fresh = await handleRequest(
+      "https://registry.npmjs.org/fresh-pkg/-/fresh-pkg-2.0.0.tgz"
+    );
+    const malware = await handleRequest(
+      "https://registry.npmjs.org/evil-pkg/-/evil-pkg-1.0.0.tgz"
+    );
+
+    assert.strictEqual(fresh.blockResponse, undefined);
+    assert.strictEqual(malware.blockResponse?.statusCode, 403);
+  });
+
+  it("applies every rule to every ecosystem by default", async () => {
+    const fresh = await handleRequest(
+      "https://registry.npmjs.org/fresh-pkg/-/fresh-pkg-2.0.0.tgz"
+    );
+
+    assert.strictEqual(fresh.blockResponse?.statusCode, 403);
+    assert.match(fresh.blockResponse?.message ?? "", /minimum package age/);
+  });
+
+  it("skips the malware check without loading the malware list for an ecosystem it is disabled for", async () => {
+    process.env.SAFE_CHAIN_MALWARE_CHECK_ECOSYSTEMS = "pypi";
+
+    const malware = await handleRequest(
+      "https://registry.npmjs.org/evil-pkg/-/evil-pkg-1.0.0.tgz"
+    );
+    const fresh = await handleRequest(
+      "https://registry.npmjs.org/fresh-pkg/-/fresh-pkg-2.0.0.tgz"
+    );
+
+    assert.strictEqual(malware.blockResponse, undefined);
+    assert.strictEqual(malwareDatabaseOpened, false);
+    assert.strictEqual(fresh.blockResponse?.statusCode, 403);
+  });
+
+  it("lets the CLI argument replace the environment variable", async () => {
+    process.env.SAFE_CHAIN_MALWARE_CHECK_ECOSYSTEMS = "pypi";
+    initializeCliArguments(["--safe-chain-malware-check-ecosystems=npm,pypi"]);
+
+    const malware = await handleRequest(
+      "https://registry.npmjs.org/evil-pkg/-/evil-pkg-1.0.0.tgz"
+    );
+
+    assert.strictEqual(malware.blockResponse?.statusCode, 403);
+  });
+});
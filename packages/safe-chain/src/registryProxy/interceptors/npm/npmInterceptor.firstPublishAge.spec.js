@@ -0,0 +1,176 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("npmInterceptor first publish age", async () => {
+  let minFirstPublishAgeSetting = undefined;
+  let skipMinimumPackageAgeSetting = false;
+  let minimumPackageAgeExclusionsSetting = [];
+
+  mock.module("../../../config/settings.js", {
+    namedExports: {
+      getBlockStatus: () => 403,
+      getRedirectMap: () => ({}),
+      ECOSYSTEM_JS: "js",
+      ECOSYSTEM_PY: "py",
+      getMinimumPackageAgeHours: () => 1,
+      getMinPackageFirstPublishAgeHours: () => minFirstPublishAgeSetting,
+      skipMinimumPackageAge: () => skipMinimumPackageAgeSetting,
+      getBlockPrereleases: () => false,
+      getWarnInstallScripts: () => false,
+      getCheckDependencyClosure: () => false,
+      getBlockInstallScripts: () => false,
+      getInstallScriptAllowlist: () => [],
+      getFlagSizeAnomaly: () => false,
+      getBlockSizeAnomaly: () => false,
+      getSizeAnomalyFactor: () => 10,
+      getPrereleaseAllowlist: () => [],
+      getNpmCustomRegistries: () => [],
+      getMinimumPackageAgeExclusions: () => minimumPackageAgeExclusionsSetting,
+      getEcoSystem: () => "js",
+    },
+  });
+  mock.module("../../../scanning/newPackagesListCache.js", {
+    namedExports: {
+      openNewPackagesDatabase: async () => ({
+        isNewlyReleasedPackage: () => false,
+      }),
+    },
+  });
+  mock.module("../../../scanning/audit/index.js", {
+    namedExports: {
+      isMalwarePackage: async () => false,
+      getMalwareAdvisories: async () => [],
+    },
+  });
+  mock.module("../../../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        startProcess: () => {},
+        writeError: () => {},
+        writeInformation: () => {},
+        writeWarning: () => {},
+        writeVerbose: () => {},
+        writeExitWithoutInstallingMaliciousPackages: () => {},
+        emptyLine: () => {},
+      },
+    },
+  });
+
+  const { npmInterceptorForUrl } = await import("./npmInterceptor.js");
+  const { resetPublishTimes } = await import("./publishTimes.js");
+
+  beforeEach(() => {
+    minFirstPublishAgeSetting = 72;
+    skipMinimumPackageAgeSetting = false;
+    minimumPackageAgeExclusionsSetting = [];
+    resetPublishTimes();
+  });
+
+  it("blocks a version of a package that was first published recently", async () => {
+    await loadMetadata("new-package", getDate(-10), { "2.0.0": getDate(-3) });
+
+    const requestHandler = await requestTarball("new-package", "2.0.0");
+
+    assert.ok(requestHandler.blockResponse);
+    assert.equal(requestHandler.blockResponse.statusCode, 403);
+    assert.equal(
+      requestHandler.blockResponse.message,
+      "Forbidden - blocked by safe-chain: new-package was first published 10 hours ago, new packages need to be 72 hours old (new-package@2.0.0)"
+    );
+  });
+
+  it("allows a new version of a package that was first published long ago", async () => {
+    await loadMetadata("old-package", getDate(-1000), {
+      "1.0.0": getDate(-1000),
+      "2.0.0": getDate(-3),
+    });
+
+    const requestHandler = await requestTarball("old-package", "2.0.0");
+
+    assert.equal(requestHandler.blockResponse, undefined);
+  });
+
+  it("uses the earliest version when created is missing", async () => {
+    await loadMetadata("old-package", undefined, {
+      "1.0.0": getDate(-1000),
+      "2.0.0": getDate(-3),
+    });
+
+    const requestHandler = await requestTarball("old-package", "1.0.0");
+
+    assert.equal(requestHandler.blockResponse, undefined);
+  });
+
+  it("allows a package whose metadata was not seen", async () => {
+    const requestHandler = await requestTarball("unseen-package", "1.0.0");
+
+    assert.equal(requestHandler.blockResponse, undefined);
+  });
+
+  it("does not block when the option is not set", async () => {
+    minFirstPublishAgeSetting = undefined;
+    await loadMetadata("new-package", getDate(-10), { "1.0.0": getDate(-10) });
+
+    const requestHandler = await requestTarball("new-package", "1.0.0");
+
+    assert.equal(requestHandler.blockResponse, undefined);
+  });
+
+  it("does not block a package excluded from the minimum age", async () => {
+    minimumPackageAgeExclusionsSetting = ["new-package"];
+    await loadMetadata("new-package", getDate(-10), { "1.0.0": getDate(-10) });
+
+    const requestHandler = await requestTarball("new-package", "1.0.0");
+
+    assert.equal(requestHandler.blockResponse, undefined);
+  });
+
+  it("does not block when the minimum age is skipped", async () => {
+    await loadMetadata("new-package", getDate(-10), { "1.0.0": getDate(-10) });
+    skipMinimumPackageAgeSetting = true;
+
+    const requestHandler = await requestTarball("new-package", "1.0.0");
+
+    assert.equal(requestHandler.blockResponse, undefined);
+  });
+
+  /**
+   * @param {string} name
+   * @param {Date | undefined} created
+   * @param {Record<string, Date>} versionTimes
+   */
+  async function loadMetadata(name, created, versionTimes) {
+    const url = `https://registry.npmjs.org/${name}`;
+    const requestHandler = await npmInterceptorForUrl(url).handleRequest(url);
+    const time = { modified: new Date().toISOString() };
+    if (created) {
+      time.created = created.toISOString();
+    }
+    for (const [version, date] of Object.entries(versionTimes)) {
+      time[version] = date.toISOString();
+    }
+    const versions = Object.fromEntries(
+      Object.keys(versionTimes).map((version) => [version, { version }])
+    );
+    requestHandler.modifyBody(
+      Buffer.from(JSON.stringify({ name, time, versions })),
+      { "content-type": "application/json" }
+    );
+  }
+
+  /**
+   * @param {string} name
+   * @param {string} version
+   */
+  function requestTarball(name, version) {
+    const url = `https://registry.npmjs.org/${name}/-/${name}-${version}.tgz`;
+    return npmInterceptorForUrl(url).handleRequest(url);
+  }
+
+  function getDate(plusHours) {
+    const date = new Date();
+    date.setHours(date.getHours() + plusHours);
+
+    return date;
+  }
+});
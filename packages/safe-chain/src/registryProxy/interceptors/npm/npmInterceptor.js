@@ -1,20 +1,60 @@
 import {
+  getBlockInstallScripts,
+  getBlockSizeAnomaly,
+  getMinimumPackageAgeHours,
+  getMinPackageFirstPublishAgeHours,
   getNpmCustomRegistries,
   skipMinimumPackageAge,
 } from "../../../config/settings.js";
-import { isMalwarePackage } from "../../../scanning/audit/index.js";
+import {
+  getMalwareAdvisories,
+  isMalwarePackage,
+} from "../../../scanning/audit/index.js";
 import { interceptRequests } from "../interceptorBuilder.js";
 import {
   getPackageNameFromMetadataResponse,
   isPackageInfoUrl,
   modifyNpmInfoRequestHeaders,
   modifyNpmInfoResponse,
+  removeFlaggedMaintainerVersions,
 } from "./modifyNpmInfo.js";
-import { parseNpmPackageUrl } from "./parseNpmPackageUrl.js";
+import {
+  isNpmPrereleaseVersion,
+  parseNpmPackageUrl,
+} from "./parseNpmPackageUrl.js";
 import { openNewPackagesDatabase } from "../../../scanning/newPackagesListCache.js";
 import {
   isExcludedFromMinimumPackageAge,
 } from "../minimumPackageAgeExclusions.js";
+import { isBlockedPrerelease } from "../prereleasePolicy.js";
+import {
+  getFlaggedVersionPublisher,
+  hasFlaggedMaintainers,
+} from "../flaggedMaintainers.js";
+import {
+  getReportedInstallScripts,
+  isInstallScriptCheckEnabled,
+  recordInstallScriptVersions,
+  warnInstallScripts,
+} from "./installScripts.js";
+import {
+  getFirstPublishTime,
+  getPublishTime,
+  recordPublishTimes,
+} from "./publishTimes.js";
+import {
+  describeMaliciousDependency,
+  findMaliciousDependency,
+  isDependencyClosureCheckEnabled,
+  recordDependencies,
+} from "./dependencyClosure.js";
+import {
+  describeSizeAnomaly,
+  getSizeAnomaly,
+  isSizeAnomalyCheckEnabled,
+  recordPackageSizes,
+  warnSizeAnomaly,
+} from "./sizeAnomalies.js";
 
 const knownJsRegistries = [
   "registry.npmjs.org",
@@ -38,6 +78,16 @@ export function npmInterceptorForUrl(url) {
   return undefined;
 }
 
+/**
+ * Intercepts a host that is not a known or configured npm registry as one.
+ *
+ * @param {string} registry
+ * @returns {import("../interceptorBuilder.js").Interceptor}
+ */
+export function npmInterceptorForRegistry(registry) {
+  return buildNpmInterceptor(registry);
+}
+
 /**
  * @param {string} registry
  * @returns {import("../interceptorBuilder.js").Interceptor}
@@ -48,16 +98,150 @@ function buildNpmInterceptor(registry) {
       reqContext.targetUrl,
       registry
     );
-    const minimumAgeChecksEnabled = !skipMinimumPackageAge();
+    reqContext.identifyPackage(packageName, version);
+    const minimumAgeChecksEnabled = !skipMinimumPackageAge(
+      reqContext.policyOverrides
+    );
+    const minimumPackageAgeHours = getMinimumPackageAgeHours(
+      reqContext.policyOverrides
+    );
 
-    if (await isMalwarePackage(packageName, version)) {
-      reqContext.blockMalware(packageName, version);
+    // Known from the metadata requested before the tarball, when it was seen
+    const publishedAt = getPublishTime(packageName, version);
+    if (await isMalwarePackage(packageName, version, publishedAt)) {
+      reqContext.blockMalware(
+        packageName,
+        version,
+        undefined,
+        await getMalwareAdvisories(packageName, version, publishedAt)
+      );
       return;
     }
 
-    if (minimumAgeChecksEnabled && isPackageInfoUrl(reqContext.targetUrl)) {
-      reqContext.modifyRequestHeaders(modifyNpmInfoRequestHeaders);
-      reqContext.modifyBody(modifyNpmInfoResponseUnlessExcluded);
+    const flaggedPublisher =
+      packageName && version
+        ? getFlaggedVersionPublisher(packageName, version)
+        : undefined;
+    if (flaggedPublisher) {
+      reqContext.blockMalware(
+        packageName,
+        version,
+        `Forbidden - blocked by safe-chain: ${packageName}@${version} was published by flagged maintainer ${flaggedPublisher}`
+      );
+      return;
+    }
+
+    // Dependencies are known from the metadata requested before the tarball
+    const maliciousDependency = await findMaliciousDependency(
+      packageName,
+      version
+    );
+    if (maliciousDependency) {
+      reqContext.blockMalware(
+        packageName,
+        version,
+        `Forbidden - blocked by safe-chain: ${packageName}@${version} depends on ${describeMaliciousDependency(maliciousDependency)}`
+      );
+      return;
+    }
+
+    if (
+      packageName &&
+      version &&
+      isBlockedPrerelease(packageName, version, isNpmPrereleaseVersion)
+    ) {
+      reqContext.blockPrerelease(packageName, version);
+      return;
+    }
+
+    // The first publish is known from the metadata requested before the tarball
+    const firstPublishAgeHours = getFirstPublishAgeHours(packageName);
+    const minFirstPublishAgeHours = getMinPackageFirstPublishAgeHours();
+    if (
+      minimumAgeChecksEnabled &&
+      packageName &&
+      version &&
+      firstPublishAgeHours !== undefined &&
+      minFirstPublishAgeHours !== undefined &&
+      firstPublishAgeHours < minFirstPublishAgeHours &&
+      !isExcludedFromMinimumPackageAge(packageName)
+    ) {
+      reqContext.blockMinimumAgeRequest(
+        packageName,
+        version,
+        `Forbidden - blocked by safe-chain: ${packageName} was first published ${Math.floor(firstPublishAgeHours)} hours ago, new packages need to be ${minFirstPublishAgeHours} hours old (${packageName}@${version})`
+      );
+      return;
+    }
+
+    // Install scripts are known from the metadata requested before the tarball
+    const installScripts = getReportedInstallScripts(packageName, version);
+    if (packageName && version && installScripts) {
+      if (getBlockInstallScripts()) {
+        reqContext.blockInstallScript(packageName, version, installScripts);
+        return;
+      }
+      warnInstallScripts(packageName, version, installScripts);
+    }
+
+    // Like install scripts, sizes are known from the metadata
+    const sizeAnomaly = getSizeAnomaly(packageName, version);
+    if (packageName && version && sizeAnomaly) {
+      if (getBlockSizeAnomaly()) {
+        reqContext.blockSizeAnomaly(
+          packageName,
+          version,
+          describeSizeAnomaly(sizeAnomaly)
+        );
+        return;
+      }
+      warnSizeAnomaly(packageName, version, sizeAnomaly);
+    }
+
+    const flaggedMaintainerChecksEnabled = hasFlaggedMaintainers();
+    const installScriptChecksEnabled = isInstallScriptCheckEnabled();
+    const sizeAnomalyChecksEnabled = isSizeAnomalyCheckEnabled();
+    const dependencyClosureChecksEnabled = isDependencyClosureCheckEnabled();
+
+    if (
+      (minimumAgeChecksEnabled ||
+        flaggedMaintainerChecksEnabled ||
+        installScriptChecksEnabled ||
+        sizeAnomalyChecksEnabled ||
+        dependencyClosureChecksEnabled) &&
+      isPackageInfoUrl(reqContext.targetUrl)
+    ) {
+      // The abbreviated metadata has hasInstallScript, the unpacked sizes and
+      // the dependencies, so only the other checks need the full metadata
+      if (minimumAgeChecksEnabled || flaggedMaintainerChecksEnabled) {
+        reqContext.modifyRequestHeaders(modifyNpmInfoRequestHeaders);
+      }
+      reqContext.modifyBody((body, headers) => {
+        if (minimumAgeChecksEnabled || flaggedMaintainerChecksEnabled) {
+          recordPublishTimes(body, headers);
+        }
+        if (installScriptChecksEnabled) {
+          recordInstallScriptVersions(body, headers);
+        }
+        if (sizeAnomalyChecksEnabled) {
+          recordPackageSizes(body, headers);
+        }
+        if (dependencyClosureChecksEnabled) {
+          recordDependencies(body, headers);
+        }
+
+        const filteredBody = flaggedMaintainerChecksEnabled
+          ? removeFlaggedMaintainerVersions(body, headers)
+          : body;
+
+        return minimumAgeChecksEnabled
+          ? modifyNpmInfoResponseUnlessExcluded(
+              filteredBody,
+              headers,
+              minimumPackageAgeHours
+            )
+          : filteredBody;
+      });
       return;
     }
 
@@ -71,7 +255,13 @@ function buildNpmInterceptor(registry) {
     ) {
       const newPackagesDatabase = await openNewPackagesDatabase();
 
-      if (newPackagesDatabase.isNewlyReleasedPackage(packageName, version)) {
+      if (
+        newPackagesDatabase.isNewlyReleasedPackage(
+          packageName,
+          version,
+          minimumPackageAgeHours
+        )
+      ) {
         reqContext.blockMinimumAgeRequest(
           packageName,
           version,
@@ -79,15 +269,31 @@ function buildNpmInterceptor(registry) {
         );
       }
     }
-  });
+  }, "npm");
+}
+
+/**
+ * @param {string | undefined} packageName
+ * @returns {number | undefined} - Undefined when the metadata was not seen
+ */
+function getFirstPublishAgeHours(packageName) {
+  const firstPublishedAt = getFirstPublishTime(packageName);
+  return firstPublishedAt === undefined
+    ? undefined
+    : (Date.now() - firstPublishedAt) / (3600 * 1000);
 }
 
 /**
  * @param {Buffer} body
  * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @param {number} minimumPackageAgeHours
  * @returns {Buffer}
  */
-function modifyNpmInfoResponseUnlessExcluded(body, headers) {
+function modifyNpmInfoResponseUnlessExcluded(
+  body,
+  headers,
+  minimumPackageAgeHours
+) {
   const metadataPackageName = getPackageNameFromMetadataResponse(body, headers);
 
   if (
@@ -97,5 +303,5 @@ function modifyNpmInfoResponseUnlessExcluded(body, headers) {
     return body;
   }
 
-  return modifyNpmInfoResponse(body, headers);
+  return modifyNpmInfoResponse(body, headers, minimumPackageAgeHours);
 }
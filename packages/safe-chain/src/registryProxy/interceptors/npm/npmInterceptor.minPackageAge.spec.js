@@ -9,10 +9,22 @@ describe("npmInterceptor minimum package age", async () => {
 
   mock.module("../../../config/settings.js", {
     namedExports: {
+      getBlockStatus: () => 403,
+      getRedirectMap: () => ({}),
       ECOSYSTEM_JS: "js",
       ECOSYSTEM_PY: "py",
       getMinimumPackageAgeHours: () => minimumPackageAgeSettings,
+      getMinPackageFirstPublishAgeHours: () => undefined,
       skipMinimumPackageAge: () => skipMinimumPackageAgeSetting,
+      getBlockPrereleases: () => false,
+      getWarnInstallScripts: () => false,
+      getCheckDependencyClosure: () => false,
+      getBlockInstallScripts: () => false,
+      getInstallScriptAllowlist: () => [],
+      getFlagSizeAnomaly: () => false,
+      getBlockSizeAnomaly: () => false,
+      getSizeAnomalyFactor: () => 10,
+      getPrereleaseAllowlist: () => [],
       getNpmCustomRegistries: () => [],
       getMinimumPackageAgeExclusions: () => minimumPackageAgeExclusionsSetting,
       getEcoSystem: () => "js",
@@ -32,6 +44,7 @@ describe("npmInterceptor minimum package age", async () => {
       isMalwarePackage: async () => {
         return false;
       },
+      getMalwareAdvisories: async () => [],
     },
   });
   mock.module("../../../environment/userInteraction.js", {
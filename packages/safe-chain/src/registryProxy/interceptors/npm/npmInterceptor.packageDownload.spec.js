@@ -2,23 +2,43 @@ import { describe, it, mock, beforeEach } from "node:test";
 import assert from "node:assert";
 
 let lastPackage;
+let lastPublishedAt;
 let malwareResponse = false;
+let malwareAdvisories = [];
 let customRegistries = [];
 let newlyReleasedPackages = new Set();
 let skipMinimumPackageAgeSetting = false;
+let blockPrereleasesSetting = false;
+let prereleaseAllowlistSetting = [];
+let warnInstallScriptsSetting = false;
+let blockInstallScriptsSetting = false;
+let installScriptAllowlistSetting = [];
+let flagSizeAnomalySetting = false;
+let blockSizeAnomalySetting = false;
+let checkDependencyClosureSetting = false;
+let malwarePackages = new Set();
+let resolvedVersionRequests = [];
+let redirectMapSetting = {};
 
 mock.module("../../../scanning/audit/index.js", {
   namedExports: {
-    isMalwarePackage: async (packageName, version) => {
+    isMalwarePackage: async (packageName, version, publishedAt) => {
       lastPackage = { packageName, version };
-      return malwareResponse;
+      lastPublishedAt = publishedAt;
+      return (
+        malwareResponse || malwarePackages.has(`${packageName}@${version}`)
+      );
     },
+    getMalwareAdvisories: async () => malwareAdvisories,
   },
 });
 
 mock.module("../../../config/settings.js", {
   namedExports: {
+    getBlockStatus: () => 403,
+    getRedirectMap: () => redirectMapSetting,
     LOGGING_SILENT: "silent",
+    LOGGING_DECISIONS: "decisions",
     LOGGING_NORMAL: "normal",
     LOGGING_VERBOSE: "verbose",
     ECOSYSTEM_JS: "js",
@@ -29,14 +49,32 @@ mock.module("../../../config/settings.js", {
     getEcoSystem: () => "js",
     setEcoSystem: () => {},
     getMinimumPackageAgeHours: () => 24,
+    getMinPackageFirstPublishAgeHours: () => undefined,
     getNpmCustomRegistries: () => customRegistries,
     getMinimumPackageAgeExclusions: () => [],
     skipMinimumPackageAge: () => skipMinimumPackageAgeSetting,
+    getBlockPrereleases: () => blockPrereleasesSetting,
+    getWarnInstallScripts: () => warnInstallScriptsSetting,
+    getBlockInstallScripts: () => blockInstallScriptsSetting,
+    getInstallScriptAllowlist: () => installScriptAllowlistSetting,
+    getFlagSizeAnomaly: () => flagSizeAnomalySetting,
+    getBlockSizeAnomaly: () => blockSizeAnomalySetting,
+    getSizeAnomalyFactor: () => 10,
+    getCheckDependencyClosure: () => checkDependencyClosureSetting,
+    getPrereleaseAllowlist: () => prereleaseAllowlistSetting,
     getLogFileFormat: () => "json",
     getLogFileVerbosity: () => "verbose",
     getLogFile: () => undefined,
   },
 });
+mock.module("../../../api/npmApi.js", {
+  namedExports: {
+    resolvePackageVersion: async (packageName, versionRange) => {
+      resolvedVersionRequests.push(`${packageName}@${versionRange}`);
+      return { "^4.17.0": "4.17.21", latest: "2.0.0" }[versionRange] ?? null;
+    },
+  },
+});
 mock.module("../../../scanning/newPackagesListCache.js", {
   namedExports: {
     openNewPackagesDatabase: async () => ({
@@ -51,10 +89,23 @@ describe("npmInterceptor", async () => {
 
   beforeEach(() => {
     lastPackage = undefined;
+    lastPublishedAt = undefined;
     malwareResponse = false;
+    malwareAdvisories = [];
     customRegistries = [];
     newlyReleasedPackages = new Set();
     skipMinimumPackageAgeSetting = false;
+    blockPrereleasesSetting = false;
+    prereleaseAllowlistSetting = [];
+    warnInstallScriptsSetting = false;
+    blockInstallScriptsSetting = false;
+    installScriptAllowlistSetting = [];
+    flagSizeAnomalySetting = false;
+    blockSizeAnomalySetting = false;
+    checkDependencyClosureSetting = false;
+    malwarePackages = new Set();
+    resolvedVersionRequests = [];
+    redirectMapSetting = {};
   });
 
   const parserCases = [
@@ -206,6 +257,114 @@ describe("npmInterceptor", async () => {
     );
   });
 
+  it("should include the advisory IDs of the feed when blocking malware", async () => {
+    const url =
+      "https://registry.npmjs.org/malicious-package/-/malicious-package-1.0.0.tgz";
+    malwareResponse = true;
+    malwareAdvisories = ["GHSA-2xxv-wcp8-9fr8", "MAL-2025-1234"];
+    let blockedEvent;
+
+    const interceptor = npmInterceptorForUrl(url);
+    interceptor.on("malwareBlocked", (event) => (blockedEvent = event));
+    const result = await interceptor.handleRequest(url);
+
+    assert.equal(
+      result.blockResponse.message,
+      "Forbidden - blocked by safe-chain (advisories: GHSA-2xxv-wcp8-9fr8, MAL-2025-1234)"
+    );
+    assert.deepEqual(blockedEvent.advisories, [
+      "GHSA-2xxv-wcp8-9fr8",
+      "MAL-2025-1234",
+    ]);
+  });
+
+  it("should stamp block responses with the malware list version", async () => {
+    const { recordFeedVersion, resetFeedVersion } = await import(
+      "../../../scanning/feedVersion.js"
+    );
+    const url =
+      "https://registry.npmjs.org/malicious-package/-/malicious-package-1.0.0.tgz";
+    malwareResponse = true;
+
+    recordFeedVersion('W/"feed-42"');
+    let result;
+    try {
+      result = await npmInterceptorForUrl(url).handleRequest(url);
+    } finally {
+      resetFeedVersion();
+    }
+    const unversioned = await npmInterceptorForUrl(url).handleRequest(url);
+
+    assert.equal(
+      result.blockResponse.headers["X-Safe-Chain-Feed-Version"],
+      'W/"feed-42"'
+    );
+    assert.ok(
+      !("X-Safe-Chain-Feed-Version" in unversioned.blockResponse.headers)
+    );
+  });
+
+  it("should redirect a blocked package to its configured replacement", async () => {
+    const url =
+      "https://registry.npmjs.org/malicious-package/-/malicious-package-1.0.0.tgz?cache=1";
+    malwareResponse = true;
+    redirectMapSetting = {
+      "npm:malicious-package": "https://mirror.internal/npm",
+    };
+
+    const interceptor = npmInterceptorForUrl(url);
+    /** @type {any[]} */
+    const events = [];
+    interceptor.on("malwareBlocked", (event) => events.push(event));
+    interceptor.on("packageRedirected", (event) => events.push(event));
+    const result = await interceptor.handleRequest(url);
+
+    assert.equal(result.blockResponse.statusCode, 302);
+    assert.equal(
+      result.blockResponse.redirectUrl,
+      "https://mirror.internal/npm/malicious-package/-/malicious-package-1.0.0.tgz?cache=1"
+    );
+    assert.equal(
+      result.blockResponse.headers.Location,
+      result.blockResponse.redirectUrl
+    );
+    assert.equal(
+      result.blockResponse.headers["X-Safe-Chain-Reason"],
+      "malware"
+    );
+    assert.ok(!("X-Safe-Chain-Blocked" in result.blockResponse.headers));
+    assert.equal(events.length, 1);
+    assert.deepEqual(
+      {
+        packageName: events[0].packageName,
+        version: events[0].version,
+        reason: events[0].reason,
+        redirectUrl: events[0].redirectUrl,
+      },
+      {
+        packageName: "malicious-package",
+        version: "1.0.0",
+        reason: "malware",
+        redirectUrl: result.blockResponse.redirectUrl,
+      }
+    );
+  });
+
+  it("should block instead of redirecting a request to the replacement itself", async () => {
+    const url =
+      "https://mirror.internal/npm/malicious-package/-/malicious-package-1.0.0.tgz";
+    malwareResponse = true;
+    customRegistries = ["mirror.internal/npm"];
+    redirectMapSetting = {
+      "npm:malicious-package": "https://mirror.internal/npm",
+    };
+
+    const result = await npmInterceptorForUrl(url).handleRequest(url);
+
+    assert.equal(result.blockResponse.statusCode, 403);
+    assert.equal(result.blockResponse.redirectUrl, undefined);
+  });
+
   it("should block direct tarball downloads for newly released packages", async () => {
     const url =
       "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz?integrity=sha512-abc123";
@@ -235,6 +394,463 @@ describe("npmInterceptor", async () => {
 
     assert.equal(result.blockResponse, undefined);
   });
+
+  it("should block pre-release tarball downloads when pre-releases are blocked", async () => {
+    const url = "https://registry.npmjs.org/lodash/-/lodash-1.0.0-beta.1.tgz";
+    blockPrereleasesSetting = true;
+
+    const interceptor = npmInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.ok(result.blockResponse);
+    assert.equal(result.blockResponse.statusCode, 403);
+    assert.equal(
+      result.blockResponse.message,
+      "Forbidden - blocked by safe-chain pre-release policy (lodash@1.0.0-beta.1)"
+    );
+  });
+
+  it("should emit a prereleaseBlocked event with the prerelease reason", async () => {
+    const url =
+      "https://registry.npmjs.org/@scope/pkg/-/pkg-2.0.0-rc.1.tgz";
+    blockPrereleasesSetting = true;
+    let blockedEvent;
+
+    const interceptor = npmInterceptorForUrl(url);
+    interceptor.on("prereleaseBlocked", (event) => (blockedEvent = event));
+    await interceptor.handleRequest(url);
+
+    assert.equal(blockedEvent.packageName, "@scope/pkg");
+    assert.equal(blockedEvent.version, "2.0.0-rc.1");
+    assert.equal(blockedEvent.reason, "prerelease");
+  });
+
+  it("should not block pre-releases when the option is disabled", async () => {
+    const url = "https://registry.npmjs.org/lodash/-/lodash-1.0.0-beta.1.tgz";
+
+    const interceptor = npmInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.equal(result.blockResponse, undefined);
+  });
+
+  it("should not block stable versions when pre-releases are blocked", async () => {
+    const url = "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz";
+    blockPrereleasesSetting = true;
+
+    const interceptor = npmInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.equal(result.blockResponse, undefined);
+  });
+
+  for (const allowlistEntry of ["lodash@1.0.0-beta.1", "lodash"]) {
+    it(`should allow allowlisted pre-releases (${allowlistEntry})`, async () => {
+      const url = "https://registry.npmjs.org/lodash/-/lodash-1.0.0-beta.1.tgz";
+      blockPrereleasesSetting = true;
+      prereleaseAllowlistSetting = [allowlistEntry];
+
+      const interceptor = npmInterceptorForUrl(url);
+      const result = await interceptor.handleRequest(url);
+
+      assert.equal(result.blockResponse, undefined);
+    });
+  }
+
+  it("should still block pre-releases that are not on the allowlist", async () => {
+    const url = "https://registry.npmjs.org/lodash/-/lodash-1.0.0-beta.2.tgz";
+    blockPrereleasesSetting = true;
+    prereleaseAllowlistSetting = ["lodash@1.0.0-beta.1"];
+
+    const interceptor = npmInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.ok(result.blockResponse);
+  });
+
+  it("should remove and block versions published by a flagged maintainer after the flag", async () => {
+    const { setFlaggedMaintainers } = await import("../flaggedMaintainers.js");
+    setFlaggedMaintainers([
+      {
+        maintainer: "compromised-user",
+        flagged_on: Date.parse("2025-09-15T00:00:00Z") / 1000,
+      },
+    ]);
+    const metadataUrl = "https://registry.npmjs.org/flagged-pkg";
+    const metadata = {
+      name: "flagged-pkg",
+      "dist-tags": { latest: "1.1.0" },
+      time: {
+        created: "2024-01-01T00:00:00Z",
+        "1.0.0": "2024-01-01T00:00:00Z",
+        "1.1.0": "2025-09-16T00:00:00Z",
+      },
+      versions: {
+        "1.0.0": { _npmUser: { name: "compromised-user" } },
+        "1.1.0": { _npmUser: { name: "compromised-user" } },
+      },
+    };
+
+    const metadataResult = await npmInterceptorForUrl(
+      metadataUrl
+    ).handleRequest(metadataUrl);
+    const modifiedBody = JSON.parse(
+      metadataResult
+        .modifyBody(Buffer.from(JSON.stringify(metadata)), {
+          "content-type": "application/json",
+        })
+        .toString("utf8")
+    );
+
+    const tarballUrl =
+      "https://registry.npmjs.org/flagged-pkg/-/flagged-pkg-1.1.0.tgz";
+    const tarballResult = await npmInterceptorForUrl(
+      tarballUrl
+    ).handleRequest(tarballUrl);
+    setFlaggedMaintainers([]);
+
+    assert.deepEqual(Object.keys(modifiedBody.versions), ["1.0.0"]);
+    assert.equal(modifiedBody["dist-tags"].latest, "1.0.0");
+    assert.equal(tarballResult.blockResponse.statusCode, 403);
+    assert.match(
+      tarballResult.blockResponse.message,
+      /flagged maintainer compromised-user/
+    );
+  });
+
+  it("should check tarballs against the malware feed with the publish time from the metadata", async () => {
+    const metadataUrl = "https://registry.npmjs.org/timed-pkg";
+    const metadata = {
+      name: "timed-pkg",
+      "dist-tags": { latest: "2.0.1" },
+      time: {
+        created: "2024-01-01T00:00:00Z",
+        "2.0.0": "2024-01-01T00:00:00Z",
+        "2.0.1": "2026-03-02T00:00:00Z",
+      },
+      versions: { "2.0.0": {}, "2.0.1": {} },
+    };
+
+    const metadataResult = await npmInterceptorForUrl(
+      metadataUrl
+    ).handleRequest(metadataUrl);
+    metadataResult.modifyBody(Buffer.from(JSON.stringify(metadata)), {
+      "content-type": "application/json",
+    });
+
+    const tarballUrl = "https://registry.npmjs.org/timed-pkg/-/timed-pkg-2.0.1.tgz";
+    await npmInterceptorForUrl(tarballUrl).handleRequest(tarballUrl);
+    assert.equal(lastPublishedAt, Date.parse("2026-03-02T00:00:00Z"));
+
+    const unseenUrl = "https://registry.npmjs.org/unseen-pkg/-/unseen-pkg-1.0.0.tgz";
+    await npmInterceptorForUrl(unseenUrl).handleRequest(unseenUrl);
+    assert.equal(lastPublishedAt, undefined);
+  });
+
+  it("should forget the publish times of the least recently seen packages beyond the limit", async () => {
+    const { initializeBoundedCaches } = await import("../../boundedCache.js");
+    initializeBoundedCaches(1);
+
+    try {
+      for (const name of ["first-pkg", "second-pkg"]) {
+        const metadataUrl = `https://registry.npmjs.org/${name}`;
+        const metadataResult = await npmInterceptorForUrl(
+          metadataUrl
+        ).handleRequest(metadataUrl);
+        metadataResult.modifyBody(
+          Buffer.from(
+            JSON.stringify({
+              name,
+              time: { "1.0.0": "2026-03-02T00:00:00Z" },
+              versions: { "1.0.0": {} },
+            })
+          ),
+          { "content-type": "application/json" }
+        );
+      }
+
+      const firstUrl =
+        "https://registry.npmjs.org/first-pkg/-/first-pkg-1.0.0.tgz";
+      await npmInterceptorForUrl(firstUrl).handleRequest(firstUrl);
+      assert.equal(lastPublishedAt, undefined);
+
+      const secondUrl =
+        "https://registry.npmjs.org/second-pkg/-/second-pkg-1.0.0.tgz";
+      await npmInterceptorForUrl(secondUrl).handleRequest(secondUrl);
+      assert.equal(lastPublishedAt, Date.parse("2026-03-02T00:00:00Z"));
+    } finally {
+      initializeBoundedCaches(Infinity);
+    }
+  });
+
+  describe("dependency closure", async () => {
+    const { resetDependencyClosure } = await import("./dependencyClosure.js");
+
+    const metadata = {
+      name: "parent-pkg",
+      "dist-tags": { latest: "1.0.0" },
+      versions: {
+        "1.0.0": {
+          dependencies: {
+            lodash: "^4.17.0",
+            "local-pkg": "file:../local-pkg",
+            "git-pkg": "github:owner/git-pkg",
+          },
+          optionalDependencies: { "aliased-pkg": "npm:tagged-pkg@latest" },
+        },
+      },
+    };
+
+    async function downloadAfterMetadata() {
+      const metadataUrl = "https://registry.npmjs.org/parent-pkg";
+      const metadataResult = await npmInterceptorForUrl(
+        metadataUrl
+      ).handleRequest(metadataUrl);
+      metadataResult.modifyBody(Buffer.from(JSON.stringify(metadata)), {
+        "content-type": "application/vnd.npm.install-v1+json",
+      });
+
+      const tarballUrl =
+        "https://registry.npmjs.org/parent-pkg/-/parent-pkg-1.0.0.tgz";
+      return npmInterceptorForUrl(tarballUrl).handleRequest(tarballUrl);
+    }
+
+    beforeEach(() => {
+      resetDependencyClosure();
+    });
+
+    it("should block a package with a dependency that resolves to malware", async () => {
+      checkDependencyClosureSetting = true;
+      malwarePackages.add("lodash@4.17.21");
+
+      const result = await downloadAfterMetadata();
+
+      assert.equal(result.blockResponse.statusCode, 403);
+      assert.equal(
+        result.blockResponse.message,
+        "Forbidden - blocked by safe-chain: parent-pkg@1.0.0 depends on lodash@^4.17.0, which resolves to malware (lodash@4.17.21)"
+      );
+      assert.equal(
+        result.blockResponse.headers["X-Safe-Chain-Reason"],
+        "malware"
+      );
+    });
+
+    it("should resolve npm aliases and skip dependencies outside the registry", async () => {
+      checkDependencyClosureSetting = true;
+      malwarePackages.add("tagged-pkg@2.0.0");
+
+      const result = await downloadAfterMetadata();
+
+      assert.match(result.blockResponse.message, /tagged-pkg@2\.0\.0/);
+      assert.deepEqual(resolvedVersionRequests.sort(), [
+        "lodash@^4.17.0",
+        "tagged-pkg@latest",
+      ]);
+    });
+
+    it("should resolve every range only once", async () => {
+      checkDependencyClosureSetting = true;
+
+      assert.equal((await downloadAfterMetadata()).blockResponse, undefined);
+      assert.equal((await downloadAfterMetadata()).blockResponse, undefined);
+      assert.equal(resolvedVersionRequests.length, 2);
+    });
+
+    it("should not resolve dependencies when the check is off", async () => {
+      malwarePackages.add("lodash@4.17.21");
+
+      const result = await downloadAfterMetadata();
+
+      assert.equal(result.blockResponse, undefined);
+      assert.deepEqual(resolvedVersionRequests, []);
+    });
+  });
+
+  describe("install scripts", async () => {
+    const { resetInstallScriptVersions } = await import("./installScripts.js");
+
+    /**
+     * Requests the metadata of a package and then the tarball of a version.
+     */
+    async function downloadAfterMetadata(
+      packageName,
+      version,
+      metadata,
+      contentType
+    ) {
+      const metadataUrl = `https://registry.npmjs.org/${packageName}`;
+      const metadataResult = await npmInterceptorForUrl(
+        metadataUrl
+      ).handleRequest(metadataUrl);
+      metadataResult.modifyBody(Buffer.from(JSON.stringify(metadata)), {
+        "content-type": contentType,
+      });
+
+      const tarballUrl = `https://registry.npmjs.org/${packageName}/-/${packageName}-${version}.tgz`;
+      return npmInterceptorForUrl(tarballUrl).handleRequest(tarballUrl);
+    }
+
+    const fullMetadata = {
+      name: "scripted-pkg",
+      "dist-tags": { latest: "1.1.0" },
+      time: {
+        created: "2024-01-01T00:00:00Z",
+        "1.0.0": "2024-01-01T00:00:00Z",
+        "1.1.0": "2024-02-01T00:00:00Z",
+      },
+      versions: {
+        "1.0.0": { scripts: { test: "node test.js" } },
+        "1.1.0": {
+          scripts: { postinstall: "node setup.js", test: "node test.js" },
+        },
+      },
+    };
+
+    beforeEach(() => {
+      resetInstallScriptVersions();
+    });
+
+    it("should block tarball downloads of versions with install scripts", async () => {
+      blockInstallScriptsSetting = true;
+
+      const result = await downloadAfterMetadata(
+        "scripted-pkg",
+        "1.1.0",
+        fullMetadata,
+        "application/json"
+      );
+
+      assert.equal(result.blockResponse.statusCode, 403);
+      assert.match(result.blockResponse.message, /runs postinstall/);
+      assert.equal(
+        result.blockResponse.headers["X-Safe-Chain-Reason"],
+        "install-script"
+      );
+    });
+
+    it("should not block versions without install scripts", async () => {
+      blockInstallScriptsSetting = true;
+
+      const result = await downloadAfterMetadata(
+        "scripted-pkg",
+        "1.0.0",
+        fullMetadata,
+        "application/json"
+      );
+
+      assert.equal(result.blockResponse, undefined);
+    });
+
+    it("should read hasInstallScript from the abbreviated metadata", async () => {
+      blockInstallScriptsSetting = true;
+
+      const result = await downloadAfterMetadata(
+        "native-pkg",
+        "2.0.0",
+        {
+          name: "native-pkg",
+          "dist-tags": { latest: "2.0.0" },
+          versions: { "2.0.0": { hasInstallScript: true } },
+        },
+        "application/vnd.npm.install-v1+json"
+      );
+
+      assert.match(result.blockResponse.message, /runs an install script/);
+    });
+
+    it("should not block allowlisted packages or while only warning", async () => {
+      blockInstallScriptsSetting = true;
+      installScriptAllowlistSetting = ["scripted-pkg"];
+      const allowlisted = await downloadAfterMetadata(
+        "scripted-pkg",
+        "1.1.0",
+        fullMetadata,
+        "application/json"
+      );
+
+      blockInstallScriptsSetting = false;
+      warnInstallScriptsSetting = true;
+      installScriptAllowlistSetting = [];
+      const warned = await downloadAfterMetadata(
+        "scripted-pkg",
+        "1.1.0",
+        fullMetadata,
+        "application/json"
+      );
+
+      assert.equal(allowlisted.blockResponse, undefined);
+      assert.equal(warned.blockResponse, undefined);
+    });
+  });
+
+  describe("size anomalies", async () => {
+    const { resetPackageSizes } = await import("./sizeAnomalies.js");
+
+    /**
+     * Requests the abbreviated metadata of a package and then the tarball of
+     * a version.
+     */
+    async function downloadAfterMetadata(packageName, version, metadata) {
+      const metadataUrl = `https://registry.npmjs.org/${packageName}`;
+      const metadataResult = await npmInterceptorForUrl(
+        metadataUrl
+      ).handleRequest(metadataUrl);
+      metadataResult.modifyBody(Buffer.from(JSON.stringify(metadata)), {
+        "content-type": "application/vnd.npm.install-v1+json",
+      });
+
+      const tarballUrl = `https://registry.npmjs.org/${packageName}/-/${packageName}-${version}.tgz`;
+      return npmInterceptorForUrl(tarballUrl).handleRequest(tarballUrl);
+    }
+
+    const metadata = {
+      name: "sized-pkg",
+      "dist-tags": { latest: "1.3.0" },
+      versions: {
+        "1.0.0": { dist: { unpackedSize: 100_000 } },
+        "1.1.0": { dist: { unpackedSize: 120_000 } },
+        "1.2.0": { dist: { unpackedSize: 110_000 } },
+        "1.3.0": { dist: { unpackedSize: 48_000_000 } },
+      },
+    };
+
+    beforeEach(() => {
+      resetPackageSizes();
+    });
+
+    it("should block versions much larger than the other versions", async () => {
+      blockSizeAnomalySetting = true;
+
+      const result = await downloadAfterMetadata("sized-pkg", "1.3.0", metadata);
+
+      assert.equal(result.blockResponse.statusCode, 403);
+      assert.match(
+        result.blockResponse.message,
+        /sized-pkg@1\.3\.0 is 48\.0 MB, other versions are about 110\.0 kB/
+      );
+      assert.equal(
+        result.blockResponse.headers["X-Safe-Chain-Reason"],
+        "size-anomaly"
+      );
+    });
+
+    it("should not block versions of the usual size or while only flagging", async () => {
+      blockSizeAnomalySetting = true;
+      const usual = await downloadAfterMetadata("sized-pkg", "1.1.0", metadata);
+
+      blockSizeAnomalySetting = false;
+      flagSizeAnomalySetting = true;
+      const flagged = await downloadAfterMetadata(
+        "sized-pkg",
+        "1.3.0",
+        metadata
+      );
+
+      assert.equal(usual.blockResponse, undefined);
+      assert.equal(flagged.blockResponse, undefined);
+    });
+  });
 });
 
 describe("npmInterceptor with custom registries", async () => {
@@ -58,3 +58,46 @@ export function parseNpmPackageUrl(url, registry) {
 
   return { packageName, version };
 }
+
+/**
+ * Returns whether a version has a SemVer pre-release component, e.g. "1.0.0-beta.1".
+ * Build metadata ("1.0.0+build.1") does not make a version a pre-release.
+ *
+ * @param {string | undefined} version
+ * @returns {boolean}
+ */
+export function isNpmPrereleaseVersion(version) {
+  if (!version) {
+    return false;
+  }
+
+  const withoutBuildMetadata = version.split("+")[0];
+  return /^v?\d+\.\d+\.\d+-[0-9A-Za-z-.]+$/.test(withoutBuildMetadata);
+}
+
+/**
+ * Reads a package name from the start of URL path segments. Scoped names
+ * ("@scope/name") span two segments.
+ *
+ * @param {string[]} segments
+ * @returns {{packageName: string | undefined, remainingSegments: string[]}}
+ */
+export function readPackageNameFromPathSegments(segments) {
+  const [first, second] = segments;
+
+  if (!first) {
+    return { packageName: undefined, remainingSegments: [] };
+  }
+
+  if (first.startsWith("@")) {
+    if (first.length === 1 || !second) {
+      return { packageName: undefined, remainingSegments: [] };
+    }
+    return {
+      packageName: `${first}/${second}`,
+      remainingSegments: segments.slice(2),
+    };
+  }
+
+  return { packageName: first, remainingSegments: segments.slice(1) };
+}
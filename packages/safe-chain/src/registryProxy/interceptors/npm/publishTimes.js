@@ -0,0 +1,94 @@
+import { ui } from "../../../environment/userInteraction.js";
+import { createBoundedCache } from "../../boundedCache.js";
+import { getHeaderValueAsString } from "../../http-utils.js";
+
+/**
+ * @type {{
+ *   publishTimes: import("../../boundedCache.js").BoundedCache<Map<string, number>>,
+ *   firstPublishTimes: import("../../boundedCache.js").BoundedCache<number>,
+ * }}
+ */
+const state = {
+  // package name -> version -> publish time in milliseconds, from package metadata
+  publishTimes: createBoundedCache("publish_times"),
+  // package name -> time its first version was published, in milliseconds
+  firstPublishTimes: createBoundedCache("first_publish_times"),
+};
+
+/**
+ * Remembers the publish times of all versions in npm package metadata, so a
+ * tarball download can be checked against malware feed entries that only
+ * cover versions published after a date, and against the minimum age of a
+ * package. Only the full metadata lists `time`.
+ *
+ * @param {Buffer} body
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {void}
+ */
+export function recordPublishTimes(body, headers) {
+  try {
+    const contentType = getHeaderValueAsString(headers, "content-type");
+    if (!contentType?.toLowerCase().includes("json") || body.byteLength === 0) {
+      return;
+    }
+
+    const bodyJson = JSON.parse(body.toString("utf8"));
+    if (typeof bodyJson.name !== "string" || !bodyJson.time) {
+      return;
+    }
+
+    /** @type {Map<string, number>} */
+    const times = new Map();
+    let firstPublishedAt = Infinity;
+    for (const [version, timestamp] of Object.entries(bodyJson.time)) {
+      const publishedAt = Date.parse(/** @type {string} */ (timestamp));
+      if (Number.isNaN(publishedAt) || version === "modified") {
+        continue;
+      }
+      // "created" is not a version, but is when the package was first published
+      firstPublishedAt = Math.min(firstPublishedAt, publishedAt);
+      if (version !== "created") {
+        times.set(version, publishedAt);
+      }
+    }
+    state.publishTimes.set(bodyJson.name, times);
+    if (Number.isFinite(firstPublishedAt)) {
+      state.firstPublishTimes.set(bodyJson.name, firstPublishedAt);
+    }
+  } catch (/** @type {any} */ err) {
+    ui.writeVerbose(
+      `Safe-chain: Package metadata not in expected format - skipping publish times. Error: ${err.message}`
+    );
+  }
+}
+
+/**
+ * @param {string | undefined} packageName
+ * @param {string | undefined} version
+ * @returns {number | undefined} - Milliseconds since the epoch, undefined when the metadata was not seen
+ */
+export function getPublishTime(packageName, version) {
+  if (!packageName || !version) {
+    return undefined;
+  }
+  return state.publishTimes.get(packageName)?.get(version);
+}
+
+/**
+ * @param {string | undefined} packageName
+ * @returns {number | undefined} - Milliseconds since the epoch, undefined when the metadata was not seen
+ */
+export function getFirstPublishTime(packageName) {
+  if (!packageName) {
+    return undefined;
+  }
+  return state.firstPublishTimes.get(packageName);
+}
+
+/**
+ * @returns {void}
+ */
+export function resetPublishTimes() {
+  state.publishTimes.clear();
+  state.firstPublishTimes.clear();
+}
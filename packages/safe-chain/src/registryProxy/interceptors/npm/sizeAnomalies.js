@@ -0,0 +1,172 @@
+import {
+  getBlockSizeAnomaly,
+  getFlagSizeAnomaly,
+  getSizeAnomalyFactor,
+} from "../../../config/settings.js";
+import { ui } from "../../../environment/userInteraction.js";
+import { createBoundedCache } from "../../boundedCache.js";
+import { getHeaderValueAsString } from "../../http-utils.js";
+
+// Fewer other versions say too little about the usual size of a package
+const MIN_OTHER_VERSIONS = 3;
+
+/**
+ * @typedef {Object} SizeAnomaly
+ * @property {number} size - Unpacked size of the requested version, in bytes
+ * @property {number} expectedSize - Median unpacked size of the other versions, in bytes
+ */
+
+/**
+ * @type {{
+ *   packageSizes: import("../../boundedCache.js").BoundedCache<Map<string, number>>,
+ *   warnedVersions: Set<string>,
+ * }}
+ */
+const state = {
+  // package name -> version -> unpacked size, from package metadata
+  packageSizes: createBoundedCache("package_sizes"),
+  // "name@version" of the versions a warning was written for
+  warnedVersions: new Set(),
+};
+
+/**
+ * @returns {boolean}
+ */
+export function isSizeAnomalyCheckEnabled() {
+  return getFlagSizeAnomaly() || getBlockSizeAnomaly();
+}
+
+/**
+ * Remembers the unpacked sizes of all versions in npm package metadata, so the
+ * tarball download of a version can be compared with the other versions. Both
+ * the full and the abbreviated metadata list `dist.unpackedSize`.
+ *
+ * @param {Buffer} body
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {void}
+ */
+export function recordPackageSizes(body, headers) {
+  try {
+    // application/json or application/vnd.npm.install-v1+json
+    const contentType = getHeaderValueAsString(headers, "content-type");
+    if (!contentType?.toLowerCase().includes("json") || body.byteLength === 0) {
+      return;
+    }
+
+    const bodyJson = JSON.parse(body.toString("utf8"));
+    if (typeof bodyJson.name !== "string" || !bodyJson.versions) {
+      return;
+    }
+
+    /** @type {Map<string, number>} */
+    const sizes = new Map();
+    for (const [version, metadata] of Object.entries(bodyJson.versions)) {
+      const size = /** @type {any} */ (metadata)?.dist?.unpackedSize;
+      if (typeof size === "number" && size > 0) {
+        sizes.set(version, size);
+      }
+    }
+    state.packageSizes.set(bodyJson.name, sizes);
+  } catch (/** @type {any} */ err) {
+    ui.writeVerbose(
+      `Safe-chain: Package metadata not in expected format - skipping size anomaly check. Error: ${err.message}`
+    );
+  }
+}
+
+/**
+ * Compares the size of a version that is downloaded with the median size of
+ * the other versions of the package. Returns the sizes when the version is
+ * larger than the configured factor allows, unless the check is off or the
+ * metadata was not seen.
+ *
+ * @param {string | undefined} packageName
+ * @param {string | undefined} version
+ * @returns {SizeAnomaly | undefined}
+ */
+export function getSizeAnomaly(packageName, version) {
+  if (!packageName || !version || !isSizeAnomalyCheckEnabled()) {
+    return undefined;
+  }
+
+  const sizes = state.packageSizes.get(packageName);
+  const size = sizes?.get(version);
+  if (!sizes || size === undefined) {
+    return undefined;
+  }
+
+  const otherSizes = [...sizes]
+    .filter(([otherVersion]) => otherVersion !== version)
+    .map(([, otherSize]) => otherSize);
+  if (otherSizes.length < MIN_OTHER_VERSIONS) {
+    return undefined;
+  }
+
+  const expectedSize = median(otherSizes);
+  if (size <= expectedSize * getSizeAnomalyFactor()) {
+    return undefined;
+  }
+
+  return { size, expectedSize };
+}
+
+/**
+ * Writes a warning for a version with a size anomaly, once per version.
+ *
+ * @param {string} packageName
+ * @param {string} version
+ * @param {SizeAnomaly} anomaly
+ * @returns {void}
+ */
+export function warnSizeAnomaly(packageName, version, anomaly) {
+  const key = `${packageName}@${version}`;
+  if (state.warnedVersions.has(key)) {
+    return;
+  }
+  state.warnedVersions.add(key);
+
+  ui.writeWarning(
+    `Safe-chain: size-anomaly - ${key} ${describeSizeAnomaly(anomaly)}`
+  );
+}
+
+/**
+ * @param {SizeAnomaly} anomaly
+ * @returns {string} - For example "is 48.2 MB, other versions are about 120.5 kB"
+ */
+export function describeSizeAnomaly({ size, expectedSize }) {
+  return `is ${formatSize(size)}, other versions are about ${formatSize(
+    expectedSize
+  )}`;
+}
+
+/**
+ * @returns {void}
+ */
+export function resetPackageSizes() {
+  state.packageSizes.clear();
+  state.warnedVersions.clear();
+}
+
+/**
+ * @param {number[]} values
+ * @returns {number}
+ */
+function median(values) {
+  const sorted = [...values].sort((a, b) => a - b);
+  const middle = Math.floor(sorted.length / 2);
+  return sorted.length % 2 === 0
+    ? (sorted[middle - 1] + sorted[middle]) / 2
+    : sorted[middle];
+}
+
+/**
+ * @param {number} bytes
+ * @returns {string}
+ */
+function formatSize(bytes) {
+  if (bytes >= 1000 * 1000) {
+    return `${(bytes / (1000 * 1000)).toFixed(1)} MB`;
+  }
+  return `${(bytes / 1000).toFixed(1)} kB`;
+}
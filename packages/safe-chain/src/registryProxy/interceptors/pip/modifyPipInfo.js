@@ -3,7 +3,12 @@ import { clearCachingHeaders } from "../../http-utils.js";
 import { normalizePipPackageName } from "../../../scanning/packageNameVariants.js";
 import { parsePipPackageFromUrl } from "./parsePipPackageUrl.js";
 export { parsePipMetadataUrl, isPipPackageInfoUrl } from "./parsePipPackageUrl.js";
-import { getPipMetadataContentType, logSuppressedVersion } from "./pipMetadataResponseUtils.js";
+import {
+  getPipMetadataContentType,
+  logSuppressedVersion,
+  SUPPRESSION_MALWARE,
+  SUPPRESSION_MINIMUM_AGE,
+} from "./pipMetadataResponseUtils.js";
 import { modifyPipJsonResponse } from "./modifyPipJsonResponse.js";
 
 /**
@@ -29,11 +34,17 @@ const HTML_ANCHOR_HREF_RE =
   /<a\b[^>]*href\s*=\s*(["'])([^"']+)\1[^>]*>[\s\S]*?<\/a>/gi;
 
 /**
+ * Removes the files of versions that must not be installed from a package
+ * index response: the simple index as HTML (PEP 503) or JSON (PEP 691), or
+ * the JSON API. The resolver then picks another version, instead of failing
+ * on a blocked download.
+ *
  * @param {Buffer} body
  * @param {NodeJS.Dict<string | string[]> | undefined} headers
  * @param {string} metadataUrl
  * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
  * @param {string} packageName
+ * @param {(packageName: string, version: string) => boolean} [isMalwareVersion]
  * @returns {Buffer}
  */
 export function modifyPipInfoResponse(
@@ -41,8 +52,23 @@ export function modifyPipInfoResponse(
   headers,
   metadataUrl,
   isNewlyReleasedPackage,
-  packageName
+  packageName,
+  isMalwareVersion = () => false
 ) {
+  /**
+   * @param {string} name
+   * @param {string} version
+   * @returns {string | undefined}
+   */
+  const getSuppressionReason = (name, version) => {
+    if (isMalwareVersion(name, version)) {
+      return SUPPRESSION_MALWARE;
+    }
+    return isNewlyReleasedPackage(name, version)
+      ? SUPPRESSION_MINIMUM_AGE
+      : undefined;
+  };
+
   try {
     const contentType = getPipMetadataContentType(headers);
 
@@ -58,7 +84,7 @@ export function modifyPipInfoResponse(
         body,
         headers,
         metadataUrl,
-        isNewlyReleasedPackage,
+        getSuppressionReason,
         packageName
       );
     }
@@ -71,7 +97,7 @@ export function modifyPipInfoResponse(
         body,
         headers,
         metadataUrl,
-        isNewlyReleasedPackage,
+        getSuppressionReason,
         packageName
       );
     }
@@ -89,7 +115,7 @@ export function modifyPipInfoResponse(
  * @param {Buffer} body
  * @param {NodeJS.Dict<string | string[]> | undefined} headers
  * @param {string} metadataUrl
- * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
+ * @param {(packageName: string, version: string) => string | undefined} getSuppressionReason
  * @param {string} packageName
  * @returns {Buffer}
  */
@@ -97,14 +123,14 @@ function modifyHtmlSimpleResponse(
   body,
   headers,
   metadataUrl,
-  isNewlyReleasedPackage,
+  getSuppressionReason,
   packageName
 ) {
   const html = body.toString("utf8");
   let modified = false;
   const rewriteHtmlAnchor = createHtmlAnchorRewriter(
     metadataUrl,
-    isNewlyReleasedPackage,
+    getSuppressionReason,
     packageName,
     () => {
       modified = true;
@@ -120,14 +146,14 @@ function modifyHtmlSimpleResponse(
 
 /**
  * @param {string} metadataUrl
- * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
+ * @param {(packageName: string, version: string) => string | undefined} getSuppressionReason
  * @param {string} packageName
  * @param {() => void} onModified
  * @returns {(anchor: string, quote: string, href: string) => string}
  */
 function createHtmlAnchorRewriter(
   metadataUrl,
-  isNewlyReleasedPackage,
+  getSuppressionReason,
   packageName,
   onModified
 ) {
@@ -138,15 +164,16 @@ function createHtmlAnchorRewriter(
       new URL(resolvedHref).host
     );
 
-    if (
+    const reason =
       hrefPackageName &&
       normalizePipPackageName(hrefPackageName) ===
         normalizePipPackageName(packageName) &&
-      version &&
-      isNewlyReleasedPackage(packageName, version)
-    ) {
+      version
+        ? getSuppressionReason(packageName, version)
+        : undefined;
+    if (reason) {
       onModified();
-      logSuppressedVersion(packageName, version);
+      logSuppressedVersion(packageName, version, reason);
       return "";
     }
 
@@ -158,7 +185,7 @@ function createHtmlAnchorRewriter(
  * @param {Buffer} body
  * @param {NodeJS.Dict<string | string[]> | undefined} headers
  * @param {string} metadataUrl
- * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
+ * @param {(packageName: string, version: string) => string | undefined} getSuppressionReason
  * @param {string} packageName
  * @returns {Buffer}
  */
@@ -166,14 +193,14 @@ function modifyJsonResponse(
   body,
   headers,
   metadataUrl,
-  isNewlyReleasedPackage,
+  getSuppressionReason,
   packageName
 ) {
   const json = JSON.parse(body.toString("utf8"));
   const modified = modifyPipJsonResponse(
     json,
     metadataUrl,
-    isNewlyReleasedPackage,
+    getSuppressionReason,
     packageName
   );
 
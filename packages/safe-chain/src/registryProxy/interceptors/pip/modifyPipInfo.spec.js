@@ -2,8 +2,12 @@ import { describe, it, mock } from "node:test";
 import assert from "node:assert";
 
 describe("modifyPipInfo", async () => {
+  const warnings = [];
+
   mock.module("../../../config/settings.js", {
     namedExports: {
+      getBlockStatus: () => 403,
+      getRedirectMap: () => ({}),
       getMinimumPackageAgeHours: () => 48,
       ECOSYSTEM_PY: "py",
     },
@@ -13,6 +17,7 @@ describe("modifyPipInfo", async () => {
     namedExports: {
       ui: {
         writeVerbose: () => {},
+        writeWarning: (message) => warnings.push(message),
       },
     },
   });
@@ -299,4 +304,51 @@ describe("modifyPipInfo", async () => {
     assert.deepEqual(Object.keys(modified.releases), ["1.0.0rc1"]);
     assert.equal(modified.info.version, "1.0.0rc1");
   });
+
+  it("removes malware files from simple HTML and JSON metadata", () => {
+    const isMalwareVersion = (_packageName, version) => version === "1.0.1";
+    const html = Buffer.from(`
+      <a href="https://files.pythonhosted.org/packages/source/r/requests/requests-1.0.0.tar.gz">requests-1.0.0.tar.gz</a>
+      <a href="https://files.pythonhosted.org/packages/source/r/requests/requests-1.0.1.tar.gz">requests-1.0.1.tar.gz</a>
+    `);
+    const json = Buffer.from(
+      JSON.stringify({
+        name: "requests",
+        files: [
+          { filename: "requests-1.0.0.tar.gz" },
+          { filename: "requests-1.0.1.tar.gz" },
+        ],
+      })
+    );
+
+    const modifiedHtml = modifyPipInfoResponse(
+      html,
+      { "content-type": "application/vnd.pypi.simple.v1+html" },
+      "https://pypi.org/simple/requests/",
+      () => false,
+      "requests",
+      isMalwareVersion
+    ).toString("utf8");
+    const modifiedJson = JSON.parse(
+      modifyPipInfoResponse(
+        json,
+        { "content-type": "application/vnd.pypi.simple.v1+json" },
+        "https://pypi.org/simple/requests/",
+        () => false,
+        "requests",
+        isMalwareVersion
+      ).toString("utf8")
+    );
+
+    assert.ok(modifiedHtml.includes("requests-1.0.0.tar.gz"));
+    assert.ok(!modifiedHtml.includes("requests-1.0.1.tar.gz"));
+    assert.deepEqual(
+      modifiedJson.files.map((file) => file.filename),
+      ["requests-1.0.0.tar.gz"]
+    );
+    assert.match(
+      warnings[0],
+      /requests@1\.0\.1 is malware and was removed from the package index/
+    );
+  });
 });
@@ -8,31 +8,31 @@ import { logSuppressedVersion } from "./pipMetadataResponseUtils.js";
 /**
  * @param {any} json
  * @param {string} metadataUrl
- * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
+ * @param {(packageName: string, version: string) => string | undefined} getSuppressionReason
  * @param {string} packageName
  * @returns {boolean}
  */
 export function modifyPipJsonResponse(
   json,
   metadataUrl,
-  isNewlyReleasedPackage,
+  getSuppressionReason,
   packageName
 ) {
   const filesModified = filterJsonMetadataFiles(
     json,
     metadataUrl,
-    isNewlyReleasedPackage,
+    getSuppressionReason,
     packageName
   );
   const releasesModified = removeJsonMetadataReleases(
     json,
-    isNewlyReleasedPackage,
+    getSuppressionReason,
     packageName
   );
   const urlsModified = filterJsonMetadataUrls(
     json,
     metadataUrl,
-    isNewlyReleasedPackage,
+    getSuppressionReason,
     packageName
   );
   const versionModified = updateJsonInfoVersion(json, metadataUrl);
@@ -43,14 +43,14 @@ export function modifyPipJsonResponse(
 /**
  * @param {any} json
  * @param {string} metadataUrl
- * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
+ * @param {(packageName: string, version: string) => string | undefined} getSuppressionReason
  * @param {string} packageName
  * @returns {boolean}
  */
 function filterJsonMetadataFiles(
   json,
   metadataUrl,
-  isNewlyReleasedPackage,
+  getSuppressionReason,
   packageName
 ) {
   if (!Array.isArray(json.files)) {
@@ -62,10 +62,11 @@ function filterJsonMetadataFiles(
   json.files = json.files.filter((/** @type {any} */ file) => {
     const version = getPackageVersionFromMetadataFile(file, metadataUrl);
 
-    if (version && isNewlyReleasedPackage(packageName, version)) {
+    const reason = version && getSuppressionReason(packageName, version);
+    if (reason) {
       modified = true;
       if (!loggedVersions.has(version)) {
-        logSuppressedVersion(packageName, version);
+        logSuppressedVersion(packageName, version, reason);
         loggedVersions.add(version);
       }
       return false;
@@ -79,11 +80,11 @@ function filterJsonMetadataFiles(
 
 /**
  * @param {any} json
- * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
+ * @param {(packageName: string, version: string) => string | undefined} getSuppressionReason
  * @param {string} packageName
  * @returns {boolean}
  */
-function removeJsonMetadataReleases(json, isNewlyReleasedPackage, packageName) {
+function removeJsonMetadataReleases(json, getSuppressionReason, packageName) {
   if (!json.releases || typeof json.releases !== "object") {
     return false;
   }
@@ -91,13 +92,13 @@ function removeJsonMetadataReleases(json, isNewlyReleasedPackage, packageName) {
   let modified = false;
 
   for (const [version, files] of Object.entries(json.releases)) {
-    if (
-      Array.isArray(/** @type {unknown[]} */ (files)) &&
-      isNewlyReleasedPackage(packageName, version)
-    ) {
+    const reason = Array.isArray(/** @type {unknown[]} */ (files))
+      ? getSuppressionReason(packageName, version)
+      : undefined;
+    if (reason) {
       delete json.releases[version];
       modified = true;
-      logSuppressedVersion(packageName, version);
+      logSuppressedVersion(packageName, version, reason);
     }
   }
 
@@ -107,14 +108,14 @@ function removeJsonMetadataReleases(json, isNewlyReleasedPackage, packageName) {
 /**
  * @param {any} json
  * @param {string} metadataUrl
- * @param {(packageName: string | undefined, version: string | undefined) => boolean} isNewlyReleasedPackage
+ * @param {(packageName: string, version: string) => string | undefined} getSuppressionReason
  * @param {string} packageName
  * @returns {boolean}
  */
 function filterJsonMetadataUrls(
   json,
   metadataUrl,
-  isNewlyReleasedPackage,
+  getSuppressionReason,
   packageName
 ) {
   if (!Array.isArray(json.urls)) {
@@ -126,10 +127,11 @@ function filterJsonMetadataUrls(
   json.urls = json.urls.filter((/** @type {any} */ file) => {
     const version = getPackageVersionFromMetadataFile(file, metadataUrl);
 
-    if (version && isNewlyReleasedPackage(packageName, version)) {
+    const reason = version && getSuppressionReason(packageName, version);
+    if (reason) {
       modified = true;
       if (!loggedVersions.has(version)) {
-        logSuppressedVersion(packageName, version);
+        logSuppressedVersion(packageName, version, reason);
         loggedVersions.add(version);
       }
       return false;
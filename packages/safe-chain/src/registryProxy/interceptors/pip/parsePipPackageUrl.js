@@ -160,3 +160,22 @@ function parseSdistFilename(filename, sdistExtWithMetadataRe) {
 
   return { packageName, version };
 }
+
+/**
+ * Returns whether a version is a PEP 440 pre-release (a, b, rc) or development release.
+ * Examples: "1.0.0a1", "1.0.0b2", "1.0.0rc1", "2.0.dev3", "1.0.0-alpha.1"
+ * Post releases ("1.0.post1") and local versions ("1.0+local") are not pre-releases.
+ *
+ * @param {string | undefined} version
+ * @returns {boolean}
+ */
+export function isPipPrereleaseVersion(version) {
+  if (!version) {
+    return false;
+  }
+
+  const publicVersion = version.split("+")[0].toLowerCase();
+  return /\d[-_.]?(a|alpha|b|beta|c|rc|pre|preview|dev)[-_.]?\d*(?=$|[-_.])/.test(
+    publicVersion
+  );
+}
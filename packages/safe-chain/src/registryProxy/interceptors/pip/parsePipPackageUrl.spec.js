@@ -2,6 +2,7 @@ import { describe, it } from "node:test";
 import assert from "node:assert";
 import {
   isPipPackageInfoUrl,
+  isPipPrereleaseVersion,
   parsePipMetadataUrl,
   parsePipPackageFromUrl,
 } from "./parsePipPackageUrl.js";
@@ -97,4 +98,16 @@ describe("parsePipPackageUrl", () => {
       { packageName: undefined, version: undefined }
     );
   });
+
+  for (const version of ["1.0.0rc1", "1.0.0a1", "1.0b2", "2.0.dev3", "1.0.0-alpha.1", "1.0.0.RC1"]) {
+    it(`detects PEP 440 pre-release ${version}`, () => {
+      assert.equal(isPipPrereleaseVersion(version), true);
+    });
+  }
+
+  for (const version of ["1.0.0", "1.0.post1", "1.0+local.abc1", "2024.1"]) {
+    it(`does not treat ${version} as a pre-release`, () => {
+      assert.equal(isPipPrereleaseVersion(version), false);
+    });
+  }
 });
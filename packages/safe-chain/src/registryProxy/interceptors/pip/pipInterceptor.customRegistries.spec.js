@@ -8,6 +8,8 @@ describe("pipInterceptor custom registries", async () => {
 
   mock.module("../../../config/settings.js", {
     namedExports: {
+      getBlockStatus: () => 403,
+      getRedirectMap: () => ({}),
       ECOSYSTEM_PY: "py",
       getEcoSystem: () => "py",
       getLoggingLevel: () => "silent",
@@ -15,10 +17,20 @@ describe("pipInterceptor custom registries", async () => {
       getMinimumPackageAgeExclusions: () => [],
       getPipCustomRegistries: () => customRegistries,
       LOGGING_SILENT: "silent",
+      LOGGING_DECISIONS: "decisions",
       LOGGING_VERBOSE: "verbose",
       LOG_FILE_FORMAT_JSON: "json",
       LOG_FILE_FORMAT_PLAIN: "plain",
       skipMinimumPackageAge: () => false,
+      getBlockPrereleases: () => false,
+      getWarnInstallScripts: () => false,
+      getCheckDependencyClosure: () => false,
+      getBlockInstallScripts: () => false,
+      getInstallScriptAllowlist: () => [],
+      getFlagSizeAnomaly: () => false,
+      getBlockSizeAnomaly: () => false,
+      getSizeAnomalyFactor: () => 10,
+      getPrereleaseAllowlist: () => [],
       getLogFileFormat: () => "json",
       getLogFileVerbosity: () => "verbose",
       getLogFile: () => undefined,
@@ -39,6 +51,8 @@ describe("pipInterceptor custom registries", async () => {
         scannedPackages.push({ packageName, version });
         return malwareResponse;
       },
+      getMalwareAdvisories: async () => [],
+      openMalwareVersionCheck: async () => () => false,
     },
   });
 
@@ -1,13 +1,19 @@
 import {
   ECOSYSTEM_PY,
+  getMinimumPackageAgeHours,
   getPipCustomRegistries,
   skipMinimumPackageAge,
 } from "../../../config/settings.js";
-import { isMalwarePackage } from "../../../scanning/audit/index.js";
+import {
+  getMalwareAdvisories,
+  isMalwarePackage,
+  openMalwareVersionCheck,
+} from "../../../scanning/audit/index.js";
 import { getEquivalentPackageNames } from "../../../scanning/packageNameVariants.js";
 import { openNewPackagesDatabase } from "../../../scanning/newPackagesListCache.js";
 import { interceptRequests } from "../interceptorBuilder.js";
 import { isExcludedFromMinimumPackageAge } from "../minimumPackageAgeExclusions.js";
+import { isBlockedPrerelease } from "../prereleasePolicy.js";
 import {
   modifyPipInfoRequestHeaders,
   modifyPipInfoResponse,
@@ -17,7 +23,10 @@ import {
   getTestPackageCanonicalName,
   synthesizePipSimpleResponse,
 } from "./pipTestPackages.js";
-import { parsePipPackageFromUrl } from "./parsePipPackageUrl.js";
+import {
+  isPipPrereleaseVersion,
+  parsePipPackageFromUrl,
+} from "./parsePipPackageUrl.js";
 
 const knownPipRegistries = [
   "files.pythonhosted.org",
@@ -42,12 +51,22 @@ export function pipInterceptorForUrl(url) {
   return undefined;
 }
 
+/**
+ * Intercepts a host that is not a known or configured pip registry as one.
+ *
+ * @param {string} registry
+ * @returns {import("../interceptorBuilder.js").Interceptor | undefined}
+ */
+export function pipInterceptorForRegistry(registry) {
+  return buildPipInterceptor(registry);
+}
+
 /**
  * @param {string} registry
  * @returns {import("../interceptorBuilder.js").Interceptor | undefined}
  */
 function buildPipInterceptor(registry) {
-  return interceptRequests(createPipRequestHandler(registry));
+  return interceptRequests(createPipRequestHandler(registry), "pypi");
 }
 
 /**
@@ -56,11 +75,17 @@ function buildPipInterceptor(registry) {
  */
 function createPipRequestHandler(registry) {
   return async (reqContext) => {
-    const minimumAgeChecksEnabled = !skipMinimumPackageAge();
+    const minimumAgeChecksEnabled = !skipMinimumPackageAge(
+      reqContext.policyOverrides
+    );
+    const minimumPackageAgeHours = getMinimumPackageAgeHours(
+      reqContext.policyOverrides
+    );
     const metadataInfo = parsePipMetadataUrl(reqContext.targetUrl);
     const metadataPackageName = metadataInfo.packageName;
 
     if (metadataPackageName) {
+      reqContext.identifyPackage(metadataPackageName, undefined);
       const canonical = getTestPackageCanonicalName(metadataPackageName);
       if (canonical) {
         reqContext.setSyntheticResponse(synthesizePipSimpleResponse(canonical));
@@ -68,20 +93,29 @@ function createPipRequestHandler(registry) {
       }
     }
 
-    if (
-      minimumAgeChecksEnabled &&
-      metadataPackageName &&
-      !isExcludedFromMinimumPackageAge(metadataPackageName)
-    ) {
-      const newPackagesDatabase = await openNewPackagesDatabase();
+    // The index lists the files the resolver picks from, so malware and
+    // too-new versions are removed before their download is even requested
+    if (metadataPackageName) {
+      const newPackagesDatabase =
+        minimumAgeChecksEnabled &&
+        !isExcludedFromMinimumPackageAge(metadataPackageName)
+          ? await openNewPackagesDatabase()
+          : undefined;
+      const isMalwareVersion = await openMalwareVersionCheck();
       reqContext.modifyRequestHeaders(modifyPipInfoRequestHeaders);
       reqContext.modifyBody((body, headers) =>
         modifyPipInfoResponse(
           body,
           headers,
           reqContext.targetUrl,
-          newPackagesDatabase.isNewlyReleasedPackage,
-          metadataPackageName
+          (packageName, version) =>
+            newPackagesDatabase?.isNewlyReleasedPackage(
+              packageName,
+              version,
+              minimumPackageAgeHours
+            ) ?? false,
+          metadataPackageName,
+          isMalwareVersion
         )
       );
       return;
@@ -91,6 +125,7 @@ function createPipRequestHandler(registry) {
       reqContext.targetUrl,
       registry
     );
+    reqContext.identifyPackage(packageName, version);
 
     if (!packageName) {
       return;
@@ -100,16 +135,30 @@ function createPipRequestHandler(registry) {
       packageName,
       ECOSYSTEM_PY
     );
-    let isMalicious = false;
+    /** @type {string | undefined} */
+    let maliciousPackageName = undefined;
     for (const equivalentPackageName of equivalentPackageNames) {
       if (await isMalwarePackage(equivalentPackageName, version)) {
-        isMalicious = true;
+        maliciousPackageName = equivalentPackageName;
         break;
       }
     }
 
-    if (isMalicious) {
-      reqContext.blockMalware(packageName, version);
+    if (maliciousPackageName) {
+      reqContext.blockMalware(
+        packageName,
+        version,
+        undefined,
+        await getMalwareAdvisories(maliciousPackageName, version)
+      );
+      return;
+    }
+
+    if (
+      version &&
+      isBlockedPrerelease(packageName, version, isPipPrereleaseVersion)
+    ) {
+      reqContext.blockPrerelease(packageName, version);
       return;
     }
 
@@ -121,7 +170,8 @@ function createPipRequestHandler(registry) {
       const newPackagesDatabase = await openNewPackagesDatabase();
       const isNewlyReleased = newPackagesDatabase.isNewlyReleasedPackage(
         packageName,
-        version
+        version,
+        minimumPackageAgeHours
       );
 
       if (isNewlyReleased) {
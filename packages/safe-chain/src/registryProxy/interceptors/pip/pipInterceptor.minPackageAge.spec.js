@@ -9,6 +9,8 @@ describe("pipInterceptor minimum package age", async () => {
   mock.module("../../../scanning/audit/index.js", {
     namedExports: {
       isMalwarePackage: async () => false,
+      getMalwareAdvisories: async () => [],
+      openMalwareVersionCheck: async () => () => false,
     },
   });
 
@@ -28,6 +30,8 @@ describe("pipInterceptor minimum package age", async () => {
 
   mock.module("../../../config/settings.js", {
     namedExports: {
+      getBlockStatus: () => 403,
+      getRedirectMap: () => ({}),
       ECOSYSTEM_PY: "py",
       getEcoSystem: () => "py",
       getLoggingLevel: () => "silent",
@@ -35,10 +39,20 @@ describe("pipInterceptor minimum package age", async () => {
       getMinimumPackageAgeExclusions: () => minimumPackageAgeExclusionsSetting,
       getPipCustomRegistries: () => [],
       LOGGING_SILENT: "silent",
+      LOGGING_DECISIONS: "decisions",
       LOGGING_VERBOSE: "verbose",
       LOG_FILE_FORMAT_JSON: "json",
       LOG_FILE_FORMAT_PLAIN: "plain",
       skipMinimumPackageAge: () => skipMinimumPackageAgeSetting,
+      getBlockPrereleases: () => false,
+      getWarnInstallScripts: () => false,
+      getCheckDependencyClosure: () => false,
+      getBlockInstallScripts: () => false,
+      getInstallScriptAllowlist: () => [],
+      getFlagSizeAnomaly: () => false,
+      getBlockSizeAnomaly: () => false,
+      getSizeAnomalyFactor: () => 10,
+      getPrereleaseAllowlist: () => [],
       getLogFileFormat: () => "json",
       getLogFileVerbosity: () => "verbose",
       getLogFile: () => undefined,
@@ -120,15 +134,23 @@ describe("pipInterceptor minimum package age", async () => {
     newlyReleasedPackageResponse = false;
   });
 
-  it("should not modify metadata responses when the package is excluded", async () => {
+  it("should not suppress too-young versions in metadata responses when the package is excluded", async () => {
     const url = "https://pypi.org/simple/foo-bar/";
     newlyReleasedPackageResponse = true;
     minimumPackageAgeExclusionsSetting = ["foo-bar"];
 
     const interceptor = pipInterceptorForUrl(url);
     const result = await interceptor.handleRequest(url);
+    const modifiedBody = result.modifyBody(
+      Buffer.from(`
+        <a href="https://files.pythonhosted.org/packages/xx/yy/foo_bar-2.0.0.tar.gz">foo_bar-2.0.0.tar.gz</a>
+      `),
+      {
+        "content-type": "application/vnd.pypi.simple.v1+html",
+      }
+    ).toString("utf8");
 
-    assert.equal(result.modifiesResponse(), false);
+    assert.ok(modifiedBody.includes("foo_bar-2.0.0.tar.gz"));
 
     minimumPackageAgeExclusionsSetting = [];
     newlyReleasedPackageResponse = false;
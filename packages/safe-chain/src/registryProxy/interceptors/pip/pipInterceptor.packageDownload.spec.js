@@ -4,6 +4,9 @@ import assert from "node:assert";
 describe("pipInterceptor", async () => {
   let scannedPackages;
   let malwareResponse = false;
+  let malwareVersions = new Set();
+  let blockPrereleasesSetting = false;
+  let prereleaseAllowlistSetting = [];
 
   mock.module("../../../scanning/audit/index.js", {
     namedExports: {
@@ -11,6 +14,9 @@ describe("pipInterceptor", async () => {
         scannedPackages.push({ packageName, version });
         return malwareResponse;
       },
+      getMalwareAdvisories: async () => [],
+      openMalwareVersionCheck: async () => (packageName, version) =>
+        malwareVersions.has(`${packageName}@${version}`),
     },
   });
 
@@ -24,6 +30,8 @@ describe("pipInterceptor", async () => {
 
   mock.module("../../../config/settings.js", {
     namedExports: {
+      getBlockStatus: () => 403,
+      getRedirectMap: () => ({}),
       ECOSYSTEM_PY: "py",
       getEcoSystem: () => "py",
       getLoggingLevel: () => "silent",
@@ -31,10 +39,20 @@ describe("pipInterceptor", async () => {
       getMinimumPackageAgeExclusions: () => [],
       getPipCustomRegistries: () => [],
       LOGGING_SILENT: "silent",
+      LOGGING_DECISIONS: "decisions",
       LOGGING_VERBOSE: "verbose",
       LOG_FILE_FORMAT_JSON: "json",
       LOG_FILE_FORMAT_PLAIN: "plain",
       skipMinimumPackageAge: () => false,
+      getBlockPrereleases: () => blockPrereleasesSetting,
+      getWarnInstallScripts: () => false,
+      getCheckDependencyClosure: () => false,
+      getBlockInstallScripts: () => false,
+      getInstallScriptAllowlist: () => [],
+      getFlagSizeAnomaly: () => false,
+      getBlockSizeAnomaly: () => false,
+      getSizeAnomalyFactor: () => 10,
+      getPrereleaseAllowlist: () => prereleaseAllowlistSetting,
       getLogFileFormat: () => "json",
       getLogFileVerbosity: () => "verbose",
       getLogFile: () => undefined,
@@ -165,4 +183,72 @@ describe("pipInterceptor", async () => {
 
     malwareResponse = false;
   });
+
+  it("should block pre-release downloads when pre-releases are blocked", async () => {
+    scannedPackages = [];
+    blockPrereleasesSetting = true;
+    prereleaseAllowlistSetting = [];
+    const url =
+      "https://files.pythonhosted.org/packages/xx/yy/foo_bar-1.0.0rc1-py3-none-any.whl";
+
+    const interceptor = pipInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.ok(result.blockResponse);
+    assert.equal(result.blockResponse.statusCode, 403);
+    assert.equal(
+      result.blockResponse.message,
+      "Forbidden - blocked by safe-chain pre-release policy (foo_bar@1.0.0rc1)"
+    );
+
+    blockPrereleasesSetting = false;
+  });
+
+  it("should allow allowlisted pre-releases using an equivalent package name", async () => {
+    scannedPackages = [];
+    blockPrereleasesSetting = true;
+    prereleaseAllowlistSetting = ["foo-bar@1.0.0rc1"];
+    const url =
+      "https://files.pythonhosted.org/packages/xx/yy/foo_bar-1.0.0rc1.tar.gz";
+
+    const interceptor = pipInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+
+    assert.equal(result.blockResponse, undefined);
+
+    blockPrereleasesSetting = false;
+    prereleaseAllowlistSetting = [];
+  });
+
+  it("should remove malware versions from the simple index before they are downloaded", async () => {
+    malwareVersions = new Set(["foo-bar@2.0.0"]);
+    const url = "https://pypi.org/simple/foo-bar/";
+
+    const interceptor = pipInterceptorForUrl(url);
+    const result = await interceptor.handleRequest(url);
+    const modifiedBody = JSON.parse(
+      result
+        .modifyBody(
+          Buffer.from(
+            JSON.stringify({
+              name: "foo-bar",
+              files: [
+                { filename: "foo_bar-1.0.0.tar.gz" },
+                { filename: "foo_bar-2.0.0-py3-none-any.whl" },
+              ],
+            })
+          ),
+          { "content-type": "application/vnd.pypi.simple.v1+json" }
+        )
+        .toString("utf8")
+    );
+
+    assert.equal(result.blockResponse, undefined);
+    assert.deepEqual(
+      modifiedBody.files.map((file) => file.filename),
+      ["foo_bar-1.0.0.tar.gz"]
+    );
+
+    malwareVersions = new Set();
+  });
 });
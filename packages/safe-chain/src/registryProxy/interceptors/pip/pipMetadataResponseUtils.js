@@ -14,12 +14,28 @@ export function getPipMetadataContentType(headers) {
     .trim();
 }
 
+// Why a version is removed from package metadata
+export const SUPPRESSION_MINIMUM_AGE = "minimum-age";
+export const SUPPRESSION_MALWARE = "malware";
+
 /**
  * @param {string} packageName
  * @param {string} version
+ * @param {string} [reason] - SUPPRESSION_MINIMUM_AGE or SUPPRESSION_MALWARE
  * @returns {void}
  */
-export function logSuppressedVersion(packageName, version) {
+export function logSuppressedVersion(
+  packageName,
+  version,
+  reason = SUPPRESSION_MINIMUM_AGE
+) {
+  if (reason === SUPPRESSION_MALWARE) {
+    ui.writeWarning(
+      `Safe-chain: ${packageName}@${version} is malware and was removed from the package index.`
+    );
+    return;
+  }
+
   recordSuppressedVersion();
   ui.writeVerbose(
     `Safe-chain: ${packageName}@${version} is newer than ${getMinimumPackageAgeHours()} hours and was removed (minimumPackageAgeInHours setting).`
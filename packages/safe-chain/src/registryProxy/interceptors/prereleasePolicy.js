@@ -0,0 +1,68 @@
+import {
+  getBlockPrereleases,
+  getPrereleaseAllowlist,
+  getEcoSystem,
+} from "../../config/settings.js";
+import { getEquivalentPackageNames } from "../../scanning/packageNameVariants.js";
+import {
+  matchesExclusionPattern,
+  splitEcosystemPrefix,
+} from "./minimumPackageAgeExclusions.js";
+
+/**
+ * Returns whether a pre-release download must be blocked. Allowlist entries are
+ * either a package pattern (all pre-releases of the package) or "<package>@<version>".
+ *
+ * @param {string | undefined} packageName
+ * @param {string | undefined} version
+ * @param {(version: string | undefined) => boolean} isPrereleaseVersion - Ecosystem-specific pre-release detection
+ * @returns {boolean}
+ */
+export function isBlockedPrerelease(packageName, version, isPrereleaseVersion) {
+  if (!packageName || !version || !getBlockPrereleases()) {
+    return false;
+  }
+
+  if (!isPrereleaseVersion(version)) {
+    return false;
+  }
+
+  return !isAllowedPrerelease(packageName, version);
+}
+
+/**
+ * @param {string} packageName
+ * @param {string} version
+ * @returns {boolean}
+ */
+function isAllowedPrerelease(packageName, version) {
+  const candidateNames = getEquivalentPackageNames(packageName, getEcoSystem());
+
+  return getPrereleaseAllowlist().some((entry) => {
+    const { pattern, allowedVersion } = parseAllowlistEntry(entry);
+    if (allowedVersion !== undefined && allowedVersion !== version) {
+      return false;
+    }
+    return candidateNames.some((name) => matchesExclusionPattern(name, pattern));
+  });
+}
+
+/**
+ * Splits "<package>@<version>" while keeping the leading "@" of scoped npm
+ * packages and the ecosystem prefix.
+ *
+ * @param {string} entry
+ * @returns {{pattern: string, allowedVersion: string | undefined}}
+ */
+function parseAllowlistEntry(entry) {
+  const { prefix, pattern } = splitEcosystemPrefix(entry);
+  const separatorIndex = pattern.lastIndexOf("@");
+  if (separatorIndex <= 0) {
+    return { pattern: entry, allowedVersion: undefined };
+  }
+
+  return {
+    pattern: prefix + pattern.substring(0, separatorIndex),
+    allowedVersion: pattern.substring(separatorIndex + 1),
+  };
+}
@@ -0,0 +1,115 @@
+import { describe, it, afterEach } from "node:test";
+import assert from "node:assert";
+import { isBlockedPrerelease } from "./prereleasePolicy.js";
+import { isNpmPrereleaseVersion } from "./npm/parseNpmPackageUrl.js";
+import { isPipPrereleaseVersion } from "./pip/parsePipPackageUrl.js";
+import { initializeCliArguments } from "../../config/cliArguments.js";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  setEcoSystem,
+} from "../../config/settings.js";
+
+describe("prereleasePolicy", () => {
+  afterEach(() => {
+    initializeCliArguments([]);
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  it("blocks npm pre-release 1.0.0-beta.1", () => {
+    initializeCliArguments(["--safe-chain-block-prereleases"]);
+
+    assert.equal(
+      isBlockedPrerelease("lodash", "1.0.0-beta.1", isNpmPrereleaseVersion),
+      true
+    );
+  });
+
+  it("blocks PyPI pre-release 1.0.0rc1", () => {
+    setEcoSystem(ECOSYSTEM_PY);
+    initializeCliArguments(["--safe-chain-block-prereleases"]);
+
+    assert.equal(
+      isBlockedPrerelease("requests", "1.0.0rc1", isPipPrereleaseVersion),
+      true
+    );
+  });
+
+  it("blocks Cargo (SemVer) pre-release 1.0.0-alpha", () => {
+    initializeCliArguments(["--safe-chain-block-prereleases"]);
+
+    assert.equal(
+      isBlockedPrerelease("serde", "1.0.0-alpha", isNpmPrereleaseVersion),
+      true
+    );
+  });
+
+  it("does not block pre-releases unless the option is enabled", () => {
+    assert.equal(
+      isBlockedPrerelease("lodash", "1.0.0-beta.1", isNpmPrereleaseVersion),
+      false
+    );
+  });
+
+  it("does not block stable versions or build metadata", () => {
+    initializeCliArguments(["--safe-chain-block-prereleases"]);
+
+    assert.equal(
+      isBlockedPrerelease("lodash", "4.17.21", isNpmPrereleaseVersion),
+      false
+    );
+    assert.equal(
+      isBlockedPrerelease("lodash", "1.0.0+build.1", isNpmPrereleaseVersion),
+      false
+    );
+  });
+
+  it("allows scoped npm pre-releases on the allowlist", () => {
+    initializeCliArguments([
+      "--safe-chain-block-prereleases",
+      "--safe-chain-allow-prerelease=@scope/pkg@2.0.0-rc.1",
+    ]);
+
+    assert.equal(
+      isBlockedPrerelease("@scope/pkg", "2.0.0-rc.1", isNpmPrereleaseVersion),
+      false
+    );
+    assert.equal(
+      isBlockedPrerelease("@scope/pkg", "2.0.0-rc.2", isNpmPrereleaseVersion),
+      true
+    );
+  });
+
+  it("allows every pre-release of an allowlisted scope", () => {
+    initializeCliArguments([
+      "--safe-chain-block-prereleases",
+      "--safe-chain-allow-prerelease=@scope/*",
+    ]);
+
+    assert.equal(
+      isBlockedPrerelease("@scope/pkg", "2.0.0-rc.1", isNpmPrereleaseVersion),
+      false
+    );
+  });
+
+  it("allows a version of ecosystem-prefixed globs on the allowlist", () => {
+    setEcoSystem(ECOSYSTEM_PY);
+    initializeCliArguments([
+      "--safe-chain-block-prereleases",
+      "--safe-chain-allow-prerelease=pypi:acme-*@2.0.0rc1",
+    ]);
+
+    assert.equal(
+      isBlockedPrerelease("acme_utils", "2.0.0rc1", isPipPrereleaseVersion),
+      false
+    );
+    assert.equal(
+      isBlockedPrerelease("acme-utils", "2.0.0rc2", isPipPrereleaseVersion),
+      true
+    );
+    assert.equal(
+      isBlockedPrerelease("requests", "2.0.0rc1", isPipPrereleaseVersion),
+      true
+    );
+  });
+});
@@ -0,0 +1,33 @@
+import { ECOSYSTEM_JS, ECOSYSTEM_PY } from "../../config/settings.js";
+
+/**
+ * Package managers identify themselves with their name as the first product
+ * token of the User-Agent, for example "npm/10.2.4 node/v20.11.0 darwin arm64"
+ * or "pip/24.0 {...}". Cargo uses a space instead of a slash: "cargo 1.75.0".
+ * Cargo has no ecosystem in safe-chain, so it maps to none.
+ */
+const userAgentEcosystems = [
+  { names: ["npm", "pnpm", "yarn", "bun", "deno"], ecosystem: ECOSYSTEM_JS },
+  { names: ["pip", "uv", "poetry", "pdm", "pipenv"], ecosystem: ECOSYSTEM_PY },
+  { names: ["cargo"], ecosystem: undefined },
+];
+
+/**
+ * Gets the ecosystem a User-Agent points to. This is a hint, not authoritative:
+ * any client can send any User-Agent.
+ *
+ * @param {string | undefined} userAgent
+ * @returns {string | undefined} - ECOSYSTEM_JS, ECOSYSTEM_PY or undefined
+ */
+export function getEcosystemFromUserAgent(userAgent) {
+  const product = userAgent
+    ?.trim()
+    .split(/[\s/]/, 1)[0]
+    .toLowerCase();
+  if (!product) {
+    return undefined;
+  }
+
+  return userAgentEcosystems.find(({ names }) => names.includes(product))
+    ?.ecosystem;
+}
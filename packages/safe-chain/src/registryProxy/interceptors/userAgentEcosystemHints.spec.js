@@ -0,0 +1,85 @@
+import { describe, it, afterEach } from "node:test";
+import assert from "node:assert";
+import { getEcosystemFromUserAgent } from "./userAgentEcosystemHints.js";
+import { createInterceptorForUserAgentHint } from "./createInterceptorForEcoSystem.js";
+import { initializeCliArguments } from "../../config/cliArguments.js";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  setEcoSystem,
+} from "../../config/settings.js";
+
+describe("getEcosystemFromUserAgent", () => {
+  const cases = [
+    ["npm/10.2.4 node/v20.11.0 darwin arm64 workspaces/false", ECOSYSTEM_JS],
+    ["pnpm/8.15.1 npm/? node/v20.11.0 linux x64", ECOSYSTEM_JS],
+    ["yarn/1.22.19 npm/? node/v20.11.0 darwin arm64", ECOSYSTEM_JS],
+    ["Bun/1.0.25", ECOSYSTEM_JS],
+    ["Deno/1.40.2", ECOSYSTEM_JS],
+    [
+      'pip/24.0 {"ci":null,"cpu":"x86_64","implementation":{"name":"CPython","version":"3.12.1"}}',
+      ECOSYSTEM_PY,
+    ],
+    ['uv/0.1.24 {"installer":{"name":"uv","version":"0.1.24"}}', ECOSYSTEM_PY],
+    ["poetry/1.7.1", ECOSYSTEM_PY],
+    ["cargo 1.75.0 (1d8b05cdd 2023-11-20)", undefined],
+    ["curl/8.4.0", undefined],
+    ["", undefined],
+    [undefined, undefined],
+  ];
+
+  for (const [userAgent, ecosystem] of cases) {
+    it(`maps ${JSON.stringify(userAgent)} to ${ecosystem}`, () => {
+      assert.strictEqual(getEcosystemFromUserAgent(userAgent), ecosystem);
+    });
+  }
+});
+
+describe("createInterceptorForUserAgentHint", () => {
+  afterEach(() => {
+    initializeCliArguments([]);
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  it("does not use the User-Agent unless hints are enabled", () => {
+    initializeCliArguments([]);
+
+    assert.strictEqual(
+      createInterceptorForUserAgentHint("mirror.corp.example:443", "npm/10.2.4"),
+      undefined
+    );
+  });
+
+  it("intercepts an unknown host when the User-Agent matches the ecosystem", async () => {
+    initializeCliArguments(["--safe-chain-ua-ecosystem-hints"]);
+    setEcoSystem(ECOSYSTEM_PY);
+
+    const interceptor = createInterceptorForUserAgentHint(
+      "mirror.corp.example:443",
+      "pip/24.0"
+    );
+
+    assert.ok(interceptor);
+    const handler = await interceptor.handleRequest(
+      "https://mirror.corp.example/simple/"
+    );
+    assert.strictEqual(handler.blockResponse, undefined);
+  });
+
+  it("does not intercept when the User-Agent points to another ecosystem", () => {
+    initializeCliArguments(["--safe-chain-ua-ecosystem-hints"]);
+    setEcoSystem(ECOSYSTEM_JS);
+
+    assert.strictEqual(
+      createInterceptorForUserAgentHint("mirror.corp.example:443", "pip/24.0"),
+      undefined
+    );
+    assert.strictEqual(
+      createInterceptorForUserAgentHint(
+        "mirror.corp.example:443",
+        "cargo 1.75.0 (1d8b05cdd 2023-11-20)"
+      ),
+      undefined
+    );
+  });
+});
@@ -0,0 +1,112 @@
+export const GITHUB_HOST = "github.com";
+export const GITHUB_CODELOAD_HOST = "codeload.github.com";
+export const GITLAB_HOST = "gitlab.com";
+export const BITBUCKET_HOST = "bitbucket.org";
+
+export const knownVcsHosts = [
+  GITHUB_HOST,
+  GITHUB_CODELOAD_HOST,
+  GITLAB_HOST,
+  BITBUCKET_HOST,
+];
+
+const GIT_SMART_HTTP_PATH = /^\/(.+?)(?:\.git)?\/(?:info\/refs|git-upload-pack)$/;
+const ARCHIVE_REF_PREFIX = /^refs\/(?:heads|tags)\//;
+const CODELOAD_FORMATS = ["tar.gz", "zip", "legacy.tar.gz", "legacy.zip"];
+const ARCHIVE_EXTENSION = /\.(?:tar\.gz|tgz|tar\.bz2|zip)$/;
+
+/**
+ * Parses a dependency fetch from a VCS host into the repository and, when
+ * the URL contains it, the git ref. Both git over HTTPS and source archives
+ * are recognized; other requests to the host, such as web pages or API
+ * calls, are not dependency fetches.
+ *
+ * With git over HTTPS the ref is negotiated in the request body, so it is
+ * not known.
+ *
+ * @example
+ * parseVcsUrl("https://github.com/org/repo.git/info/refs?service=git-upload-pack")
+ * // => { repository: "github.com/org/repo", ref: undefined }
+ *
+ * parseVcsUrl("https://codeload.github.com/org/repo/tar.gz/v1.2.0")
+ * // => { repository: "github.com/org/repo", ref: "v1.2.0" }
+ *
+ * @param {string} url
+ * @returns {{repository: string, ref: string | undefined} | undefined}
+ */
+export function parseVcsUrl(url) {
+  let parsedUrl;
+  try {
+    parsedUrl = new URL(url);
+  } catch {
+    return undefined;
+  }
+
+  const hostname = parsedUrl.hostname.toLowerCase();
+  if (!knownVcsHosts.includes(hostname)) {
+    return undefined;
+  }
+
+  let pathname;
+  try {
+    pathname = decodeURIComponent(parsedUrl.pathname);
+  } catch {
+    return undefined;
+  }
+
+  const gitPath = GIT_SMART_HTTP_PATH.exec(pathname);
+  if (gitPath && hostname !== GITHUB_CODELOAD_HOST) {
+    return createDependency(hostname, gitPath[1], undefined);
+  }
+
+  const segments = pathname.split("/").filter(Boolean);
+  if (hostname === GITHUB_CODELOAD_HOST) {
+    // /<org>/<repo>/<tar.gz|zip|legacy.tar.gz>/<ref>
+    const [org, repo, format, ...ref] = segments;
+    return org && repo && CODELOAD_FORMATS.includes(format) && ref.length > 0
+      ? createDependency(GITHUB_HOST, `${org}/${repo}`, ref.join("/"))
+      : undefined;
+  }
+
+  if (hostname === GITLAB_HOST) {
+    // /<group>/<repo>/-/archive/<ref>/<file>
+    const archiveIndex = segments.indexOf("-");
+    return archiveIndex >= 2 &&
+      segments[archiveIndex + 1] === "archive" &&
+      segments.length === archiveIndex + 4
+      ? createDependency(
+          hostname,
+          segments.slice(0, archiveIndex).join("/"),
+          segments[archiveIndex + 2]
+        )
+      : undefined;
+  }
+
+  // GitHub: /<org>/<repo>/archive/<ref>.tar.gz
+  // Bitbucket: /<org>/<repo>/get/<ref>.tar.gz
+  const [org, repo, kind, ...ref] = segments;
+  const archiveKind = hostname === GITHUB_HOST ? "archive" : "get";
+  const archive = ref.join("/");
+  if (!org || !repo || kind !== archiveKind || !ARCHIVE_EXTENSION.test(archive)) {
+    return undefined;
+  }
+  return createDependency(
+    hostname,
+    `${org}/${repo}`,
+    archive.replace(ARCHIVE_EXTENSION, "")
+  );
+}
+
+/**
+ * @param {string} hostname
+ * @param {string} repositoryPath
+ * @param {string | undefined} ref
+ * @returns {{repository: string, ref: string | undefined}}
+ */
+function createDependency(hostname, repositoryPath, ref) {
+  return {
+    // The hosts treat repository paths case-insensitively
+    repository: `${hostname}/${repositoryPath.toLowerCase()}`,
+    ref: ref?.replace(ARCHIVE_REF_PREFIX, "") || undefined,
+  };
+}
@@ -0,0 +1,54 @@
+import { describe, it } from "node:test";
+import assert from "node:assert";
+import { parseVcsUrl } from "./parseVcsUrl.js";
+
+describe("parseVcsUrl", () => {
+  const cases = [
+    {
+      url: "https://github.com/Org/Repo.git/info/refs?service=git-upload-pack",
+      expected: { repository: "github.com/org/repo", ref: undefined },
+    },
+    {
+      url: "https://github.com/org/repo/git-upload-pack",
+      expected: { repository: "github.com/org/repo", ref: undefined },
+    },
+    {
+      url: "https://codeload.github.com/org/repo/tar.gz/refs/heads/main",
+      expected: { repository: "github.com/org/repo", ref: "main" },
+    },
+    {
+      url: "https://github.com/org/repo/archive/refs/tags/v1.2.0.tar.gz",
+      expected: { repository: "github.com/org/repo", ref: "v1.2.0" },
+    },
+    {
+      url: "https://gitlab.com/group/sub/repo/-/archive/1a2b3c/repo-1a2b3c.tar.gz",
+      expected: { repository: "gitlab.com/group/sub/repo", ref: "1a2b3c" },
+    },
+    {
+      url: "https://gitlab.com/group/sub/repo.git/info/refs?service=git-upload-pack",
+      expected: { repository: "gitlab.com/group/sub/repo", ref: undefined },
+    },
+    {
+      url: "https://bitbucket.org/org/repo/get/v2.zip",
+      expected: { repository: "bitbucket.org/org/repo", ref: "v2" },
+    },
+  ];
+
+  for (const { url, expected } of cases) {
+    it(`parses ${url}`, () => {
+      assert.deepStrictEqual(parseVcsUrl(url), expected);
+    });
+  }
+
+  it("ignores requests that are not dependency fetches", () => {
+    assert.strictEqual(parseVcsUrl("https://github.com/org/repo"), undefined);
+    assert.strictEqual(
+      parseVcsUrl("https://github.com/org/repo/archive/main"),
+      undefined
+    );
+    assert.strictEqual(
+      parseVcsUrl("https://example.com/org/repo.git/info/refs"),
+      undefined
+    );
+  });
+});
@@ -0,0 +1,127 @@
+import {
+  getVcsPolicy,
+  VCS_POLICY_ALLOW,
+  VCS_POLICY_DENY,
+} from "../../../config/settings.js";
+import { ui } from "../../../environment/userInteraction.js";
+import {
+  getMalwareAdvisories,
+  isMalwarePackage,
+} from "../../../scanning/audit/index.js";
+import { interceptRequests } from "../interceptorBuilder.js";
+import { knownVcsHosts, parseVcsUrl } from "./parseVcsUrl.js";
+
+/** The version checked against the malware list when the ref is not known */
+const ANY_REF = "*";
+
+/**
+ * @type {{
+ *   fetches: {repository: string, ref: string | undefined, url: string}[],
+ *   reportedRepositories: Set<string>,
+ * }}
+ */
+const state = {
+  fetches: [],
+  // Repositories a message was written for
+  reportedRepositories: new Set(),
+};
+
+/**
+ * Dependencies installed straight from a VCS host, such as
+ * `git+https://github.com/org/repo#ref`, are only inspected when the VCS
+ * policy is "log" or "deny". With the default "allow" policy the hosts are
+ * tunneled like any other host.
+ *
+ * @param {string} url - A CONNECT target (host:port) or a full URL
+ * @returns {import("../interceptorBuilder.js").Interceptor | undefined}
+ */
+export function vcsInterceptorForUrl(url) {
+  if (getVcsPolicy() === VCS_POLICY_ALLOW) {
+    return undefined;
+  }
+
+  const hostname = getHostname(url);
+  if (hostname && knownVcsHosts.includes(hostname)) {
+    return buildVcsInterceptor();
+  }
+
+  return undefined;
+}
+
+/**
+ * @returns {import("../interceptorBuilder.js").Interceptor}
+ */
+function buildVcsInterceptor() {
+  return interceptRequests(async (reqContext) => {
+    const dependency = parseVcsUrl(reqContext.targetUrl);
+    if (!dependency) {
+      return;
+    }
+
+    const { repository, ref } = dependency;
+    recordVcsFetch(repository, ref, reqContext.targetUrl);
+
+    // Feed entries for a repository use its host and path as the package
+    // name, and "*" or a ref as the version
+    if (await isMalwarePackage(repository, ref ?? ANY_REF)) {
+      reqContext.blockMalware(
+        repository,
+        ref,
+        `Forbidden - blocked by safe-chain: ${repository} is malware`,
+        await getMalwareAdvisories(repository, ref ?? ANY_REF)
+      );
+      return;
+    }
+
+    if (getVcsPolicy() === VCS_POLICY_DENY) {
+      reqContext.blockVcsDependency(repository, ref);
+    }
+  }, "git");
+}
+
+/**
+ * @param {string} repository
+ * @param {string | undefined} ref
+ * @param {string} url
+ * @returns {void}
+ */
+function recordVcsFetch(repository, ref, url) {
+  state.fetches.push({ repository, ref, url });
+
+  if (!state.reportedRepositories.has(repository)) {
+    state.reportedRepositories.add(repository);
+    ui.writeInformation(
+      `Safe-chain: vcs.fetch - ${repository}${
+        ref ? `@${ref}` : ""
+      } is installed from a VCS host (${url}). The exact version of a git dependency cannot be inspected.`
+    );
+  }
+}
+
+/**
+ * @returns {{repository: string, ref: string | undefined, url: string}[]}
+ */
+export function getVcsFetches() {
+  return state.fetches;
+}
+
+/**
+ * @returns {void}
+ */
+export function resetVcsFetches() {
+  state.fetches = [];
+  state.reportedRepositories.clear();
+}
+
+/**
+ * @param {string} url
+ * @returns {string | undefined}
+ */
+function getHostname(url) {
+  try {
+    return new URL(/^[a-z]+:\/\//i.test(url) ? url : `https://${url}`)
+      .hostname.toLowerCase();
+  } catch {
+    return undefined;
+  }
+}
@@ -0,0 +1,117 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+let malwarePackages = new Set();
+let vcsPolicySetting = "log";
+const informationMessages = [];
+
+mock.module("../../../scanning/audit/index.js", {
+  namedExports: {
+    isMalwarePackage: async (packageName, version) =>
+      malwarePackages.has(`${packageName}@${version}`),
+    getMalwareAdvisories: async () => [],
+  },
+});
+
+mock.module("../../../config/settings.js", {
+  namedExports: {
+    getBlockStatus: () => 403,
+    getRedirectMap: () => ({}),
+    getVcsPolicy: () => vcsPolicySetting,
+    VCS_POLICY_ALLOW: "allow",
+    VCS_POLICY_LOG: "log",
+    VCS_POLICY_DENY: "deny",
+  },
+});
+
+mock.module("../../../environment/userInteraction.js", {
+  namedExports: {
+    ui: {
+      writeInformation: (message) => informationMessages.push(message),
+    },
+  },
+});
+
+describe("vcsInterceptor", async () => {
+  const { getVcsFetches, resetVcsFetches, vcsInterceptorForUrl } =
+    await import("./vcsInterceptor.js");
+
+  const gitUrl =
+    "https://github.com/acme/tools.git/info/refs?service=git-upload-pack";
+
+  beforeEach(() => {
+    malwarePackages = new Set();
+    vcsPolicySetting = "log";
+    informationMessages.length = 0;
+    resetVcsFetches();
+  });
+
+  it("only intercepts VCS hosts when the policy is not allow", () => {
+    assert.ok(vcsInterceptorForUrl("github.com:443"));
+    assert.ok(vcsInterceptorForUrl("codeload.github.com:443"));
+    assert.equal(vcsInterceptorForUrl("api.github.com:443"), undefined);
+
+    vcsPolicySetting = "allow";
+    assert.equal(vcsInterceptorForUrl("github.com:443"), undefined);
+  });
+
+  it("records git dependencies and reports each repository once", async () => {
+    const interceptor = vcsInterceptorForUrl(gitUrl);
+    const first = await interceptor.handleRequest(gitUrl);
+    await interceptor.handleRequest(
+      "https://github.com/acme/tools.git/git-upload-pack"
+    );
+
+    assert.equal(first.blockResponse, undefined);
+    assert.deepStrictEqual(
+      getVcsFetches().map((fetch) => fetch.repository),
+      ["github.com/acme/tools", "github.com/acme/tools"]
+    );
+    assert.equal(informationMessages.length, 1);
+    assert.match(informationMessages[0], /vcs\.fetch - github\.com\/acme\/tools/);
+  });
+
+  it("blocks repositories the malware list names", async () => {
+    const url = "https://codeload.github.com/evil-org/evil-repo/tar.gz/main";
+    malwarePackages = new Set(["github.com/evil-org/evil-repo@main"]);
+
+    const result = await vcsInterceptorForUrl(url).handleRequest(url);
+
+    assert.equal(result.blockResponse.statusCode, 403);
+    assert.equal(result.blockResponse.headers["X-Safe-Chain-Reason"], "malware");
+    assert.equal(
+      result.blockResponse.headers["X-Safe-Chain-Package"],
+      "git:github.com/evil-org/evil-repo@main"
+    );
+  });
+
+  it("blocks every git dependency under the deny policy", async () => {
+    vcsPolicySetting = "deny";
+    let blockedEvent;
+
+    const interceptor = vcsInterceptorForUrl(gitUrl);
+    interceptor.on("vcsDependencyBlocked", (event) => (blockedEvent = event));
+    const result = await interceptor.handleRequest(gitUrl);
+
+    assert.equal(
+      result.blockResponse.message,
+      "Forbidden - blocked by safe-chain VCS dependency policy (github.com/acme/tools)"
+    );
+    assert.equal(
+      result.blockResponse.headers["X-Safe-Chain-Reason"],
+      "vcs-dependency"
+    );
+    assert.equal(blockedEvent.repository, "github.com/acme/tools");
+    assert.equal(blockedEvent.ref, undefined);
+  });
+
+  it("passes requests that are not dependency fetches", async () => {
+    vcsPolicySetting = "deny";
+    const url = "https://github.com/acme/tools";
+
+    const result = await vcsInterceptorForUrl(url).handleRequest(url);
+
+    assert.equal(result.blockResponse, undefined);
+    assert.deepStrictEqual(getVcsFetches(), []);
+  });
+});
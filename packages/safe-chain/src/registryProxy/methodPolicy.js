@@ -0,0 +1,81 @@
+import { getAllowMethods, getDenyMethods } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * The methods advertised in the Allow header when no allow list is set.
+ */
+const STANDARD_METHODS = [
+  "GET",
+  "HEAD",
+  "POST",
+  "PUT",
+  "DELETE",
+  "PATCH",
+  "OPTIONS",
+];
+
+/**
+ * Whether the proxy forwards requests with this method. A denied method is
+ * never forwarded, even when it is also allowed. CONNECT is not governed by
+ * this policy: it only opens a tunnel, the requests sent through it are.
+ *
+ * @param {string | undefined} method
+ * @returns {boolean}
+ */
+export function isMethodAllowed(method) {
+  const normalizedMethod = (method ?? "").toUpperCase();
+  if (getDenyMethods().includes(normalizedMethod)) {
+    return false;
+  }
+
+  const allowMethods = getAllowMethods();
+  return !allowMethods || allowMethods.includes(normalizedMethod);
+}
+
+/**
+ * Answers a request whose method is not forwarded with 405 Method Not Allowed,
+ * before anything is sent upstream.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("http").ServerResponse} res
+ * @param {string} url
+ * @returns {void}
+ */
+export function rejectDisallowedMethod(req, res, url) {
+  ui.writeWarning(
+    `Safe-chain: method.blocked - rejected ${req.method} ${url}, the method is not allowed by the proxy configuration`
+  );
+  req.resume();
+  res.writeHead(405, "Method Not Allowed", {
+    Allow: getAdvertisedMethods().join(", "),
+    "Content-Type": "text/plain",
+  });
+  res.end(`Method Not Allowed: safe-chain does not forward ${req.method}\n`);
+}
+
+/**
+ * Answers an upgrade request whose method is not forwarded with 405 Method Not
+ * Allowed on the raw client socket, as upgrades have no response object.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("stream").Duplex} clientSocket
+ * @param {string} url
+ */
+export function rejectDisallowedUpgradeMethod(req, clientSocket, url) {
+  ui.writeWarning(
+    `Safe-chain: method.blocked - rejected ${req.method} ${url}, the method is not allowed by the proxy configuration`
+  );
+  clientSocket.end(
+    `HTTP/1.1 405 Method Not Allowed\r\nAllow: ${getAdvertisedMethods().join(", ")}\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\nMethod Not Allowed: safe-chain does not forward ${req.method}\n`
+  );
+}
+
+/**
+ * @returns {string[]}
+ */
+function getAdvertisedMethods() {
+  const denyMethods = getDenyMethods();
+  return (getAllowMethods() ?? STANDARD_METHODS).filter(
+    (method) => !denyMethods.includes(method)
+  );
+}
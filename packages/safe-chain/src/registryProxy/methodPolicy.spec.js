@@ -0,0 +1,48 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("methodPolicy", async () => {
+  let allowMethods;
+  let denyMethods = [];
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getAllowMethods: () => allowMethods,
+      getDenyMethods: () => denyMethods,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: { writeWarning: () => {} },
+    },
+  });
+
+  const { isMethodAllowed } = await import("./methodPolicy.js");
+
+  beforeEach(() => {
+    allowMethods = undefined;
+    denyMethods = [];
+  });
+
+  it("allows every method by default", () => {
+    assert.strictEqual(isMethodAllowed("GET"), true);
+    assert.strictEqual(isMethodAllowed("DELETE"), true);
+  });
+
+  it("only allows the listed methods", () => {
+    allowMethods = ["GET", "HEAD"];
+
+    assert.strictEqual(isMethodAllowed("get"), true);
+    assert.strictEqual(isMethodAllowed("POST"), false);
+  });
+
+  it("lets a denied method win over an allowed one", () => {
+    allowMethods = ["GET", "PUT"];
+    denyMethods = ["PUT", "DELETE"];
+
+    assert.strictEqual(isMethodAllowed("GET"), true);
+    assert.strictEqual(isMethodAllowed("PUT"), false);
+    assert.strictEqual(isMethodAllowed("DELETE"), false);
+  });
+});
@@ -0,0 +1,239 @@
+import { getBlockingDisabled, getMetricsEnabled } from "../config/settings.js";
+import { getFeedAgeSeconds, isFeedStale } from "../scanning/feedFreshness.js";
+import { getFeedVersion } from "../scanning/feedVersion.js";
+import { getBoundedCacheSizes } from "./boundedCache.js";
+import { getDnsCacheStats } from "./dnsCache.js";
+
+export const METRICS_PATH = "/metrics";
+export const HEALTH_PATH = "/healthz";
+
+/** The decision did not need registry metadata */
+export const CACHE_NONE = "none";
+/** The metadata was served from the response cache */
+export const CACHE_HIT = "hit";
+/** The metadata was fetched from the registry */
+export const CACHE_MISS = "miss";
+
+const POLICY_EVAL_METRIC = "safe_chain_policy_eval_seconds";
+const FEED_AGE_METRIC = "safe_chain_feed_age_seconds";
+const BLOCKING_DISABLED_METRIC = "safe_chain_blocking_disabled";
+const TRACKED_PACKAGES_METRIC = "safe_chain_tracked_packages";
+const TRACKED_PACKAGE_EVICTIONS_METRIC =
+  "safe_chain_tracked_package_evictions_total";
+const DNS_CACHE_METRIC = "safe_chain_dns_cache_lookups_total";
+const DNS_CACHE_ENTRIES_METRIC = "safe_chain_dns_cache_entries";
+const POLICY_EVAL_BUCKETS = [
+  0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2.5, 5, 10,
+];
+
+/**
+ * @typedef {Object} Histogram
+ * @property {Record<string, string>} labels
+ * @property {number[]} bucketCounts - Counts per upper bound in POLICY_EVAL_BUCKETS, not cumulative
+ * @property {number} count
+ * @property {number} sum
+ */
+
+/**
+ * @type {{enabled: boolean, policyEvaluations: Map<string, Histogram>}}
+ */
+const state = {
+  enabled: false,
+  policyEvaluations: new Map(),
+};
+
+/**
+ * Reads the metrics setting and clears recorded metrics. Called when the proxy
+ * starts, so requests do not read the setting.
+ *
+ * @returns {void}
+ */
+export function initializeMetrics() {
+  state.enabled = getMetricsEnabled();
+  state.policyEvaluations.clear();
+}
+
+/**
+ * Starts timing a policy decision. Returns undefined when metrics are disabled,
+ * so disabled metrics cost a single check per request.
+ *
+ * @param {string} ecosystem
+ * @returns {((cache: string) => void) | undefined} - Records the decision; later calls are ignored
+ */
+export function startPolicyEvaluation(ecosystem) {
+  if (!state.enabled) {
+    return undefined;
+  }
+
+  const start = process.hrtime.bigint();
+  let recorded = false;
+  return (cache) => {
+    if (recorded) {
+      return;
+    }
+    recorded = true;
+    const seconds = Number(process.hrtime.bigint() - start) / 1e9;
+    observePolicyEvaluation(ecosystem, cache, seconds);
+  };
+}
+
+/**
+ * @param {string} ecosystem
+ * @param {string} cache
+ * @param {number} seconds
+ * @returns {void}
+ */
+export function observePolicyEvaluation(ecosystem, cache, seconds) {
+  const key = `${ecosystem}\n${cache}`;
+  let histogram = state.policyEvaluations.get(key);
+  if (!histogram) {
+    histogram = {
+      labels: { ecosystem, cache },
+      bucketCounts: POLICY_EVAL_BUCKETS.map(() => 0),
+      count: 0,
+      sum: 0,
+    };
+    state.policyEvaluations.set(key, histogram);
+  }
+
+  const bucketIndex = POLICY_EVAL_BUCKETS.findIndex((bound) => seconds <= bound);
+  if (bucketIndex !== -1) {
+    histogram.bucketCounts[bucketIndex]++;
+  }
+  histogram.count++;
+  histogram.sum += seconds;
+}
+
+/**
+ * Formats the recorded metrics in the Prometheus text exposition format.
+ *
+ * @returns {string}
+ */
+export function formatMetrics() {
+  const lines = [
+    `# HELP ${POLICY_EVAL_METRIC} Time to reach a policy decision for a registry request, including metadata fetches for age checks.`,
+    `# TYPE ${POLICY_EVAL_METRIC} histogram`,
+  ];
+
+  for (const histogram of state.policyEvaluations.values()) {
+    const labels = formatLabels(histogram.labels);
+    let cumulativeCount = 0;
+    POLICY_EVAL_BUCKETS.forEach((bound, index) => {
+      cumulativeCount += histogram.bucketCounts[index];
+      lines.push(
+        `${POLICY_EVAL_METRIC}_bucket{${labels},le="${bound}"} ${cumulativeCount}`
+      );
+    });
+    lines.push(
+      `${POLICY_EVAL_METRIC}_bucket{${labels},le="+Inf"} ${histogram.count}`,
+      `${POLICY_EVAL_METRIC}_sum{${labels}} ${histogram.sum}`,
+      `${POLICY_EVAL_METRIC}_count{${labels}} ${histogram.count}`
+    );
+  }
+
+  // Not known until the malware list is loaded on the first request
+  const feedAgeSeconds = getFeedAgeSeconds();
+  if (feedAgeSeconds !== undefined) {
+    lines.push(
+      `# HELP ${FEED_AGE_METRIC} Seconds since the malware list in use was last refreshed from the server.`,
+      `# TYPE ${FEED_AGE_METRIC} gauge`,
+      `${FEED_AGE_METRIC} ${feedAgeSeconds}`
+    );
+  }
+
+  lines.push(
+    `# HELP ${BLOCKING_DISABLED_METRIC} 1 while blocking is turned off with --safe-chain-no-block, 0 otherwise.`,
+    `# TYPE ${BLOCKING_DISABLED_METRIC} gauge`,
+    `${BLOCKING_DISABLED_METRIC} ${getBlockingDisabled() ? 1 : 0}`
+  );
+
+  const cacheSizes = getBoundedCacheSizes();
+  if (cacheSizes.length > 0) {
+    lines.push(
+      `# HELP ${TRACKED_PACKAGES_METRIC} Entries in the in-memory caches of details from package metadata and of DNS answers.`,
+      `# TYPE ${TRACKED_PACKAGES_METRIC} gauge`,
+      ...cacheSizes.map(
+        ({ name, size }) =>
+          `${TRACKED_PACKAGES_METRIC}{${formatLabels({ cache: name })}} ${size}`
+      ),
+      `# HELP ${TRACKED_PACKAGE_EVICTIONS_METRIC} Entries dropped from the in-memory caches because they were full.`,
+      `# TYPE ${TRACKED_PACKAGE_EVICTIONS_METRIC} counter`,
+      ...cacheSizes.map(
+        ({ name, evictions }) =>
+          `${TRACKED_PACKAGE_EVICTIONS_METRIC}{${formatLabels({ cache: name })}} ${evictions}`
+      )
+    );
+  }
+
+  const dnsCacheStats = getDnsCacheStats();
+  lines.push(
+    `# HELP ${DNS_CACHE_METRIC} Upstream hostname lookups by whether the DNS cache answered them.`,
+    `# TYPE ${DNS_CACHE_METRIC} counter`,
+    `${DNS_CACHE_METRIC}{result="hit"} ${dnsCacheStats.hits}`,
+    `${DNS_CACHE_METRIC}{result="negative_hit"} ${dnsCacheStats.negativeHits}`,
+    `${DNS_CACHE_METRIC}{result="miss"} ${dnsCacheStats.misses}`,
+    `# HELP ${DNS_CACHE_ENTRIES_METRIC} Hostnames whose answer the DNS cache holds, including hostnames that do not exist.`,
+    `# TYPE ${DNS_CACHE_ENTRIES_METRIC} gauge`,
+    `${DNS_CACHE_ENTRIES_METRIC} ${dnsCacheStats.size}`
+  );
+
+  return lines.join("\n") + "\n";
+}
+
+/**
+ * Serves the metrics for `GET /metrics` sent to the proxy itself, rather than
+ * through it.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("http").ServerResponse} res
+ * @returns {boolean} - true when the request was handled
+ */
+export function handleMetricsRequest(req, res) {
+  if (!state.enabled || req.url !== METRICS_PATH || req.method !== "GET") {
+    return false;
+  }
+
+  res.writeHead(200, {
+    "content-type": "text/plain; version=0.0.4; charset=utf-8",
+  });
+  res.end(formatMetrics());
+  return true;
+}
+
+/**
+ * Serves the health of the proxy for `GET /healthz` sent to the proxy itself.
+ * It answers 503 once the malware list is stale, so monitoring notices a proxy
+ * that keeps protecting with outdated data.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("http").ServerResponse} res
+ * @returns {boolean} - true when the request was handled
+ */
+export function handleHealthRequest(req, res) {
+  if (!state.enabled || req.url !== HEALTH_PATH || req.method !== "GET") {
+    return false;
+  }
+
+  const stale = isFeedStale();
+  res.writeHead(stale ? 503 : 200, {
+    "content-type": "application/json",
+  });
+  res.end(
+    JSON.stringify({
+      status: stale ? "stale" : "ok",
+      feed_version: getFeedVersion() ?? null,
+      feed_age_seconds: getFeedAgeSeconds() ?? null,
+    })
+  );
+  return true;
+}
+
+/**
+ * @param {Record<string, string>} labels
+ * @returns {string}
+ */
+function formatLabels(labels) {
+  return Object.entries(labels)
+    .map(([name, value]) => `${name}="${value}"`)
+    .join(",");
+}
@@ -0,0 +1,173 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("metrics", async () => {
+  let metricsEnabled = true;
+  let blockingDisabled = false;
+  /** @type {number | undefined} */
+  let feedAgeSeconds;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getMetricsEnabled: () => metricsEnabled,
+      getBlockingDisabled: () => blockingDisabled,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: { writeVerbose: () => {} },
+    },
+  });
+
+  mock.module("../scanning/feedFreshness.js", {
+    namedExports: {
+      getFeedAgeSeconds: () => feedAgeSeconds,
+      isFeedStale: () => feedAgeSeconds !== undefined && feedAgeSeconds > 3600,
+    },
+  });
+
+  mock.module("../scanning/feedVersion.js", {
+    namedExports: {
+      getFeedVersion: () => "feed-42",
+    },
+  });
+
+  mock.module("./dnsCache.js", {
+    namedExports: {
+      getDnsCacheStats: () => ({
+        hits: 7,
+        negativeHits: 2,
+        misses: 3,
+        size: 4,
+      }),
+    },
+  });
+
+  const {
+    CACHE_HIT,
+    formatMetrics,
+    handleHealthRequest,
+    initializeMetrics,
+    observePolicyEvaluation,
+    startPolicyEvaluation,
+  } = await import("./metrics.js");
+
+  beforeEach(() => {
+    metricsEnabled = true;
+    blockingDisabled = false;
+    feedAgeSeconds = undefined;
+    initializeMetrics();
+  });
+
+  it("formats cumulative histogram buckets per ecosystem and cache result", () => {
+    observePolicyEvaluation("js", CACHE_HIT, 0.003);
+    observePolicyEvaluation("js", CACHE_HIT, 0.2);
+    observePolicyEvaluation("js", CACHE_HIT, 30);
+
+    const lines = formatMetrics().split("\n");
+
+    assert.ok(lines.includes('safe_chain_policy_eval_seconds_bucket{ecosystem="js",cache="hit",le="0.001"} 0'));
+    assert.ok(lines.includes('safe_chain_policy_eval_seconds_bucket{ecosystem="js",cache="hit",le="0.005"} 1'));
+    assert.ok(lines.includes('safe_chain_policy_eval_seconds_bucket{ecosystem="js",cache="hit",le="0.25"} 2'));
+    assert.ok(lines.includes('safe_chain_policy_eval_seconds_bucket{ecosystem="js",cache="hit",le="10"} 2'));
+    assert.ok(lines.includes('safe_chain_policy_eval_seconds_bucket{ecosystem="js",cache="hit",le="+Inf"} 3'));
+    assert.ok(lines.includes('safe_chain_policy_eval_seconds_count{ecosystem="js",cache="hit"} 3'));
+  });
+
+  it("records a decision once", () => {
+    const finish = startPolicyEvaluation("py");
+    finish("miss");
+    finish("hit");
+
+    const metrics = formatMetrics();
+    assert.match(metrics, /_count\{ecosystem="py",cache="miss"\} 1/);
+    assert.doesNotMatch(metrics, /cache="hit"/);
+  });
+
+  it("does not time decisions when metrics are disabled", () => {
+    metricsEnabled = false;
+    initializeMetrics();
+
+    assert.strictEqual(startPolicyEvaluation("js"), undefined);
+  });
+
+  it("reports the age of the malware list once it is known", () => {
+    assert.doesNotMatch(formatMetrics(), /safe_chain_feed_age_seconds/);
+
+    feedAgeSeconds = 120;
+
+    assert.ok(
+      formatMetrics().split("\n").includes("safe_chain_feed_age_seconds 120")
+    );
+  });
+
+  it("reports whether blocking is disabled", () => {
+    assert.ok(
+      formatMetrics().split("\n").includes("safe_chain_blocking_disabled 0")
+    );
+
+    blockingDisabled = true;
+
+    assert.ok(
+      formatMetrics().split("\n").includes("safe_chain_blocking_disabled 1")
+    );
+  });
+
+  it("reports the size of the in-memory package caches", async () => {
+    const { createBoundedCache } = await import("./boundedCache.js");
+    const cache = createBoundedCache("metrics_test");
+    cache.set("lodash", new Map());
+
+    const lines = formatMetrics().split("\n");
+
+    assert.ok(
+      lines.includes('safe_chain_tracked_packages{cache="metrics_test"} 1')
+    );
+    assert.ok(
+      lines.includes(
+        'safe_chain_tracked_package_evictions_total{cache="metrics_test"} 0'
+      )
+    );
+  });
+
+  it("reports the lookups answered by the DNS cache and its size", () => {
+    const lines = formatMetrics().split("\n");
+
+    assert.ok(
+      lines.includes('safe_chain_dns_cache_lookups_total{result="hit"} 7')
+    );
+    assert.ok(
+      lines.includes(
+        'safe_chain_dns_cache_lookups_total{result="negative_hit"} 2'
+      )
+    );
+    assert.ok(
+      lines.includes('safe_chain_dns_cache_lookups_total{result="miss"} 3')
+    );
+    assert.ok(lines.includes("safe_chain_dns_cache_entries 4"));
+  });
+
+  it("answers health checks with 503 once the malware list is stale", () => {
+    /** @type {{status?: number, body?: string}} */
+    const response = {};
+    const res = /** @type {any} */ ({
+      writeHead: (/** @type {number} */ status) => (response.status = status),
+      end: (/** @type {string} */ body) => (response.body = body),
+    });
+    feedAgeSeconds = 7200;
+
+    const handled = handleHealthRequest(
+      /** @type {any} */ ({ url: "/healthz", method: "GET" }),
+      res
+    );
+
+    assert.strictEqual(handled, true);
+    assert.strictEqual(response.status, 503);
+    assert.deepStrictEqual(JSON.parse(response.body ?? ""), {
+      status: "stale",
+      feed_version: "feed-42",
+      feed_age_seconds: 7200,
+    });
+  });
+});
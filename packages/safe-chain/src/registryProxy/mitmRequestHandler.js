@@ -3,7 +3,73 @@ import { generateCertForHost } from "./certUtils.js";
 import { HttpsProxyAgent } from "https-proxy-agent";
 import { ui } from "../environment/userInteraction.js";
 import { gunzipSync } from "zlib";
-import { omitHeaders } from "./http-utils.js";
+import { omitHeaders, omitHopByHopHeaders } from "./http-utils.js";
+import { getUpstreamLookup } from "./dohResolver.js";
+import { getUpstreamBindOptions } from "./upstreamBind.js";
+import {
+  createDenylistLookup,
+  DENIED_ADDRESS_ERROR_CODE,
+  recordBlockedHostRequest,
+} from "./hostDenylist.js";
+import { isExcludedPath } from "./excludedPaths.js";
+import {
+  getBlockingDisabled,
+  getEcoSystem,
+  getInspectOnly,
+  getMaxDecompressedMb,
+  getMaxHeaderBytes,
+  getMinTlsVersion,
+  getStrictSni,
+} from "../config/settings.js";
+import {
+  applyRequestHeaderPolicy,
+  applyResponseHeaderPolicy,
+} from "./headerPolicy.js";
+import { createBodyTracer, traceBody } from "./bodyTrace.js";
+import { renderBlockMessage } from "./blockMessages.js";
+import { recordInterceptionFailure } from "./interceptionBypass.js";
+import {
+  CACHE_HIT,
+  CACHE_MISS,
+  CACHE_NONE,
+  startPolicyEvaluation,
+} from "./metrics.js";
+import { recordServerDate } from "../environment/clockSkew.js";
+import { isMethodAllowed, rejectDisallowedMethod } from "./methodPolicy.js";
+import { handleVerifyRequest, isVerifyHost } from "./verifySentinel.js";
+import { recordUpstreamResult, rejectIfCircuitOpen } from "./circuitBreaker.js";
+import {
+  parseRequestPolicyOverrides,
+  stripSafeChainHeaders,
+} from "./requestPolicyOverrides.js";
+import {
+  isClientDisconnectError,
+  recordClientDisconnect,
+} from "./clientDisconnects.js";
+import {
+  getResponseCacheKey,
+  getRevalidationHeaders,
+  isCacheableResponse,
+  isFresh,
+  openCachedResponseBody,
+  readCachedResponse,
+  recordCacheHit,
+  recordCacheMiss,
+  refreshCachedResponse,
+  writeCachedResponse,
+} from "./responseCache.js";
+import {
+  getUpstreamProxyUrl,
+  withUpstreamProxyCredentials,
+} from "./upstreamProxy.js";
+import {
+  compressBody,
+  createCompressionStream,
+  getCompressedResponseHeaders,
+  isCompressibleResponse,
+  negotiateResponseEncoding,
+} from "./responseCompression.js";
+import { handleClientError } from "./requestFraming.js";
 
 /**
  * @typedef {import("./interceptors/interceptorBuilder.js").Interceptor} Interceptor
@@ -72,13 +138,56 @@ function createHttpsServer(hostname, port, interceptor) {
       const pathAndQuery = getRequestPathAndQuery(req.url);
       const targetUrl = `https://${hostname}${pathAndQuery}`;
 
-      const requestInterceptor = await interceptor.handleRequest(targetUrl);
+      if (isVerifyHost(hostname)) {
+        await handleVerifyRequest(req, res, "https");
+        return;
+      }
+
+      if (!isMethodAllowed(req.method)) {
+        rejectDisallowedMethod(req, res, targetUrl);
+        return;
+      }
+
+      if (isExcludedPath(pathAndQuery)) {
+        forwardRequest(req, targetUrl, hostname, port, res, passThroughHandler);
+        return;
+      }
+
+      const finishPolicyEvaluation = startPolicyEvaluation(getEcoSystem());
+      const requestInterceptor = await interceptor.handleRequest(
+        targetUrl,
+        parseRequestPolicyOverrides(req.headers)
+      );
       const blockResponse = requestInterceptor.blockResponse;
 
-      if (blockResponse) {
-        ui.writeVerbose(`Safe-chain: Blocking request to ${targetUrl}`);
-        res.writeHead(blockResponse.statusCode, blockResponse.message);
-        res.end(blockResponse.message);
+      // Age checks on metadata are decided once the metadata has been fetched
+      const metadataPolicyEvaluation =
+        !blockResponse &&
+        !requestInterceptor.syntheticResponse &&
+        requestInterceptor.modifiesResponse()
+          ? finishPolicyEvaluation
+          : undefined;
+      if (!metadataPolicyEvaluation) {
+        finishPolicyEvaluation?.(CACHE_NONE);
+      }
+
+      const inspectOnly = getInspectOnly();
+      if (blockResponse && inspectOnly) {
+        ui.writeVerbose(
+          `Safe-chain: inspect.would-block - ${targetUrl}: ${blockResponse.statusCode} ${blockResponse.message}`
+        );
+      } else if (blockResponse) {
+        ui.writeVerbose(
+          blockResponse.redirectUrl
+            ? `Safe-chain: Redirecting request to ${targetUrl} to ${blockResponse.redirectUrl}`
+            : `Safe-chain: Blocking request to ${targetUrl}`
+        );
+        const { body, contentType } = renderBlockMessage(blockResponse);
+        res.writeHead(blockResponse.statusCode, blockResponse.message, {
+          ...blockResponse.headers,
+          "Content-Type": contentType,
+        });
+        res.end(body);
         return;
       }
 
@@ -91,7 +200,15 @@ function createHttpsServer(hostname, port, interceptor) {
       }
 
       // Collect request body
-      forwardRequest(req, hostname, port, res, requestInterceptor);
+      forwardRequest(
+        req,
+        targetUrl,
+        hostname,
+        port,
+        res,
+        inspectOnly ? withoutEnforcement(requestInterceptor) : requestInterceptor,
+        metadataPolicyEvaluation
+      );
     } catch (err) {
       // The 'request' listener's returned promise is not awaited by Node, so an
       // uncaught rejection here surfaces as an unhandledRejection (and can crash
@@ -108,17 +225,93 @@ function createHttpsServer(hostname, port, interceptor) {
     }
   }
 
+  const strictSni = getStrictSni();
+  let sniRejected = false;
+
   const server = https.createServer(
     {
       key: cert.privateKey,
       cert: cert.certificate,
+      maxHeaderSize: getMaxHeaderBytes(),
+      SNICallback: (servername, callback) => {
+        // Only called when the client sends an SNI. A name that differs from
+        // the CONNECT host can indicate domain fronting: the proxy would apply
+        // the policy for one host while the client talks to another.
+        if (strictSni && !isSameHost(servername, hostname)) {
+          ui.writeWarning(
+            `Safe-chain: sni.mismatch - CONNECT to ${hostname} sent SNI ${servername}, connection rejected`
+          );
+          sniRejected = true;
+          callback(new Error(`SNI ${servername} does not match ${hostname}`));
+          return;
+        }
+        // No context: use the server's certificate for the CONNECT host
+        callback(null);
+      },
     },
     handleRequest
   );
+  server.on("clientError", handleClientError);
+
+  // A client that pins the registry certificate, or speaks a protocol that
+  // cannot be intercepted, aborts the handshake with safe-chain's certificate.
+  // SNI mismatches are rejected on purpose and are not retried as a tunnel.
+  server.on("tlsClientError", (err) => {
+    if (!sniRejected) {
+      recordInterceptionFailure(hostname, err);
+    }
+  });
+
+  // Relay `100 Continue` from the registry instead of letting Node send it
+  // before the request was checked, for example for large publishes
+  server.on("checkContinue", handleRequest);
 
   return server;
 }
 
+/**
+ * Forwards a request and its response unchanged, for paths excluded from
+ * inspection.
+ *
+ * @type {import("./interceptors/interceptorBuilder.js").RequestInterceptionHandler}
+ */
+const passThroughHandler = {
+  blockResponse: undefined,
+  syntheticResponse: undefined,
+  modifyRequestHeaders: (headers) => headers,
+  modifiesResponse: () => false,
+  modifyBody: (body) => body,
+};
+
+/**
+ * In inspect-only mode the interceptor still evaluates metadata responses, so
+ * what it would suppress is recorded, but the client gets them unmodified.
+ *
+ * @param {import("./interceptors/interceptorBuilder.js").RequestInterceptionHandler} requestHandler
+ * @returns {import("./interceptors/interceptorBuilder.js").RequestInterceptionHandler}
+ */
+function withoutEnforcement(requestHandler) {
+  return {
+    ...requestHandler,
+    blockResponse: undefined,
+    modifyBody: (body, headers) => {
+      requestHandler.modifyBody(body, headers);
+      return body;
+    },
+  };
+}
+
+/**
+ * @param {string} servername
+ * @param {string} hostname
+ * @returns {boolean}
+ */
+function isSameHost(servername, hostname) {
+  /** @param {string} host */
+  const normalize = (host) => host.toLowerCase().replace(/\.$/, "");
+  return normalize(servername) === normalize(hostname);
+}
+
 /**
  * @param {string} url
  * @returns {string}
@@ -133,30 +326,184 @@ function getRequestPathAndQuery(url) {
 
 /**
  * @param {import("http").IncomingMessage} req
+ * @param {string} targetUrl
  * @param {string} hostname
  * @param {string} port
  * @param {import("http").ServerResponse} res
  * @param {import("./interceptors/interceptorBuilder.js").RequestInterceptionHandler} requestHandler
+ * @param {((cache: string) => void) | undefined} [finishPolicyEvaluation] - Records the decision once the metadata it depends on is available
  */
-function forwardRequest(req, hostname, port, res, requestHandler) {
-  const proxyReq = createProxyRequest(hostname, port, req, res, requestHandler);
+function forwardRequest(
+  req,
+  targetUrl,
+  hostname,
+  port,
+  res,
+  requestHandler,
+  finishPolicyEvaluation
+) {
+  const headers = getForwardedHeaders(req, requestHandler);
+  // A HEAD response has no body to compress
+  const acceptEncoding =
+    req.method === "HEAD" ? undefined : req.headers["accept-encoding"];
+
+  // The interceptor already approved this request, so responses served from the
+  // cache pass the same policy check as responses fetched from the registry.
+  const cacheKey = getResponseCacheKey(req.method, targetUrl, headers);
+  const cachedResponse = cacheKey ? readCachedResponse(cacheKey) : undefined;
+
+  /**
+   * @param {string} key
+   * @param {import("./responseCache.js").CachedResponse} entry
+   */
+  function serveCachedResponse(key, entry) {
+    recordCacheHit(targetUrl);
+    const body = openCachedResponseBody(key);
+    body.on("error", (err) => {
+      ui.writeError(
+        `Safe-chain: Error reading cached response for ${targetUrl}: ${err.message}`
+      );
+      if (!res.headersSent) {
+        res.writeHead(502);
+        res.end("Bad Gateway");
+      } else {
+        res.destroy();
+      }
+    });
+    writeResponse(
+      res,
+      entry.statusCode,
+      entry.headers,
+      body,
+      requestHandler,
+      targetUrl,
+      acceptEncoding
+    );
+  }
+
+  if (cacheKey && cachedResponse && isFresh(cachedResponse)) {
+    req.resume();
+    finishPolicyEvaluation?.(CACHE_HIT);
+    serveCachedResponse(cacheKey, cachedResponse);
+    return;
+  }
+
+  if (rejectIfCircuitOpen(hostname, res)) {
+    req.resume();
+    return;
+  }
+
+  if (cachedResponse) {
+    Object.assign(headers, getRevalidationHeaders(cachedResponse));
+  }
+
+  const proxyReq = createProxyRequest(
+    hostname,
+    port,
+    req,
+    res,
+    headers,
+    (proxyRes) => {
+      // createProxyRequest only invokes this callback for responses with a status code
+      const statusCode = /** @type {number} */ (proxyRes.statusCode);
+      const responseHeaders = proxyRes.headers;
+      recordServerDate(responseHeaders.date, hostname);
+      recordUpstreamResult(hostname, statusCode);
+      finishPolicyEvaluation?.(CACHE_MISS);
+
+      if (cacheKey && cachedResponse && statusCode === 304) {
+        proxyRes.resume();
+        serveCachedResponse(
+          cacheKey,
+          refreshCachedResponse(cacheKey, cachedResponse, responseHeaders)
+        );
+        return;
+      }
+
+      if (cacheKey) {
+        recordCacheMiss(targetUrl);
+        if (isCacheableResponse(statusCode, responseHeaders)) {
+          writeCachedResponse(
+            cacheKey,
+            targetUrl,
+            statusCode,
+            responseHeaders,
+            proxyRes
+          );
+        }
+      }
 
-  proxyReq.on("error", (err) => {
+      // A HEAD response has only the headers of the GET response and no body
+      // to inspect or rewrite, so it is relayed as is
+      writeResponse(
+        res,
+        statusCode,
+        responseHeaders,
+        proxyRes,
+        req.method === "HEAD" ? passThroughHandler : requestHandler,
+        targetUrl,
+        acceptEncoding
+      );
+    }
+  );
+
+  let clientDisconnected = false;
+
+  /**
+   * @param {string} [reason]
+   */
+  function handleClientDisconnect(reason) {
+    if (!clientDisconnected) {
+      clientDisconnected = true;
+      recordClientDisconnect(targetUrl, reason);
+    }
+    // Abort the upstream request so its socket is released right away
+    proxyReq.destroy();
+  }
+
+  proxyReq.on("continue", () => res.writeContinue());
+
+  proxyReq.on("error", (/** @type {NodeJS.ErrnoException} */ err) => {
+    if (clientDisconnected) {
+      // The upstream request was aborted because the client went away
+      return;
+    }
+    if (err.code === DENIED_ADDRESS_ERROR_CODE) {
+      recordBlockedHostRequest(hostname, `${targetUrl} (${err.message})`);
+      res.writeHead(403, "Forbidden");
+      res.end(`Forbidden: ${err.message}`);
+      return;
+    }
     ui.writeVerbose(
       `Safe-chain: Error occurred while proxying request to ${req.url} for ${hostname}: ${err.message}`
     );
+    recordUpstreamResult(hostname, undefined);
     res.writeHead(502);
     res.end("Bad Gateway");
   });
 
   req.on("error", (err) => {
+    if (isClientDisconnectError(err)) {
+      handleClientDisconnect(err.message);
+      return;
+    }
     ui.writeError(
       `Safe-chain: Error reading client request to ${req.url} for ${hostname}: ${err.message}`
     );
     proxyReq.destroy();
   });
 
+  res.on("close", () => {
+    if (!res.writableFinished) {
+      // Client disconnected before the response was fully sent
+      handleClientDisconnect();
+    }
+  });
+
+  const requestBodyTracer = createBodyTracer("request", targetUrl, req.headers);
+
   req.on("data", (chunk) => {
+    requestBodyTracer.add(chunk);
     proxyReq.write(chunk);
   });
 
@@ -164,20 +511,18 @@ function forwardRequest(req, hostname, port, res, requestHandler) {
     ui.writeVerbose(
       `Safe-chain: Finished proxying request to ${req.url} for ${hostname}`
     );
+    requestBodyTracer.end();
     proxyReq.end();
   });
 }
 
 /**
- * @param {string} hostname
- * @param {string} port
  * @param {import("http").IncomingMessage} req
- * @param {import("http").ServerResponse} res
  * @param {import("./interceptors/interceptorBuilder.js").RequestInterceptionHandler} requestHandler
  *
- * @returns {import("http").ClientRequest}
+ * @returns {NodeJS.Dict<string | string[]>}
  */
-function createProxyRequest(hostname, port, req, res, requestHandler) {
+function getForwardedHeaders(req, requestHandler) {
   /** @type {NodeJS.Dict<string | string[]> | undefined} */
   let headers = { ...req.headers };
   // Remove the host header from the incoming request before forwarding.
@@ -185,20 +530,53 @@ function createProxyRequest(hostname, port, req, res, requestHandler) {
   if (headers.host) {
     delete headers.host;
   }
+  // Per-request policy headers are meant for safe-chain only
+  stripSafeChainHeaders(headers);
   headers = requestHandler.modifyRequestHeaders(headers);
+  if (requestHandler.modifiesResponse()) {
+    // A partial body cannot be inspected, so the full response is requested.
+    // Servers may ignore ranges, and clients then read the 200 response.
+    headers = omitHeaders(headers, ["range", "if-range"], {
+      caseInsensitive: true,
+    });
+  }
+
+  return applyRequestHeaderPolicy({ ...omitHopByHopHeaders(headers) });
+}
 
+/**
+ * @param {string} hostname
+ * @param {string} port
+ * @param {import("http").IncomingMessage} req
+ * @param {import("http").ServerResponse} res
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @param {(proxyRes: import("http").IncomingMessage) => void} onResponse
+ *
+ * @returns {import("http").ClientRequest}
+ */
+function createProxyRequest(hostname, port, req, res, headers, onResponse) {
   /** @type {import("http").RequestOptions} */
   const options = {
     hostname: hostname,
     port: port || 443,
     path: req.url,
     method: req.method,
-    headers: { ...headers },
+    headers: headers,
+    // Applied to the re-originated connection, upstream proxies included;
+    // a registry that only offers older versions fails the handshake.
+    minVersion: getMinTlsVersion(),
   };
 
-  const httpsProxy = process.env.HTTPS_PROXY || process.env.https_proxy;
+  const httpsProxy = getUpstreamProxyUrl(hostname, port);
   if (httpsProxy) {
-    options.agent = new HttpsProxyAgent(httpsProxy);
+    // The agent sends the credentials in its CONNECT to the proxy only
+    options.agent = new HttpsProxyAgent(
+      withUpstreamProxyCredentials(httpsProxy),
+      getUpstreamBindOptions()
+    );
+  } else {
+    options.lookup = createDenylistLookup(getUpstreamLookup());
+    Object.assign(options, getUpstreamBindOptions());
   }
 
   const proxyReq = https.request(options, (proxyRes) => {
@@ -221,54 +599,221 @@ function createProxyRequest(hostname, port, req, res, requestHandler) {
       return;
     }
 
-    const { statusCode, headers } = proxyRes;
+    onResponse(proxyRes);
+  });
+
+  proxyReq.on("socket", (socket) => logNegotiatedTlsVersion(socket, hostname));
 
-    if (requestHandler.modifiesResponse()) {
-      /** @type {Array<any>} */
-      let chunks = [];
+  return proxyReq;
+}
 
-      proxyRes.on("data", (chunk) => chunks.push(chunk));
+/** @type {WeakSet<import("net").Socket>} */
+const loggedUpstreamSockets = new WeakSet();
 
-      proxyRes.on("end", () => {
-        /** @type {Buffer} */
-        const originalBuffer = Buffer.concat(chunks);
-        let decodedBuffer = originalBuffer;
+/**
+ * Logs the TLS version once per upstream connection, not for every request
+ * sent over a keep-alive connection.
+ *
+ * @param {import("net").Socket} socket
+ * @param {string} hostname
+ * @returns {void}
+ */
+function logNegotiatedTlsVersion(socket, hostname) {
+  if (loggedUpstreamSockets.has(socket)) {
+    return;
+  }
+  loggedUpstreamSockets.add(socket);
 
-        if (proxyRes.headers["content-encoding"] === "gzip") {
-          decodedBuffer = gunzipSync(originalBuffer);
-        }
+  const tlsSocket = /** @type {import("tls").TLSSocket} */ (socket);
+  const log = () =>
+    ui.writeVerbose(
+      `Safe-chain: Upstream connection to ${hostname} negotiated ${tlsSocket.getProtocol()}`
+    );
 
-        const modifiedBuffer = requestHandler.modifyBody(decodedBuffer, headers);
+  // Upstream proxy agents hand over a socket that is already connected
+  if (tlsSocket.getProtocol?.()) {
+    log();
+  } else {
+    tlsSocket.once("secureConnect", log);
+  }
+}
 
-        if (modifiedBuffer === decodedBuffer) {
-          // The interceptor left the body unchanged, so forward the upstream
-          // response verbatim. Keeping the original encoding and caching headers
-          // (etag/cache-control) intact lets npm and the registry serve it from
-          // cache on later installs instead of issuing a fresh read.
-          res.writeHead(statusCode, headers);
+/**
+ * Writes an upstream (or cached) response to the client, applying the body
+ * modifications configured by the interceptor.
+ *
+ * @param {import("http").ServerResponse} res
+ * @param {number} statusCode
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @param {import("stream").Readable} body
+ * @param {import("./interceptors/interceptorBuilder.js").RequestInterceptionHandler} requestHandler
+ * @param {string} targetUrl
+ * @param {string | undefined} acceptEncoding - The Accept-Encoding of the client request
+ *
+ * @returns {void}
+ */
+function writeResponse(
+  res,
+  statusCode,
+  headers,
+  body,
+  requestHandler,
+  targetUrl,
+  acceptEncoding
+) {
+  // X-Safe-Chain-* response headers only ever come from safe-chain's own
+  // block responses, never from the registry
+  const clientHeaders = applyResponseHeaderPolicy(
+    stripSafeChainHeaders({ ...omitHopByHopHeaders(headers) })
+  );
+  // Only complete bodies are compressed: the byte range of a 206 response
+  // refers to the uncompressed representation
+  const encoding =
+    statusCode === 200 ? negotiateResponseEncoding(acceptEncoding) : undefined;
+
+  if (requestHandler.modifiesResponse()) {
+    /** @type {Array<any>} */
+    let chunks = [];
+
+    body.on("data", (chunk) => chunks.push(chunk));
+
+    body.on("end", () => {
+      /** @type {Buffer} */
+      const originalBuffer = Buffer.concat(chunks);
+      let decodedBuffer = originalBuffer;
+
+      if (headers["content-encoding"] === "gzip") {
+        const decompressedBuffer = decompressBody(originalBuffer, targetUrl);
+        const recordOnly = getInspectOnly() || getBlockingDisabled();
+        if (!decompressedBuffer && recordOnly) {
+          // Decisions are only recorded in these modes, so a body that cannot
+          // be inspected is passed on as the registry sent it
+          res.writeHead(statusCode, clientHeaders);
           res.end(originalBuffer);
           return;
         }
+        if (!decompressedBuffer) {
+          // The body could not be inspected, so it is not forwarded either
+          res.writeHead(502);
+          res.end("Bad Gateway: Registry response could not be decompressed");
+          return;
+        }
+        decodedBuffer = decompressedBuffer;
+      }
+      traceBody("response", targetUrl, headers, decodedBuffer);
 
-        // For rewritten responses, send the final body uncompressed.
-        // This avoids mismatches between upstream compression metadata and the
-        // rewritten payload on the wire.
-        const rewrittenHeaders = omitHeaders(
-          headers,
-          ["content-length", "transfer-encoding", "content-encoding"],
-          { caseInsensitive: true }
-        ) || {};
-        rewrittenHeaders["content-length"] = String(modifiedBuffer.byteLength);
-        res.writeHead(statusCode, rewrittenHeaders);
-        res.end(modifiedBuffer);
-      });
-    } else {
-      // If the response is not being modified, we can
-      // just pipe without the need for buffering the output
-      res.writeHead(statusCode, headers);
-      proxyRes.pipe(res);
+      const modifiedBuffer = requestHandler.modifyBody(decodedBuffer, headers);
+
+      if (modifiedBuffer === decodedBuffer) {
+        // The interceptor left the body unchanged, so forward the upstream
+        // response verbatim. Keeping the original encoding and caching headers
+        // (etag/cache-control) intact lets npm and the registry serve it from
+        // cache on later installs instead of issuing a fresh read.
+        endResponse(res, statusCode, clientHeaders, originalBuffer, encoding);
+        return;
+      }
+
+      // For rewritten responses, send the final body uncompressed.
+      // This avoids mismatches between upstream compression metadata and the
+      // rewritten payload on the wire.
+      const rewrittenHeaders = omitHeaders(
+        clientHeaders,
+        ["content-length", "transfer-encoding", "content-encoding"],
+        { caseInsensitive: true }
+      ) || {};
+      rewrittenHeaders["content-length"] = String(modifiedBuffer.byteLength);
+      endResponse(res, statusCode, rewrittenHeaders, modifiedBuffer, encoding);
+    });
+  } else {
+    // If the response is not being modified, we can
+    // just pipe without the need for buffering the output
+    const contentLength = Number(clientHeaders["content-length"]);
+    if (
+      encoding &&
+      isCompressibleResponse(
+        clientHeaders,
+        Number.isNaN(contentLength) ? undefined : contentLength
+      )
+    ) {
+      res.writeHead(
+        statusCode,
+        getCompressedResponseHeaders(
+          withoutLengthHeaders(clientHeaders),
+          encoding
+        )
+      );
+      body.pipe(createCompressionStream(encoding)).pipe(res);
+      return;
     }
-  });
+    res.writeHead(statusCode, clientHeaders);
+    body.pipe(res);
+  }
+}
 
-  return proxyReq;
+/**
+ * Sends a complete body, compressed with the negotiated encoding when the
+ * client accepts one and the content is worth compressing.
+ *
+ * @param {import("http").ServerResponse} res
+ * @param {number} statusCode
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @param {Buffer} body
+ * @param {import("./responseCompression.js").ResponseEncoding | undefined} encoding
+ * @returns {void}
+ */
+function endResponse(res, statusCode, headers, body, encoding) {
+  if (!encoding || !isCompressibleResponse(headers, body.byteLength)) {
+    res.writeHead(statusCode, headers);
+    res.end(body);
+    return;
+  }
+
+  const compressedBody = compressBody(body, encoding);
+  const compressedHeaders = getCompressedResponseHeaders(
+    withoutLengthHeaders(headers),
+    encoding
+  );
+  compressedHeaders["content-length"] = String(compressedBody.byteLength);
+  res.writeHead(statusCode, compressedHeaders);
+  res.end(compressedBody);
+}
+
+/**
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @returns {NodeJS.Dict<string | string[]>}
+ */
+function withoutLengthHeaders(headers) {
+  return (
+    omitHeaders(headers, ["content-length", "transfer-encoding"], {
+      caseInsensitive: true,
+    }) || {}
+  );
+}
+
+/**
+ * Decompresses a gzip response body for inspection. The output size is capped,
+ * so a small payload that expands to gigabytes cannot exhaust the proxy's memory.
+ *
+ * @param {Buffer} buffer
+ * @param {string} targetUrl
+ * @returns {Buffer | undefined}
+ */
+function decompressBody(buffer, targetUrl) {
+  const maxDecompressedMb = getMaxDecompressedMb();
+  try {
+    return gunzipSync(buffer, {
+      maxOutputLength: Math.floor(maxDecompressedMb * 1024 * 1024),
+    });
+  } catch (/** @type {any} */ err) {
+    if (err.code === "ERR_BUFFER_TOO_LARGE") {
+      ui.writeWarning(
+        `Safe-chain: decompress.limit_exceeded - ${targetUrl} (${buffer.byteLength} compressed bytes decompress to more than ${maxDecompressedMb} MB)`
+      );
+    } else {
+      ui.writeError(
+        `Safe-chain: Failed to decompress response from ${targetUrl}: ${err.message}`
+      );
+    }
+    return undefined;
+  }
 }
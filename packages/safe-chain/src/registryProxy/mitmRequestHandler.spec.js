@@ -4,11 +4,19 @@ import zlib from "node:zlib";
 
 describe("mitmRequestHandler", async () => {
   let capturedHandler;
+  let capturedServerOptions;
   let capturedOptions;
+  let destroyedUpstreamRequests = 0;
+  let upstreamCompressedBody;
+  let upstreamHeaders;
+  let upstreamStatusCode;
+  let upstreamError;
+  const warnings = [];
 
   mock.module("https", {
     defaultExport: {
-      createServer: (_options, handler) => {
+      createServer: (options, handler) => {
+        capturedServerOptions = options;
         capturedHandler = handler;
         return {
           on: () => {},
@@ -20,28 +28,44 @@ describe("mitmRequestHandler", async () => {
 
         const listeners = {};
         const proxyRes = {
-          statusCode: 200,
-          headers: {
+          statusCode: upstreamStatusCode ?? 200,
+          headers: upstreamHeaders ?? {
             "content-encoding": "gzip",
             "content-length": "999",
             "transfer-encoding": "chunked",
+            // A registry must not be able to pose as a safe-chain block
+            "x-safe-chain-blocked": "true",
           },
           on: (event, handler) => {
             listeners[event] = handler;
           },
+          pipe: () => {},
         };
 
-        callback(proxyRes);
+        const requestListeners = {};
+        if (!upstreamError) {
+          callback(proxyRes);
+        }
 
         return {
-          on: () => {},
+          on: (event, handler) => {
+            requestListeners[event] = handler;
+          },
           write: () => {},
           end: () => {
+            if (upstreamError) {
+              requestListeners["error"]?.(upstreamError);
+              return;
+            }
             const payload = Buffer.from("rewritten body");
-            listeners["data"]?.(zlib.gzipSync(payload));
+            listeners["data"]?.(
+              upstreamCompressedBody ?? zlib.gzipSync(payload)
+            );
             listeners["end"]?.();
           },
-          destroy: () => {},
+          destroy: () => {
+            destroyedUpstreamRequests++;
+          },
         };
       },
     },
@@ -66,12 +90,16 @@ describe("mitmRequestHandler", async () => {
     namedExports: {
       ui: {
         writeVerbose: () => {},
+        writeWarning: (message) => warnings.push(message),
         writeError: () => {},
       },
     },
   });
 
   const { mitmConnect } = await import("./mitmRequestHandler.js");
+  const { initializeCliArguments } = await import("../config/cliArguments.js");
+  const { getClientDisconnectStats } = await import("./clientDisconnects.js");
+  const { DENIED_ADDRESS_ERROR_CODE } = await import("./hostDenylist.js");
 
   it("sets content-length from the final compressed payload after body rewrite", async () => {
     const interceptor = {
@@ -105,6 +133,7 @@ describe("mitmRequestHandler", async () => {
 
     const res = {
       headersSent: false,
+      on: () => {},
       writeHead: (statusCode, headers) => {
         resState.statusCode = statusCode;
         resState.headers = headers;
@@ -128,6 +157,7 @@ describe("mitmRequestHandler", async () => {
     await capturedHandler(request, res);
 
     assert.equal(capturedOptions.hostname, "pypi.org");
+    assert.equal(capturedOptions.minVersion, "TLSv1.2");
     assert.equal(resState.statusCode, 200);
     assert.equal(resState.headers["transfer-encoding"], undefined);
     assert.equal(
@@ -169,6 +199,7 @@ describe("mitmRequestHandler", async () => {
 
     const res = {
       headersSent: false,
+      on: () => {},
       writeHead: (statusCode, headers) => {
         resState.statusCode = statusCode;
         resState.headers = headers;
@@ -193,11 +224,769 @@ describe("mitmRequestHandler", async () => {
 
     // Caching/encoding headers are preserved so npm and the registry can keep
     // serving the response from cache instead of re-reading on the next install.
+    // Transfer-Encoding is hop-by-hop: the proxy frames the client connection itself.
     assert.equal(resState.statusCode, 200);
     assert.equal(resState.headers["content-encoding"], "gzip");
     assert.equal(resState.headers["content-length"], "999");
-    assert.equal(resState.headers["transfer-encoding"], "chunked");
+    assert.equal(resState.headers["transfer-encoding"], undefined);
+    assert.equal(resState.headers["x-safe-chain-blocked"], undefined);
     // The body is forwarded still-compressed, exactly as received from upstream.
     assert.deepEqual(resState.body, zlib.gzipSync(Buffer.from("rewritten body")));
   });
+
+  it("relays HEAD responses without inspecting a body", async () => {
+    let inspected = false;
+    const interceptor = {
+      handleRequest: async () => ({
+        blockResponse: undefined,
+        modifyRequestHeaders: (headers) => headers,
+        modifiesResponse: () => true,
+        modifyBody: (body) => {
+          inspected = true;
+          return body;
+        },
+      }),
+    };
+
+    mitmConnect(
+      { url: "registry.npmjs.org:443" },
+      {
+        on: () => {},
+        write: () => {},
+        headersSent: false,
+        writable: true,
+        end: () => {},
+      },
+      interceptor
+    );
+
+    const resState = { statusCode: undefined, headers: undefined };
+    const res = {
+      headersSent: false,
+      on: () => {},
+      writeHead: (statusCode, headers) => {
+        resState.statusCode = statusCode;
+        resState.headers = headers;
+      },
+      end: () => {},
+    };
+
+    // An empty gzip body cannot be decompressed, so inspecting it would fail
+    upstreamCompressedBody = Buffer.alloc(0);
+    try {
+      await capturedHandler(
+        {
+          url: "/lodash",
+          headers: {},
+          method: "HEAD",
+          on: (event, handler) => {
+            if (event === "end") {
+              handler();
+            }
+          },
+        },
+        res
+      );
+    } finally {
+      upstreamCompressedBody = undefined;
+    }
+
+    assert.equal(inspected, false);
+    assert.equal(capturedOptions.method, "HEAD");
+    assert.equal(resState.statusCode, 200);
+    assert.equal(resState.headers["content-length"], "999");
+  });
+
+  for (const { inspected, description } of [
+    {
+      inspected: true,
+      description: "requests the full body for responses it inspects",
+    },
+    {
+      inspected: false,
+      description: "forwards range headers for responses it does not inspect",
+    },
+  ]) {
+    it(description, async () => {
+      const interceptor = {
+        handleRequest: async () => ({
+          blockResponse: undefined,
+          modifyRequestHeaders: (headers) => headers,
+          modifiesResponse: () => inspected,
+          modifyBody: (body) => body,
+        }),
+      };
+
+      mitmConnect(
+        { url: "registry.npmjs.org:443" },
+        {
+          on: () => {},
+          write: () => {},
+          headersSent: false,
+          writable: true,
+          end: () => {},
+        },
+        interceptor
+      );
+
+      await capturedHandler(
+        {
+          url: "/lodash/-/lodash-4.17.21.tgz",
+          headers: { range: "bytes=1024-", "if-range": '"abc"' },
+          method: "GET",
+          on: (event, handler) => {
+            if (event === "end") {
+              handler();
+            }
+          },
+        },
+        {
+          headersSent: false,
+          on: () => {},
+          writeHead: () => {},
+          end: () => {},
+        }
+      );
+
+      assert.equal(
+        capturedOptions.headers.range,
+        inspected ? undefined : "bytes=1024-"
+      );
+      assert.equal(
+        capturedOptions.headers["if-range"],
+        inspected ? undefined : '"abc"'
+      );
+    });
+  }
+
+  it("forwards excluded paths without evaluating the policy", async () => {
+    const evaluatedUrls = [];
+    const interceptor = {
+      handleRequest: async (targetUrl) => {
+        evaluatedUrls.push(targetUrl);
+        return {
+          blockResponse: { statusCode: 403, message: "blocked", headers: {} },
+          modifyRequestHeaders: (headers) => headers,
+          modifiesResponse: () => false,
+          modifyBody: (body) => body,
+        };
+      },
+    };
+
+    mitmConnect(
+      { url: "registry.npmjs.org:443" },
+      { on: () => {}, write: () => {}, writable: true, end: () => {} },
+      interceptor
+    );
+
+    let statusCode;
+    const res = {
+      headersSent: false,
+      on: () => {},
+      writeHead: (code) => {
+        statusCode = code;
+      },
+      end: () => {},
+    };
+    const request = (url) => ({
+      url,
+      headers: {},
+      method: "GET",
+      on: (event, handler) => {
+        if (event === "end") {
+          handler();
+        }
+      },
+    });
+
+    initializeCliArguments(["--safe-chain-exclude-path=^/-/v1/search"]);
+    try {
+      await capturedHandler(request("/-/v1/search?text=react"), res);
+      assert.equal(statusCode, 200);
+      assert.equal(capturedOptions.path, "/-/v1/search?text=react");
+      assert.deepEqual(evaluatedUrls, []);
+
+      await capturedHandler(request("/react"), res);
+      assert.equal(statusCode, 403);
+      assert.deepEqual(evaluatedUrls, ["https://registry.npmjs.org/react"]);
+    } finally {
+      initializeCliArguments([]);
+    }
+  });
+
+  it("passes X-Safe-Chain-* headers to the interceptor and strips them before forwarding", async () => {
+    let receivedOverrides;
+    const interceptor = {
+      handleRequest: async (_targetUrl, policyOverrides) => {
+        receivedOverrides = policyOverrides;
+        return {
+          blockResponse: undefined,
+          modifyRequestHeaders: (headers) => headers,
+          modifiesResponse: () => true,
+          modifyBody: (body) => body,
+        };
+      },
+    };
+
+    mitmConnect(
+      { url: "registry.npmjs.org:443" },
+      {
+        on: () => {},
+        write: () => {},
+        headersSent: false,
+        writable: true,
+        end: () => {},
+      },
+      interceptor
+    );
+
+    const res = {
+      headersSent: false,
+      on: () => {},
+      writeHead: () => {},
+      end: () => {},
+    };
+
+    const request = {
+      url: "/lodash",
+      headers: {
+        accept: "application/json",
+        "x-safe-chain-minimum-package-age-hours": "500",
+      },
+      method: "GET",
+      on: (event, handler) => {
+        if (event === "end") {
+          handler();
+        }
+      },
+    };
+
+    await capturedHandler(request, res);
+
+    assert.equal(receivedOverrides.minimumPackageAgeHours, "500");
+    assert.equal(capturedOptions.headers.accept, "application/json");
+    assert.equal(
+      capturedOptions.headers["x-safe-chain-minimum-package-age-hours"],
+      undefined
+    );
+  });
+
+  it("aborts the upstream request and counts the transfer when the client disconnects mid-download", async () => {
+    const interceptor = {
+      handleRequest: async () => ({
+        blockResponse: undefined,
+        modifyRequestHeaders: (headers) => headers,
+        modifiesResponse: () => true,
+        modifyBody: (body) => body,
+      }),
+    };
+
+    mitmConnect(
+      { url: "registry.npmjs.org:443" },
+      {
+        on: () => {},
+        write: () => {},
+        headersSent: false,
+        writable: true,
+        end: () => {},
+      },
+      interceptor
+    );
+
+    const resListeners = {};
+    const res = {
+      headersSent: false,
+      writableFinished: false,
+      on: (event, handler) => {
+        resListeners[event] = handler;
+      },
+      writeHead: () => {},
+      end: () => {},
+    };
+
+    const request = {
+      url: "/lodash/-/lodash-4.17.21.tgz",
+      headers: {},
+      method: "GET",
+      on: () => {},
+    };
+
+    await capturedHandler(request, res);
+
+    const destroyedBefore = destroyedUpstreamRequests;
+    const abortedBefore = getClientDisconnectStats().abortedTransfers;
+    resListeners["close"]();
+
+    assert.equal(destroyedUpstreamRequests, destroyedBefore + 1);
+    assert.equal(getClientDisconnectStats().abortedTransfers, abortedBefore + 1);
+  });
+
+  it("rejects a gzip response that decompresses beyond the configured limit", async () => {
+    let inspected = false;
+    const interceptor = {
+      handleRequest: async () => ({
+        blockResponse: undefined,
+        modifyRequestHeaders: (headers) => headers,
+        modifiesResponse: () => true,
+        modifyBody: (body) => {
+          inspected = true;
+          return body;
+        },
+      }),
+    };
+
+    mitmConnect(
+      { url: "registry.npmjs.org:443" },
+      {
+        on: () => {},
+        write: () => {},
+        headersSent: false,
+        writable: true,
+        end: () => {},
+      },
+      interceptor
+    );
+
+    const resState = { statusCode: undefined, body: undefined };
+    const res = {
+      headersSent: false,
+      on: () => {},
+      writeHead: (statusCode) => {
+        resState.statusCode = statusCode;
+      },
+      end: (body) => {
+        resState.body = body;
+      },
+    };
+
+    // 8 MB of zeros compresses to a few kilobytes
+    upstreamCompressedBody = zlib.gzipSync(Buffer.alloc(8 * 1024 * 1024));
+    warnings.length = 0;
+    initializeCliArguments(["--safe-chain-max-decompressed-mb=1"]);
+    try {
+      await capturedHandler(
+        {
+          url: "/lodash",
+          headers: {},
+          method: "GET",
+          on: (event, handler) => {
+            if (event === "end") {
+              handler();
+            }
+          },
+        },
+        res
+      );
+    } finally {
+      upstreamCompressedBody = undefined;
+      initializeCliArguments([]);
+    }
+
+    assert.equal(inspected, false);
+    assert.equal(resState.statusCode, 502);
+    assert.ok(
+      warnings.some((message) => message.includes("decompress.limit_exceeded"))
+    );
+  });
+
+  for (const [mode, flag] of [
+    ["inspect-only", "--safe-chain-inspect-only"],
+    ["no-block", "--safe-chain-no-block"],
+  ]) {
+    it(`forwards a gzip response beyond the limit unmodified in ${mode} mode`, async () => {
+      let inspected = false;
+      mitmConnect(
+        { url: "registry.npmjs.org:443" },
+        { on: () => {}, write: () => {}, writable: true, end: () => {} },
+        {
+          handleRequest: async () => ({
+            blockResponse: undefined,
+            modifyRequestHeaders: (headers) => headers,
+            modifiesResponse: () => true,
+            modifyBody: (body) => {
+              inspected = true;
+              return body;
+            },
+          }),
+        }
+      );
+
+      const resState = { statusCode: undefined, headers: undefined };
+      const res = {
+        headersSent: false,
+        on: () => {},
+        writeHead: (statusCode, headers) => {
+          resState.statusCode = statusCode;
+          resState.headers = headers;
+        },
+        end: (body) => {
+          resState.body = body;
+        },
+      };
+
+      const compressedBody = zlib.gzipSync(Buffer.alloc(8 * 1024 * 1024));
+      upstreamCompressedBody = compressedBody;
+      warnings.length = 0;
+      initializeCliArguments(["--safe-chain-max-decompressed-mb=1", flag]);
+      try {
+        await capturedHandler(
+          {
+            url: "/lodash",
+            headers: {},
+            method: "GET",
+            on: (event, handler) => {
+              if (event === "end") {
+                handler();
+              }
+            },
+          },
+          res
+        );
+      } finally {
+        upstreamCompressedBody = undefined;
+        initializeCliArguments([]);
+      }
+
+      assert.equal(inspected, false);
+      assert.equal(resState.statusCode, 200);
+      assert.equal(resState.headers["content-encoding"], "gzip");
+      assert.deepEqual(resState.body, compressedBody);
+      assert.ok(
+        warnings.some((message) =>
+          message.includes("decompress.limit_exceeded")
+        )
+      );
+    });
+  }
+
+  /**
+   * Sends a GET through the intercepted connection with response compression
+   * on and returns what the client receives.
+   *
+   * @param {object} options
+   * @param {Record<string, string>} options.headers - The upstream response headers
+   * @param {Buffer} options.body - The upstream response body
+   * @param {(body: Buffer) => Buffer} options.modifyBody
+   * @param {number} [options.statusCode] - The upstream status code
+   */
+  async function requestWithCompression({
+    headers,
+    body,
+    modifyBody,
+    statusCode,
+  }) {
+    mitmConnect(
+      { url: "registry.npmjs.org:443" },
+      {
+        on: () => {},
+        write: () => {},
+        headersSent: false,
+        writable: true,
+        end: () => {},
+      },
+      {
+        handleRequest: async () => ({
+          blockResponse: undefined,
+          modifyRequestHeaders: (requestHeaders) => requestHeaders,
+          modifiesResponse: () => true,
+          modifyBody,
+        }),
+      }
+    );
+
+    const resState = {
+      statusCode: undefined,
+      headers: undefined,
+      body: undefined,
+    };
+    const res = {
+      headersSent: false,
+      on: () => {},
+      writeHead: (statusCode, responseHeaders) => {
+        resState.statusCode = statusCode;
+        resState.headers = responseHeaders;
+      },
+      end: (responseBody) => {
+        resState.body = responseBody;
+      },
+    };
+
+    upstreamHeaders = headers;
+    upstreamCompressedBody = body;
+    upstreamStatusCode = statusCode;
+    initializeCliArguments(["--safe-chain-compress-responses"]);
+    try {
+      await capturedHandler(
+        {
+          url: "/lodash",
+          headers: { "accept-encoding": "gzip, deflate, br" },
+          method: "GET",
+          on: (event, handler) => {
+            if (event === "end") {
+              handler();
+            }
+          },
+        },
+        res
+      );
+    } finally {
+      upstreamHeaders = undefined;
+      upstreamCompressedBody = undefined;
+      upstreamStatusCode = undefined;
+      initializeCliArguments([]);
+    }
+
+    return resState;
+  }
+
+  it("gzips a rewritten metadata response when the client accepts it", async () => {
+    const metadata = Buffer.from(
+      JSON.stringify({ name: "lodash", versions: { filler: "x".repeat(4096) } })
+    );
+
+    const response = await requestWithCompression({
+      headers: {
+        "content-type": "application/json",
+        "content-encoding": "gzip",
+        etag: '"abc"',
+      },
+      body: zlib.gzipSync(Buffer.from("{}")),
+      modifyBody: () => metadata,
+    });
+
+    assert.equal(response.statusCode, 200);
+    assert.equal(response.headers["content-encoding"], "gzip");
+    assert.equal(response.headers.vary, "Accept-Encoding");
+    assert.equal(response.headers.etag, 'W/"abc"');
+    assert.equal(
+      response.headers["content-length"],
+      String(response.body.byteLength)
+    );
+    assert.ok(response.body.byteLength < metadata.byteLength);
+    assert.deepEqual(zlib.gunzipSync(response.body), metadata);
+  });
+
+  it("does not compress tarballs", async () => {
+    const tarball = Buffer.alloc(4096, 1);
+
+    const response = await requestWithCompression({
+      headers: {
+        "content-type": "application/octet-stream",
+        "content-length": String(tarball.byteLength),
+      },
+      body: tarball,
+      modifyBody: (body) => body,
+    });
+
+    assert.equal(response.statusCode, 200);
+    assert.equal(response.headers["content-encoding"], undefined);
+    assert.deepEqual(response.body, tarball);
+  });
+
+  it("does not compress a partial response", async () => {
+    const range = Buffer.from(JSON.stringify({ filler: "x".repeat(4096) }));
+
+    const response = await requestWithCompression({
+      headers: {
+        "content-type": "application/json",
+        "content-range": `bytes 0-${range.byteLength - 1}/10000`,
+        "content-length": String(range.byteLength),
+      },
+      body: range,
+      modifyBody: (body) => body,
+      statusCode: 206,
+    });
+
+    assert.equal(response.statusCode, 206);
+    assert.equal(response.headers["content-encoding"], undefined);
+    assert.deepEqual(response.body, range);
+  });
+
+  it("binds the upstream connection to the configured local address", async () => {
+    const interceptor = {
+      handleRequest: async () => ({
+        blockResponse: undefined,
+        modifyRequestHeaders: (headers) => headers,
+        modifiesResponse: () => false,
+        modifyBody: (body) => body,
+      }),
+    };
+
+    mitmConnect(
+      { url: "registry.npmjs.org:443" },
+      { on: () => {}, write: () => {}, writable: true, end: () => {} },
+      interceptor
+    );
+
+    const res = {
+      headersSent: false,
+      on: () => {},
+      writeHead: () => {},
+      end: () => {},
+    };
+    const request = {
+      url: "/lodash",
+      headers: {},
+      method: "GET",
+      on: (event, handler) => {
+        if (event === "end") {
+          handler();
+        }
+      },
+    };
+
+    initializeCliArguments(["--safe-chain-upstream-bind=10.0.0.5"]);
+    try {
+      await capturedHandler(request, res);
+      assert.equal(capturedOptions.localAddress, "10.0.0.5");
+      assert.equal(capturedOptions.family, 4);
+    } finally {
+      initializeCliArguments([]);
+    }
+
+    await capturedHandler(request, res);
+    assert.equal(capturedOptions.localAddress, undefined);
+    assert.equal(capturedOptions.family, undefined);
+  });
+
+  it("refuses an upstream that resolves to a denied address", async () => {
+    mitmConnect(
+      { url: "localhost:443" },
+      { on: () => {}, write: () => {}, writable: true, end: () => {} },
+      {
+        handleRequest: async () => ({
+          blockResponse: undefined,
+          modifyRequestHeaders: (headers) => headers,
+          modifiesResponse: () => false,
+          modifyBody: (body) => body,
+        }),
+      }
+    );
+
+    const resState = { statusCode: undefined, body: undefined };
+    const res = {
+      headersSent: false,
+      on: () => {},
+      writeHead: (statusCode) => {
+        resState.statusCode = statusCode;
+      },
+      end: (body) => {
+        resState.body = body;
+      },
+    };
+    const request = {
+      url: "/lodash",
+      headers: {},
+      method: "GET",
+      on: (event, handler) => {
+        if (event === "end") {
+          handler();
+        }
+      },
+    };
+
+    initializeCliArguments(["--safe-chain-deny-host=127.0.0.1"]);
+    try {
+      await capturedHandler(request, res);
+      const lookupError = await new Promise((resolve) =>
+        capturedOptions.lookup("localhost", { family: 4 }, resolve)
+      );
+      assert.equal(lookupError.code, DENIED_ADDRESS_ERROR_CODE);
+
+      upstreamError = lookupError;
+      await capturedHandler(request, res);
+    } finally {
+      upstreamError = undefined;
+      initializeCliArguments([]);
+    }
+
+    assert.equal(resState.statusCode, 403);
+    assert.equal(
+      resState.body,
+      "Forbidden: localhost resolves to denied address 127.0.0.1"
+    );
+  });
+
+  it("answers the verify sentinel without contacting a registry", async () => {
+    capturedOptions = undefined;
+    mitmConnect(
+      { url: "safe-chain-verify.invalid:443" },
+      { on: () => {}, write: () => {}, writable: true, end: () => {} },
+      {
+        handleRequest: async () => {
+          throw new Error("the verify host has no packages");
+        },
+      }
+    );
+
+    const resState = { statusCode: undefined, body: undefined };
+    await capturedHandler(
+      {
+        url: "/",
+        headers: {},
+        method: "GET",
+        on: () => {},
+        resume: () => {},
+      },
+      {
+        headersSent: false,
+        on: () => {},
+        writeHead: (statusCode) => {
+          resState.statusCode = statusCode;
+        },
+        end: (body) => {
+          resState.body = body;
+        },
+      }
+    );
+
+    assert.equal(capturedOptions, undefined);
+    assert.equal(resState.statusCode, 200);
+    const payload = JSON.parse(resState.body);
+    assert.equal(payload.status, "ok");
+    assert.equal(payload.message, "OK: Safe-chain works!");
+    assert.equal(payload.transport, "https");
+    assert.match(payload.safe_chain_version, /^\d+\.\d+\.\d+/);
+    assert.equal(payload.blocking, true);
+  });
+
+  describe("strict SNI", () => {
+    const clientSocket = {
+      on: () => {},
+      write: () => {},
+      headersSent: false,
+      writable: true,
+      end: () => {},
+    };
+
+    const checkSni = (servername) =>
+      new Promise((resolve) => {
+        capturedServerOptions.SNICallback(servername, (err) => resolve(err));
+      });
+
+    it("rejects a TLS handshake whose SNI differs from the CONNECT host", async () => {
+      mitmConnect({ url: "registry.npmjs.org:443" }, clientSocket, {});
+
+      const err = await checkSni("evil.example");
+
+      assert.match(
+        err.message,
+        /evil\.example does not match registry\.npmjs\.org/
+      );
+    });
+
+    it("accepts an SNI that matches the CONNECT host", async () => {
+      mitmConnect({ url: "registry.npmjs.org:443" }, clientSocket, {});
+
+      assert.equal(await checkSni("Registry.npmjs.org."), null);
+    });
+
+    it("accepts a mismatching SNI when strict SNI is disabled", async () => {
+      initializeCliArguments(["--safe-chain-strict-sni=false"]);
+      mitmConnect({ url: "registry.npmjs.org:443" }, clientSocket, {});
+      initializeCliArguments([]);
+
+      assert.equal(await checkSni("evil.example"), null);
+    });
+  });
 });
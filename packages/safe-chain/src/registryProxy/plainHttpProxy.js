@@ -1,6 +1,39 @@
 import * as http from "http";
 import * as https from "https";
 import { ui } from "../environment/userInteraction.js";
+import { getUpstreamLookup } from "./dohResolver.js";
+import { getUpstreamBindOptions } from "./upstreamBind.js";
+import {
+  createDenylistLookup,
+  DENIED_ADDRESS_ERROR_CODE,
+  isDeniedHost,
+  recordBlockedHostRequest,
+} from "./hostDenylist.js";
+import { stripSafeChainHeaders } from "./requestPolicyOverrides.js";
+import { recordClientDisconnect } from "./clientDisconnects.js";
+import {
+  applyRequestHeaderPolicy,
+  applyResponseHeaderPolicy,
+} from "./headerPolicy.js";
+import {
+  recordUpstreamResult,
+  rejectIfCircuitOpen,
+  rejectUpgradeIfCircuitOpen,
+} from "./circuitBreaker.js";
+import { omitHopByHopHeaders } from "./http-utils.js";
+import { handleHealthRequest, handleMetricsRequest } from "./metrics.js";
+import { handleAdminRequest } from "./adminEndpoint.js";
+import {
+  isMethodAllowed,
+  rejectDisallowedMethod,
+  rejectDisallowedUpgradeMethod,
+} from "./methodPolicy.js";
+import { handleVerifyRequest, isVerifyHost } from "./verifySentinel.js";
+import { createInterceptorForUrl } from "./interceptors/createInterceptorForEcoSystem.js";
+import {
+  isBlockedUnknownHost,
+  UNKNOWN_REGISTRY_HEADERS,
+} from "./unknownHosts.js";
 
 /**
  * @param {import("http").IncomingMessage} req
@@ -10,6 +43,13 @@ import { ui } from "../environment/userInteraction.js";
  */
 export function handleHttpProxyRequest(req, res) {
   try {
+    if (
+      handleMetricsRequest(req, res) ||
+      handleHealthRequest(req, res) ||
+      handleAdminRequest(req, res)
+    ) {
+      return;
+    }
     handleRequest(req, res);
   } catch (err) {
     // This is a synchronous 'request' listener, so an uncaught throw (e.g. a
@@ -41,7 +81,39 @@ function handleRequest(req, res) {
     return;
   }
 
-  const url = new URL(req.url);
+  const url = parseAbsoluteUrl(req.url);
+  if (!url) {
+    rejectOriginFormRequest(req, res);
+    return;
+  }
+
+  if (isVerifyHost(url.hostname)) {
+    handleVerifyRequest(req, res, "http");
+    return;
+  }
+
+  if (!isMethodAllowed(req.method)) {
+    rejectDisallowedMethod(req, res, req.url);
+    return;
+  }
+
+  if (
+    isDeniedHost(url.hostname) &&
+    recordBlockedHostRequest(url.hostname, req.url)
+  ) {
+    res.writeHead(403, "Forbidden");
+    res.end(`Forbidden: ${url.hostname} is on the Safe-chain deny list`);
+    return;
+  }
+
+  if (
+    !createInterceptorForUrl(req.url) &&
+    isBlockedUnknownHost(url.hostname, req.url)
+  ) {
+    res.writeHead(403, "Forbidden", UNKNOWN_REGISTRY_HEADERS);
+    res.end(`Forbidden: ${url.hostname} is not a known registry`);
+    return;
+  }
 
   // The protocol for the plainHttpProxy should usually only be http:
   // but when the client for some reason sends an https: request directly
@@ -57,10 +129,36 @@ function handleRequest(req, res) {
     return;
   }
 
+  if (rejectIfCircuitOpen(url.host, res)) {
+    req.resume();
+    return;
+  }
+
+  let clientDisconnected = false;
+
+  /**
+   * @param {string} [reason]
+   */
+  function handleClientDisconnect(reason) {
+    if (!clientDisconnected) {
+      clientDisconnected = true;
+      recordClientDisconnect(url.href, reason);
+    }
+    // Abort the proxy request so the upstream socket is released right away
+    proxyRequest.destroy();
+  }
+
   const proxyRequest = protocol
     .request(
       req.url,
-      { method: req.method, headers: req.headers },
+      {
+        method: req.method,
+        headers: applyRequestHeaderPolicy(
+          stripSafeChainHeaders({ ...omitHopByHopHeaders(req.headers) })
+        ),
+        lookup: createDenylistLookup(getUpstreamLookup()),
+        ...getUpstreamBindOptions(),
+      },
       (proxyRes) => {
         if (!proxyRes.statusCode) {
           ui.writeError("Safe-chain: Proxy response missing status code");
@@ -68,8 +166,14 @@ function handleRequest(req, res) {
           res.end("Internal Server Error");
           return;
         }
+        recordUpstreamResult(url.host, proxyRes.statusCode);
 
-        res.writeHead(proxyRes.statusCode, proxyRes.headers);
+        res.writeHead(
+          proxyRes.statusCode,
+          applyResponseHeaderPolicy(
+            stripSafeChainHeaders({ ...omitHopByHopHeaders(proxyRes.headers) })
+          )
+        );
         proxyRes.pipe(res);
 
         proxyRes.on("error", () => {
@@ -88,7 +192,21 @@ function handleRequest(req, res) {
         });
       }
     )
-    .on("error", (err) => {
+    // The Expect header is forwarded, so the client sends the body once the
+    // upstream accepts it. A final response instead is relayed as usual.
+    .on("continue", () => res.writeContinue())
+    .on("error", (/** @type {NodeJS.ErrnoException} */ err) => {
+      if (clientDisconnected) {
+        // The upstream request was aborted because the client went away
+        return;
+      }
+      if (err.code === DENIED_ADDRESS_ERROR_CODE) {
+        recordBlockedHostRequest(url.hostname, `${req.url} (${err.message})`);
+        res.writeHead(403, "Forbidden");
+        res.end(`Forbidden: ${err.message}`);
+        return;
+      }
+      recordUpstreamResult(url.host, undefined);
       if (!res.headersSent) {
         res.writeHead(502);
         res.end(`Bad Gateway: ${err.message}`);
@@ -98,23 +216,242 @@ function handleRequest(req, res) {
       }
     });
 
-  req.on("error", () => {
+  req.on("error", (err) => {
     // Client request stream error
-    // Abort the proxy request
-    proxyRequest.destroy();
+    handleClientDisconnect(err.message);
   });
 
-  res.on("error", () => {
+  res.on("error", (err) => {
     // Client response stream error (client disconnected)
-    // Clean up proxy streams
-    proxyRequest.destroy();
+    handleClientDisconnect(err.message);
   });
 
   res.on("close", () => {
-    // Client disconnected
+    if (!res.writableFinished) {
+      // Client disconnected before the response was fully sent
+      handleClientDisconnect();
+      return;
+    }
     // Abort the proxy request to avoid unnecessary work
     proxyRequest.destroy();
   });
 
   req.pipe(proxyRequest);
 }
+
+const PROXY_DOCUMENTATION_URL =
+  "https://github.com/AikidoSec/safe-chain#proxy-port";
+
+/**
+ * @param {string} requestUrl
+ * @returns {URL | undefined} - Undefined for origin-form targets such as "/"
+ */
+function parseAbsoluteUrl(requestUrl) {
+  try {
+    return new URL(requestUrl);
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * A client that sends "GET / HTTP/1.1" to the proxy port treats it as the
+ * registry itself, usually because the proxy address ended up in a registry
+ * setting instead of HTTP_PROXY or HTTPS_PROXY. There is no target to forward
+ * to, so the client is told how to use the proxy instead.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("http").ServerResponse} res
+ * @returns {void}
+ */
+function rejectOriginFormRequest(req, res) {
+  ui.writeVerbose(
+    `Safe-chain: proxy.origin-form - rejected ${req.method} ${req.url} for host ${req.headers.host}, the client is not using the proxy as a proxy`
+  );
+  req.resume();
+  res.writeHead(400, "Bad Request", { "Content-Type": "text/plain" });
+  res.end(
+    `Bad Request: this is the safe-chain proxy, not a registry. Configure HTTP_PROXY and HTTPS_PROXY to point at it instead of requesting ${req.url} directly. See ${PROXY_DOCUMENTATION_URL}\n`
+  );
+}
+
+// Only WebSocket upgrades are tunneled. Other protocols (such as h2c) would need
+// the proxy to speak them, so they are rejected instead of being forwarded as a
+// regular request whose 101 response the proxy cannot relay.
+const SUPPORTED_UPGRADES = ["websocket"];
+
+/**
+ * Handles a non-CONNECT request with an `Upgrade` header, for example
+ * `GET ws://host/path` with `Upgrade: websocket`. Once the upstream accepts the
+ * upgrade, bytes are relayed in both directions without inspection.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("stream").Duplex} clientSocket
+ * @param {Buffer} head
+ *
+ * @returns {void}
+ */
+export function handleHttpProxyUpgrade(req, clientSocket, head) {
+  const upgrade = String(req.headers.upgrade ?? "").trim().toLowerCase();
+  if (!SUPPORTED_UPGRADES.includes(upgrade)) {
+    ui.writeVerbose(
+      `Safe-chain: Rejecting unsupported upgrade to "${upgrade}" for ${req.url}`
+    );
+    clientSocket.end(
+      `HTTP/1.1 501 Not Implemented\r\nConnection: close\r\n\r\nUpgrade to "${upgrade}" is not supported by safe-chain`
+    );
+    return;
+  }
+
+  /** @type {URL} */
+  let url;
+  try {
+    url = new URL(req.url ?? "");
+  } catch {
+    clientSocket.end(
+      "HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\nBad Request: Upgrade requests need an absolute URL"
+    );
+    return;
+  }
+
+  if (!isMethodAllowed(req.method)) {
+    rejectDisallowedUpgradeMethod(req, clientSocket, url.href);
+    return;
+  }
+
+  if (
+    isDeniedHost(url.hostname) &&
+    recordBlockedHostRequest(url.hostname, url.href)
+  ) {
+    clientSocket.end(
+      `HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\nForbidden: ${url.hostname} is on the Safe-chain deny list`
+    );
+    return;
+  }
+
+  if (
+    !createInterceptorForUrl(url.href) &&
+    isBlockedUnknownHost(url.hostname, url.href)
+  ) {
+    clientSocket.end(
+      `HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\nForbidden: ${url.hostname} is not a known registry`
+    );
+    return;
+  }
+
+  if (rejectUpgradeIfCircuitOpen(url.host, clientSocket)) {
+    return;
+  }
+
+  const protocol =
+    url.protocol === "https:" || url.protocol === "wss:" ? https : http;
+  const headers = applyRequestHeaderPolicy(
+    stripSafeChainHeaders({ ...req.headers })
+  );
+  const target = new URL(url.href);
+  target.protocol = protocol === https ? "https:" : "http:";
+
+  ui.writeVerbose(`Safe-chain: Tunneling ${upgrade} upgrade to ${url.href}`);
+
+  const proxyRequest = protocol.request(target, {
+    method: req.method,
+    headers,
+    lookup: createDenylistLookup(getUpstreamLookup()),
+    ...getUpstreamBindOptions(),
+  });
+
+  proxyRequest.on("upgrade", (proxyRes, proxySocket, proxyHead) => {
+    recordUpstreamResult(url.host, proxyRes.statusCode);
+    clientSocket.write(
+      formatResponseHead(
+        /** @type {number} */ (proxyRes.statusCode),
+        proxyRes.statusMessage,
+        proxyRes.rawHeaders
+      )
+    );
+    if (proxyHead.length > 0) {
+      clientSocket.write(proxyHead);
+    }
+    if (head.length > 0) {
+      proxySocket.write(head);
+    }
+
+    proxySocket.pipe(clientSocket);
+    clientSocket.pipe(proxySocket);
+    proxySocket.on("error", () => clientSocket.destroy());
+    clientSocket.on("error", () => proxySocket.destroy());
+    proxySocket.on("close", () => clientSocket.destroy());
+    clientSocket.on("close", () => proxySocket.destroy());
+  });
+
+  // The upstream declined the upgrade: relay its regular response and close.
+  proxyRequest.on("response", (proxyRes) => {
+    recordUpstreamResult(url.host, proxyRes.statusCode);
+    clientSocket.write(
+      formatResponseHead(
+        /** @type {number} */ (proxyRes.statusCode),
+        proxyRes.statusMessage,
+        [
+          ...omitRawHeaders(proxyRes.rawHeaders, [
+            "connection",
+            "transfer-encoding",
+          ]),
+          "Connection",
+          "close",
+        ]
+      )
+    );
+    proxyRes.pipe(clientSocket);
+  });
+
+  proxyRequest.on("error", (/** @type {NodeJS.ErrnoException} */ err) => {
+    if (err.code === DENIED_ADDRESS_ERROR_CODE) {
+      recordBlockedHostRequest(url.hostname, `${url.href} (${err.message})`);
+      clientSocket.end(
+        `HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\nForbidden: ${err.message}`
+      );
+      return;
+    }
+    ui.writeVerbose(
+      `Safe-chain: Error tunneling ${upgrade} upgrade to ${url.href}: ${err.message}`
+    );
+    recordUpstreamResult(url.host, undefined);
+    clientSocket.end(
+      `HTTP/1.1 502 Bad Gateway\r\nConnection: close\r\n\r\nBad Gateway: ${err.message}`
+    );
+  });
+
+  clientSocket.on("error", () => proxyRequest.destroy());
+
+  proxyRequest.end();
+}
+
+/**
+ * @param {number} statusCode
+ * @param {string | undefined} statusMessage
+ * @param {string[]} rawHeaders - Alternating names and values, as in IncomingMessage.rawHeaders
+ * @returns {string}
+ */
+function formatResponseHead(statusCode, statusMessage, rawHeaders) {
+  let head = `HTTP/1.1 ${statusCode} ${statusMessage ?? ""}\r\n`;
+  for (let i = 0; i + 1 < rawHeaders.length; i += 2) {
+    head += `${rawHeaders[i]}: ${rawHeaders[i + 1]}\r\n`;
+  }
+  return head + "\r\n";
+}
+
+/**
+ * @param {string[]} rawHeaders
+ * @param {string[]} names - Lowercase header names to leave out
+ * @returns {string[]}
+ */
+function omitRawHeaders(rawHeaders, names) {
+  /** @type {string[]} */
+  const result = [];
+  for (let i = 0; i + 1 < rawHeaders.length; i += 2) {
+    if (!names.includes(rawHeaders[i].toLowerCase())) {
+      result.push(rawHeaders[i], rawHeaders[i + 1]);
+    }
+  }
+  return result;
+}
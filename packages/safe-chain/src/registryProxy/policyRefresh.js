@@ -0,0 +1,58 @@
+import { getOffline, getPolicySource } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import { getFeedVersion } from "../scanning/feedVersion.js";
+
+// The agent caches the malware lists itself, so asking it often is cheap
+const POLICY_REFRESH_INTERVAL_MS = 5 * 60 * 1000;
+
+/**
+ * @type {{ timer: NodeJS.Timeout | null }}
+ */
+const state = {
+  timer: null,
+};
+
+/**
+ * Periodically loads the policy again while the proxy runs, when it comes from
+ * a local Aikido agent. A refresh that fails keeps the policy in use, so an
+ * agent that is restarted or briefly unavailable does not interrupt installs.
+ *
+ * @param {() => Promise<import("../scanning/malwareDatabase.js").MalwareDatabase>} refresh
+ * @param {number} [intervalMs]
+ * @returns {void}
+ */
+export function startPolicyRefresh(
+  refresh,
+  intervalMs = POLICY_REFRESH_INTERVAL_MS
+) {
+  const agentUrl = getPolicySource();
+  if (!agentUrl || getOffline()) {
+    return;
+  }
+
+  state.timer = setInterval(async () => {
+    const previousVersion = getFeedVersion();
+    try {
+      const database = await refresh();
+      ui.writeVerbose(
+        `Safe-chain: policy.refreshed - loaded malware list version ${database.version ?? "unknown"} with ${database.entryCount} entries from the Aikido agent at ${agentUrl}`
+      );
+    } catch (/** @type {any} */ error) {
+      ui.writeWarning(
+        `Safe-chain: policy.refresh-failed - the Aikido agent at ${agentUrl} is unavailable, keeping malware list version ${previousVersion ?? "unknown"}: ${error.message}`
+      );
+    }
+  }, intervalMs);
+  // Refreshing alone does not keep the process running
+  state.timer.unref();
+}
+
+/**
+ * @returns {void}
+ */
+export function stopPolicyRefresh() {
+  if (state.timer) {
+    clearInterval(state.timer);
+    state.timer = null;
+  }
+}
@@ -0,0 +1,41 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import { startPolicyRefresh, stopPolicyRefresh } from "./policyRefresh.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+
+describe("policyRefresh", () => {
+  afterEach(() => {
+    stopPolicyRefresh();
+    initializeCliArguments([]);
+  });
+
+  const database = { version: "v2", entryCount: 1 };
+
+  it("does not refresh unless the policy comes from an agent", async () => {
+    let refreshes = 0;
+    startPolicyRefresh(async () => {
+      refreshes++;
+      return database;
+    }, 10);
+
+    await new Promise((resolve) => setTimeout(resolve, 60));
+    assert.strictEqual(refreshes, 0);
+  });
+
+  it("keeps refreshing from the agent after a refresh failed", async () => {
+    initializeCliArguments([
+      "--safe-chain-policy-source=agent://127.0.0.1:7981",
+    ]);
+    let refreshes = 0;
+    startPolicyRefresh(async () => {
+      refreshes++;
+      if (refreshes === 1) {
+        throw new Error("connect ECONNREFUSED 127.0.0.1:7981");
+      }
+      return database;
+    }, 10);
+
+    await new Promise((resolve) => setTimeout(resolve, 80));
+    assert.ok(refreshes >= 2, `refreshed ${refreshes} times`);
+  });
+});
@@ -0,0 +1,47 @@
+import { getRedirectMap } from "../config/settings.js";
+
+/**
+ * Returns where a blocked request is redirected to when the organisation
+ * mirrors a vetted copy of the package: the path of the request on the
+ * replacement base URL configured for the package. Package names are compared
+ * as the registry does, so PyPI names are normalised per PEP 503.
+ *
+ * @param {string | undefined} packageType - For example "npm" or "pypi"
+ * @param {string | undefined} packageName
+ * @param {string} targetUrl
+ * @returns {string | undefined} - Undefined when the package is not mapped
+ */
+export function getRedirectUrl(packageType, packageName, targetUrl) {
+  if (!packageType || !packageName) {
+    return undefined;
+  }
+
+  const packageKey = normalizePackageKey(`${packageType}:${packageName}`);
+  const baseUrl = Object.entries(getRedirectMap()).find(
+    ([key]) => normalizePackageKey(key) === packageKey
+  )?.[1];
+  // A mirror that is itself inspected would be redirected to itself
+  if (!baseUrl || targetUrl.startsWith(`${baseUrl}/`)) {
+    return undefined;
+  }
+
+  try {
+    const url = new URL(targetUrl);
+    return `${baseUrl}${url.pathname}${url.search}`;
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * @param {string} packageKey - "type:name"
+ * @returns {string}
+ */
+function normalizePackageKey(packageKey) {
+  const separatorIndex = packageKey.indexOf(":");
+  const packageType = packageKey.slice(0, separatorIndex).toLowerCase();
+  const packageName = packageKey.slice(separatorIndex + 1);
+  return packageType === "pypi"
+    ? `${packageType}:${packageName.toLowerCase().replace(/[-_.]+/g, "-")}`
+    : `${packageType}:${packageName}`;
+}
@@ -0,0 +1,476 @@
+import { after, before, describe, it, mock } from "node:test";
+import assert from "node:assert";
+import net from "net";
+import tls from "tls";
+import * as http from "http";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import {
+  TEST_REGISTRY_CERTIFICATE,
+  TEST_REGISTRY_PRIVATE_KEY,
+} from "../tests/support/testRegistryCertificate.js";
+
+describe("registryProxy blocking", async () => {
+  const upstreamLookups = [];
+
+  const originalHome = process.env.HOME;
+  const homeDir = fs.mkdtempSync(
+    path.join(os.tmpdir(), "safe-chain-blocking-")
+  );
+  process.env.HOME = homeDir;
+
+  // The feed server has none of the optional lists, such as the denied hosts.
+  mock.module("make-fetch-happen", {
+    defaultExport: async () => new Response(null, { status: 404 }),
+  });
+
+  // The feed only contains the test packages. No version is returned, so
+  // nothing is written to the local malware database cache.
+  const aikido = await import("../api/aikido.js");
+  mock.module("../api/aikido.js", {
+    namedExports: {
+      ...aikido,
+      fetchMalwareDatabase: async () => ({
+        malwareDatabase: [
+          {
+            package_name: "safe-chain-test",
+            version: "0.0.1-security",
+            reason: "MALWARE",
+            advisories: ["GHSA-2xxv-wcp8-9fr8", "not an id\r\nX-Injected: 1"],
+          },
+          {
+            package_name: "@safe-chain-test/scoped",
+            version: "1.0.0",
+            reason: "MALWARE",
+          },
+          {
+            package_name: "safe-chain-pi-test",
+            version: "0.0.1",
+            reason: "MALWARE",
+          },
+        ],
+        version: undefined,
+      }),
+      fetchNewPackagesList: async () => ({
+        newPackagesList: [
+          {
+            package_name: "fresh-package",
+            version: "1.0.0",
+            released_on: Math.floor(Date.now() / 1000) - 3600,
+            scraped_on: Math.floor(Date.now() / 1000),
+          },
+        ],
+        version: undefined,
+      }),
+    },
+  });
+
+  // Blocked requests are answered by the proxy itself, without contacting the
+  // registry. Looking up a registry address would mean a request got through.
+  mock.module("./dohResolver.js", {
+    namedExports: {
+      getUpstreamLookup: () => (hostname, options, callback) => {
+        upstreamLookups.push(hostname);
+        const error = Object.assign(new Error("No upstream registry in tests"), {
+          code: "ENOTFOUND",
+        });
+        callback(error);
+      },
+    },
+  });
+
+  mock.module("./certUtils.js", {
+    namedExports: {
+      generateCertForHost: () => ({
+        privateKey: TEST_REGISTRY_PRIVATE_KEY,
+        certificate: TEST_REGISTRY_CERTIFICATE,
+      }),
+      getCaCertPath: () => "",
+    },
+  });
+
+  const { createSafeChainProxy } = await import("./registryProxy.js");
+  const { setEcoSystem, ECOSYSTEM_JS, ECOSYSTEM_PY } = await import(
+    "../config/settings.js"
+  );
+  const { initializeCliArguments } = await import("../config/cliArguments.js");
+  const { recordBlockedHostRequest } = await import("./hostDenylist.js");
+
+  let proxy;
+  let proxyPort;
+
+  before(async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+    initializeCliArguments(["--safe-chain-metrics"]);
+    proxy = createSafeChainProxy();
+    ({ port: proxyPort } = await proxy.startServer());
+  });
+
+  after(async () => {
+    await proxy.stopServer();
+    setEcoSystem(ECOSYSTEM_JS);
+    initializeCliArguments([]);
+    process.env.HOME = originalHome;
+    fs.rmSync(homeDir, { recursive: true, force: true });
+  });
+
+  it("blocks a malicious npm tarball download with a 403", async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+
+    const response = await requestThroughProxy(
+      "registry.npmjs.org",
+      "/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz"
+    );
+
+    assert.strictEqual(response.statusCode, 403);
+    assert.match(response.body, /blocked by safe-chain/);
+    assert.strictEqual(proxy.hasBlockedMaliciousPackages(), true);
+  });
+
+  it("describes the block in X-Safe-Chain-* response headers", async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+
+    const malware = await requestThroughProxy(
+      "registry.npmjs.org",
+      "/@safe-chain-test/scoped/-/scoped-1.0.0.tgz"
+    );
+    const minimumAge = await requestThroughProxy(
+      "registry.npmjs.org",
+      "/fresh-package/-/fresh-package-1.0.0.tgz"
+    );
+
+    assert.strictEqual(malware.headers["x-safe-chain-blocked"], "true");
+    assert.strictEqual(
+      malware.headers["x-safe-chain-package"],
+      "npm:@safe-chain-test/scoped@1.0.0"
+    );
+    assert.strictEqual(malware.headers["x-safe-chain-reason"], "malware");
+    assert.strictEqual(
+      minimumAge.headers["x-safe-chain-package"],
+      "npm:fresh-package@1.0.0"
+    );
+    assert.strictEqual(minimumAge.headers["x-safe-chain-reason"], "minimum-age");
+  });
+
+  it("carries the advisory IDs of the feed through to the block response", async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+
+    const response = await requestThroughProxy(
+      "registry.npmjs.org",
+      "/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz"
+    );
+
+    assert.strictEqual(response.statusCode, 403);
+    assert.strictEqual(
+      response.body,
+      "Forbidden - blocked by safe-chain (advisories: GHSA-2xxv-wcp8-9fr8)"
+    );
+  });
+
+  it("answers HEAD requests for blocked packages with the block headers and no body", async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+
+    const response = await rawRequestThroughProxy(
+      "registry.npmjs.org",
+      "HEAD /safe-chain-test/-/safe-chain-test-0.0.1-security.tgz HTTP/1.1\r\nHost: registry.npmjs.org\r\nConnection: close\r\n\r\n"
+    );
+    const [head, body] = response.split("\r\n\r\n");
+
+    assert.match(head, /^HTTP\/1\.1 403 /);
+    assert.match(head, /\r\nX-Safe-Chain-Blocked: true\r\n/);
+    assert.match(head, /\r\nX-Safe-Chain-Reason: malware\r\n/);
+    assert.strictEqual(body, "");
+  });
+
+  it("blocks a malicious scoped npm tarball download with a 403", async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+
+    const response = await requestThroughProxy(
+      "registry.npmjs.org",
+      "/@safe-chain-test/scoped/-/scoped-1.0.0.tgz"
+    );
+
+    assert.strictEqual(response.statusCode, 403);
+  });
+
+  it("blocks a malicious PyPI wheel download with a 403", async () => {
+    setEcoSystem(ECOSYSTEM_PY);
+
+    const response = await requestThroughProxy(
+      "files.pythonhosted.org",
+      "/packages/00/00/safe_chain_pi_test-0.0.1-py3-none-any.whl"
+    );
+
+    assert.strictEqual(response.statusCode, 403);
+    assert.match(response.body, /blocked by safe-chain/);
+  });
+
+  it("blocks a malicious PyPI source distribution download with a 403", async () => {
+    setEcoSystem(ECOSYSTEM_PY);
+
+    const response = await requestThroughProxy(
+      "files.pythonhosted.org",
+      "/packages/source/s/safe-chain-pi-test/safe-chain-pi-test-0.0.1.tar.gz"
+    );
+
+    assert.strictEqual(response.statusCode, 403);
+  });
+
+  it("blocks a direct download of a package newer than the minimum age", async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+
+    const response = await requestThroughProxy(
+      "registry.npmjs.org",
+      "/fresh-package/-/fresh-package-1.0.0.tgz"
+    );
+
+    assert.strictEqual(response.statusCode, 403);
+    assert.match(response.body, /direct download minimum package age/);
+    assert.strictEqual(proxy.hasBlockedMinimumAgeRequests(), true);
+  });
+
+  it("never contacts the registry for blocked requests", () => {
+    assert.deepStrictEqual(upstreamLookups, []);
+  });
+
+  it("records how long the blocking decisions took on /metrics", async () => {
+    const metrics = await new Promise((resolve, reject) => {
+      http
+        .get({ host: "127.0.0.1", port: proxyPort, path: "/metrics" }, (res) => {
+          let body = "";
+          res.setEncoding("utf8");
+          res.on("data", (chunk) => (body += chunk));
+          res.on("end", () => resolve(body));
+        })
+        .on("error", reject);
+    });
+
+    assert.match(metrics, /# TYPE safe_chain_policy_eval_seconds histogram/);
+    assert.match(
+      metrics,
+      /safe_chain_policy_eval_seconds_count\{ecosystem="js",cache="none"\} [1-9]\d*\n/
+    );
+    assert.match(
+      metrics,
+      /safe_chain_policy_eval_seconds_count\{ecosystem="py",cache="none"\} [1-9]\d*\n/
+    );
+  });
+
+  it("responds with the configured block status of the ecosystem", async () => {
+    initializeCliArguments([
+      "--safe-chain-metrics",
+      "--safe-chain-block-status=451,pypi=410",
+    ]);
+
+    try {
+      setEcoSystem(ECOSYSTEM_JS);
+      const npmResponse = await requestThroughProxy(
+        "registry.npmjs.org",
+        "/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz"
+      );
+      setEcoSystem(ECOSYSTEM_PY);
+      const pipResponse = await requestThroughProxy(
+        "files.pythonhosted.org",
+        "/packages/00/00/safe_chain_pi_test-0.0.1-py3-none-any.whl"
+      );
+
+      assert.strictEqual(npmResponse.statusCode, 451);
+      assert.match(npmResponse.body, /blocked by safe-chain/);
+      assert.strictEqual(pipResponse.statusCode, 410);
+    } finally {
+      initializeCliArguments(["--safe-chain-metrics"]);
+    }
+  });
+
+  it("forwards malicious downloads to the registry in inspect-only mode", async () => {
+    initializeCliArguments(["--safe-chain-metrics", "--safe-chain-inspect-only"]);
+
+    try {
+      setEcoSystem(ECOSYSTEM_JS);
+      const response = await requestThroughProxy(
+        "registry.npmjs.org",
+        "/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz"
+      );
+
+      // The tests have no upstream registry, so the forwarded request fails.
+      assert.notStrictEqual(response.statusCode, 403);
+      assert.strictEqual(response.headers["x-safe-chain-blocked"], undefined);
+      assert.ok(upstreamLookups.includes("registry.npmjs.org"));
+    } finally {
+      initializeCliArguments(["--safe-chain-metrics"]);
+    }
+  });
+
+  it("refuses hosts that are not known registries in strict unknown hosts mode", async () => {
+    initializeCliArguments([
+      "--safe-chain-metrics",
+      "--safe-chain-strict-unknown-hosts",
+    ]);
+
+    try {
+      setEcoSystem(ECOSYSTEM_JS);
+      const connectResponse = await new Promise((resolve, reject) => {
+        const socket = net.connect({ host: "127.0.0.1", port: proxyPort });
+        let response = "";
+        socket.setEncoding("utf8");
+        socket.on("data", (chunk) => (response += chunk));
+        socket.on("end", () => resolve(response));
+        socket.on("error", reject);
+        socket.write(
+          "CONNECT downloads.example.com:443 HTTP/1.1\r\nHost: downloads.example.com:443\r\n\r\n"
+        );
+      });
+      const plainResponse = await new Promise((resolve, reject) => {
+        http
+          .get(
+            {
+              host: "127.0.0.1",
+              port: proxyPort,
+              path: "http://downloads.example.com/payload.sh",
+              headers: { host: "downloads.example.com" },
+            },
+            (res) => {
+              res.resume();
+              resolve(res);
+            }
+          )
+          .on("error", reject);
+      });
+
+      assert.match(connectResponse, /^HTTP\/1\.1 403 Forbidden\r\n/);
+      assert.match(connectResponse, /X-Safe-Chain-Reason: unknown-registry/);
+      assert.strictEqual(plainResponse.statusCode, 403);
+      assert.strictEqual(
+        plainResponse.headers["x-safe-chain-reason"],
+        "unknown-registry"
+      );
+      assert.ok(!upstreamLookups.includes("downloads.example.com"));
+      assert.strictEqual(proxy.hasBlockedUnknownHostRequests(), true);
+    } finally {
+      initializeCliArguments(["--safe-chain-metrics"]);
+    }
+  });
+
+  it("does not write the denied host error in inspect-only mode", async (t) => {
+    initializeCliArguments(["--safe-chain-metrics", "--safe-chain-inspect-only"]);
+    const consoleError = t.mock.method(console, "error", () => {});
+    t.mock.method(console, "log", () => {});
+    t.mock.method(console, "warn", () => {});
+
+    try {
+      recordBlockedHostRequest("evil.example.com", "https://evil.example.com/");
+
+      assert.strictEqual(proxy.hasBlockedHostRequests(), true);
+      assert.deepStrictEqual(consoleError.mock.calls, []);
+
+      initializeCliArguments(["--safe-chain-metrics"]);
+      proxy.hasBlockedHostRequests();
+      assert.match(
+        String(consoleError.mock.calls[0]?.arguments[0]),
+        /A process tried to reach a host on the deny list/
+      );
+    } finally {
+      initializeCliArguments(["--safe-chain-metrics"]);
+    }
+  });
+
+  it("writes a ready line once the proxy accepts connections", async (t) => {
+    initializeCliArguments(["--safe-chain-ready-banner"]);
+    const stderrWrite = t.mock.method(process.stderr, "write", () => true);
+    const readyProxy = createSafeChainProxy();
+
+    try {
+      const { port } = await readyProxy.startServer();
+      const lines = stderrWrite.mock.calls.map((call) =>
+        String(call.arguments[0])
+      );
+
+      assert.deepStrictEqual(lines.map((line) => JSON.parse(line)), [
+        {
+          event: "ready",
+          address: `127.0.0.1:${port}`,
+          feed_entries: 3,
+          feed_version: null,
+          feed_age_seconds: 0,
+          self_test: null,
+        },
+      ]);
+    } finally {
+      stderrWrite.mock.restore();
+      await readyProxy.stopServer();
+      initializeCliArguments(["--safe-chain-metrics"]);
+    }
+  });
+
+  /**
+   * Opens a CONNECT tunnel through the proxy, completes the TLS handshake with
+   * the intercepting server and sends a GET request over it.
+   *
+   * @param {string} targetHost
+   * @param {string} path
+   * @returns {Promise<{statusCode: number | undefined, headers: import("http").IncomingHttpHeaders, body: string}>}
+   */
+  /**
+   * Sends a raw request and returns everything the proxy wrote back, so a body
+   * that a client would ignore is still visible.
+   */
+  async function rawRequestThroughProxy(targetHost, request) {
+    const tlsSocket = await connectThroughProxy(targetHost);
+    return new Promise((resolve, reject) => {
+      let response = "";
+      tlsSocket.setEncoding("utf8");
+      tlsSocket.on("data", (chunk) => (response += chunk));
+      tlsSocket.on("end", () => resolve(response));
+      tlsSocket.on("error", reject);
+      tlsSocket.write(request);
+    });
+  }
+
+  async function requestThroughProxy(targetHost, path) {
+    const tlsSocket = await connectThroughProxy(targetHost);
+
+    return new Promise((resolve, reject) => {
+      const req = http.request(
+        {
+          host: targetHost,
+          path,
+          headers: { connection: "close" },
+          createConnection: () => tlsSocket,
+        },
+        (res) => {
+          let body = "";
+          res.setEncoding("utf8");
+          res.on("data", (chunk) => (body += chunk));
+          res.on("end", () =>
+            resolve({ statusCode: res.statusCode, headers: res.headers, body })
+          );
+        }
+      );
+      req.on("error", reject);
+      req.end();
+    });
+  }
+
+  async function connectThroughProxy(targetHost) {
+    const socket = net.connect({ host: "127.0.0.1", port: proxyPort });
+    await new Promise((resolve, reject) => {
+      socket.once("error", reject);
+      socket.write(
+        `CONNECT ${targetHost}:443 HTTP/1.1\r\nHost: ${targetHost}:443\r\n\r\n`
+      );
+      socket.once("data", resolve);
+    });
+
+    const tlsSocket = tls.connect({
+      socket,
+      servername: targetHost,
+      ca: TEST_REGISTRY_CERTIFICATE,
+    });
+    await new Promise((resolve, reject) => {
+      tlsSocket.once("secureConnect", resolve);
+      tlsSocket.once("error", reject);
+    });
+    return tlsSocket;
+  }
+});
@@ -34,6 +34,7 @@ mock.module("./getConnectTimeout.js", {
 // Use dynamic import AFTER mocking to ensure mock is applied
 const { createSafeChainProxy, mergeSafeChainProxyEnvironmentVariables } =
   await import("./registryProxy.js");
+const { initializeCliArguments } = await import("../config/cliArguments.js");
 
 describe("registryProxy.connectTunnel", () => {
   let proxy, proxyHost, proxyPort;
@@ -160,6 +161,46 @@ describe("registryProxy.connectTunnel", () => {
     });
   });
 
+  describe("Deny list", () => {
+    after(() => {
+      initializeCliArguments([]);
+    });
+
+    it("should reject tunnels to denied hosts with 403 Forbidden", async () => {
+      initializeCliArguments(["--safe-chain-deny-host=*.evil.example"]);
+
+      const socket = await connectToProxy(proxyHost, proxyPort);
+      const tunnelResponse = await establishHttpsTunnel(
+        socket,
+        "c2.evil.example",
+        443
+      );
+
+      assert.ok(tunnelResponse.startsWith("HTTP/1.1 403 Forbidden"));
+      socket.destroy();
+    });
+
+    it("should reject tunnels whose hostname resolves to a denied address", async () => {
+      const target = net.createServer((connection) => connection.end());
+      await new Promise((resolve) => target.listen(0, "127.0.0.1", resolve));
+      const targetPort = target.address().port;
+      initializeCliArguments(["--safe-chain-deny-host=127.0.0.1"]);
+
+      // "localhost" passes the CONNECT check, the deny list only matches once
+      // it resolves to 127.0.0.1.
+      const socket = await connectToProxy(proxyHost, proxyPort);
+      const tunnelResponse = await establishHttpsTunnel(
+        socket,
+        "localhost",
+        targetPort
+      );
+
+      assert.ok(tunnelResponse.startsWith("HTTP/1.1 403 Forbidden"));
+      socket.destroy();
+      target.close();
+    });
+  });
+
   describe("Connection Timeout", () => {
     it("should timeout quickly when connecting to IMDS endpoint", async () => {
       // We need to make sure we're not running behind an existing safe-chain installation to allow this test to work
@@ -298,6 +339,38 @@ describe("registryProxy.connectTunnel", () => {
         process.env.HTTPS_PROXY = https_proxy;
       }
     });
+
+    it("should time out tunnels through an upstream proxy that does not answer", async () => {
+      const https_proxy = process.env.HTTPS_PROXY;
+      // A black-holed address: the connection to the upstream proxy never completes
+      process.env.HTTPS_PROXY = "http://192.0.2.3:3128";
+
+      try {
+        const socket = await connectToProxy(proxyHost, proxyPort);
+        const startTime = Date.now();
+        socket.write(
+          `CONNECT registry.example.com:443 HTTP/1.1\r\nHost: registry.example.com:443\r\n\r\n`
+        );
+
+        const responseData = await new Promise((resolve) => {
+          socket.once("data", (data) => resolve(data.toString()));
+        });
+        const duration = Date.now() - startTime;
+        socket.destroy();
+
+        assert.ok(responseData.startsWith("HTTP/1.1 504 Gateway Timeout"));
+        assert.ok(
+          duration >= 400 && duration < 5000,
+          `Expected the mocked 500ms timeout, got ${duration}ms`
+        );
+      } finally {
+        if (https_proxy) {
+          process.env.HTTPS_PROXY = https_proxy;
+        } else {
+          delete process.env.HTTPS_PROXY;
+        }
+      }
+    });
   });
 });
 
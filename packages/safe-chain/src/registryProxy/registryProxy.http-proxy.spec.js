@@ -1,14 +1,24 @@
 import { before, after, describe, it } from "node:test";
 import assert from "node:assert";
 import http from "http";
+import net from "net";
 import {
   createSafeChainProxy,
   mergeSafeChainProxyEnvironmentVariables,
 } from "./registryProxy.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+import { getClientDisconnectStats } from "./clientDisconnects.js";
+import { createMockRegistry } from "../tests/support/mockRegistry.js";
+import {
+  recordUpstreamResult,
+  resetCircuitBreakers,
+} from "./circuitBreaker.js";
 
 describe("registryProxy.httpProxy", () => {
   let proxy, proxyHost, proxyPort;
   let testHttpServer, testHttpServerPort;
+  let registry;
+  let onSlowRequest;
 
   before(async () => {
     // Start safe-chain proxy
@@ -27,6 +37,9 @@ describe("registryProxy.httpProxy", () => {
       } else if (req.url === "/echo-headers") {
         res.writeHead(200, { "Content-Type": "application/json" });
         res.end(JSON.stringify(req.headers));
+      } else if (req.url === "/echo-address") {
+        res.writeHead(200, { "Content-Type": "text/plain" });
+        res.end(req.socket.remoteAddress);
       } else if (req.url === "/echo-method") {
         res.writeHead(200, { "Content-Type": "text/plain" });
         res.end(req.method);
@@ -39,6 +52,34 @@ describe("registryProxy.httpProxy", () => {
           res.writeHead(200, { "Content-Type": "text/plain" });
           res.end(body);
         });
+      } else if (req.url === "/streamed") {
+        // Without a Content-Length, HTTP/1.1 clients get a chunked response
+        res.writeHead(200, { "Content-Type": "text/plain" });
+        res.write("hello ");
+        res.end("world");
+      } else if (req.url === "/sized") {
+        res.writeHead(200, { "Content-Type": "text/plain", "Content-Length": 5 });
+        res.end("sized");
+      } else if (req.url === "/ranged") {
+        // Honors "bytes=N-" ranges while the representation is unchanged
+        const content = "0123456789";
+        const range = /^bytes=(\d+)-$/.exec(req.headers.range ?? "");
+        const ifRange = req.headers["if-range"];
+        if (range && (!ifRange || ifRange === '"v1"')) {
+          const start = Number(range[1]);
+          res.writeHead(206, {
+            "Content-Type": "application/octet-stream",
+            "Content-Range": `bytes ${start}-${content.length - 1}/${content.length}`,
+            ETag: '"v1"',
+          });
+          res.end(content.slice(start));
+        } else {
+          res.writeHead(200, {
+            "Content-Type": "application/octet-stream",
+            ETag: '"v1"',
+          });
+          res.end(content);
+        }
       } else if (req.url === "/404") {
         res.writeHead(404, { "Content-Type": "text/plain" });
         res.end("Not Found");
@@ -48,15 +89,54 @@ describe("registryProxy.httpProxy", () => {
       }
     });
 
+    // Rejects uploads to /too-large before the client sends the body, and
+    // accepts all others with an interim 100 Continue
+    testHttpServer.on("checkContinue", (req, res) => {
+      if (req.url === "/too-large") {
+        res.writeHead(413, { "Content-Type": "text/plain" });
+        res.end("Payload Too Large");
+        return;
+      }
+      res.writeContinue();
+      testHttpServer.emit("request", req, res);
+    });
+
+    // Accepts WebSocket upgrades on /ws and echoes every byte it receives
+    testHttpServer.on("upgrade", (req, socket) => {
+      if (req.url !== "/ws") {
+        socket.end("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        return;
+      }
+      socket.write(
+        "HTTP/1.1 101 Switching Protocols\r\n" +
+          "Upgrade: websocket\r\n" +
+          "Connection: Upgrade\r\n" +
+          "Sec-WebSocket-Accept: test-accept\r\n\r\n"
+      );
+      socket.pipe(socket);
+    });
+
     testHttpServerPort = await new Promise((resolve) => {
       testHttpServer.listen(0, () => {
         resolve(testHttpServer.address().port);
       });
     });
+
+    // Holds back /slow, so the proxy is still waiting when the client leaves
+    registry = await createMockRegistry()
+      .withResponse("/slow", (registryUrl, req) => {
+        onSlowRequest?.(req);
+        return { body: "too late" };
+      })
+      .withDelay("/slow", 60_000)
+      .withRedirect("/moved", "/lodash", 301)
+      .withNpmPackage("lodash", ["4.17.21"])
+      .start();
   });
 
   after(async () => {
     await proxy.stopServer();
+    await registry.stop();
     await new Promise((resolve) => {
       testHttpServer.close(() => resolve());
       setTimeout(resolve, 1000);
@@ -89,6 +169,56 @@ describe("registryProxy.httpProxy", () => {
     assert.strictEqual(response.body, postData);
   });
 
+  it("should relay 100 Continue before the client sends the body", async () => {
+    const response = await makeExpectContinueRequest(
+      proxyHost,
+      proxyPort,
+      `http://localhost:${testHttpServerPort}/post-echo`,
+      "x".repeat(64 * 1024)
+    );
+
+    assert.strictEqual(response.continued, true);
+    assert.strictEqual(response.statusCode, 200);
+    assert.strictEqual(response.body.length, 64 * 1024);
+  });
+
+  it("should relay a rejection of an Expect: 100-continue upload", async () => {
+    const response = await makeExpectContinueRequest(
+      proxyHost,
+      proxyPort,
+      `http://localhost:${testHttpServerPort}/too-large`,
+      "x".repeat(64 * 1024)
+    );
+
+    assert.strictEqual(response.continued, false);
+    assert.strictEqual(response.statusCode, 413);
+  });
+
+  it("should abort the upstream request when the client disconnects mid-fetch", async () => {
+    const upstreamRequest = new Promise((resolve) => {
+      onSlowRequest = resolve;
+    });
+    const abortedBefore = getClientDisconnectStats().abortedTransfers;
+    const { host } = new URL(registry.url);
+
+    const client = net.connect({ host: proxyHost, port: proxyPort });
+    client.on("error", () => {});
+    client.write(
+      `GET ${registry.url}/slow HTTP/1.1\r\nHost: ${host}\r\n\r\n`
+    );
+    const req = await upstreamRequest;
+    const upstreamClosed = new Promise((resolve) =>
+      req.socket.on("close", resolve)
+    );
+    client.destroy();
+
+    await upstreamClosed;
+    assert.strictEqual(
+      getClientDisconnectStats().abortedTransfers,
+      abortedBefore + 1
+    );
+  });
+
   it("should preserve request headers", async () => {
     const response = await makeHttpProxyRequest(
       proxyHost,
@@ -124,6 +254,46 @@ describe("registryProxy.httpProxy", () => {
     }
   });
 
+  it("should relay partial content for range requests", async () => {
+    const targetUrl = `http://localhost:${testHttpServerPort}/ranged`;
+
+    const resumed = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      targetUrl,
+      "GET",
+      null,
+      { Range: "bytes=6-", "If-Range": '"v1"' }
+    );
+    const changed = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      targetUrl,
+      "GET",
+      null,
+      { Range: "bytes=6-", "If-Range": '"v0"' }
+    );
+
+    assert.strictEqual(resumed.statusCode, 206);
+    assert.strictEqual(resumed.headers["content-range"], "bytes 6-9/10");
+    assert.strictEqual(resumed.body, "6789");
+    assert.strictEqual(changed.statusCode, 200);
+    assert.strictEqual(changed.body, "0123456789");
+  });
+
+  it("should relay redirects to the client without following them", async () => {
+    const response = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      `${registry.url}/moved`,
+      "GET"
+    );
+
+    assert.strictEqual(response.statusCode, 301);
+    assert.strictEqual(response.headers.location, `${registry.url}/lodash`);
+    assert.ok(!registry.requests.some((request) => request.url === "/lodash"));
+  });
+
   it("should forward 404 responses correctly", async () => {
     const response = await makeHttpProxyRequest(
       proxyHost,
@@ -163,6 +333,111 @@ describe("registryProxy.httpProxy", () => {
     assert.ok(response.body.includes("lodash"));
   });
 
+  it("should reject requests to denied hosts with 403", async () => {
+    initializeCliArguments(["--safe-chain-deny-host=localhost"]);
+
+    try {
+      const response = await makeHttpProxyRequest(
+        proxyHost,
+        proxyPort,
+        `http://localhost:${testHttpServerPort}/test`,
+        "GET"
+      );
+
+      assert.strictEqual(response.statusCode, 403);
+      assert.ok(response.body.includes("deny list"));
+    } finally {
+      initializeCliArguments([]);
+    }
+  });
+
+  it("should reject requests to hosts that resolve to a denied address with 403", async () => {
+    // A fresh upstream, as kept-alive sockets to it were resolved already
+    const upstream = http.createServer((req, res) => res.end("reached"));
+    await new Promise((resolve) => upstream.listen(0, resolve));
+    initializeCliArguments(["--safe-chain-deny-host=127.0.0.1"]);
+
+    try {
+      const response = await makeHttpProxyRequest(
+        proxyHost,
+        proxyPort,
+        `http://localhost:${upstream.address().port}/test`,
+        "GET"
+      );
+
+      assert.strictEqual(response.statusCode, 403);
+      assert.ok(response.body.includes("resolves to denied address"));
+    } finally {
+      initializeCliArguments([]);
+      upstream.close();
+    }
+  });
+
+  it("should answer the verify sentinel over plain HTTP", async () => {
+    const response = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      "http://safe-chain-verify.invalid/",
+      "GET"
+    );
+
+    assert.strictEqual(response.statusCode, 200);
+    const payload = JSON.parse(response.body);
+    assert.strictEqual(payload.status, "ok");
+    assert.strictEqual(payload.transport, "http");
+  });
+
+  it("should reject methods that are not allowed with 405", async () => {
+    initializeCliArguments(["--safe-chain-allow-methods=GET,HEAD"]);
+
+    try {
+      const targetUrl = `http://localhost:${testHttpServerPort}/test`;
+      const rejected = await makeHttpProxyRequest(
+        proxyHost,
+        proxyPort,
+        targetUrl,
+        "POST",
+        "payload"
+      );
+      const allowed = await makeHttpProxyRequest(
+        proxyHost,
+        proxyPort,
+        targetUrl,
+        "GET"
+      );
+
+      assert.strictEqual(rejected.statusCode, 405);
+      assert.strictEqual(rejected.headers.allow, "GET, HEAD");
+      assert.strictEqual(allowed.statusCode, 200);
+      assert.strictEqual(allowed.body, "HTTP test response");
+    } finally {
+      initializeCliArguments([]);
+    }
+  });
+
+  it(
+    "should connect upstream from the configured local address",
+    // Only Linux routes all of 127.0.0.0/8 to the loopback interface
+    { skip: process.platform !== "linux" },
+    async () => {
+      initializeCliArguments(["--safe-chain-upstream-bind=127.0.0.2"]);
+
+      try {
+        const response = await makeHttpProxyRequest(
+          proxyHost,
+          proxyPort,
+          `http://127.0.0.1:${testHttpServerPort}/echo-address`,
+          "GET"
+        );
+
+        assert.strictEqual(response.statusCode, 200);
+        assert.strictEqual(response.body.replace(/^::ffff:/, ""), "127.0.0.2");
+      } finally {
+        initializeCliArguments([]);
+      }
+    }
+  );
+
   it("should handle unsupported protocols with 502", async () => {
     const response = await makeHttpProxyRequest(
       proxyHost,
@@ -174,6 +449,299 @@ describe("registryProxy.httpProxy", () => {
     assert.strictEqual(response.statusCode, 502);
     assert.ok(response.body.includes("Unsupported protocol"));
   });
+
+  it("should explain how to use the proxy when it receives an origin-form request", async () => {
+    const { response } = await sendRawRequest(
+      proxyHost,
+      proxyPort,
+      `GET / HTTP/1.1\r\nHost: ${proxyHost}:${proxyPort}\r\n\r\n`
+    );
+
+    const [head, body] = response.split("\r\n\r\n");
+    assert.match(head, /^HTTP\/1\.1 400 Bad Request/);
+    assert.match(body, /this is the safe-chain proxy/);
+    assert.match(body, /HTTP_PROXY and HTTPS_PROXY/);
+    assert.match(body, /github\.com\/AikidoSec\/safe-chain#proxy-port/);
+  });
+
+  it("should answer HTTP/1.0 requests without chunked encoding and close the connection", async () => {
+    const { response, closed } = await sendRawRequest(
+      proxyHost,
+      proxyPort,
+      `GET http://localhost:${testHttpServerPort}/streamed HTTP/1.0\r\n` +
+        `Host: localhost:${testHttpServerPort}\r\n\r\n`
+    );
+
+    const [head, body] = response.split("\r\n\r\n");
+    assert.doesNotMatch(head, /transfer-encoding/i);
+    assert.doesNotMatch(head, /keep-alive/i);
+    assert.strictEqual(body, "hello world");
+    assert.strictEqual(closed, true);
+  });
+
+  it("should keep an HTTP/1.0 keep-alive connection open when the length is known", async () => {
+    const { response, closed } = await sendRawRequest(
+      proxyHost,
+      proxyPort,
+      `GET http://localhost:${testHttpServerPort}/sized HTTP/1.0\r\n` +
+        `Host: localhost:${testHttpServerPort}\r\n` +
+        "Connection: keep-alive\r\n\r\n"
+    );
+
+    assert.match(response, /\r\ncontent-length: 5\r\n/i);
+    assert.match(response, /\r\nconnection: keep-alive\r\n/i);
+    assert.ok(response.endsWith("\r\n\r\nsized"));
+    assert.strictEqual(closed, false);
+  });
+
+  it("should not forward hop-by-hop request headers", async () => {
+    const response = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      `http://localhost:${testHttpServerPort}/echo-headers`,
+      "GET",
+      null,
+      {
+        Connection: "close, X-Hop",
+        "X-Hop": "1",
+        "Proxy-Connection": "keep-alive",
+        "X-End-To-End": "1",
+      }
+    );
+
+    const forwardedHeaders = JSON.parse(response.body);
+    assert.strictEqual(forwardedHeaders["x-hop"], undefined);
+    assert.strictEqual(forwardedHeaders["proxy-connection"], undefined);
+    assert.strictEqual(forwardedHeaders["x-end-to-end"], "1");
+  });
+
+  for (const { vector, framing } of [
+    {
+      vector: "CL.TE",
+      framing:
+        "Content-Length: 6\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+    },
+    {
+      vector: "TE.CL",
+      framing:
+        "Transfer-Encoding: chunked\r\nContent-Length: 3\r\n\r\n0\r\n\r\n",
+    },
+    {
+      vector: "duplicate Content-Length",
+      framing: "Content-Length: 0\r\nContent-Length: 5\r\n\r\n",
+    },
+    {
+      vector: "obfuscated Transfer-Encoding",
+      framing:
+        "Transfer-Encoding: chunked\r\nTransfer-Encoding: identity\r\n\r\n0\r\n\r\n",
+    },
+  ]) {
+    it(`should reject ${vector} request smuggling with 400`, async () => {
+      const smuggledRequest =
+        `GET http://localhost:${testHttpServerPort}/404 HTTP/1.1\r\n` +
+        `Host: localhost:${testHttpServerPort}\r\n\r\n`;
+
+      const { response } = await sendRawRequest(
+        proxyHost,
+        proxyPort,
+        `POST http://localhost:${testHttpServerPort}/post-echo HTTP/1.1\r\n` +
+          `Host: localhost:${testHttpServerPort}\r\n` +
+          framing +
+          smuggledRequest
+      );
+
+      assert.match(response, /^HTTP\/1\.1 400 Bad Request/);
+      assert.doesNotMatch(response, /Not Found/);
+      assert.strictEqual(response.match(/HTTP\/1\.1 /g)?.length, 1);
+    });
+  }
+
+  it("should frame forwarded request bodies itself", async () => {
+    // Written in chunks, so the client sends Transfer-Encoding: chunked
+    const response = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      `http://localhost:${testHttpServerPort}/echo-headers`,
+      "POST",
+      "hello"
+    );
+
+    const forwardedHeaders = JSON.parse(response.body);
+    assert.strictEqual(forwardedHeaders["transfer-encoding"], "chunked");
+    assert.strictEqual(forwardedHeaders["content-length"], undefined);
+  });
+
+  it("should tunnel WebSocket upgrades to the upstream", async () => {
+    const req = http.request({
+      hostname: proxyHost,
+      port: proxyPort,
+      path: `http://localhost:${testHttpServerPort}/ws`,
+      headers: {
+        Host: `localhost:${testHttpServerPort}`,
+        Connection: "Upgrade",
+        Upgrade: "websocket",
+        "Sec-WebSocket-Key": "dGhlIHNhbXBsZSBub25jZQ==",
+        "Sec-WebSocket-Version": "13",
+      },
+    });
+
+    const { res, socket } = await new Promise((resolve, reject) => {
+      req.on("upgrade", (res, socket) => resolve({ res, socket }));
+      req.on("response", (res) =>
+        reject(new Error(`Expected an upgrade, got ${res.statusCode}`))
+      );
+      req.on("error", reject);
+      req.end();
+    });
+
+    assert.strictEqual(res.statusCode, 101);
+    assert.strictEqual(res.headers["sec-websocket-accept"], "test-accept");
+
+    const echoed = await new Promise((resolve) => {
+      socket.once("data", (data) => resolve(data.toString()));
+      socket.write("ping");
+    });
+    socket.destroy();
+
+    assert.strictEqual(echoed, "ping");
+  });
+
+  it("should reject WebSocket upgrades to hosts that resolve to a denied address", async () => {
+    initializeCliArguments(["--safe-chain-deny-host=127.0.0.1"]);
+
+    try {
+      const response = await makeHttpProxyRequest(
+        proxyHost,
+        proxyPort,
+        `http://localhost:${testHttpServerPort}/ws`,
+        "GET",
+        null,
+        { Connection: "Upgrade", Upgrade: "websocket" }
+      );
+
+      assert.strictEqual(response.statusCode, 403);
+      assert.ok(response.body.includes("resolves to denied address"));
+    } finally {
+      initializeCliArguments([]);
+    }
+  });
+
+  it("should relay the upstream response when it declines a WebSocket upgrade", async () => {
+    const response = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      `http://localhost:${testHttpServerPort}/not-a-socket`,
+      "GET",
+      null,
+      { Connection: "Upgrade", Upgrade: "websocket" }
+    );
+
+    assert.strictEqual(response.statusCode, 404);
+  });
+
+  it("should reject WebSocket upgrades with methods that are not allowed with 405", async () => {
+    initializeCliArguments(["--safe-chain-deny-methods=GET"]);
+
+    try {
+      const response = await makeHttpProxyRequest(
+        proxyHost,
+        proxyPort,
+        `http://localhost:${testHttpServerPort}/ws`,
+        "GET",
+        null,
+        { Connection: "Upgrade", Upgrade: "websocket" }
+      );
+
+      assert.strictEqual(response.statusCode, 405);
+      assert.ok(!response.headers.allow.includes("GET"));
+    } finally {
+      initializeCliArguments([]);
+    }
+  });
+
+  it("should short-circuit WebSocket upgrades to a failing host with 503", async () => {
+    const host = `localhost:${testHttpServerPort}`;
+    for (let i = 0; i < 5; i++) {
+      recordUpstreamResult(host, 502);
+    }
+
+    try {
+      const response = await makeHttpProxyRequest(
+        proxyHost,
+        proxyPort,
+        `http://${host}/ws`,
+        "GET",
+        null,
+        { Connection: "Upgrade", Upgrade: "websocket" }
+      );
+
+      assert.strictEqual(response.statusCode, 503);
+      assert.ok(response.headers["retry-after"]);
+    } finally {
+      resetCircuitBreakers();
+    }
+  });
+
+  it("should reject upgrades to protocols other than WebSocket with 501", async () => {
+    const response = await makeHttpProxyRequest(
+      proxyHost,
+      proxyPort,
+      `http://localhost:${testHttpServerPort}/test`,
+      "GET",
+      null,
+      { Connection: "Upgrade", Upgrade: "h2c" }
+    );
+
+    assert.strictEqual(response.statusCode, 501);
+    assert.ok(response.body.includes("not supported"));
+  });
+});
+
+describe("registryProxy.maxHeaderBytes", () => {
+  let proxy, proxyPort;
+
+  before(async () => {
+    initializeCliArguments(["--safe-chain-max-header-bytes=1024"]);
+    proxy = createSafeChainProxy();
+    ({ port: proxyPort } = await proxy.startServer());
+  });
+
+  after(async () => {
+    await proxy.stopServer();
+    initializeCliArguments([]);
+  });
+
+  it("should answer oversized request headers with 431", async () => {
+    const response = await new Promise((resolve, reject) => {
+      const socket = net.connect(proxyPort, "127.0.0.1", () => {
+        socket.write(
+          "GET http://localhost/test HTTP/1.1\r\n" +
+            "Host: localhost\r\n" +
+            `X-Large: ${"a".repeat(4096)}\r\n\r\n`
+        );
+      });
+      let data = "";
+      socket.on("data", (chunk) => (data += chunk.toString()));
+      socket.on("end", () => resolve(data));
+      socket.on("error", reject);
+    });
+
+    assert.match(response, /^HTTP\/1\.1 431 Request Header Fields Too Large/);
+  });
+
+  it("should accept requests within the limit", async () => {
+    const response = await makeHttpProxyRequest(
+      "127.0.0.1",
+      proxyPort,
+      "ftp://example.com/file.txt",
+      "GET",
+      null,
+      { "X-Small": "a".repeat(100) }
+    );
+
+    // Reaches the request handler, which rejects the protocol
+    assert.strictEqual(response.statusCode, 502);
+  });
 });
 
 function makeHttpProxyRequest(
@@ -223,3 +791,67 @@ function makeHttpProxyRequest(
     req.end();
   });
 }
+
+/**
+ * Writes a raw request and collects the response until the proxy closes the
+ * connection or stays idle for a moment.
+ *
+ * @returns {Promise<{response: string, closed: boolean}>}
+ */
+/**
+ * Sends a PUT with `Expect: 100-continue` and only writes the body once the
+ * proxy relays `100 Continue`, like npm and twine do for large uploads.
+ */
+function makeExpectContinueRequest(proxyHost, proxyPort, targetUrl, body) {
+  return new Promise((resolve, reject) => {
+    let continued = false;
+    const req = http.request({
+      hostname: proxyHost,
+      port: proxyPort,
+      path: targetUrl,
+      method: "PUT",
+      headers: {
+        Host: new URL(targetUrl).host,
+        Expect: "100-continue",
+        "Content-Length": Buffer.byteLength(body),
+      },
+    });
+
+    req.on("continue", () => {
+      continued = true;
+      req.end(body);
+    });
+    req.on("response", (res) => {
+      let responseBody = "";
+      res.on("data", (chunk) => (responseBody += chunk));
+      res.on("end", () =>
+        resolve({ continued, statusCode: res.statusCode, body: responseBody })
+      );
+    });
+    req.on("error", reject);
+    req.flushHeaders();
+  });
+}
+
+function sendRawRequest(proxyHost, proxyPort, rawRequest) {
+  return new Promise((resolve, reject) => {
+    const socket = net.connect({ host: proxyHost, port: proxyPort });
+    let response = "";
+    let idleTimer;
+    const finish = (closed) => {
+      clearTimeout(idleTimer);
+      socket.destroy();
+      resolve({ response, closed });
+    };
+
+    socket.setEncoding("utf8");
+    socket.on("data", (chunk) => {
+      response += chunk;
+      clearTimeout(idleTimer);
+      idleTimer = setTimeout(() => finish(false), 300);
+    });
+    socket.on("end", () => finish(true));
+    socket.on("error", reject);
+    socket.write(rawRequest);
+  });
+}
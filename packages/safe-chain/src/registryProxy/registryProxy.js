@@ -1,25 +1,115 @@
 import * as http from "http";
+import fs from "fs";
 import { tunnelRequest } from "./tunnelRequestHandler.js";
 import { mitmConnect } from "./mitmRequestHandler.js";
-import { handleHttpProxyRequest } from "./plainHttpProxy.js";
+import {
+  handleHttpProxyRequest,
+  handleHttpProxyUpgrade,
+} from "./plainHttpProxy.js";
 import { getCombinedCaBundlePath, cleanupCertBundle } from "./certBundle.js";
 import { ui } from "../environment/userInteraction.js";
 import chalk from "chalk";
-import { createInterceptorForUrl } from "./interceptors/createInterceptorForEcoSystem.js";
+import * as semver from "semver";
+import { shouldBypassInterception } from "./interceptionBypass.js";
+import { initializeMetrics } from "./metrics.js";
+import { initializeBoundedCaches } from "./boundedCache.js";
+import {
+  createInterceptorForUrl,
+  createInterceptorForUserAgentHint,
+} from "./interceptors/createInterceptorForEcoSystem.js";
 import { getHasSuppressedVersions } from "./interceptors/suppressedVersionsState.js";
+import {
+  getBlockedHostRequests,
+  isDeniedHost,
+  loadDeniedHostsFromFeed,
+  recordBlockedHostRequest,
+} from "./hostDenylist.js";
+import { getResponseCacheStats } from "./responseCache.js";
+import {
+  getBlockedUnknownHostRequests,
+  isBlockedUnknownHost,
+  UNKNOWN_REGISTRY_HEADERS,
+} from "./unknownHosts.js";
+import { setFlaggedMaintainers } from "./interceptors/flaggedMaintainers.js";
+import { fetchFlaggedMaintainers } from "../api/aikido.js";
+import { loadFeedList } from "./feedLists.js";
+import { getClientDisconnectStats } from "./clientDisconnects.js";
+import {
+  openMalwareDatabase,
+  refreshMalwareDatabase,
+} from "../scanning/malwareDatabase.js";
+import { getFeedAgeSeconds } from "../scanning/feedFreshness.js";
+import { runSelfTest } from "./selfTest.js";
+import { startWrappedGuard, stopWrappedGuard } from "./wrappedGuard.js";
+import { startPolicyRefresh, stopPolicyRefresh } from "./policyRefresh.js";
+import { isVerifyHost, verifyInterceptor } from "./verifySentinel.js";
+import { logUpstreamProxyConfiguration } from "./upstreamProxy.js";
+import {
+  closeEventSocket,
+  emitDecisionEvent,
+  openEventSocket,
+} from "./eventSocket.js";
+import {
+  BLOCK_REASON_INSTALL_SCRIPT,
+  BLOCK_REASON_MALWARE,
+  BLOCK_REASON_MINIMUM_AGE,
+  BLOCK_REASON_PRERELEASE,
+  BLOCK_REASON_SIZE_ANOMALY,
+  BLOCK_REASON_VCS_DEPENDENCY,
+} from "./interceptors/interceptorBuilder.js";
+import {
+  getBlockingDisabled,
+  getInspectOnly,
+  getMaxHeaderBytes,
+  getMaxLifetimeSecs,
+  getMaxTrackedPackages,
+  getEcoSystem,
+  getPortFile,
+  getPortRetry,
+  getProxyPort,
+  getReadyBanner,
+  getReusePort,
+  getSelfTest,
+  getTcpBacklog,
+  getTcpNoDelay,
+  getVcsPolicy,
+  VCS_POLICY_ALLOW,
+} from "../config/settings.js";
+import {
+  EXIT_CODE_PROXY_BIND_FAILURE,
+  SafeChainStartupError,
+} from "../utils/exitCodes.js";
+import { handleClientError } from "./requestFraming.js";
 
 const SERVER_STOP_TIMEOUT_MS = 1000;
+const PORT_RETRY_ATTEMPTS = 3;
+const PORT_RETRY_DELAY_MS = 250;
+const REUSE_PORT_PLATFORMS = ["linux", "freebsd"];
 /**
  * @type {{
  *   port: number | null,
- *   blockedRequests: {packageName: string, version: string, url: string}[],
- *   blockedMinimumAgeRequests: {packageName: string, version: string, url: string}[]
+ *   portFile: string | null,
+ *   blockedRequests: {packageName: string, version: string, url: string, advisories: string[]}[],
+ *   blockedMinimumAgeRequests: {packageName: string, version: string, url: string}[],
+ *   blockedPrereleaseRequests: {packageName: string, version: string, url: string}[],
+ *   blockedInstallScriptRequests: {packageName: string, version: string, scripts: string, url: string}[],
+ *   blockedSizeAnomalyRequests: {packageName: string, version: string, description: string, url: string}[],
+ *   blockedVcsDependencyRequests: {repository: string, ref: string | undefined, url: string}[],
+ *   lifetimeTimer: NodeJS.Timeout | null,
+ *   onLifetimeExpired: (() => void) | null
  * }}
  */
 const state = {
   port: null,
+  portFile: null,
   blockedRequests: [],
   blockedMinimumAgeRequests: [],
+  blockedPrereleaseRequests: [],
+  blockedInstallScriptRequests: [],
+  blockedSizeAnomalyRequests: [],
+  blockedVcsDependencyRequests: [],
+  lifetimeTimer: null,
+  onLifetimeExpired: null,
 };
 
 export function createSafeChainProxy() {
@@ -28,9 +118,21 @@ export function createSafeChainProxy() {
   return {
     startServer: () => startServer(server),
     stopServer: () => stopServer(server),
+    onLifetimeExpired: (/** @type {() => void} */ callback) => {
+      state.onLifetimeExpired = callback;
+    },
     hasBlockedMaliciousPackages,
     hasBlockedMinimumAgeRequests,
+    hasBlockedPrereleaseRequests,
+    hasBlockedInstallScriptRequests,
+    hasBlockedSizeAnomalyRequests,
+    hasBlockedVcsDependencyRequests,
+    hasBlockedHostRequests,
+    hasBlockedUnknownHostRequests,
     hasSuppressedVersions: getHasSuppressedVersions,
+    isBlockingDisabled: getBlockingDisabled,
+    getResponseCacheStats,
+    getClientDisconnectStats,
   };
 }
 
@@ -45,13 +147,28 @@ function getSafeChainProxyEnvironmentVariables() {
   const proxyUrl = `http://127.0.0.1:${state.port}`;
   const caCertPath = getCombinedCaBundlePath();
 
-  return {
+  /** @type {Record<string, string>} */
+  const proxyEnv = {
     HTTPS_PROXY: proxyUrl,
     GLOBAL_AGENT_HTTP_PROXY: proxyUrl,
     NODE_EXTRA_CA_CERTS: caCertPath,
   };
+  if (getVcsPolicy() !== VCS_POLICY_ALLOW) {
+    // git does not read NODE_EXTRA_CA_CERTS, but VCS hosts are intercepted
+    proxyEnv.GIT_SSL_CAINFO = caCertPath;
+  }
+  return proxyEnv;
 }
 
+// Secrets only safe-chain itself reads. The wrapped command and the install
+// scripts it runs do not inherit them: with the admin token, a malicious
+// package could reload or probe the policy of the proxy that checks it, and
+// with the upstream proxy password it could leave the network through it.
+const SAFE_CHAIN_SECRET_VARIABLES = new Set([
+  "SAFE_CHAIN_ADMIN_TOKEN",
+  "SAFE_CHAIN_UPSTREAM_PROXY_PASS",
+]);
+
 /**
  * @param {Record<string, string | undefined>} env
  *
@@ -65,6 +182,9 @@ export function mergeSafeChainProxyEnvironmentVariables(env) {
     // the proxy settings set by safe-chain when casing varies (e.g. http_proxy vs HTTP_PROXY)
     // So we only copy the variable if it's not already set in a different case
     const upperKey = key.toUpperCase();
+    if (SAFE_CHAIN_SECRET_VARIABLES.has(upperKey)) {
+      continue;
+    }
 
     if (!proxyEnv[upperKey] && env[key]) {
       proxyEnv[key] = env[key];
@@ -76,6 +196,9 @@ export function mergeSafeChainProxyEnvironmentVariables(env) {
 
 function createProxyServer() {
   const server = http.createServer(
+    // Requests with a larger request line and headers get 431 Request Header
+    // Fields Too Large from Node before any handler runs
+    { maxHeaderSize: getMaxHeaderBytes() },
     // This handles direct HTTP requests (non-CONNECT requests)
     // This is normally http-only traffic, but we also handle
     // https for clients that don't properly use CONNECT
@@ -85,33 +208,299 @@ function createProxyServer() {
   // This handles HTTPS requests via the CONNECT method
   server.on("connect", handleConnect);
 
+  // Without this listener Node passes upgrade requests to the request handler,
+  // which cannot relay a 101 response
+  server.on("upgrade", handleHttpProxyUpgrade);
+
+  // Without this listener Node answers `Expect: 100-continue` itself, before the
+  // upstream has decided whether it accepts the body
+  server.on("checkContinue", handleHttpProxyRequest);
+
+  // Requests with ambiguous framing are rejected by Node's parser; this
+  // listener reports them
+  server.on("clientError", handleClientError);
+
   return server;
 }
 
 /**
+ * Starts the proxy and resolves with the bound address once the listener is
+ * bound, so callers can connect to an OS-assigned port.
+ *
+ * @param {import("http").Server} server
+ *
+ * @returns {Promise<import("net").AddressInfo>}
+ */
+async function startServer(server) {
+  // The cached lists apply right away; the downloads finish in the background,
+  // so a slow feed server does not hold up the install
+  loadProxyPolicy();
+  initializeMetrics();
+  initializeBoundedCaches(getMaxTrackedPackages());
+  openEventSocket();
+
+  // The malware list is otherwise loaded on the first request. The ready line
+  // promises that requests are checked right away, and the self-test checks
+  // requests against it, so it is loaded up front.
+  const malwareDatabase =
+    getReadyBanner() || getSelfTest() ? await openMalwareDatabase() : undefined;
+  const selfTest = getSelfTest() ? await runSelfTest() : undefined;
+
+  // Node disables Nagle's algorithm on accepted sockets by default. Turning it
+  // back on trades per-packet latency for fewer small packets.
+  const noDelay = getTcpNoDelay();
+  server.on("connection", (/** @type {import("net").Socket} */ socket) => {
+    socket.setNoDelay(noDelay);
+  });
+
+  const configuredPort = getProxyPort();
+
+  try {
+    state.port = await listenWithRetry(server, configuredPort);
+  } catch (/** @type {any} */ err) {
+    throw new SafeChainStartupError(
+      EXIT_CODE_PROXY_BIND_FAILURE,
+      `Could not start the proxy server on 127.0.0.1:${configuredPort ?? 0}: ${err.message}`,
+      { cause: err }
+    );
+  }
+
+  ui.writeVerbose(`Safe-chain: Proxy listening on 127.0.0.1:${state.port}`);
+  logUpstreamProxyConfiguration();
+  writePortFile(state.port);
+  if (malwareDatabase && getReadyBanner()) {
+    writeReadyBanner(state.port, malwareDatabase, selfTest);
+  }
+  startLifetimeTimer(server);
+  startPolicyRefresh(async () => {
+    const [database] = await Promise.all([
+      refreshMalwareDatabase(),
+      loadProxyPolicy(),
+    ]);
+    return database;
+  });
+  // Ends the run the same way as reaching the maximum lifetime
+  startWrappedGuard(server, async () => {
+    await stopServer(server);
+    state.onLifetimeExpired?.();
+  });
+
+  return /** @type {import("net").AddressInfo} */ (server.address());
+}
+
+/**
+ * Shuts the proxy down once it reached the maximum lifetime, as a safety net
+ * for CI jobs that do not reliably stop it. Like any shutdown, in-flight
+ * requests get the shutdown timeout to finish.
+ *
  * @param {import("http").Server} server
+ * @returns {void}
+ */
+function startLifetimeTimer(server) {
+  const maxLifetimeSecs = getMaxLifetimeSecs();
+  if (maxLifetimeSecs === undefined) {
+    return;
+  }
+
+  state.lifetimeTimer = setTimeout(async () => {
+    state.lifetimeTimer = null;
+    ui.writeWarning(
+      `Safe-chain: lifetime.expired - the proxy reached its maximum lifetime of ${maxLifetimeSecs} seconds and shuts down`
+    );
+    await stopServer(server);
+    state.onLifetimeExpired?.();
+  }, maxLifetimeSecs * 1000);
+  // The safety net alone does not keep the process running
+  state.lifetimeTimer.unref();
+}
+
+/**
+ * Writes a single JSON line to stderr once the proxy accepts connections, for
+ * tools that launch safe-chain and wait until it is ready. It is written at
+ * every logging level, because tools depend on it.
+ *
+ * @param {number} port
+ * @param {import("../scanning/malwareDatabase.js").MalwareDatabase} malwareDatabase
+ * @param {string | undefined} selfTest - Result of the self-test, when it ran
+ * @returns {void}
+ */
+function writeReadyBanner(port, malwareDatabase, selfTest) {
+  const banner = {
+    event: "ready",
+    address: `127.0.0.1:${port}`,
+    feed_entries: malwareDatabase.entryCount,
+    feed_version: malwareDatabase.version ?? null,
+    feed_age_seconds: getFeedAgeSeconds() ?? null,
+    self_test: selfTest ?? null,
+  };
+  process.stderr.write(`${JSON.stringify(banner)}\n`);
+}
+
+/**
+ * Writes the bound port, which can differ from the configured one, for tools
+ * that need to find the proxy.
+ *
+ * @param {number} port
+ * @returns {void}
+ */
+function writePortFile(port) {
+  const portFile = getPortFile();
+  if (!portFile) {
+    return;
+  }
+
+  try {
+    fs.writeFileSync(portFile, `${port}\n`);
+    state.portFile = portFile;
+  } catch (/** @type {any} */ err) {
+    ui.writeWarning(
+      `Safe-chain: Could not write the proxy port to ${portFile}: ${err.message}`
+    );
+  }
+}
+
+/**
+ * @returns {void}
+ */
+function removePortFile() {
+  if (!state.portFile) {
+    return;
+  }
+
+  try {
+    fs.rmSync(state.portFile, { force: true });
+  } catch {
+    // best-effort: a stale port file is overwritten by the next run
+  }
+  state.portFile = null;
+}
+
+/**
+ * Loads the parts of the policy that come from the remote feeds. Their cached
+ * copies apply before the returned promise settles. The malware database
+ * itself is opened on the first package lookup.
  *
  * @returns {Promise<void>}
  */
-function startServer(server) {
+export async function loadProxyPolicy() {
+  await Promise.all([
+    loadDeniedHostsFromFeed(),
+    loadFlaggedMaintainersFromFeed(),
+  ]);
+}
+
+/**
+ * Loads the flagged maintainers shipped with the remote malware database.
+ * Failures are not fatal: the exact malware matches are still enforced.
+ *
+ * @returns {Promise<void>}
+ */
+function loadFlaggedMaintainersFromFeed() {
+  return loadFeedList({
+    description: "flagged maintainers",
+    cacheName: `flaggedMaintainers_${getEcoSystem()}`,
+    download: fetchFlaggedMaintainers,
+    apply: setFlaggedMaintainers,
+  });
+}
+
+/**
+ * Binds the configured port. When it is in use and port retry is enabled, the
+ * bind is retried a few times before falling back to an OS-assigned port.
+ *
+ * @param {import("http").Server} server
+ * @param {number | undefined} configuredPort
+ * @returns {Promise<number>}
+ */
+async function listenWithRetry(server, configuredPort) {
+  if (configuredPort === undefined) {
+    return await listen(server, 0, false);
+  }
+
+  const retryEnabled = getPortRetry();
+  const attempts = retryEnabled ? PORT_RETRY_ATTEMPTS : 1;
+  const reusePort = shouldReusePort();
+
+  for (let attempt = 1; attempt <= attempts; attempt++) {
+    try {
+      return await listen(server, configuredPort, reusePort);
+    } catch (/** @type {any} */ err) {
+      if (!retryEnabled || err.code !== "EADDRINUSE") {
+        throw err;
+      }
+
+      ui.writeVerbose(
+        `Safe-chain: Port ${configuredPort} is in use (attempt ${attempt} of ${attempts})`
+      );
+      if (attempt < attempts) {
+        await new Promise((resolve) => setTimeout(resolve, PORT_RETRY_DELAY_MS));
+      }
+    }
+  }
+
+  const port = await listen(server, 0, false);
+  ui.writeWarning(
+    `Safe-chain: Port ${configuredPort} is in use, the proxy listens on port ${port} instead.`
+  );
+  return port;
+}
+
+/**
+ * With SO_REUSEPORT a new proxy instance can bind the port of a running one,
+ * which keeps accepting connections until it is stopped. Node supports it from
+ * 22.12 and on some platforms only; elsewhere the port is bound without it.
+ *
+ * @returns {boolean}
+ */
+function shouldReusePort() {
+  if (!getReusePort()) {
+    return false;
+  }
+
+  if (
+    !semver.satisfies(process.versions.node, "^22.12.0 || >=23.1.0") ||
+    !REUSE_PORT_PLATFORMS.includes(process.platform)
+  ) {
+    ui.writeWarning(
+      `Safe-chain: reuse-port.unsupported - SO_REUSEPORT needs Node.js 22.12 or later on Linux or FreeBSD, the port is bound without it (Node.js ${process.versions.node} on ${process.platform}).`
+    );
+    return false;
+  }
+
+  return true;
+}
+
+/**
+ * @param {import("http").Server} server
+ * @param {number} port
+ * @param {boolean} reusePort
+ * @returns {Promise<number>}
+ */
+function listen(server, port, reusePort) {
   return new Promise((resolve, reject) => {
+    /** @param {Error} err */
+    const onError = (err) => reject(err);
+    server.once("error", onError);
+
     // Bind to loopback only. Without an explicit host, Node listens on every
     // interface, turning the proxy into an unauthenticated forward proxy that
     // anyone reachable on the network can use to hit the victim's localhost,
     // intranet, or cloud metadata endpoints. Port 0 lets the OS pick a port.
-    server.listen(0, "127.0.0.1", () => {
+    /** @type {import("net").ListenOptions & {reusePort?: boolean}} */
+    const listenOptions = { port, host: "127.0.0.1", backlog: getTcpBacklog() };
+    if (reusePort) {
+      listenOptions.reusePort = true;
+    }
+    server.listen(listenOptions, () => {
+      server.off("error", onError);
+
       const address = server.address();
       if (address && typeof address === "object") {
-        state.port = address.port;
-        resolve();
+        resolve(address.port);
       } else {
         reject(new Error("Failed to start proxy server"));
       }
     });
-
-    server.on("error", (err) => {
-      reject(err);
-    });
   });
 }
 
@@ -121,10 +510,19 @@ function startServer(server) {
  * @returns {Promise<void>}
  */
 function stopServer(server) {
+  if (state.lifetimeTimer) {
+    clearTimeout(state.lifetimeTimer);
+    state.lifetimeTimer = null;
+  }
+  stopWrappedGuard();
+  stopPolicyRefresh();
+
   return new Promise((resolve) => {
     try {
       server.close(() => {
         cleanupCertBundle();
+        removePortFile();
+        closeEventSocket();
         resolve();
       });
     } catch {
@@ -132,6 +530,8 @@ function stopServer(server) {
     }
     setTimeout(() => {
       cleanupCertBundle();
+      removePortFile();
+      closeEventSocket();
       resolve();
     }, SERVER_STOP_TIMEOUT_MS);
   });
@@ -148,16 +548,47 @@ function handleConnect(req, clientSocket, head) {
   // CONNECT method is used for HTTPS requests
   // It establishes a tunnel to the server identified by the request URL
 
-  const interceptor = createInterceptorForUrl(req.url || "");
+  const hostname = getConnectHostname(req.url || "");
+  if (isVerifyHost(hostname)) {
+    // Answered by the proxy itself, even when blocking is disabled
+    mitmConnect(req, clientSocket, verifyInterceptor);
+    return;
+  }
+
+  if (
+    hostname &&
+    isDeniedHost(hostname) &&
+    recordBlockedHostRequest(hostname, req.url || "")
+  ) {
+    clientSocket.end("HTTP/1.1 403 Forbidden\r\n\r\n");
+    return;
+  }
+
+  const interceptor =
+    createInterceptorForUrl(req.url || "") ??
+    createInterceptorForUserAgentHint(req.url || "", req.headers["user-agent"]);
 
-  if (interceptor) {
+  if (interceptor && getBlockingDisabled()) {
+    // Break-glass: registry traffic is not even decrypted, so nothing can be blocked
+    ui.writeWarning(
+      `Safe-chain: protection.off - blocking is disabled, not inspecting ${req.url}`
+    );
+    tunnelRequest(req, clientSocket, head);
+  } else if (interceptor && shouldBypassInterception(hostname || "")) {
+    tunnelRequest(req, clientSocket, head);
+  } else if (interceptor) {
     // Subscribe to malware blocked events
     interceptor.on(
       "malwareBlocked",
       (
         /** @type {import("./interceptors/interceptorBuilder.js").MalwareBlockedEvent} */ event
       ) => {
-        onMalwareBlocked(event.packageName, event.version, event.targetUrl);
+        onMalwareBlocked(
+          event.packageName,
+          event.version,
+          event.targetUrl,
+          event.advisories
+        );
       }
     );
     interceptor.on(
@@ -172,8 +603,64 @@ function handleConnect(req, clientSocket, head) {
         );
       }
     );
+    interceptor.on(
+      "prereleaseBlocked",
+      (
+        /** @type {import("./interceptors/interceptorBuilder.js").PrereleaseBlockedEvent} */ event
+      ) => {
+        onPrereleaseBlocked(event.packageName, event.version, event.targetUrl);
+      }
+    );
+    interceptor.on(
+      "installScriptBlocked",
+      (
+        /** @type {import("./interceptors/interceptorBuilder.js").InstallScriptBlockedEvent} */ event
+      ) => {
+        onInstallScriptBlocked(
+          event.packageName,
+          event.version,
+          event.scripts,
+          event.targetUrl
+        );
+      }
+    );
+    interceptor.on(
+      "sizeAnomalyBlocked",
+      (
+        /** @type {import("./interceptors/interceptorBuilder.js").SizeAnomalyBlockedEvent} */ event
+      ) => {
+        onSizeAnomalyBlocked(
+          event.packageName,
+          event.version,
+          event.description,
+          event.targetUrl
+        );
+      }
+    );
+    interceptor.on(
+      "vcsDependencyBlocked",
+      (
+        /** @type {import("./interceptors/interceptorBuilder.js").VcsDependencyBlockedEvent} */ event
+      ) => {
+        onVcsDependencyBlocked(event.repository, event.ref, event.targetUrl);
+      }
+    );
+    interceptor.on(
+      "packageRedirected",
+      (
+        /** @type {import("./interceptors/interceptorBuilder.js").PackageRedirectedEvent} */ event
+      ) => {
+        onPackageRedirected(event);
+      }
+    );
 
     mitmConnect(req, clientSocket, interceptor);
+  } else if (hostname && isBlockedUnknownHost(hostname, req.url || "")) {
+    clientSocket.end(
+      `HTTP/1.1 403 Forbidden\r\n${Object.entries(UNKNOWN_REGISTRY_HEADERS)
+        .map(([name, value]) => `${name}: ${value}\r\n`)
+        .join("")}\r\n`
+    );
   } else {
     // For other hosts, just tunnel the request to the destination tcp socket
     ui.writeVerbose(`Safe-chain: Tunneling request to ${req.url}`);
@@ -181,14 +668,28 @@ function handleConnect(req, clientSocket, head) {
   }
 }
 
+/**
+ * @param {string} connectUrl - The CONNECT target in authority form (host:port)
+ * @returns {string | undefined}
+ */
+function getConnectHostname(connectUrl) {
+  try {
+    return new URL(`http://${connectUrl}`).hostname;
+  } catch {
+    return undefined;
+  }
+}
+
 /**
  *
  * @param {string} packageName
  * @param {string} version
  * @param {string} url
+ * @param {string[]} [advisories]
  */
-function onMalwareBlocked(packageName, version, url) {
-  state.blockedRequests.push({ packageName, version, url });
+function onMalwareBlocked(packageName, version, url, advisories = []) {
+  state.blockedRequests.push({ packageName, version, url, advisories });
+  emitDecisionEvent(packageName, version, BLOCK_REASON_MALWARE, url);
 }
 
 /**
@@ -199,6 +700,81 @@ function onMalwareBlocked(packageName, version, url) {
  */
 function onMinimumAgeRequestBlocked(packageName, version, url) {
   state.blockedMinimumAgeRequests.push({ packageName, version, url });
+  emitDecisionEvent(packageName, version, BLOCK_REASON_MINIMUM_AGE, url);
+}
+
+/**
+ * @param {string[]} advisories
+ * @returns {string}
+ */
+function formatAdvisories(advisories) {
+  return advisories.length > 0 ? ` (advisories: ${advisories.join(", ")})` : "";
+}
+
+/**
+ *
+ * @param {string} packageName
+ * @param {string} version
+ * @param {string} url
+ */
+function onPrereleaseBlocked(packageName, version, url) {
+  state.blockedPrereleaseRequests.push({ packageName, version, url });
+  emitDecisionEvent(packageName, version, BLOCK_REASON_PRERELEASE, url);
+}
+
+/**
+ *
+ * @param {string} packageName
+ * @param {string} version
+ * @param {string} scripts
+ * @param {string} url
+ */
+function onInstallScriptBlocked(packageName, version, scripts, url) {
+  state.blockedInstallScriptRequests.push({ packageName, version, scripts, url });
+  emitDecisionEvent(packageName, version, BLOCK_REASON_INSTALL_SCRIPT, url);
+}
+
+/**
+ *
+ * @param {string} packageName
+ * @param {string} version
+ * @param {string} description
+ * @param {string} url
+ */
+function onSizeAnomalyBlocked(packageName, version, description, url) {
+  state.blockedSizeAnomalyRequests.push({
+    packageName,
+    version,
+    description,
+    url,
+  });
+  emitDecisionEvent(packageName, version, BLOCK_REASON_SIZE_ANOMALY, url);
+}
+
+/**
+ *
+ * @param {string} repository
+ * @param {string | undefined} ref
+ * @param {string} url
+ */
+function onVcsDependencyBlocked(repository, ref, url) {
+  state.blockedVcsDependencyRequests.push({ repository, ref, url });
+  emitDecisionEvent(repository, ref ?? "", BLOCK_REASON_VCS_DEPENDENCY, url);
+}
+
+/**
+ * Records the substitution of a blocked package in the log, so every
+ * redirect can be traced like a block.
+ *
+ * @param {import("./interceptors/interceptorBuilder.js").PackageRedirectedEvent} event
+ */
+function onPackageRedirected(event) {
+  const packageSpec = `${event.packageName}${
+    event.version ? `@${event.version}` : ""
+  }`;
+  ui.writeDecision(
+    `Safe-chain: ${getInspectOnly() ? "inspect.would-redirect" : "redirect"} - ${packageSpec} (${event.targetUrl}) is blocked (${event.reason}) and replaced by ${event.redirectUrl}`
+  );
 }
 
 function hasBlockedMaliciousPackages() {
@@ -208,19 +784,25 @@ function hasBlockedMaliciousPackages() {
 
   ui.emptyLine();
 
-  ui.writeInformation(
+  ui.writeDecision(
     `Safe-chain: ${chalk.bold(
-      `blocked ${state.blockedRequests.length} malicious package downloads`
+      `${blockedVerb()} ${state.blockedRequests.length} malicious package downloads`
     )}:`
   );
 
   for (const req of state.blockedRequests) {
-    ui.writeInformation(` - ${req.packageName}@${req.version} (${req.url})`);
+    ui.writeDecision(
+      ` - ${req.packageName}@${req.version} (${req.url})${formatAdvisories(
+        req.advisories
+      )}`
+    );
   }
 
   ui.emptyLine();
-  ui.writeExitWithoutInstallingMaliciousPackages();
-  ui.emptyLine();
+  if (!getInspectOnly()) {
+    ui.writeExitWithoutInstallingMaliciousPackages();
+    ui.emptyLine();
+  }
 
   return true;
 }
@@ -232,27 +814,234 @@ function hasBlockedMinimumAgeRequests() {
 
   ui.emptyLine();
 
-  ui.writeInformation(
+  ui.writeDecision(
     `Safe-chain: ${chalk.bold(
-      `blocked ${state.blockedMinimumAgeRequests.length} direct package download request(s) due to minimum package age`
+      `${blockedVerb()} ${state.blockedMinimumAgeRequests.length} direct package download request(s) due to minimum package age`
     )}:`
   );
 
   for (const req of state.blockedMinimumAgeRequests) {
-    ui.writeInformation(` - ${req.packageName}@${req.version} (${req.url})`);
+    ui.writeDecision(` - ${req.packageName}@${req.version} (${req.url})`);
   }
 
-  ui.writeInformation(
+  ui.writeDecision(
     `  To disable this check, use: ${chalk.cyan(
       "--safe-chain-skip-minimum-package-age"
     )}`
   );
 
   ui.emptyLine();
-  ui.writeError(
-    "Safe-chain: Exiting without installing packages blocked by the direct download minimum package age check."
+  if (!getInspectOnly()) {
+    ui.writeError(
+      "Safe-chain: Exiting without installing packages blocked by the direct download minimum package age check."
+    );
+    ui.emptyLine();
+  }
+
+  return true;
+}
+
+function hasBlockedPrereleaseRequests() {
+  if (state.blockedPrereleaseRequests.length === 0) {
+    return false;
+  }
+
+  ui.emptyLine();
+
+  ui.writeDecision(
+    `Safe-chain: ${chalk.bold(
+      `${blockedVerb()} ${state.blockedPrereleaseRequests.length} pre-release package download(s)`
+    )}:`
   );
+
+  for (const req of state.blockedPrereleaseRequests) {
+    ui.writeDecision(` - ${req.packageName}@${req.version} (${req.url})`);
+  }
+
+  ui.writeDecision(
+    `  To allow a specific pre-release, use: ${chalk.cyan(
+      "--safe-chain-allow-prerelease=<package>@<version>"
+    )}`
+  );
+
   ui.emptyLine();
+  if (!getInspectOnly()) {
+    ui.writeError("Safe-chain: Exiting without installing pre-release packages.");
+    ui.emptyLine();
+  }
 
   return true;
 }
+
+function hasBlockedInstallScriptRequests() {
+  if (state.blockedInstallScriptRequests.length === 0) {
+    return false;
+  }
+
+  ui.emptyLine();
+
+  ui.writeDecision(
+    `Safe-chain: ${chalk.bold(
+      `${blockedVerb()} ${state.blockedInstallScriptRequests.length} package download(s) with install scripts`
+    )}:`
+  );
+
+  for (const req of state.blockedInstallScriptRequests) {
+    ui.writeDecision(
+      ` - ${req.packageName}@${req.version} runs ${req.scripts} (${req.url})`
+    );
+  }
+
+  ui.writeDecision(
+    `  To allow the install scripts of a package, use: ${chalk.cyan(
+      "--safe-chain-allow-install-scripts=<package>"
+    )}`
+  );
+
+  ui.emptyLine();
+  if (!getInspectOnly()) {
+    ui.writeError(
+      "Safe-chain: Exiting without installing packages with install scripts."
+    );
+    ui.emptyLine();
+  }
+
+  return true;
+}
+
+function hasBlockedSizeAnomalyRequests() {
+  if (state.blockedSizeAnomalyRequests.length === 0) {
+    return false;
+  }
+
+  ui.emptyLine();
+
+  ui.writeDecision(
+    `Safe-chain: ${chalk.bold(
+      `${blockedVerb()} ${state.blockedSizeAnomalyRequests.length} package download(s) with a size anomaly`
+    )}:`
+  );
+
+  for (const req of state.blockedSizeAnomalyRequests) {
+    ui.writeDecision(
+      ` - ${req.packageName}@${req.version} ${req.description} (${req.url})`
+    );
+  }
+
+  ui.emptyLine();
+  if (!getInspectOnly()) {
+    ui.writeError(
+      "Safe-chain: Exiting without installing packages with a size anomaly."
+    );
+    ui.emptyLine();
+  }
+
+  return true;
+}
+
+function hasBlockedHostRequests() {
+  const blockedHostRequests = getBlockedHostRequests();
+  if (blockedHostRequests.length === 0) {
+    return false;
+  }
+
+  ui.emptyLine();
+
+  ui.writeDecision(
+    `Safe-chain: ${chalk.bold(
+      `${blockedVerb()} ${blockedHostRequests.length} request(s) to denied hosts`
+    )}:`
+  );
+
+  for (const req of blockedHostRequests) {
+    ui.writeDecision(` - ${req.host} (${req.url})`);
+  }
+
+  ui.emptyLine();
+  if (!getInspectOnly()) {
+    ui.writeError(
+      "Safe-chain: A process tried to reach a host on the deny list. Review the installed packages and their install scripts."
+    );
+    ui.emptyLine();
+  }
+
+  return true;
+}
+
+function hasBlockedUnknownHostRequests() {
+  const blockedRequests = getBlockedUnknownHostRequests();
+  if (blockedRequests.length === 0) {
+    return false;
+  }
+
+  ui.emptyLine();
+
+  ui.writeDecision(
+    `Safe-chain: ${chalk.bold(
+      `${blockedVerb()} ${blockedRequests.length} request(s) to hosts that are not known registries`
+    )}:`
+  );
+
+  for (const req of blockedRequests) {
+    ui.writeDecision(` - ${req.host} (${req.url})`);
+  }
+
+  ui.writeDecision(
+    `  To allow a host, use: ${chalk.cyan(
+      "--safe-chain-allow-unknown-host=<host>"
+    )}`
+  );
+
+  ui.emptyLine();
+  if (!getInspectOnly()) {
+    ui.writeError(
+      "Safe-chain: Strict unknown hosts mode is on, so requests to hosts that are not known registries were refused."
+    );
+    ui.emptyLine();
+  }
+
+  return true;
+}
+
+function hasBlockedVcsDependencyRequests() {
+  if (state.blockedVcsDependencyRequests.length === 0) {
+    return false;
+  }
+
+  ui.emptyLine();
+
+  ui.writeDecision(
+    `Safe-chain: ${chalk.bold(
+      `${blockedVerb()} ${state.blockedVcsDependencyRequests.length} dependency download(s) from VCS hosts`
+    )}:`
+  );
+
+  for (const req of state.blockedVcsDependencyRequests) {
+    ui.writeDecision(
+      ` - ${req.repository}${req.ref ? `@${req.ref}` : ""} (${req.url})`
+    );
+  }
+
+  ui.writeDecision(
+    `  To allow dependencies from VCS hosts, use: ${chalk.cyan(
+      "--safe-chain-vcs-policy=log"
+    )}`
+  );
+
+  ui.emptyLine();
+  if (!getInspectOnly()) {
+    ui.writeError(
+      "Safe-chain: Exiting without installing dependencies from VCS hosts."
+    );
+    ui.emptyLine();
+  }
+
+  return true;
+}
+
+/**
+ * @returns {string}
+ */
+function blockedVerb() {
+  return getInspectOnly() ? "would have blocked" : "blocked";
+}
@@ -0,0 +1,54 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import net from "net";
+import { createSafeChainProxy } from "./registryProxy.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+
+describe("registryProxy maximum lifetime", () => {
+  let proxy;
+
+  afterEach(async () => {
+    await proxy?.stopServer();
+    proxy = undefined;
+    initializeCliArguments([]);
+  });
+
+  it("shuts down once the maximum lifetime is reached", async () => {
+    initializeCliArguments(["--safe-chain-max-lifetime-secs=0.2"]);
+    proxy = createSafeChainProxy();
+    const expired = new Promise((resolve) => proxy.onLifetimeExpired(resolve));
+
+    const startTime = Date.now();
+    const { port } = await proxy.startServer();
+    await expired;
+
+    assert.ok(Date.now() - startTime >= 150);
+    await assert.rejects(connect(port), { code: "ECONNREFUSED" });
+  });
+
+  it("keeps running without a maximum lifetime", async () => {
+    proxy = createSafeChainProxy();
+    let expired = false;
+    proxy.onLifetimeExpired(() => {
+      expired = true;
+    });
+
+    const { port } = await proxy.startServer();
+    await new Promise((resolve) => setTimeout(resolve, 300));
+
+    assert.strictEqual(expired, false);
+    (await connect(port)).destroy();
+  });
+});
+
+/**
+ * @param {number} port
+ * @returns {Promise<net.Socket>}
+ */
+function connect(port) {
+  return new Promise((resolve, reject) => {
+    const socket = net.createConnection({ host: "127.0.0.1", port });
+    socket.once("connect", () => resolve(socket));
+    socket.once("error", reject);
+  });
+}
@@ -30,6 +30,39 @@ describe("registryProxy loopback binding", () => {
     );
   });
 
+  it("keeps the secrets out of the environment of the wrapped command", () => {
+    const envVars = mergeSafeChainProxyEnvironmentVariables({
+      SAFE_CHAIN_ADMIN_TOKEN: "s3cret",
+      safe_chain_admin_token: "s3cret",
+      SAFE_CHAIN_UPSTREAM_PROXY_PASS: "hunter2",
+      SAFE_CHAIN_BLOCK_STATUS: "403",
+    });
+
+    assert.strictEqual(envVars.SAFE_CHAIN_ADMIN_TOKEN, undefined);
+    assert.strictEqual(envVars.safe_chain_admin_token, undefined);
+    assert.strictEqual(envVars.SAFE_CHAIN_UPSTREAM_PROXY_PASS, undefined);
+    assert.strictEqual(envVars.SAFE_CHAIN_BLOCK_STATUS, "403");
+  });
+
+  it("does not let the wrapped command ask the proxy to explain its decisions", async () => {
+    process.env.SAFE_CHAIN_ADMIN_TOKEN = "s3cret";
+    try {
+      const childEnv = mergeSafeChainProxyEnvironmentVariables(process.env);
+      const explainUrl = new URL("/admin/explain", childEnv.HTTPS_PROXY);
+      explainUrl.searchParams.set("url", "https://registry.npmjs.org/lodash");
+
+      const response = await fetch(explainUrl, {
+        headers: {
+          Authorization: `Bearer ${childEnv.SAFE_CHAIN_ADMIN_TOKEN ?? ""}`,
+        },
+      });
+
+      assert.strictEqual(response.status, 401);
+    } finally {
+      delete process.env.SAFE_CHAIN_ADMIN_TOKEN;
+    }
+  });
+
   it("refuses connections on non-loopback interfaces", async () => {
     const externalAddrs = Object.values(os.networkInterfaces())
       .flat()
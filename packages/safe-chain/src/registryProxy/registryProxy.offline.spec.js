@@ -0,0 +1,114 @@
+import { after, describe, it, mock } from "node:test";
+import assert from "node:assert";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import {
+  TEST_REGISTRY_CERTIFICATE,
+  TEST_REGISTRY_PRIVATE_KEY,
+} from "../tests/support/testRegistryCertificate.js";
+
+describe("registryProxy offline without telemetry", async () => {
+  const fetchedUrls = [];
+  const originalHome = process.env.HOME;
+  const homeDir = fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-offline-"));
+  process.env.HOME = homeDir;
+
+  // Every request safe-chain makes itself goes through make-fetch-happen
+  mock.module("make-fetch-happen", {
+    defaultExport: async (url) => {
+      fetchedUrls.push(String(url));
+      throw new Error(`Unexpected outbound request to ${url}`);
+    },
+  });
+
+  mock.module("./certUtils.js", {
+    namedExports: {
+      generateCertForHost: () => ({
+        privateKey: TEST_REGISTRY_PRIVATE_KEY,
+        certificate: TEST_REGISTRY_CERTIFICATE,
+      }),
+      getCaCertPath: () => "",
+    },
+  });
+
+  const aikidoDir = path.join(homeDir, ".aikido");
+  fs.mkdirSync(aikidoDir, { recursive: true });
+  fs.writeFileSync(
+    path.join(aikidoDir, "malwareDatabase_js.json"),
+    JSON.stringify([
+      {
+        package_name: "safe-chain-test",
+        version: "0.0.1-security",
+        reason: "MALWARE",
+      },
+    ])
+  );
+  fs.writeFileSync(path.join(aikidoDir, "version_js.txt"), '"cached"');
+  fs.writeFileSync(
+    path.join(aikidoDir, "deniedHosts.json"),
+    JSON.stringify(["evil.example.com"])
+  );
+  fs.writeFileSync(
+    path.join(aikidoDir, "flaggedMaintainers_js.json"),
+    JSON.stringify([{ maintainer: "mallory", flagged_on: 1700000000 }])
+  );
+
+  const { createSafeChainProxy } = await import("./registryProxy.js");
+  const { initializeCliArguments } = await import("../config/cliArguments.js");
+  const { openMalwareDatabase } = await import("../scanning/malwareDatabase.js");
+  const { openNewPackagesDatabase } = await import(
+    "../scanning/newPackagesListCache.js"
+  );
+  const { checkForUpdates } = await import("../environment/updateCheck.js");
+  const { isDeniedHost } = await import("./hostDenylist.js");
+  const { hasFlaggedMaintainers } = await import(
+    "./interceptors/flaggedMaintainers.js"
+  );
+
+  after(() => {
+    initializeCliArguments([]);
+    process.env.HOME = originalHome;
+    fs.rmSync(homeDir, { recursive: true, force: true });
+  });
+
+  it("makes no outbound requests besides the proxied package traffic", async () => {
+    initializeCliArguments([
+      "--safe-chain-offline",
+      "--safe-chain-no-telemetry",
+      "--safe-chain-check-updates",
+      "--safe-chain-self-test",
+      "--safe-chain-malware-feed-url=https://feed.example.com",
+    ]);
+
+    const proxy = createSafeChainProxy();
+    await proxy.startServer();
+    try {
+      const malwareDatabase = await openMalwareDatabase();
+      await openNewPackagesDatabase();
+      await checkForUpdates();
+
+      assert.strictEqual(
+        malwareDatabase.isMalware("safe-chain-test", "0.0.1-security"),
+        true
+      );
+    } finally {
+      await proxy.stopServer();
+    }
+
+    assert.deepStrictEqual(fetchedUrls, []);
+  });
+
+  it("applies the cached denied hosts and flagged maintainers", async () => {
+    initializeCliArguments(["--safe-chain-offline"]);
+
+    const proxy = createSafeChainProxy();
+    await proxy.startServer();
+    try {
+      assert.strictEqual(isDeniedHost("evil.example.com"), true);
+      assert.strictEqual(hasFlaggedMaintainers(), true);
+    } finally {
+      await proxy.stopServer();
+    }
+  });
+});
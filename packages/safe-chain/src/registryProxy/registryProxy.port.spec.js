@@ -0,0 +1,196 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import net from "net";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import {
+  createSafeChainProxy,
+  mergeSafeChainProxyEnvironmentVariables,
+} from "./registryProxy.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+import { EXIT_CODE_PROXY_BIND_FAILURE } from "../utils/exitCodes.js";
+
+describe("registryProxy port selection", () => {
+  /** @type {(() => Promise<void>)[]} */
+  let cleanups = [];
+
+  afterEach(async () => {
+    for (const cleanup of cleanups) {
+      await cleanup();
+    }
+    cleanups = [];
+    initializeCliArguments([]);
+  });
+
+  it("listens on the configured port", async () => {
+    const port = await getFreePort();
+    initializeCliArguments([`--safe-chain-proxy-port=${port}`]);
+
+    await startProxy();
+
+    assert.strictEqual(getProxyEnvPort(), port);
+  });
+
+  it("resolves with the bound loopback address of an OS-assigned port", async () => {
+    const proxy = createSafeChainProxy();
+    cleanups.push(() => proxy.stopServer());
+
+    const address = await proxy.startServer();
+
+    assert.strictEqual(address.address, "127.0.0.1");
+    assert.ok(address.port > 0);
+    assert.strictEqual(address.port, getProxyEnvPort());
+  });
+
+  it("fails with the bind failure exit code when the port is in use", async () => {
+    const port = await occupyPort();
+    initializeCliArguments([`--safe-chain-proxy-port=${port}`]);
+
+    const proxy = createSafeChainProxy();
+    cleanups.push(() => proxy.stopServer());
+
+    await assert.rejects(proxy.startServer(), {
+      name: "SafeChainStartupError",
+      exitCode: EXIT_CODE_PROXY_BIND_FAILURE,
+    });
+  });
+
+  it("falls back to an OS-assigned port with port retry enabled", async () => {
+    const port = await occupyPort();
+    initializeCliArguments([
+      `--safe-chain-proxy-port=${port}`,
+      "--safe-chain-port-retry",
+    ]);
+
+    await startProxy();
+
+    const proxyPort = getProxyEnvPort();
+    assert.notStrictEqual(proxyPort, port);
+    assert.ok(proxyPort > 0);
+  });
+
+  it("binds the configured port when it is released during the retries", async () => {
+    const port = await getFreePort();
+    const blocker = await listenOn(port);
+    initializeCliArguments([
+      `--safe-chain-proxy-port=${port}`,
+      "--safe-chain-port-retry",
+    ]);
+
+    setTimeout(() => blocker.close(), 100);
+    await startProxy();
+
+    assert.strictEqual(getProxyEnvPort(), port);
+  });
+
+  it("writes the bound port to the port file when port 0 is requested", async () => {
+    const portFile = path.join(
+      fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-port-")),
+      "proxy.port"
+    );
+    initializeCliArguments([
+      "--safe-chain-proxy-port=0",
+      `--safe-chain-port-file=${portFile}`,
+    ]);
+
+    const proxy = createSafeChainProxy();
+    const { port } = await proxy.startServer();
+
+    try {
+      const writtenPort = Number(fs.readFileSync(portFile, "utf8").trim());
+      assert.ok(writtenPort > 0);
+      assert.strictEqual(writtenPort, port);
+      assert.strictEqual(writtenPort, getProxyEnvPort());
+    } finally {
+      await proxy.stopServer();
+    }
+
+    assert.strictEqual(fs.existsSync(portFile), false);
+  });
+
+  it("writes the fallback port to the port file", async () => {
+    const port = await occupyPort();
+    const portFile = path.join(
+      fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-port-")),
+      "proxy.port"
+    );
+    initializeCliArguments([
+      `--safe-chain-proxy-port=${port}`,
+      "--safe-chain-port-retry",
+      `--safe-chain-port-file=${portFile}`,
+    ]);
+
+    await startProxy();
+
+    assert.strictEqual(
+      Number(fs.readFileSync(portFile, "utf8").trim()),
+      getProxyEnvPort()
+    );
+  });
+
+  it("lets a second proxy bind the port of a running one with reuse port", async () => {
+    const port = await getFreePort();
+    initializeCliArguments([
+      `--safe-chain-proxy-port=${port}`,
+      "--safe-chain-reuse-port",
+    ]);
+
+    if (!supportsReusePort()) {
+      // The port is bound without SO_REUSEPORT, as without the option
+      await startProxy();
+      await assert.rejects(createSafeChainProxy().startServer(), {
+        exitCode: EXIT_CODE_PROXY_BIND_FAILURE,
+      });
+      return;
+    }
+
+    await startProxy();
+    await startProxy();
+
+    assert.strictEqual(getProxyEnvPort(), port);
+  });
+
+  async function startProxy() {
+    const proxy = createSafeChainProxy();
+    cleanups.push(() => proxy.stopServer());
+    await proxy.startServer();
+  }
+
+  async function occupyPort() {
+    const server = await listenOn(0);
+    cleanups.push(() => new Promise((resolve) => server.close(() => resolve())));
+    return server.address().port;
+  }
+});
+
+function supportsReusePort() {
+  const [major, minor] = process.versions.node.split(".").map(Number);
+  const supportedNode =
+    major > 23 || (major === 23 && minor >= 1) || (major === 22 && minor >= 12);
+  return supportedNode && ["linux", "freebsd"].includes(process.platform);
+}
+
+function getProxyEnvPort() {
+  const envVars = mergeSafeChainProxyEnvironmentVariables({});
+  return parseInt(new URL(envVars.HTTPS_PROXY).port, 10);
+}
+
+/**
+ * @param {number} port
+ * @returns {Promise<net.Server>}
+ */
+function listenOn(port) {
+  return new Promise((resolve, reject) => {
+    const server = net.createServer();
+    server.once("error", reject);
+    server.listen(port, "127.0.0.1", () => resolve(server));
+  });
+}
+
+async function getFreePort() {
+  const server = await listenOn(0);
+  const port = server.address().port;
+  await new Promise((resolve) => server.close(() => resolve()));
+  return port;
+}
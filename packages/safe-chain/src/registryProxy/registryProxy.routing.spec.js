@@ -0,0 +1,213 @@
+import { after, before, describe, it, mock } from "node:test";
+import assert from "node:assert";
+import net from "net";
+import tls from "tls";
+import * as http from "http";
+import * as https from "https";
+import {
+  TEST_REGISTRY_CERTIFICATE,
+  TEST_REGISTRY_PRIVATE_KEY,
+} from "../tests/support/testRegistryCertificate.js";
+
+// Multi-tenant registry gateways serve several registries from one address and
+// pick the tenant from the SNI (HTTPS) or the Host header (HTTP). Every host
+// resolves to the same local upstream here, so a response from the wrong
+// tenant means the proxy did not forward the name the client asked for.
+describe("registryProxy routing by Host and SNI", async () => {
+  mock.module("../api/aikido.js", {
+    namedExports: {
+      fetchMalwareDatabase: async () => ({
+        malwareDatabase: [],
+        version: undefined,
+      }),
+      fetchMalwareDatabaseVersion: async () => undefined,
+      fetchMalwareFeed: async () => [],
+      fetchNewPackagesList: async () => ({
+        newPackagesList: [],
+        version: undefined,
+      }),
+      fetchNewPackagesListVersion: async () => undefined,
+      fetchDeniedHosts: async () => [],
+      fetchFlaggedMaintainers: async () => [],
+    },
+  });
+
+  mock.module("./dohResolver.js", {
+    namedExports: {
+      getUpstreamLookup: () => (hostname, options, callback) => {
+        if (options?.all) {
+          callback(null, [{ address: "127.0.0.1", family: 4 }]);
+        } else {
+          callback(null, "127.0.0.1", 4);
+        }
+      },
+    },
+  });
+
+  mock.module("./certUtils.js", {
+    namedExports: {
+      generateCertForHost: () => ({
+        privateKey: TEST_REGISTRY_PRIVATE_KEY,
+        certificate: TEST_REGISTRY_CERTIFICATE,
+      }),
+      getCaCertPath: () => "",
+    },
+  });
+
+  const { createSafeChainProxy } = await import("./registryProxy.js");
+  const { setEcoSystem, ECOSYSTEM_JS } = await import("../config/settings.js");
+
+  let proxy, proxyPort;
+  let tlsUpstream, tlsUpstreamPort;
+  let httpUpstream, httpUpstreamPort;
+  let originalCa, originalHttpsProxy;
+
+  before(async () => {
+    setEcoSystem(ECOSYSTEM_JS);
+    originalHttpsProxy = process.env.HTTPS_PROXY;
+    delete process.env.HTTPS_PROXY;
+    // The intercepting server re-originates requests with the default agent
+    originalCa = https.globalAgent.options.ca;
+    https.globalAgent.options.ca = TEST_REGISTRY_CERTIFICATE;
+
+    tlsUpstream = https.createServer(
+      { key: TEST_REGISTRY_PRIVATE_KEY, cert: TEST_REGISTRY_CERTIFICATE },
+      (req, res) => {
+        res.writeHead(200, { "content-type": "text/plain" });
+        res.end(`tenant ${req.socket.servername} host ${req.headers.host}`);
+      }
+    );
+    tlsUpstreamPort = await listen(tlsUpstream);
+
+    httpUpstream = http.createServer((req, res) => {
+      res.writeHead(200, { "content-type": "text/plain" });
+      res.end(`tenant ${req.headers.host}`);
+    });
+    httpUpstreamPort = await listen(httpUpstream);
+
+    proxy = createSafeChainProxy();
+    ({ port: proxyPort } = await proxy.startServer());
+  });
+
+  after(async () => {
+    await proxy.stopServer();
+    tlsUpstream.close();
+    httpUpstream.close();
+    https.globalAgent.options.ca = originalCa;
+    if (originalHttpsProxy !== undefined) {
+      process.env.HTTPS_PROXY = originalHttpsProxy;
+    }
+  });
+
+  it("re-originates intercepted requests with the SNI of the CONNECT host", async () => {
+    const response = await requestThroughTunnel("registry.npmjs.org", "/-/ping");
+
+    assert.strictEqual(response.statusCode, 200);
+    assert.strictEqual(
+      response.body,
+      `tenant registry.npmjs.org host registry.npmjs.org:${tlsUpstreamPort}`
+    );
+  });
+
+  it("keeps the client's SNI for tunneled connections", async () => {
+    const response = await requestThroughTunnel("pypi.org", "/simple/");
+
+    assert.strictEqual(response.statusCode, 200);
+    assert.strictEqual(
+      response.body,
+      `tenant pypi.org host pypi.org:${tlsUpstreamPort}`
+    );
+  });
+
+  it("forwards the Host header of plain HTTP requests", async () => {
+    const tenantA = await requestThroughPlainProxy(
+      `http://tenant-a.registry.test:${httpUpstreamPort}/`
+    );
+    const tenantB = await requestThroughPlainProxy(
+      `http://tenant-b.registry.test:${httpUpstreamPort}/`
+    );
+
+    assert.strictEqual(tenantA, `tenant tenant-a.registry.test:${httpUpstreamPort}`);
+    assert.strictEqual(tenantB, `tenant tenant-b.registry.test:${httpUpstreamPort}`);
+  });
+
+  /**
+   * @param {import("net").Server} server
+   * @returns {Promise<number>}
+   */
+  function listen(server) {
+    return new Promise((resolve) => {
+      server.listen(0, "127.0.0.1", () => resolve(server.address().port));
+    });
+  }
+
+  /**
+   * @param {string} targetHost
+   * @param {string} path
+   * @returns {Promise<{statusCode: number | undefined, body: string}>}
+   */
+  async function requestThroughTunnel(targetHost, path) {
+    const authority = `${targetHost}:${tlsUpstreamPort}`;
+    const socket = net.connect({ host: "127.0.0.1", port: proxyPort });
+    await new Promise((resolve, reject) => {
+      socket.once("error", reject);
+      socket.write(`CONNECT ${authority} HTTP/1.1\r\nHost: ${authority}\r\n\r\n`);
+      socket.once("data", resolve);
+    });
+
+    const tlsSocket = tls.connect({
+      socket,
+      servername: targetHost,
+      ca: TEST_REGISTRY_CERTIFICATE,
+    });
+    await new Promise((resolve, reject) => {
+      tlsSocket.once("secureConnect", resolve);
+      tlsSocket.once("error", reject);
+    });
+
+    return new Promise((resolve, reject) => {
+      const req = http.request(
+        {
+          host: targetHost,
+          port: tlsUpstreamPort,
+          path,
+          headers: { connection: "close" },
+          createConnection: () => tlsSocket,
+        },
+        (res) => {
+          let body = "";
+          res.setEncoding("utf8");
+          res.on("data", (chunk) => (body += chunk));
+          res.on("end", () => resolve({ statusCode: res.statusCode, body }));
+        }
+      );
+      req.on("error", reject);
+      req.end();
+    });
+  }
+
+  /**
+   * @param {string} url
+   * @returns {Promise<string>}
+   */
+  function requestThroughPlainProxy(url) {
+    return new Promise((resolve, reject) => {
+      const req = http.request(
+        {
+          host: "127.0.0.1",
+          port: proxyPort,
+          path: url,
+          headers: { host: new URL(url).host, connection: "close" },
+        },
+        (res) => {
+          let body = "";
+          res.setEncoding("utf8");
+          res.on("data", (chunk) => (body += chunk));
+          res.on("end", () => resolve(body));
+        }
+      );
+      req.on("error", reject);
+      req.end();
+    });
+  }
+});
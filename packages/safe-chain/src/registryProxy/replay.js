@@ -0,0 +1,268 @@
+import { ui } from "../environment/userInteraction.js";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  getEcoSystem,
+  getMinimumPackageAgeHours,
+  setEcoSystem,
+  skipMinimumPackageAge,
+} from "../config/settings.js";
+import { getFeedVersion } from "../scanning/feedVersion.js";
+import { createInterceptorForUrl } from "./interceptors/createInterceptorForEcoSystem.js";
+import { getPublishTime } from "./interceptors/npm/publishTimes.js";
+import { isDeniedHost } from "./hostDenylist.js";
+import { loadProxyPolicy } from "./registryProxy.js";
+import { getExitCodeForError } from "../utils/exitCodes.js";
+
+const AUDIT_LINE_ARG = "--audit-line=";
+
+/**
+ * The interceptor rules, named after the event they emit when they block.
+ * Interceptors stop at the first rule that blocks.
+ */
+const INTERCEPTOR_RULES = [
+  { name: "malware database", event: "malwareBlocked" },
+  { name: "minimum package age", event: "minimumAgeRequestBlocked" },
+  { name: "pre-release policy", event: "prereleaseBlocked" },
+];
+
+/**
+ * @typedef {Object} RuleResult
+ * @property {string} name
+ * @property {string} outcome
+ * @property {Record<string, string | number | boolean | null>} [input] - What the rule was evaluated on
+ *
+ * @typedef {Object} ReplayResult
+ * @property {string} url
+ * @property {string | undefined} ecosystem - Undefined when no interceptor handles the URL
+ * @property {string | undefined} packageName - As parsed from the URL
+ * @property {string | undefined} version
+ * @property {string | undefined} blockResponse - Status and message when blocked
+ * @property {RuleResult[]} rules
+ */
+
+/**
+ * Finds the request URL in a line of the log file. Both the plain and the
+ * JSON log format are supported.
+ *
+ * @param {string} line
+ * @returns {string | undefined}
+ */
+export function getUrlFromAuditLine(line) {
+  let message = line;
+  try {
+    const entry = JSON.parse(line);
+    if (typeof entry?.message === "string") {
+      message = entry.message;
+    }
+  } catch {
+    // Plain log format
+  }
+
+  return message.match(/https?:\/\/[^\s()"'<>]+/)?.[0];
+}
+
+/**
+ * Evaluates a request URL against the current feeds and configuration, the
+ * same way the proxy would, without sending the request.
+ *
+ * @param {string} targetUrl
+ * @param {{detectEcosystem?: boolean}} [options] - Without detectEcosystem, the URL is evaluated for the current ecosystem only, as a running proxy does
+ * @returns {Promise<ReplayResult>}
+ */
+export async function evaluateRequest(
+  targetUrl,
+  { detectEcosystem = true } = {}
+) {
+  const url = new URL(targetUrl);
+  /** @type {RuleResult[]} */
+  const rules = [];
+  const unidentified = {
+    url: targetUrl,
+    ecosystem: undefined,
+    packageName: undefined,
+    version: undefined,
+  };
+
+  const hostDenied = isDeniedHost(url.hostname);
+  rules.push({
+    name: "host deny list",
+    outcome: hostDenied ? "blocked" : "passed",
+    input: { host: url.hostname },
+  });
+  if (hostDenied) {
+    return {
+      ...unidentified,
+      blockResponse: `403 Forbidden - ${url.hostname} is a denied host`,
+      rules,
+    };
+  }
+
+  let interceptor = undefined;
+  if (url.protocol === "https:") {
+    interceptor = detectEcosystem
+      ? findInterceptor(targetUrl)
+      : createInterceptorForUrl(targetUrl);
+  }
+  if (!interceptor) {
+    rules.push({
+      name: "interception",
+      outcome:
+        url.protocol === "https:"
+          ? "not a known registry, tunneled"
+          : "plain HTTP is forwarded without inspection",
+    });
+    return { ...unidentified, blockResponse: undefined, rules };
+  }
+  rules.push({ name: "interception", outcome: "inspected" });
+
+  /** @type {Set<string>} */
+  const firedEvents = new Set();
+  /** @type {string[]} */
+  let advisories = [];
+  for (const rule of INTERCEPTOR_RULES) {
+    interceptor.on(rule.event, (/** @type {any} */ event) => {
+      firedEvents.add(rule.event);
+      advisories = event.advisories ?? advisories;
+    });
+  }
+
+  const handler = await interceptor.handleRequest(targetUrl);
+  const blockResponse = handler.blockResponse;
+  const inputs = getRuleInputs(handler.packageName, handler.version);
+
+  for (const rule of INTERCEPTOR_RULES) {
+    let outcome = "passed";
+    if (firedEvents.has(rule.event)) {
+      outcome =
+        advisories.length > 0
+          ? `blocked (advisories: ${advisories.join(", ")})`
+          : "blocked";
+    } else if (blockResponse) {
+      outcome = "passed or not evaluated";
+    }
+    rules.push({ name: rule.name, outcome, input: inputs[rule.name] });
+  }
+
+  if (!blockResponse && handler.modifiesResponse()) {
+    rules.push({
+      name: "metadata filtering",
+      outcome: "the response would be filtered before it reaches the client",
+    });
+  }
+
+  return {
+    url: targetUrl,
+    ecosystem: getEcoSystem(),
+    packageName: handler.packageName,
+    version: handler.version,
+    blockResponse: blockResponse
+      ? `${blockResponse.statusCode} ${blockResponse.message}`
+      : undefined,
+    rules,
+  };
+}
+
+/**
+ * What the interceptor rules are evaluated on. The age of a version is only
+ * known once its metadata passed through the proxy.
+ *
+ * @param {string | undefined} packageName
+ * @param {string | undefined} version
+ * @returns {Record<string, Record<string, string | number | boolean | null>>}
+ */
+function getRuleInputs(packageName, version) {
+  const publishedAt = getPublishTime(packageName, version);
+
+  return {
+    "malware database": { feed_version: getFeedVersion() ?? null },
+    "minimum package age": {
+      enabled: !skipMinimumPackageAge(),
+      minimum_age_hours: getMinimumPackageAgeHours(),
+      age_hours:
+        publishedAt === undefined
+          ? null
+          : Math.round((Date.now() - publishedAt) / 360_000) / 10,
+    },
+  };
+}
+
+/**
+ * Handles `safe-chain replay <URL>` and `safe-chain replay --audit-line=<LINE>`:
+ * prints the decision for the request and the outcome of each rule.
+ *
+ * @param {string[]} args - The arguments after `replay`
+ * @returns {Promise<number>} - 0 when the request is allowed, 1 when it is blocked
+ */
+export async function replay(args) {
+  const auditLine = args
+    .find((arg) => arg.startsWith(AUDIT_LINE_ARG))
+    ?.slice(AUDIT_LINE_ARG.length);
+  const targetUrl =
+    auditLine !== undefined
+      ? getUrlFromAuditLine(auditLine)
+      : args.find((arg) => !arg.startsWith("--"));
+
+  if (!targetUrl || !isHttpUrl(targetUrl)) {
+    ui.writeError(
+      auditLine !== undefined
+        ? "The audit line does not contain a request URL."
+        : "Usage: safe-chain replay <URL> or safe-chain replay --audit-line=<LINE>"
+    );
+    return 1;
+  }
+
+  /** @type {ReplayResult} */
+  let result;
+  try {
+    await loadProxyPolicy();
+    result = await evaluateRequest(targetUrl);
+  } catch (/** @type {any} */ error) {
+    ui.writeError(`Failed to evaluate ${targetUrl}: ${error.message}`);
+    return getExitCodeForError(error);
+  }
+
+  ui.writeInformation(`Replaying ${result.url}`);
+  for (const rule of result.rules) {
+    ui.writeInformation(` - ${rule.name}: ${rule.outcome}`);
+  }
+  ui.writeInformation(
+    result.blockResponse
+      ? `Decision: blocked (${result.blockResponse})`
+      : "Decision: allowed"
+  );
+
+  return result.blockResponse ? 1 : 0;
+}
+
+/**
+ * The proxy picks the interceptor for the ecosystem of the package manager it
+ * wraps. Without a package manager, the ecosystem follows from the URL.
+ *
+ * @param {string} targetUrl
+ * @returns {import("./interceptors/interceptorBuilder.js").Interceptor | undefined}
+ */
+function findInterceptor(targetUrl) {
+  for (const ecosystem of [ECOSYSTEM_JS, ECOSYSTEM_PY]) {
+    setEcoSystem(ecosystem);
+    const interceptor = createInterceptorForUrl(targetUrl);
+    if (interceptor) {
+      return interceptor;
+    }
+  }
+
+  return undefined;
+}
+
+/**
+ * @param {string} value
+ * @returns {boolean}
+ */
+function isHttpUrl(value) {
+  try {
+    const { protocol } = new URL(value);
+    return protocol === "https:" || protocol === "http:";
+  } catch {
+    return false;
+  }
+}
@@ -0,0 +1,139 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("replay", async () => {
+  const output = [];
+
+  mock.module("../api/aikido.js", {
+    namedExports: {
+      fetchMalwareDatabase: async () => ({
+        malwareDatabase: [
+          {
+            package_name: "safe-chain-test",
+            version: "0.0.1-security",
+            reason: "MALWARE",
+            advisories: ["GHSA-2xxv-wcp8-9fr8"],
+          },
+        ],
+        version: undefined,
+      }),
+      fetchMalwareDatabaseVersion: async () => undefined,
+      fetchMalwareFeed: async () => [],
+      fetchNewPackagesList: async () => ({
+        newPackagesList: [],
+        version: undefined,
+      }),
+      fetchNewPackagesListVersion: async () => undefined,
+      fetchDeniedHosts: async () => ["evil.example.com"],
+      fetchFlaggedMaintainers: async () => [],
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeInformation: (message) => output.push(message),
+        writeWarning: (message) => output.push(message),
+        writeError: (message) => output.push(message),
+        writeDecision: (message) => output.push(message),
+        emptyLine: () => {},
+      },
+    },
+  });
+
+  const { evaluateRequest, getUrlFromAuditLine, replay } = await import(
+    "./replay.js"
+  );
+  const { loadProxyPolicy } = await import("./registryProxy.js");
+
+  beforeEach(() => {
+    output.length = 0;
+  });
+
+  it("reports the rule that blocks a malicious download", async () => {
+    const exitCode = await replay([
+      "https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz",
+    ]);
+
+    assert.strictEqual(exitCode, 1);
+    assert.ok(output.includes(" - host deny list: passed"));
+    assert.ok(
+      output.includes(
+        " - malware database: blocked (advisories: GHSA-2xxv-wcp8-9fr8)"
+      )
+    );
+    assert.strictEqual(
+      output.at(-1),
+      "Decision: blocked (403 Forbidden - blocked by safe-chain (advisories: GHSA-2xxv-wcp8-9fr8))"
+    );
+  });
+
+  it("allows a clean download", async () => {
+    const exitCode = await replay([
+      "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+    ]);
+
+    assert.strictEqual(exitCode, 0);
+    assert.ok(output.includes(" - malware database: passed"));
+    assert.strictEqual(output.at(-1), "Decision: allowed");
+  });
+
+  it("blocks denied hosts before any other rule", async () => {
+    const exitCode = await replay(["https://evil.example.com/payload.tgz"]);
+
+    assert.strictEqual(exitCode, 1);
+    assert.deepStrictEqual(output.slice(1), [
+      " - host deny list: blocked",
+      "Decision: blocked (403 Forbidden - evil.example.com is a denied host)",
+    ]);
+  });
+
+  it("reports the package and what each rule was evaluated on", async () => {
+    await loadProxyPolicy();
+
+    const result = await evaluateRequest(
+      "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+      { detectEcosystem: false }
+    );
+
+    assert.strictEqual(result.ecosystem, "js");
+    assert.strictEqual(result.packageName, "lodash");
+    assert.strictEqual(result.version, "4.17.21");
+    assert.deepStrictEqual(result.rules[0].input, {
+      host: "registry.npmjs.org",
+    });
+    const ageRule = result.rules.find(
+      (rule) => rule.name === "minimum package age"
+    );
+    assert.strictEqual(ageRule?.input?.age_hours, null);
+  });
+
+  it("replays the URL of an audit line", async () => {
+    const exitCode = await replay([
+      "--audit-line=[2026-10-14T09:12:44.120Z] [decision]  - safe-chain-test@0.0.1-security (https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz)",
+    ]);
+
+    assert.strictEqual(exitCode, 1);
+    assert.strictEqual(
+      output[0],
+      "Replaying https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz"
+    );
+  });
+
+  it("finds the URL in JSON log lines", () => {
+    const line = JSON.stringify({
+      timestamp: "2026-10-14T09:12:44.120Z",
+      level: "verbose",
+      message: "Safe-chain: Blocking request to https://pypi.org/simple/foo/",
+    });
+
+    assert.strictEqual(getUrlFromAuditLine(line), "https://pypi.org/simple/foo/");
+    assert.strictEqual(getUrlFromAuditLine("no url here"), undefined);
+  });
+
+  it("prints usage without a URL", async () => {
+    assert.strictEqual(await replay([]), 1);
+    assert.match(output[0], /Usage: safe-chain replay <URL>/);
+  });
+});
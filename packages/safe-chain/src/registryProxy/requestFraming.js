@@ -0,0 +1,59 @@
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * Parser errors for a request whose body length is ambiguous: Content-Length
+ * together with Transfer-Encoding, several Content-Length values, or a
+ * Transfer-Encoding that does not end in chunked. Two servers can frame such a
+ * request differently, which is what request smuggling relies on.
+ */
+const AMBIGUOUS_FRAMING_ERRORS = new Set([
+  "HPE_UNEXPECTED_CONTENT_LENGTH",
+  "HPE_INVALID_CONTENT_LENGTH",
+  "HPE_INVALID_TRANSFER_ENCODING",
+]);
+
+/**
+ * Answers a request Node could not parse, as Node does without a listener, and
+ * reports requests with ambiguous framing. Those never reach the handlers, so
+ * they are never forwarded: requests that are forwarded are framed by Node
+ * again, with Transfer-Encoding removed as a hop-by-hop header.
+ *
+ * @param {Error & {code?: string}} err
+ * @param {import("stream").Duplex} socket
+ * @returns {void}
+ */
+export function handleClientError(err, socket) {
+  const netSocket = /** @type {import("net").Socket & {_httpMessage?: any}} */ (
+    socket
+  );
+  if (err.code && AMBIGUOUS_FRAMING_ERRORS.has(err.code)) {
+    ui.writeWarning(
+      `Safe-chain: request.ambiguous-framing - rejected a request from ${netSocket.remoteAddress ?? "unknown"} with conflicting Content-Length/Transfer-Encoding headers (${err.code})`
+    );
+  }
+
+  // Like Node, only answer when no response on this connection has started
+  if (netSocket.writable && !netSocket._httpMessage?.headersSent) {
+    netSocket.write(
+      `HTTP/1.1 ${getErrorStatus(err.code)}\r\nConnection: close\r\n\r\n`
+    );
+  }
+  netSocket.destroy(err);
+}
+
+/**
+ * @param {string | undefined} code
+ * @returns {string}
+ */
+function getErrorStatus(code) {
+  switch (code) {
+    case "HPE_HEADER_OVERFLOW":
+      return "431 Request Header Fields Too Large";
+    case "HPE_CHUNK_EXTENSIONS_OVERFLOW":
+      return "413 Payload Too Large";
+    case "ERR_HTTP_REQUEST_TIMEOUT":
+      return "408 Request Timeout";
+    default:
+      return "400 Bad Request";
+  }
+}
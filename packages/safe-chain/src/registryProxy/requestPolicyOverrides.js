@@ -0,0 +1,66 @@
+import { getHeaderValueAsString } from "./http-utils.js";
+
+/**
+ * Per-request policy options a wrapper can pass to the proxy with
+ * X-Safe-Chain-* request headers. Any client of the proxy can send them, so
+ * they only apply when they make the configured policy stricter.
+ *
+ * @typedef {Object} RequestPolicyOverrides
+ * @property {string | undefined} [minimumPackageAgeHours]
+ * @property {boolean | undefined} [skipMinimumPackageAge]
+ */
+
+const SAFE_CHAIN_HEADER_PREFIX = "x-safe-chain-";
+
+export const MINIMUM_PACKAGE_AGE_HOURS_HEADER =
+  SAFE_CHAIN_HEADER_PREFIX + "minimum-package-age-hours";
+export const SKIP_MINIMUM_PACKAGE_AGE_HEADER =
+  SAFE_CHAIN_HEADER_PREFIX + "skip-minimum-package-age";
+
+/**
+ * Values are validated where they are resolved, an invalid value falls back to the configured setting.
+ *
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {RequestPolicyOverrides}
+ */
+export function parseRequestPolicyOverrides(headers) {
+  return {
+    minimumPackageAgeHours: getHeaderValueAsString(
+      headers,
+      MINIMUM_PACKAGE_AGE_HOURS_HEADER
+    )?.trim(),
+    skipMinimumPackageAge: parseBooleanHeader(
+      getHeaderValueAsString(headers, SKIP_MINIMUM_PACKAGE_AGE_HEADER)
+    ),
+  };
+}
+
+/**
+ * Removes every X-Safe-Chain-* header, so they never reach the registry.
+ *
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @returns {NodeJS.Dict<string | string[]>}
+ */
+export function stripSafeChainHeaders(headers) {
+  for (const headerName of Object.keys(headers)) {
+    if (headerName.toLowerCase().startsWith(SAFE_CHAIN_HEADER_PREFIX)) {
+      delete headers[headerName];
+    }
+  }
+  return headers;
+}
+
+/**
+ * @param {string | undefined} value
+ * @returns {boolean | undefined}
+ */
+function parseBooleanHeader(value) {
+  const normalizedValue = value?.trim().toLowerCase();
+  if (normalizedValue === "true" || normalizedValue === "1") {
+    return true;
+  }
+  if (normalizedValue === "false" || normalizedValue === "0") {
+    return false;
+  }
+  return undefined;
+}
@@ -0,0 +1,92 @@
+import { describe, it, afterEach } from "node:test";
+import assert from "node:assert";
+import {
+  parseRequestPolicyOverrides,
+  stripSafeChainHeaders,
+} from "./requestPolicyOverrides.js";
+import {
+  getMinimumPackageAgeHours,
+  skipMinimumPackageAge,
+} from "../config/settings.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+
+describe("requestPolicyOverrides", () => {
+  afterEach(() => {
+    initializeCliArguments([]);
+  });
+
+  it("parses the per-request policy headers", () => {
+    const overrides = parseRequestPolicyOverrides({
+      "x-safe-chain-minimum-package-age-hours": " 500 ",
+      "x-safe-chain-skip-minimum-package-age": "true",
+    });
+
+    assert.deepStrictEqual(overrides, {
+      minimumPackageAgeHours: "500",
+      skipMinimumPackageAge: true,
+    });
+  });
+
+  it("leaves overrides undefined when the headers are missing or unrecognized", () => {
+    const overrides = parseRequestPolicyOverrides({
+      "x-safe-chain-skip-minimum-package-age": "maybe",
+    });
+
+    assert.strictEqual(overrides.minimumPackageAgeHours, undefined);
+    assert.strictEqual(overrides.skipMinimumPackageAge, undefined);
+  });
+
+  it("applies a higher minimum package age than the configured one", () => {
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=24"]);
+
+    const overrides = parseRequestPolicyOverrides({
+      "x-safe-chain-minimum-package-age-hours": "500",
+    });
+
+    assert.strictEqual(getMinimumPackageAgeHours(overrides), 500);
+    assert.strictEqual(getMinimumPackageAgeHours(), 24);
+  });
+
+  it("does not let a client weaken the configured policy", () => {
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=24"]);
+
+    const overrides = parseRequestPolicyOverrides({
+      "x-safe-chain-minimum-package-age-hours": "0",
+      "x-safe-chain-skip-minimum-package-age": "true",
+    });
+
+    assert.strictEqual(getMinimumPackageAgeHours(overrides), 24);
+    assert.strictEqual(skipMinimumPackageAge(overrides), false);
+  });
+
+  it("keeps the minimum package age check when a request asks to", () => {
+    initializeCliArguments(["--safe-chain-skip-minimum-package-age"]);
+
+    const overrides = parseRequestPolicyOverrides({
+      "x-safe-chain-skip-minimum-package-age": "false",
+    });
+
+    assert.strictEqual(skipMinimumPackageAge(overrides), false);
+    assert.strictEqual(skipMinimumPackageAge(), true);
+  });
+
+  it("falls back to the configured value for invalid header values", () => {
+    initializeCliArguments(["--safe-chain-minimum-package-age-hours=24"]);
+
+    const overrides = parseRequestPolicyOverrides({
+      "x-safe-chain-minimum-package-age-hours": "-5",
+    });
+
+    assert.strictEqual(getMinimumPackageAgeHours(overrides), 24);
+  });
+
+  it("strips every X-Safe-Chain-* header", () => {
+    const headers = stripSafeChainHeaders({
+      accept: "application/json",
+      "x-safe-chain-minimum-package-age-hours": "500",
+      "X-Safe-Chain-Future-Option": "value",
+    });
+
+    assert.deepStrictEqual(headers, { accept: "application/json" });
+  });
+});
@@ -0,0 +1,445 @@
+import fs from "fs";
+import path from "path";
+import crypto from "crypto";
+import { getCacheDir, getCacheMaxSizeGb } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * @typedef {Object} CachedResponse
+ * @property {string} url
+ * @property {number} statusCode
+ * @property {NodeJS.Dict<string | string[]>} headers
+ * @property {number} storedAt - Unix timestamp (milliseconds)
+ */
+
+const state = {
+  hits: 0,
+  misses: 0,
+};
+
+const BYTES_PER_GB = 1024 * 1024 * 1024;
+
+const REVALIDATION_UPDATED_HEADERS = [
+  "cache-control",
+  "expires",
+  "etag",
+  "last-modified",
+  "date",
+];
+
+/**
+ * Returns the cache key for a request, or undefined when the request must not be
+ * served from or stored in the cache. Only anonymous GET requests for the full
+ * body are cached: responses for authenticated requests can differ per token,
+ * and range requests are answered with a part of the body.
+ * The key includes the headers that registries vary metadata responses on.
+ *
+ * @param {string | undefined} method
+ * @param {string} targetUrl
+ * @param {NodeJS.Dict<string | string[]> | undefined} headers
+ * @returns {string | undefined}
+ */
+export function getResponseCacheKey(method, targetUrl, headers) {
+  if (!getCacheDir() || method !== "GET") {
+    return undefined;
+  }
+
+  if (headers?.authorization || headers?.range) {
+    return undefined;
+  }
+
+  if (parseCacheControl(headers?.["cache-control"])["no-store"]) {
+    return undefined;
+  }
+
+  return crypto
+    .createHash("sha256")
+    .update(
+      [
+        targetUrl,
+        headerValue(headers?.accept),
+        headerValue(headers?.["accept-encoding"]),
+      ].join("\n")
+    )
+    .digest("hex");
+}
+
+/**
+ * @param {string} key
+ * @returns {CachedResponse | undefined}
+ */
+export function readCachedResponse(key) {
+  try {
+    const metadataPath = getMetadataPath(key);
+    if (!fs.existsSync(metadataPath) || !fs.existsSync(getBodyPath(key))) {
+      return undefined;
+    }
+
+    return JSON.parse(fs.readFileSync(metadataPath, "utf8"));
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * Opens the cached body and marks the entry as recently used.
+ *
+ * @param {string} key
+ * @returns {import("stream").Readable}
+ */
+export function openCachedResponseBody(key) {
+  const bodyPath = getBodyPath(key);
+  try {
+    const now = new Date();
+    fs.utimesSync(bodyPath, now, now);
+  } catch {
+    // Only affects eviction order
+  }
+  return fs.createReadStream(bodyPath);
+}
+
+/**
+ * @param {CachedResponse} entry
+ * @param {number} [now]
+ * @returns {boolean}
+ */
+export function isFresh(entry, now = Date.now()) {
+  const cacheControl = parseCacheControl(entry.headers["cache-control"]);
+
+  if (cacheControl["no-cache"]) {
+    return false;
+  }
+
+  if (cacheControl.immutable) {
+    return true;
+  }
+
+  const maxAge = Number(cacheControl["max-age"]);
+  if (cacheControl["max-age"] !== undefined && !Number.isNaN(maxAge)) {
+    return entry.storedAt + maxAge * 1000 > now;
+  }
+
+  const expires = Date.parse(headerValue(entry.headers.expires));
+  if (!Number.isNaN(expires)) {
+    return expires > now;
+  }
+
+  return false;
+}
+
+/**
+ * @param {CachedResponse} entry
+ * @returns {NodeJS.Dict<string>}
+ */
+export function getRevalidationHeaders(entry) {
+  /** @type {NodeJS.Dict<string>} */
+  const headers = {};
+  const etag = headerValue(entry.headers.etag);
+  const lastModified = headerValue(entry.headers["last-modified"]);
+
+  if (etag) {
+    headers["if-none-match"] = etag;
+  }
+  if (lastModified) {
+    headers["if-modified-since"] = lastModified;
+  }
+
+  return headers;
+}
+
+/**
+ * @param {number} statusCode
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @returns {boolean}
+ */
+export function isCacheableResponse(statusCode, headers) {
+  if (statusCode !== 200) {
+    return false;
+  }
+
+  const cacheControl = parseCacheControl(headers["cache-control"]);
+  if (cacheControl["no-store"]) {
+    return false;
+  }
+
+  const contentLength = Number(headerValue(headers["content-length"]));
+  if (!Number.isNaN(contentLength) && contentLength > getMaxSizeBytes()) {
+    return false;
+  }
+
+  // Without validators or freshness information the entry could never be reused
+  return Boolean(
+    headers.etag ||
+      headers["last-modified"] ||
+      headers.expires ||
+      cacheControl["max-age"] !== undefined ||
+      cacheControl.immutable
+  );
+}
+
+/**
+ * Streams a response body into the cache. The body goes to a temporary file in
+ * the cache directory that is moved into place once complete, so an entry is
+ * never held in memory and a partial body is never served. Bodies larger than
+ * the cache are dropped as soon as they exceed it.
+ *
+ * @param {string} key
+ * @param {string} targetUrl
+ * @param {number} statusCode
+ * @param {NodeJS.Dict<string | string[]>} headers
+ * @param {import("stream").Readable} body
+ * @returns {Promise<void>} - Settles once the entry was stored or dropped
+ */
+export function writeCachedResponse(key, targetUrl, statusCode, headers, body) {
+  return new Promise((resolve) => {
+    const maxSizeBytes = getMaxSizeBytes();
+    const bodyPath = getBodyPath(key);
+    const tempPath = `${bodyPath}.${crypto.randomUUID()}.tmp`;
+    /** @type {fs.WriteStream} */
+    let file;
+    try {
+      getCacheDirectory();
+      file = fs.createWriteStream(tempPath);
+    } catch (/** @type {any} */ error) {
+      logWriteFailure(targetUrl, error);
+      resolve();
+      return;
+    }
+
+    let size = 0;
+    let ended = false;
+    let settled = false;
+
+    /**
+     * @param {Error} [error]
+     */
+    function drop(error) {
+      if (settled) {
+        return;
+      }
+      settled = true;
+      if (error) {
+        logWriteFailure(targetUrl, error);
+      }
+      file.destroy();
+      fs.rm(tempPath, { force: true }, () => resolve());
+    }
+
+    file.on("error", drop);
+    body.on("data", (chunk) => {
+      if (ended || settled) {
+        return;
+      }
+      size += chunk.byteLength;
+      if (size > maxSizeBytes) {
+        drop();
+        return;
+      }
+      file.write(chunk);
+    });
+    body.on("error", () => drop());
+    // An aborted upstream response closes without ending
+    body.on("close", () => {
+      if (!ended) {
+        drop();
+      }
+    });
+    body.on("end", () => {
+      if (settled || /** @type {any} */ (body).complete === false) {
+        drop();
+        return;
+      }
+      ended = true;
+
+      file.end(() => {
+        if (settled) {
+          return;
+        }
+        settled = true;
+
+        /** @type {NodeJS.Dict<string | string[]>} */
+        const storedHeaders = { ...headers };
+        delete storedHeaders["transfer-encoding"];
+        delete storedHeaders.connection;
+        storedHeaders["content-length"] = String(size);
+
+        /** @type {CachedResponse} */
+        const entry = {
+          url: targetUrl,
+          statusCode,
+          headers: storedHeaders,
+          storedAt: Date.now(),
+        };
+
+        try {
+          // The body is moved first, entries without metadata are never served
+          fs.renameSync(tempPath, bodyPath);
+          fs.writeFileSync(getMetadataPath(key), JSON.stringify(entry));
+          enforceSizeLimit();
+        } catch (/** @type {any} */ error) {
+          logWriteFailure(targetUrl, error);
+          fs.rmSync(tempPath, { force: true });
+        }
+        resolve();
+      });
+    });
+  });
+}
+
+/**
+ * @param {string} targetUrl
+ * @param {Error} error
+ * @returns {void}
+ */
+function logWriteFailure(targetUrl, error) {
+  ui.writeVerbose(
+    `Safe-chain: Failed to write ${targetUrl} to the response cache: ${error.message}`
+  );
+}
+
+/**
+ * Updates a cached entry after the upstream confirmed it is still valid (304 Not Modified).
+ *
+ * @param {string} key
+ * @param {CachedResponse} entry
+ * @param {NodeJS.Dict<string | string[]>} revalidationHeaders - Headers of the 304 response
+ * @returns {CachedResponse}
+ */
+export function refreshCachedResponse(key, entry, revalidationHeaders) {
+  const headers = { ...entry.headers };
+  for (const name of REVALIDATION_UPDATED_HEADERS) {
+    if (revalidationHeaders[name] !== undefined) {
+      headers[name] = revalidationHeaders[name];
+    }
+  }
+
+  const refreshedEntry = { ...entry, headers, storedAt: Date.now() };
+
+  try {
+    fs.writeFileSync(getMetadataPath(key), JSON.stringify(refreshedEntry));
+  } catch {
+    // The entry will be revalidated again next time
+  }
+
+  return refreshedEntry;
+}
+
+/**
+ * @param {string} targetUrl
+ * @returns {void}
+ */
+export function recordCacheHit(targetUrl) {
+  state.hits++;
+  ui.writeVerbose(`Safe-chain: Response cache hit for ${targetUrl}`);
+}
+
+/**
+ * @param {string} targetUrl
+ * @returns {void}
+ */
+export function recordCacheMiss(targetUrl) {
+  state.misses++;
+  ui.writeVerbose(`Safe-chain: Response cache miss for ${targetUrl}`);
+}
+
+/**
+ * @returns {{hits: number, misses: number}}
+ */
+export function getResponseCacheStats() {
+  return { hits: state.hits, misses: state.misses };
+}
+
+/**
+ * Evicts the least recently used entries until the cache fits the configured size.
+ * @returns {void}
+ */
+function enforceSizeLimit() {
+  const cacheDir = getCacheDirectory();
+  const bodies = fs
+    .readdirSync(cacheDir)
+    .filter((file) => file.endsWith(".body"))
+    .map((file) => {
+      const stats = fs.statSync(path.join(cacheDir, file));
+      return {
+        key: file.slice(0, -".body".length),
+        size: stats.size,
+        mtime: stats.mtimeMs,
+      };
+    })
+    .sort((a, b) => a.mtime - b.mtime);
+
+  let totalSize = bodies.reduce((sum, entry) => sum + entry.size, 0);
+  const maxSize = getMaxSizeBytes();
+
+  for (const entry of bodies) {
+    if (totalSize <= maxSize) {
+      break;
+    }
+    fs.rmSync(getMetadataPath(entry.key), { force: true });
+    fs.rmSync(getBodyPath(entry.key), { force: true });
+    totalSize -= entry.size;
+  }
+}
+
+/**
+ * @param {string | string[] | undefined} value
+ * @returns {Record<string, string | true>}
+ */
+function parseCacheControl(value) {
+  /** @type {Record<string, string | true>} */
+  const directives = {};
+
+  for (const part of headerValue(value).split(",")) {
+    const [name, directiveValue] = part.trim().split("=");
+    if (name) {
+      directives[name.toLowerCase()] = directiveValue?.replace(/"/g, "") ?? true;
+    }
+  }
+
+  return directives;
+}
+
+/**
+ * @param {string | string[] | undefined} value
+ * @returns {string}
+ */
+function headerValue(value) {
+  if (Array.isArray(value)) {
+    return value.join(", ");
+  }
+  return value ?? "";
+}
+
+/**
+ * @returns {number}
+ */
+function getMaxSizeBytes() {
+  return getCacheMaxSizeGb() * BYTES_PER_GB;
+}
+
+/**
+ * @returns {string}
+ */
+function getCacheDirectory() {
+  const cacheDir = /** @type {string} */ (getCacheDir());
+  if (!fs.existsSync(cacheDir)) {
+    fs.mkdirSync(cacheDir, { recursive: true });
+  }
+  return cacheDir;
+}
+
+/**
+ * @param {string} key
+ * @returns {string}
+ */
+function getMetadataPath(key) {
+  return path.join(/** @type {string} */ (getCacheDir()), `${key}.json`);
+}
+
+/**
+ * @param {string} key
+ * @returns {string}
+ */
+function getBodyPath(key) {
+  return path.join(/** @type {string} */ (getCacheDir()), `${key}.body`);
+}
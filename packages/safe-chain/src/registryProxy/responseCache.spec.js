@@ -0,0 +1,205 @@
+import { describe, it, mock, beforeEach, afterEach } from "node:test";
+import assert from "node:assert";
+import fs from "node:fs";
+import os from "node:os";
+import path from "node:path";
+import { Readable } from "node:stream";
+
+describe("responseCache", async () => {
+  let tmpDir, cacheDir;
+  let cacheMaxSizeGb = 1;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getCacheDir: () => cacheDir,
+      getCacheMaxSizeGb: () => cacheMaxSizeGb,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+      },
+    },
+  });
+
+  const {
+    getResponseCacheKey,
+    readCachedResponse,
+    writeCachedResponse,
+    refreshCachedResponse,
+    isFresh,
+    isCacheableResponse,
+    getRevalidationHeaders,
+  } = await import("./responseCache.js");
+
+  const url = "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz";
+
+  beforeEach(() => {
+    tmpDir = fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-cache-"));
+    cacheDir = tmpDir;
+    cacheMaxSizeGb = 1;
+  });
+
+  afterEach(() => {
+    fs.rmSync(tmpDir, { recursive: true, force: true });
+  });
+
+  describe("getResponseCacheKey", () => {
+    it("returns undefined when no cache directory is configured", () => {
+      cacheDir = undefined;
+      assert.strictEqual(getResponseCacheKey("GET", url, {}), undefined);
+    });
+
+    it("only caches anonymous GET requests", () => {
+      assert.ok(getResponseCacheKey("GET", url, {}));
+      assert.strictEqual(getResponseCacheKey("POST", url, {}), undefined);
+      assert.strictEqual(
+        getResponseCacheKey("GET", url, { authorization: "Bearer token" }),
+        undefined
+      );
+    });
+
+    it("does not cache range requests", () => {
+      assert.strictEqual(
+        getResponseCacheKey("GET", url, { range: "bytes=1024-" }),
+        undefined
+      );
+    });
+
+    it("varies the key on the accept header", () => {
+      const fullMetadata = getResponseCacheKey("GET", url, {
+        accept: "application/json",
+      });
+      const abbreviatedMetadata = getResponseCacheKey("GET", url, {
+        accept: "application/vnd.npm.install-v1+json",
+      });
+
+      assert.notStrictEqual(fullMetadata, abbreviatedMetadata);
+    });
+  });
+
+  describe("isFresh", () => {
+    it("uses max-age relative to the time the entry was stored", () => {
+      const entry = {
+        headers: { "cache-control": "public, max-age=300" },
+        storedAt: 1_000_000,
+      };
+
+      assert.strictEqual(isFresh(entry, 1_000_000 + 299_000), true);
+      assert.strictEqual(isFresh(entry, 1_000_000 + 301_000), false);
+    });
+
+    it("treats immutable responses as fresh and no-cache responses as stale", () => {
+      assert.strictEqual(
+        isFresh({ headers: { "cache-control": "immutable" }, storedAt: 0 }),
+        true
+      );
+      assert.strictEqual(
+        isFresh({
+          headers: { "cache-control": "no-cache, max-age=300" },
+          storedAt: Date.now(),
+        }),
+        false
+      );
+    });
+  });
+
+  it("does not cache responses without validators or freshness information", () => {
+    assert.strictEqual(isCacheableResponse(200, {}), false);
+    assert.strictEqual(isCacheableResponse(200, { etag: '"abc"' }), true);
+    assert.strictEqual(isCacheableResponse(404, { etag: '"abc"' }), false);
+    assert.strictEqual(
+      isCacheableResponse(200, { etag: '"abc"', "cache-control": "no-store" }),
+      false
+    );
+  });
+
+  it("stores and reads back responses", async () => {
+    const key = getResponseCacheKey("GET", url, {});
+    await writeCachedResponse(
+      key,
+      url,
+      200,
+      { etag: '"abc"', "transfer-encoding": "chunked" },
+      Readable.from([Buffer.from("tar"), Buffer.from("ball")])
+    );
+
+    const entry = readCachedResponse(key);
+
+    assert.strictEqual(entry.url, url);
+    assert.strictEqual(entry.headers["content-length"], "7");
+    assert.strictEqual(entry.headers["transfer-encoding"], undefined);
+    assert.deepStrictEqual(getRevalidationHeaders(entry), {
+      "if-none-match": '"abc"',
+    });
+  });
+
+  it("refreshes freshness information after revalidation", async () => {
+    const key = getResponseCacheKey("GET", url, {});
+    await writeCachedResponse(key, url, 200, { etag: '"abc"' }, body("body"));
+
+    const refreshed = refreshCachedResponse(key, readCachedResponse(key), {
+      "cache-control": "max-age=60",
+    });
+
+    assert.strictEqual(isFresh(refreshed), true);
+    assert.strictEqual(
+      readCachedResponse(key).headers["cache-control"],
+      "max-age=60"
+    );
+  });
+
+  it("evicts the least recently used entries when the cache is full", async () => {
+    // 10 bytes
+    cacheMaxSizeGb = 10 / (1024 * 1024 * 1024);
+
+    const oldKey = getResponseCacheKey("GET", `${url}?old`, {});
+    await writeCachedResponse(oldKey, url, 200, { etag: '"1"' }, body("123456"));
+    const past = new Date(Date.now() - 60_000);
+    fs.utimesSync(path.join(cacheDir, `${oldKey}.body`), past, past);
+
+    const newKey = getResponseCacheKey("GET", `${url}?new`, {});
+    await writeCachedResponse(newKey, url, 200, { etag: '"2"' }, body("123456"));
+
+    assert.strictEqual(readCachedResponse(oldKey), undefined);
+    assert.ok(readCachedResponse(newKey));
+  });
+
+  it("drops a body larger than the cache without keeping it", async () => {
+    // 10 bytes
+    cacheMaxSizeGb = 10 / (1024 * 1024 * 1024);
+
+    const key = getResponseCacheKey("GET", url, {});
+    await writeCachedResponse(
+      key,
+      url,
+      200,
+      { etag: '"abc"' },
+      Readable.from([Buffer.from("123456"), Buffer.from("789012")])
+    );
+
+    assert.strictEqual(readCachedResponse(key), undefined);
+    assert.deepStrictEqual(fs.readdirSync(cacheDir), []);
+  });
+
+  it("does not store a body that ends early", async () => {
+    const key = getResponseCacheKey("GET", url, {});
+    const aborted = new Readable({ read() {} });
+    const stored = writeCachedResponse(key, url, 200, { etag: '"1"' }, aborted);
+    aborted.push(Buffer.from("partial"));
+    aborted.destroy();
+    await stored;
+
+    assert.strictEqual(readCachedResponse(key), undefined);
+    assert.deepStrictEqual(fs.readdirSync(cacheDir), []);
+  });
+
+  /**
+   * @param {string} content
+   */
+  function body(content) {
+    return Readable.from([Buffer.from(content)]);
+  }
+});
@@ -0,0 +1,141 @@
+import zlib from "zlib";
+import { getCompressResponses } from "../config/settings.js";
+
+// zstd is only available from Node.js 22.15 (and in its type definitions)
+const zlibWithZstd = /** @type {any} */ (zlib);
+
+/** Bodies smaller than this gain too little from compression */
+const MIN_COMPRESSIBLE_BYTES = 1024;
+
+/**
+ * Package metadata and indexes compress well. Tarballs, wheels and other
+ * archives are already compressed and are sent as they are.
+ */
+const COMPRESSIBLE_CONTENT_TYPES = [
+  /^text\//,
+  /^application\/json$/,
+  /^application\/vnd\.[\w.-]+\+json$/,
+  /^application\/xml$/,
+  /^application\/javascript$/,
+];
+
+/**
+ * @typedef {"zstd" | "gzip"} ResponseEncoding
+ */
+
+/**
+ * Picks the encoding for a response to the client from its Accept-Encoding
+ * header: the one with the highest quality value that Node.js supports, and
+ * zstd over gzip when both are equally preferred. A quality value of 0 refuses
+ * an encoding. Undefined when compression is off or the client accepts
+ * neither.
+ *
+ * @param {string | string[] | undefined} acceptEncoding
+ * @returns {ResponseEncoding | undefined}
+ */
+export function negotiateResponseEncoding(acceptEncoding) {
+  if (!getCompressResponses() || !acceptEncoding) {
+    return undefined;
+  }
+
+  /** @type {Map<string, number>} */
+  const accepted = new Map();
+  for (const entry of String(acceptEncoding).split(",")) {
+    const [coding, ...params] = entry.trim().toLowerCase().split(";");
+    const quality = params
+      .map((param) => /^\s*q=([\d.]+)\s*$/.exec(param)?.[1])
+      .find((value) => value !== undefined);
+    accepted.set(coding.trim(), quality === undefined ? 1 : Number(quality));
+  }
+
+  /**
+   * @param {string} coding
+   * @returns {number}
+   */
+  const getQuality = (coding) => accepted.get(coding) ?? accepted.get("*") ?? 0;
+
+  const zstdSupported = typeof zlibWithZstd.zstdCompressSync === "function";
+  /** @type {ResponseEncoding[]} */
+  const candidates = zstdSupported ? ["zstd", "gzip"] : ["gzip"];
+
+  /** @type {ResponseEncoding | undefined} */
+  let encoding = undefined;
+  for (const candidate of candidates) {
+    const quality = getQuality(candidate);
+    if (quality > 0 && (!encoding || quality > getQuality(encoding))) {
+      encoding = candidate;
+    }
+  }
+  return encoding;
+}
+
+/**
+ * @param {NodeJS.Dict<string | string[]>} headers - The headers the response is sent with
+ * @param {number | undefined} byteLength - Undefined when the length is not known up front
+ * @returns {boolean}
+ */
+export function isCompressibleResponse(headers, byteLength) {
+  const contentEncoding = String(headers["content-encoding"] ?? "identity");
+  if (contentEncoding.trim().toLowerCase() !== "identity") {
+    return false;
+  }
+  if (byteLength !== undefined && byteLength < MIN_COMPRESSIBLE_BYTES) {
+    return false;
+  }
+
+  const contentType = String(headers["content-type"] ?? "")
+    .split(";")[0]
+    .trim()
+    .toLowerCase();
+  return COMPRESSIBLE_CONTENT_TYPES.some((pattern) =>
+    pattern.test(contentType)
+  );
+}
+
+/**
+ * @param {Buffer} body
+ * @param {ResponseEncoding} encoding
+ * @returns {Buffer}
+ */
+export function compressBody(body, encoding) {
+  return encoding === "zstd"
+    ? zlibWithZstd.zstdCompressSync(body)
+    : zlib.gzipSync(body);
+}
+
+/**
+ * @param {ResponseEncoding} encoding
+ * @returns {import("stream").Transform}
+ */
+export function createCompressionStream(encoding) {
+  return encoding === "zstd"
+    ? zlibWithZstd.createZstdCompress()
+    : zlib.createGzip();
+}
+
+/**
+ * The headers of a response compressed by the proxy. The compressed body is
+ * another representation, so a strong entity tag is made weak.
+ *
+ * @param {NodeJS.Dict<string | string[]>} headers - Without content-length and transfer-encoding
+ * @param {ResponseEncoding} encoding
+ * @returns {NodeJS.Dict<string | string[]>}
+ */
+export function getCompressedResponseHeaders(headers, encoding) {
+  /** @type {NodeJS.Dict<string | string[]>} */
+  const compressedHeaders = { ...headers, "content-encoding": encoding };
+
+  const vary = String(headers.vary ?? "");
+  if (!/(^|,)\s*(accept-encoding|\*)\s*(,|$)/i.test(vary)) {
+    compressedHeaders.vary = vary
+      ? `${vary}, Accept-Encoding`
+      : "Accept-Encoding";
+  }
+
+  const etag = headers.etag;
+  if (typeof etag === "string" && !etag.startsWith("W/")) {
+    compressedHeaders.etag = `W/${etag}`;
+  }
+
+  return compressedHeaders;
+}
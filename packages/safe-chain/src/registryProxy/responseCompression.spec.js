@@ -0,0 +1,82 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import zlib from "node:zlib";
+import { initializeCliArguments } from "../config/cliArguments.js";
+import {
+  isCompressibleResponse,
+  negotiateResponseEncoding,
+} from "./responseCompression.js";
+
+describe("responseCompression", () => {
+  const zstdSupported = typeof zlib.zstdCompressSync === "function";
+
+  afterEach(() => {
+    initializeCliArguments([]);
+  });
+
+  it("does not compress unless turned on", () => {
+    assert.strictEqual(negotiateResponseEncoding("gzip"), undefined);
+  });
+
+  it("negotiates the encoding from Accept-Encoding", () => {
+    initializeCliArguments(["--safe-chain-compress-responses"]);
+
+    assert.strictEqual(negotiateResponseEncoding("gzip, deflate"), "gzip");
+    assert.strictEqual(
+      negotiateResponseEncoding("zstd, gzip"),
+      zstdSupported ? "zstd" : "gzip"
+    );
+    assert.strictEqual(negotiateResponseEncoding("gzip;q=0, br"), undefined);
+    assert.strictEqual(
+      negotiateResponseEncoding("*;q=0.5"),
+      zstdSupported ? "zstd" : "gzip"
+    );
+    assert.strictEqual(negotiateResponseEncoding("identity"), undefined);
+    assert.strictEqual(negotiateResponseEncoding(undefined), undefined);
+  });
+
+  it("picks the encoding with the highest quality value", () => {
+    initializeCliArguments(["--safe-chain-compress-responses"]);
+    // Treat zstd as supported, so the choice between both encodings is tested
+    // on every Node.js version
+    const zstdCompressSync = zlib.zstdCompressSync;
+    zlib.zstdCompressSync = zstdCompressSync ?? (() => Buffer.alloc(0));
+
+    try {
+      assert.strictEqual(
+        negotiateResponseEncoding("zstd;q=0.1, gzip;q=1"),
+        "gzip"
+      );
+      assert.strictEqual(negotiateResponseEncoding("gzip;q=0.5, zstd"), "zstd");
+      assert.strictEqual(negotiateResponseEncoding("gzip, zstd"), "zstd");
+      assert.strictEqual(negotiateResponseEncoding("zstd;q=0, *"), "gzip");
+    } finally {
+      zlib.zstdCompressSync = zstdCompressSync;
+    }
+  });
+
+  it("only compresses uncompressed text and JSON of some size", () => {
+    const json = { "content-type": "application/json; charset=utf-8" };
+
+    assert.strictEqual(isCompressibleResponse(json, 4096), true);
+    assert.strictEqual(isCompressibleResponse(json, 100), false);
+    assert.strictEqual(
+      isCompressibleResponse(
+        { "content-type": "application/vnd.npm.install-v1+json" },
+        undefined
+      ),
+      true
+    );
+    assert.strictEqual(
+      isCompressibleResponse({ ...json, "content-encoding": "gzip" }, 4096),
+      false
+    );
+    assert.strictEqual(
+      isCompressibleResponse(
+        { "content-type": "application/octet-stream" },
+        4096
+      ),
+      false
+    );
+  });
+});
@@ -0,0 +1,86 @@
+import { ui } from "../environment/userInteraction.js";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  getEcoSystem,
+  setEcoSystem,
+} from "../config/settings.js";
+import { evaluateRequest } from "./replay.js";
+import {
+  EXIT_CODE_SELF_TEST_FAILED,
+  SafeChainStartupError,
+} from "../utils/exitCodes.js";
+
+export const SELF_TEST_PASSED = "passed";
+export const SELF_TEST_SKIPPED = "skipped";
+
+/**
+ * A request the policy must block and one it must allow, per ecosystem. The
+ * malicious packages are the test packages of the Aikido malware list; the
+ * good ones are old releases of popular packages, so they pass the minimum
+ * package age.
+ */
+const SELF_TEST_FIXTURES = {
+  [ECOSYSTEM_JS]: {
+    malicious:
+      "https://registry.npmjs.org/safe-chain-test/-/safe-chain-test-0.0.1-security.tgz",
+    good: "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+  },
+  [ECOSYSTEM_PY]: {
+    malicious:
+      "https://files.pythonhosted.org/packages/00/00/safe_chain_pi_test-0.0.1-py3-none-any.whl",
+    good: "https://files.pythonhosted.org/packages/f9/9b/335f9764261e915ed497fcdeb11df5dfd6f7bf257d4a6a2a686d80da4d54/requests-2.32.3-py3-none-any.whl",
+  },
+};
+
+/**
+ * Evaluates the fixtures of the current ecosystem through the same policy
+ * path as proxied requests, once the malware list is loaded. Startup fails
+ * when the malicious request is allowed or the good one is blocked, so a
+ * broken feed or policy is caught before packages are installed.
+ *
+ * @returns {Promise<string>} - SELF_TEST_PASSED, or SELF_TEST_SKIPPED for an ecosystem without fixtures
+ */
+export async function runSelfTest() {
+  const ecosystem = getEcoSystem();
+  const fixtures =
+    SELF_TEST_FIXTURES[/** @type {keyof typeof SELF_TEST_FIXTURES} */ (ecosystem)];
+  if (!fixtures) {
+    ui.writeVerbose(
+      `Safe-chain: self-test.skipped - no fixtures for the ${ecosystem} ecosystem`
+    );
+    return SELF_TEST_SKIPPED;
+  }
+
+  let malicious;
+  let good;
+  try {
+    malicious = await evaluateRequest(fixtures.malicious);
+    good = await evaluateRequest(fixtures.good);
+  } finally {
+    // The interceptor for a fixture is picked by trying the ecosystems in turn
+    setEcoSystem(ecosystem);
+  }
+
+  /** @type {string[]} */
+  const failures = [];
+  if (!malicious.blockResponse) {
+    failures.push(`${fixtures.malicious} was allowed`);
+  }
+  if (good.blockResponse) {
+    failures.push(`${fixtures.good} was blocked (${good.blockResponse})`);
+  }
+  if (failures.length > 0) {
+    throw new SafeChainStartupError(
+      EXIT_CODE_SELF_TEST_FAILED,
+      `The self-test failed: ${failures.join(
+        ", "
+      )}. Check the malware list and the policy configuration.`
+    );
+  }
+
+  ui.writeVerbose(
+    `Safe-chain: self-test.passed - ${fixtures.malicious} was blocked and ${fixtures.good} was allowed`
+  );
+  return SELF_TEST_PASSED;
+}
@@ -0,0 +1,77 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("selfTest", async () => {
+  let ecosystem = "js";
+  /** @type {string[]} */
+  let blockedUrls = [];
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      ECOSYSTEM_JS: "js",
+      ECOSYSTEM_PY: "py",
+      getEcoSystem: () => ecosystem,
+      setEcoSystem: (value) => (ecosystem = value),
+    },
+  });
+
+  mock.module("./replay.js", {
+    namedExports: {
+      evaluateRequest: async (url) => {
+        // Like the replay, which tries the ecosystems in turn
+        ecosystem = "py";
+        return {
+          url,
+          blockResponse: blockedUrls.some((blocked) => url.includes(blocked))
+            ? "403 Forbidden - blocked by safe-chain"
+            : undefined,
+          rules: [],
+        };
+      },
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+      },
+    },
+  });
+
+  const { runSelfTest, SELF_TEST_PASSED } = await import("./selfTest.js");
+  const { EXIT_CODE_SELF_TEST_FAILED } = await import("../utils/exitCodes.js");
+
+  beforeEach(() => {
+    ecosystem = "js";
+    blockedUrls = [];
+  });
+
+  it("passes when the malicious fixture is blocked and the good one is allowed", async () => {
+    blockedUrls = ["safe-chain-test"];
+
+    assert.strictEqual(await runSelfTest(), SELF_TEST_PASSED);
+    assert.strictEqual(ecosystem, "js");
+  });
+
+  it("fails startup when the malicious fixture is allowed", async () => {
+    await assert.rejects(runSelfTest(), (error) => {
+      assert.strictEqual(error.exitCode, EXIT_CODE_SELF_TEST_FAILED);
+      assert.match(
+        error.message,
+        /safe-chain-test-0\.0\.1-security\.tgz was allowed/
+      );
+      return true;
+    });
+  });
+
+  it("fails startup when the good fixture is blocked", async () => {
+    ecosystem = "py";
+    blockedUrls = ["safe_chain_pi_test", "requests"];
+
+    await assert.rejects(
+      runSelfTest(),
+      /requests-2\.32\.3-py3-none-any\.whl was blocked/
+    );
+  });
+});
@@ -2,6 +2,18 @@ import * as net from "net";
 import { ui } from "../environment/userInteraction.js";
 import { isImdsEndpoint } from "./isImdsEndpoint.js";
 import { getConnectTimeout } from "./getConnectTimeout.js";
+import { getUpstreamLookup } from "./dohResolver.js";
+import { getUpstreamBindOptions } from "./upstreamBind.js";
+import {
+  createDenylistLookup,
+  DENIED_ADDRESS_ERROR_CODE,
+  recordBlockedHostRequest,
+} from "./hostDenylist.js";
+import { recordClientDisconnect } from "./clientDisconnects.js";
+import {
+  getUpstreamProxyAuthorization,
+  getUpstreamProxyUrl,
+} from "./upstreamProxy.js";
 
 /** @type {string[]} */
 let timedoutImdsEndpoints = [];
@@ -14,7 +26,8 @@ let timedoutImdsEndpoints = [];
  * @returns {void}
  */
 export function tunnelRequest(req, clientSocket, head) {
-  const httpsProxy = process.env.HTTPS_PROXY || process.env.https_proxy;
+  const { port, hostname } = new URL(`http://${req.url}`);
+  const httpsProxy = getUpstreamProxyUrl(hostname, port);
 
   if (httpsProxy) {
     // If an HTTPS proxy is set, tunnel the request via the proxy
@@ -67,11 +80,11 @@ function tunnelRequestToDestination(req, clientSocket, head) {
     if (isImds) {
       timedoutImdsEndpoints.push(hostname);
       ui.writeVerbose(
-        `Safe-chain: connect to ${hostname}:${targetPort} timed out after ${connectTimeout}ms`
+        `Safe-chain: tunnel.connect_timeout - connect to ${hostname}:${targetPort} timed out after ${connectTimeout}ms`
       );
     } else {
       ui.writeError(
-        `Safe-chain: connect to ${hostname}:${targetPort} timed out after ${connectTimeout}ms`
+        `Safe-chain: tunnel.connect_timeout - connect to ${hostname}:${targetPort} timed out after ${connectTimeout}ms`
       );
     }
     serverSocket.destroy();
@@ -80,23 +93,30 @@ function tunnelRequestToDestination(req, clientSocket, head) {
     }
   }, connectTimeout);
 
-  const serverSocket = net.connect(targetPort, hostname, () => {
-    // Clear timer to prevent false timeout errors after successful connection
-    clearTimeout(connectTimer);
+  const serverSocket = net.connect(
+    {
+      port: targetPort,
+      host: hostname,
+      lookup: createDenylistLookup(getUpstreamLookup()),
+      ...getUpstreamBindOptions(),
+    },
+    () => {
+      // Clear timer to prevent false timeout errors after successful connection
+      clearTimeout(connectTimer);
 
-    clientSocket.write("HTTP/1.1 200 Connection Established\r\n\r\n");
-    serverSocket.write(head);
-    serverSocket.pipe(clientSocket);
-    clientSocket.pipe(serverSocket);
-  });
+      clientSocket.write("HTTP/1.1 200 Connection Established\r\n\r\n");
+      serverSocket.write(head);
+      serverSocket.pipe(clientSocket);
+      clientSocket.pipe(serverSocket);
+    }
+  );
 
-  clientSocket.on("error", () => {
+  clientSocket.on("error", (err) => {
     // This can happen if the client TCP socket sends RST instead of FIN.
     // Not subscribing to 'error' event will cause node to throw and crash.
     clearTimeout(connectTimer);
-    if (serverSocket.writable) {
-      serverSocket.end();
-    }
+    recordClientDisconnect(req.url ?? hostname, err.message);
+    serverSocket.destroy();
   });
 
   clientSocket.on("close", () => {
@@ -107,8 +127,15 @@ function tunnelRequestToDestination(req, clientSocket, head) {
     }
   });
 
-  serverSocket.on("error", (err) => {
+  serverSocket.on("error", (/** @type {NodeJS.ErrnoException} */ err) => {
     clearTimeout(connectTimer);
+    if (err.code === DENIED_ADDRESS_ERROR_CODE) {
+      recordBlockedHostRequest(hostname, `${req.url} (${err.message})`);
+      if (clientSocket.writable) {
+        clientSocket.end("HTTP/1.1 403 Forbidden\r\n\r\n");
+      }
+      return;
+    }
     if (isImds) {
       ui.writeVerbose(
         `Safe-chain: error connecting to ${hostname}:${targetPort} - ${err.message}`
@@ -141,18 +168,36 @@ function tunnelRequestToDestination(req, clientSocket, head) {
 function tunnelRequestViaProxy(req, clientSocket, head, proxyUrl) {
   const { port, hostname } = new URL(`http://${req.url}`);
   const proxy = new URL(proxyUrl);
+  const connectTimeout = getConnectTimeout(hostname);
+
+  // Covers both the connection to the proxy and its answer to the CONNECT,
+  // which the proxy only sends once it reached the destination
+  const connectTimer = setTimeout(() => {
+    ui.writeError(
+      `Safe-chain: tunnel.connect_timeout - connect to ${hostname}:${
+        port || 443
+      } via proxy ${proxy.host} timed out after ${connectTimeout}ms`
+    );
+    proxySocket.destroy();
+    if (clientSocket.writable) {
+      clientSocket.end("HTTP/1.1 504 Gateway Timeout\r\n\r\n");
+    }
+  }, connectTimeout);
 
   // Connect to proxy server
   const proxySocket = net.connect({
     host: proxy.hostname,
     port: Number.parseInt(proxy.port) || 80,
+    ...getUpstreamBindOptions(),
   });
 
   proxySocket.on("connect", () => {
     // Send CONNECT request to proxy
+    const authorization = getUpstreamProxyAuthorization(proxy);
     const connectRequest = [
       `CONNECT ${hostname}:${port || 443} HTTP/1.1`,
       `Host: ${hostname}:${port || 443}`,
+      ...(authorization ? [`Proxy-Authorization: ${authorization}`] : []),
       "",
       "",
     ].join("\r\n");
@@ -162,6 +207,7 @@ function tunnelRequestViaProxy(req, clientSocket, head, proxyUrl) {
 
   let isConnected = false;
   proxySocket.once("data", (data) => {
+    clearTimeout(connectTimer);
     const response = data.toString();
 
     // Check if CONNECT succeeded (HTTP/1.1 200)
@@ -185,6 +231,7 @@ function tunnelRequestViaProxy(req, clientSocket, head, proxyUrl) {
   });
 
   proxySocket.on("error", (err) => {
+    clearTimeout(connectTimer);
     if (!isConnected) {
       ui.writeError(
         `Safe-chain: error connecting to proxy ${proxy.hostname}:${
@@ -204,7 +251,14 @@ function tunnelRequestViaProxy(req, clientSocket, head, proxyUrl) {
     }
   });
 
-  clientSocket.on("error", () => {
+  clientSocket.on("error", (err) => {
+    clearTimeout(connectTimer);
+    recordClientDisconnect(req.url ?? hostname, err.message);
+    proxySocket.destroy();
+  });
+
+  clientSocket.on("close", () => {
+    clearTimeout(connectTimer);
     if (proxySocket.writable) {
       proxySocket.end();
     }
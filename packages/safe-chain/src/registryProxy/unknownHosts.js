@@ -0,0 +1,79 @@
+import {
+  getAllowedUnknownHosts,
+  getBlockingDisabled,
+  getInspectOnly,
+  getStrictUnknownHosts,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import { matchesHostPattern } from "./hostDenylist.js";
+import { BLOCK_REASON_UNKNOWN_REGISTRY } from "./interceptors/interceptorBuilder.js";
+
+/**
+ * @type {{
+ *   blockedRequests: {host: string, url: string}[],
+ *   reportedHosts: Set<string>,
+ * }}
+ */
+const state = {
+  blockedRequests: [],
+  // Hosts a warning was written for
+  reportedHosts: new Set(),
+};
+
+/** Headers of the response to a request that is blocked as an unknown host */
+export const UNKNOWN_REGISTRY_HEADERS = {
+  "X-Safe-Chain-Blocked": "true",
+  "X-Safe-Chain-Reason": BLOCK_REASON_UNKNOWN_REGISTRY,
+};
+
+/**
+ * Checks whether a request to a host that is not a known or configured registry
+ * is refused, which is only the case in strict unknown hosts mode. The host is
+ * logged once, so operators can configure it as a registry or allow it. In
+ * inspect-only mode the request is recorded the same way, but goes through.
+ *
+ * @param {string} hostname
+ * @param {string} url
+ * @returns {boolean} - true when the request must be refused
+ */
+export function isBlockedUnknownHost(hostname, url) {
+  const normalizedHost = hostname.trim().toLowerCase().replace(/\.$/, "");
+  if (
+    !getStrictUnknownHosts() ||
+    getBlockingDisabled() ||
+    getAllowedUnknownHosts().some((pattern) =>
+      matchesHostPattern(normalizedHost, pattern)
+    )
+  ) {
+    return false;
+  }
+
+  state.blockedRequests.push({ host: normalizedHost, url });
+  const inspectOnly = getInspectOnly();
+
+  if (!state.reportedHosts.has(normalizedHost)) {
+    state.reportedHosts.add(normalizedHost);
+    ui.writeWarning(
+      `Safe-chain: ${
+        inspectOnly ? "inspect.would-block" : BLOCK_REASON_UNKNOWN_REGISTRY
+      } - ${normalizedHost} (${url}) is not a known registry. Configure it as a custom registry or allow it with --safe-chain-allow-unknown-host=${normalizedHost}`
+    );
+  }
+
+  return !inspectOnly;
+}
+
+/**
+ * @returns {{host: string, url: string}[]}
+ */
+export function getBlockedUnknownHostRequests() {
+  return state.blockedRequests;
+}
+
+/**
+ * @returns {void}
+ */
+export function resetUnknownHosts() {
+  state.blockedRequests = [];
+  state.reportedHosts.clear();
+}
@@ -0,0 +1,105 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("unknownHosts", async () => {
+  const warnings = [];
+  let strictUnknownHosts = false;
+  let allowedUnknownHosts = [];
+  let inspectOnly = false;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getStrictUnknownHosts: () => strictUnknownHosts,
+      getAllowedUnknownHosts: () => allowedUnknownHosts,
+      getBlockingDisabled: () => false,
+      getInspectOnly: () => inspectOnly,
+    },
+  });
+
+  mock.module("./hostDenylist.js", {
+    namedExports: {
+      matchesHostPattern: (hostname, pattern) =>
+        pattern.startsWith("*.")
+          ? hostname.endsWith(pattern.substring(1))
+          : hostname === pattern,
+    },
+  });
+
+  mock.module("./interceptors/interceptorBuilder.js", {
+    namedExports: {
+      BLOCK_REASON_UNKNOWN_REGISTRY: "unknown-registry",
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeWarning: (message) => warnings.push(message),
+      },
+    },
+  });
+
+  const {
+    getBlockedUnknownHostRequests,
+    isBlockedUnknownHost,
+    resetUnknownHosts,
+  } = await import("./unknownHosts.js");
+
+  beforeEach(() => {
+    resetUnknownHosts();
+    strictUnknownHosts = false;
+    allowedUnknownHosts = [];
+    inspectOnly = false;
+    warnings.length = 0;
+  });
+
+  it("lets unknown hosts through unless strict mode is on", () => {
+    assert.strictEqual(
+      isBlockedUnknownHost("github.com", "github.com:443"),
+      false
+    );
+    assert.strictEqual(warnings.length, 0);
+  });
+
+  it("blocks unknown hosts in strict mode and logs each host once", () => {
+    strictUnknownHosts = true;
+
+    assert.strictEqual(
+      isBlockedUnknownHost("GitHub.com.", "github.com:443"),
+      true
+    );
+    assert.strictEqual(
+      isBlockedUnknownHost("github.com", "github.com:443"),
+      true
+    );
+
+    assert.strictEqual(getBlockedUnknownHostRequests().length, 2);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(
+      warnings[0],
+      /unknown-registry - github\.com \(github\.com:443\) is not a known registry/
+    );
+  });
+
+  it("lets allowed hosts through and only records hosts in inspect-only mode", () => {
+    strictUnknownHosts = true;
+    allowedUnknownHosts = ["*.githubusercontent.com"];
+
+    assert.strictEqual(
+      isBlockedUnknownHost(
+        "codeload.githubusercontent.com",
+        "codeload.githubusercontent.com:443"
+      ),
+      false
+    );
+
+    inspectOnly = true;
+    assert.strictEqual(
+      isBlockedUnknownHost("github.com", "github.com:443"),
+      false
+    );
+    assert.strictEqual(getBlockedUnknownHostRequests().length, 1);
+    assert.match(warnings[0], /inspect\.would-block - github\.com/);
+  });
+});
@@ -0,0 +1,19 @@
+import net from "net";
+import { getUpstreamBind } from "../config/settings.js";
+
+/**
+ * Socket options that make an upstream connection originate from the
+ * configured local address, for registries and firewalls that allowlist
+ * egress by source IP. The address family is pinned to the one of the local
+ * address, as a socket bound to an IPv4 address cannot reach an IPv6 one.
+ *
+ * @returns {{localAddress?: string, family?: number}}
+ */
+export function getUpstreamBindOptions() {
+  const localAddress = getUpstreamBind();
+  if (!localAddress) {
+    return {};
+  }
+
+  return { localAddress, family: net.isIP(localAddress) };
+}
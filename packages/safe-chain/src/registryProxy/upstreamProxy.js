@@ -0,0 +1,241 @@
+import {
+  getUpstreamProxyPass,
+  getUpstreamProxyUser,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * @typedef {Object} ProxyEnvironmentVariable
+ * @property {string} name - The spelling the value was read from
+ * @property {string} value
+ */
+
+/**
+ * @type {{reportedConflicts: Set<string>}}
+ */
+const state = {
+  // Variables a conflict between spellings was reported for
+  reportedConflicts: new Set(),
+};
+
+/**
+ * Returns the proxy that safe-chain sends its own upstream traffic to a host
+ * through, from HTTPS_PROXY in the environment of the safe-chain process, or
+ * undefined when NO_PROXY excludes the host. The package manager gets the
+ * safe-chain proxy instead.
+ *
+ * @param {string} [hostname] - The destination, checked against NO_PROXY
+ * @param {string | number} [port]
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyUrl(hostname, port) {
+  const proxyUrl = readProxyEnvironmentVariable("HTTPS_PROXY")?.value;
+  if (!proxyUrl || (hostname && isExcludedFromUpstreamProxy(hostname, port))) {
+    return undefined;
+  }
+  return proxyUrl;
+}
+
+/**
+ * Reads a proxy environment variable in any case. On Windows the environment
+ * is case-insensitive already; elsewhere HTTPS_PROXY and https_proxy can both
+ * be set. The upper case spelling wins, then the lower case one, then any
+ * other. Spellings with different values are reported once.
+ *
+ * @param {string} name - The upper case name, for example "NO_PROXY"
+ * @returns {ProxyEnvironmentVariable | undefined}
+ */
+export function readProxyEnvironmentVariable(name) {
+  const spellings = Object.keys(process.env)
+    .filter((key) => key.toUpperCase() === name && process.env[key])
+    .sort((a, b) => spellingRank(a, name) - spellingRank(b, name));
+  if (spellings.length === 0) {
+    return undefined;
+  }
+
+  const [winner, ...others] = spellings;
+  const value = /** @type {string} */ (process.env[winner]).trim();
+  const conflicting = others.filter(
+    (key) => process.env[key]?.trim() !== value
+  );
+  if (conflicting.length > 0 && !state.reportedConflicts.has(name)) {
+    state.reportedConflicts.add(name);
+    ui.writeWarning(
+      `Safe-chain: upstream-proxy.conflict - ${[winner, ...conflicting].join(
+        " and "
+      )} are set to different values, using ${winner}`
+    );
+  }
+  return { name: winner, value };
+}
+
+/**
+ * @param {string} key
+ * @param {string} name
+ * @returns {number}
+ */
+function spellingRank(key, name) {
+  if (key === name) {
+    return 0;
+  }
+  return key === name.toLowerCase() ? 1 : 2;
+}
+
+/**
+ * Returns the NO_PROXY entries: host names, domains and IP addresses,
+ * optionally with a port, separated by commas or whitespace.
+ *
+ * @returns {string[]}
+ */
+export function getNoProxyEntries() {
+  const noProxy = readProxyEnvironmentVariable("NO_PROXY")?.value ?? "";
+  return noProxy
+    .split(/[\s,]+/)
+    .map((entry) => entry.trim().toLowerCase())
+    .filter((entry) => entry.length > 0);
+}
+
+/**
+ * Whether traffic to a host bypasses the upstream proxy. "*" matches every
+ * host. "example.com", ".example.com" and "*.example.com" all match the
+ * domain and its subdomains. An entry with a port only matches that port.
+ *
+ * @param {string} hostname
+ * @param {string | number} [port] - 443 when omitted
+ * @returns {boolean}
+ */
+export function isExcludedFromUpstreamProxy(hostname, port) {
+  const host = normalizeHost(hostname);
+  const targetPort = String(port || 443);
+
+  return getNoProxyEntries().some((entry) => {
+    if (entry === "*") {
+      return true;
+    }
+
+    const { host: entryHost, port: entryPort } = splitHostPort(entry);
+    if (entryPort && entryPort !== targetPort) {
+      return false;
+    }
+    const domain = normalizeHost(entryHost.replace(/^\*?\./, ""));
+    return host === domain || host.endsWith(`.${domain}`);
+  });
+}
+
+/**
+ * @param {string} entry - For example "example.com:8443" or "[::1]:443"
+ * @returns {{host: string, port: string | undefined}}
+ */
+function splitHostPort(entry) {
+  const match = entry.match(/^(\[[^\]]+\]|[^:]+):(\d+)$/);
+  if (!match) {
+    return { host: entry, port: undefined };
+  }
+  return { host: match[1], port: match[2] };
+}
+
+/**
+ * @param {string} hostname
+ * @returns {string}
+ */
+function normalizeHost(hostname) {
+  return hostname
+    .toLowerCase()
+    .replace(/^\[(.*)\]$/, "$1")
+    .replace(/\.$/, "");
+}
+
+/**
+ * Writes the effective upstream proxy and NO_PROXY list, so a proxy that is
+ * picked up from an unexpected variable is easy to spot.
+ *
+ * @returns {void}
+ */
+export function logUpstreamProxyConfiguration() {
+  const proxy = readProxyEnvironmentVariable("HTTPS_PROXY");
+  if (!proxy) {
+    ui.writeVerbose("Safe-chain: upstream-proxy - none, connecting directly");
+    return;
+  }
+
+  const noProxyEntries = getNoProxyEntries();
+  ui.writeVerbose(
+    `Safe-chain: upstream-proxy - ${describeProxy(proxy.value)} from ${
+      proxy.name
+    }, bypassed for: ${
+      noProxyEntries.length > 0 ? noProxyEntries.join(", ") : "no hosts"
+    }`
+  );
+}
+
+/**
+ * Describes a proxy without its credentials.
+ *
+ * @param {string} proxyUrl
+ * @returns {string}
+ */
+function describeProxy(proxyUrl) {
+  try {
+    const proxy = new URL(proxyUrl);
+    return `${proxy.protocol}//${proxy.host}`;
+  } catch {
+    return "an invalid URL";
+  }
+}
+
+/**
+ * @returns {void}
+ */
+export function resetUpstreamProxyWarnings() {
+  state.reportedConflicts.clear();
+}
+
+/**
+ * Returns the credentials for the upstream proxy. The configured user name and
+ * password take precedence over the ones in the proxy URL.
+ *
+ * @param {URL} proxy
+ * @returns {{user: string, pass: string} | undefined}
+ */
+function getUpstreamProxyCredentials(proxy) {
+  const user = getUpstreamProxyUser() ?? decodeURIComponent(proxy.username);
+  const pass = getUpstreamProxyPass() ?? decodeURIComponent(proxy.password);
+  if (!user && !pass) {
+    return undefined;
+  }
+  return { user, pass };
+}
+
+/**
+ * Returns the Proxy-Authorization header value for a CONNECT request to the
+ * upstream proxy. It is only ever sent to the proxy itself: the tunnel to the
+ * registry is opened after the proxy accepted it.
+ *
+ * @param {URL} proxy
+ * @returns {string | undefined}
+ */
+export function getUpstreamProxyAuthorization(proxy) {
+  const credentials = getUpstreamProxyCredentials(proxy);
+  if (!credentials) {
+    return undefined;
+  }
+  const token = Buffer.from(`${credentials.user}:${credentials.pass}`);
+  return `Basic ${token.toString("base64")}`;
+}
+
+/**
+ * Returns the proxy URL with the credentials in it, for proxy agents that read
+ * them from the URL. Do not log it: use the host of the proxy instead.
+ *
+ * @param {string} proxyUrl
+ * @returns {URL}
+ */
+export function withUpstreamProxyCredentials(proxyUrl) {
+  const proxy = new URL(proxyUrl);
+  const credentials = getUpstreamProxyCredentials(proxy);
+  if (credentials) {
+    proxy.username = encodeURIComponent(credentials.user);
+    proxy.password = encodeURIComponent(credentials.pass);
+  }
+  return proxy;
+}
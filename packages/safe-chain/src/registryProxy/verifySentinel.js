@@ -0,0 +1,61 @@
+import {
+  getBlockingDisabled,
+  getEcoSystem,
+  getInspectOnly,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import { getSafeChainVersion } from "../environment/safeChainVersion.js";
+import { getFeedVersion } from "../scanning/feedVersion.js";
+import { interceptRequests } from "./interceptors/interceptorBuilder.js";
+
+/**
+ * A host that never resolves (RFC 2606), so a request for it can only be
+ * answered by a proxy that intercepts it.
+ */
+export const VERIFY_HOST = "safe-chain-verify.invalid";
+
+export const VERIFY_MESSAGE = "OK: Safe-chain works!";
+
+/**
+ * Connections to the verify host are intercepted like registry connections,
+ * but no package is ever looked up for them.
+ */
+export const verifyInterceptor = interceptRequests(async () => {});
+
+/**
+ * @param {string | undefined} hostname
+ * @returns {boolean}
+ */
+export function isVerifyHost(hostname) {
+  return hostname?.toLowerCase() === VERIFY_HOST;
+}
+
+/**
+ * Answers a request for the verify host with a single JSON line confirming
+ * that the proxy intercepts it, without contacting any registry.
+ *
+ * @param {import("http").IncomingMessage} req
+ * @param {import("http").ServerResponse} res
+ * @param {"http" | "https"} transport - "https" when the TLS connection was intercepted
+ * @returns {Promise<void>}
+ */
+export async function handleVerifyRequest(req, res, transport) {
+  req.resume();
+  const body = {
+    status: "ok",
+    message: VERIFY_MESSAGE,
+    transport,
+    safe_chain_version: await getSafeChainVersion(),
+    ecosystem: getEcoSystem(),
+    feed_version: getFeedVersion() ?? null,
+    blocking: !getBlockingDisabled() && !getInspectOnly(),
+  };
+  ui.writeVerbose(
+    `Safe-chain: verify - answered ${req.method} ${req.url} over ${transport}`
+  );
+  res.writeHead(200, {
+    "content-type": "application/json",
+    "cache-control": "no-store",
+  });
+  res.end(`${JSON.stringify(body)}\n`);
+}
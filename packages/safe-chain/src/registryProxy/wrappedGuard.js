@@ -0,0 +1,105 @@
+import {
+  getIdleStartupTimeoutSecs,
+  getListenOnlyWhenWrapped,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+const PARENT_POLL_INTERVAL_MS = 1000;
+
+/**
+ * @type {{
+ *   idleTimer: NodeJS.Timeout | null,
+ *   parentTimer: NodeJS.Timeout | null,
+ * }}
+ */
+const state = {
+  idleTimer: null,
+  parentTimer: null,
+};
+
+/**
+ * Shuts a per-session proxy down once it outlived its purpose: when no client
+ * connected within the idle startup timeout, or when the process that launched
+ * safe-chain exited. Does nothing unless the proxy only listens when wrapped.
+ *
+ * @param {import("http").Server} server
+ * @param {() => Promise<void>} shutDown
+ * @param {number} [parentPid] - The process to watch, the parent by default
+ * @returns {void}
+ */
+export function startWrappedGuard(server, shutDown, parentPid = process.ppid) {
+  if (!getListenOnlyWhenWrapped()) {
+    return;
+  }
+
+  const idleTimeoutSecs = getIdleStartupTimeoutSecs();
+  state.idleTimer = setTimeout(() => {
+    state.idleTimer = null;
+    ui.writeWarning(
+      `Safe-chain: wrapped.idle - no client connected within ${idleTimeoutSecs} seconds of starting, the proxy shuts down`
+    );
+    stopWrappedGuard();
+    shutDown();
+  }, idleTimeoutSecs * 1000);
+  state.idleTimer.unref();
+  server.once("connection", clearIdleTimer);
+
+  // Parent process monitoring is Unix only
+  if (process.platform === "win32") {
+    ui.writeVerbose(
+      `Safe-chain: wrapped - the proxy shuts down when no client connects within ${idleTimeoutSecs} seconds. Parent process monitoring is not supported on Windows.`
+    );
+    return;
+  }
+
+  ui.writeVerbose(
+    `Safe-chain: wrapped - the proxy shuts down when no client connects within ${idleTimeoutSecs} seconds or parent process ${parentPid} exits`
+  );
+  state.parentTimer = setInterval(() => {
+    if (isProcessRunning(parentPid)) {
+      return;
+    }
+    ui.writeWarning(
+      `Safe-chain: wrapped.parent-exited - parent process ${parentPid} exited, the proxy shuts down`
+    );
+    stopWrappedGuard();
+    shutDown();
+  }, PARENT_POLL_INTERVAL_MS);
+  state.parentTimer.unref();
+}
+
+/**
+ * @returns {void}
+ */
+export function stopWrappedGuard() {
+  clearIdleTimer();
+  if (state.parentTimer) {
+    clearInterval(state.parentTimer);
+    state.parentTimer = null;
+  }
+}
+
+/**
+ * @returns {void}
+ */
+function clearIdleTimer() {
+  if (state.idleTimer) {
+    clearTimeout(state.idleTimer);
+    state.idleTimer = null;
+  }
+}
+
+/**
+ * @param {number} pid
+ * @returns {boolean}
+ */
+function isProcessRunning(pid) {
+  try {
+    // Signal 0 only checks whether the process exists
+    process.kill(pid, 0);
+    return true;
+  } catch (/** @type {any} */ err) {
+    // The process exists, but belongs to another user
+    return err.code === "EPERM";
+  }
+}
@@ -0,0 +1,97 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import { spawn } from "child_process";
+import http from "http";
+import net from "net";
+import { startWrappedGuard, stopWrappedGuard } from "./wrappedGuard.js";
+import { initializeCliArguments } from "../config/cliArguments.js";
+
+describe("wrappedGuard", () => {
+  /** @type {http.Server} */
+  let server;
+
+  afterEach(async () => {
+    stopWrappedGuard();
+    initializeCliArguments([]);
+    await new Promise((resolve) => server.close(resolve));
+  });
+
+  /**
+   * The listening server keeps the test running, the guard's timers do not.
+   */
+  async function listen() {
+    server = http.createServer();
+    await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+    return server;
+  }
+
+  it("shuts down when no client connects within the idle startup timeout", async () => {
+    initializeCliArguments([
+      "--safe-chain-listen-only-when-wrapped",
+      "--safe-chain-idle-startup-timeout-secs=0.2",
+    ]);
+    await listen();
+    const shutDown = new Promise((resolve) =>
+      startWrappedGuard(server, async () => resolve(Date.now()))
+    );
+
+    const startTime = Date.now();
+    assert.ok((await shutDown) - startTime >= 150);
+  });
+
+  it("keeps running once a client connected", async () => {
+    initializeCliArguments([
+      "--safe-chain-listen-only-when-wrapped",
+      "--safe-chain-idle-startup-timeout-secs=0.2",
+    ]);
+    await listen();
+    let shutDown = false;
+    startWrappedGuard(server, async () => {
+      shutDown = true;
+    });
+
+    const { port } = server.address();
+    await new Promise((resolve) => {
+      const socket = net.createConnection({ host: "127.0.0.1", port }, () => {
+        socket.destroy();
+        resolve();
+      });
+    });
+    await new Promise((resolve) => setTimeout(resolve, 400));
+
+    assert.strictEqual(shutDown, false);
+  });
+
+  it(
+    "shuts down when the parent process exits",
+    { skip: process.platform === "win32" },
+    async () => {
+      initializeCliArguments(["--safe-chain-listen-only-when-wrapped"]);
+      await listen();
+      const parent = spawn(process.execPath, [
+        "-e",
+        "setTimeout(() => {}, 200)",
+      ]);
+      const exited = new Promise((resolve) => parent.once("exit", resolve));
+      const shutDown = new Promise((resolve) =>
+        startWrappedGuard(server, async () => resolve(true), parent.pid)
+      );
+
+      await exited;
+      assert.strictEqual(await shutDown, true);
+    }
+  );
+
+  it("does nothing unless enabled", async () => {
+    initializeCliArguments(["--safe-chain-idle-startup-timeout-secs=0.1"]);
+    await listen();
+    let shutDown = false;
+    startWrappedGuard(server, async () => {
+      shutDown = true;
+    });
+
+    await new Promise((resolve) => setTimeout(resolve, 300));
+
+    assert.strictEqual(shutDown, false);
+  });
+});
@@ -1,3 +1,4 @@
+import { isMalwareCheckEnabled } from "../../config/settings.js";
 import { ui } from "../../environment/userInteraction.js";
 import {
   MALWARE_STATUS_MALWARE,
@@ -9,12 +10,13 @@ import {
  * @property {string} name
  * @property {string} version
  * @property {string} type
+ * @property {number} [publishedAt] - Milliseconds since the epoch, when known
  */
 
 /**
  * @typedef {Object} AuditResult
  * @property {PackageChange[]} allowedChanges
- * @property {(PackageChange & {reason: string})[]} disallowedChanges
+ * @property {(PackageChange & {reason: string, advisories: string[]})[]} disallowedChanges
  * @property {boolean} isAllowed
  */
 
@@ -45,18 +47,53 @@ export function getAuditStats() {
  *
  * @param {string | undefined} name
  * @param {string | undefined} version
+ * @param {number} [publishedAt] - Milliseconds since the epoch, for feed entries with a publish-date cutoff
  * @returns {Promise<boolean>}
  */
-export async function isMalwarePackage(name, version) {
+export async function isMalwarePackage(name, version, publishedAt) {
   if (!name || !version) {
     return false;
   }
 
-  const auditResult = await auditChanges([{ name, version, type: "add" }]);
+  const auditResult = await auditChanges([
+    { name, version, type: "add", publishedAt },
+  ]);
 
   return !auditResult.isAllowed;
 }
 
+/**
+ * Returns the advisory IDs (OSV/GHSA) the malware feed lists for the package.
+ *
+ * @param {string | undefined} name
+ * @param {string | undefined} version
+ * @param {number} [publishedAt]
+ * @returns {Promise<string[]>}
+ */
+export async function getMalwareAdvisories(name, version, publishedAt) {
+  if (!name || !version || !isMalwareCheckEnabled()) {
+    return [];
+  }
+
+  const malwareDb = await openMalwareDatabase();
+  return malwareDb.getAdvisories(name, version, publishedAt);
+}
+
+/**
+ * Opens the malware database and returns a check that does not need to be
+ * awaited, for filtering the versions listed in package metadata.
+ *
+ * @returns {Promise<(name: string, version: string) => boolean>}
+ */
+export async function openMalwareVersionCheck() {
+  if (!isMalwareCheckEnabled()) {
+    return () => false;
+  }
+
+  const malwareDb = await openMalwareDatabase();
+  return (name, version) => malwareDb.isMalware(name, version);
+}
+
 /**
  * @param {PackageChange[]} changes
  *
@@ -82,7 +119,11 @@ export async function auditChanges(changes) {
       ui.writeVerbose(
         `Safe-chain: Package ${change.name}@${change.version} is marked as malware: ${malwarePackage.status}`
       );
-      disallowedChanges.push({ ...change, reason: malwarePackage.status });
+      disallowedChanges.push({
+        ...change,
+        reason: malwarePackage.status,
+        advisories: malwarePackage.advisories,
+      });
     } else {
       auditStats.safePackages += 1;
       ui.writeVerbose(
@@ -104,11 +145,12 @@ export async function auditChanges(changes) {
 }
 
 /**
- * @param {{name: string, version: string, type: string}[]} changes
- * @returns {Promise<{name: string, version: string, status: string}[]>}
+ * @param {PackageChange[]} changes
+ * @returns {Promise<{name: string, version: string, status: string, advisories: string[]}[]>}
  */
 async function getPackagesWithMalware(changes) {
-  if (changes.length === 0) {
+  // The malware list is not even loaded for an ecosystem without the check
+  if (changes.length === 0 || !isMalwareCheckEnabled()) {
     return [];
   }
 
@@ -116,11 +158,16 @@ async function getPackagesWithMalware(changes) {
   let allVulnerablePackages = [];
 
   for (const change of changes) {
-    if (malwareDb.isMalware(change.name, change.version)) {
+    if (malwareDb.isMalware(change.name, change.version, change.publishedAt)) {
       allVulnerablePackages.push({
         name: change.name,
         version: change.version,
         status: MALWARE_STATUS_MALWARE,
+        advisories: malwareDb.getAdvisories(
+          change.name,
+          change.version,
+          change.publishedAt
+        ),
       });
     }
   }
@@ -20,6 +20,8 @@ describe("audit/index", async () => {
       MALWARE_STATUS_MALWARE: "malware",
       openMalwareDatabase: async () => ({
         isMalware: mockIsMalware,
+        getAdvisories: (name) =>
+          name === "malicious-pkg" ? ["GHSA-2xxv-wcp8-9fr8"] : [],
       }),
     },
   });
@@ -86,6 +88,9 @@ describe("audit/index", async () => {
       assert.equal(result.disallowedChanges[0].name, "malicious-pkg");
       assert.equal(result.disallowedChanges[0].version, "1.0.0");
       assert.equal(result.disallowedChanges[0].reason, "malware");
+      assert.deepEqual(result.disallowedChanges[0].advisories, [
+        "GHSA-2xxv-wcp8-9fr8",
+      ]);
     });
 
     it("should handle mixed safe and malware packages", async () => {
@@ -0,0 +1,118 @@
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  getEcoSystem,
+  setEcoSystem,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import { getExitCodeForError } from "../utils/exitCodes.js";
+import { loadMalwareList } from "./malwareDatabase.js";
+
+/** @type {Record<string, string>} */
+const ecosystemNames = {
+  npm: ECOSYSTEM_JS,
+  pypi: ECOSYSTEM_PY,
+};
+
+/**
+ * @typedef {Object} EcosystemSummary
+ * @property {string | null} version - Version of the malware list, when known
+ * @property {number} entries - Entries of all feeds, after merging
+ * @property {number} matching - Entries that match the name filter
+ *
+ * @typedef {Object} FeedDump
+ * @property {Record<string, EcosystemSummary>} summary - Per ecosystem name
+ * @property {(import("../api/aikido.js").MalwarePackage & {ecosystem: string})[]} entries
+ */
+
+/**
+ * Loads the malware list of each ecosystem and returns the entries whose
+ * package name contains the filter, compared case-insensitively.
+ *
+ * @param {string[]} ecosystems - Ecosystem names, "npm" or "pypi"
+ * @param {string} [filter]
+ * @returns {Promise<FeedDump>}
+ */
+export async function collectFeedEntries(ecosystems, filter) {
+  const normalizedFilter = filter?.toLowerCase();
+  /** @type {FeedDump} */
+  const dump = { summary: {}, entries: [] };
+
+  const previousEcosystem = getEcoSystem();
+  try {
+    for (const ecosystem of ecosystems) {
+      setEcoSystem(ecosystemNames[ecosystem]);
+      const { entries, version } = await loadMalwareList();
+      const matching = normalizedFilter
+        ? entries.filter((entry) =>
+            entry.package_name.toLowerCase().includes(normalizedFilter)
+          )
+        : entries;
+
+      dump.summary[ecosystem] = {
+        version: version ?? null,
+        entries: entries.length,
+        matching: matching.length,
+      };
+      dump.entries.push(...matching.map((entry) => ({ ecosystem, ...entry })));
+    }
+  } finally {
+    setEcoSystem(previousEcosystem);
+  }
+
+  return dump;
+}
+
+/**
+ * Handles `safe-chain dump-feed [--ecosystem=<npm|pypi>] [--grep=<TEXT>]`:
+ * prints the malware list entries as JSON, with counts per ecosystem. The
+ * lists are loaded like for an install, so `--safe-chain-offline` only reads
+ * the cached copies.
+ *
+ * @param {string[]} args - The arguments after `dump-feed`
+ * @returns {Promise<number>}
+ */
+export async function dumpFeed(args) {
+  const ecosystem = getOptionValue(args, "--ecosystem")?.toLowerCase();
+  const filter = getOptionValue(args, "--grep");
+
+  if (ecosystem !== undefined && !(ecosystem in ecosystemNames)) {
+    ui.writeError(
+      "Usage: safe-chain dump-feed [--ecosystem=<npm|pypi>] [--grep=<TEXT>]"
+    );
+    return 1;
+  }
+
+  try {
+    const dump = await collectFeedEntries(
+      ecosystem ? [ecosystem] : Object.keys(ecosystemNames),
+      filter
+    );
+    ui.writeInformation(JSON.stringify(dump, null, 2));
+  } catch (/** @type {any} */ error) {
+    ui.writeError(`Failed to load the malware list: ${error.message}`);
+    return getExitCodeForError(error);
+  }
+
+  return 0;
+}
+
+/**
+ * Reads an option given as `--name=value` or as `--name value`.
+ *
+ * @param {string[]} args
+ * @param {string} name
+ * @returns {string | undefined}
+ */
+function getOptionValue(args, name) {
+  const index = args.findIndex(
+    (arg) => arg === name || arg.startsWith(`${name}=`)
+  );
+  if (index === -1) {
+    return undefined;
+  }
+
+  return args[index] === name
+    ? (args[index + 1] ?? "")
+    : args[index].slice(name.length + 1);
+}
@@ -0,0 +1,137 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  getEcoSystem,
+  setEcoSystem,
+} from "../config/settings.js";
+import {
+  EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+  SafeChainStartupError,
+} from "../utils/exitCodes.js";
+
+describe("dumpFeed", async () => {
+  const output = [];
+  let loadError = undefined;
+
+  const malwareLists = {
+    [ECOSYSTEM_JS]: {
+      entries: [
+        {
+          package_name: "safe-chain-test",
+          version: "0.0.1-security",
+          reason: "MALWARE",
+        },
+        { package_name: "@acme/Left-Pad", version: "*", reason: "MALWARE" },
+        { package_name: "lodash", version: "4.17.21", reason: "OK" },
+      ],
+      version: '"npm-1"',
+    },
+    [ECOSYSTEM_PY]: {
+      entries: [
+        { package_name: "left_pad", version: "1.0.0", reason: "MALWARE" },
+      ],
+      version: undefined,
+    },
+  };
+
+  mock.module("./malwareDatabase.js", {
+    namedExports: {
+      loadMalwareList: async () => {
+        if (loadError) {
+          throw loadError;
+        }
+        return malwareLists[getEcoSystem()];
+      },
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeInformation: (message) => output.push(message),
+        writeWarning: () => {},
+        writeError: (message) => output.push(message),
+        emptyLine: () => {},
+      },
+    },
+  });
+
+  const { dumpFeed } = await import("./dumpFeed.js");
+
+  beforeEach(() => {
+    output.length = 0;
+    loadError = undefined;
+    setEcoSystem(ECOSYSTEM_JS);
+  });
+
+  it("prints the entries of every ecosystem with counts per ecosystem", async () => {
+    const exitCode = await dumpFeed([]);
+
+    assert.strictEqual(exitCode, 0);
+    const dump = JSON.parse(output[0]);
+    assert.deepStrictEqual(dump.summary, {
+      npm: { version: '"npm-1"', entries: 3, matching: 3 },
+      pypi: { version: null, entries: 1, matching: 1 },
+    });
+    assert.deepStrictEqual(dump.entries[0], {
+      ecosystem: "npm",
+      package_name: "safe-chain-test",
+      version: "0.0.1-security",
+      reason: "MALWARE",
+    });
+    assert.strictEqual(dump.entries[3].ecosystem, "pypi");
+  });
+
+  it("only loads the requested ecosystem", async () => {
+    const exitCode = await dumpFeed(["--ecosystem", "pypi"]);
+
+    assert.strictEqual(exitCode, 0);
+    const dump = JSON.parse(output[0]);
+    assert.deepStrictEqual(Object.keys(dump.summary), ["pypi"]);
+    assert.deepStrictEqual(
+      dump.entries.map((entry) => entry.package_name),
+      ["left_pad"]
+    );
+  });
+
+  it("only prints entries whose name contains the filter, ignoring case", async () => {
+    const exitCode = await dumpFeed(["--grep=left"]);
+
+    assert.strictEqual(exitCode, 0);
+    const dump = JSON.parse(output[0]);
+    assert.deepStrictEqual(
+      dump.entries.map((entry) => `${entry.ecosystem}:${entry.package_name}`),
+      ["npm:@acme/Left-Pad", "pypi:left_pad"]
+    );
+    assert.strictEqual(dump.summary.npm.entries, 3);
+    assert.strictEqual(dump.summary.npm.matching, 1);
+  });
+
+  it("restores the ecosystem of the invocation", async () => {
+    await dumpFeed([]);
+
+    assert.strictEqual(getEcoSystem(), ECOSYSTEM_JS);
+  });
+
+  it("rejects an unknown ecosystem", async () => {
+    const exitCode = await dumpFeed(["--ecosystem=cargo"]);
+
+    assert.strictEqual(exitCode, 1);
+    assert.match(output[0], /^Usage: safe-chain dump-feed/);
+  });
+
+  it("exits with the code of the error when the malware list is unavailable", async () => {
+    loadError = new SafeChainStartupError(
+      EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+      "Safe-chain runs offline, but no cached malware database is available."
+    );
+
+    const exitCode = await dumpFeed(["--ecosystem=npm"]);
+
+    assert.strictEqual(exitCode, EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE);
+    assert.match(output[0], /^Failed to load the malware list: Safe-chain/);
+  });
+});
@@ -0,0 +1,68 @@
+import { getFeedMaxStalenessMins } from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+
+/**
+ * @type {{refreshedAt: number | undefined, warned: boolean}}
+ */
+const state = {
+  refreshedAt: undefined,
+  warned: false,
+};
+
+/**
+ * Records when the malware list in use was last refreshed from the server. A
+ * list from the local cache is still used when the server cannot be reached,
+ * so blocks keep working, but newly reported malware is missing from it. A
+ * list older than the maximum staleness is reported once.
+ *
+ * @param {number | undefined} refreshedAt - Milliseconds since the epoch, undefined when not known
+ * @returns {void}
+ */
+export function recordFeedRefresh(refreshedAt) {
+  state.refreshedAt = refreshedAt;
+
+  if (isFeedStale() && !state.warned) {
+    state.warned = true;
+    ui.writeWarning(
+      `Safe-chain: feed.stale - ${describeFeedAge()}, longer than the maximum of ${getFeedMaxStalenessMins()} minutes. Recently reported malware may not be blocked.`
+    );
+  }
+}
+
+/**
+ * @param {number} [now]
+ * @returns {number | undefined} - Seconds since the last refresh, undefined when not known
+ */
+export function getFeedAgeSeconds(now = Date.now()) {
+  if (state.refreshedAt === undefined) {
+    return undefined;
+  }
+  return Math.max(0, Math.floor((now - state.refreshedAt) / 1000));
+}
+
+/**
+ * @param {number} [now]
+ * @returns {boolean}
+ */
+export function isFeedStale(now = Date.now()) {
+  const ageSeconds = getFeedAgeSeconds(now);
+  return (
+    ageSeconds !== undefined && ageSeconds > getFeedMaxStalenessMins() * 60
+  );
+}
+
+/**
+ * @returns {string}
+ */
+export function describeFeedAge() {
+  const hours = ((getFeedAgeSeconds() ?? 0) / (60 * 60)).toFixed(1);
+  return `The malware list was last refreshed ${hours} hours ago`;
+}
+
+/**
+ * @returns {void}
+ */
+export function resetFeedFreshness() {
+  state.refreshedAt = undefined;
+  state.warned = false;
+}
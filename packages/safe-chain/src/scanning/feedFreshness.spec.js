@@ -0,0 +1,62 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+
+describe("feedFreshness", async () => {
+  const warnings = [];
+  let feedMaxStalenessMins = 60;
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      getFeedMaxStalenessMins: () => feedMaxStalenessMins,
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeWarning: (message) => warnings.push(message),
+      },
+    },
+  });
+
+  const {
+    getFeedAgeSeconds,
+    isFeedStale,
+    recordFeedRefresh,
+    resetFeedFreshness,
+  } = await import("./feedFreshness.js");
+
+  beforeEach(() => {
+    resetFeedFreshness();
+    feedMaxStalenessMins = 60;
+    warnings.length = 0;
+  });
+
+  it("does not report a feed that was refreshed recently", () => {
+    recordFeedRefresh(Date.now() - 5 * 60 * 1000);
+
+    assert.strictEqual(isFeedStale(), false);
+    assert.ok(Math.abs(getFeedAgeSeconds() - 5 * 60) <= 1);
+    assert.deepStrictEqual(warnings, []);
+  });
+
+  it("reports a stale feed once", () => {
+    recordFeedRefresh(Date.now() - 3 * 60 * 60 * 1000);
+    recordFeedRefresh(Date.now() - 3 * 60 * 60 * 1000);
+
+    assert.strictEqual(isFeedStale(), true);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(
+      warnings[0],
+      /feed\.stale - The malware list was last refreshed 3\.0 hours ago, longer than the maximum of 60 minutes/
+    );
+  });
+
+  it("does not report a feed of unknown age", () => {
+    recordFeedRefresh(undefined);
+
+    assert.strictEqual(getFeedAgeSeconds(), undefined);
+    assert.strictEqual(isFeedStale(), false);
+    assert.deepStrictEqual(warnings, []);
+  });
+});
@@ -0,0 +1,31 @@
+/**
+ * @type {{version: string | undefined}}
+ */
+const state = {
+  version: undefined,
+};
+
+/**
+ * Records the version of the malware list in use, so decisions can be traced
+ * back to the exact list they were made with.
+ *
+ * @param {string | undefined} version - Undefined when the list has no version
+ * @returns {void}
+ */
+export function recordFeedVersion(version) {
+  state.version = version;
+}
+
+/**
+ * @returns {string | undefined} - Undefined until a list with a version is loaded
+ */
+export function getFeedVersion() {
+  return state.version;
+}
+
+/**
+ * @returns {void}
+ */
+export function resetFeedVersion() {
+  state.version = undefined;
+}
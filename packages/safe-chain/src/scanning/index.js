@@ -1,5 +1,6 @@
 import { auditChanges } from "./audit/index.js";
 import { getScanTimeout } from "../config/configFile.js";
+import { getInspectOnly } from "../config/settings.js";
 import { setTimeout } from "timers/promises";
 import chalk from "chalk";
 import { getPackageManager } from "../packagemanager/currentPackageManager.js";
@@ -56,22 +57,33 @@ export async function scanCommand(args) {
     return 0;
   } else {
     printMaliciousChanges(audit.disallowedChanges);
+    if (getInspectOnly()) {
+      ui.writeWarning(
+        "Safe-chain: inspect.would-block - continuing the install in inspect-only mode."
+      );
+      return 0;
+    }
     onMalwareFound();
     return 1;
   }
 }
 
 /**
- * @param {import("./audit/index.js").PackageChange[]} changes
+ * @param {import("./audit/index.js").AuditResult["disallowedChanges"]} changes
  * @return {void}
  */
 function printMaliciousChanges(changes) {
-  ui.writeInformation(
+  ui.writeDecision(
     chalk.red("✖") + " Safe-chain: " + chalk.bold("Malicious changes detected:")
   );
 
   for (const change of changes) {
-    ui.writeInformation(` - ${change.name}@${change.version}`);
+    const advisories = change.advisories ?? [];
+    ui.writeDecision(
+      ` - ${change.name}@${change.version}${
+        advisories.length > 0 ? ` (advisories: ${advisories.join(", ")})` : ""
+      }`
+    );
   }
 }
 
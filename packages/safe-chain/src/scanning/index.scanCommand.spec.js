@@ -23,6 +23,7 @@ describe("scanCommand", async () => {
     namedExports: {
       getScanTimeout: getScanTimeoutMock,
       getBaseUrl: () => undefined,
+      getInspectOnly: () => undefined,
     },
   });
 
@@ -32,6 +33,7 @@ describe("scanCommand", async () => {
       ui: {
         writeError: () => {},
         writeInformation: () => {},
+        writeDecision: () => {},
         writeWarning: () => {},
         writeExitWithoutInstallingMaliciousPackages: () => {},
         emptyLine: () => {},
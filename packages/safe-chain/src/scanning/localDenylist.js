@@ -0,0 +1,243 @@
+import fs from "fs";
+import {
+  readDatabaseFromLocalCache,
+  readLocalDenylist,
+  writeLocalDenylist,
+} from "../config/configFile.js";
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  getEcoSystem,
+  setEcoSystem,
+} from "../config/settings.js";
+import { ui } from "../environment/userInteraction.js";
+import { normalizePipPackageName } from "./packageNameVariants.js";
+
+/**
+ * @typedef {Object} DenylistEntry
+ * @property {string} ecosystem - ECOSYSTEM_JS or ECOSYSTEM_PY
+ * @property {string} package_name
+ *
+ * @typedef {Object} DenylistImportResult
+ * @property {number} added
+ * @property {number} duplicates - Already on the local denylist or repeated in the file
+ * @property {number} alreadyInFeed - Every version is already blocked by the malware database
+ * @property {{line: number, value: string}[]} rejected
+ */
+
+/** @type {Record<string, string>} */
+const ecosystemPrefixes = {
+  npm: ECOSYSTEM_JS,
+  pypi: ECOSYSTEM_PY,
+};
+
+const NPM_PACKAGE_NAME_PATTERN = /^(?:@[a-z0-9][a-z0-9._~-]*\/)?[a-z0-9][a-z0-9._~-]*$/i;
+const NPM_PACKAGE_NAME_MAX_LENGTH = 214;
+const MAX_REPORTED_REJECTIONS = 10;
+const PYPI_PACKAGE_NAME_PATTERN = /^([a-z0-9]|[a-z0-9][a-z0-9._-]*[a-z0-9])$/i;
+
+/** @type {{keys: Set<string> | null}} */
+const state = {
+  keys: null,
+};
+
+/**
+ * @param {string} ecosystem
+ * @param {string} packageName
+ * @returns {string}
+ */
+function getEntryKey(ecosystem, packageName) {
+  const name =
+    ecosystem === ECOSYSTEM_PY
+      ? normalizePipPackageName(packageName)
+      : packageName;
+  return `${ecosystem}:${name}`;
+}
+
+/**
+ * Parses one `ecosystem:name` line, for example `npm:@scope/pkg` or `pypi:requests`.
+ *
+ * @param {string} line
+ * @returns {DenylistEntry | undefined}
+ */
+export function parseDenylistLine(line) {
+  const separatorIndex = line.indexOf(":");
+  if (separatorIndex <= 0) {
+    return undefined;
+  }
+
+  const ecosystem =
+    ecosystemPrefixes[line.slice(0, separatorIndex).trim().toLowerCase()];
+  const packageName = line.slice(separatorIndex + 1).trim();
+
+  if (ecosystem === ECOSYSTEM_JS) {
+    if (
+      packageName.length > NPM_PACKAGE_NAME_MAX_LENGTH ||
+      !NPM_PACKAGE_NAME_PATTERN.test(packageName)
+    ) {
+      return undefined;
+    }
+    return { ecosystem, package_name: packageName };
+  }
+
+  if (ecosystem === ECOSYSTEM_PY) {
+    if (!PYPI_PACKAGE_NAME_PATTERN.test(packageName)) {
+      return undefined;
+    }
+    return { ecosystem, package_name: packageName };
+  }
+
+  return undefined;
+}
+
+/**
+ * Validates a newline-delimited list of `ecosystem:name` entries and merges it
+ * into the local denylist. Blank lines and lines starting with `#` are ignored.
+ *
+ * @param {string} filePath
+ * @returns {DenylistImportResult}
+ */
+export function importDenylistFile(filePath) {
+  const lines = fs.readFileSync(filePath, "utf8").split(/\r?\n/);
+
+  const existingEntries = readLocalDenylist();
+  const knownKeys = new Set(
+    existingEntries.map((entry) =>
+      getEntryKey(entry.ecosystem, entry.package_name)
+    )
+  );
+  /** @type {Record<string, Set<string>>} */
+  const feedKeys = {};
+
+  /** @type {DenylistEntry[]} */
+  const newEntries = [];
+  /** @type {DenylistImportResult} */
+  const result = { added: 0, duplicates: 0, alreadyInFeed: 0, rejected: [] };
+
+  lines.forEach((rawLine, index) => {
+    const line = rawLine.trim();
+    if (line.length === 0 || line.startsWith("#")) {
+      return;
+    }
+
+    const entry = parseDenylistLine(line);
+    if (!entry) {
+      result.rejected.push({ line: index + 1, value: line });
+      return;
+    }
+
+    const key = getEntryKey(entry.ecosystem, entry.package_name);
+    if (knownKeys.has(key)) {
+      result.duplicates++;
+      return;
+    }
+    knownKeys.add(key);
+
+    feedKeys[entry.ecosystem] ??= readFeedKeys(entry.ecosystem);
+    if (feedKeys[entry.ecosystem].has(key)) {
+      result.alreadyInFeed++;
+      return;
+    }
+
+    newEntries.push(entry);
+  });
+
+  if (newEntries.length > 0) {
+    writeLocalDenylist([...existingEntries, ...newEntries]);
+  }
+  result.added = newEntries.length;
+  reloadLocalDenylist();
+
+  return result;
+}
+
+/**
+ * Handles `safe-chain import-denylist <FILE>`: imports the file and reports the result.
+ *
+ * @param {string | undefined} filePath
+ * @returns {number} - The exit code
+ */
+export function importDenylist(filePath) {
+  if (!filePath) {
+    ui.writeError("Usage: safe-chain import-denylist <FILE>");
+    return 1;
+  }
+
+  /** @type {DenylistImportResult} */
+  let result;
+  try {
+    result = importDenylistFile(filePath);
+  } catch (/** @type {any} */ error) {
+    ui.writeError(`Failed to import ${filePath}: ${error.message}`);
+    return 1;
+  }
+
+  ui.writeInformation(
+    `Imported ${filePath}: ${result.added} added, ${result.duplicates} duplicate(s), ${result.alreadyInFeed} already in the malware database, ${result.rejected.length} rejected.`
+  );
+  const reportedRejections = result.rejected.slice(0, MAX_REPORTED_REJECTIONS);
+  for (const { line, value } of reportedRejections) {
+    ui.writeWarning(`  Line ${line}: invalid entry "${value}"`);
+  }
+  if (result.rejected.length > MAX_REPORTED_REJECTIONS) {
+    ui.writeWarning(
+      `  ... and ${result.rejected.length - MAX_REPORTED_REJECTIONS} more invalid entries`
+    );
+  }
+  if (result.added > 0) {
+    ui.writeInformation(
+      "Running safe-chain proxies with a maximum lifetime enforce the new entries after a reload: send them SIGHUP (kill -HUP <pid>)."
+    );
+  }
+
+  return 0;
+}
+
+/**
+ * Keys of the packages for which the cached malware database blocks every version.
+ *
+ * @param {string} ecosystem
+ * @returns {Set<string>}
+ */
+function readFeedKeys(ecosystem) {
+  const previousEcosystem = getEcoSystem();
+  setEcoSystem(ecosystem);
+  try {
+    const { malwareDatabase } = readDatabaseFromLocalCache();
+    return new Set(
+      (malwareDatabase ?? [])
+        .filter((pkg) => pkg.version === "*")
+        .map((pkg) => getEntryKey(ecosystem, pkg.package_name))
+    );
+  } finally {
+    setEcoSystem(previousEcosystem);
+  }
+}
+
+/**
+ * @param {string} packageName
+ * @returns {boolean}
+ */
+export function isOnLocalDenylist(packageName) {
+  if (!state.keys) {
+    reloadLocalDenylist();
+  }
+
+  return /** @type {Set<string>} */ (state.keys).has(
+    getEntryKey(getEcoSystem(), packageName)
+  );
+}
+
+/**
+ * Re-reads the local denylist from disk, so entries imported while safe-chain
+ * is running are enforced right away.
+ *
+ * @returns {number} - The number of entries on the local denylist
+ */
+export function reloadLocalDenylist() {
+  const entries = readLocalDenylist();
+  state.keys = new Set(
+    entries.map((entry) => getEntryKey(entry.ecosystem, entry.package_name))
+  );
+  return entries.length;
+}
@@ -0,0 +1,150 @@
+import { describe, it, mock, beforeEach } from "node:test";
+import assert from "node:assert";
+import fs from "fs";
+import os from "os";
+import path from "path";
+
+describe("localDenylist", async () => {
+  let storedDenylist = [];
+  let feedDatabases = {};
+  let currentEcosystem = "js";
+
+  mock.module("../config/configFile.js", {
+    namedExports: {
+      readLocalDenylist: () => storedDenylist,
+      writeLocalDenylist: (entries) => (storedDenylist = entries),
+      readDatabaseFromLocalCache: () => ({
+        malwareDatabase: feedDatabases[currentEcosystem] ?? null,
+        version: null,
+      }),
+    },
+  });
+
+  mock.module("../config/settings.js", {
+    namedExports: {
+      ECOSYSTEM_JS: "js",
+      ECOSYSTEM_PY: "py",
+      getEcoSystem: () => currentEcosystem,
+      setEcoSystem: (ecosystem) => (currentEcosystem = ecosystem),
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeInformation: () => {},
+        writeWarning: () => {},
+        writeError: () => {},
+      },
+    },
+  });
+
+  const {
+    parseDenylistLine,
+    importDenylistFile,
+    isOnLocalDenylist,
+    reloadLocalDenylist,
+  } = await import("./localDenylist.js");
+
+  /**
+   * @param {string} content
+   * @returns {string}
+   */
+  function writeListFile(content) {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "safe-chain-denylist-"));
+    const filePath = path.join(dir, "affected.txt");
+    fs.writeFileSync(filePath, content);
+    return filePath;
+  }
+
+  beforeEach(() => {
+    storedDenylist = [];
+    feedDatabases = {};
+    currentEcosystem = "js";
+    reloadLocalDenylist();
+  });
+
+  it("parses npm and pypi entries", () => {
+    assert.deepStrictEqual(parseDenylistLine("npm:@ctrl/tinycolor"), {
+      ecosystem: "js",
+      package_name: "@ctrl/tinycolor",
+    });
+    assert.deepStrictEqual(parseDenylistLine("PyPI:Some_Package"), {
+      ecosystem: "py",
+      package_name: "Some_Package",
+    });
+  });
+
+  it("rejects unknown ecosystems and invalid names", () => {
+    assert.strictEqual(parseDenylistLine("cargo:serde"), undefined);
+    assert.strictEqual(parseDenylistLine("lodash"), undefined);
+    assert.strictEqual(parseDenylistLine("npm:"), undefined);
+    assert.strictEqual(parseDenylistLine("npm:has space"), undefined);
+    assert.strictEqual(parseDenylistLine("pypi:-leading-dash"), undefined);
+  });
+
+  it("merges a list and reports added, duplicate, feed and rejected entries", () => {
+    storedDenylist = [{ ecosystem: "js", package_name: "already-listed" }];
+    feedDatabases.js = [
+      { package_name: "in-feed", version: "*", reason: "MALWARE" },
+      {
+        package_name: "one-version-in-feed",
+        version: "1.0.0",
+        reason: "MALWARE",
+      },
+    ];
+    const filePath = writeListFile(
+      [
+        "# Affected packages",
+        "npm:already-listed",
+        "npm:in-feed",
+        "npm:one-version-in-feed",
+        "npm:@ctrl/tinycolor",
+        "npm:@ctrl/tinycolor",
+        "",
+        "pypi:requests_toolbelt",
+        "pypi:requests-toolbelt",
+        "not an entry",
+      ].join("\n")
+    );
+
+    const result = importDenylistFile(filePath);
+
+    assert.strictEqual(result.added, 3);
+    assert.strictEqual(result.duplicates, 3);
+    assert.strictEqual(result.alreadyInFeed, 1);
+    assert.deepStrictEqual(result.rejected, [
+      { line: 10, value: "not an entry" },
+    ]);
+    assert.deepStrictEqual(
+      storedDenylist.map((entry) => entry.package_name),
+      [
+        "already-listed",
+        "one-version-in-feed",
+        "@ctrl/tinycolor",
+        "requests_toolbelt",
+      ]
+    );
+  });
+
+  it("enforces imported entries for the current ecosystem", () => {
+    importDenylistFile(writeListFile("npm:evil-pkg\npypi:Evil.Py\n"));
+
+    assert.strictEqual(isOnLocalDenylist("evil-pkg"), true);
+    assert.strictEqual(isOnLocalDenylist("evil-py"), false);
+
+    currentEcosystem = "py";
+    assert.strictEqual(isOnLocalDenylist("evil_py"), true);
+    assert.strictEqual(isOnLocalDenylist("evil-pkg"), false);
+  });
+
+  it("picks up entries written by another process after a reload", () => {
+    assert.strictEqual(isOnLocalDenylist("late-entry"), false);
+
+    storedDenylist = [{ ecosystem: "js", package_name: "late-entry" }];
+    assert.strictEqual(isOnLocalDenylist("late-entry"), false);
+
+    reloadLocalDenylist();
+    assert.strictEqual(isOnLocalDenylist("late-entry"), true);
+  });
+});
@@ -1,18 +1,49 @@
+import * as semver from "semver";
 import {
   fetchMalwareDatabase,
   fetchMalwareDatabaseVersion,
+  fetchMalwareFeed,
 } from "../api/aikido.js";
 import {
+  markDatabaseCacheRefreshed,
   readDatabaseFromLocalCache,
   writeDatabaseToLocalCache,
 } from "../config/configFile.js";
 import { ui } from "../environment/userInteraction.js";
-import { getEcoSystem, ECOSYSTEM_PY } from "../config/settings.js";
+import {
+  getEcoSystem,
+  getFailOnStaleFeed,
+  getFeedEntryMinAgeMins,
+  getMalwareFeedUrls,
+  getMalwareListBaseUrl,
+  getOffline,
+  getUseTestFixture,
+  ECOSYSTEM_PY,
+} from "../config/settings.js";
+import {
+  EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+  SafeChainStartupError,
+} from "../utils/exitCodes.js";
+import {
+  describeFeedAge,
+  isFeedStale,
+  recordFeedRefresh,
+} from "./feedFreshness.js";
+import { isOnLocalDenylist } from "./localDenylist.js";
+import { recordFeedVersion } from "./feedVersion.js";
+import { getTestFixtureMalwareList } from "./testFixture.js";
 
 /**
  * @typedef {Object} MalwareDatabase
- * @property {function(string, string): string} getPackageStatus
- * @property {function(string, string): boolean} isMalware
+ * @property {function(string, string, number=): string} getPackageStatus
+ * @property {function(string, string, number=): boolean} isMalware
+ * @property {function(string, string, number=): string[]} getAdvisories
+ * @property {number} entryCount - Entries of all feeds, after merging
+ * @property {string | undefined} version - Version of the malware list, when known
+ *
+ * @typedef {Object} LoadedMalwareList
+ * @property {import("../api/aikido.js").MalwarePackage[]} entries
+ * @property {string | undefined} version
  */
 
 // Caching the Promise (rather than the resolved database) prevents duplicate fetches. If we cached the resolved
@@ -22,6 +53,10 @@ import { getEcoSystem, ECOSYSTEM_PY } from "../config/settings.js";
 /** @type {Promise<MalwareDatabase> | null} */
 let cachedMalwareDatabasePromise = null;
 
+// "name@version" of the staged feed entries a warning was written for
+/** @type {Set<string>} */
+const reportedStagedEntries = new Set();
+
 /**
  * Normalize package name for comparison.
  * For Python packages (PEP-503): lowercase and replace _, -, . with -
@@ -40,82 +75,409 @@ function normalizePackageName(name) {
 
 export function openMalwareDatabase() {
   if (!cachedMalwareDatabasePromise) {
-    cachedMalwareDatabasePromise = getMalwareDatabase().then((malwareDatabase) => {
-      /**
-       * @param {string} name
-       * @param {string} version
-       * @returns {string}
-       */
-      function getPackageStatus(name, version) {
-        const normalizedName = normalizePackageName(name);
-        const packageData = malwareDatabase.find(
-          (pkg) => {
-            const normalizedPkgName = normalizePackageName(pkg.package_name);
-            return normalizedPkgName === normalizedName &&
-              (pkg.version === version || pkg.version === "*");
-          }
-        );
+    cachedMalwareDatabasePromise = getMergedMalwareDatabase()
+      .then(createMalwareDatabase)
+      .catch((error) => {
+        cachedMalwareDatabasePromise = null;
+        throw error;
+      });
+  }
+  return cachedMalwareDatabasePromise;
+}
+
+/**
+ * Loads the malware list again and replaces the one in use, for pushing an
+ * urgent update into a running proxy. Unlike the first load, a download that
+ * fails is reported instead of falling back on the cached copy, and the list
+ * in use is kept.
+ *
+ * @returns {Promise<MalwareDatabase>}
+ */
+export async function refreshMalwareDatabase() {
+  const database = createMalwareDatabase(
+    await getMergedMalwareDatabase({ requireDownload: true })
+  );
+  cachedMalwareDatabasePromise = Promise.resolve(database);
+  return database;
+}
 
-        if (!packageData) {
-          return MALWARE_STATUS_OK;
-        }
+/**
+ * Loads the malware list of the current ecosystem, merged with the additional
+ * feeds, to inspect its entries. The list in use is not replaced.
+ *
+ * @returns {Promise<LoadedMalwareList>}
+ */
+export function loadMalwareList() {
+  return getMergedMalwareDatabase();
+}
+
+/**
+ * @param {LoadedMalwareList} malwareList
+ * @returns {MalwareDatabase}
+ */
+function createMalwareDatabase({ entries: malwareDatabase, version }) {
+  recordFeedVersion(version);
 
-        return packageData.reason;
+  /**
+   * @param {string} name
+   * @param {string} version
+   * @param {number} [publishedAt] - Publish time of the version in milliseconds since the epoch, when known
+   * @returns {import("../api/aikido.js").MalwarePackage | undefined}
+   */
+  function findPackageData(name, version, publishedAt) {
+    const normalizedName = normalizePackageName(name);
+    return malwareDatabase.find(
+      (pkg) => {
+        const normalizedPkgName = normalizePackageName(pkg.package_name);
+        return normalizedPkgName === normalizedName &&
+          matchesEntryVersion(pkg, version, publishedAt);
       }
+    );
+  }
+
+  /**
+   * @param {string} name
+   * @param {string} version
+   * @param {number} [publishedAt]
+   * @returns {string}
+   */
+  function getPackageStatus(name, version, publishedAt) {
+    if (isOnLocalDenylist(name)) {
+      return MALWARE_STATUS_MALWARE;
+    }
+
+    const packageData = findPackageData(name, version, publishedAt);
 
-      return {
-        getPackageStatus,
-        isMalware: (/** @type {string} */ name, /** @type {string} */ version) => {
-          const status = getPackageStatus(name, version);
-          return isMalwareStatus(status);
-        },
-      };
-    }).catch((error) => {
-      cachedMalwareDatabasePromise = null;
-      throw error;
-    });
+    if (!packageData || isStagedEntry(packageData)) {
+      return MALWARE_STATUS_OK;
+    }
+
+    return packageData.reason;
   }
-  return cachedMalwareDatabasePromise;
+
+  return {
+    getPackageStatus,
+    isMalware: (
+      /** @type {string} */ name,
+      /** @type {string} */ version,
+      /** @type {number | undefined} */ publishedAt
+    ) => {
+      const status = getPackageStatus(name, version, publishedAt);
+      return isMalwareStatus(status);
+    },
+    getAdvisories: (
+      /** @type {string} */ name,
+      /** @type {string} */ version,
+      /** @type {number | undefined} */ publishedAt
+    ) => getAdvisoryIds(findPackageData(name, version, publishedAt)),
+    entryCount: malwareDatabase.length,
+    version,
+  };
+}
+
+/**
+ * Checks whether a feed entry covers a version. The `version` of an entry is
+ * an exact version, "*" for every version, or a SemVer range such as
+ * ">=2.0.1" or "1.0.1 || 1.0.3". An entry with `published_after` only covers
+ * versions published after that time. A version with an unknown publish time
+ * is covered, so a cutoff cannot let through a version the range blocks.
+ *
+ * @param {import("../api/aikido.js").MalwarePackage} entry
+ * @param {string} version
+ * @param {number} [publishedAt] - Milliseconds since the epoch
+ * @returns {boolean}
+ */
+export function matchesEntryVersion(entry, version, publishedAt) {
+  const versionMatches =
+    entry.version === version ||
+    entry.version === "*" ||
+    (isVersionRange(entry.version) &&
+      semver.satisfies(version, entry.version, { includePrerelease: true }));
+  if (!versionMatches) {
+    return false;
+  }
+
+  const cutoff = parsePublishCutoff(entry.published_after);
+  return (
+    cutoff === undefined || publishedAt === undefined || publishedAt > cutoff
+  );
+}
+
+/**
+ * Exact versions are compared as strings, so "1.0" only matches "1.0" and not
+ * every 1.0.x version. Only a version with a comparison operator or several
+ * alternatives is a range.
+ *
+ * @param {string} version
+ * @returns {boolean}
+ */
+function isVersionRange(version) {
+  return (
+    typeof version === "string" &&
+    (/^\s*[<>=^~]/.test(version) || version.includes("||")) &&
+    semver.validRange(version) !== null
+  );
+}
+
+/**
+ * @param {number | string | undefined} publishedAfter - Unix timestamp (seconds) or a date such as "2026-03-01"
+ * @returns {number | undefined} - Milliseconds since the epoch
+ */
+function parsePublishCutoff(publishedAfter) {
+  if (typeof publishedAfter === "number") {
+    return publishedAfter * 1000;
+  }
+  if (typeof publishedAfter === "string") {
+    const cutoff = Date.parse(publishedAfter);
+    return Number.isNaN(cutoff) ? undefined : cutoff;
+  }
+  return undefined;
+}
+
+/**
+ * @typedef {Object} MalwareFeed
+ * @property {string} source - Base URL of the feed
+ * @property {import("../api/aikido.js").MalwarePackage[]} entries
+ */
+
+/**
+ * Merges malware feeds, given in ascending precedence. When feeds list the same
+ * package version, the entry of the later feed wins, so an internal feed can
+ * also clear a false positive with an `OK` reason. Every merged entry records
+ * the feed it came from.
+ *
+ * @param {MalwareFeed[]} feeds
+ * @returns {import("../api/aikido.js").MalwarePackage[]}
+ */
+export function mergeMalwareFeeds(feeds) {
+  /** @type {Map<string, import("../api/aikido.js").MalwarePackage>} */
+  const merged = new Map();
+
+  for (const { source, entries } of feeds) {
+    ui.writeVerbose(`Safe-chain: Malware feed ${source} has ${entries.length} entries`);
+
+    for (const entry of entries) {
+      const key = `${normalizePackageName(entry.package_name)}@${entry.version}`;
+      const overridden = merged.get(key);
+      if (overridden) {
+        ui.writeVerbose(
+          `Safe-chain: ${source} overrides ${entry.package_name}@${entry.version} from ${overridden.source}`
+        );
+        // Re-inserted below, so the entry moves to the position of its new feed
+        merged.delete(key);
+      }
+      merged.set(key, { ...entry, source });
+    }
+  }
+
+  // Lookups use the first matching entry (exact version or "*"), so the
+  // entries of the feed with the highest precedence go first.
+  return [...merged.values()].reverse();
+}
+
+/**
+ * @typedef {Object} LoadOptions
+ * @property {boolean} [requireDownload] - Fail instead of using the cached
+ * copy when the malware list cannot be downloaded
+ */
+
+/**
+ * @param {LoadOptions} [options]
+ * @returns {Promise<LoadedMalwareList>}
+ */
+async function getMergedMalwareDatabase(options = {}) {
+  // The fixture replaces the malware list and all additional feeds
+  if (getUseTestFixture()) {
+    recordFeedRefresh(Date.now());
+    return getTestFixtureMalwareList();
+  }
+
+  const { entries: malwareDatabase, version } = await getMalwareDatabase(
+    options
+  );
+  const feedUrls = getMalwareFeedUrls();
+  if (feedUrls.length === 0) {
+    return { entries: malwareDatabase, version };
+  }
+  if (getOffline()) {
+    ui.writeWarning(
+      `Safe-chain: offline - not loading ${feedUrls.length} additional malware feed(s), they are not cached.`
+    );
+    return { entries: malwareDatabase, version };
+  }
+
+  // Additional feeds are fetched on every run; only the malware list itself is
+  // cached. A feed that cannot be loaded does not stop the install.
+  const additionalFeeds = await Promise.all(
+    feedUrls.map(async (source) => {
+      try {
+        return { source, entries: await fetchMalwareFeed(source) };
+      } catch (/** @type {any} */ error) {
+        ui.writeWarning(
+          `Safe-chain: Could not load malware feed ${source}, continuing without it: ${error.message}`
+        );
+        return { source, entries: [] };
+      }
+    })
+  );
+
+  return {
+    entries: mergeMalwareFeeds([
+      { source: getMalwareListBaseUrl(), entries: malwareDatabase },
+      ...additionalFeeds,
+    ]),
+    version,
+  };
 }
 
 /**
- * @returns {Promise<import("../api/aikido.js").MalwarePackage[]>}
+ * @param {LoadOptions} options
+ * @returns {Promise<LoadedMalwareList>}
  */
-async function getMalwareDatabase() {
-  const { malwareDatabase: cachedDatabase, version: cachedVersion } =
-    readDatabaseFromLocalCache();
+async function getMalwareDatabase(options) {
+  const {
+    malwareDatabase: cachedDatabase,
+    version: cachedVersion,
+    refreshedAt: cachedRefreshedAt,
+  } = readDatabaseFromLocalCache();
+
+  if (getOffline()) {
+    return getOfflineMalwareDatabase(
+      cachedDatabase,
+      cachedVersion,
+      cachedRefreshedAt
+    );
+  }
 
   try {
     if (cachedDatabase) {
       const currentVersion = await fetchMalwareDatabaseVersion();
       if (cachedVersion === currentVersion) {
-        return cachedDatabase;
+        markDatabaseCacheRefreshed();
+        recordFeedRefresh(Date.now());
+        return { entries: cachedDatabase, version: cachedVersion ?? undefined };
       }
     }
 
     const { malwareDatabase, version } = await fetchMalwareDatabase();
+    recordFeedRefresh(Date.now());
 
     if (version) {
       // Only cache the malware database when we have a version.
       writeDatabaseToLocalCache(malwareDatabase, version);
-      return malwareDatabase;
+      return { entries: malwareDatabase, version };
     } else {
       // We received a valid malware database, but the response
       // did not contain an etag header with the version
       ui.writeWarning(
         "The malware database was downloaded, but could not be cached due to a missing version."
       );
-      return malwareDatabase;
+      return { entries: malwareDatabase, version: undefined };
     }
   } catch (/** @type any */ error) {
-    if (cachedDatabase) {
+    if (cachedDatabase && !options.requireDownload) {
       ui.writeWarning(
         "Failed to fetch the latest malware database. Using cached version."
       );
-      return cachedDatabase;
+      recordFeedRefresh(cachedRefreshedAt ?? undefined);
+      if (isFeedStale() && getFailOnStaleFeed()) {
+        throw new SafeChainStartupError(
+          EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+          `${describeFeedAge()} and the latest version could not be downloaded (${error.message}). Refusing to continue with a stale malware list.`,
+          { cause: error }
+        );
+      }
+      return { entries: cachedDatabase, version: cachedVersion ?? undefined };
     }
-    throw error;
+    throw new SafeChainStartupError(
+      EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+      `The malware database could not be downloaded and no cached copy is available (${error.message}). Check your network connection or the malware list base URL.`,
+      { cause: error }
+    );
+  }
+}
+
+/**
+ * In offline mode the cached malware list is used without checking for a
+ * newer version. Its age is still reported, and enforced with
+ * --safe-chain-fail-on-stale-feed.
+ *
+ * @param {import("../api/aikido.js").MalwarePackage[] | null} cachedDatabase
+ * @param {string | null} cachedVersion
+ * @param {number | null} cachedRefreshedAt
+ * @returns {LoadedMalwareList}
+ */
+function getOfflineMalwareDatabase(
+  cachedDatabase,
+  cachedVersion,
+  cachedRefreshedAt
+) {
+  if (!cachedDatabase) {
+    throw new SafeChainStartupError(
+      EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+      "Safe-chain runs offline, but no cached malware database is available. Run safe-chain once with network access to cache it."
+    );
   }
+
+  recordFeedRefresh(cachedRefreshedAt ?? undefined);
+  if (isFeedStale() && getFailOnStaleFeed()) {
+    throw new SafeChainStartupError(
+      EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+      `${describeFeedAge()} and safe-chain runs offline. Refusing to continue with a stale malware list.`
+    );
+  }
+  return { entries: cachedDatabase, version: cachedVersion ?? undefined };
+}
+
+/**
+ * Checks whether a feed entry is younger than the grace window. Such entries
+ * are reported but not enforced, so a false positive pushed to the feed can be
+ * caught before it blocks installs. Entries without `added_on` are enforced.
+ *
+ * @param {import("../api/aikido.js").MalwarePackage} packageData
+ * @returns {boolean}
+ */
+function isStagedEntry(packageData) {
+  const minAgeMins = getFeedEntryMinAgeMins();
+  if (
+    minAgeMins <= 0 ||
+    typeof packageData.added_on !== "number" ||
+    !isMalwareStatus(packageData.reason)
+  ) {
+    return false;
+  }
+
+  const ageMins = (Date.now() / 1000 - packageData.added_on) / 60;
+  if (ageMins >= minAgeMins) {
+    return false;
+  }
+
+  const key = `${packageData.package_name}@${packageData.version}`;
+  if (!reportedStagedEntries.has(key)) {
+    reportedStagedEntries.add(key);
+    ui.writeWarning(
+      `Safe-chain: feed-entry.staged - ${key} was added to the malware feed ${Math.max(0, Math.floor(ageMins))} minutes ago and is enforced once it is ${minAgeMins} minutes old. Allowing it for now.`
+    );
+  }
+  return true;
+}
+
+/**
+ * Advisory IDs end up in status lines of block responses, so anything that is
+ * not a plain identifier (such as `GHSA-xxxx-xxxx-xxxx` or `MAL-2024-1234`)
+ * is dropped.
+ *
+ * @param {import("../api/aikido.js").MalwarePackage | undefined} packageData
+ * @returns {string[]}
+ */
+function getAdvisoryIds(packageData) {
+  if (!packageData || !Array.isArray(packageData.advisories)) {
+    return [];
+  }
+
+  return packageData.advisories.filter(
+    (id) => typeof id === "string" && /^[A-Za-z0-9][A-Za-z0-9._:-]*$/.test(id)
+  );
 }
 
 /**
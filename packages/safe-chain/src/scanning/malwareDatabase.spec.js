@@ -0,0 +1,230 @@
+import { describe, it, mock, afterEach } from "node:test";
+import assert from "node:assert";
+
+describe("malwareDatabase", async () => {
+  const warnings = [];
+  /** @type {Record<string, any[] | Error>} */
+  let additionalFeeds = {};
+  /** @type {Error | undefined} */
+  let databaseFetchError;
+
+  // No version is returned, so nothing is written to the local cache.
+  mock.module("../api/aikido.js", {
+    namedExports: {
+      fetchMalwareDatabase: async () => {
+        if (databaseFetchError) {
+          throw databaseFetchError;
+        }
+        return {
+          malwareDatabase: [
+            {
+              package_name: "vendor-flagged",
+              version: "1.0.0",
+              reason: "MALWARE",
+            },
+            {
+              package_name: "false-positive",
+              version: "*",
+              reason: "MALWARE",
+            },
+            {
+              package_name: "just-added",
+              version: "1.0.0",
+              reason: "MALWARE",
+              added_on: Math.floor(Date.now() / 1000) - 10 * 60,
+            },
+            {
+              package_name: "compromised",
+              version: ">=2.0.1",
+              reason: "MALWARE",
+              published_after: "2026-03-01T00:00:00Z",
+            },
+          ],
+          version: undefined,
+        };
+      },
+      fetchMalwareDatabaseVersion: async () => undefined,
+      fetchMalwareFeed: async (baseUrl) => {
+        const feed = additionalFeeds[baseUrl];
+        if (feed instanceof Error) {
+          throw feed;
+        }
+        return feed ?? [];
+      },
+    },
+  });
+
+  mock.module("../environment/userInteraction.js", {
+    namedExports: {
+      ui: {
+        writeVerbose: () => {},
+        writeWarning: (message) => warnings.push(message),
+      },
+    },
+  });
+
+  const {
+    matchesEntryVersion,
+    mergeMalwareFeeds,
+    openMalwareDatabase,
+    refreshMalwareDatabase,
+  } = await import("./malwareDatabase.js");
+  const { initializeCliArguments } = await import("../config/cliArguments.js");
+
+  afterEach(() => {
+    initializeCliArguments([]);
+    additionalFeeds = {};
+    databaseFetchError = undefined;
+    warnings.length = 0;
+  });
+
+  it("lets later feeds win on conflicting entries and records the origin", () => {
+    const merged = mergeMalwareFeeds([
+      {
+        source: "https://vendor.example.com",
+        entries: [
+          { package_name: "shared", version: "1.0.0", reason: "MALWARE" },
+          { package_name: "vendor-only", version: "2.0.0", reason: "MALWARE" },
+        ],
+      },
+      {
+        source: "https://intel.acme.com",
+        entries: [{ package_name: "shared", version: "1.0.0", reason: "OK" }],
+      },
+    ]);
+
+    assert.deepStrictEqual(merged, [
+      {
+        package_name: "shared",
+        version: "1.0.0",
+        reason: "OK",
+        source: "https://intel.acme.com",
+      },
+      {
+        package_name: "vendor-only",
+        version: "2.0.0",
+        reason: "MALWARE",
+        source: "https://vendor.example.com",
+      },
+    ]);
+  });
+
+  it("layers additional feeds on top of the malware database", async () => {
+    additionalFeeds = {
+      "https://intel.acme.com/feeds": [
+        { package_name: "internal-only", version: "3.1.0", reason: "MALWARE" },
+        { package_name: "false-positive", version: "1.2.3", reason: "OK" },
+      ],
+      "https://broken.example.com": new Error("Error fetching js malware feed"),
+    };
+    initializeCliArguments([
+      "--safe-chain-malware-feed-url=https://intel.acme.com/feeds/",
+      "--safe-chain-malware-feed-url=https://broken.example.com",
+    ]);
+
+    const database = await openMalwareDatabase();
+
+    assert.strictEqual(database.isMalware("vendor-flagged", "1.0.0"), true);
+    assert.strictEqual(database.isMalware("internal-only", "3.1.0"), true);
+    assert.strictEqual(database.isMalware("false-positive", "1.2.3"), false);
+    assert.strictEqual(database.isMalware("false-positive", "1.2.4"), true);
+    const feedWarnings = warnings.filter((message) =>
+      message.includes("malware feed")
+    );
+    assert.strictEqual(feedWarnings.length, 1);
+    assert.match(feedWarnings[0], /Could not load malware feed https:\/\/broken\.example\.com/);
+  });
+
+  it("reports feed entries younger than the grace window without enforcing them", async () => {
+    const database = await openMalwareDatabase();
+    assert.strictEqual(database.isMalware("just-added", "1.0.0"), true);
+
+    initializeCliArguments(["--safe-chain-feed-entry-min-age-mins=30"]);
+
+    assert.strictEqual(database.isMalware("just-added", "1.0.0"), false);
+    assert.strictEqual(database.isMalware("just-added", "1.0.0"), false);
+    assert.strictEqual(database.isMalware("vendor-flagged", "1.0.0"), true);
+    assert.strictEqual(warnings.length, 1);
+    assert.match(
+      warnings[0],
+      /feed-entry\.staged - just-added@1\.0\.0 was added to the malware feed 10 minutes ago/
+    );
+
+    initializeCliArguments(["--safe-chain-feed-entry-min-age-mins=5"]);
+
+    assert.strictEqual(database.isMalware("just-added", "1.0.0"), true);
+  });
+
+  it("matches versions against SemVer ranges in feed entries", () => {
+    const range = {
+      package_name: "foo",
+      version: ">=2.0.1 <3.0.0",
+      reason: "MALWARE",
+    };
+
+    assert.strictEqual(matchesEntryVersion(range, "2.0.1"), true);
+    assert.strictEqual(matchesEntryVersion(range, "2.4.0-beta.1"), true);
+    assert.strictEqual(matchesEntryVersion(range, "2.0.0"), false);
+    assert.strictEqual(matchesEntryVersion(range, "3.0.0"), false);
+
+    const alternatives = { ...range, version: "1.0.1 || 1.0.3" };
+    assert.strictEqual(matchesEntryVersion(alternatives, "1.0.3"), true);
+    assert.strictEqual(matchesEntryVersion(alternatives, "1.0.2"), false);
+  });
+
+  it("does not treat exact versions as ranges", () => {
+    const entry = { package_name: "foo", version: "1.0", reason: "MALWARE" };
+
+    assert.strictEqual(matchesEntryVersion(entry, "1.0"), true);
+    assert.strictEqual(matchesEntryVersion(entry, "1.0.5"), false);
+  });
+
+  it("only covers versions published after the cutoff of an entry", () => {
+    const entry = {
+      package_name: "foo",
+      version: "*",
+      reason: "MALWARE",
+      published_after: 1772323200, // 2026-03-01T00:00:00Z
+    };
+
+    assert.strictEqual(
+      matchesEntryVersion(entry, "2.0.1", Date.parse("2026-03-02T08:00:00Z")),
+      true
+    );
+    assert.strictEqual(
+      matchesEntryVersion(entry, "2.0.0", Date.parse("2026-02-27T08:00:00Z")),
+      false
+    );
+    // Without a known publish time, the range alone decides
+    assert.strictEqual(matchesEntryVersion(entry, "2.0.0"), true);
+  });
+
+  it("blocks versions in a compromised range published after the cutoff", async () => {
+    const database = await openMalwareDatabase();
+
+    assert.strictEqual(
+      database.isMalware("compromised", "2.0.1", Date.parse("2026-03-05")),
+      true
+    );
+    assert.strictEqual(
+      database.isMalware("compromised", "2.0.1", Date.parse("2026-01-05")),
+      false
+    );
+    assert.strictEqual(
+      database.isMalware("compromised", "2.0.0", Date.parse("2026-03-05")),
+      false
+    );
+  });
+
+  it("replaces the database in use on refresh and keeps it when the download fails", async () => {
+    const initial = await openMalwareDatabase();
+    const refreshed = await refreshMalwareDatabase();
+
+    assert.notStrictEqual(refreshed, initial);
+    assert.strictEqual(await openMalwareDatabase(), refreshed);
+
+    databaseFetchError = new Error("registry unavailable");
+    await assert.rejects(refreshMalwareDatabase(), /registry unavailable/);
+    assert.strictEqual(await openMalwareDatabase(), refreshed);
+  });
+});
@@ -52,6 +52,8 @@ mock.module("../config/settings.js", {
     getMinimumPackageAgeHours: () => minimumPackageAgeHours,
     getEcoSystem: () => ecosystem,
     getMalwareListBaseUrl: () => "https://malware-list.aikido.dev",
+    getOffline: () => false,
+    getUseTestFixture: () => false,
     ECOSYSTEM_JS: "js",
     ECOSYSTEM_PY: "py",
   },
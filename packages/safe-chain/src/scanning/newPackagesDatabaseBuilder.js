@@ -8,7 +8,7 @@ import { getEquivalentPackageNames } from "./packageNameVariants.js";
 
 /**
  * @typedef {Object} NewPackagesDatabase
- * @property {function(string | undefined, string | undefined): boolean} isNewlyReleasedPackage
+ * @property {function(string | undefined, string | undefined, number=): boolean} isNewlyReleasedPackage
  */
 
 /**
@@ -39,15 +39,20 @@ export function buildNewPackagesDatabase(newPackagesList) {
   /**
    * @param {string | undefined} name
    * @param {string | undefined} version
+   * @param {number} [minimumPackageAgeHours]
    * @returns {boolean}
    */
-  function isNewlyReleasedPackage(name, version) {
+  function isNewlyReleasedPackage(
+    name,
+    version,
+    minimumPackageAgeHours = getMinimumPackageAgeHours()
+  ) {
     if (!name || !version) {
       return false;
     }
 
     const cutOff = new Date(
-      new Date().getTime() - getMinimumPackageAgeHours() * 3600 * 1000
+      new Date().getTime() - minimumPackageAgeHours * 3600 * 1000
     );
     const expectedSource = getCurrentFeedSource();
     const candidateNames = getEquivalentPackageNames(name, ecosystem);
@@ -7,6 +7,7 @@ import {
   getNewPackagesListPath,
   getNewPackagesListVersionPath,
 } from "../config/configFile.js";
+import { getOffline, getUseTestFixture } from "../config/settings.js";
 import { ui } from "../environment/userInteraction.js";
 import { buildNewPackagesDatabase } from "./newPackagesDatabaseBuilder.js";
 import { warnOnceAboutUnavailableDatabase } from "./newPackagesDatabaseWarnings.js";
@@ -46,6 +47,20 @@ async function getNewPackagesList() {
   const { newPackagesList: cachedList, version: cachedVersion } =
     readNewPackagesListFromLocalCache();
 
+  // The test fixture has no newly released packages
+  if (getUseTestFixture()) {
+    return [];
+  }
+
+  if (getOffline()) {
+    if (cachedList) {
+      return cachedList;
+    }
+    throw new Error(
+      "safe-chain runs offline and no cached new packages list is available"
+    );
+  }
+
   try {
     if (cachedList) {
       const currentVersion = await fetchNewPackagesListVersion();
@@ -21,6 +21,10 @@ mock.module("../config/settings.js", {
     getEcoSystem: () => ecosystem,
     getMinimumPackageAgeHours: () => 24,
     getMalwareListBaseUrl: () => "https://malware-list.aikido.dev",
+    getMalwareListToken: () => undefined,
+    getMaxRetryAfterSecs: () => 30,
+    getOffline: () => false,
+    getUseTestFixture: () => false,
     ECOSYSTEM_JS: "js",
     ECOSYSTEM_PY: "py",
   },
@@ -0,0 +1,42 @@
+import {
+  ECOSYSTEM_JS,
+  ECOSYSTEM_PY,
+  getEcoSystem,
+} from "../config/settings.js";
+
+export const TEST_FIXTURE_VERSION = "test-fixture";
+
+/**
+ * The test packages of the Aikido malware list and nothing else, so the same
+ * packages are blocked on every run, with or without network access.
+ *
+ * @type {Record<string, import("../api/aikido.js").MalwarePackage[]>}
+ */
+const TEST_FIXTURE_ENTRIES = {
+  [ECOSYSTEM_JS]: [
+    {
+      package_name: "safe-chain-test",
+      version: "0.0.1-security",
+      reason: "MALWARE",
+    },
+  ],
+  [ECOSYSTEM_PY]: [
+    { package_name: "safe-chain-pi-test", version: "0.0.1", reason: "MALWARE" },
+    {
+      package_name: "aikido-endpoint-test",
+      version: "0.0.1",
+      reason: "MALWARE",
+    },
+  ],
+};
+
+/**
+ * @returns {import("./malwareDatabase.js").LoadedMalwareList}
+ */
+export function getTestFixtureMalwareList() {
+  const entries = TEST_FIXTURE_ENTRIES[getEcoSystem()] ?? [];
+  return {
+    entries: entries.map((entry) => ({ ...entry })),
+    version: TEST_FIXTURE_VERSION,
+  };
+}
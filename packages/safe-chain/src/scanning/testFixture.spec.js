@@ -0,0 +1,66 @@
+import { describe, it, mock, afterEach } from "node:test";
+import assert from "node:assert";
+
+describe("testFixture", async () => {
+  let fetchCount = 0;
+
+  mock.module("../api/aikido.js", {
+    namedExports: {
+      fetchMalwareDatabase: async () => {
+        fetchCount++;
+        throw new Error("No network access in fixture mode");
+      },
+      fetchMalwareDatabaseVersion: async () => {
+        fetchCount++;
+        throw new Error("No network access in fixture mode");
+      },
+      fetchMalwareFeed: async () => {
+        fetchCount++;
+        return [];
+      },
+    },
+  });
+
+  const { openMalwareDatabase } = await import("./malwareDatabase.js");
+  const { getOffline, getUseTestFixture } = await import(
+    "../config/settings.js"
+  );
+  const { initializeCliArguments } = await import("../config/cliArguments.js");
+
+  afterEach(() => {
+    initializeCliArguments([]);
+    delete process.env.SAFE_CHAIN_USE_TEST_FIXTURE;
+  });
+
+  it("is off unless requested for the run", () => {
+    assert.strictEqual(getUseTestFixture(), false);
+
+    process.env.SAFE_CHAIN_USE_TEST_FIXTURE = "true";
+    assert.strictEqual(getUseTestFixture(), true);
+  });
+
+  it("implies offline mode", () => {
+    initializeCliArguments(["--safe-chain-use-test-fixture"]);
+
+    assert.strictEqual(getOffline(), true);
+  });
+
+  it("replaces the malware list with the test packages without downloading it", async () => {
+    initializeCliArguments([
+      "--safe-chain-use-test-fixture",
+      "--safe-chain-malware-feed-url=https://intel.acme.com/feeds",
+    ]);
+
+    const database = await openMalwareDatabase();
+
+    assert.strictEqual(
+      database.isMalware("safe-chain-test", "0.0.1-security"),
+      true
+    );
+    assert.strictEqual(database.isMalware("safe-chain-test", "0.0.2"), false);
+    assert.strictEqual(database.isMalware("lodash", "4.17.21"), false);
+    assert.strictEqual(database.entryCount, 1);
+    assert.strictEqual(database.version, "test-fixture");
+    assert.strictEqual(fetchCount, 0);
+  });
+});
@@ -0,0 +1,358 @@
+// An in-process registry for tests. It serves npm and PyPI shaped metadata and
+// artifacts for the packages a test declares, and can answer any path with a
+// canned response, a delay, a redirect, a number of failures or a malformed
+// payload. It is never used outside the tests.
+//
+//   const registry = await createMockRegistry()
+//     .withNpmPackage("left-pad", ["1.0.0", "1.1.0"])
+//     .withDelay("/left-pad", 500)
+//     .start();
+//   await fetch(`${registry.url}/left-pad`);
+//   await registry.stop();
+
+import * as crypto from "crypto";
+import * as http from "http";
+import * as https from "https";
+import * as zlib from "zlib";
+import {
+  TEST_REGISTRY_CERTIFICATE,
+  TEST_REGISTRY_PRIVATE_KEY,
+} from "./testRegistryCertificate.js";
+
+/**
+ * @typedef {Object} MockResponse
+ * @property {number} [statusCode] - 200 by default
+ * @property {Record<string, string>} [headers]
+ * @property {string | Buffer | object} [body] - Objects are sent as JSON
+ *
+ * @typedef {(registryUrl: string, req: import("http").IncomingMessage) => MockResponse} MockResponder
+ *
+ * @typedef {Object} MockRoute
+ * @property {string | undefined} method - Undefined matches every method
+ * @property {MockResponder} respond
+ *
+ * @typedef {Object} RecordedRequest
+ * @property {string} method
+ * @property {string} url - Path and query, as received
+ * @property {import("http").IncomingHttpHeaders} headers
+ *
+ * @typedef {Object} MockRegistry
+ * @property {string} url - For example "http://127.0.0.1:41234", without a trailing slash
+ * @property {number} port
+ * @property {string | undefined} certificate - The CA to trust when started with TLS
+ * @property {RecordedRequest[]} requests - Every request received, in order
+ * @property {() => Promise<void>} stop
+ *
+ * @typedef {Object} MockRegistryOptions
+ * @property {boolean} [tls] - Serve HTTPS with the test registry certificate
+ *
+ * Paths are matched without the query. Every method returns the builder.
+ *
+ * @typedef {Object} MockRegistryBuilder
+ * @property {(path: string, response: MockResponse | MockResponder, method?: string) => MockRegistryBuilder} withResponse - Answers a path with a canned response, for every method unless one is given. A later response for the same path and method replaces an earlier one.
+ * @property {(name: string, versions: string[], options?: {time?: Record<string, string>}) => MockRegistryBuilder} withNpmPackage - Package metadata with the last version as latest, and a tarball per version whose integrity matches the metadata
+ * @property {(name: string, versions: string[]) => MockRegistryBuilder} withPypiPackage - The simple index (HTML, or JSON per PEP 691), the JSON API and a wheel per version
+ * @property {(path: string, delayMs: number) => MockRegistryBuilder} withDelay - Holds back the response, for timeout tests
+ * @property {(path: string, location: string, statusCode?: number) => MockRegistryBuilder} withRedirect - Redirects, 302 by default; relative locations are resolved against the registry
+ * @property {(path: string, count: number, statusCode?: number) => MockRegistryBuilder} withFailures - Fails the first requests, with 503 by default, for retry tests
+ * @property {(path: string, contentType?: string) => MockRegistryBuilder} withMalformedBody - A truncated JSON document, for parse error tests
+ * @property {(options?: MockRegistryOptions) => Promise<MockRegistry>} start - Listens on a random port of 127.0.0.1
+ */
+
+const PYPI_SIMPLE_JSON = "application/vnd.pypi.simple.v1+json";
+
+/**
+ * @returns {MockRegistryBuilder}
+ */
+export function createMockRegistry() {
+  /** @type {Map<string, MockRoute[]>} */
+  const routes = new Map();
+  /** @type {Map<string, number>} */
+  const delays = new Map();
+  /** @type {Map<string, {remaining: number, statusCode: number}>} */
+  const failures = new Map();
+
+  /** @type {MockRegistryBuilder} */
+  const builder = {
+    withResponse(path, response, method) {
+      const respond =
+        typeof response === "function" ? response : () => response;
+      const pathRoutes = (routes.get(path) ?? []).filter(
+        (route) => route.method !== method?.toUpperCase()
+      );
+      pathRoutes.push({ method: method?.toUpperCase(), respond });
+      routes.set(path, pathRoutes);
+      return builder;
+    },
+
+    withNpmPackage(name, versions, options = {}) {
+      const basename = name.split("/").pop();
+      const tarballs = new Map(
+        versions.map((version) => [
+          version,
+          createArtifact(`npm:${name}@${version}`, true),
+        ])
+      );
+
+      builder.withResponse(`/${name}`, (registryUrl) => ({
+        headers: { "Content-Type": "application/json" },
+        body: {
+          name,
+          "dist-tags": { latest: versions[versions.length - 1] },
+          versions: Object.fromEntries(
+            [...tarballs].map(([version, tarball]) => [
+              version,
+              {
+                name,
+                version,
+                dist: {
+                  tarball: `${registryUrl}/${name}/-/${basename}-${version}.tgz`,
+                  shasum: hash("sha1", tarball, "hex"),
+                  integrity: `sha512-${hash("sha512", tarball, "base64")}`,
+                },
+              },
+            ])
+          ),
+          time: options.time ?? {},
+        },
+      }));
+
+      for (const [version, tarball] of tarballs) {
+        builder.withResponse(`/${name}/-/${basename}-${version}.tgz`, {
+          headers: { "Content-Type": "application/octet-stream" },
+          body: tarball,
+        });
+      }
+      return builder;
+    },
+
+    withPypiPackage(name, versions) {
+      const normalizedName = name.toLowerCase().replace(/[-_.]+/g, "-");
+      const distributionName = normalizedName.replace(/-/g, "_");
+      const wheels = versions.map((version) => ({
+        version,
+        filename: `${distributionName}-${version}-py3-none-any.whl`,
+        wheel: createArtifact(`pypi:${name}==${version}`, false),
+      }));
+
+      /**
+       * @param {string} registryUrl
+       */
+      const listFiles = (registryUrl) =>
+        wheels.map(({ version, filename, wheel }) => ({
+          version,
+          filename,
+          url: `${registryUrl}/packages/${filename}`,
+          sha256: hash("sha256", wheel, "hex"),
+        }));
+
+      builder.withResponse(`/simple/${normalizedName}/`, (registryUrl, req) =>
+        (req.headers.accept ?? "").includes(PYPI_SIMPLE_JSON)
+          ? {
+              headers: { "Content-Type": PYPI_SIMPLE_JSON },
+              body: {
+                meta: { "api-version": "1.0" },
+                name: normalizedName,
+                files: listFiles(registryUrl).map((file) => ({
+                  filename: file.filename,
+                  url: file.url,
+                  hashes: { sha256: file.sha256 },
+                })),
+              },
+            }
+          : {
+              headers: { "Content-Type": "text/html" },
+              body: [
+                "<!DOCTYPE html><html><body>",
+                ...listFiles(registryUrl).map(
+                  (file) =>
+                    `<a href="${file.url}#sha256=${file.sha256}">${file.filename}</a><br>`
+                ),
+                "</body></html>",
+              ].join(""),
+            }
+      );
+
+      builder.withResponse(`/pypi/${normalizedName}/json`, (registryUrl) => ({
+        headers: { "Content-Type": "application/json" },
+        body: {
+          info: { name, version: versions[versions.length - 1] },
+          releases: Object.fromEntries(
+            versions.map((version) => [
+              version,
+              listFiles(registryUrl).filter((file) => file.version === version),
+            ])
+          ),
+        },
+      }));
+
+      for (const { filename, wheel } of wheels) {
+        builder.withResponse(`/packages/${filename}`, {
+          headers: { "Content-Type": "application/octet-stream" },
+          body: wheel,
+        });
+      }
+      return builder;
+    },
+
+    withDelay(path, delayMs) {
+      delays.set(path, delayMs);
+      return builder;
+    },
+
+    withRedirect(path, location, statusCode = 302) {
+      return builder.withResponse(path, (registryUrl) => ({
+        statusCode,
+        headers: {
+          Location: location.startsWith("/")
+            ? `${registryUrl}${location}`
+            : location,
+        },
+      }));
+    },
+
+    withFailures(path, count, statusCode = 503) {
+      failures.set(path, { remaining: count, statusCode });
+      return builder;
+    },
+
+    withMalformedBody(path, contentType = "application/json") {
+      return builder.withResponse(path, {
+        headers: { "Content-Type": contentType },
+        body: '{"name": "truncated", "versions": {',
+      });
+    },
+
+    async start(options = {}) {
+      /** @type {RecordedRequest[]} */
+      const requests = [];
+      /** @type {Set<import("net").Socket>} */
+      const sockets = new Set();
+      let registryUrl = "";
+
+      /** @type {http.RequestListener} */
+      const handler = (req, res) => {
+        requests.push({
+          method: req.method ?? "GET",
+          url: req.url ?? "/",
+          headers: req.headers,
+        });
+        const response = resolveResponse(req, registryUrl);
+        const delayMs = delays.get(getPath(req)) ?? 0;
+        const timer = setTimeout(() => writeResponse(res, response), delayMs);
+        res.on("close", () => clearTimeout(timer));
+      };
+
+      const server = options.tls
+        ? https.createServer(
+            { key: TEST_REGISTRY_PRIVATE_KEY, cert: TEST_REGISTRY_CERTIFICATE },
+            handler
+          )
+        : http.createServer(handler);
+      server.on("connection", (socket) => {
+        sockets.add(socket);
+        socket.on("close", () => sockets.delete(socket));
+      });
+
+      await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+      const { port } = /** @type {import("net").AddressInfo} */ (
+        server.address()
+      );
+      registryUrl = `${options.tls ? "https" : "http"}://127.0.0.1:${port}`;
+
+      return {
+        url: registryUrl,
+        port,
+        certificate: options.tls ? TEST_REGISTRY_CERTIFICATE : undefined,
+        requests,
+        stop: () =>
+          new Promise((resolve) => {
+            // Delayed responses would otherwise keep the server open
+            for (const socket of sockets) {
+              socket.destroy();
+            }
+            server.close(() => resolve());
+          }),
+      };
+    },
+  };
+
+  /**
+   * @param {import("http").IncomingMessage} req
+   * @param {string} registryUrl
+   * @returns {MockResponse}
+   */
+  function resolveResponse(req, registryUrl) {
+    const path = getPath(req);
+
+    const failure = failures.get(path);
+    if (failure && failure.remaining > 0) {
+      failure.remaining--;
+      return { statusCode: failure.statusCode, body: "Service Unavailable" };
+    }
+
+    const pathRoutes = routes.get(path) ?? [];
+    const route =
+      pathRoutes.find((r) => r.method === req.method) ??
+      pathRoutes.find((r) => r.method === undefined);
+    if (!route) {
+      return { statusCode: 404, body: { error: "Not found" } };
+    }
+    return route.respond(registryUrl, req);
+  }
+
+  return builder;
+}
+
+/**
+ * @param {import("http").ServerResponse} res
+ * @param {MockResponse} response
+ * @returns {void}
+ */
+function writeResponse(res, response) {
+  const body =
+    response.body === undefined ||
+    typeof response.body === "string" ||
+    Buffer.isBuffer(response.body)
+      ? response.body
+      : JSON.stringify(response.body);
+  const headers = { ...response.headers };
+  if (
+    response.body !== undefined &&
+    !Buffer.isBuffer(response.body) &&
+    typeof response.body === "object" &&
+    !Object.keys(headers).some((name) => name.toLowerCase() === "content-type")
+  ) {
+    headers["Content-Type"] = "application/json";
+  }
+  res.writeHead(response.statusCode ?? 200, headers);
+  res.end(body);
+}
+
+/**
+ * @param {import("http").IncomingMessage} req
+ * @returns {string}
+ */
+function getPath(req) {
+  return new URL(req.url ?? "/", "http://registry.invalid").pathname;
+}
+
+/**
+ * @param {string} content
+ * @param {boolean} gzip
+ * @returns {Buffer}
+ */
+function createArtifact(content, gzip) {
+  const artifact = Buffer.from(content);
+  return gzip ? zlib.gzipSync(artifact) : artifact;
+}
+
+/**
+ * @param {string} algorithm
+ * @param {Buffer} data
+ * @param {"hex" | "base64"} encoding
+ * @returns {string}
+ */
+function hash(algorithm, data, encoding) {
+  return crypto.createHash(algorithm).update(data).digest(encoding);
+}
@@ -0,0 +1,133 @@
+import { afterEach, describe, it } from "node:test";
+import assert from "node:assert";
+import * as crypto from "crypto";
+import { createMockRegistry } from "./mockRegistry.js";
+
+describe("mockRegistry", () => {
+  /** @type {import("./mockRegistry.js").MockRegistry | undefined} */
+  let registry;
+
+  afterEach(async () => {
+    await registry?.stop();
+    registry = undefined;
+  });
+
+  it("serves npm metadata whose tarball matches its integrity", async () => {
+    registry = await createMockRegistry()
+      .withNpmPackage("@acme/left-pad", ["1.0.0", "1.1.0"])
+      .start();
+
+    const metadata = await (
+      await fetch(`${registry.url}/@acme/left-pad`)
+    ).json();
+    const dist = metadata.versions["1.1.0"].dist;
+    const tarball = Buffer.from(
+      await (await fetch(dist.tarball)).arrayBuffer()
+    );
+
+    assert.strictEqual(metadata["dist-tags"].latest, "1.1.0");
+    assert.strictEqual(
+      dist.tarball,
+      `${registry.url}/@acme/left-pad/-/left-pad-1.1.0.tgz`
+    );
+    assert.strictEqual(
+      dist.integrity,
+      `sha512-${crypto.createHash("sha512").update(tarball).digest("base64")}`
+    );
+  });
+
+  it("serves the PyPI simple index as HTML or JSON", async () => {
+    registry = await createMockRegistry()
+      .withPypiPackage("Foo.Bar", ["2.0.0"])
+      .start();
+
+    const html = await (await fetch(`${registry.url}/simple/foo-bar/`)).text();
+    const json = await (
+      await fetch(`${registry.url}/simple/foo-bar/`, {
+        headers: { Accept: "application/vnd.pypi.simple.v1+json" },
+      })
+    ).json();
+    const wheel = await fetch(json.files[0].url);
+
+    assert.match(html, /foo_bar-2\.0\.0-py3-none-any\.whl<\/a>/);
+    assert.strictEqual(
+      json.files[0].filename,
+      "foo_bar-2.0.0-py3-none-any.whl"
+    );
+    assert.strictEqual(wheel.status, 200);
+  });
+
+  it("answers canned responses per method and 404 otherwise", async () => {
+    registry = await createMockRegistry()
+      .withResponse("/-/ping", { body: { ok: true } })
+      .withResponse("/-/ping", { statusCode: 405 }, "post")
+      .start();
+
+    const get = await fetch(`${registry.url}/-/ping?write=true`);
+    const post = await fetch(`${registry.url}/-/ping`, { method: "POST" });
+    const missing = await fetch(`${registry.url}/unknown`);
+
+    assert.deepStrictEqual(await get.json(), { ok: true });
+    assert.strictEqual(post.status, 405);
+    assert.strictEqual(missing.status, 404);
+    assert.deepStrictEqual(
+      registry.requests.map((request) => `${request.method} ${request.url}`),
+      ["GET /-/ping?write=true", "POST /-/ping", "GET /unknown"]
+    );
+  });
+
+  it("delays, redirects, fails and sends malformed payloads", async () => {
+    registry = await createMockRegistry()
+      .withResponse("/slow", { body: "done" })
+      .withDelay("/slow", 200)
+      .withRedirect("/old", "/new")
+      .withResponse("/new", { body: "moved" })
+      .withResponse("/flaky", { body: "recovered" })
+      .withFailures("/flaky", 2)
+      .withMalformedBody("/broken")
+      .start();
+
+    const startedAt = Date.now();
+    const slow = await fetch(`${registry.url}/slow`);
+    assert.strictEqual(await slow.text(), "done");
+    assert.ok(Date.now() - startedAt >= 190);
+
+    const redirect = await fetch(`${registry.url}/old`, { redirect: "manual" });
+    assert.strictEqual(redirect.status, 302);
+    assert.strictEqual(redirect.headers.get("location"), `${registry.url}/new`);
+
+    const statuses = [];
+    for (let attempt = 0; attempt < 3; attempt++) {
+      statuses.push((await fetch(`${registry.url}/flaky`)).status);
+    }
+    assert.deepStrictEqual(statuses, [503, 503, 200]);
+
+    await assert.rejects(
+      (await fetch(`${registry.url}/broken`)).json(),
+      SyntaxError
+    );
+  });
+
+  it("stops while a delayed response is still pending", async () => {
+    registry = await createMockRegistry()
+      .withResponse("/hang", { body: "never" })
+      .withDelay("/hang", 60_000)
+      .start();
+
+    const pending = fetch(`${registry.url}/hang`).catch((error) => error);
+    await new Promise((resolve) => setTimeout(resolve, 50));
+    await registry.stop();
+    registry = undefined;
+
+    assert.ok((await pending) instanceof Error);
+  });
+
+  it("serves HTTPS with the test registry certificate", async () => {
+    registry = await createMockRegistry()
+      .withResponse("/", { body: "secure" })
+      .start({ tls: true });
+
+    assert.ok(registry.url.startsWith("https://127.0.0.1:"));
+    assert.match(registry.certificate ?? "", /BEGIN CERTIFICATE/);
+  });
+});
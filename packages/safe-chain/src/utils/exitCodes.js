@@ -0,0 +1,38 @@
+/**
+ * Exit codes for failures that happen before the package manager runs.
+ * Successful runs return the exit code of the package manager itself.
+ */
+export const EXIT_CODE_GENERAL_ERROR = 1;
+export const EXIT_CODE_PROXY_BIND_FAILURE = 2;
+export const EXIT_CODE_CONFIG_ERROR = 3;
+export const EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE = 4;
+export const EXIT_CODE_CLOCK_SKEW = 5;
+export const EXIT_CODE_SELF_TEST_FAILED = 6;
+
+/**
+ * An error that stops safe-chain from starting. The message is printed as a
+ * single line, so it should tell the user what to fix.
+ */
+export class SafeChainStartupError extends Error {
+  /**
+   * @param {number} exitCode
+   * @param {string} message
+   * @param {{cause?: unknown}} [options]
+   */
+  constructor(exitCode, message, options) {
+    super(message, options);
+    this.name = "SafeChainStartupError";
+    this.exitCode = exitCode;
+  }
+}
+
+/**
+ * @param {unknown} error
+ * @returns {number}
+ */
+export function getExitCodeForError(error) {
+  if (error instanceof SafeChainStartupError) {
+    return error.exitCode;
+  }
+  return EXIT_CODE_GENERAL_ERROR;
+}
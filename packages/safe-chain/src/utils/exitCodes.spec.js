@@ -0,0 +1,37 @@
+import { describe, it } from "node:test";
+import assert from "node:assert";
+import {
+  EXIT_CODE_GENERAL_ERROR,
+  EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+  EXIT_CODE_PROXY_BIND_FAILURE,
+  getExitCodeForError,
+  SafeChainStartupError,
+} from "./exitCodes.js";
+
+describe("getExitCodeForError", () => {
+  it("returns the exit code of a startup error", () => {
+    assert.strictEqual(
+      getExitCodeForError(
+        new SafeChainStartupError(EXIT_CODE_PROXY_BIND_FAILURE, "port in use")
+      ),
+      EXIT_CODE_PROXY_BIND_FAILURE
+    );
+    assert.strictEqual(
+      getExitCodeForError(
+        new SafeChainStartupError(
+          EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE,
+          "offline"
+        )
+      ),
+      EXIT_CODE_MALWARE_DATABASE_UNAVAILABLE
+    );
+  });
+
+  it("returns the general error code for other errors", () => {
+    assert.strictEqual(
+      getExitCodeForError(new Error("unexpected")),
+      EXIT_CODE_GENERAL_ERROR
+    );
+    assert.strictEqual(getExitCodeForError("unexpected"), EXIT_CODE_GENERAL_ERROR);
+  });
+});